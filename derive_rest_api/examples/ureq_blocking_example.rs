@@ -78,10 +78,11 @@ mod example {
 
         // Example 3: Using a custom ureq agent with configuration
         println!("3. Using custom ureq agent with timeout...");
-        let custom_agent = ureq::AgentBuilder::new()
-            .timeout(std::time::Duration::from_secs(10))
+        let custom_agent: ureq::Agent = ureq::Agent::config_builder()
+            .timeout_global(Some(std::time::Duration::from_secs(10)))
             .user_agent("derive-rest-api-ureq-example/1.0")
-            .build();
+            .build()
+            .into();
 
         let custom_client = JsonPlaceholderClient::new()
             .with_http_client(custom_agent);
@@ -0,0 +1,637 @@
+//! Support code for macro-generated code. Not part of the public API.
+
+use crate::RestApiError;
+use serde::ser::{self, Serialize, Serializer};
+
+pub use crate::graphql::unwrap_data as graphql_data;
+pub use crate::lenient::decode as decode_json_lenient;
+pub use crate::unknown_fields::decode_strict as decode_json_strict;
+pub use crate::health::{check as check_health, check_async as check_health_async};
+#[cfg(not(target_arch = "wasm32"))]
+pub use crate::health::{wait_until_ready, wait_until_ready_async};
+pub use crate::poll::{PollConfig, poll};
+pub use crate::transport::Transport;
+pub use crate::query::{encode as encode_query, order as order_query};
+#[cfg(not(target_arch = "wasm32"))]
+pub use crate::poll::{Delay, poll_async};
+#[cfg(not(target_arch = "wasm32"))]
+pub use crate::background::send_background;
+#[cfg(feature = "config")]
+pub use crate::config_loader::{LoadedConfig, load as load_config};
+#[cfg(feature = "arbitrary")]
+pub use arbitrary;
+#[cfg(feature = "cli")]
+pub use clap;
+pub use serde::de::DeserializeOwned;
+pub use serde_json::Value as JsonValue;
+
+/// Wrapper used to pick how a path parameter is converted into a string.
+///
+/// Generated code calls `(&PathValue(&value)).to_path_value(name)` with both
+/// [`DisplayPathValue`] and [`SerializePathValue`] in scope. Method resolution
+/// prefers the `Display` impl (on `PathValue`) and falls back to the
+/// `Serialize` impl (on `&PathValue`, reached through autoref) for types such
+/// as fieldless enums that only derive `Serialize`.
+pub struct PathValue<'a, T: ?Sized>(pub &'a T);
+
+/// Converts a path parameter using its `Display` implementation.
+pub trait DisplayPathValue {
+    fn to_path_value(&self, param: &str) -> Result<String, RestApiError>;
+}
+
+impl<T: std::fmt::Display + ?Sized> DisplayPathValue for PathValue<'_, T> {
+    fn to_path_value(&self, _param: &str) -> Result<String, RestApiError> {
+        Ok(self.0.to_string())
+    }
+}
+
+/// Converts a path parameter using its `Serialize` implementation.
+///
+/// Only values that serialize to a string, number or boolean (including unit
+/// enum variants) can be used as path parameters.
+pub trait SerializePathValue {
+    fn to_path_value(&self, param: &str) -> Result<String, RestApiError>;
+}
+
+impl<T: Serialize + ?Sized> SerializePathValue for &PathValue<'_, T> {
+    fn to_path_value(&self, param: &str) -> Result<String, RestApiError> {
+        match serde_json::to_value(self.0) {
+            Ok(serde_json::Value::String(value)) => Ok(value),
+            Ok(serde_json::Value::Number(value)) => Ok(value.to_string()),
+            Ok(serde_json::Value::Bool(value)) => Ok(value.to_string()),
+            Ok(_) => Err(RestApiError::invalid_path_parameter(
+                param,
+                "value does not serialize to a string, number or boolean",
+            )),
+            Err(e) => Err(RestApiError::invalid_path_parameter(param, e.to_string())),
+        }
+    }
+}
+
+/// Wrapper used to pick how a command-line value is parsed, for `#[request_builder(cli)]`.
+///
+/// Generated code calls `(&CliValue::<T>::new(raw)).parse_cli_value(flag)` with
+/// both [`FromStrCliValue`] and [`JsonCliValue`] in scope, so `FromStr` types
+/// are parsed with it, and other types such as structs, lists and fieldless
+/// enums fall back to `Deserialize` from JSON (like [`PathValue`]).
+#[cfg(feature = "cli")]
+pub struct CliValue<'a, T>(&'a str, std::marker::PhantomData<T>);
+
+#[cfg(feature = "cli")]
+impl<'a, T> CliValue<'a, T> {
+    pub fn new(raw: &'a str) -> Self {
+        Self(raw, std::marker::PhantomData)
+    }
+}
+
+#[cfg(feature = "cli")]
+fn invalid_cli_value(flag: &str, raw: &str, error: impl std::fmt::Display) -> clap::Error {
+    clap::Error::raw(
+        clap::error::ErrorKind::ValueValidation,
+        format!("invalid value '{}' for '--{}': {}\n", raw, flag, error),
+    )
+}
+
+/// Parses a command-line value using its `FromStr` implementation.
+#[cfg(feature = "cli")]
+pub trait FromStrCliValue<T> {
+    fn parse_cli_value(&self, flag: &str) -> Result<T, clap::Error>;
+}
+
+#[cfg(feature = "cli")]
+impl<T> FromStrCliValue<T> for CliValue<'_, T>
+where
+    T: std::str::FromStr,
+    T::Err: std::fmt::Display,
+{
+    fn parse_cli_value(&self, flag: &str) -> Result<T, clap::Error> {
+        self.0.parse().map_err(|error| invalid_cli_value(flag, self.0, error))
+    }
+}
+
+/// Parses a command-line value as JSON, or as a JSON string if it isn't JSON.
+#[cfg(feature = "cli")]
+pub trait JsonCliValue<T> {
+    fn parse_cli_value(&self, flag: &str) -> Result<T, clap::Error>;
+}
+
+#[cfg(feature = "cli")]
+impl<T: serde::de::DeserializeOwned> JsonCliValue<T> for &CliValue<'_, T> {
+    fn parse_cli_value(&self, flag: &str) -> Result<T, clap::Error> {
+        serde_json::from_str(self.0)
+            .or_else(|_| serde_json::from_value(serde_json::Value::String(self.0.to_string())))
+            .map_err(|error| invalid_cli_value(flag, self.0, error))
+    }
+}
+
+/// Percent-encodes a `{name}` path parameter as a single segment, so a `/`,
+/// `?` or `#` in the value can't change the path or start a query.
+///
+/// `.` and `..` are rejected, as servers resolve them even when encoded.
+pub fn encode_path_segment(param: &str, value: &str) -> Result<String, RestApiError> {
+    if value == "." || value == ".." {
+        let message = format!("`{}` is not allowed as a path segment", value);
+        return Err(RestApiError::invalid_path_parameter(param, message));
+    }
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => encoded.push(byte as char),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    Ok(encoded)
+}
+
+/// Percent-encodes a `{name*}` path parameter, keeping the slashes between its
+/// segments. A leading slash is dropped, as the template supplies its own.
+///
+/// Like [`encode_path_segment`], `.` and `..` segments are rejected, so the
+/// value can't climb out of the template's prefix.
+pub fn encode_wildcard_path(param: &str, value: &str) -> Result<String, RestApiError> {
+    let segments = value
+        .trim_start_matches('/')
+        .split('/')
+        .map(|segment| encode_path_segment(param, segment))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(segments.join("/"))
+}
+
+/// Serializes the wrapped value with unit enum variants written as plain
+/// strings.
+///
+/// `serde_qs` encodes unit variants as bare keys (`state[open]`), which is
+/// only meaningful for map keys. Query parameters are wrapped in this type so
+/// fieldless enums produce `state=open` instead.
+pub struct QueryValue<'a, T: ?Sized>(pub &'a T);
+
+impl<T: Serialize + ?Sized> Serialize for QueryValue<'_, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(UnitVariantAsStr(serializer))
+    }
+}
+
+/// Serializer adapter used by [`QueryValue`], forwarding everything except
+/// unit variants to the inner serializer and wrapping nested values.
+struct UnitVariantAsStr<S>(S);
+
+macro_rules! forward_primitives {
+    ($($method:ident($ty:ty)),* $(,)?) => {
+        $(
+            fn $method(self, v: $ty) -> Result<Self::Ok, Self::Error> {
+                self.0.$method(v)
+            }
+        )*
+    };
+}
+
+impl<S: Serializer> Serializer for UnitVariantAsStr<S> {
+    type Ok = S::Ok;
+    type Error = S::Error;
+    type SerializeSeq = UnitVariantAsStr<S::SerializeSeq>;
+    type SerializeTuple = UnitVariantAsStr<S::SerializeTuple>;
+    type SerializeTupleStruct = UnitVariantAsStr<S::SerializeTupleStruct>;
+    type SerializeTupleVariant = UnitVariantAsStr<S::SerializeTupleVariant>;
+    type SerializeMap = UnitVariantAsStr<S::SerializeMap>;
+    type SerializeStruct = UnitVariantAsStr<S::SerializeStruct>;
+    type SerializeStructVariant = UnitVariantAsStr<S::SerializeStructVariant>;
+
+    forward_primitives! {
+        serialize_bool(bool),
+        serialize_i8(i8),
+        serialize_i16(i16),
+        serialize_i32(i32),
+        serialize_i64(i64),
+        serialize_i128(i128),
+        serialize_u8(u8),
+        serialize_u16(u16),
+        serialize_u32(u32),
+        serialize_u64(u64),
+        serialize_u128(u128),
+        serialize_f32(f32),
+        serialize_f64(f64),
+        serialize_char(char),
+        serialize_str(&str),
+        serialize_bytes(&[u8]),
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+        self.0.serialize_none()
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Self::Ok, Self::Error> {
+        self.0.serialize_some(&QueryValue(value))
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+        self.0.serialize_unit()
+    }
+
+    fn serialize_unit_struct(self, name: &'static str) -> Result<Self::Ok, Self::Error> {
+        self.0.serialize_unit_struct(name)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Self::Ok, Self::Error> {
+        self.0.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error> {
+        self.0.serialize_newtype_struct(name, &QueryValue(value))
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error> {
+        self.0.serialize_newtype_variant(name, variant_index, variant, &QueryValue(value))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        self.0.serialize_seq(len).map(UnitVariantAsStr)
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        self.0.serialize_tuple(len).map(UnitVariantAsStr)
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        self.0.serialize_tuple_struct(name, len).map(UnitVariantAsStr)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        self.0
+            .serialize_tuple_variant(name, variant_index, variant, len)
+            .map(UnitVariantAsStr)
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        self.0.serialize_map(len).map(UnitVariantAsStr)
+    }
+
+    fn serialize_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        self.0.serialize_struct(name, len).map(UnitVariantAsStr)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        self.0
+            .serialize_struct_variant(name, variant_index, variant, len)
+            .map(UnitVariantAsStr)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.0.is_human_readable()
+    }
+}
+
+impl<S: ser::SerializeSeq> ser::SerializeSeq for UnitVariantAsStr<S> {
+    type Ok = S::Ok;
+    type Error = S::Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.0.serialize_element(&QueryValue(value))
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.0.end()
+    }
+}
+
+impl<S: ser::SerializeTuple> ser::SerializeTuple for UnitVariantAsStr<S> {
+    type Ok = S::Ok;
+    type Error = S::Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.0.serialize_element(&QueryValue(value))
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.0.end()
+    }
+}
+
+impl<S: ser::SerializeTupleStruct> ser::SerializeTupleStruct for UnitVariantAsStr<S> {
+    type Ok = S::Ok;
+    type Error = S::Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.0.serialize_field(&QueryValue(value))
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.0.end()
+    }
+}
+
+impl<S: ser::SerializeTupleVariant> ser::SerializeTupleVariant for UnitVariantAsStr<S> {
+    type Ok = S::Ok;
+    type Error = S::Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.0.serialize_field(&QueryValue(value))
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.0.end()
+    }
+}
+
+impl<S: ser::SerializeMap> ser::SerializeMap for UnitVariantAsStr<S> {
+    type Ok = S::Ok;
+    type Error = S::Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Self::Error> {
+        self.0.serialize_key(key)
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.0.serialize_value(&QueryValue(value))
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.0.end()
+    }
+}
+
+impl<S: ser::SerializeStruct> ser::SerializeStruct for UnitVariantAsStr<S> {
+    type Ok = S::Ok;
+    type Error = S::Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        self.0.serialize_field(key, &QueryValue(value))
+    }
+
+    fn skip_field(&mut self, key: &'static str) -> Result<(), Self::Error> {
+        self.0.skip_field(key)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.0.end()
+    }
+}
+
+impl<S: ser::SerializeStructVariant> ser::SerializeStructVariant for UnitVariantAsStr<S> {
+    type Ok = S::Ok;
+    type Error = S::Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        self.0.serialize_field(key, &QueryValue(value))
+    }
+
+    fn skip_field(&mut self, key: &'static str) -> Result<(), Self::Error> {
+        self.0.skip_field(key)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.0.end()
+    }
+}
+
+/// Headers added to a builder through [`RequestModifier`](crate::RequestModifier).
+///
+/// `RequestModifier` methods can't fail, so the first invalid header is kept
+/// and reported when the headers are taken for sending.
+#[derive(Clone, Debug, Default)]
+pub struct DynamicHeaders {
+    headers: http::HeaderMap,
+    /// Name and message of the first invalid header
+    error: Option<(String, String)>,
+}
+
+impl DynamicHeaders {
+    /// Inserts a header, replacing any previous values for the same name.
+    pub fn insert<K, V>(&mut self, name: K, value: V)
+    where
+        http::HeaderName: TryFrom<K>,
+        <http::HeaderName as TryFrom<K>>::Error: Into<http::Error>,
+        http::HeaderValue: TryFrom<V>,
+        <http::HeaderValue as TryFrom<V>>::Error: Into<http::Error>,
+    {
+        if let Some((name, value)) = self.parse(name, value) {
+            self.headers.insert(name, value);
+        }
+    }
+
+    /// Appends a header, keeping any previous values for the same name.
+    pub fn append<K, V>(&mut self, name: K, value: V)
+    where
+        http::HeaderName: TryFrom<K>,
+        <http::HeaderName as TryFrom<K>>::Error: Into<http::Error>,
+        http::HeaderValue: TryFrom<V>,
+        <http::HeaderValue as TryFrom<V>>::Error: Into<http::Error>,
+    {
+        if let Some((name, value)) = self.parse(name, value) {
+            self.headers.append(name, value);
+        }
+    }
+
+    /// Consumes `self`, returning the headers or the first invalid header error.
+    pub fn into_header_map(self) -> Result<http::HeaderMap, RestApiError> {
+        match self.error {
+            Some((name, message)) => Err(RestApiError::invalid_header(name, message)),
+            None => Ok(self.headers),
+        }
+    }
+
+    fn parse<K, V>(&mut self, name: K, value: V) -> Option<(http::HeaderName, http::HeaderValue)>
+    where
+        http::HeaderName: TryFrom<K>,
+        <http::HeaderName as TryFrom<K>>::Error: Into<http::Error>,
+        http::HeaderValue: TryFrom<V>,
+        <http::HeaderValue as TryFrom<V>>::Error: Into<http::Error>,
+    {
+        let name = match http::HeaderName::try_from(name) {
+            Ok(name) => name,
+            Err(error) => {
+                self.record_error(String::new(), error.into());
+                return None;
+            }
+        };
+        match http::HeaderValue::try_from(value) {
+            Ok(value) => Some((name, value)),
+            Err(error) => {
+                self.record_error(name.to_string(), error.into());
+                None
+            }
+        }
+    }
+
+    fn record_error(&mut self, name: String, error: http::Error) {
+        self.error.get_or_insert_with(|| (name, error.to_string()));
+    }
+}
+
+/// Converts a header field's value into a `HeaderValue`.
+pub fn header_value(name: &str, value: impl std::fmt::Display) -> Result<http::HeaderValue, RestApiError> {
+    http::HeaderValue::try_from(value.to_string())
+        .map_err(|error| RestApiError::invalid_header(name, error.to_string()))
+}
+
+/// Collects the errors found by a generated `try_build()`, which reports at
+/// least one whenever it fails.
+pub fn build_errors(errors: Vec<RestApiError>) -> crate::BuildErrors {
+    crate::BuildErrors::new(errors)
+}
+
+/// Runs `garde` validation on a built request for `#[request_builder(garde)]`.
+///
+/// Each reported error becomes a `ValidationError` whose field is the garde
+/// path of the invalid value (empty for errors on the struct itself).
+#[cfg(feature = "garde")]
+pub fn garde_validate<T>(value: &T) -> Result<(), crate::BuildErrors>
+where
+    T: garde::Validate + ?Sized,
+    T::Context: Default,
+{
+    value.validate().map_err(|report| {
+        crate::BuildErrors::new(
+            report
+                .iter()
+                .map(|(path, error)| RestApiError::validation_error(path.to_string(), error.to_string()))
+                .collect(),
+        )
+    })
+}
+
+/// Builds the `User-Agent` value `<product> derive_rest_api/<version>`.
+pub fn user_agent(product: &str) -> String {
+    format!("{} derive_rest_api/{}", product, env!("CARGO_PKG_VERSION"))
+}
+
+/// Awaits `future`, giving up with `RestApiError::Cancelled` if `token` is cancelled first.
+///
+/// The future is dropped on cancellation, which aborts the in-flight request.
+pub async fn with_cancellation<F: std::future::Future>(
+    token: Option<crate::CancellationToken>,
+    future: F,
+) -> Result<F::Output, RestApiError> {
+    let Some(token) = token else {
+        return Ok(future.await);
+    };
+
+    let mut future = std::pin::pin!(future);
+    let mut cancelled = std::pin::pin!(token.cancelled());
+    std::future::poll_fn(|cx| {
+        if cancelled.as_mut().poll(cx).is_ready() {
+            return std::task::Poll::Ready(Err(RestApiError::Cancelled));
+        }
+        future.as_mut().poll(cx).map(Ok)
+    })
+    .await
+}
+
+/// Rejects a response body longer than the request's `max_response_bytes`.
+pub fn check_response_size(
+    response: http::Response<Vec<u8>>,
+    options: &crate::RequestOptions,
+) -> Result<http::Response<Vec<u8>>, RestApiError> {
+    match options.max_response_bytes {
+        Some(limit) if response.body().len() as u64 > limit => Err(RestApiError::ResponseTooLarge { limit }),
+        _ => Ok(response),
+    }
+}
+
+/// Records a response's rate-limit headers in the builder's tracked state, if any.
+pub fn record_rate_limit(state: &Option<crate::RateLimitState>, response: &http::Response<Vec<u8>>) {
+    if let Some(state) = state {
+        state.update(response.headers());
+    }
+}
+
+/// Waits for the rate-limit window to reset if the builder's tracked state throttles and the quota is low.
+pub fn wait_for_quota(state: &Option<crate::RateLimitState>) {
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(state) = state {
+        let delay = state.delay();
+        if !delay.is_zero() {
+            state.wait_clock().sleep(delay);
+        }
+    }
+    #[cfg(target_arch = "wasm32")]
+    let _ = state;
+}
+
+/// Async version of [`wait_for_quota`].
+pub async fn wait_for_quota_async(state: &Option<crate::RateLimitState>) {
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(state) = state {
+        let delay = state.delay();
+        if !delay.is_zero() {
+            state.wait_clock().sleep_async(delay).await;
+        }
+    }
+    #[cfg(target_arch = "wasm32")]
+    let _ = state;
+}
+
+/// Deserializes a JSON response body into the type chosen with `send_as::<T>()`.
+pub fn decode_json<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, RestApiError> {
+    serde_json::from_slice(bytes).map_err(RestApiError::response_deserialization_error)
+}
+
+/// Checks a response's checksum headers against its body for `#[request_builder(verify_checksum)]`.
+#[cfg(feature = "checksum")]
+pub fn verify_checksum(response: http::Response<Vec<u8>>) -> Result<http::Response<Vec<u8>>, RestApiError> {
+    crate::verify_checksums(response.headers(), response.body())?;
+    Ok(response)
+}
+
+// The futures awaited by `send_async()` on generated builders, and by the async methods of generated
+// clients, are `Send` whenever the HTTP client is, so requests can be sent from spawned tasks
+#[cfg(not(target_arch = "wasm32"))]
+const _: () = {
+    fn assert_send<T: Send>(_: &T) {}
+
+    #[allow(dead_code)]
+    fn assert_futures<A: crate::AsyncHttpClient + Send + Sync>(
+        client: &A,
+        transport: &Transport,
+        rate_limit: &Option<crate::RateLimitState>,
+    ) {
+        let options = crate::RequestOptions::default();
+        assert_send(&transport.send_async(client, "GET", "", "", http::HeaderMap::new(), None, options));
+        assert_send(&transport.replay_async(client, &http::HeaderMap::new(), options));
+        assert_send(&check_health_async(client, transport, "", "", http::HeaderMap::new(), options));
+        assert_send(&wait_for_quota_async(rate_limit));
+        assert_send(&with_cancellation(None, async {}));
+    }
+};
@@ -0,0 +1,69 @@
+//! Audit records of the writes a client makes.
+
+use crate::digest::{hex, sha256};
+use http::StatusCode;
+
+/// A mutating request recorded by an [`AuditSink`].
+///
+/// The body itself is never recorded, only its SHA-256 digest, so secrets and
+/// personal data in it stay out of the audit log while still letting the write
+/// be matched against what the other side received.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditRecord {
+    /// The request struct the request was built from, such as `CreateUser`
+    pub endpoint: &'static str,
+    /// The HTTP method, such as `POST`
+    pub method: String,
+    /// The full URL of the last attempt, including the query string
+    pub url: String,
+    /// Who the request was made on behalf of, as given to `audit_actor()`
+    pub actor: Option<String>,
+    /// The SHA-256 digest of the body, as `sha256:` and lowercase hex, or `None` without a body
+    pub body_digest: Option<String>,
+    /// The status of the final response, or `None` if the request failed without one
+    pub status: Option<StatusCode>,
+}
+
+/// Where a client records every request it makes other than a `GET`, for
+/// compliance audit logs.
+///
+/// The sink is called once per request, after any retries, whether or not it
+/// succeeded. Closures taking a `&AuditRecord` are sinks.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use derive_rest_api::AuditRecord;
+///
+/// let client = UsersClient::builder()
+///     .audit(|record: &AuditRecord| {
+///         log::info!(target: "audit", "{:?} {} {} {:?}", record.actor, record.method, record.url, record.body_digest);
+///     })
+///     .audit_actor("billing-service")
+///     .build()?;
+///
+/// client.delete_user().id(42).audit_actor("alice@example.com").send()?;
+/// ```
+pub trait AuditSink: Send + Sync + 'static {
+    /// Called once a mutating request has completed or failed.
+    fn record(&self, record: &AuditRecord);
+}
+
+impl<F> AuditSink for F
+where
+    F: Fn(&AuditRecord) + Send + Sync + 'static,
+{
+    fn record(&self, record: &AuditRecord) {
+        self(record)
+    }
+}
+
+/// Returns whether requests with `method` are audited
+pub(crate) fn is_audited(method: &str) -> bool {
+    !method.eq_ignore_ascii_case("GET")
+}
+
+/// Returns the digest of `body` recorded in an [`AuditRecord`]
+pub(crate) fn body_digest(body: &[u8]) -> String {
+    format!("sha256:{}", hex(&sha256(body)))
+}
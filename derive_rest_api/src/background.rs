@@ -0,0 +1,263 @@
+//! A worker task sending fire-and-forget requests for `send_background()`.
+
+use crate::clock::clock_or_system;
+use crate::{AsyncHttpClient, Clock, RequestOptions, RestApiError, __private::Transport};
+use http::{HeaderMap, Response, StatusCode};
+use std::collections::VecDeque;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::time::Duration;
+
+/// Runs a future in the background
+type Spawn = Arc<dyn Fn(Pin<Box<dyn Future<Output = ()> + Send>>) + Send + Sync>;
+
+/// One attempt at sending a queued request
+type SendFuture = Pin<Box<dyn Future<Output = Result<Response<Vec<u8>>, RestApiError>> + Send>>;
+
+/// A request waiting for the worker, which can be sent again to retry it
+type Job = Box<dyn Fn() -> SendFuture + Send>;
+
+/// Sends requests from `send_background()` on a worker task, so that callers
+/// such as request handlers don't wait for telemetry or analytics calls.
+///
+/// Requests go into a channel holding up to [`capacity`](Self::capacity) of
+/// them, and `send_background()` fails with
+/// [`RestApiError::BackgroundQueueFull`] rather than wait when it is full.
+/// The worker sends them one at a time, in order, through the client's usual
+/// middleware, credentials and retry policy. A request that still fails with
+/// a transient HTTP client error, a `5xx` or a `429` is tried again up to
+/// [`max_retries`](Self::max_retries) times, with a backoff doubling from
+/// [`backoff`](Self::backoff), then dropped. Outcomes can be observed through
+/// the client's metrics hook and event subscribers.
+///
+/// The worker is a future given to `spawn` when the first request is queued,
+/// so it runs on the HTTP client's runtime. It ends once every clone of the
+/// sender, and every client holding one, has been dropped and the channel is
+/// empty.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use derive_rest_api::BackgroundSender;
+///
+/// let sender = BackgroundSender::new(|worker| {
+///     tokio::spawn(worker);
+/// })
+/// .capacity(10_000);
+/// let client = AnalyticsAsyncClient::builder().background_sender(sender).build()?;
+///
+/// client.track_event().name("page_view".to_string()).send_background()?;
+/// ```
+#[derive(Clone)]
+pub struct BackgroundSender {
+    channel: Arc<Channel>,
+    /// Closes the channel when the last clone is dropped
+    _open: Arc<Open>,
+    spawn: Spawn,
+    capacity: usize,
+    max_retries: u32,
+    backoff: Duration,
+    clock: Option<Arc<dyn Clock>>,
+}
+
+impl BackgroundSender {
+    /// Creates a sender whose worker is started with `spawn`, holding up to
+    /// 1024 requests and retrying each up to 3 times, after 500ms at first.
+    pub fn new(spawn: impl Fn(Pin<Box<dyn Future<Output = ()> + Send>>) + Send + Sync + 'static) -> Self {
+        let channel = Arc::new(Channel::default());
+        Self {
+            _open: Arc::new(Open(channel.clone())),
+            channel,
+            spawn: Arc::new(spawn),
+            capacity: 1024,
+            max_retries: 3,
+            backoff: Duration::from_millis(500),
+            clock: None,
+        }
+    }
+
+    /// Sets how many requests can wait for the worker (at least 1), counting
+    /// the one being sent.
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity.max(1);
+        self
+    }
+
+    /// Sets how many times the worker tries a failed request again.
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Sets the wait before the first retry, doubled for each one after it.
+    pub fn backoff(mut self, backoff: Duration) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Waits out retry backoff with `clock` instead of the system clock.
+    pub fn clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Some(Arc::new(clock));
+        self
+    }
+
+    /// Returns how many requests are waiting or being sent.
+    pub fn pending(&self) -> usize {
+        let state = self.channel.lock();
+        state.jobs.len() + usize::from(state.sending)
+    }
+
+    /// Queues a request for the worker, starting it if this is the first
+    fn push(&self, job: Job) -> Result<(), RestApiError> {
+        {
+            let mut state = self.channel.lock();
+            if state.jobs.len() + usize::from(state.sending) >= self.capacity {
+                return Err(RestApiError::BackgroundQueueFull { capacity: self.capacity });
+            }
+            state.jobs.push_back(job);
+            if let Some(waker) = state.waker.take() {
+                waker.wake();
+            }
+        }
+        if !self.channel.started.swap(true, Ordering::AcqRel) {
+            let worker = Worker {
+                channel: self.channel.clone(),
+                max_retries: self.max_retries,
+                backoff: self.backoff,
+                clock: self.clock.clone(),
+            };
+            (self.spawn)(Box::pin(worker.run()));
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for BackgroundSender {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BackgroundSender")
+            .field("pending", &self.pending())
+            .field("capacity", &self.capacity)
+            .field("max_retries", &self.max_retries)
+            .field("backoff", &self.backoff)
+            .field("clock", &self.clock)
+            .finish()
+    }
+}
+
+/// Queues a request built by `send_background()` on the transport's sender
+#[allow(clippy::too_many_arguments)]
+pub fn send_background<A: AsyncHttpClient + Send + Sync + 'static>(
+    client: A,
+    mut transport: Transport,
+    method: &'static str,
+    base_url: String,
+    path: String,
+    headers: HeaderMap,
+    body: Option<Vec<u8>>,
+    options: RequestOptions,
+) -> Result<(), RestApiError> {
+    let sender = transport.background.take().ok_or(RestApiError::NoBackgroundSender)?;
+    let request = Arc::new((client, transport, base_url, path, headers, body));
+    sender.push(Box::new(move || {
+        let request = request.clone();
+        Box::pin(async move {
+            let (client, transport, base_url, path, headers, body) = &*request;
+            transport
+                .send_async(client, method, base_url, path, headers.clone(), body.clone(), options)
+                .await
+        })
+    }))
+}
+
+/// The requests waiting for a worker
+#[derive(Default)]
+struct Channel {
+    state: Mutex<ChannelState>,
+    started: AtomicBool,
+}
+
+#[derive(Default)]
+struct ChannelState {
+    jobs: VecDeque<Job>,
+    /// Whether the worker is sending a request
+    sending: bool,
+    /// Set once every sender has been dropped
+    closed: bool,
+    /// Wakes the worker when a request is queued or the channel closes
+    waker: Option<Waker>,
+}
+
+impl Channel {
+    fn lock(&self) -> std::sync::MutexGuard<'_, ChannelState> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Held by every clone of a sender
+struct Open(Arc<Channel>);
+
+impl Drop for Open {
+    fn drop(&mut self) {
+        let mut state = self.0.lock();
+        state.closed = true;
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+struct Worker {
+    channel: Arc<Channel>,
+    max_retries: u32,
+    backoff: Duration,
+    clock: Option<Arc<dyn Clock>>,
+}
+
+impl Worker {
+    async fn run(self) {
+        while let Some(job) = (NextJob { channel: &self.channel }).await {
+            let mut retry = 0;
+            while is_retried(&job().await) && retry < self.max_retries {
+                let delay = self.backoff.saturating_mul(2u32.saturating_pow(retry));
+                clock_or_system(&self.clock).sleep_async(delay).await;
+                retry += 1;
+            }
+            self.channel.lock().sending = false;
+        }
+    }
+}
+
+/// Waits for the next request, or `None` once the channel is closed and empty
+struct NextJob<'a> {
+    channel: &'a Channel,
+}
+
+impl Future for NextJob<'_> {
+    type Output = Option<Job>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Job>> {
+        let mut state = self.channel.lock();
+        match state.jobs.pop_front() {
+            Some(job) => {
+                state.sending = true;
+                Poll::Ready(Some(job))
+            }
+            None if state.closed => Poll::Ready(None),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// Returns `true` if a background request failed in a way worth retrying
+fn is_retried(result: &Result<Response<Vec<u8>>, RestApiError>) -> bool {
+    match result {
+        Ok(response) => response.status().is_server_error() || response.status() == StatusCode::TOO_MANY_REQUESTS,
+        Err(error) => error.is_transient(),
+    }
+}
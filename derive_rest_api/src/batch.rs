@@ -0,0 +1,419 @@
+//! Multipart (OData-style `$batch`) batching of built requests.
+//!
+//! A [`Batch`] packs several requests into one `multipart/mixed` POST, each
+//! as an `application/http` part, and splits the multipart response back into
+//! per-request results that decode like the requests' own `send()`.
+
+use crate::{AsyncHttpClient, HttpClient, RequestOptions, RestApiError, RestRequest};
+use http::{HeaderMap, HeaderName, HeaderValue, Response, StatusCode};
+use std::fmt;
+
+/// A set of requests sent together as one `multipart/mixed` batch request.
+///
+/// # Example
+///
+/// ```ignore
+/// let mut batch = Batch::new();
+/// let user = batch.add(&GetUserBuilder::new().id(1).build()?)?;
+/// let posts = batch.add(&ListPostsBuilder::new().user_id(1).build()?)?;
+///
+/// let mut responses = batch.send(&client, "https://example.com/odata")?;
+/// let user: User = responses.take(user)?;
+/// let posts: Vec<Post> = responses.take(posts)?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct Batch {
+    path: String,
+    headers: HeaderMap,
+    options: RequestOptions,
+    parts: Vec<BatchPart>,
+}
+
+/// A request packed into a batch
+#[derive(Debug, Clone)]
+struct BatchPart {
+    method: &'static str,
+    path: String,
+    headers: HeaderMap,
+    body: Option<Vec<u8>>,
+}
+
+impl Default for Batch {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Batch {
+    /// Creates an empty batch sent to `/$batch` under the base URL.
+    pub fn new() -> Self {
+        Self {
+            path: "/$batch".to_string(),
+            headers: HeaderMap::new(),
+            options: RequestOptions::default(),
+            parts: Vec::new(),
+        }
+    }
+
+    /// Sets the path of the batch endpoint, relative to the base URL.
+    pub fn path(mut self, path: impl Into<String>) -> Self {
+        self.path = path.into();
+        self
+    }
+
+    /// Adds a header to the outer batch request (e.g. `OData-Version` or `Authorization`).
+    pub fn header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.headers.insert(name, value);
+        self
+    }
+
+    /// Sets the timeouts and response size limit of the batch request.
+    pub fn options(mut self, options: RequestOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Adds a built request to the batch, returning the handle to its result.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if building the request's URL, headers or body fails.
+    pub fn add<R: RestRequest>(&mut self, request: &R) -> Result<BatchHandle<R::Response>, RestApiError> {
+        let path = request
+            .build_url()
+            .map_err(|e| RestApiError::UrlBuildError { source: Box::new(e) })?;
+        self.parts.push(BatchPart {
+            method: request.method(),
+            path,
+            headers: request.build_headers()?,
+            body: request.build_body()?,
+        });
+
+        Ok(BatchHandle {
+            index: self.parts.len() - 1,
+            parse: R::parse_response,
+        })
+    }
+
+    /// Returns the number of requests in the batch.
+    pub fn len(&self) -> usize {
+        self.parts.len()
+    }
+
+    /// Returns `true` if no requests have been added.
+    pub fn is_empty(&self) -> bool {
+        self.parts.is_empty()
+    }
+
+    /// Sends the batch using the provided client.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the HTTP request fails or the response isn't a
+    /// `multipart/mixed` batch response.
+    pub fn send<C: HttpClient>(&self, client: &C, base_url: &str) -> Result<BatchResponse, RestApiError> {
+        let (url, headers, body) = self.encode(base_url);
+        let response = client
+            .send("POST", &url, headers, Some(body), self.options)
+            .map_err(RestApiError::http_error)
+            .and_then(|response| crate::__private::check_response_size(response, &self.options))?;
+        BatchResponse::parse(response, self.parts.len())
+    }
+
+    /// Sends the batch using the provided async client.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the HTTP request fails or the response isn't a
+    /// `multipart/mixed` batch response.
+    pub async fn send_async<C: AsyncHttpClient>(&self, client: &C, base_url: &str) -> Result<BatchResponse, RestApiError> {
+        let (url, headers, body) = self.encode(base_url);
+        let response = client
+            .send_async("POST", &url, headers, Some(body), self.options)
+            .await
+            .map_err(RestApiError::http_error)
+            .and_then(|response| crate::__private::check_response_size(response, &self.options))?;
+        BatchResponse::parse(response, self.parts.len())
+    }
+
+    /// Returns the URL, headers and multipart body of the batch request
+    fn encode(&self, base_url: &str) -> (String, HeaderMap, Vec<u8>) {
+        let parts: Vec<Vec<u8>> = self
+            .parts
+            .iter()
+            .enumerate()
+            .map(|(index, part)| part.encode(base_url, index + 1))
+            .collect();
+        let boundary = unused_boundary(&parts);
+
+        let mut body = Vec::new();
+        for part in &parts {
+            body.extend_from_slice(format!("--{}\r\n", boundary).as_bytes());
+            body.extend_from_slice(part);
+            body.extend_from_slice(b"\r\n");
+        }
+        body.extend_from_slice(format!("--{}--\r\n", boundary).as_bytes());
+
+        let mut headers = self.headers.clone();
+        let content_type = format!("multipart/mixed; boundary={}", boundary);
+        if let Ok(value) = HeaderValue::from_str(&content_type) {
+            headers.insert(http::header::CONTENT_TYPE, value);
+        }
+        headers
+            .entry(http::header::ACCEPT)
+            .or_insert(HeaderValue::from_static("multipart/mixed"));
+
+        (format!("{}{}", base_url, self.path), headers, body)
+    }
+}
+
+impl BatchPart {
+    /// Encodes the request as an `application/http` MIME part (headers and content)
+    fn encode(&self, base_url: &str, content_id: usize) -> Vec<u8> {
+        let mut part = format!(
+            "Content-Type: application/http\r\nContent-Transfer-Encoding: binary\r\nContent-ID: {}\r\n\r\n{} {}{} HTTP/1.1\r\n",
+            content_id, self.method, base_url, self.path
+        )
+        .into_bytes();
+        for (name, value) in &self.headers {
+            part.extend_from_slice(name.as_str().as_bytes());
+            part.extend_from_slice(b": ");
+            part.extend_from_slice(value.as_bytes());
+            part.extend_from_slice(b"\r\n");
+        }
+        part.extend_from_slice(b"\r\n");
+        if let Some(body) = &self.body {
+            part.extend_from_slice(body);
+        }
+        part
+    }
+}
+
+/// Returns a multipart boundary that doesn't occur in any of the parts
+fn unused_boundary(parts: &[Vec<u8>]) -> String {
+    (0u64..)
+        .map(|n| format!("batch_{:016x}", 0x5eed_ba7c_u64.wrapping_mul(n + 1)))
+        .find(|boundary| !parts.iter().any(|part| find(part, boundary.as_bytes()).is_some()))
+        .unwrap_or_default()
+}
+
+/// Handle to the result of a request added to a [`Batch`].
+pub struct BatchHandle<T> {
+    index: usize,
+    parse: fn(Response<Vec<u8>>) -> Result<T, RestApiError>,
+}
+
+impl<T> Clone for BatchHandle<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for BatchHandle<T> {}
+
+impl<T> fmt::Debug for BatchHandle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BatchHandle").field("index", &self.index).finish()
+    }
+}
+
+impl<T> BatchHandle<T> {
+    /// Returns the position of the request in its batch.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
+
+/// The demultiplexed responses to a [`Batch`].
+#[derive(Debug)]
+pub struct BatchResponse {
+    responses: Vec<Option<Response<Vec<u8>>>>,
+}
+
+impl BatchResponse {
+    /// Returns the response to a request, if the batch response included one.
+    ///
+    /// Like `send()`, error statuses are returned as responses.
+    pub fn response<T>(&self, handle: BatchHandle<T>) -> Option<&Response<Vec<u8>>> {
+        self.responses.get(handle.index)?.as_ref()
+    }
+
+    /// Removes the response to a request and decodes it as the request's `send()` would.
+    ///
+    /// # Errors
+    ///
+    /// Returns `RestApiError::InvalidBatchResponse` if the batch response has
+    /// no part for the request (or it was already taken), and otherwise the
+    /// errors of decoding the response.
+    pub fn take<T>(&mut self, handle: BatchHandle<T>) -> Result<T, RestApiError> {
+        let response = self
+            .responses
+            .get_mut(handle.index)
+            .and_then(Option::take)
+            .ok_or_else(|| RestApiError::InvalidBatchResponse {
+                message: format!("no response for request {}", handle.index + 1),
+            })?;
+        (handle.parse)(response)
+    }
+
+    /// Splits a `multipart/mixed` batch response into the responses to `count` requests
+    fn parse(response: Response<Vec<u8>>, count: usize) -> Result<Self, RestApiError> {
+        let invalid = |message: String| RestApiError::InvalidBatchResponse { message };
+        let content_type = response
+            .headers()
+            .get(http::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default();
+        let Some(boundary) = multipart_boundary(content_type) else {
+            return Err(invalid(format!(
+                "expected a multipart/mixed response, got status {} with content type '{}'",
+                response.status(),
+                content_type
+            )));
+        };
+
+        let mut parts = Vec::new();
+        collect_parts(response.body(), &boundary, &mut parts)?;
+
+        // Parts are matched by Content-ID where the server echoes it, and otherwise in order
+        let mut responses: Vec<Option<Response<Vec<u8>>>> = (0..count).map(|_| None).collect();
+        for (content_id, part) in parts {
+            let slot = match content_id {
+                Some(id) if (1..=count).contains(&id) && responses[id - 1].is_none() => id - 1,
+                _ => responses
+                    .iter()
+                    .position(Option::is_none)
+                    .ok_or_else(|| invalid(format!("more responses than the {} requests", count)))?,
+            };
+            responses[slot] = Some(part);
+        }
+        Ok(Self { responses })
+    }
+}
+
+/// Returns the boundary of a `multipart/mixed` content type
+fn multipart_boundary(content_type: &str) -> Option<String> {
+    let mut params = content_type.split(';');
+    if !params.next()?.trim().eq_ignore_ascii_case("multipart/mixed") {
+        return None;
+    }
+    params.find_map(|param| {
+        let (name, value) = param.split_once('=')?;
+        name.trim()
+            .eq_ignore_ascii_case("boundary")
+            .then(|| value.trim().trim_matches('"').to_string())
+    })
+}
+
+/// Parses the parts of a multipart body, descending into nested changesets
+fn collect_parts(
+    body: &[u8],
+    boundary: &str,
+    parts: &mut Vec<(Option<usize>, Response<Vec<u8>>)>,
+) -> Result<(), RestApiError> {
+    for part in split_multipart(body, boundary) {
+        let (headers, content) = split_head(part);
+        let content_type = headers
+            .get(http::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default();
+
+        if let Some(nested) = multipart_boundary(content_type) {
+            collect_parts(content, &nested, parts)?;
+            continue;
+        }
+        let content_id = headers
+            .get("content-id")
+            .and_then(|value| value.to_str().ok()?.trim().parse().ok());
+        parts.push((content_id, parse_http_response(content)?));
+    }
+    Ok(())
+}
+
+/// Returns the contents of each part of a multipart body
+fn split_multipart<'a>(body: &'a [u8], boundary: &str) -> Vec<&'a [u8]> {
+    let delimiter = format!("--{}", boundary).into_bytes();
+    let mut positions = Vec::new();
+    let mut offset = 0;
+    while let Some(index) = find(&body[offset..], &delimiter) {
+        let position = offset + index;
+        // Delimiters must start a line
+        if position == 0 || body[position - 1] == b'\n' {
+            positions.push(position);
+        }
+        offset = position + delimiter.len();
+    }
+
+    let mut parts = Vec::new();
+    for window in positions.windows(2) {
+        let after = window[0] + delimiter.len();
+        if body[after..].starts_with(b"--") {
+            break;
+        }
+        let Some(line_end) = find(&body[after..window[1]], b"\n") else {
+            continue;
+        };
+        // The line break before a delimiter belongs to the delimiter
+        let mut end = window[1];
+        if end > 0 && body[end - 1] == b'\n' {
+            end -= 1;
+        }
+        if end > 0 && body[end - 1] == b'\r' {
+            end -= 1;
+        }
+        let start = after + line_end + 1;
+        parts.push(&body[start..end.max(start)]);
+    }
+    parts
+}
+
+/// Splits a header block from what follows the blank line ending it
+fn split_head(message: &[u8]) -> (HeaderMap, &[u8]) {
+    let (head, rest) = if let Some(rest) = message.strip_prefix(b"\r\n") {
+        (&message[..0], rest)
+    } else if let Some(rest) = message.strip_prefix(b"\n") {
+        (&message[..0], rest)
+    } else {
+        match (find(message, b"\r\n\r\n"), find(message, b"\n\n")) {
+            (Some(crlf), Some(lf)) if lf < crlf => (&message[..lf], &message[lf + 2..]),
+            (Some(crlf), _) => (&message[..crlf], &message[crlf + 4..]),
+            (None, Some(lf)) => (&message[..lf], &message[lf + 2..]),
+            (None, None) => (message, &message[message.len()..]),
+        }
+    };
+
+    let mut headers = HeaderMap::new();
+    for line in String::from_utf8_lossy(head).lines() {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        if let (Ok(name), Ok(value)) = (HeaderName::from_bytes(name.trim().as_bytes()), HeaderValue::from_str(value.trim())) {
+            headers.append(name, value);
+        }
+    }
+    (headers, rest)
+}
+
+/// Parses an `application/http` part into a response
+fn parse_http_response(content: &[u8]) -> Result<Response<Vec<u8>>, RestApiError> {
+    let line_end = find(content, b"\n").unwrap_or(content.len());
+    let status_line = String::from_utf8_lossy(&content[..line_end]);
+    let status = status_line
+        .split_whitespace()
+        .nth(1)
+        .and_then(|code| StatusCode::from_bytes(code.as_bytes()).ok())
+        .ok_or_else(|| RestApiError::InvalidBatchResponse {
+            message: format!("invalid status line '{}'", status_line.trim_end()),
+        })?;
+    let (headers, body) = split_head(content.get(line_end + 1..).unwrap_or_default());
+
+    let mut response = Response::new(body.to_vec());
+    *response.status_mut() = status;
+    *response.headers_mut() = headers;
+    Ok(response)
+}
+
+/// Returns the position of `needle` in `haystack`
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|window| window == needle)
+}
@@ -0,0 +1,87 @@
+//! Requests as they would be sent, for snapshot tests.
+
+use crate::CanonicalRequest;
+use http::HeaderMap;
+
+/// A request as a builder would send it, from `preview()`.
+///
+/// Request builders generated with a `path` have a `preview(base_url)` method
+/// that builds the request without sending it, so tests can compare exactly
+/// what would go over the wire against a snapshot, without a mock client.
+/// Headers are sorted by name, keeping the order of a repeated header's
+/// values, so the snapshot doesn't depend on the order they were set in.
+///
+/// Credentials and middleware are applied when a request is sent, so they
+/// aren't part of a preview.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let request = GetUserBuilder::new().id(42).preview("https://api.example.com")?;
+/// assert_eq!(request.to_string(), "GET https://api.example.com/users/42\n");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BuiltRequest {
+    /// The HTTP method
+    pub method: String,
+    /// The full URL, including the query string
+    pub url: String,
+    /// Header names, in lowercase, and their values, sorted by name
+    pub headers: Vec<(String, String)>,
+    /// The serialized body, if the request has one
+    pub body: Option<Vec<u8>>,
+}
+
+impl BuiltRequest {
+    /// Creates a preview from the parts of a request.
+    ///
+    /// Header values that aren't UTF-8 are converted lossily.
+    pub fn new(method: impl Into<String>, url: impl Into<String>, headers: &HeaderMap, body: Option<Vec<u8>>) -> Self {
+        let mut headers: Vec<_> = headers
+            .iter()
+            .map(|(name, value)| (name.to_string(), String::from_utf8_lossy(value.as_bytes()).into_owned()))
+            .collect();
+        // A stable sort keeps the values of a repeated header in order
+        headers.sort_by(|(a, _), (b, _)| a.cmp(b));
+        Self {
+            method: method.into(),
+            url: url.into(),
+            headers,
+            body,
+        }
+    }
+
+    /// Returns the first value of the header `name`, compared case-insensitively.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Returns the body as text, if there is one and it is UTF-8.
+    pub fn body_text(&self) -> Option<&str> {
+        self.body.as_deref().and_then(|body| std::str::from_utf8(body).ok())
+    }
+
+    /// Returns the request in a normalized form, for signing or as a cache key.
+    pub fn canonical_representation(&self) -> CanonicalRequest {
+        let headers = self.headers.iter().map(|(name, value)| (name.as_str(), value.clone()));
+        CanonicalRequest::from_parts(&self.method, &self.url, headers, self.body.as_deref())
+    }
+}
+
+/// Formats the request like an HTTP message: the method and URL, a line per
+/// header, then a blank line and the body if there is one.
+impl std::fmt::Display for BuiltRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{} {}", self.method, self.url)?;
+        for (name, value) in &self.headers {
+            writeln!(f, "{}: {}", name, value)?;
+        }
+        if let Some(body) = &self.body {
+            write!(f, "\n{}", String::from_utf8_lossy(body))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,481 @@
+//! Response caching that follows the `Cache-Control` directives of responses
+//! (RFC 9111), as a client wrapper.
+
+use crate::clock::clock_or_system;
+use crate::{AsyncHttpClient, CanonicalRequest, Clock, HttpClient, RequestOptions};
+use http::header::{AGE, AUTHORIZATION, CACHE_CONTROL, PRAGMA, VARY};
+use http::{HeaderMap, HeaderName, HeaderValue, Response, StatusCode, Version};
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Stored responses, kept for as long as the responses themselves allow.
+///
+/// A response to a `GET` or `HEAD` is stored when its `Cache-Control` gives it
+/// a lifetime with `max-age` (or `s-maxage`, in a shared cache), and repeats of
+/// the request are answered from the cache until that lifetime has passed,
+/// with an `Age` header saying how long ago it was received. Nothing else is
+/// configured per endpoint:
+///
+/// - `no-store` and `no-cache` responses, and responses without a lifetime,
+///   aren't stored
+/// - `private` responses are only stored by a [`private`](Self::private) cache
+/// - requests with `Cache-Control: no-cache` or `no-store`, or `Pragma:
+///   no-cache`, skip the cache and fetch a fresh response
+/// - requests whose headers differ in one named by the response's `Vary` are
+///   different requests, and `Vary: *` responses aren't stored
+/// - a successful request with another method, such as a `POST` or `DELETE`,
+///   removes the stored responses for its URL
+///
+/// A cache made by [`new`](Self::new) is shared: it may be used by clients
+/// acting for different users, so it also doesn't store the responses to
+/// requests with an `Authorization` header, unless the response is `public` or
+/// has `s-maxage` or `must-revalidate`. Clones share the stored responses.
+///
+/// With [`stale_while_revalidate`](Self::stale_while_revalidate), async
+/// clients answer with an expired response straight away and refresh it in
+/// the background.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use derive_rest_api::ResponseCache;
+///
+/// let cache = ResponseCache::private().capacity(500);
+/// let client = UsersClient::new().with_cache(cache.clone());
+/// ```
+#[derive(Clone)]
+pub struct ResponseCache {
+    entries: Arc<Mutex<HashMap<String, CacheEntry>>>,
+    shared: bool,
+    capacity: usize,
+    clock: Option<Arc<dyn Clock>>,
+    /// How long after expiring responses without a `stale-while-revalidate` of their own are served
+    stale_window: Duration,
+    spawn: Option<Spawn>,
+}
+
+/// Runs a future in the background
+type Spawn = Arc<dyn Fn(Pin<Box<dyn Future<Output = ()> + Send>>) + Send + Sync>;
+
+impl ResponseCache {
+    /// Creates an empty shared cache, holding up to 1024 responses.
+    pub fn new() -> Self {
+        Self {
+            entries: Arc::default(),
+            shared: true,
+            capacity: 1024,
+            clock: None,
+            stale_window: Duration::ZERO,
+            spawn: None,
+        }
+    }
+
+    /// Creates an empty private cache, which also stores `private` responses
+    /// and responses to authorized requests.
+    ///
+    /// A private cache should only be used by clients acting for one user.
+    pub fn private() -> Self {
+        Self {
+            shared: false,
+            ..Self::new()
+        }
+    }
+
+    /// Sets how many responses the cache holds at most (at least 1).
+    ///
+    /// When it is full, expired responses are dropped first, then the ones
+    /// closest to expiring.
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity.max(1);
+        self
+    }
+
+    /// Ages stored responses with `clock` instead of the system clock.
+    pub fn clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Some(Arc::new(clock));
+        self
+    }
+
+    /// Serves expired responses while they are refreshed in the background, on
+    /// async clients.
+    ///
+    /// A request whose stored response has expired less than `window` ago, or
+    /// less than the response's own `stale-while-revalidate` directive allows,
+    /// gets that response at once, and the request is sent again by a task
+    /// given to `spawn` to update the cache. Only one refresh of a response
+    /// runs at a time. Responses with `must-revalidate` are never served
+    /// stale, and blocking clients wait for a fresh response as before.
+    ///
+    /// `spawn` starts the refresh on the runtime of the HTTP client:
+    ///
+    /// ```rust,ignore
+    /// let cache = ResponseCache::private().stale_while_revalidate(Duration::from_secs(300), |refresh| {
+    ///     tokio::spawn(refresh);
+    /// });
+    /// ```
+    pub fn stale_while_revalidate(
+        mut self,
+        window: Duration,
+        spawn: impl Fn(Pin<Box<dyn Future<Output = ()> + Send>>) + Send + Sync + 'static,
+    ) -> Self {
+        self.stale_window = window;
+        self.spawn = Some(Arc::new(spawn));
+        self
+    }
+
+    /// Returns how many responses are stored, including expired ones not yet dropped.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Returns `true` if no responses are stored.
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Drops every stored response.
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    /// Returns the stored response to a request, if there is a fresh one.
+    ///
+    /// With `stale` set, an expired response that may be served stale is
+    /// returned too, along with `true` if the caller should refresh it.
+    fn lookup(&self, method: &str, url: &str, headers: &HeaderMap, stale: bool) -> Option<(Response<Vec<u8>>, bool)> {
+        if !is_cacheable_method(method) || bypasses_cache(headers) {
+            return None;
+        }
+        let now = clock_or_system(&self.clock).now();
+        let mut entries = self.lock();
+        let entry = entries.get_mut(&cache_key(method, url)).filter(|entry| entry.matches(headers))?;
+        let age = entry.age(now);
+        if age < entry.lifetime {
+            return Some((entry.response(age), false));
+        }
+        let window = entry.stale_window.unwrap_or(self.stale_window);
+        if !stale || self.spawn.is_none() || entry.must_revalidate || age >= entry.lifetime + window {
+            return None;
+        }
+        let refresh = !std::mem::replace(&mut entry.revalidating, true);
+        Some((entry.response(age), refresh))
+    }
+
+    /// Lets a later request refresh a response whose refresh failed
+    fn revalidation_failed(&self, method: &str, url: &str) {
+        if let Some(entry) = self.lock().get_mut(&cache_key(method, url)) {
+            entry.revalidating = false;
+        }
+    }
+
+    /// Stores the response to a request if it may be, or drops what it replaces
+    fn store(&self, method: &str, url: &str, headers: &HeaderMap, response: &Response<Vec<u8>>) {
+        if !is_cacheable_method(method) {
+            // A change to the resource makes what was stored for it stale
+            if response.status().is_success() || response.status().is_redirection() {
+                let mut entries = self.lock();
+                entries.remove(&cache_key("GET", url));
+                entries.remove(&cache_key("HEAD", url));
+            }
+            return;
+        }
+        let key = cache_key(method, url);
+        let Some(entry) = self.entry(headers, response) else {
+            self.lock().remove(&key);
+            return;
+        };
+
+        let mut entries = self.lock();
+        if entries.len() >= self.capacity && !entries.contains_key(&key) {
+            let now = entry.stored;
+            entries.retain(|_, stored| stored.age(now) < stored.lifetime);
+            while entries.len() >= self.capacity {
+                let soonest = entries.iter().min_by_key(|(_, stored)| stored.expires()).map(|(key, _)| key.clone());
+                match soonest {
+                    Some(soonest) => entries.remove(&soonest),
+                    None => break,
+                };
+            }
+        }
+        entries.insert(key, entry);
+    }
+
+    /// Returns what to store for a response, or `None` if it can't be stored
+    fn entry(&self, headers: &HeaderMap, response: &Response<Vec<u8>>) -> Option<CacheEntry> {
+        if !is_cacheable_status(response.status()) || directives(headers).no_store {
+            return None;
+        }
+        let directives = directives(response.headers());
+        if directives.no_store || directives.no_cache || (self.shared && directives.private) {
+            return None;
+        }
+        let authorized = headers.contains_key(AUTHORIZATION);
+        if self.shared && authorized && !(directives.public || directives.s_maxage.is_some() || directives.must_revalidate) {
+            return None;
+        }
+        let max_age = match self.shared {
+            true => directives.s_maxage.or(directives.max_age),
+            false => directives.max_age,
+        }?;
+        let vary = vary(headers, response.headers())?;
+        let age = header_seconds(response.headers(), AGE).unwrap_or(0);
+
+        Some(CacheEntry {
+            status: response.status(),
+            version: response.version(),
+            headers: response.headers().clone(),
+            body: response.body().clone(),
+            vary,
+            stored: clock_or_system(&self.clock).now(),
+            initial_age: Duration::from_secs(age),
+            lifetime: Duration::from_secs(max_age),
+            stale_window: directives.stale_while_revalidate.map(Duration::from_secs),
+            must_revalidate: directives.must_revalidate,
+            revalidating: false,
+        })
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, CacheEntry>> {
+        self.entries.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Default for ResponseCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for ResponseCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ResponseCache")
+            .field("entries", &self.len())
+            .field("shared", &self.shared)
+            .field("capacity", &self.capacity)
+            .field("clock", &self.clock)
+            .field("stale_window", &self.stale_window)
+            .field("revalidate", &self.spawn.is_some())
+            .finish()
+    }
+}
+
+/// A stored response
+#[derive(Debug)]
+struct CacheEntry {
+    status: StatusCode,
+    version: Version,
+    headers: HeaderMap,
+    body: Vec<u8>,
+    /// The request headers named by `Vary`, and their values
+    vary: Vec<(HeaderName, Option<HeaderValue>)>,
+    stored: Instant,
+    /// The `Age` the response already had when it was received
+    initial_age: Duration,
+    lifetime: Duration,
+    /// The response's own `stale-while-revalidate` window
+    stale_window: Option<Duration>,
+    must_revalidate: bool,
+    /// Whether a background refresh of the response is running
+    revalidating: bool,
+}
+
+impl CacheEntry {
+    fn age(&self, now: Instant) -> Duration {
+        self.initial_age + now.saturating_duration_since(self.stored)
+    }
+
+    fn expires(&self) -> Instant {
+        self.stored + self.lifetime.saturating_sub(self.initial_age)
+    }
+
+    /// Returns `true` if a request has the same values for the `Vary` headers
+    fn matches(&self, headers: &HeaderMap) -> bool {
+        self.vary.iter().all(|(name, value)| headers.get(name) == value.as_ref())
+    }
+
+    fn response(&self, age: Duration) -> Response<Vec<u8>> {
+        let mut response = Response::new(self.body.clone());
+        *response.status_mut() = self.status;
+        *response.version_mut() = self.version;
+        *response.headers_mut() = self.headers.clone();
+        response.headers_mut().insert(AGE, HeaderValue::from(age.as_secs()));
+        response
+    }
+}
+
+/// The `Cache-Control` directives the cache acts on
+#[derive(Debug, Default)]
+struct Directives {
+    no_store: bool,
+    no_cache: bool,
+    private: bool,
+    public: bool,
+    must_revalidate: bool,
+    max_age: Option<u64>,
+    s_maxage: Option<u64>,
+    stale_while_revalidate: Option<u64>,
+}
+
+fn directives(headers: &HeaderMap) -> Directives {
+    let mut directives = Directives::default();
+    let values = headers.get_all(CACHE_CONTROL).iter().filter_map(|value| value.to_str().ok());
+    for directive in values.flat_map(|value| value.split(',')) {
+        let (name, argument) = directive.split_once('=').unwrap_or((directive, ""));
+        let seconds = || argument.trim().trim_matches('"').parse::<u64>().ok();
+        match name.trim().to_ascii_lowercase().as_str() {
+            "no-store" => directives.no_store = true,
+            "no-cache" => directives.no_cache = true,
+            "private" => directives.private = true,
+            "public" => directives.public = true,
+            "must-revalidate" => directives.must_revalidate = true,
+            "max-age" => directives.max_age = seconds(),
+            "s-maxage" => directives.s_maxage = seconds(),
+            "stale-while-revalidate" => directives.stale_while_revalidate = seconds(),
+            _ => {}
+        }
+    }
+    directives
+}
+
+/// Returns `true` if a request asks for a response fresh from the server
+fn bypasses_cache(headers: &HeaderMap) -> bool {
+    let request = directives(headers);
+    let pragma = headers
+        .get_all(PRAGMA)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .any(|value| value.to_ascii_lowercase().contains("no-cache"));
+    request.no_store || request.no_cache || pragma
+}
+
+/// Returns the request headers named by the response's `Vary`, or `None` for `Vary: *`
+fn vary(request: &HeaderMap, response: &HeaderMap) -> Option<Vec<(HeaderName, Option<HeaderValue>)>> {
+    let names = response.get_all(VARY).iter().filter_map(|value| value.to_str().ok());
+    let mut vary = Vec::new();
+    for name in names.flat_map(|value| value.split(',')).map(str::trim).filter(|name| !name.is_empty()) {
+        if name == "*" {
+            return None;
+        }
+        if let Ok(name) = HeaderName::from_bytes(name.as_bytes()) {
+            let value = request.get(&name).cloned();
+            vary.push((name, value));
+        }
+    }
+    Some(vary)
+}
+
+fn header_seconds(headers: &HeaderMap, name: HeaderName) -> Option<u64> {
+    headers.get(name)?.to_str().ok()?.trim().parse().ok()
+}
+
+fn is_cacheable_method(method: &str) -> bool {
+    method.eq_ignore_ascii_case("GET") || method.eq_ignore_ascii_case("HEAD")
+}
+
+/// The statuses RFC 9110 lets caches store by default
+fn is_cacheable_status(status: StatusCode) -> bool {
+    matches!(status.as_u16(), 200 | 203 | 204 | 300 | 301 | 308 | 404 | 405 | 410 | 414 | 501)
+}
+
+/// Identifies a request by its method and normalized URL
+fn cache_key(method: &str, url: &str) -> String {
+    CanonicalRequest::new(method, url, &HeaderMap::new(), None).digest()
+}
+
+/// HTTP client wrapper that answers repeated requests from a [`ResponseCache`].
+///
+/// Generated clients wrap their HTTP client in this with `with_cache()`. A
+/// default `CachingClient` has no cache and sends every request.
+#[derive(Debug, Clone, Default)]
+pub struct CachingClient<C> {
+    inner: C,
+    cache: Option<ResponseCache>,
+}
+
+impl<C> CachingClient<C> {
+    /// Wraps `inner`, caching its responses in `cache`.
+    pub fn new(inner: C, cache: ResponseCache) -> Self {
+        Self {
+            inner,
+            cache: Some(cache),
+        }
+    }
+
+    /// Returns the wrapped client.
+    pub fn inner(&self) -> &C {
+        &self.inner
+    }
+
+    /// Returns the cache responses are stored in.
+    pub fn cache(&self) -> Option<&ResponseCache> {
+        self.cache.as_ref()
+    }
+}
+
+impl<C: HttpClient> HttpClient for CachingClient<C> {
+    type Error = C::Error;
+
+    fn send(
+        &self,
+        method: &str,
+        url: &str,
+        headers: HeaderMap,
+        body: Option<Vec<u8>>,
+        options: RequestOptions,
+    ) -> Result<Response<Vec<u8>>, Self::Error> {
+        let Some(cache) = &self.cache else {
+            return self.inner.send(method, url, headers, body, options);
+        };
+        if let Some((response, _)) = cache.lookup(method, url, &headers, false) {
+            return Ok(response);
+        }
+        let response = self.inner.send(method, url, headers.clone(), body, options)?;
+        cache.store(method, url, &headers, &response);
+        Ok(response)
+    }
+}
+
+impl<C: AsyncHttpClient + Send + Sync + 'static> CachingClient<C> {
+    /// Sends a request again in the background, storing the response
+    fn revalidate(&self, cache: &ResponseCache, method: &str, url: &str, headers: HeaderMap, options: RequestOptions) {
+        let Some(spawn) = &cache.spawn else {
+            return;
+        };
+        let (inner, cache, method, url) = (self.inner.clone(), cache.clone(), method.to_string(), url.to_string());
+        spawn(Box::pin(async move {
+            match inner.send_async(&method, &url, headers.clone(), None, options).await {
+                Ok(response) => cache.store(&method, &url, &headers, &response),
+                Err(_) => cache.revalidation_failed(&method, &url),
+            }
+        }));
+    }
+}
+
+impl<C: AsyncHttpClient + Send + Sync + 'static> AsyncHttpClient for CachingClient<C> {
+    type Error = C::Error;
+
+    async fn send_async(
+        &self,
+        method: &str,
+        url: &str,
+        headers: HeaderMap,
+        body: Option<Vec<u8>>,
+        options: RequestOptions,
+    ) -> Result<Response<Vec<u8>>, Self::Error> {
+        let Some(cache) = &self.cache else {
+            return self.inner.send_async(method, url, headers, body, options).await;
+        };
+        if let Some((response, refresh)) = cache.lookup(method, url, &headers, true) {
+            if refresh {
+                self.revalidate(cache, method, url, headers, options);
+            }
+            return Ok(response);
+        }
+        let response = self.inner.send_async(method, url, headers.clone(), body, options).await?;
+        cache.store(method, url, &headers, &response);
+        Ok(response)
+    }
+}
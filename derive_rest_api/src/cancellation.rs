@@ -0,0 +1,125 @@
+//! Runtime-agnostic cancellation of in-flight async requests.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+/// A token that cancels the async requests it is attached to.
+///
+/// Attach a token to a request builder with `.cancellation_token(token)`; calling
+/// [`cancel`](Self::cancel) on any clone of the token makes every pending `send_async`
+/// using it stop waiting and return [`RestApiError::Cancelled`](crate::RestApiError::Cancelled).
+/// The in-flight HTTP request is dropped, which aborts it for all bundled clients.
+///
+/// The token does not depend on any async runtime. To cancel when a scope ends (for
+/// example when a UI component unmounts or a server handler returns), hold the guard
+/// returned by [`drop_guard`](Self::drop_guard).
+///
+/// # Example
+///
+/// ```
+/// use derive_rest_api::CancellationToken;
+///
+/// let token = CancellationToken::new();
+/// let handle = token.clone();
+///
+/// assert!(!handle.is_cancelled());
+/// token.cancel();
+/// assert!(handle.is_cancelled());
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken {
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    cancelled: AtomicBool,
+    wakers: Mutex<Vec<Waker>>,
+}
+
+impl CancellationToken {
+    /// Creates a new token that has not been cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancels the token, waking every request waiting on it.
+    ///
+    /// Cancelling an already cancelled token has no effect.
+    pub fn cancel(&self) {
+        self.inner.cancelled.store(true, Ordering::SeqCst);
+        let wakers = std::mem::take(&mut *self.inner.wakers.lock().unwrap_or_else(|e| e.into_inner()));
+        for waker in wakers {
+            waker.wake();
+        }
+    }
+
+    /// Returns `true` if the token has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Returns a future that completes once the token is cancelled.
+    pub fn cancelled(&self) -> WaitForCancellation<'_> {
+        WaitForCancellation { token: self }
+    }
+
+    /// Returns a guard that cancels the token when it is dropped.
+    pub fn drop_guard(self) -> CancellationDropGuard {
+        CancellationDropGuard { token: Some(self) }
+    }
+}
+
+/// Future returned by [`CancellationToken::cancelled`].
+#[derive(Debug)]
+#[must_use = "futures do nothing unless polled"]
+pub struct WaitForCancellation<'a> {
+    token: &'a CancellationToken,
+}
+
+impl Future for WaitForCancellation<'_> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.token.is_cancelled() {
+            return Poll::Ready(());
+        }
+
+        let mut wakers = self.token.inner.wakers.lock().unwrap_or_else(|e| e.into_inner());
+        // Check again under the lock so a concurrent cancel() can't be missed
+        if self.token.is_cancelled() {
+            return Poll::Ready(());
+        }
+        if !wakers.iter().any(|waker| waker.will_wake(cx.waker())) {
+            wakers.push(cx.waker().clone());
+        }
+        Poll::Pending
+    }
+}
+
+/// Guard that cancels its [`CancellationToken`] when dropped.
+///
+/// Created with [`CancellationToken::drop_guard`].
+#[derive(Debug)]
+#[must_use = "the token is cancelled as soon as the guard is dropped"]
+pub struct CancellationDropGuard {
+    token: Option<CancellationToken>,
+}
+
+impl CancellationDropGuard {
+    /// Returns the token without cancelling it.
+    pub fn disarm(mut self) -> CancellationToken {
+        self.token.take().expect("guard holds its token until dropped or disarmed")
+    }
+}
+
+impl Drop for CancellationDropGuard {
+    fn drop(&mut self) {
+        if let Some(token) = &self.token {
+            token.cancel();
+        }
+    }
+}
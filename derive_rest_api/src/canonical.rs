@@ -0,0 +1,205 @@
+//! A normalized form of a request, for signing and caching.
+
+use crate::digest::{hex, sha256};
+use http::HeaderMap;
+
+/// A request in a normalized form, so that requests meaning the same thing
+/// compare, hash and sign the same way however they were written.
+///
+/// Built by `canonical_representation()` on a [`BuiltRequest`](crate::BuiltRequest)
+/// or, in middleware, a [`RequestParts`](crate::RequestParts):
+///
+/// - the method is uppercased
+/// - the path and each query name and value are percent-decoded, then
+///   percent-encoded again following RFC 3986, with uppercase hex digits and
+///   only `A-Za-z0-9-._~` left as-is, besides the slashes separating the path's
+///   segments. A `+` in the query is read as a space, and written as `%20`
+/// - query parameters are sorted by name, then by value
+/// - header names are lowercased, values have their surrounding whitespace
+///   trimmed and inner runs of whitespace collapsed, and headers are sorted by
+///   name, with a repeated header's values joined by `,` in order
+/// - the body is replaced by its SHA-256 hash, in lowercase hex, which is the
+///   hash of nothing when there is no body
+///
+/// Its `Display` output is the canonical request of AWS Signature Version 4,
+/// so signers can hash it directly, and [`digest()`](Self::digest) gives a
+/// cache key.
+///
+/// # Example
+///
+/// ```
+/// use derive_rest_api::http::HeaderMap;
+/// use derive_rest_api::CanonicalRequest;
+///
+/// let mut headers = HeaderMap::new();
+/// headers.insert("X-Amz-Date", "20240101T000000Z".parse().unwrap());
+/// headers.insert("Host", "api.example.com".parse().unwrap());
+/// let canonical = CanonicalRequest::new("get", "https://api.example.com/a%2fb c?z=1&a=x+y", &headers, None);
+///
+/// assert_eq!(canonical.path, "/a%2Fb%20c");
+/// assert_eq!(canonical.query, "a=x%20y&z=1");
+/// assert_eq!(canonical.signed_headers(), "host;x-amz-date");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CanonicalRequest {
+    /// The uppercased HTTP method
+    pub method: String,
+    /// The lowercased scheme and host, such as `https://api.example.com`, or
+    /// empty if the URL is relative
+    pub authority: String,
+    /// The normalized path, `/` if the URL has none
+    pub path: String,
+    /// The normalized and sorted query string, without the leading `?`
+    pub query: String,
+    /// Lowercased header names and their normalized values, sorted by name
+    pub headers: Vec<(String, String)>,
+    /// The SHA-256 hash of the body, in lowercase hex
+    pub body_sha256: String,
+}
+
+impl CanonicalRequest {
+    /// Creates the canonical form of a request from its parts.
+    ///
+    /// Header values that aren't UTF-8 are converted lossily.
+    pub fn new(method: &str, url: &str, headers: &HeaderMap, body: Option<&[u8]>) -> Self {
+        let headers = headers
+            .iter()
+            .map(|(name, value)| (name.as_str(), String::from_utf8_lossy(value.as_bytes()).into_owned()));
+        Self::from_parts(method, url, headers, body)
+    }
+
+    pub(crate) fn from_parts<'a>(
+        method: &str,
+        url: &str,
+        headers: impl IntoIterator<Item = (&'a str, String)>,
+        body: Option<&[u8]>,
+    ) -> Self {
+        let (authority, rest) = split_authority(url);
+        let (path, query) = rest.split_once('?').unwrap_or((rest, ""));
+        // Segments are normalized separately, so an encoded `/` stays encoded
+        let path = match path.split('/').map(canonical_component).collect::<Vec<_>>().join("/") {
+            path if path.is_empty() => "/".to_string(),
+            path => path,
+        };
+
+        Self {
+            method: method.to_ascii_uppercase(),
+            authority: authority.to_ascii_lowercase(),
+            path,
+            query: canonical_query(query),
+            headers: canonical_headers(headers),
+            body_sha256: hex(&sha256(body.unwrap_or_default())),
+        }
+    }
+
+    /// Returns the header names joined by `;`, as SigV4 lists its signed headers.
+    pub fn signed_headers(&self) -> String {
+        self.headers.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>().join(";")
+    }
+
+    /// Returns the SHA-256 hash of the authority and the canonical request, in
+    /// lowercase hex, which identifies the request for caching.
+    pub fn digest(&self) -> String {
+        hex(&sha256(format!("{}\n{}", self.authority, self).as_bytes()))
+    }
+}
+
+/// Writes the method, path, query, a `name:value` line per header, a blank
+/// line, the signed headers and the body hash, each on its own line.
+impl std::fmt::Display for CanonicalRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}", self.method)?;
+        writeln!(f, "{}", self.path)?;
+        writeln!(f, "{}", self.query)?;
+        for (name, value) in &self.headers {
+            writeln!(f, "{}:{}", name, value)?;
+        }
+        writeln!(f)?;
+        writeln!(f, "{}", self.signed_headers())?;
+        write!(f, "{}", self.body_sha256)
+    }
+}
+
+/// Splits `scheme://host` off the front of a URL, if it has one
+fn split_authority(url: &str) -> (&str, &str) {
+    let Some(scheme_end) = url.find("://") else {
+        return ("", url);
+    };
+    let host_start = scheme_end + 3;
+    let host_end = url[host_start..].find(['/', '?']).map_or(url.len(), |end| host_start + end);
+    url.split_at(host_end)
+}
+
+fn canonical_query(query: &str) -> String {
+    let mut params: Vec<(String, String)> = query
+        .split('&')
+        .filter(|param| !param.is_empty())
+        .map(|param| {
+            let (name, value) = param.split_once('=').unwrap_or((param, ""));
+            (canonical_component(&name.replace('+', " ")), canonical_component(&value.replace('+', " ")))
+        })
+        .collect();
+    params.sort();
+    params
+        .iter()
+        .map(|(name, value)| format!("{}={}", name, value))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+fn canonical_headers<'a>(headers: impl IntoIterator<Item = (&'a str, String)>) -> Vec<(String, String)> {
+    let mut headers: Vec<(String, String)> = headers
+        .into_iter()
+        .map(|(name, value)| (name.to_ascii_lowercase(), value.split_whitespace().collect::<Vec<_>>().join(" ")))
+        .collect();
+    // A stable sort keeps the values of a repeated header in order
+    headers.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    let mut joined: Vec<(String, String)> = Vec::with_capacity(headers.len());
+    for (name, value) in headers {
+        match joined.last_mut() {
+            Some((last, values)) if *last == name => {
+                values.push(',');
+                values.push_str(&value);
+            }
+            _ => joined.push((name, value)),
+        }
+    }
+    joined
+}
+
+/// Percent-decodes `component`, then percent-encodes it following RFC 3986
+fn canonical_component(component: &str) -> String {
+    let mut encoded = String::with_capacity(component.len());
+    for byte in percent_decode(component) {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => encoded.push(byte as char),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+/// Decodes `%XX` sequences, leaving malformed ones as they are
+pub(crate) fn percent_decode(component: &str) -> Vec<u8> {
+    let bytes = component.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex_digits = bytes
+            .get(i + 1..i + 3)
+            .filter(|digits| bytes[i] == b'%' && digits.iter().all(u8::is_ascii_hexdigit))
+            .and_then(|digits| std::str::from_utf8(digits).ok());
+        match hex_digits.and_then(|digits| u8::from_str_radix(digits, 16).ok()) {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    decoded
+}
@@ -0,0 +1,140 @@
+//! Content checksums for request and response bodies.
+
+use crate::RestApiError;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use http::{HeaderMap, HeaderName, HeaderValue};
+use std::fmt;
+use std::str::FromStr;
+
+/// A checksum algorithm used by object-storage style APIs.
+///
+/// `Md5` is sent in the standard `Content-MD5` header; the others use the
+/// `x-amz-checksum-*` headers understood by S3 and compatible services. Every
+/// checksum is the base64 encoding of the raw digest.
+///
+/// # Example
+///
+/// ```
+/// use derive_rest_api::ChecksumAlgorithm;
+///
+/// let algorithm: ChecksumAlgorithm = "sha256".parse().unwrap();
+/// assert_eq!(algorithm.header_name(), "x-amz-checksum-sha256");
+/// assert_eq!(ChecksumAlgorithm::Md5.compute(b""), "1B2M2Y8AsgTpgAmY7PhCfg==");
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ChecksumAlgorithm {
+    /// MD5, sent as `Content-MD5`.
+    Md5,
+    /// SHA-1, sent as `x-amz-checksum-sha1`.
+    Sha1,
+    /// SHA-256, sent as `x-amz-checksum-sha256`.
+    Sha256,
+    /// CRC-32 (IEEE), sent as `x-amz-checksum-crc32`.
+    Crc32,
+    /// CRC-32C (Castagnoli), sent as `x-amz-checksum-crc32c`.
+    Crc32c,
+}
+
+impl ChecksumAlgorithm {
+    /// Every supported algorithm.
+    pub const ALL: [ChecksumAlgorithm; 5] = [
+        ChecksumAlgorithm::Md5,
+        ChecksumAlgorithm::Sha1,
+        ChecksumAlgorithm::Sha256,
+        ChecksumAlgorithm::Crc32,
+        ChecksumAlgorithm::Crc32c,
+    ];
+
+    /// Returns the lowercase name of the algorithm (e.g. `"sha256"`).
+    pub fn name(&self) -> &'static str {
+        match self {
+            ChecksumAlgorithm::Md5 => "md5",
+            ChecksumAlgorithm::Sha1 => "sha1",
+            ChecksumAlgorithm::Sha256 => "sha256",
+            ChecksumAlgorithm::Crc32 => "crc32",
+            ChecksumAlgorithm::Crc32c => "crc32c",
+        }
+    }
+
+    /// Returns the header that carries this checksum.
+    pub fn header_name(&self) -> HeaderName {
+        HeaderName::from_static(match self {
+            ChecksumAlgorithm::Md5 => "content-md5",
+            ChecksumAlgorithm::Sha1 => "x-amz-checksum-sha1",
+            ChecksumAlgorithm::Sha256 => "x-amz-checksum-sha256",
+            ChecksumAlgorithm::Crc32 => "x-amz-checksum-crc32",
+            ChecksumAlgorithm::Crc32c => "x-amz-checksum-crc32c",
+        })
+    }
+
+    /// Computes the base64-encoded checksum of `body`.
+    pub fn compute(&self, body: &[u8]) -> String {
+        use sha2::Digest;
+
+        match self {
+            ChecksumAlgorithm::Md5 => STANDARD.encode(md5::Md5::digest(body)),
+            ChecksumAlgorithm::Sha1 => STANDARD.encode(sha1::Sha1::digest(body)),
+            ChecksumAlgorithm::Sha256 => STANDARD.encode(sha2::Sha256::digest(body)),
+            ChecksumAlgorithm::Crc32 => STANDARD.encode(crc32fast::hash(body).to_be_bytes()),
+            ChecksumAlgorithm::Crc32c => STANDARD.encode(crc32c::crc32c(body).to_be_bytes()),
+        }
+    }
+
+    /// Adds this checksum of `body` to `headers`, replacing any existing value.
+    pub fn insert_header(&self, headers: &mut HeaderMap, body: &[u8]) {
+        let value = HeaderValue::try_from(self.compute(body))
+            .expect("base64 is always a valid header value");
+        headers.insert(self.header_name(), value);
+    }
+}
+
+impl fmt::Display for ChecksumAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for ChecksumAlgorithm {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ChecksumAlgorithm::ALL
+            .into_iter()
+            .find(|algorithm| algorithm.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("unknown checksum algorithm '{}'", s))
+    }
+}
+
+/// Verifies every checksum header in `headers` against `body`.
+///
+/// Each of `Content-MD5` and the `x-amz-checksum-*` headers that is present
+/// is checked; a response without any of them passes. Composite checksums of
+/// multipart uploads (values ending in `-<part count>`) describe the parts
+/// rather than the body and are skipped.
+///
+/// # Errors
+///
+/// Returns `RestApiError::ChecksumMismatch` for the first header whose value
+/// does not match the body.
+pub fn verify_checksums(headers: &HeaderMap, body: &[u8]) -> Result<(), RestApiError> {
+    for algorithm in ChecksumAlgorithm::ALL {
+        let header = algorithm.header_name();
+        for value in headers.get_all(&header) {
+            let expected = String::from_utf8_lossy(value.as_bytes()).trim().to_string();
+            if expected.contains('-') {
+                continue;
+            }
+
+            let actual = algorithm.compute(body);
+            if expected != actual {
+                return Err(RestApiError::ChecksumMismatch {
+                    header: header.to_string(),
+                    expected,
+                    actual,
+                });
+            }
+        }
+    }
+    Ok(())
+}
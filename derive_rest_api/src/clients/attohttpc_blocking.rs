@@ -0,0 +1,136 @@
+//! attohttpc blocking HTTP client implementation.
+
+use crate::{HttpClient, RequestOptions};
+use http::HeaderMap;
+
+/// attohttpc client wrapper that implements HttpClient
+///
+/// This wrapper provides a blocking HTTP client implementation using
+/// attohttpc, a minimal HTTP client with very few dependencies. It is a good
+/// fit for CLI tools that want a small dependency footprint. It can be
+/// created with default settings or with a custom attohttpc session whose
+/// settings (default headers, timeouts, proxies, etc.) apply to every request.
+///
+/// Connect, read and total timeouts are all applied per request, overriding
+/// the session's values. attohttpc has no setting for the IP address family,
+/// so a connect timeout on the session is the way to bound how long
+/// unreachable addresses can take.
+///
+/// # Examples
+///
+/// Basic usage:
+/// ```no_run
+/// use derive_rest_api::AttohttpcClient;
+///
+/// let client = AttohttpcClient::new();
+/// ```
+///
+/// With custom configuration:
+/// ```no_run
+/// use derive_rest_api::AttohttpcClient;
+///
+/// let mut session = attohttpc::Session::new();
+/// session.timeout(std::time::Duration::from_secs(30));
+///
+/// let client = AttohttpcClient::with_session(session);
+/// ```
+#[derive(Clone, Default)]
+pub struct AttohttpcClient {
+    session: attohttpc::Session,
+}
+
+impl AttohttpcClient {
+    /// Creates a new attohttpc client wrapper with default settings
+    pub fn new() -> Self {
+        Self {
+            session: attohttpc::Session::new(),
+        }
+    }
+
+    /// Creates a new attohttpc client wrapper with a custom session
+    ///
+    /// This allows you to configure the attohttpc session with custom settings
+    /// such as timeouts, default headers, proxy settings, etc.
+    pub fn with_session(session: attohttpc::Session) -> Self {
+        Self { session }
+    }
+
+    /// Returns the wrapped attohttpc session
+    pub fn inner(&self) -> &attohttpc::Session {
+        &self.session
+    }
+
+    /// Returns the wrapped attohttpc session mutably, e.g. to change its settings
+    pub fn inner_mut(&mut self) -> &mut attohttpc::Session {
+        &mut self.session
+    }
+
+    /// Consumes the wrapper, returning the wrapped attohttpc session
+    pub fn into_inner(self) -> attohttpc::Session {
+        self.session
+    }
+}
+
+impl From<attohttpc::Session> for AttohttpcClient {
+    fn from(session: attohttpc::Session) -> Self {
+        AttohttpcClient::with_session(session)
+    }
+}
+
+impl HttpClient for AttohttpcClient {
+    type Error = attohttpc::Error;
+
+    fn send(
+        &self,
+        method: &str,
+        url: &str,
+        headers: HeaderMap,
+        body: Option<Vec<u8>>,
+        options: RequestOptions,
+    ) -> Result<http::Response<Vec<u8>>, Self::Error> {
+        let mut request = match method.to_uppercase().as_str() {
+            "GET" => self.session.get(url),
+            "POST" => self.session.post(url),
+            "PUT" => self.session.put(url),
+            "DELETE" => self.session.delete(url),
+            "PATCH" => self.session.patch(url),
+            "HEAD" => self.session.head(url),
+            _ => {
+                // Sessions only expose the standard methods, so other methods
+                // are sent without the session's settings
+                attohttpc::RequestBuilder::new(
+                    attohttpc::Method::from_bytes(method.as_bytes())
+                        .unwrap_or(attohttpc::Method::GET),
+                    url,
+                )
+            }
+        };
+
+        // Add headers (these override the session's default headers)
+        request.headers_mut().extend(headers);
+
+        // Add timeouts if present
+        let timeouts = options.timeouts;
+        if let Some(connect) = timeouts.connect {
+            request = request.connect_timeout(connect);
+        }
+        if let Some(read) = timeouts.read {
+            request = request.read_timeout(read);
+        }
+        if let Some(total) = timeouts.total {
+            request = request.timeout(total);
+        }
+
+        // Send the request with or without body
+        let response = match body {
+            Some(body_data) => request.bytes(body_data).send()?,
+            None => request.send()?,
+        };
+
+        let (status, response_headers, reader) = response.split();
+        let mut result = http::Response::new(super::read_body(reader, &options)?);
+        *result.status_mut() = status;
+        *result.headers_mut() = response_headers;
+        Ok(result)
+    }
+}
@@ -0,0 +1,290 @@
+//! isahc HTTP client implementation (blocking and async).
+
+use crate::{AsyncHttpClient, HttpClient, HttpProtocol, IpFamily, RequestOptions, Timeouts};
+use http::HeaderMap;
+use std::net::SocketAddr;
+use std::time::Duration;
+use isahc::config::Configurable;
+use futures_lite::AsyncReadExt;
+use isahc::{AsyncBody, Body};
+
+/// isahc client wrapper that implements both HttpClient and AsyncHttpClient
+///
+/// isahc is built on libcurl and offers blocking and async requests (with
+/// HTTP/2 support) from a single client, so one backend covers both kinds of
+/// generated clients. It can be created with default settings or with a
+/// custom isahc client for advanced configuration.
+///
+/// Connect and total timeouts map directly onto isahc's. libcurl has no read
+/// timeout, so the read timeout aborts a transfer that stays below one byte
+/// per second for that long. A connect timeout set with
+/// [`with_connect_timeout`](Self::with_connect_timeout) applies to requests
+/// that don't set their own.
+///
+/// On Unix, `unix://` URLs (see `UnixSocketUrl`) are sent to the socket they
+/// name, so a base URL like `unix://%2Fvar%2Frun%2Fdocker.sock/v1.43` talks to
+/// a local daemon.
+///
+/// Hosts pinned with [`resolve`](IsahcClient::resolve) connect to their
+/// address through libcurl's `CONNECT_TO`, which leaves the URL, `Host`
+/// header and TLS server name unchanged.
+///
+/// # Examples
+///
+/// Basic usage:
+/// ```no_run
+/// use derive_rest_api::IsahcClient;
+///
+/// let client = IsahcClient::new().unwrap();
+/// ```
+///
+/// With custom configuration:
+/// ```no_run
+/// use derive_rest_api::IsahcClient;
+/// use isahc::config::Configurable;
+///
+/// let isahc_client = isahc::HttpClient::builder()
+///     .timeout(std::time::Duration::from_secs(30))
+///     .build()
+///     .unwrap();
+///
+/// let client = IsahcClient::with_client(isahc_client);
+/// ```
+#[derive(Clone)]
+pub struct IsahcClient {
+    client: isahc::HttpClient,
+    /// Addresses pinned with `resolve`, by host name
+    resolve: Vec<(String, SocketAddr)>,
+    ip_family: IpFamily,
+    connect_timeout: Option<Duration>,
+}
+
+impl IsahcClient {
+    /// Creates a new isahc client wrapper with default settings
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the isahc client cannot be created
+    pub fn new() -> Result<Self, isahc::Error> {
+        Ok(Self::with_client(isahc::HttpClient::new()?))
+    }
+
+    /// Creates a new isahc client wrapper with a custom client
+    ///
+    /// This allows you to configure the isahc client with custom settings
+    /// such as timeouts, HTTP version preferences, etc.
+    pub fn with_client(client: isahc::HttpClient) -> Self {
+        Self {
+            client,
+            resolve: Vec::new(),
+            ip_family: IpFamily::Any,
+            connect_timeout: None,
+        }
+    }
+
+    /// Creates a new isahc client wrapper speaking the given HTTP version
+    ///
+    /// All three [`HttpProtocol`]s are supported, for blocking and async
+    /// requests alike. `Http2PriorKnowledge` talks h2c to `http://` URLs.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the isahc client cannot be created
+    pub fn with_protocol(protocol: HttpProtocol) -> Result<Self, isahc::Error> {
+        let negotiation = match protocol {
+            HttpProtocol::Negotiate => isahc::config::VersionNegotiation::latest_compatible(),
+            HttpProtocol::Http1Only => isahc::config::VersionNegotiation::http11(),
+            HttpProtocol::Http2PriorKnowledge => isahc::config::VersionNegotiation::http2(),
+        };
+        Ok(Self::with_client(
+            isahc::HttpClient::builder().version_negotiation(negotiation).build()?,
+        ))
+    }
+
+    /// Connects to `addr` for requests to `host`, instead of looking it up
+    ///
+    /// The URL keeps its host name, so TLS still verifies the certificate for
+    /// it and sends it as the SNI server name. A port in the URL takes
+    /// precedence over `addr`'s; give `addr` port `0` to use the scheme's
+    /// default port. Call it again to pin more hosts.
+    pub fn resolve(mut self, host: &str, addr: SocketAddr) -> Self {
+        self.resolve.push((host.to_string(), addr));
+        self
+    }
+
+    /// Restricts connections to one IP address family
+    ///
+    /// With the default, `IpFamily::Any`, libcurl races IPv6 and IPv4
+    /// connection attempts.
+    pub fn with_ip_family(mut self, family: IpFamily) -> Self {
+        self.ip_family = family;
+        self
+    }
+
+    /// Sets the timeout for establishing a connection, applied to requests
+    /// that don't set their own
+    pub fn with_connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Returns the address to connect to for `url` if its host is pinned
+    fn pinned_addr(&self, url: &str) -> Option<SocketAddr> {
+        if self.resolve.is_empty() {
+            return None;
+        }
+        let uri: http::Uri = url.parse().ok()?;
+        let host = uri.host()?.trim_start_matches('[').trim_end_matches(']');
+        let (_, addr) = self.resolve.iter().rev().find(|(pinned, _)| pinned.eq_ignore_ascii_case(host))?;
+        let port = uri
+            .port_u16()
+            .or((addr.port() != 0).then_some(addr.port()))
+            .unwrap_or(if uri.scheme_str() == Some("https") { 443 } else { 80 });
+        Some(SocketAddr::new(addr.ip(), port))
+    }
+
+    /// Returns the wrapped isahc client
+    pub fn inner(&self) -> &isahc::HttpClient {
+        &self.client
+    }
+
+    /// Returns the wrapped isahc client mutably, e.g. to change its settings
+    pub fn inner_mut(&mut self) -> &mut isahc::HttpClient {
+        &mut self.client
+    }
+
+    /// Consumes the wrapper, returning the wrapped isahc client
+    pub fn into_inner(self) -> isahc::HttpClient {
+        self.client
+    }
+
+    /// Builds an isahc request from the trait's request parameters
+    fn build_request<B>(
+        &self,
+        method: &str,
+        url: &str,
+        headers: &HeaderMap,
+        body: B,
+        timeouts: &Timeouts,
+    ) -> Result<isahc::Request<B>, isahc::Error> {
+        let builder = isahc::Request::builder().method(method.to_uppercase().as_str());
+
+        // Send `unix://` URLs to the socket they name as plain HTTP
+        #[cfg(unix)]
+        let mut request = match crate::UnixSocketUrl::parse(url) {
+            Some(socket_url) => builder
+                .uri(socket_url.http_url())
+                .dial(isahc::config::Dialer::unix_socket(socket_url.socket())),
+            None => builder.uri(url),
+        };
+        #[cfg(not(unix))]
+        let mut request = builder.uri(url);
+
+        request = request.ip_version(match self.ip_family {
+            IpFamily::Any => isahc::config::IpVersion::Any,
+            IpFamily::Ipv4Only => isahc::config::IpVersion::V4,
+            IpFamily::Ipv6Only => isahc::config::IpVersion::V6,
+        });
+
+        // Connect pinned hosts to their address, keeping the URL's host for TLS
+        if let Some(addr) = self.pinned_addr(url) {
+            request = request.dial(isahc::config::Dialer::ip_socket(addr));
+        }
+
+        // Add headers (isahc uses its own version of the http crate, so copy
+        // names and values over as bytes)
+        for (key, value) in headers {
+            request = request.header(key.as_str(), value.as_bytes());
+        }
+
+        // Add timeouts if present (libcurl has no read timeout, so a stalled
+        // transfer is detected as dropping below one byte per second instead)
+        if let Some(connect) = timeouts.connect.or(self.connect_timeout) {
+            request = request.connect_timeout(connect);
+        }
+        if let Some(read) = timeouts.read {
+            request = request.low_speed_timeout(1, read);
+        }
+        if let Some(total) = timeouts.total {
+            request = request.timeout(total);
+        }
+
+        Ok(request.body(body)?)
+    }
+
+    /// Converts isahc's response head into this crate's `http` types
+    fn convert_response(parts: isahc::http::response::Parts, body: Vec<u8>) -> http::Response<Vec<u8>> {
+        let mut response = http::Response::new(body);
+        // isahc uses its own version of the http crate, so copy the status
+        // code and headers over as plain values
+        *response.status_mut() = http::StatusCode::from_u16(parts.status.as_u16())
+            .unwrap_or(http::StatusCode::INTERNAL_SERVER_ERROR);
+        for (key, value) in &parts.headers {
+            if let (Ok(name), Ok(value)) = (
+                http::HeaderName::from_bytes(key.as_str().as_bytes()),
+                http::HeaderValue::from_bytes(value.as_bytes()),
+            ) {
+                response.headers_mut().append(name, value);
+            }
+        }
+        response
+    }
+}
+
+impl From<isahc::HttpClient> for IsahcClient {
+    fn from(client: isahc::HttpClient) -> Self {
+        IsahcClient::with_client(client)
+    }
+}
+
+impl Default for IsahcClient {
+    fn default() -> Self {
+        Self::new().expect("failed to create isahc client")
+    }
+}
+
+impl HttpClient for IsahcClient {
+    type Error = isahc::Error;
+
+    fn send(
+        &self,
+        method: &str,
+        url: &str,
+        headers: HeaderMap,
+        body: Option<Vec<u8>>,
+        options: RequestOptions,
+    ) -> Result<http::Response<Vec<u8>>, Self::Error> {
+        let body = body.map(Body::from).unwrap_or_else(Body::empty);
+        let request = self.build_request(method, url, &headers, body, &options.timeouts)?;
+
+        let response = self.client.send(request)?;
+        let (parts, body) = response.into_parts();
+        Ok(Self::convert_response(parts, super::read_body(body, &options)?))
+    }
+}
+
+impl AsyncHttpClient for IsahcClient {
+    type Error = isahc::Error;
+
+    async fn send_async(
+        &self,
+        method: &str,
+        url: &str,
+        headers: HeaderMap,
+        body: Option<Vec<u8>>,
+        options: RequestOptions,
+    ) -> Result<http::Response<Vec<u8>>, Self::Error> {
+        let body = body.map(AsyncBody::from).unwrap_or_else(AsyncBody::empty);
+        let request = self.build_request(method, url, &headers, body, &options.timeouts)?;
+
+        let response = self.client.send_async(request).await?;
+        let (parts, body) = response.into_parts();
+
+        // Read the body, stopping after the request's read limit
+        let mut bytes = Vec::new();
+        body.take(options.read_limit().unwrap_or(u64::MAX))
+            .read_to_end(&mut bytes)
+            .await?;
+        Ok(Self::convert_response(parts, bytes))
+    }
+}
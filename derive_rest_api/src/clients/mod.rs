@@ -4,6 +4,18 @@
 #[derive(Clone, Debug, Default)]
 pub struct UnimplementedClient;
 
+/// Reads a response body, stopping after the request's read limit
+#[cfg(any(feature = "ureq-blocking", feature = "isahc", feature = "attohttpc-blocking"))]
+fn read_body(reader: impl std::io::Read, options: &crate::RequestOptions) -> std::io::Result<Vec<u8>> {
+    use std::io::Read;
+
+    let mut body = Vec::new();
+    reader
+        .take(options.read_limit().unwrap_or(u64::MAX))
+        .read_to_end(&mut body)?;
+    Ok(body)
+}
+
 #[cfg(feature = "reqwest-blocking")]
 mod reqwest_blocking;
 
@@ -13,11 +25,38 @@ mod reqwest_async;
 #[cfg(feature = "ureq-blocking")]
 mod ureq_blocking;
 
+#[cfg(feature = "isahc")]
+mod isahc_client;
+
+#[cfg(feature = "attohttpc-blocking")]
+mod attohttpc_blocking;
+
+#[cfg(feature = "surf")]
+mod surf_client;
+
+#[cfg(all(feature = "wasm-fetch", target_arch = "wasm32"))]
+mod wasm_fetch;
+
 #[cfg(feature = "reqwest-blocking")]
-pub use reqwest_blocking::ReqwestBlockingClient;
+pub use reqwest_blocking::{ReqwestBlockingClient, ReqwestBlockingClientBuilder};
 
 #[cfg(feature = "reqwest-async")]
 pub use reqwest_async::ReqwestAsyncClient;
 
+#[cfg(all(feature = "reqwest-async", not(target_arch = "wasm32")))]
+pub use reqwest_async::ReqwestAsyncClientBuilder;
+
 #[cfg(feature = "ureq-blocking")]
 pub use ureq_blocking::UreqBlockingClient;
+
+#[cfg(feature = "isahc")]
+pub use isahc_client::IsahcClient;
+
+#[cfg(feature = "attohttpc-blocking")]
+pub use attohttpc_blocking::AttohttpcClient;
+
+#[cfg(feature = "surf")]
+pub use surf_client::{SurfClient, SurfClientError};
+
+#[cfg(all(feature = "wasm-fetch", target_arch = "wasm32"))]
+pub use wasm_fetch::{FetchBodyStream, WasmFetchClient, WasmFetchError};
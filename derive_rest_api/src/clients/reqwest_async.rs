@@ -1,7 +1,7 @@
 //! Async reqwest HTTP client implementation.
 
-use crate::AsyncHttpClient;
-use std::collections::HashMap;
+use crate::{AsyncHttpClient, RequestOptions};
+use http::HeaderMap;
 
 /// Async reqwest client wrapper that implements AsyncHttpClient
 ///
@@ -9,6 +9,15 @@ use std::collections::HashMap;
 /// async API. It can be created with default settings or with a custom
 /// reqwest client for advanced configuration.
 ///
+/// Only the total timeout is applied per request. reqwest sets connect and
+/// read timeouts on the client, so configure those on the client you pass in.
+/// On `wasm32`, reqwest enforces the total timeout with an `AbortController`,
+/// which also aborts reading the body.
+///
+/// On Unix, `unix://` URLs (see `UnixSocketUrl`) are sent to the socket they
+/// name. Each socket gets its own reqwest client with default settings, kept
+/// for reuse by later requests.
+///
 /// # Examples
 ///
 /// Basic usage:
@@ -36,6 +45,9 @@ use std::collections::HashMap;
 #[derive(Clone)]
 pub struct ReqwestAsyncClient {
     client: reqwest::Client,
+    /// Clients for the sockets named by `unix://` URLs, created on first use
+    #[cfg(unix)]
+    unix_clients: std::sync::Arc<std::sync::Mutex<std::collections::HashMap<std::path::PathBuf, reqwest::Client>>>,
 }
 
 impl ReqwestAsyncClient {
@@ -45,9 +57,7 @@ impl ReqwestAsyncClient {
     ///
     /// Returns an error if the reqwest client cannot be created
     pub fn new() -> Result<Self, reqwest::Error> {
-        Ok(Self {
-            client: reqwest::Client::new(),
-        })
+        Ok(Self::with_client(reqwest::Client::new()))
     }
 
     /// Creates a new async reqwest client wrapper with a custom client
@@ -55,7 +65,193 @@ impl ReqwestAsyncClient {
     /// This allows you to configure the reqwest client with custom settings
     /// such as timeouts, user agents, etc.
     pub fn with_client(client: reqwest::Client) -> Self {
-        Self { client }
+        Self {
+            client,
+            #[cfg(unix)]
+            unix_clients: Default::default(),
+        }
+    }
+
+    /// Returns a builder for a wrapper with tuned connection settings
+    ///
+    /// Not available on `wasm32`, where the browser manages connections.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn builder() -> ReqwestAsyncClientBuilder {
+        ReqwestAsyncClientBuilder { builder: reqwest::Client::builder() }
+    }
+
+    /// Creates a new async reqwest client wrapper with tuned connection pooling
+    ///
+    /// Shorthand for `ReqwestAsyncClient::builder().pool(pool).build()`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the reqwest client cannot be created
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn with_pool(pool: crate::PoolOptions) -> Result<Self, reqwest::Error> {
+        Self::builder().pool(pool).build()
+    }
+
+    /// Creates a new async reqwest client wrapper speaking the given HTTP version
+    ///
+    /// Shorthand for `ReqwestAsyncClient::builder().protocol(protocol).build()`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the reqwest client cannot be created
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn with_protocol(protocol: crate::HttpProtocol) -> Result<Self, reqwest::Error> {
+        Self::builder().protocol(protocol).build()
+    }
+
+    /// Returns the client to send a request to `url` with, and the URL to send
+    ///
+    /// `unix://` URLs are rewritten to `http://localhost` and sent over a client
+    /// for their socket.
+    fn route<'a>(&self, url: &'a str) -> Result<(reqwest::Client, std::borrow::Cow<'a, str>), reqwest::Error> {
+        #[cfg(unix)]
+        if let Some(socket_url) = crate::UnixSocketUrl::parse(url) {
+            let mut clients = self.unix_clients.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+            let client = match clients.get(socket_url.socket()) {
+                Some(client) => client.clone(),
+                None => {
+                    let client = reqwest::Client::builder().unix_socket(socket_url.socket()).build()?;
+                    clients.insert(socket_url.socket().to_path_buf(), client.clone());
+                    client
+                }
+            };
+            return Ok((client, socket_url.http_url().into()));
+        }
+        Ok((self.client.clone(), url.into()))
+    }
+
+    /// Returns the wrapped reqwest client
+    pub fn inner(&self) -> &reqwest::Client {
+        &self.client
+    }
+
+    /// Returns the wrapped reqwest client mutably, e.g. to change its settings
+    pub fn inner_mut(&mut self) -> &mut reqwest::Client {
+        &mut self.client
+    }
+
+    /// Consumes the wrapper, returning the wrapped reqwest client
+    pub fn into_inner(self) -> reqwest::Client {
+        self.client
+    }
+}
+
+/// Builder for a [`ReqwestAsyncClient`] with tuned connection settings
+///
+/// Created with [`ReqwestAsyncClient::builder`]. Settings that aren't set keep
+/// reqwest's defaults; for anything not covered here, build a
+/// `reqwest::Client` yourself and use [`ReqwestAsyncClient::with_client`].
+///
+/// # Example
+///
+/// ```no_run
+/// use derive_rest_api::{HttpProtocol, PoolOptions, ReqwestAsyncClient};
+///
+/// let client = ReqwestAsyncClient::builder()
+///     .pool(PoolOptions::new().with_max_idle_per_host(64))
+///     .protocol(HttpProtocol::Http1Only)
+///     .resolve("api.example.com", "10.0.0.7:443".parse().unwrap())
+///     .build()
+///     .unwrap();
+/// ```
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug)]
+pub struct ReqwestAsyncClientBuilder {
+    builder: reqwest::ClientBuilder,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl ReqwestAsyncClientBuilder {
+    /// Applies every setting in `pool`
+    pub fn pool(mut self, pool: crate::PoolOptions) -> Self {
+        self.builder = self
+            .builder
+            .http2_keep_alive_interval(pool.http2_keep_alive_interval)
+            .http2_keep_alive_while_idle(pool.http2_keep_alive_while_idle);
+        if let Some(max) = pool.max_idle_per_host {
+            self.builder = self.builder.pool_max_idle_per_host(max);
+        }
+        if let Some(timeout) = pool.idle_timeout {
+            self.builder = self.builder.pool_idle_timeout(timeout);
+        }
+        if let Some(timeout) = pool.http2_keep_alive_timeout {
+            self.builder = self.builder.http2_keep_alive_timeout(timeout);
+        }
+        self
+    }
+
+    /// Sets the HTTP version to speak
+    ///
+    /// All three [`HttpProtocol`](crate::HttpProtocol)s are supported.
+    /// `Http2PriorKnowledge` talks h2c to `http://` URLs.
+    pub fn protocol(mut self, protocol: crate::HttpProtocol) -> Self {
+        self.builder = match protocol {
+            crate::HttpProtocol::Negotiate => self.builder,
+            crate::HttpProtocol::Http1Only => self.builder.http1_only(),
+            crate::HttpProtocol::Http2PriorKnowledge => self.builder.http2_prior_knowledge(),
+        };
+        self
+    }
+
+    /// Restricts connections to one IP address family
+    ///
+    /// Works by binding outgoing connections to the family's unspecified
+    /// address, so reqwest skips addresses of the other family.
+    pub fn ip_family(mut self, family: crate::IpFamily) -> Self {
+        let local_address: Option<std::net::IpAddr> = match family {
+            crate::IpFamily::Any => None,
+            crate::IpFamily::Ipv4Only => Some(std::net::Ipv4Addr::UNSPECIFIED.into()),
+            crate::IpFamily::Ipv6Only => Some(std::net::Ipv6Addr::UNSPECIFIED.into()),
+        };
+        self.builder = self.builder.local_address(local_address);
+        self
+    }
+
+    /// Sets the timeout for establishing each connection, including TLS
+    ///
+    /// reqwest only supports this on the client, so it applies to every
+    /// request, and a connect timeout set on a single request is ignored.
+    pub fn connect_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.builder = self.builder.connect_timeout(timeout);
+        self
+    }
+
+    /// Refuses to negotiate TLS versions older than `version`
+    ///
+    /// This is the client-side counterpart of
+    /// `RequestModifier::min_tls_version()`, which only checks that requests
+    /// use HTTPS.
+    pub fn min_tls_version(mut self, version: crate::TlsVersion) -> Self {
+        self.builder = self.builder.min_tls_version(match version {
+            crate::TlsVersion::Tls1_2 => reqwest::tls::Version::TLS_1_2,
+            crate::TlsVersion::Tls1_3 => reqwest::tls::Version::TLS_1_3,
+        });
+        self
+    }
+
+    /// Connects to `addr` for requests to `host`, instead of looking it up
+    ///
+    /// The URL keeps its host name, so TLS still verifies the certificate for
+    /// it and sends it as the SNI server name. A port in the URL takes
+    /// precedence over `addr`'s; give `addr` port `0` to use the scheme's
+    /// default port. Call it again to pin more hosts.
+    pub fn resolve(mut self, host: &str, addr: std::net::SocketAddr) -> Self {
+        self.builder = self.builder.resolve(host, addr);
+        self
+    }
+
+    /// Builds the wrapper
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the reqwest client cannot be created
+    pub fn build(self) -> Result<ReqwestAsyncClient, reqwest::Error> {
+        Ok(ReqwestAsyncClient::with_client(self.builder.build()?))
     }
 }
 
@@ -67,9 +263,7 @@ impl From<reqwest::Client> for ReqwestAsyncClient {
 
 impl Default for ReqwestAsyncClient {
     fn default() -> Self {
-        Self {
-            client: reqwest::Client::new(),
-        }
+        Self::with_client(reqwest::Client::new())
     }
 }
 
@@ -80,20 +274,22 @@ impl AsyncHttpClient for ReqwestAsyncClient {
         &self,
         method: &str,
         url: &str,
-        headers: HashMap<String, String>,
+        headers: HeaderMap,
         body: Option<Vec<u8>>,
-        timeout: Option<std::time::Duration>,
-    ) -> Result<Vec<u8>, Self::Error> {
+        options: RequestOptions,
+    ) -> Result<http::Response<Vec<u8>>, Self::Error> {
+        let (client, url) = self.route(url)?;
+        let url = &*url;
         let mut request = match method.to_uppercase().as_str() {
-            "GET" => self.client.get(url),
-            "POST" => self.client.post(url),
-            "PUT" => self.client.put(url),
-            "DELETE" => self.client.delete(url),
-            "PATCH" => self.client.patch(url),
-            "HEAD" => self.client.head(url),
+            "GET" => client.get(url),
+            "POST" => client.post(url),
+            "PUT" => client.put(url),
+            "DELETE" => client.delete(url),
+            "PATCH" => client.patch(url),
+            "HEAD" => client.head(url),
             _ => {
                 // For other methods, use the generic request method
-                self.client.request(
+                client.request(
                     reqwest::Method::from_bytes(method.as_bytes())
                         .unwrap_or(reqwest::Method::GET),
                     url
@@ -102,22 +298,51 @@ impl AsyncHttpClient for ReqwestAsyncClient {
         };
 
         // Add headers
-        for (key, value) in headers {
-            request = request.header(key, value);
-        }
+        request = request.headers(headers);
 
         // Add body if present
         if let Some(body_data) = body {
             request = request.body(body_data);
         }
 
-        // Add timeout if present
-        if let Some(timeout_duration) = timeout {
+        // Add total timeout if present (reqwest only supports connect and read
+        // timeouts on the client itself)
+        if let Some(timeout_duration) = options.timeouts.total {
             request = request.timeout(timeout_duration);
         }
 
-        let response = request.send().await?;
-        let bytes = response.bytes().await?;
-        Ok(bytes.to_vec())
+        let mut response = request.send().await?;
+        let status = response.status();
+        let response_headers = std::mem::take(response.headers_mut());
+        let bytes = read_body(response, options.read_limit().unwrap_or(u64::MAX)).await?;
+
+        let mut result = http::Response::new(bytes);
+        *result.status_mut() = status;
+        *result.headers_mut() = response_headers;
+        Ok(result)
+    }
+}
+
+/// Reads the body chunk by chunk, stopping after `limit` bytes
+#[cfg(not(target_arch = "wasm32"))]
+async fn read_body(mut response: reqwest::Response, limit: u64) -> Result<Vec<u8>, reqwest::Error> {
+    let mut bytes = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        let remaining = limit.saturating_sub(bytes.len() as u64);
+        let take = chunk.len().min(usize::try_from(remaining).unwrap_or(usize::MAX));
+        bytes.extend_from_slice(&chunk[..take]);
+        if bytes.len() as u64 >= limit {
+            break;
+        }
     }
+    Ok(bytes)
+}
+
+/// Reads the body, keeping the first `limit` bytes (reqwest can't read it
+/// chunk by chunk on `wasm32`)
+#[cfg(target_arch = "wasm32")]
+async fn read_body(response: reqwest::Response, limit: u64) -> Result<Vec<u8>, reqwest::Error> {
+    let mut bytes = response.bytes().await?.to_vec();
+    bytes.truncate(usize::try_from(limit).unwrap_or(usize::MAX));
+    Ok(bytes)
 }
@@ -1,7 +1,7 @@
 //! Blocking reqwest HTTP client implementation.
 
-use crate::HttpClient;
-use std::collections::HashMap;
+use crate::{HttpClient, PoolOptions, RequestOptions};
+use http::HeaderMap;
 
 /// Blocking reqwest client wrapper that implements HttpClient
 ///
@@ -9,6 +9,13 @@ use std::collections::HashMap;
 /// blocking API. It can be created with default settings or with a custom
 /// reqwest blocking client for advanced configuration.
 ///
+/// Only the total timeout is applied per request. reqwest sets connect and
+/// read timeouts on the client, so configure those on the client you pass in.
+///
+/// On Unix, `unix://` URLs (see `UnixSocketUrl`) are sent to the socket they
+/// name. Each socket gets its own reqwest client with default settings, kept
+/// for reuse by later requests.
+///
 /// # Examples
 ///
 /// Basic usage:
@@ -32,6 +39,9 @@ use std::collections::HashMap;
 #[derive(Clone)]
 pub struct ReqwestBlockingClient {
     client: reqwest::blocking::Client,
+    /// Clients for the sockets named by `unix://` URLs, created on first use
+    #[cfg(unix)]
+    unix_clients: std::sync::Arc<std::sync::Mutex<std::collections::HashMap<std::path::PathBuf, reqwest::blocking::Client>>>,
 }
 
 impl ReqwestBlockingClient {
@@ -41,9 +51,7 @@ impl ReqwestBlockingClient {
     ///
     /// Returns an error if the reqwest client cannot be created
     pub fn new() -> Result<Self, reqwest::Error> {
-        Ok(Self {
-            client: reqwest::blocking::Client::new(),
-        })
+        Ok(Self::with_client(reqwest::blocking::Client::new()))
     }
 
     /// Creates a new blocking reqwest client wrapper with a custom client
@@ -51,7 +59,158 @@ impl ReqwestBlockingClient {
     /// This allows you to configure the reqwest client with custom settings
     /// such as timeouts, user agents, etc.
     pub fn with_client(client: reqwest::blocking::Client) -> Self {
-        Self { client }
+        Self {
+            client,
+            #[cfg(unix)]
+            unix_clients: Default::default(),
+        }
+    }
+
+    /// Returns a builder for a wrapper with tuned connection settings
+    pub fn builder() -> ReqwestBlockingClientBuilder {
+        ReqwestBlockingClientBuilder { builder: reqwest::blocking::Client::builder() }
+    }
+
+    /// Creates a new blocking reqwest client wrapper with tuned connection pooling
+    ///
+    /// Shorthand for `ReqwestBlockingClient::builder().pool(pool).build()`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the reqwest client cannot be created
+    pub fn with_pool(pool: PoolOptions) -> Result<Self, reqwest::Error> {
+        Self::builder().pool(pool).build()
+    }
+
+    /// Returns the client to send a request to `url` with, and the URL to send
+    ///
+    /// `unix://` URLs are rewritten to `http://localhost` and sent over a client
+    /// for their socket.
+    fn route<'a>(&self, url: &'a str) -> Result<(reqwest::blocking::Client, std::borrow::Cow<'a, str>), reqwest::Error> {
+        #[cfg(unix)]
+        if let Some(socket_url) = crate::UnixSocketUrl::parse(url) {
+            let mut clients = self.unix_clients.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+            let client = match clients.get(socket_url.socket()) {
+                Some(client) => client.clone(),
+                None => {
+                    let client = reqwest::blocking::Client::builder().unix_socket(socket_url.socket()).build()?;
+                    clients.insert(socket_url.socket().to_path_buf(), client.clone());
+                    client
+                }
+            };
+            return Ok((client, socket_url.http_url().into()));
+        }
+        Ok((self.client.clone(), url.into()))
+    }
+
+    /// Returns the wrapped reqwest client
+    pub fn inner(&self) -> &reqwest::blocking::Client {
+        &self.client
+    }
+
+    /// Returns the wrapped reqwest client mutably, e.g. to change its settings
+    pub fn inner_mut(&mut self) -> &mut reqwest::blocking::Client {
+        &mut self.client
+    }
+
+    /// Consumes the wrapper, returning the wrapped reqwest client
+    pub fn into_inner(self) -> reqwest::blocking::Client {
+        self.client
+    }
+}
+
+/// Builder for a [`ReqwestBlockingClient`] with tuned connection settings
+///
+/// Created with [`ReqwestBlockingClient::builder`]. Settings that aren't set
+/// keep reqwest's defaults; for anything not covered here, build a
+/// `reqwest::blocking::Client` yourself and use
+/// [`ReqwestBlockingClient::with_client`].
+///
+/// # Example
+///
+/// ```no_run
+/// use derive_rest_api::{PoolOptions, ReqwestBlockingClient};
+///
+/// let client = ReqwestBlockingClient::builder()
+///     .pool(PoolOptions::new().with_max_idle_per_host(16))
+///     .resolve("api.example.com", "10.0.0.7:443".parse().unwrap())
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug)]
+pub struct ReqwestBlockingClientBuilder {
+    builder: reqwest::blocking::ClientBuilder,
+}
+
+impl ReqwestBlockingClientBuilder {
+    /// Applies the idle connection limit and idle timeout from `pool`
+    ///
+    /// reqwest's blocking client has no HTTP/2 keep-alive settings, so those
+    /// are ignored.
+    pub fn pool(mut self, pool: PoolOptions) -> Self {
+        if let Some(max) = pool.max_idle_per_host {
+            self.builder = self.builder.pool_max_idle_per_host(max);
+        }
+        if let Some(timeout) = pool.idle_timeout {
+            self.builder = self.builder.pool_idle_timeout(timeout);
+        }
+        self
+    }
+
+    /// Restricts connections to one IP address family
+    ///
+    /// Works by binding outgoing connections to the family's unspecified
+    /// address, so reqwest skips addresses of the other family.
+    pub fn ip_family(mut self, family: crate::IpFamily) -> Self {
+        let local_address: Option<std::net::IpAddr> = match family {
+            crate::IpFamily::Any => None,
+            crate::IpFamily::Ipv4Only => Some(std::net::Ipv4Addr::UNSPECIFIED.into()),
+            crate::IpFamily::Ipv6Only => Some(std::net::Ipv6Addr::UNSPECIFIED.into()),
+        };
+        self.builder = self.builder.local_address(local_address);
+        self
+    }
+
+    /// Sets the timeout for establishing each connection, including TLS
+    ///
+    /// reqwest only supports this on the client, so it applies to every
+    /// request, and a connect timeout set on a single request is ignored.
+    pub fn connect_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.builder = self.builder.connect_timeout(timeout);
+        self
+    }
+
+    /// Refuses to negotiate TLS versions older than `version`
+    ///
+    /// This is the client-side counterpart of
+    /// `RequestModifier::min_tls_version()`, which only checks that requests
+    /// use HTTPS.
+    pub fn min_tls_version(mut self, version: crate::TlsVersion) -> Self {
+        self.builder = self.builder.min_tls_version(match version {
+            crate::TlsVersion::Tls1_2 => reqwest::tls::Version::TLS_1_2,
+            crate::TlsVersion::Tls1_3 => reqwest::tls::Version::TLS_1_3,
+        });
+        self
+    }
+
+    /// Connects to `addr` for requests to `host`, instead of looking it up
+    ///
+    /// The URL keeps its host name, so TLS still verifies the certificate for
+    /// it and sends it as the SNI server name. A port in the URL takes
+    /// precedence over `addr`'s; give `addr` port `0` to use the scheme's
+    /// default port. Call it again to pin more hosts.
+    pub fn resolve(mut self, host: &str, addr: std::net::SocketAddr) -> Self {
+        self.builder = self.builder.resolve(host, addr);
+        self
+    }
+
+    /// Builds the wrapper
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the reqwest client cannot be created
+    pub fn build(self) -> Result<ReqwestBlockingClient, reqwest::Error> {
+        Ok(ReqwestBlockingClient::with_client(self.builder.build()?))
     }
 }
 
@@ -63,9 +222,7 @@ impl From<reqwest::blocking::Client> for ReqwestBlockingClient {
 
 impl Default for ReqwestBlockingClient {
     fn default() -> Self {
-        Self {
-            client: reqwest::blocking::Client::new(),
-        }
+        Self::with_client(reqwest::blocking::Client::new())
     }
 }
 
@@ -76,20 +233,22 @@ impl HttpClient for ReqwestBlockingClient {
         &self,
         method: &str,
         url: &str,
-        headers: HashMap<String, String>,
+        headers: HeaderMap,
         body: Option<Vec<u8>>,
-        timeout: Option<std::time::Duration>,
-    ) -> Result<Vec<u8>, Self::Error> {
+        options: RequestOptions,
+    ) -> Result<http::Response<Vec<u8>>, Self::Error> {
+        let (client, url) = self.route(url)?;
+        let url = &*url;
         let mut request = match method.to_uppercase().as_str() {
-            "GET" => self.client.get(url),
-            "POST" => self.client.post(url),
-            "PUT" => self.client.put(url),
-            "DELETE" => self.client.delete(url),
-            "PATCH" => self.client.patch(url),
-            "HEAD" => self.client.head(url),
+            "GET" => client.get(url),
+            "POST" => client.post(url),
+            "PUT" => client.put(url),
+            "DELETE" => client.delete(url),
+            "PATCH" => client.patch(url),
+            "HEAD" => client.head(url),
             _ => {
                 // For other methods, use the generic request method
-                self.client.request(
+                client.request(
                     reqwest::Method::from_bytes(method.as_bytes())
                         .unwrap_or(reqwest::Method::GET),
                     url
@@ -98,22 +257,66 @@ impl HttpClient for ReqwestBlockingClient {
         };
 
         // Add headers
-        for (key, value) in headers {
-            request = request.header(key, value);
-        }
+        request = request.headers(headers);
 
         // Add body if present
         if let Some(body_data) = body {
             request = request.body(body_data);
         }
 
-        // Add timeout if present
-        if let Some(timeout_duration) = timeout {
+        // Add total timeout if present (reqwest only supports connect and read
+        // timeouts on the client itself)
+        if let Some(timeout_duration) = options.timeouts.total {
             request = request.timeout(timeout_duration);
         }
 
-        let response = request.send()?;
-        let bytes = response.bytes()?;
-        Ok(bytes.to_vec())
+        let mut response = request.send()?;
+        let status = response.status();
+        let response_headers = std::mem::take(response.headers_mut());
+
+        // Read the body, stopping after the request's read limit
+        let mut body = LimitedBuffer {
+            bytes: Vec::new(),
+            limit: options.read_limit().unwrap_or(u64::MAX),
+        };
+        match response.copy_to(&mut body) {
+            Ok(_) => {}
+            // The buffer refuses more data once full, which ends the copy early
+            Err(_) if body.is_full() => {}
+            Err(error) => return Err(error),
+        }
+
+        let mut result = http::Response::new(body.bytes);
+        *result.status_mut() = status;
+        *result.headers_mut() = response_headers;
+        Ok(result)
+    }
+}
+
+/// Buffer that accepts at most `limit` bytes and refuses writes after that
+struct LimitedBuffer {
+    bytes: Vec<u8>,
+    limit: u64,
+}
+
+impl LimitedBuffer {
+    fn is_full(&self) -> bool {
+        self.bytes.len() as u64 >= self.limit
+    }
+}
+
+impl std::io::Write for LimitedBuffer {
+    fn write(&mut self, data: &[u8]) -> std::io::Result<usize> {
+        let remaining = self.limit.saturating_sub(self.bytes.len() as u64);
+        if remaining == 0 {
+            return Err(std::io::ErrorKind::WriteZero.into());
+        }
+        let take = data.len().min(usize::try_from(remaining).unwrap_or(usize::MAX));
+        self.bytes.extend_from_slice(&data[..take]);
+        Ok(take)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
     }
 }
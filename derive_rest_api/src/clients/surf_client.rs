@@ -0,0 +1,195 @@
+//! surf async HTTP client implementation.
+
+use crate::{AsyncHttpClient, RequestOptions};
+use async_std::io::ReadExt;
+use http::HeaderMap;
+
+/// Errors returned by [`SurfClient`]
+#[derive(Debug, thiserror::Error)]
+pub enum SurfClientError {
+    /// The request could not be built or sent, or the response body could not be read
+    #[error("surf error: {0}")]
+    Request(surf::Error),
+
+    /// The request did not complete within its timeout
+    #[error("request timed out")]
+    Timeout,
+}
+
+impl From<surf::Error> for SurfClientError {
+    fn from(error: surf::Error) -> Self {
+        SurfClientError::Request(error)
+    }
+}
+
+/// surf client wrapper that implements AsyncHttpClient
+///
+/// This wrapper provides an async HTTP client implementation using surf,
+/// which runs on async-std rather than tokio. Request timeouts use
+/// async-std's timers, which are driven by their own reactor, so the
+/// generated async clients can be awaited from async-std, smol, or any other
+/// executor. It can be created with default settings or with a custom surf
+/// client for advanced configuration.
+///
+/// Only the total timeout is applied per request; surf has no separate
+/// connect or read timeouts, nor a setting for the IP address family.
+///
+/// surf's HTTP/1 backend always speaks HTTP/1.1, so there is no
+/// `with_protocol` constructor; use `IsahcClient` or
+/// `ReqwestAsyncClient` for HTTP/2.
+///
+/// # Examples
+///
+/// Basic usage:
+/// ```no_run
+/// use derive_rest_api::SurfClient;
+///
+/// let client = SurfClient::new();
+/// ```
+///
+/// With custom configuration:
+/// ```no_run
+/// use derive_rest_api::SurfClient;
+///
+/// let surf_client: surf::Client = surf::Config::new()
+///     .set_timeout(Some(std::time::Duration::from_secs(30)))
+///     .try_into()
+///     .unwrap();
+///
+/// let client = SurfClient::with_client(surf_client);
+/// ```
+#[derive(Clone, Debug)]
+pub struct SurfClient {
+    client: surf::Client,
+}
+
+impl SurfClient {
+    /// Creates a new surf client wrapper with default settings
+    pub fn new() -> Self {
+        Self {
+            client: surf::Client::new(),
+        }
+    }
+
+    /// Creates a new surf client wrapper with a custom client
+    ///
+    /// This allows you to configure the surf client with custom settings
+    /// such as timeouts, middleware, base URLs, etc.
+    pub fn with_client(client: surf::Client) -> Self {
+        Self { client }
+    }
+
+    /// Returns the wrapped surf client
+    pub fn inner(&self) -> &surf::Client {
+        &self.client
+    }
+
+    /// Returns the wrapped surf client mutably, e.g. to change its settings
+    pub fn inner_mut(&mut self) -> &mut surf::Client {
+        &mut self.client
+    }
+
+    /// Consumes the wrapper, returning the wrapped surf client
+    pub fn into_inner(self) -> surf::Client {
+        self.client
+    }
+
+    /// Builds a surf request from the trait's request parameters
+    fn build_request(
+        method: &str,
+        url: &str,
+        headers: &HeaderMap,
+        body: Option<Vec<u8>>,
+    ) -> Result<surf::Request, SurfClientError> {
+        let method: surf::http::Method = method
+            .to_uppercase()
+            .parse()
+            .map_err(SurfClientError::Request)?;
+        let url = surf::Url::parse(url)
+            .map_err(|e| surf::Error::new(surf::StatusCode::BadRequest, e))?;
+
+        let mut request = surf::Request::new(method, url);
+
+        // Add headers (surf uses http-types, so names and values are copied as strings)
+        for (key, value) in headers {
+            let value = value
+                .to_str()
+                .map_err(|e| surf::Error::new(surf::StatusCode::BadRequest, e))?;
+            request.append_header(key.as_str(), value);
+        }
+
+        // Add body if present
+        if let Some(body_data) = body {
+            request.set_body(body_data);
+        }
+
+        Ok(request)
+    }
+
+    /// Copies the status code and headers of a surf response into this crate's `http` types
+    fn convert_response(response: &surf::Response, body: Vec<u8>) -> http::Response<Vec<u8>> {
+        let mut result = http::Response::new(body);
+        *result.status_mut() = http::StatusCode::from_u16(response.status().into())
+            .unwrap_or(http::StatusCode::INTERNAL_SERVER_ERROR);
+        for (key, values) in response.iter() {
+            for value in values {
+                if let (Ok(name), Ok(value)) = (
+                    http::HeaderName::from_bytes(key.as_str().as_bytes()),
+                    http::HeaderValue::from_str(value.as_str()),
+                ) {
+                    result.headers_mut().append(name, value);
+                }
+            }
+        }
+        result
+    }
+}
+
+impl From<surf::Client> for SurfClient {
+    fn from(client: surf::Client) -> Self {
+        SurfClient::with_client(client)
+    }
+}
+
+impl Default for SurfClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AsyncHttpClient for SurfClient {
+    type Error = SurfClientError;
+
+    async fn send_async(
+        &self,
+        method: &str,
+        url: &str,
+        headers: HeaderMap,
+        body: Option<Vec<u8>>,
+        options: RequestOptions,
+    ) -> Result<http::Response<Vec<u8>>, Self::Error> {
+        let request = Self::build_request(method, url, &headers, body)?;
+
+        let exchange = async {
+            let mut response = self.client.send(request).await?;
+
+            // Read the body, stopping after the request's read limit
+            let mut bytes = Vec::new();
+            response
+                .take_body()
+                .take(options.read_limit().unwrap_or(u64::MAX))
+                .read_to_end(&mut bytes)
+                .await
+                .map_err(surf::Error::from)?;
+            Ok(Self::convert_response(&response, bytes))
+        };
+
+        // Add total timeout if present (covering both the request and the body read)
+        match options.timeouts.total {
+            Some(timeout_duration) => async_std::future::timeout(timeout_duration, exchange)
+                .await
+                .map_err(|_| SurfClientError::Timeout)?,
+            None => exchange.await,
+        }
+    }
+}
@@ -1,7 +1,9 @@
 //! Ureq blocking HTTP client implementation.
 
-use crate::HttpClient;
-use std::collections::HashMap;
+use crate::{HttpClient, IpFamily, RequestOptions};
+use http::HeaderMap;
+use std::time::Duration;
+use ureq::AsSendBody;
 
 /// Ureq client wrapper that implements HttpClient
 ///
@@ -9,6 +11,17 @@ use std::collections::HashMap;
 /// a lightweight, synchronous HTTP client library. It can be created with
 /// default settings or with a custom ureq agent for advanced configuration.
 ///
+/// Connections are pooled by the agent and reused across requests; the pool
+/// size is part of the agent's configuration. Every per-request timeout is
+/// supported: the total timeout bounds the whole call, the connect timeout
+/// only bounds establishing the connection, and the read timeout bounds
+/// waiting for the response headers and for the body. A connect timeout set
+/// with [`with_connect_timeout`](Self::with_connect_timeout) applies to
+/// requests that don't set their own.
+///
+/// Responses with an error status are returned like any other response
+/// rather than as `ureq::Error::StatusCode`.
+///
 /// # Examples
 ///
 /// Basic usage:
@@ -22,23 +35,26 @@ use std::collections::HashMap;
 /// ```no_run
 /// use derive_rest_api::UreqBlockingClient;
 ///
-/// let agent = ureq::AgentBuilder::new()
-///     .timeout(std::time::Duration::from_secs(30))
+/// let config = ureq::Agent::config_builder()
+///     .timeout_global(Some(std::time::Duration::from_secs(30)))
+///     .max_idle_connections(16)
+///     .max_idle_connections_per_host(4)
 ///     .build();
 ///
-/// let client = UreqBlockingClient::with_agent(agent);
+/// let client = UreqBlockingClient::with_config(config)
+///     .with_connect_timeout(std::time::Duration::from_secs(5));
 /// ```
 #[derive(Clone)]
 pub struct UreqBlockingClient {
     agent: ureq::Agent,
+    connect_timeout: Option<Duration>,
+    ip_family: IpFamily,
 }
 
 impl UreqBlockingClient {
     /// Creates a new ureq client wrapper with default settings
     pub fn new() -> Self {
-        Self {
-            agent: ureq::Agent::new(),
-        }
+        Self::with_agent(ureq::Agent::new_with_defaults())
     }
 
     /// Creates a new ureq client wrapper with a custom agent
@@ -46,7 +62,103 @@ impl UreqBlockingClient {
     /// This allows you to configure the ureq agent with custom settings
     /// such as timeouts, proxy settings, etc.
     pub fn with_agent(agent: ureq::Agent) -> Self {
-        Self { agent }
+        Self {
+            agent,
+            connect_timeout: None,
+            ip_family: IpFamily::Any,
+        }
+    }
+
+    /// Creates a new ureq client wrapper with an agent built from `config`
+    pub fn with_config(config: ureq::config::Config) -> Self {
+        Self::with_agent(config.new_agent())
+    }
+
+    /// Creates a new ureq client wrapper with the given connection pool limits
+    ///
+    /// # Arguments
+    ///
+    /// - `max_idle_connections`: Idle connections kept open across all hosts
+    /// - `max_idle_connections_per_host`: Idle connections kept open per host
+    pub fn with_pool_limits(
+        max_idle_connections: usize,
+        max_idle_connections_per_host: usize,
+    ) -> Self {
+        let config = ureq::Agent::config_builder()
+            .max_idle_connections(max_idle_connections)
+            .max_idle_connections_per_host(max_idle_connections_per_host)
+            .build();
+        Self::with_config(config)
+    }
+
+    /// Sets the timeout for establishing a connection, applied to every request
+    pub fn with_connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Restricts connections to one IP address family, applied to every request
+    ///
+    /// ureq tries a host's addresses one at a time rather than racing them,
+    /// so with `IpFamily::Any` an unreachable IPv6 address delays every new
+    /// connection until its attempt times out.
+    pub fn with_ip_family(mut self, family: IpFamily) -> Self {
+        self.ip_family = family;
+        self
+    }
+
+    /// Returns the wrapped ureq agent
+    pub fn inner(&self) -> &ureq::Agent {
+        &self.agent
+    }
+
+    /// Returns the wrapped ureq agent mutably, e.g. to change its settings
+    pub fn inner_mut(&mut self) -> &mut ureq::Agent {
+        &mut self.agent
+    }
+
+    /// Consumes the wrapper, returning the wrapped ureq agent
+    ///
+    /// The agent doesn't carry settings made with `with_connect_timeout()` or
+    /// `with_ip_family()`.
+    pub fn into_inner(self) -> ureq::Agent {
+        self.agent
+    }
+
+    /// Applies per-request timeouts and runs the request, reading the full body
+    fn run<S: AsSendBody>(
+        &self,
+        request: http::Request<S>,
+        options: RequestOptions,
+    ) -> Result<http::Response<Vec<u8>>, ureq::Error> {
+        let timeouts = options.timeouts;
+        // Return error statuses as responses, like the other clients
+        let mut config = self
+            .agent
+            .configure_request(request)
+            .http_status_as_error(false)
+            .ip_family(match self.ip_family {
+                IpFamily::Any => ureq::config::IpFamily::Any,
+                IpFamily::Ipv4Only => ureq::config::IpFamily::Ipv4Only,
+                IpFamily::Ipv6Only => ureq::config::IpFamily::Ipv6Only,
+            });
+        if let Some(total) = timeouts.total {
+            config = config.timeout_global(Some(total));
+        }
+        if let Some(connect) = timeouts.connect.or(self.connect_timeout) {
+            config = config.timeout_connect(Some(connect));
+        }
+        if let Some(read) = timeouts.read {
+            config = config
+                .timeout_recv_response(Some(read))
+                .timeout_recv_body(Some(read));
+        }
+
+        let (parts, body) = self.agent.run(config.build())?.into_parts();
+
+        // Read the raw bytes so binary responses are returned unchanged
+        let bytes = super::read_body(body.into_reader(), &options)?;
+        Ok(http::Response::from_parts(parts, bytes))
     }
 }
 
@@ -69,45 +181,23 @@ impl HttpClient for UreqBlockingClient {
         &self,
         method: &str,
         url: &str,
-        headers: HashMap<String, String>,
+        headers: HeaderMap,
         body: Option<Vec<u8>>,
-        timeout: Option<std::time::Duration>,
-    ) -> Result<Vec<u8>, Self::Error> {
-        // Create the request based on the HTTP method
-        let mut request = match method.to_uppercase().as_str() {
-            "GET" => self.agent.get(url),
-            "POST" => self.agent.post(url),
-            "PUT" => self.agent.put(url),
-            "DELETE" => self.agent.delete(url),
-            "PATCH" => self.agent.request("PATCH", url),
-            "HEAD" => self.agent.head(url),
-            _ => self.agent.request(method, url),
-        };
+        options: RequestOptions,
+    ) -> Result<http::Response<Vec<u8>>, Self::Error> {
+        let mut request = http::Request::builder()
+            .method(method.to_uppercase().as_str())
+            .uri(url);
 
         // Add headers
-        for (key, value) in headers {
-            request = request.set(&key, &value);
-        }
-
-        // Add timeout if present
-        if let Some(timeout_duration) = timeout {
-            request = request.timeout(timeout_duration);
+        if let Some(request_headers) = request.headers_mut() {
+            request_headers.extend(headers);
         }
 
         // Send the request with or without body
-        let response = if let Some(body_data) = body {
-            request.send_bytes(&body_data)?
-        } else {
-            request.call()?
-        };
-
-        // Read the response body into a string first, then convert to bytes
-        // This is the recommended way to read ureq responses
-        let body_str = response.into_string()
-            .map_err(|_e| ureq::Error::Status(500,
-                ureq::Response::new(500, "IO Error", "Failed to read response body").unwrap()
-            ))?;
-
-        Ok(body_str.into_bytes())
+        match body {
+            Some(body_data) => self.run(request.body(body_data)?, options),
+            None => self.run(request.body(())?, options),
+        }
     }
 }
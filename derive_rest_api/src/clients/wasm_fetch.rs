@@ -0,0 +1,384 @@
+//! Browser `fetch` HTTP client implementation for WebAssembly.
+
+use crate::{AsyncHttpClient, RequestOptions};
+use bytes::Bytes;
+use futures_core::Stream;
+use http::HeaderMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll, ready};
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::{JsCast, JsValue, prelude::wasm_bindgen};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{AbortController, AbortSignal, Headers, ReadableStreamDefaultReader, Request, RequestInit, Response};
+
+// Bound on the global object so the client works in windows and workers alike
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_name = fetch)]
+    fn fetch_with_request(input: &Request) -> js_sys::Promise;
+
+    #[wasm_bindgen(js_name = setTimeout)]
+    fn set_timeout(handler: &js_sys::Function, timeout: i32) -> JsValue;
+
+    #[wasm_bindgen(js_name = clearTimeout)]
+    fn clear_timeout(handle: &JsValue);
+}
+
+/// Errors returned by [`WasmFetchClient`]
+#[derive(Debug, thiserror::Error)]
+pub enum WasmFetchError {
+    /// The request could not be built or the fetch call failed
+    #[error("fetch error: {0}")]
+    Fetch(String),
+
+    /// The request was aborted because its timeout elapsed
+    #[error("request timed out")]
+    Timeout,
+
+    /// A streamed response body grew past the request's `max_response_bytes`
+    #[error("response body exceeds {limit} bytes")]
+    ResponseTooLarge {
+        /// The configured limit
+        limit: u64,
+    },
+}
+
+impl From<JsValue> for WasmFetchError {
+    fn from(value: JsValue) -> Self {
+        let message = value
+            .dyn_ref::<js_sys::Error>()
+            .map(|error| String::from(error.message()))
+            .or_else(|| value.as_string())
+            .unwrap_or_else(|| format!("{value:?}"));
+        WasmFetchError::Fetch(message)
+    }
+}
+
+/// Browser `fetch` client that implements AsyncHttpClient
+///
+/// This client calls the platform `fetch` API directly through `web-sys`,
+/// so browser builds don't need reqwest's WASM shim and end up with smaller
+/// bundles. Each request gets an `AbortController`, which cancels the
+/// underlying fetch when the total timeout elapses or when the request's
+/// future is dropped. `fetch` has no connect or read timeouts, so only the
+/// total timeout is applied.
+///
+/// It is only available when compiling for `wasm32` targets.
+///
+/// # Examples
+///
+/// ```no_run
+/// use derive_rest_api::WasmFetchClient;
+///
+/// let client = WasmFetchClient::new();
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct WasmFetchClient {
+    _private: (),
+}
+
+impl WasmFetchClient {
+    /// Creates a new fetch client
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builds a fetch request from the trait's request parameters
+    fn build_request(
+        method: &str,
+        url: &str,
+        headers: &HeaderMap,
+        body: Option<&[u8]>,
+        signal: &web_sys::AbortSignal,
+    ) -> Result<Request, WasmFetchError> {
+        let request_headers = Headers::new()?;
+        for (key, value) in headers {
+            let value = value
+                .to_str()
+                .map_err(|e| WasmFetchError::Fetch(format!("invalid header value for {key}: {e}")))?;
+            request_headers.append(key.as_str(), value)?;
+        }
+
+        let init = RequestInit::new();
+        init.set_method(&method.to_uppercase());
+        init.set_headers(&request_headers);
+        if let Some(body_data) = body {
+            init.set_body(&js_sys::Uint8Array::from(body_data));
+        }
+        init.set_signal(Some(signal));
+
+        Ok(Request::new_with_str_and_init(url, &init)?)
+    }
+
+    /// Runs the fetch and reads the response, up to `read_limit` body bytes
+    async fn fetch(request: &Request, read_limit: Option<u64>) -> Result<http::Response<Vec<u8>>, JsValue> {
+        let response = Self::fetch_response(request).await?;
+        let body = Self::read_body(&response, read_limit).await?;
+        Ok(Self::response_parts(&response)?.map(|()| body))
+    }
+
+    /// Runs the fetch, resolving once the response headers have arrived
+    async fn fetch_response(request: &Request) -> Result<Response, JsValue> {
+        JsFuture::from(fetch_with_request(request)).await?.dyn_into()
+    }
+
+    /// Converts the status and headers of a fetch response
+    fn response_parts(response: &Response) -> Result<http::Response<()>, JsValue> {
+        let mut result = http::Response::new(());
+        *result.status_mut() = http::StatusCode::from_u16(response.status())
+            .unwrap_or(http::StatusCode::INTERNAL_SERVER_ERROR);
+
+        // Headers iterate as `[name, value]` pairs; only the headers CORS
+        // exposes to the page are visible here
+        if let Some(entries) = js_sys::try_iter(&response.headers())? {
+            for entry in entries {
+                let entry: js_sys::Array = entry?.unchecked_into();
+                let (Some(key), Some(value)) = (entry.get(0).as_string(), entry.get(1).as_string()) else {
+                    continue;
+                };
+                if let (Ok(name), Ok(value)) = (
+                    http::HeaderName::from_bytes(key.as_bytes()),
+                    http::HeaderValue::from_str(&value),
+                ) {
+                    result.headers_mut().append(name, value);
+                }
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Arms the request's abort controller and timeout, so the fetch is
+    /// aborted when the guard is dropped or the timeout elapses
+    fn start(controller: AbortController, options: &RequestOptions) -> FetchGuard {
+        let abort_on_drop = AbortOnDrop(Some(controller.clone()));
+        let timer = options.timeouts.total.map(|timeout_duration| {
+            let callback = Closure::once(move || controller.abort());
+            let millis = i32::try_from(timeout_duration.as_millis()).unwrap_or(i32::MAX);
+            let handle = set_timeout(callback.as_ref().unchecked_ref(), millis);
+            TimeoutGuard { handle, _callback: callback }
+        });
+        FetchGuard { abort_on_drop, _timer: timer }
+    }
+
+    /// Sends a request and returns as soon as the response headers arrive,
+    /// with the body as a stream of chunks read from its `ReadableStream`.
+    ///
+    /// Long responses can be processed or rendered progressively instead of
+    /// after the whole download. The total timeout covers reading the body,
+    /// and dropping the stream cancels the rest of the download. The stream
+    /// fails with [`WasmFetchError::ResponseTooLarge`] once the body grows past
+    /// the options' `max_response_bytes`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request can't be built, the fetch fails, or the
+    /// timeout elapses before the response headers arrive.
+    pub async fn send_streaming(
+        &self,
+        method: &str,
+        url: &str,
+        headers: HeaderMap,
+        body: Option<Vec<u8>>,
+        options: RequestOptions,
+    ) -> Result<http::Response<FetchBodyStream>, WasmFetchError> {
+        let controller = AbortController::new()?;
+        let signal = controller.signal();
+        let request = Self::build_request(method, url, &headers, body.as_deref(), &signal)?;
+        let guard = Self::start(controller, &options);
+
+        let response = Self::fetch_response(&request)
+            .await
+            .and_then(|response| Ok((Self::response_parts(&response)?, response)));
+        let (parts, response) = response.map_err(|error| fetch_error(&signal, error))?;
+        let reader = response
+            .body()
+            .map(|stream| stream.get_reader().unchecked_into::<ReadableStreamDefaultReader>());
+
+        Ok(parts.map(|()| FetchBodyStream {
+            reader,
+            pending: None,
+            signal,
+            received: 0,
+            max_response_bytes: options.max_response_bytes,
+            guard: Some(guard),
+        }))
+    }
+
+    /// Reads the response body, up to `read_limit` bytes
+    async fn read_body(response: &Response, read_limit: Option<u64>) -> Result<Vec<u8>, JsValue> {
+        let Some(limit) = read_limit else {
+            let buffer = JsFuture::from(response.array_buffer()?).await?;
+            return Ok(js_sys::Uint8Array::new(&buffer).to_vec());
+        };
+
+        // Read the body stream chunk by chunk so an oversized body is never buffered
+        let Some(stream) = response.body() else {
+            return Ok(Vec::new());
+        };
+        let reader: ReadableStreamDefaultReader = stream.get_reader().unchecked_into();
+        let mut bytes = Vec::new();
+        loop {
+            let result = JsFuture::from(reader.read()).await?;
+            if js_sys::Reflect::get(&result, &JsValue::from_str("done"))?.is_truthy() {
+                break;
+            }
+            let chunk = js_sys::Uint8Array::new(&js_sys::Reflect::get(&result, &JsValue::from_str("value"))?);
+            let remaining = limit.saturating_sub(bytes.len() as u64);
+            let take = u64::from(chunk.length()).min(remaining) as u32;
+            bytes.extend(chunk.subarray(0, take).to_vec());
+            if bytes.len() as u64 >= limit {
+                // Stop downloading the rest of the body
+                let _ = reader.cancel();
+                break;
+            }
+        }
+        Ok(bytes)
+    }
+}
+
+/// Maps a failed fetch to a timeout if its signal was aborted by the timer
+fn fetch_error(signal: &AbortSignal, error: JsValue) -> WasmFetchError {
+    if signal.aborted() {
+        WasmFetchError::Timeout
+    } else {
+        WasmFetchError::from(error)
+    }
+}
+
+/// A response body streamed chunk by chunk, returned by [`WasmFetchClient::send_streaming`].
+///
+/// Implements `Stream<Item = Result<Bytes, WasmFetchError>>`. Dropping it
+/// before the end of the body aborts the download.
+pub struct FetchBodyStream {
+    /// `None` for responses without a body
+    reader: Option<ReadableStreamDefaultReader>,
+    /// The read in progress, if any
+    pending: Option<JsFuture>,
+    signal: AbortSignal,
+    received: u64,
+    max_response_bytes: Option<u64>,
+    /// Released once the body has been read, or the stream has failed
+    guard: Option<FetchGuard>,
+}
+
+impl FetchBodyStream {
+    /// Handles the result of a `read()`, returning the next chunk or `None` at the end of the body
+    fn next_chunk(&mut self, result: JsValue) -> Result<Option<Bytes>, WasmFetchError> {
+        if js_sys::Reflect::get(&result, &JsValue::from_str("done"))?.is_truthy() {
+            return Ok(None);
+        }
+        let chunk = js_sys::Uint8Array::new(&js_sys::Reflect::get(&result, &JsValue::from_str("value"))?);
+        self.received += u64::from(chunk.length());
+        if let Some(limit) = self.max_response_bytes
+            && self.received > limit
+        {
+            return Err(WasmFetchError::ResponseTooLarge { limit });
+        }
+        Ok(Some(Bytes::from(chunk.to_vec())))
+    }
+
+    /// Ends the stream, aborting the fetch unless the body was `complete`
+    fn finish(&mut self, complete: bool) {
+        let guard = self.guard.take();
+        if complete && let Some(mut guard) = guard {
+            guard.disarm();
+        }
+    }
+}
+
+impl Stream for FetchBodyStream {
+    type Item = Result<Bytes, WasmFetchError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        let Some(reader) = this.reader.clone().filter(|_| this.guard.is_some()) else {
+            this.finish(true);
+            return Poll::Ready(None);
+        };
+
+        let pending = this.pending.get_or_insert_with(|| JsFuture::from(reader.read()));
+        let result = ready!(Pin::new(pending).poll(cx));
+        this.pending = None;
+
+        match result
+            .map_err(|error| fetch_error(&this.signal, error))
+            .and_then(|result| this.next_chunk(result))
+        {
+            Ok(Some(chunk)) => Poll::Ready(Some(Ok(chunk))),
+            Ok(None) => {
+                this.finish(true);
+                Poll::Ready(None)
+            }
+            Err(error) => {
+                let _ = reader.cancel();
+                this.finish(false);
+                Poll::Ready(Some(Err(error)))
+            }
+        }
+    }
+}
+
+/// Keeps a fetch abortable until it completes: aborts it when dropped, and
+/// when the total timeout elapses
+struct FetchGuard {
+    abort_on_drop: AbortOnDrop,
+    _timer: Option<TimeoutGuard>,
+}
+
+impl FetchGuard {
+    /// Marks the fetch as complete, so dropping the guard no longer aborts it
+    fn disarm(&mut self) {
+        self.abort_on_drop.0.take();
+    }
+}
+
+/// Aborts the fetch when dropped, unless the fetch has already completed
+struct AbortOnDrop(Option<AbortController>);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        if let Some(controller) = self.0.take() {
+            controller.abort();
+        }
+    }
+}
+
+/// Pending `setTimeout` call, cleared when dropped so its callback never outlives it
+struct TimeoutGuard {
+    handle: JsValue,
+    _callback: Closure<dyn FnMut()>,
+}
+
+impl Drop for TimeoutGuard {
+    fn drop(&mut self) {
+        clear_timeout(&self.handle);
+    }
+}
+
+impl AsyncHttpClient for WasmFetchClient {
+    type Error = WasmFetchError;
+
+    async fn send_async(
+        &self,
+        method: &str,
+        url: &str,
+        headers: HeaderMap,
+        body: Option<Vec<u8>>,
+        options: RequestOptions,
+    ) -> Result<http::Response<Vec<u8>>, Self::Error> {
+        let controller = AbortController::new()?;
+        let signal = controller.signal();
+        let request = Self::build_request(method, url, &headers, body.as_deref(), &signal)?;
+
+        // Abort the fetch (including reading the body) if this future is
+        // dropped before it completes, or once the timeout elapses
+        let mut guard = Self::start(controller, &options);
+        let result = Self::fetch(&request, options.read_limit()).await;
+        guard.disarm();
+        drop(guard);
+
+        result.map_err(|error| fetch_error(&signal, error))
+    }
+}
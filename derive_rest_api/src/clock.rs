@@ -0,0 +1,142 @@
+//! Time sources for retries, retry budgets and rate limiting.
+
+use std::fmt::Debug;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// A source of the current time, and a way to wait.
+///
+/// Retry backoff, [`RetryBudget`](crate::RetryBudget) windows,
+/// [`RateLimitState`](crate::RateLimitState) and
+/// [`ThrottlePolicy`](crate::ThrottlePolicy) read the time and wait through a
+/// clock, which is [`SystemClock`] unless they're given another. Tests can use
+/// a [`ManualClock`] instead, so that backoff and rate-limit windows pass
+/// instantly and deterministically.
+pub trait Clock: Debug + Send + Sync {
+    /// Returns the current time.
+    fn now(&self) -> Instant;
+
+    /// Blocks the current thread for `duration`.
+    fn sleep(&self, duration: Duration);
+
+    /// Waits for `duration` without blocking the thread.
+    fn sleep_async(&self, duration: Duration) -> Pin<Box<dyn Future<Output = ()> + Send + '_>>;
+}
+
+impl<T: Clock + ?Sized> Clock for Arc<T> {
+    fn now(&self) -> Instant {
+        (**self).now()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        (**self).sleep(duration)
+    }
+
+    fn sleep_async(&self, duration: Duration) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        (**self).sleep_async(duration)
+    }
+}
+
+/// The real time, as given by [`Instant::now`].
+///
+/// Async waits share a single timer thread rather than taking a thread each,
+/// and use `setTimeout` on `wasm32`, so backoff and throttling waits are kept
+/// in the browser too. Blocking waits can't be made on `wasm32`, where
+/// `sleep` returns immediately.
+///
+/// The time isn't available on `wasm32-unknown-unknown`, where `Instant::now`
+/// panics. So on `wasm32`, requests only read the time for features that need
+/// it, such as metrics hooks and event subscribers, and those need a clock
+/// that doesn't rely on `Instant::now`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn sleep(&self, duration: Duration) {
+        std::thread::sleep(duration);
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn sleep(&self, _duration: Duration) {}
+
+    fn sleep_async(&self, duration: Duration) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        Box::pin(crate::poll::Delay::new(duration))
+    }
+}
+
+/// A virtual clock that only moves when told to.
+///
+/// Sleeping advances the clock by the time slept and returns immediately, so
+/// code waiting on a `ManualClock` runs without delay while still seeing time
+/// pass. Clones share the same time.
+///
+/// # Example
+///
+/// ```
+/// use derive_rest_api::{Clock, ManualClock};
+/// use std::time::Duration;
+///
+/// let clock = ManualClock::new();
+/// let start = clock.now();
+/// clock.sleep(Duration::from_secs(30));
+/// clock.advance(Duration::from_secs(30));
+/// assert_eq!(clock.now() - start, Duration::from_secs(60));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ManualClock {
+    start: Instant,
+    elapsed: Arc<Mutex<Duration>>,
+}
+
+impl ManualClock {
+    /// Creates a clock starting at the current time.
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            elapsed: Arc::default(),
+        }
+    }
+
+    /// Moves the clock forward by `duration`.
+    pub fn advance(&self, duration: Duration) {
+        *self.elapsed.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) += duration;
+    }
+
+    /// Returns how far the clock has moved since it was created.
+    pub fn elapsed(&self) -> Duration {
+        *self.elapsed.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Default for ManualClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        self.start + self.elapsed()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        self.advance(duration);
+    }
+
+    fn sleep_async(&self, duration: Duration) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        self.advance(duration);
+        Box::pin(std::future::ready(()))
+    }
+}
+
+/// Returns the clock set on a component, or the system clock
+pub(crate) fn clock_or_system(clock: &Option<Arc<dyn Clock>>) -> &dyn Clock {
+    clock.as_deref().unwrap_or(&SystemClock)
+}
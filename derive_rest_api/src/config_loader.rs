@@ -0,0 +1,44 @@
+//! Loading API client configs for `#[api_client(env_prefix = "...")]`.
+
+use crate::RestApiError;
+use serde::de::DeserializeOwned;
+use std::path::Path;
+
+/// A config loaded from a file and the environment, with the base URL if one was given.
+#[derive(Debug)]
+pub struct LoadedConfig<T> {
+    pub config: T,
+    pub base_url: Option<String>,
+}
+
+/// Loads a config from the file at `path`, if any, overridden by environment
+/// variables starting with `<prefix>_`.
+///
+/// Variable names after the prefix are lowercased into keys, so `MY_API_API_KEY`
+/// sets `api_key`, and `__` separates nested keys. The file's format is chosen
+/// by its extension: `.toml`, `.json`, `.yaml` or `.yml`. A `base_url` key is
+/// also returned separately, for the client.
+pub fn load<T: DeserializeOwned>(prefix: &str, path: Option<&Path>) -> Result<LoadedConfig<T>, RestApiError> {
+    let mut builder = config::Config::builder();
+    if let Some(path) = path {
+        builder = builder.add_source(config::File::from(path));
+    }
+    let source = builder
+        .add_source(
+            config::Environment::with_prefix(prefix)
+                .prefix_separator("_")
+                .separator("__")
+                .try_parsing(true),
+        )
+        .build()
+        .map_err(config_error)?;
+    let base_url = source.get_string("base_url").ok();
+    let config = source.try_deserialize().map_err(config_error)?;
+    Ok(LoadedConfig { config, base_url })
+}
+
+fn config_error(error: config::ConfigError) -> RestApiError {
+    RestApiError::ConfigError {
+        source: Box::new(error),
+    }
+}
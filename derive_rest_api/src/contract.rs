@@ -0,0 +1,534 @@
+//! Checking request definitions against an OpenAPI document.
+
+use crate::RestRequest;
+use crate::mock::{parse_query, path_matches, split_url};
+use serde_json::{Map, Value};
+
+/// How deep examples of recursive schemas are generated
+const MAX_EXAMPLE_DEPTH: usize = 8;
+
+/// An OpenAPI 3 document to check request definitions against.
+///
+/// Hand-written request structs drift from the provider's API as it evolves.
+/// [`check`](Self::check) compares a request, built with representative
+/// values, against the operation in the document that its method and path
+/// match:
+///
+/// - the path parameters, query parameters and any header parameters the
+///   request sets must be declared, and their values must fit their schemas
+/// - required query parameters must be sent
+/// - the body must be allowed, present when required, and match the JSON
+///   schema of the request body
+/// - the response type must decode every `2xx` JSON response the operation
+///   describes, both with every property filled in and with only the
+///   required ones (and nullable properties `null`)
+///
+/// Responses are checked by decoding an example generated from the schema,
+/// or the schema's own `example`, with the request's
+/// [`parse_response`](RestRequest::parse_response). Only JSON documents are
+/// read; convert YAML specs to JSON first.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use derive_rest_api::OpenApiSpec;
+///
+/// #[test]
+/// fn requests_match_the_spec() {
+///     let spec = OpenApiSpec::from_json(include_str!("openapi.json")).unwrap();
+///     spec.assert_conforms(&GetUserBuilder::new().id(1).build().unwrap());
+///     spec.assert_conforms(&CreateUserBuilder::new().name("Ada".into()).build().unwrap());
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct OpenApiSpec {
+    document: Value,
+}
+
+/// A difference between a request definition and an [`OpenApiSpec`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContractMismatch {
+    /// The request's method and URL path, e.g. `GET /users/42`
+    pub request: String,
+    /// What doesn't match the spec
+    pub message: String,
+}
+
+impl std::fmt::Display for ContractMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.request, self.message)
+    }
+}
+
+/// The operation a request maps to
+struct Operation<'a> {
+    template: &'a str,
+    path_item: &'a Value,
+    operation: &'a Value,
+}
+
+impl OpenApiSpec {
+    /// Reads an OpenAPI document from JSON.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `json` isn't valid JSON.
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json).map(Self::from_value)
+    }
+
+    /// Uses an already parsed OpenAPI document.
+    pub fn from_value(document: Value) -> Self {
+        Self { document }
+    }
+
+    /// Returns every way `request` and its response type differ from the spec.
+    pub fn check<R: RestRequest>(&self, request: &R) -> Vec<ContractMismatch> {
+        let method = request.method();
+        let url = match request.build_url() {
+            Ok(url) => url,
+            Err(error) => {
+                return vec![ContractMismatch {
+                    request: method.to_string(),
+                    message: format!("the URL doesn't build: {}", error),
+                }];
+            }
+        };
+        let (path, query) = split_url(&url);
+        let label = format!("{} {}", method, path);
+        let mut messages = Vec::new();
+
+        match self.find_operation(method, path) {
+            Ok(operation) => {
+                self.check_parameters(&operation, path, query, request, &mut messages);
+                self.check_body(operation.operation, request, &mut messages);
+                self.check_responses::<R>(operation.operation, &mut messages);
+            }
+            Err(message) => messages.push(message),
+        }
+
+        messages
+            .into_iter()
+            .map(|message| ContractMismatch {
+                request: label.clone(),
+                message,
+            })
+            .collect()
+    }
+
+    /// Checks `request` against the spec.
+    ///
+    /// # Panics
+    ///
+    /// Panics listing every mismatch, if there are any.
+    #[track_caller]
+    pub fn assert_conforms<R: RestRequest>(&self, request: &R) {
+        let mismatches = self.check(request);
+        if !mismatches.is_empty() {
+            let lines: Vec<_> = mismatches.iter().map(|mismatch| format!("  {}", mismatch)).collect();
+            panic!("request doesn't conform to the OpenAPI spec:\n{}", lines.join("\n"));
+        }
+    }
+
+    /// Finds the operation for `method` on `path`, preferring the path
+    /// template with the fewest parameters when several match
+    fn find_operation(&self, method: &str, path: &str) -> Result<Operation<'_>, String> {
+        let paths = self.document.get("paths").and_then(Value::as_object);
+        let prefixes = self.server_prefixes();
+        let mut best: Option<(usize, &str, &Value)> = None;
+        for (template, item) in paths.into_iter().flatten() {
+            let matches = prefixes.iter().any(|prefix| {
+                path.strip_prefix(prefix.as_str())
+                    .is_some_and(|rest| rest.starts_with('/') && path_matches(template, rest))
+            });
+            let params = template.matches('{').count();
+            if matches && best.is_none_or(|(fewest, ..)| params < fewest) {
+                best = Some((params, template, self.resolve(item)));
+            }
+        }
+
+        let Some((_, template, path_item)) = best else {
+            return Err("no path in the spec matches".to_string());
+        };
+        match path_item.get(method.to_ascii_lowercase()) {
+            Some(operation) => Ok(Operation { template, path_item, operation }),
+            None => Err(format!("`{}` has no {} operation", template, method)),
+        }
+    }
+
+    /// Returns the path prefixes of the document's servers, and the empty prefix
+    fn server_prefixes(&self) -> Vec<String> {
+        let mut prefixes = vec![String::new()];
+        let servers = self.document.get("servers").and_then(Value::as_array);
+        for url in servers.into_iter().flatten().filter_map(|server| server.get("url")?.as_str()) {
+            let (path, _) = split_url(url);
+            let path = path.trim_end_matches('/');
+            if !path.is_empty() && !prefixes.iter().any(|prefix| prefix == path) {
+                prefixes.push(path.to_string());
+            }
+        }
+        prefixes
+    }
+
+    fn check_parameters<R: RestRequest>(
+        &self,
+        operation: &Operation<'_>,
+        path: &str,
+        query: Option<&str>,
+        request: &R,
+        messages: &mut Vec<String>,
+    ) {
+        let parameters = self.parameters(operation);
+        let find = |location: &str, name: &str| {
+            parameters.iter().find(|parameter| {
+                parameter.get("in").and_then(Value::as_str) == Some(location)
+                    && parameter
+                        .get("name")
+                        .and_then(Value::as_str)
+                        .is_some_and(|declared| declared.eq_ignore_ascii_case(name))
+            })
+        };
+
+        // Path parameters line up with the template's last segments, after any server prefix
+        let template_segments = operation.template.trim_end_matches('/').split('/').rev();
+        for (expected, segment) in template_segments.zip(path.trim_end_matches('/').split('/').rev()) {
+            let Some(name) = expected.strip_prefix('{').and_then(|rest| rest.strip_suffix('}')) else {
+                continue;
+            };
+            match find("path", name) {
+                Some(parameter) => self.check_parameter_value("path", name, parameter, segment, messages),
+                None => messages.push(format!("path parameter `{}` isn't declared", name)),
+            }
+        }
+
+        let sent = query.map(parse_query).unwrap_or_default();
+        for (name, value) in &sent {
+            // serde_qs sends sequences and maps as `name[0]` and `name[key]`
+            let name = name.split('[').next().unwrap_or(name);
+            match find("query", name) {
+                Some(parameter) => self.check_parameter_value("query", name, parameter, value, messages),
+                None => messages.push(format!("query parameter `{}` isn't in the spec", name)),
+            }
+        }
+        for parameter in &parameters {
+            let (Some("query"), Some(name)) = (
+                parameter.get("in").and_then(Value::as_str),
+                parameter.get("name").and_then(Value::as_str),
+            ) else {
+                continue;
+            };
+            let required = parameter.get("required").and_then(Value::as_bool).unwrap_or(false);
+            if required && !sent.iter().any(|(sent, _)| sent.split('[').next() == Some(name)) {
+                messages.push(format!("required query parameter `{}` isn't sent", name));
+            }
+        }
+
+        // Client-level headers aren't part of the request, so only the headers it sets are checked
+        if let Ok(headers) = request.build_headers() {
+            for (name, value) in &headers {
+                if let (Some(parameter), Ok(value)) = (find("header", name.as_str()), value.to_str()) {
+                    self.check_parameter_value("header", name.as_str(), parameter, value, messages);
+                }
+            }
+        }
+    }
+
+    /// Returns the operation's parameters, including those shared by its path
+    fn parameters<'a>(&'a self, operation: &Operation<'a>) -> Vec<&'a Value> {
+        let declared = |item: &'a Value| {
+            item.get("parameters")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .map(|parameter| self.resolve(parameter))
+        };
+        let own: Vec<_> = declared(operation.operation).collect();
+        let key = |parameter: &Value| (parameter.get("in").cloned(), parameter.get("name").cloned());
+        // Parameters declared on the operation override those on the path
+        let shared = declared(operation.path_item).filter(|shared| own.iter().all(|own| key(own) != key(shared)));
+        shared.chain(own.iter().copied()).collect()
+    }
+
+    /// Checks a parameter value sent as text against the parameter's schema
+    fn check_parameter_value(&self, location: &str, name: &str, parameter: &Value, value: &str, messages: &mut Vec<String>) {
+        let Some(schema) = parameter.get("schema").map(|schema| self.resolve(schema)) else {
+            return;
+        };
+        if let Some(allowed) = schema.get("enum").and_then(Value::as_array)
+            && !allowed.iter().any(|allowed| allowed.as_str() == Some(value) || serde_json::from_str::<Value>(value).ok().as_ref() == Some(allowed))
+        {
+            messages.push(format!("{} parameter `{}` is `{}`, which isn't one of {}", location, name, value, Value::from(allowed.clone())));
+            return;
+        }
+        let fits = match schema_types(schema).first().copied() {
+            Some("integer") => value.parse::<i64>().is_ok() || value.parse::<u64>().is_ok(),
+            Some("number") => value.parse::<f64>().is_ok(),
+            Some("boolean") => value == "true" || value == "false",
+            _ => true,
+        };
+        if !fits {
+            messages.push(format!(
+                "{} parameter `{}` is `{}`, expected {}",
+                location,
+                name,
+                value,
+                schema_types(schema).join(" or ")
+            ));
+        }
+    }
+
+    fn check_body<R: RestRequest>(&self, operation: &Value, request: &R, messages: &mut Vec<String>) {
+        let body = match request.build_body() {
+            Ok(body) => body,
+            Err(error) => {
+                messages.push(format!("the body doesn't serialize: {}", error));
+                return;
+            }
+        };
+        let request_body = operation.get("requestBody").map(|body| self.resolve(body));
+        match (body, request_body) {
+            (None, None) => {}
+            (Some(_), None) => messages.push("a body is sent, but the operation has no request body".to_string()),
+            (None, Some(request_body)) => {
+                if request_body.get("required").and_then(Value::as_bool).unwrap_or(false) {
+                    messages.push("the operation requires a request body, but none is sent".to_string());
+                }
+            }
+            (Some(body), Some(request_body)) => {
+                let Some(schema) = json_schema(request_body) else {
+                    return;
+                };
+                match serde_json::from_slice::<Value>(&body) {
+                    Ok(value) => self.validate(schema, &value, "body", messages, 0),
+                    Err(_) => messages.push("the operation takes a JSON body, but the body isn't JSON".to_string()),
+                }
+            }
+        }
+    }
+
+    fn check_responses<R: RestRequest>(&self, operation: &Value, messages: &mut Vec<String>) {
+        let responses = operation.get("responses").and_then(Value::as_object);
+        for (code, response) in responses.into_iter().flatten() {
+            let status = match code.as_str() {
+                "2XX" | "2xx" => 200,
+                code if code.starts_with('2') => match code.parse() {
+                    Ok(status) => status,
+                    Err(_) => continue,
+                },
+                _ => continue,
+            };
+            let Some(schema) = json_schema(self.resolve(response)) else {
+                continue;
+            };
+
+            for (full, description) in [(true, "every property"), (false, "only the required properties")] {
+                let example = self.example(schema, full, 0);
+                let mut response = http::Response::new(serde_json::to_vec(&example).unwrap_or_default());
+                *response.status_mut() = http::StatusCode::from_u16(status).unwrap_or(http::StatusCode::OK);
+                response
+                    .headers_mut()
+                    .insert(http::header::CONTENT_TYPE, http::HeaderValue::from_static("application/json"));
+                if let Err(error) = R::parse_response(response) {
+                    messages.push(format!(
+                        "the response type doesn't decode a {} response with {}, such as {}: {}",
+                        code, description, example, error
+                    ));
+                    // The minimal example would usually fail the same way
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Validates `value` against a JSON schema, recording where it doesn't fit
+    fn validate(&self, schema: &Value, value: &Value, at: &str, messages: &mut Vec<String>, depth: usize) {
+        let schema = self.resolve(schema);
+        if depth > 64 || schema.as_bool() == Some(true) {
+            return;
+        }
+
+        for part in schema.get("allOf").and_then(Value::as_array).into_iter().flatten() {
+            self.validate(part, value, at, messages, depth + 1);
+        }
+        for key in ["anyOf", "oneOf"] {
+            if let Some(options) = schema.get(key).and_then(Value::as_array) {
+                let fits_one = options.iter().any(|option| {
+                    let mut errors = Vec::new();
+                    self.validate(option, value, at, &mut errors, depth + 1);
+                    errors.is_empty()
+                });
+                if !fits_one {
+                    messages.push(format!("{} doesn't match any of the schemas in `{}`", at, key));
+                }
+            }
+        }
+
+        let types = schema_types(schema);
+        let nullable = schema.get("nullable").and_then(Value::as_bool).unwrap_or(false) || types.contains(&"null");
+        if value.is_null() && nullable {
+            return;
+        }
+        if let Some(allowed) = schema.get("enum").and_then(Value::as_array) {
+            if !allowed.contains(value) {
+                messages.push(format!("{} is {}, which isn't one of {}", at, value, Value::from(allowed.clone())));
+            }
+            return;
+        }
+        if !types.is_empty() && !types.iter().any(|expected| fits_type(expected, value)) {
+            messages.push(format!("{} is {}, expected {}", at, json_type(value), types.join(" or ")));
+            return;
+        }
+
+        match value {
+            Value::Object(object) => self.validate_object(schema, object, at, messages, depth),
+            Value::Array(items) => {
+                if let Some(item_schema) = schema.get("items") {
+                    for (index, item) in items.iter().enumerate() {
+                        self.validate(item_schema, item, &format!("{}[{}]", at, index), messages, depth + 1);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn validate_object(&self, schema: &Value, object: &Map<String, Value>, at: &str, messages: &mut Vec<String>, depth: usize) {
+        let properties = schema.get("properties").and_then(Value::as_object);
+        for name in schema.get("required").and_then(Value::as_array).into_iter().flatten().filter_map(Value::as_str) {
+            if !object.contains_key(name) {
+                messages.push(format!("{} is missing the required property `{}`", at, name));
+            }
+        }
+        for (name, value) in object {
+            let at = format!("{}.{}", at, name);
+            match (properties.and_then(|properties| properties.get(name)), schema.get("additionalProperties")) {
+                (Some(property), _) => self.validate(property, value, &at, messages, depth + 1),
+                (None, Some(Value::Bool(false))) => messages.push(format!("{} isn't a property in the spec", at)),
+                (None, Some(additional)) => self.validate(additional, value, &at, messages, depth + 1),
+                (None, None) => {}
+            }
+        }
+    }
+
+    /// Generates an example value for a schema, with every property if `full`,
+    /// or only the required properties, and `null` for nullable values, if not
+    fn example(&self, schema: &Value, full: bool, depth: usize) -> Value {
+        let schema = self.resolve(schema);
+        if let Some(example) = schema.get("example") {
+            return example.clone();
+        }
+        if let Some(example) = schema.get("examples").and_then(Value::as_array).and_then(|examples| examples.first()) {
+            return example.clone();
+        }
+        let types = schema_types(schema);
+        let nullable = schema.get("nullable").and_then(Value::as_bool).unwrap_or(false) || types.contains(&"null");
+        if nullable && !full {
+            return Value::Null;
+        }
+        if let Some(value) = schema.get("enum").and_then(Value::as_array).and_then(|allowed| allowed.first()) {
+            return value.clone();
+        }
+        if let Some(parts) = schema.get("allOf").and_then(Value::as_array) {
+            let mut merged = Map::new();
+            for part in parts {
+                if let Value::Object(object) = self.example(part, full, depth + 1) {
+                    merged.extend(object);
+                }
+            }
+            return Value::Object(merged);
+        }
+        for key in ["oneOf", "anyOf"] {
+            if let Some(option) = schema.get(key).and_then(Value::as_array).and_then(|options| options.first()) {
+                return self.example(option, full, depth + 1);
+            }
+        }
+
+        let kind = types.iter().copied().find(|kind| *kind != "null");
+        match kind {
+            Some("object") | None if schema.get("properties").is_some() => {
+                let mut object = Map::new();
+                if depth < MAX_EXAMPLE_DEPTH {
+                    let required: Vec<_> = schema.get("required").and_then(Value::as_array).into_iter().flatten().collect();
+                    for (name, property) in schema.get("properties").and_then(Value::as_object).into_iter().flatten() {
+                        if full || required.iter().any(|required| required.as_str() == Some(name)) {
+                            object.insert(name.clone(), self.example(property, full, depth + 1));
+                        }
+                    }
+                }
+                Value::Object(object)
+            }
+            Some("object") => Value::Object(Map::new()),
+            Some("array") => match schema.get("items") {
+                Some(items) if full && depth < MAX_EXAMPLE_DEPTH => Value::Array(vec![self.example(items, full, depth + 1)]),
+                _ => Value::Array(Vec::new()),
+            },
+            Some("string") => Value::from(match schema.get("format").and_then(Value::as_str) {
+                Some("date-time") => "1970-01-01T00:00:00Z",
+                Some("date") => "1970-01-01",
+                Some("uuid") => "00000000-0000-0000-0000-000000000000",
+                Some("email") => "user@example.com",
+                Some("uri") | Some("url") => "https://example.com/",
+                _ => "string",
+            }),
+            Some("integer") => Value::from(1),
+            Some("number") => Value::from(1.5),
+            Some("boolean") => Value::from(true),
+            _ => Value::Null,
+        }
+    }
+
+    /// Follows `$ref`s within the document
+    fn resolve<'a>(&'a self, mut value: &'a Value) -> &'a Value {
+        for _ in 0..32 {
+            let Some(reference) = value.get("$ref").and_then(Value::as_str) else {
+                break;
+            };
+            match reference.strip_prefix('#').and_then(|pointer| self.document.pointer(pointer)) {
+                Some(target) => value = target,
+                None => break,
+            }
+        }
+        value
+    }
+}
+
+/// Returns the JSON schema of a request body or response, if it has JSON content
+fn json_schema(body: &Value) -> Option<&Value> {
+    let content = body.get("content")?.as_object()?;
+    content
+        .iter()
+        .find(|(media_type, _)| {
+            let media_type = media_type.split(';').next().unwrap_or_default().trim();
+            media_type == "application/json" || media_type.ends_with("+json")
+        })
+        .and_then(|(_, media)| media.get("schema"))
+}
+
+/// Returns a schema's `type`, which OpenAPI 3.1 allows to be a list
+fn schema_types(schema: &Value) -> Vec<&str> {
+    match schema.get("type") {
+        Some(Value::String(kind)) => vec![kind.as_str()],
+        Some(Value::Array(kinds)) => kinds.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn fits_type(expected: &str, value: &Value) -> bool {
+    match expected {
+        "integer" => value.is_i64() || value.is_u64() || value.as_f64().is_some_and(|number| number.fract() == 0.0),
+        "number" => value.is_number(),
+        kind => json_type(value) == kind,
+    }
+}
+
+fn json_type(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(number) if number.is_f64() => "number",
+        Value::Number(_) => "integer",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
@@ -0,0 +1,185 @@
+//! Credentials looked up when each request is sent.
+
+use crate::RestApiError;
+use http::{HeaderMap, HeaderName, HeaderValue};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, RwLock};
+
+/// The future returned by [`AsyncCredentialsProvider::credentials_async`].
+#[cfg(not(target_arch = "wasm32"))]
+pub type CredentialsFuture<'a> = Pin<Box<dyn Future<Output = Result<Credentials, RestApiError>> + Send + 'a>>;
+
+/// The future returned by [`AsyncCredentialsProvider::credentials_async`].
+///
+/// Futures awaiting browser APIs aren't `Send`, so on `wasm32` it needn't be either.
+#[cfg(target_arch = "wasm32")]
+pub type CredentialsFuture<'a> = Pin<Box<dyn Future<Output = Result<Credentials, RestApiError>> + 'a>>;
+
+/// A secret sent with a request.
+///
+/// The `Debug` output leaves the secret out, so credentials can be logged safely.
+#[derive(Clone, PartialEq, Eq)]
+pub enum Credentials {
+    /// Sent as `Authorization: Bearer <token>`
+    Bearer(String),
+    /// Sent as-is in the header `name`, such as an API key in `X-API-Key`
+    Header { name: HeaderName, value: String },
+}
+
+impl Credentials {
+    /// Creates bearer token credentials.
+    pub fn bearer(token: impl Into<String>) -> Self {
+        Self::Bearer(token.into())
+    }
+
+    /// Creates credentials sent in a custom header.
+    pub fn header(name: HeaderName, value: impl Into<String>) -> Self {
+        Self::Header {
+            name,
+            value: value.into(),
+        }
+    }
+
+    /// Sets the header carrying these credentials in `headers`, replacing any
+    /// previous value.
+    ///
+    /// # Errors
+    ///
+    /// Returns `RestApiError::InvalidHeader` if the secret isn't a valid header value.
+    pub fn apply(&self, headers: &mut HeaderMap) -> Result<(), RestApiError> {
+        let (name, value) = match self {
+            Self::Bearer(token) => (http::header::AUTHORIZATION, format!("Bearer {}", token)),
+            Self::Header { name, value } => (name.clone(), value.clone()),
+        };
+        let mut value = HeaderValue::try_from(value)
+            .map_err(|error| RestApiError::invalid_header(name.as_str(), error.to_string()))?;
+        value.set_sensitive(true);
+        headers.insert(name, value);
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Bearer(_) => f.write_str("Bearer(..)"),
+            Self::Header { name, .. } => f.debug_struct("Header").field("name", name).finish_non_exhaustive(),
+        }
+    }
+}
+
+/// Supplies the credentials for each request when it is sent, rather than when
+/// the client or request builder is created.
+///
+/// Unlike headers set through `ConfigureRequest`, which are copied into every
+/// request builder up front, a provider is asked again for every attempt, so
+/// secrets rotated at runtime are picked up by the next request. Closures
+/// returning `Result<Credentials, RestApiError>` are providers too.
+///
+/// Providers that fetch secrets asynchronously, such as from a secrets
+/// manager, implement [`AsyncCredentialsProvider`] instead.
+///
+/// # Example
+///
+/// ```
+/// use derive_rest_api::{Credentials, CredentialsProvider, RestApiError};
+///
+/// struct FromEnv;
+///
+/// impl CredentialsProvider for FromEnv {
+///     fn credentials(&self) -> Result<Credentials, RestApiError> {
+///         let token = std::env::var("API_TOKEN").map_err(RestApiError::credentials_error)?;
+///         Ok(Credentials::bearer(token))
+///     }
+/// }
+/// ```
+pub trait CredentialsProvider: Send + Sync + 'static {
+    /// Returns the credentials to send with the next request.
+    ///
+    /// # Errors
+    ///
+    /// An error fails the request, usually with `RestApiError::CredentialsError`.
+    fn credentials(&self) -> Result<Credentials, RestApiError>;
+}
+
+impl<F> CredentialsProvider for F
+where
+    F: Fn() -> Result<Credentials, RestApiError> + Send + Sync + 'static,
+{
+    fn credentials(&self) -> Result<Credentials, RestApiError> {
+        self()
+    }
+}
+
+/// Async version of [`CredentialsProvider`], used by `send_async()`.
+///
+/// Every [`CredentialsProvider`] is also an async provider.
+pub trait AsyncCredentialsProvider: Send + Sync + 'static {
+    /// Returns the credentials to send with the next request.
+    ///
+    /// # Errors
+    ///
+    /// An error fails the request, usually with `RestApiError::CredentialsError`.
+    fn credentials_async(&self) -> CredentialsFuture<'_>;
+}
+
+impl<P: CredentialsProvider> AsyncCredentialsProvider for P {
+    fn credentials_async(&self) -> CredentialsFuture<'_> {
+        Box::pin(std::future::ready(self.credentials()))
+    }
+}
+
+/// Credentials that can be replaced at runtime, for example by a background
+/// task renewing a token before it expires.
+///
+/// Clones share the credentials, so one clone can be given to a client while
+/// another is kept to [`rotate`](Self::rotate) them.
+///
+/// # Example
+///
+/// ```
+/// use derive_rest_api::{Credentials, CredentialsProvider, RotatingCredentials};
+///
+/// let credentials = RotatingCredentials::new(Credentials::bearer("first"));
+/// let provider = credentials.clone();
+///
+/// credentials.rotate(Credentials::bearer("second"));
+/// assert_eq!(provider.credentials().unwrap(), Credentials::bearer("second"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct RotatingCredentials {
+    current: Arc<RwLock<Credentials>>,
+}
+
+impl RotatingCredentials {
+    /// Creates rotating credentials starting with `credentials`.
+    pub fn new(credentials: Credentials) -> Self {
+        Self {
+            current: Arc::new(RwLock::new(credentials)),
+        }
+    }
+
+    /// Replaces the credentials sent with later requests.
+    pub fn rotate(&self, credentials: Credentials) {
+        *self.current.write().unwrap_or_else(|poisoned| poisoned.into_inner()) = credentials;
+    }
+}
+
+impl CredentialsProvider for RotatingCredentials {
+    fn credentials(&self) -> Result<Credentials, RestApiError> {
+        Ok(self.current.read().unwrap_or_else(|poisoned| poisoned.into_inner()).clone())
+    }
+}
+
+/// Returns `true` for headers that carry credentials: `Authorization`,
+/// `Proxy-Authorization`, `Cookie`, ones marked sensitive, and ones named like
+/// an API key, token, secret or password
+pub(crate) fn is_sensitive(name: &HeaderName, value: &HeaderValue) -> bool {
+    let name = name.as_str();
+    value.is_sensitive()
+        || matches!(name, "authorization" | "proxy-authorization" | "cookie")
+        || ["api-key", "apikey", "token", "secret", "password"]
+            .iter()
+            .any(|marker| name.contains(marker))
+}
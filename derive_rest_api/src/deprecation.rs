@@ -0,0 +1,202 @@
+//! Detection of the `Deprecation` (RFC 9745) and `Sunset` (RFC 8594)
+//! response headers as a client wrapper.
+
+use crate::{AsyncHttpClient, HttpClient, RequestOptions};
+use http::{HeaderMap, Response};
+use std::sync::Arc;
+
+/// An endpoint's announcement that it is deprecated or will be removed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeprecationNotice {
+    /// The HTTP method of the request that received the notice
+    pub method: String,
+    /// The URL of the request that received the notice
+    pub url: String,
+    /// The raw `Deprecation` header, such as `@1688169599` or an HTTP date
+    pub deprecation: Option<String>,
+    /// The raw `Sunset` header, an HTTP date after which the endpoint may be removed
+    pub sunset: Option<String>,
+    /// Targets of `Link` headers with `rel="deprecation"` or `rel="sunset"`,
+    /// which usually point at migration documentation
+    pub links: Vec<String>,
+}
+
+impl DeprecationNotice {
+    /// Reads a notice from response headers, if either `Deprecation` or
+    /// `Sunset` is present.
+    pub fn from_headers(method: &str, url: &str, headers: &HeaderMap) -> Option<Self> {
+        let header = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(|value| value.trim().to_string())
+        };
+        let deprecation = header("deprecation");
+        let sunset = header("sunset");
+        if deprecation.is_none() && sunset.is_none() {
+            return None;
+        }
+
+        let links = headers
+            .get_all(http::header::LINK)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .filter_map(deprecation_link)
+            .collect();
+
+        Some(Self {
+            method: method.to_string(),
+            url: url.to_string(),
+            deprecation,
+            sunset,
+            links,
+        })
+    }
+}
+
+impl std::fmt::Display for DeprecationNotice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} is deprecated", self.method, self.url)?;
+        if let Some(deprecation) = &self.deprecation {
+            write!(f, " (deprecation: {})", deprecation)?;
+        }
+        if let Some(sunset) = &self.sunset {
+            write!(f, " and will be removed after {}", sunset)?;
+        }
+        for link in &self.links {
+            write!(f, "; see {}", link)?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the target of a single `Link` value if its relation is
+/// `deprecation` or `sunset`.
+fn deprecation_link(link: &str) -> Option<String> {
+    let (target, params) = link.trim().split_once(';')?;
+    let target = target.trim().strip_prefix('<')?.strip_suffix('>')?;
+    let relevant = params.split(';').any(|param| {
+        param.trim().split_once('=').is_some_and(|(name, value)| {
+            name.trim().eq_ignore_ascii_case("rel")
+                && value
+                    .trim()
+                    .trim_matches('"')
+                    .split_whitespace()
+                    .any(|rel| rel.eq_ignore_ascii_case("deprecation") || rel.eq_ignore_ascii_case("sunset"))
+        })
+    });
+    relevant.then(|| target.to_string())
+}
+
+type DeprecationHandler = Arc<dyn Fn(&DeprecationNotice) + Send + Sync>;
+
+/// HTTP client wrapper that reports `Deprecation` and `Sunset` response headers.
+///
+/// Every response carrying either header is turned into a
+/// [`DeprecationNotice`] and passed to the handler. Without a handler, and
+/// with the `tracing` feature enabled, notices are logged as `tracing`
+/// warnings instead. Responses are returned unchanged.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use derive_rest_api::{DeprecationClient, UreqBlockingClient};
+///
+/// let http_client = DeprecationClient::new(UreqBlockingClient::new())
+///     .with_handler(|notice| eprintln!("warning: {}", notice));
+/// let client = MyApiClient::new_with_client(http_client);
+/// ```
+#[derive(Clone, Default)]
+pub struct DeprecationClient<C> {
+    inner: C,
+    handler: Option<DeprecationHandler>,
+}
+
+impl<C> DeprecationClient<C> {
+    /// Wraps `inner`, logging notices with `tracing` when that feature is enabled.
+    pub fn new(inner: C) -> Self {
+        Self { inner, handler: None }
+    }
+
+    /// Calls `handler` with every notice instead of logging it.
+    pub fn with_handler(mut self, handler: impl Fn(&DeprecationNotice) + Send + Sync + 'static) -> Self {
+        self.handler = Some(Arc::new(handler));
+        self
+    }
+
+    /// Returns a reference to the wrapped client.
+    pub fn inner(&self) -> &C {
+        &self.inner
+    }
+
+    fn inspect(&self, method: &str, url: &str, response: &Response<Vec<u8>>) {
+        let Some(notice) = DeprecationNotice::from_headers(method, url, response.headers()) else {
+            return;
+        };
+        match &self.handler {
+            Some(handler) => handler(&notice),
+            None => log_notice(&notice),
+        }
+    }
+}
+
+/// Logs a notice as a `tracing` warning.
+#[cfg(feature = "tracing")]
+fn log_notice(notice: &DeprecationNotice) {
+    tracing::warn!(
+        method = %notice.method,
+        url = %notice.url,
+        deprecation = notice.deprecation.as_deref(),
+        sunset = notice.sunset.as_deref(),
+        "{}",
+        notice
+    );
+}
+
+/// Notices are dropped when no handler is set and `tracing` is disabled.
+#[cfg(not(feature = "tracing"))]
+fn log_notice(_notice: &DeprecationNotice) {}
+
+impl<C: std::fmt::Debug> std::fmt::Debug for DeprecationClient<C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DeprecationClient")
+            .field("inner", &self.inner)
+            .field("handler", &self.handler.as_ref().map(|_| "<fn>"))
+            .finish()
+    }
+}
+
+impl<C: HttpClient> HttpClient for DeprecationClient<C> {
+    type Error = C::Error;
+
+    fn send(
+        &self,
+        method: &str,
+        url: &str,
+        headers: HeaderMap,
+        body: Option<Vec<u8>>,
+        options: RequestOptions,
+    ) -> Result<Response<Vec<u8>>, Self::Error> {
+        let response = self.inner.send(method, url, headers, body, options)?;
+        self.inspect(method, url, &response);
+        Ok(response)
+    }
+}
+
+impl<C: AsyncHttpClient + Sync> AsyncHttpClient for DeprecationClient<C> {
+    type Error = C::Error;
+
+    async fn send_async(
+        &self,
+        method: &str,
+        url: &str,
+        headers: HeaderMap,
+        body: Option<Vec<u8>>,
+        options: RequestOptions,
+    ) -> Result<Response<Vec<u8>>, Self::Error> {
+        let response = self.inner.send_async(method, url, headers, body, options).await?;
+        self.inspect(method, url, &response);
+        Ok(response)
+    }
+}
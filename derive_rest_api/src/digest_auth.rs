@@ -0,0 +1,508 @@
+//! HTTP Digest authentication (RFC 7616) as a client wrapper.
+
+use crate::{AsyncHttpClient, HttpClient, RequestOptions};
+use http::{HeaderMap, HeaderValue, Response, StatusCode};
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// HTTP client wrapper that answers Digest authentication challenges.
+///
+/// Requests are sent through the inner client. When a response is a
+/// `401 Unauthorized` carrying a `WWW-Authenticate: Digest` challenge, the
+/// request is retried once with a computed `Authorization` header. The
+/// challenge is remembered for the origin (scheme, host and port) and realm it
+/// came from, so later requests to that origin authenticate up front (with an
+/// incremented nonce count) and only need another round trip once the server
+/// issues a new nonce. Requests to other origins never get an `Authorization`
+/// header until their own server challenges them.
+///
+/// `MD5`, `SHA-256` and their `-sess` variants are supported, with the
+/// `auth` or `auth-int` quality of protection. A 401 without a usable Digest
+/// challenge is returned unchanged.
+///
+/// Every bundled client returns error statuses as responses, which this
+/// wrapper relies on; a custom client must do the same.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use derive_rest_api::{DigestAuthClient, UreqBlockingClient};
+///
+/// let http_client = DigestAuthClient::new(UreqBlockingClient::new(), "admin", "secret");
+/// let client = CameraClient::new_with_client(http_client);
+/// ```
+pub struct DigestAuthClient<C> {
+    inner: C,
+    username: String,
+    password: String,
+    state: Arc<Mutex<DigestCache>>,
+}
+
+impl<C> DigestAuthClient<C> {
+    /// Wraps `inner`, authenticating as `username` with `password`.
+    pub fn new(inner: C, username: impl Into<String>, password: impl Into<String>) -> Self {
+        Self {
+            inner,
+            username: username.into(),
+            password: password.into(),
+            state: Arc::default(),
+        }
+    }
+
+    /// Returns the wrapped client.
+    pub fn inner(&self) -> &C {
+        &self.inner
+    }
+
+    /// Builds the `Authorization` header for the last challenge from the origin of `url`, if any
+    fn cached_authorization(&self, method: &str, url: &str, body: Option<&[u8]>) -> Option<HeaderValue> {
+        let mut cache = self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let origin = origin(url);
+        let realm = cache.realms.get(&origin)?.clone();
+        let state = cache.challenges.get_mut(&(origin, realm))?;
+        state.nonce_count += 1;
+        self.authorization(&state.challenge, state.nonce_count, method, url, body)
+    }
+
+    /// Stores the challenge from a 401 response and builds its `Authorization` header
+    fn answer_challenge(
+        &self,
+        response: &Response<Vec<u8>>,
+        method: &str,
+        url: &str,
+        body: Option<&[u8]>,
+    ) -> Option<HeaderValue> {
+        if response.status() != StatusCode::UNAUTHORIZED {
+            return None;
+        }
+
+        let challenge = response
+            .headers()
+            .get_all(http::header::WWW_AUTHENTICATE)
+            .iter()
+            .filter_map(|value| Challenge::parse(value.to_str().ok()?))
+            // Prefer the strongest algorithm the server offers
+            .max_by_key(|challenge| challenge.algorithm == Algorithm::Sha256)?;
+
+        let authorization = self.authorization(&challenge, 1, method, url, body);
+        let mut cache = self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let origin = origin(url);
+        cache.realms.insert(origin.clone(), challenge.realm.clone());
+        cache.challenges.insert((origin, challenge.realm.clone()), DigestState { challenge, nonce_count: 1 });
+        authorization
+    }
+
+    fn authorization(
+        &self,
+        challenge: &Challenge,
+        nonce_count: u32,
+        method: &str,
+        url: &str,
+        body: Option<&[u8]>,
+    ) -> Option<HeaderValue> {
+        let header = challenge.authorization(
+            &self.username,
+            &self.password,
+            &method.to_uppercase(),
+            request_target(url),
+            body.unwrap_or_default(),
+            nonce_count,
+            &new_cnonce(),
+        );
+        HeaderValue::try_from(header).ok()
+    }
+}
+
+impl<C: Clone> Clone for DigestAuthClient<C> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            username: self.username.clone(),
+            password: self.password.clone(),
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl<C: Default> Default for DigestAuthClient<C> {
+    /// Wraps a default inner client with empty credentials; use [`new`](Self::new) to set them.
+    fn default() -> Self {
+        Self::new(C::default(), "", "")
+    }
+}
+
+impl<C: std::fmt::Debug> std::fmt::Debug for DigestAuthClient<C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Never print the password
+        f.debug_struct("DigestAuthClient")
+            .field("inner", &self.inner)
+            .field("username", &self.username)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<C: HttpClient> HttpClient for DigestAuthClient<C> {
+    type Error = C::Error;
+
+    fn send(
+        &self,
+        method: &str,
+        url: &str,
+        mut headers: HeaderMap,
+        body: Option<Vec<u8>>,
+        options: RequestOptions,
+    ) -> Result<Response<Vec<u8>>, Self::Error> {
+        if let Some(authorization) = self.cached_authorization(method, url, body.as_deref()) {
+            headers.insert(http::header::AUTHORIZATION, authorization);
+        }
+
+        let response = self.inner.send(method, url, headers.clone(), body.clone(), options)?;
+        match self.answer_challenge(&response, method, url, body.as_deref()) {
+            Some(authorization) => {
+                headers.insert(http::header::AUTHORIZATION, authorization);
+                self.inner.send(method, url, headers, body, options)
+            }
+            None => Ok(response),
+        }
+    }
+}
+
+impl<C: AsyncHttpClient + Sync> AsyncHttpClient for DigestAuthClient<C> {
+    type Error = C::Error;
+
+    async fn send_async(
+        &self,
+        method: &str,
+        url: &str,
+        mut headers: HeaderMap,
+        body: Option<Vec<u8>>,
+        options: RequestOptions,
+    ) -> Result<Response<Vec<u8>>, Self::Error> {
+        if let Some(authorization) = self.cached_authorization(method, url, body.as_deref()) {
+            headers.insert(http::header::AUTHORIZATION, authorization);
+        }
+
+        let response = self
+            .inner
+            .send_async(method, url, headers.clone(), body.clone(), options)
+            .await?;
+        match self.answer_challenge(&response, method, url, body.as_deref()) {
+            Some(authorization) => {
+                headers.insert(http::header::AUTHORIZATION, authorization);
+                self.inner.send_async(method, url, headers, body, options).await
+            }
+            None => Ok(response),
+        }
+    }
+}
+
+/// The challenges received, by origin and realm
+#[derive(Default)]
+struct DigestCache {
+    challenges: HashMap<(String, String), DigestState>,
+    /// The realm of the latest challenge from each origin, which requests to it answer up front
+    realms: HashMap<String, String>,
+}
+
+/// A challenge received, and how many requests have answered it
+struct DigestState {
+    challenge: Challenge,
+    nonce_count: u32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Algorithm {
+    Md5,
+    Sha256,
+}
+
+impl Algorithm {
+    fn hash(&self, data: &str) -> String {
+        self.hash_bytes(data.as_bytes())
+    }
+
+    fn hash_bytes(&self, data: &[u8]) -> String {
+        use sha2::Digest;
+
+        let digest = match self {
+            Algorithm::Md5 => md5::Md5::digest(data).to_vec(),
+            Algorithm::Sha256 => sha2::Sha256::digest(data).to_vec(),
+        };
+        digest.iter().fold(String::new(), |mut hex, byte| {
+            let _ = write!(hex, "{:02x}", byte);
+            hex
+        })
+    }
+}
+
+/// A parsed `WWW-Authenticate: Digest` challenge
+#[derive(Clone, Debug)]
+struct Challenge {
+    realm: String,
+    nonce: String,
+    opaque: Option<String>,
+    algorithm: Algorithm,
+    /// The `algorithm` value as sent, echoed back in the response
+    algorithm_name: Option<String>,
+    session: bool,
+    /// The chosen quality of protection (`auth` or `auth-int`), if the server offered one
+    qop: Option<&'static str>,
+}
+
+impl Challenge {
+    /// Parses a Digest challenge, returning `None` for other schemes or unsupported parameters
+    fn parse(header: &str) -> Option<Self> {
+        let (scheme, params) = header.trim().split_once(' ')?;
+        if !scheme.eq_ignore_ascii_case("digest") {
+            return None;
+        }
+
+        let mut realm = None;
+        let mut nonce = None;
+        let mut opaque = None;
+        let mut algorithm_name = None;
+        let mut qop_options = None;
+        for (key, value) in parse_params(params) {
+            match key.to_ascii_lowercase().as_str() {
+                "realm" => realm = Some(value),
+                "nonce" => nonce = Some(value),
+                "opaque" => opaque = Some(value),
+                "algorithm" => algorithm_name = Some(value),
+                "qop" => qop_options = Some(value),
+                _ => {}
+            }
+        }
+
+        let (algorithm, session) = match algorithm_name.as_deref().map(str::to_ascii_uppercase).as_deref() {
+            None | Some("MD5") => (Algorithm::Md5, false),
+            Some("MD5-SESS") => (Algorithm::Md5, true),
+            Some("SHA-256") => (Algorithm::Sha256, false),
+            Some("SHA-256-SESS") => (Algorithm::Sha256, true),
+            Some(_) => return None,
+        };
+
+        let qop = match qop_options {
+            None => None,
+            Some(options) => {
+                let options: Vec<&str> = options.split(',').map(str::trim).collect();
+                if options.iter().any(|option| option.eq_ignore_ascii_case("auth")) {
+                    Some("auth")
+                } else if options.iter().any(|option| option.eq_ignore_ascii_case("auth-int")) {
+                    Some("auth-int")
+                } else {
+                    return None;
+                }
+            }
+        };
+
+        Some(Self {
+            realm: realm?,
+            nonce: nonce?,
+            opaque,
+            algorithm,
+            algorithm_name,
+            session,
+            qop,
+        })
+    }
+
+    /// Computes the `Authorization` header value answering this challenge
+    #[allow(clippy::too_many_arguments)]
+    fn authorization(
+        &self,
+        username: &str,
+        password: &str,
+        method: &str,
+        uri: &str,
+        body: &[u8],
+        nonce_count: u32,
+        cnonce: &str,
+    ) -> String {
+        let hash = |data: &str| self.algorithm.hash(data);
+        let nc = format!("{:08x}", nonce_count);
+
+        let mut ha1 = hash(&format!("{}:{}:{}", username, self.realm, password));
+        if self.session {
+            ha1 = hash(&format!("{}:{}:{}", ha1, self.nonce, cnonce));
+        }
+        let ha2 = match self.qop {
+            Some("auth-int") => hash(&format!("{}:{}:{}", method, uri, self.algorithm.hash_bytes(body))),
+            _ => hash(&format!("{}:{}", method, uri)),
+        };
+        let response = match self.qop {
+            Some(qop) => hash(&format!("{}:{}:{}:{}:{}:{}", ha1, self.nonce, nc, cnonce, qop, ha2)),
+            None => hash(&format!("{}:{}:{}", ha1, self.nonce, ha2)),
+        };
+
+        let mut header = format!(
+            "Digest username=\"{}\", realm=\"{}\", nonce=\"{}\", uri=\"{}\"",
+            quote(username),
+            quote(&self.realm),
+            quote(&self.nonce),
+            quote(uri)
+        );
+        if let Some(algorithm) = &self.algorithm_name {
+            let _ = write!(header, ", algorithm={}", algorithm);
+        }
+        if let Some(qop) = self.qop {
+            let _ = write!(header, ", qop={}, nc={}, cnonce=\"{}\"", qop, nc, cnonce);
+        }
+        let _ = write!(header, ", response=\"{}\"", response);
+        if let Some(opaque) = &self.opaque {
+            let _ = write!(header, ", opaque=\"{}\"", quote(opaque));
+        }
+        header
+    }
+}
+
+/// Splits `key=value` challenge parameters, unquoting quoted values
+fn parse_params(params: &str) -> Vec<(String, String)> {
+    let mut result = Vec::new();
+    let mut chars = params.chars().peekable();
+    loop {
+        // Skip separators before the key
+        while chars.next_if(|c| *c == ',' || c.is_whitespace()).is_some() {}
+        let key: String = std::iter::from_fn(|| chars.next_if(|c| *c != '=')).collect();
+        if chars.next().is_none() {
+            break;
+        }
+
+        let mut value = String::new();
+        if chars.next_if_eq(&'"').is_some() {
+            while let Some(c) = chars.next() {
+                match c {
+                    '\\' => value.extend(chars.next()),
+                    '"' => break,
+                    _ => value.push(c),
+                }
+            }
+        } else {
+            value.extend(std::iter::from_fn(|| chars.next_if(|c| *c != ',')));
+        }
+        result.push((key.trim().to_string(), value.trim().to_string()));
+    }
+    result
+}
+
+/// Escapes a value for a quoted string
+fn quote(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Returns the path and query of `url`, which is what the `uri` parameter must match
+fn request_target(url: &str) -> &str {
+    let after_scheme = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let target = after_scheme.find('/').map_or("/", |index| &after_scheme[index..]);
+    target.split('#').next().unwrap_or(target)
+}
+
+/// Returns the origin of `url`, `scheme://host:port` in lowercase, with the scheme's default port if it has none
+fn origin(url: &str) -> String {
+    let (scheme, rest) = url.split_once("://").unwrap_or(("", url));
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or(rest);
+    let host = authority.rsplit_once('@').map_or(authority, |(_, host)| host);
+    let scheme = scheme.to_ascii_lowercase();
+    // A port follows the last colon, unless it's inside an IPv6 literal
+    let has_port = host.rsplit_once(':').is_some_and(|(_, port)| !port.contains(']'));
+    let default_port = match scheme.as_str() {
+        "http" => ":80",
+        "https" => ":443",
+        _ => "",
+    };
+    let port = if has_port { "" } else { default_port };
+    format!("{}://{}{}", scheme, host.to_ascii_lowercase(), port)
+}
+
+/// Returns a fresh client nonce
+fn new_cnonce() -> String {
+    static COUNT: AtomicU64 = AtomicU64::new(0);
+
+    // `RandomState` is seeded randomly per process; mixing in a counter and
+    // the time keeps successive nonces distinct
+    let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+    hasher.write_u64(COUNT.fetch_add(1, Ordering::Relaxed));
+    if let Some(nanos) = unix_nanos() {
+        hasher.write_u128(nanos);
+    }
+    format!("{:016x}", hasher.finish())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn unix_nanos() -> Option<u128> {
+    let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).ok()?;
+    Some(now.as_nanos())
+}
+
+// The system clock isn't available on wasm32-unknown-unknown
+#[cfg(target_arch = "wasm32")]
+fn unix_nanos() -> Option<u128> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rfc_2617_example() {
+        let challenge = Challenge::parse(
+            r#"Digest realm="testrealm@host.com", qop="auth,auth-int", nonce="dcd98b7102dd2f0e8b11d0f600bfb0c093", opaque="5ccc069c403ebaf9f0171e9517f40e41""#,
+        )
+        .unwrap();
+        let header = challenge.authorization(
+            "Mufasa",
+            "Circle Of Life",
+            "GET",
+            "/dir/index.html",
+            b"",
+            1,
+            "0a4f113b",
+        );
+        assert!(header.contains(r#"response="6629fae49393a05397450978507c4ef1""#));
+        assert!(header.contains("qop=auth, nc=00000001"));
+        assert!(header.contains(r#"opaque="5ccc069c403ebaf9f0171e9517f40e41""#));
+    }
+
+    #[test]
+    fn test_rfc_7616_sha256_example() {
+        let challenge = Challenge::parse(
+            r#"Digest realm="http-auth@example.org", qop="auth, auth-int", algorithm=SHA-256, nonce="7ypf/xlj9XXwfDPEoM4URrv/xwf94BcCAzFZH4GiTo0v", opaque="FQhe/qaU925kfnzjCev0ciny7QMkPqMAFRtzCUYo5tdS""#,
+        )
+        .unwrap();
+        let header = challenge.authorization(
+            "Mufasa",
+            "Circle of Life",
+            "GET",
+            "/dir/index.html",
+            b"",
+            1,
+            "f2/wE4q74E6zIJEtWaHKaf5wv/H5QzzpXusqGemxURZJ",
+        );
+        assert!(header.contains(r#"response="753927fa0e85d155564e2e272a28d1802ca10daf4496794697cf8db5856cb6c1""#));
+        assert!(header.contains("algorithm=SHA-256"));
+    }
+
+    #[test]
+    fn test_request_target() {
+        assert_eq!(request_target("http://camera.local/cgi-bin/snapshot?channel=1"), "/cgi-bin/snapshot?channel=1");
+        assert_eq!(request_target("https://example.com"), "/");
+        assert_eq!(request_target("https://example.com/a#frag"), "/a");
+    }
+
+    #[test]
+    fn test_origin() {
+        assert_eq!(origin("https://Camera.local/a?b"), "https://camera.local:443");
+        assert_eq!(origin("http://user@camera.local:8080/"), "http://camera.local:8080");
+        assert_eq!(origin("http://[::1]/status"), "http://[::1]:80");
+        assert_eq!(origin("http://[::1]:8080"), "http://[::1]:8080");
+    }
+
+    #[test]
+    fn test_rejects_other_schemes() {
+        assert!(Challenge::parse(r#"Basic realm="x""#).is_none());
+        assert!(Challenge::parse(r#"Digest realm="x", nonce="n", algorithm=SHA-512-256"#).is_none());
+    }
+}
@@ -1,9 +1,15 @@
 //! Error types for request building and execution.
 
 use std::error::Error as StdError;
+use std::fmt;
 
 /// Errors that can occur during request building and execution.
+///
+/// New variants may be added in minor releases, so matches need a wildcard
+/// arm. [`kind()`](Self::kind) sorts every variant into a stable
+/// [`ErrorKind`] for handling errors by category.
 #[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
 pub enum RestApiError {
     /// A required field was not set in the builder.
     #[error("Missing required field: {field}")]
@@ -13,6 +19,14 @@ pub enum RestApiError {
     #[error("Missing required path parameter: {param}")]
     MissingPathParameter { param: String },
 
+    /// A path parameter could not be converted into a path segment.
+    #[error("Invalid path parameter '{param}': {message}")]
+    InvalidPathParameter { param: String, message: String },
+
+    /// A header name or value is not valid for an HTTP request.
+    #[error("Invalid header '{name}': {message}")]
+    InvalidHeader { name: String, message: String },
+
     /// Query string serialization failed.
     #[error("Failed to serialize query parameters: {source}")]
     QuerySerializationError {
@@ -20,6 +34,13 @@ pub enum RestApiError {
         source: serde_qs::Error,
     },
 
+    /// A [`QueryEncoder`](crate::QueryEncoder) couldn't encode the query parameters.
+    #[error("Failed to encode query parameters: {source}")]
+    QueryEncodingError {
+        #[source]
+        source: Box<dyn StdError + Send + Sync>,
+    },
+
     /// Request body serialization failed.
     #[error("Failed to serialize request body: {source}")]
     BodySerializationError {
@@ -27,11 +48,14 @@ pub enum RestApiError {
         source: serde_json::Error,
     },
 
-    /// Request body serialization failed.
-    #[error("Failed to deserialize response body: {source}")]
+    /// Response body deserialization failed.
+    ///
+    /// With `#[request_builder(lenient_response)]`, `context` locates the failure in the body.
+    #[error("Failed to deserialize response body: {source}{}", crate::lenient::describe(context))]
     ResponseDeserializationError {
         #[source]
         source: serde_json::Error,
+        context: Option<Box<crate::DeserializationContext>>,
     },
 
     /// Field validation failed.
@@ -49,11 +73,150 @@ pub enum RestApiError {
         source: Box<RestApiError>,
     },
 
+    /// The request requires HTTPS, but its URL uses another scheme.
+    ///
+    /// Raised before anything is sent, so credentials never go out in the clear.
+    #[error("Refusing to send a request that requires HTTPS to {url}")]
+    InsecureUrl { url: String },
+
+    /// The response body was larger than the configured maximum size.
+    #[error("Response body exceeds the limit of {limit} bytes")]
+    ResponseTooLarge { limit: u64 },
+
+    /// A checksum header on the response did not match the received body.
+    #[error("Checksum mismatch for '{header}': expected {expected}, got {actual}")]
+    ChecksumMismatch {
+        header: String,
+        expected: String,
+        actual: String,
+    },
+
+    /// A GraphQL response reported errors.
+    ///
+    /// `data` holds whatever partial result the server returned with them.
+    #[error("GraphQL request failed: {}", crate::graphql::summarize(errors))]
+    GraphQlErrors {
+        errors: Vec<crate::GraphQlError>,
+        data: Option<serde_json::Value>,
+    },
+
+    /// A batch response couldn't be split into the responses to its requests.
+    #[error("Invalid batch response: {message}")]
+    InvalidBatchResponse { message: String },
+
+    /// A polled long-running operation did not finish within its timeout.
+    #[error("Operation did not complete within {timeout:?}")]
+    PollTimeout { timeout: std::time::Duration },
+
+    /// The request was cancelled through its `CancellationToken` before it completed.
+    #[error("Request was cancelled")]
+    Cancelled,
+
+    /// The server answered with a client or server error status, as reported
+    /// by [`RestApiError::error_for_status`].
+    #[error("Server responded with status {status}")]
+    ErrorStatus { status: http::StatusCode, body: Vec<u8> },
+
+    /// An API client's health check got an unsuccessful status.
+    #[error("Health check failed with status {status}")]
+    Unhealthy { status: http::StatusCode },
+
+    /// An API client's health check didn't pass within the timeout of `wait_until_ready()`.
+    ///
+    /// `source` is the error from the last check.
+    #[error("Not ready within {timeout:?}: {source}")]
+    NotReady {
+        timeout: std::time::Duration,
+        #[source]
+        source: Box<RestApiError>,
+    },
+
+    /// A `CredentialsProvider` couldn't supply credentials for a request.
+    #[error("Failed to get credentials: {source}")]
+    CredentialsError {
+        #[source]
+        source: Box<dyn StdError + Send + Sync>,
+    },
+
+    /// An API client config couldn't be loaded from its file or the environment.
+    #[error("Failed to load configuration: {source}")]
+    ConfigError {
+        #[source]
+        source: Box<dyn StdError + Send + Sync>,
+    },
+
+    /// The API couldn't be reached, so the request was saved in the client's
+    /// [`OfflineQueue`](crate::OfflineQueue), to be sent once it can be.
+    #[error("Request queued to be sent once back online (id {id})")]
+    QueuedOffline { id: u64 },
+
+    /// An [`OfflineQueue`](crate::OfflineQueue) couldn't be read or written.
+    #[error("Offline queue failed: {source}")]
+    OfflineQueueError {
+        #[source]
+        source: std::io::Error,
+    },
+
+    /// `send_background()` was called on a client without a
+    /// [`BackgroundSender`](crate::BackgroundSender).
+    #[error("No background sender configured. Use .background_sender() on the client builder to set one.")]
+    NoBackgroundSender,
+
+    /// A [`BackgroundSender`](crate::BackgroundSender) already had as many
+    /// requests waiting as it can hold, so the request was dropped.
+    #[error("Background queue is full ({capacity} requests)")]
+    BackgroundQueueFull { capacity: usize },
+
     /// HTTP request failed with a client-specific error.
     ///
     /// This wraps errors from the underlying HTTP client implementation.
-    #[error("HTTP request failed: {0}")]
-    HttpError(Box<dyn StdError + Send + Sync>),
+    /// Requests sent with `send()` or `send_async()` have a `context` saying
+    /// how long they took and how much they transferred before failing.
+    #[error("HTTP request failed: {source}{}", crate::http_error_context::describe(context))]
+    HttpError {
+        source: Box<dyn StdError + Send + Sync>,
+        context: Option<Box<crate::HttpErrorContext>>,
+    },
+}
+
+/// The category of a [`RestApiError`], returned by [`RestApiError::kind`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The request couldn't be put together: a missing field, path parameter,
+    /// base URL or credential, an invalid header, or a URL that isn't allowed.
+    Build,
+
+    /// The query parameters or request body couldn't be serialized.
+    Serialize,
+
+    /// The request couldn't be sent or its response received: the HTTP client
+    /// failed, the request was cancelled, timed out or queued for later.
+    Transport,
+
+    /// The server answered with something unusable: an unhealthy status, a
+    /// body that's too large or fails its checksum, or GraphQL errors.
+    Http,
+
+    /// The response body couldn't be deserialized.
+    Deserialize,
+
+    /// A field failed validation.
+    Validation,
+}
+
+impl ErrorKind {
+    /// Returns the kind's name in snake case, such as `transport`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Build => "build",
+            Self::Serialize => "serialize",
+            Self::Transport => "transport",
+            Self::Http => "http",
+            Self::Deserialize => "deserialize",
+            Self::Validation => "validation",
+        }
+    }
 }
 
 impl RestApiError {
@@ -71,6 +234,27 @@ impl RestApiError {
         }
     }
 
+    /// Creates a new `InvalidPathParameter` error.
+    pub fn invalid_path_parameter(param: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::InvalidPathParameter {
+            param: param.into(),
+            message: message.into(),
+        }
+    }
+
+    /// Creates a new `InvalidHeader` error.
+    pub fn invalid_header(name: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::InvalidHeader {
+            name: name.into(),
+            message: message.into(),
+        }
+    }
+
+    /// Creates a new `ResponseDeserializationError` without context.
+    pub fn response_deserialization_error(source: serde_json::Error) -> Self {
+        Self::ResponseDeserializationError { source, context: None }
+    }
+
     /// Creates a new `ValidationError`.
     pub fn validation_error(field: impl Into<String>, message: impl Into<String>) -> Self {
         Self::ValidationError {
@@ -81,6 +265,318 @@ impl RestApiError {
 
     /// Creates a new `HttpError` from any error type.
     pub fn http_error(error: impl StdError + Send + Sync + 'static) -> Self {
-        Self::HttpError(Box::new(error))
+        Self::HttpError { source: Box::new(error), context: None }
+    }
+
+    /// Returns the category of this error.
+    ///
+    /// A `NotReady` error takes the category of the last check's error.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::MissingField { .. }
+            | Self::MissingPathParameter { .. }
+            | Self::InvalidPathParameter { .. }
+            | Self::InvalidHeader { .. }
+            | Self::MissingBaseUrl
+            | Self::UrlBuildError { .. }
+            | Self::InsecureUrl { .. }
+            | Self::CredentialsError { .. }
+            | Self::ConfigError { .. }
+            | Self::NoBackgroundSender => ErrorKind::Build,
+            Self::QuerySerializationError { .. }
+            | Self::QueryEncodingError { .. }
+            | Self::BodySerializationError { .. } => ErrorKind::Serialize,
+            Self::PollTimeout { .. }
+            | Self::Cancelled
+            | Self::QueuedOffline { .. }
+            | Self::OfflineQueueError { .. }
+            | Self::BackgroundQueueFull { .. }
+            | Self::HttpError { .. } => ErrorKind::Transport,
+            Self::ResponseTooLarge { .. }
+            | Self::ChecksumMismatch { .. }
+            | Self::GraphQlErrors { .. }
+            | Self::ErrorStatus { .. }
+            | Self::Unhealthy { .. } => ErrorKind::Http,
+            Self::ResponseDeserializationError { .. } | Self::InvalidBatchResponse { .. } => ErrorKind::Deserialize,
+            Self::ValidationError { .. } => ErrorKind::Validation,
+            Self::NotReady { source, .. } => source.kind(),
+        }
+    }
+
+    /// Returns `true` if the request may succeed if sent again unchanged.
+    ///
+    /// That is when the HTTP client timed out or its connection was refused,
+    /// reset or closed early, when the [`status`](Self::status) is `429`,
+    /// `502`, `503` or `504`, or when a `NotReady` error's last check failed
+    /// that way. Other
+    /// HTTP client failures, such as name resolution or TLS errors, are taken
+    /// to be permanent. The built-in [`RetryPolicy`](crate::RetryPolicy) retries
+    /// errors for which this is `true`.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Self::HttpError { source, .. } => {
+                let mut error: Option<&(dyn StdError + 'static)> = Some(source.as_ref());
+                while let Some(current) = error {
+                    if is_transient_client_error(current) {
+                        return true;
+                    }
+                    error = current.source();
+                }
+                false
+            }
+            Self::ErrorStatus { status, .. } | Self::Unhealthy { status } => is_transient_status(*status),
+            Self::NotReady { source, .. } => source.is_transient(),
+            _ => false,
+        }
+    }
+
+    /// Returns the response status this error carries, if it's an
+    /// `ErrorStatus` or `Unhealthy` error, or a `NotReady` wrapping one.
+    pub fn status(&self) -> Option<http::StatusCode> {
+        match self {
+            Self::ErrorStatus { status, .. } | Self::Unhealthy { status } => Some(*status),
+            Self::NotReady { source, .. } => source.status(),
+            _ => None,
+        }
+    }
+
+    /// Turns a response with a client or server error status into an
+    /// `ErrorStatus` error, passing other responses through.
+    ///
+    /// Requests return the body whatever the status, so this is for checking
+    /// the response of `send_raw()`, e.g. in a retry loop driven by
+    /// [`is_transient`](Self::is_transient).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use derive_rest_api::RestApiError;
+    /// use derive_rest_api::http::{Response, StatusCode};
+    ///
+    /// let response = Response::builder().status(StatusCode::SERVICE_UNAVAILABLE).body(Vec::new()).unwrap();
+    /// let error = RestApiError::error_for_status(response).unwrap_err();
+    /// assert_eq!(error.status(), Some(StatusCode::SERVICE_UNAVAILABLE));
+    /// assert!(error.is_transient());
+    /// ```
+    pub fn error_for_status(response: http::Response<Vec<u8>>) -> Result<http::Response<Vec<u8>>, Self> {
+        let status = response.status();
+        if status.is_client_error() || status.is_server_error() {
+            Err(Self::ErrorStatus { status, body: response.into_body() })
+        } else {
+            Ok(response)
+        }
+    }
+
+    /// Returns the HTTP client's error, if this is an `HttpError` or a
+    /// `NotReady` wrapping one.
+    pub fn http_source(&self) -> Option<&(dyn StdError + Send + Sync + 'static)> {
+        match self {
+            Self::HttpError { source, .. } => Some(source.as_ref()),
+            Self::NotReady { source, .. } => source.http_source(),
+            _ => None,
+        }
+    }
+
+    /// Finds an error of type `E` in the HTTP client error's source chain.
+    ///
+    /// Client wrappers such as `FaultInjectingClient` keep the wrapped
+    /// client's error as their source, so this finds a backend's error
+    /// however many wrappers it went through.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use derive_rest_api::RestApiError;
+    ///
+    /// let error = RestApiError::http_error(std::io::Error::from(std::io::ErrorKind::TimedOut));
+    /// let io_error = error.find_http_source::<std::io::Error>().unwrap();
+    /// assert_eq!(io_error.kind(), std::io::ErrorKind::TimedOut);
+    /// ```
+    pub fn find_http_source<E: StdError + 'static>(&self) -> Option<&E> {
+        let mut error: Option<&(dyn StdError + 'static)> = Some(self.http_source()?);
+        while let Some(current) = error {
+            if let Some(found) = current.downcast_ref::<E>() {
+                return Some(found);
+            }
+            error = current.source();
+        }
+        None
+    }
+
+    /// Returns the `reqwest` error this request failed with, to tell e.g.
+    /// connect failures and timeouts apart with `is_connect()` and
+    /// `is_timeout()`.
+    #[cfg(any(feature = "reqwest-blocking", feature = "reqwest-async"))]
+    pub fn as_reqwest_error(&self) -> Option<&reqwest::Error> {
+        self.find_http_source()
+    }
+
+    /// Returns the `ureq` error this request failed with.
+    #[cfg(feature = "ureq-blocking")]
+    pub fn as_ureq_error(&self) -> Option<&ureq::Error> {
+        self.find_http_source()
+    }
+
+    /// Returns the `isahc` error this request failed with, whose `kind()`
+    /// tells TLS, name resolution and timeout failures apart.
+    #[cfg(feature = "isahc")]
+    pub fn as_isahc_error(&self) -> Option<&isahc::Error> {
+        self.find_http_source()
+    }
+
+    /// Returns the `attohttpc` error this request failed with.
+    #[cfg(feature = "attohttpc-blocking")]
+    pub fn as_attohttpc_error(&self) -> Option<&attohttpc::Error> {
+        self.find_http_source()
+    }
+
+    /// Returns the `surf` error this request failed with. A request that
+    /// timed out has none; see [`SurfClientError::Timeout`](crate::SurfClientError::Timeout).
+    #[cfg(feature = "surf")]
+    pub fn as_surf_error(&self) -> Option<&surf::Error> {
+        match self.find_http_source()? {
+            crate::SurfClientError::Request(error) => Some(error),
+            crate::SurfClientError::Timeout => None,
+        }
+    }
+
+    /// Creates a new `CredentialsError` error.
+    pub fn credentials_error(error: impl Into<Box<dyn StdError + Send + Sync>>) -> Self {
+        Self::CredentialsError { source: error.into() }
+    }
+
+    /// Creates a new `OfflineQueueError` error.
+    pub fn offline_queue_error(error: impl Into<std::io::Error>) -> Self {
+        Self::OfflineQueueError { source: error.into() }
+    }
+
+    /// Creates a new `QueryEncodingError` error.
+    pub fn query_encoding_error(error: impl Into<Box<dyn StdError + Send + Sync>>) -> Self {
+        Self::QueryEncodingError { source: error.into() }
+    }
+}
+
+/// Statuses worth retrying the request for: rate limiting and a gateway that
+/// couldn't reach or wait for the server.
+pub(crate) const TRANSIENT_STATUSES: [http::StatusCode; 4] = [
+    http::StatusCode::TOO_MANY_REQUESTS,
+    http::StatusCode::BAD_GATEWAY,
+    http::StatusCode::SERVICE_UNAVAILABLE,
+    http::StatusCode::GATEWAY_TIMEOUT,
+];
+
+fn is_transient_status(status: http::StatusCode) -> bool {
+    TRANSIENT_STATUSES.contains(&status)
+}
+
+/// Returns `true` if `error`, one link of an HTTP client error's source chain,
+/// is a timeout or a failed connection
+fn is_transient_client_error(error: &(dyn StdError + 'static)) -> bool {
+    if let Some(error) = error.downcast_ref::<std::io::Error>() {
+        return is_transient_io_error(error);
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    if error.is::<crate::fault_injection::InjectedFault>() {
+        return true;
+    }
+    #[cfg(any(feature = "reqwest-blocking", feature = "reqwest-async"))]
+    if let Some(error) = error.downcast_ref::<reqwest::Error>() {
+        return error.is_timeout();
+    }
+    #[cfg(feature = "ureq-blocking")]
+    if let Some(error) = error.downcast_ref::<ureq::Error>() {
+        return match error {
+            ureq::Error::Timeout(_) => true,
+            ureq::Error::Io(error) => is_transient_io_error(error),
+            _ => false,
+        };
+    }
+    #[cfg(feature = "isahc")]
+    if let Some(error) = error.downcast_ref::<isahc::Error>() {
+        return matches!(error.kind(), isahc::error::ErrorKind::Timeout | isahc::error::ErrorKind::ConnectionFailed);
+    }
+    #[cfg(feature = "attohttpc-blocking")]
+    if let Some(error) = error.downcast_ref::<attohttpc::Error>() {
+        return matches!(error.kind(), attohttpc::ErrorKind::Io(error) if is_transient_io_error(error));
+    }
+    #[cfg(feature = "surf")]
+    if let Some(error) = error.downcast_ref::<crate::SurfClientError>() {
+        return matches!(error, crate::SurfClientError::Timeout);
+    }
+    #[cfg(all(feature = "wasm-fetch", target_arch = "wasm32"))]
+    if let Some(error) = error.downcast_ref::<crate::WasmFetchError>() {
+        return matches!(error, crate::WasmFetchError::Timeout);
+    }
+    false
+}
+
+fn is_transient_io_error(error: &std::io::Error) -> bool {
+    use std::io::ErrorKind;
+
+    matches!(
+        error.kind(),
+        ErrorKind::TimedOut
+            | ErrorKind::ConnectionRefused
+            | ErrorKind::ConnectionReset
+            | ErrorKind::ConnectionAborted
+            | ErrorKind::BrokenPipe
+            | ErrorKind::UnexpectedEof
+    )
+}
+
+/// Every missing-field and validation error found by a builder's `try_build()`.
+///
+/// Unlike `build()`, which stops at the first problem, `try_build()` checks all
+/// fields so that every issue can be reported at once. The errors are listed in
+/// field declaration order and there is always at least one.
+#[derive(Debug)]
+pub struct BuildErrors {
+    errors: Vec<RestApiError>,
+}
+
+impl BuildErrors {
+    /// Collects `errors`, which must not be empty
+    pub(crate) fn new(errors: Vec<RestApiError>) -> Self {
+        debug_assert!(!errors.is_empty(), "BuildErrors must contain at least one error");
+        Self { errors }
+    }
+
+    /// Returns the collected errors.
+    pub fn errors(&self) -> &[RestApiError] {
+        &self.errors
+    }
+
+    /// Consumes `self`, returning the collected errors.
+    pub fn into_errors(self) -> Vec<RestApiError> {
+        self.errors
+    }
+
+    /// Consumes `self`, returning the first error (the one `build()` reports).
+    pub fn into_first(self) -> RestApiError {
+        self.errors
+            .into_iter()
+            .next()
+            .expect("BuildErrors always contains at least one error")
+    }
+}
+
+impl fmt::Display for BuildErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Failed to build request ({} errors)", self.errors.len())?;
+        for error in &self.errors {
+            write!(f, "\n- {}", error)?;
+        }
+        Ok(())
+    }
+}
+
+impl StdError for BuildErrors {}
+
+impl IntoIterator for BuildErrors {
+    type Item = RestApiError;
+    type IntoIter = std::vec::IntoIter<RestApiError>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.errors.into_iter()
     }
 }
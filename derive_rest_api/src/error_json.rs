@@ -0,0 +1,158 @@
+//! Structured JSON rendering of errors for log aggregation.
+
+use crate::RestApiError;
+use serde_json::{Map, Value, json};
+use std::error::Error as StdError;
+
+impl RestApiError {
+    /// Renders the error as a JSON object for structured logging.
+    ///
+    /// The object always has the error's `kind` (as in [`ErrorKind`](crate::ErrorKind),
+    /// in snake case), its `variant`, its `message` and a `chain` of the
+    /// messages of its sources, outermost first. Details are added when the
+    /// error knows them: the `endpoint`, the `url` of the last attempt and the
+    /// `elapsed_ms`, `attempts`, `bytes_sent` and `bytes_received` of an
+    /// `HttpError`, a `status`, and the `field`, `param` or `header` at fault.
+    ///
+    /// The `status` is the error's [`status`](RestApiError::status), which
+    /// only `ErrorStatus` and `Unhealthy` errors, and `NotReady` errors caused
+    /// by one, have: requests return responses with unsuccessful statuses to
+    /// the caller rather than failing.
+    ///
+    /// Every URL in the object has its query values replaced with `REDACTED`
+    /// and its fragment dropped, including URLs quoted anywhere in `message`
+    /// and `chain`, so tokens and other secrets in query strings stay out of
+    /// logs.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use derive_rest_api::RestApiError;
+    ///
+    /// let error = RestApiError::InsecureUrl { url: "http://api.example.com/users?token=abc".to_string() };
+    /// let json = error.to_json();
+    /// assert_eq!(json["kind"], "build");
+    /// assert_eq!(json["variant"], "InsecureUrl");
+    /// assert_eq!(json["url"], "http://api.example.com/users?token=REDACTED");
+    /// ```
+    pub fn to_json(&self) -> Value {
+        let mut object = Map::new();
+        object.insert("kind".to_string(), json!(self.kind().as_str()));
+        object.insert("variant".to_string(), json!(self.variant_name()));
+        object.insert("message".to_string(), json!(redact_urls(&self.to_string())));
+
+        let mut chain = Vec::new();
+        let mut source = self.source();
+        while let Some(error) = source {
+            chain.push(json!(redact_urls(&error.to_string())));
+            source = error.source();
+        }
+        object.insert("chain".to_string(), Value::Array(chain));
+
+        match self {
+            Self::HttpError { context: Some(context), .. } => {
+                if let Some(endpoint) = context.endpoint {
+                    object.insert("endpoint".to_string(), json!(endpoint));
+                }
+                object.insert("url".to_string(), json!(redact_query(&context.url)));
+                if let Some(elapsed) = context.elapsed {
+                    object.insert("elapsed_ms".to_string(), json!(elapsed.as_millis() as u64));
+                }
+                object.insert("attempts".to_string(), json!(context.attempts));
+                object.insert("bytes_sent".to_string(), json!(context.bytes_sent));
+                object.insert("bytes_received".to_string(), json!(context.bytes_received));
+            }
+            Self::InsecureUrl { url } => {
+                object.insert("url".to_string(), json!(redact_query(url)));
+            }
+            Self::MissingField { field } | Self::ValidationError { field, .. } => {
+                object.insert("field".to_string(), json!(field));
+            }
+            Self::MissingPathParameter { param } | Self::InvalidPathParameter { param, .. } => {
+                object.insert("param".to_string(), json!(param));
+            }
+            Self::InvalidHeader { name, .. } | Self::ChecksumMismatch { header: name, .. } => {
+                object.insert("header".to_string(), json!(name));
+            }
+            _ => {}
+        }
+        if let Some(status) = self.status() {
+            object.insert("status".to_string(), json!(status.as_u16()));
+        }
+        Value::Object(object)
+    }
+
+    /// Returns the name of the error's variant, such as `HttpError`
+    fn variant_name(&self) -> &'static str {
+        match self {
+            Self::MissingField { .. } => "MissingField",
+            Self::MissingPathParameter { .. } => "MissingPathParameter",
+            Self::InvalidPathParameter { .. } => "InvalidPathParameter",
+            Self::InvalidHeader { .. } => "InvalidHeader",
+            Self::QuerySerializationError { .. } => "QuerySerializationError",
+            Self::QueryEncodingError { .. } => "QueryEncodingError",
+            Self::BodySerializationError { .. } => "BodySerializationError",
+            Self::ResponseDeserializationError { .. } => "ResponseDeserializationError",
+            Self::ValidationError { .. } => "ValidationError",
+            Self::MissingBaseUrl => "MissingBaseUrl",
+            Self::UrlBuildError { .. } => "UrlBuildError",
+            Self::InsecureUrl { .. } => "InsecureUrl",
+            Self::ResponseTooLarge { .. } => "ResponseTooLarge",
+            Self::ChecksumMismatch { .. } => "ChecksumMismatch",
+            Self::GraphQlErrors { .. } => "GraphQlErrors",
+            Self::InvalidBatchResponse { .. } => "InvalidBatchResponse",
+            Self::PollTimeout { .. } => "PollTimeout",
+            Self::Cancelled => "Cancelled",
+            Self::ErrorStatus { .. } => "ErrorStatus",
+            Self::Unhealthy { .. } => "Unhealthy",
+            Self::NotReady { .. } => "NotReady",
+            Self::CredentialsError { .. } => "CredentialsError",
+            Self::ConfigError { .. } => "ConfigError",
+            Self::QueuedOffline { .. } => "QueuedOffline",
+            Self::OfflineQueueError { .. } => "OfflineQueueError",
+            Self::NoBackgroundSender => "NoBackgroundSender",
+            Self::BackgroundQueueFull { .. } => "BackgroundQueueFull",
+            Self::HttpError { .. } => "HttpError",
+        }
+    }
+}
+
+/// Redacts the query of each URL quoted in `text`, as `redact_query` does
+///
+/// A URL runs from its scheme to the next whitespace, quote, bracket or angle
+/// bracket, so `(https://host/?token=abc)` keeps its closing parenthesis.
+fn redact_urls(text: &str) -> String {
+    let mut redacted = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(separator) = rest.find("://") {
+        let start = rest[..separator]
+            .rfind(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.')))
+            .map_or(0, |i| i + 1);
+        let end = rest[separator..]
+            .find(|c: char| c.is_whitespace() || matches!(c, '"' | '\'' | '`' | '(' | ')' | '<' | '>' | '[' | ']'))
+            .map_or(rest.len(), |i| separator + i);
+        redacted.push_str(&rest[..start]);
+        redacted.push_str(&redact_query(&rest[start..end]));
+        rest = &rest[end..];
+    }
+    redacted.push_str(rest);
+    redacted
+}
+
+/// Replaces the value of each query parameter in `url` with `REDACTED`,
+/// dropping any fragment
+fn redact_query(url: &str) -> String {
+    let url = url.split_once('#').map_or(url, |(url, _)| url);
+    let Some((path, query)) = url.split_once('?') else {
+        return url.to_string();
+    };
+    let query = query
+        .split('&')
+        .map(|pair| match pair.split_once('=') {
+            Some((name, _)) => format!("{}=REDACTED", name),
+            None => pair.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("&");
+    format!("{}?{}", path, query)
+}
@@ -0,0 +1,95 @@
+//! Structured events through the lifecycle of each request.
+
+use http::StatusCode;
+use std::time::{Duration, Instant};
+
+/// Something that happened to a request, from [`EventSubscriber`]s.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestEvent {
+    /// The request struct the request was built from, such as `GetUser`
+    pub endpoint: &'static str,
+    /// The HTTP method, such as `GET`
+    pub method: String,
+    /// The URL path and query string, relative to the base URL
+    pub path: String,
+    /// When the event happened, by the client's [`Clock`](crate::Clock)
+    pub at: Instant,
+    /// What happened
+    pub kind: RequestEventKind,
+}
+
+/// The stages of a request reported by [`RequestEvent`].
+///
+/// Every request starts with `Built` and ends with either `Completed` or
+/// `Failed`, with a `Sent` for each attempt and a `Retried` before each retry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RequestEventKind {
+    /// The request was built and is about to be sent
+    Built,
+    /// An attempt was handed to the HTTP client, after credentials and middleware
+    Sent {
+        /// The full URL the attempt went to, which may be a failover's base URL
+        url: String,
+        /// The attempt's number, starting at 1
+        attempt: u32,
+    },
+    /// An attempt failed and the request will be sent again after `delay`
+    Retried {
+        /// The number of the retry about to happen, starting at 1
+        retry: u32,
+        /// How long the request waits before it
+        delay: Duration,
+        /// The status of the failed attempt, or `None` if the HTTP client failed
+        status: Option<StatusCode>,
+    },
+    /// The request failed without a response
+    Failed {
+        /// The error returned, as text
+        error: String,
+    },
+    /// A response was received, which may have an error status
+    Completed {
+        /// The response's status
+        status: StatusCode,
+        /// How long the request took, including retries
+        duration: Duration,
+    },
+}
+
+/// Code told about every stage of every request a client sends, for audit
+/// logging and custom telemetry.
+///
+/// Subscribers are called synchronously, in the order they were added, as the
+/// request goes through each stage. Closures taking a `&RequestEvent` are
+/// subscribers. Health checks are reported with the endpoint `health_check`.
+///
+/// Timestamps come from the client's [`Clock`](crate::Clock). On `wasm32`,
+/// where the system clock can't be read, give the client another clock when
+/// subscribing.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use derive_rest_api::{RequestEvent, RequestEventKind};
+///
+/// let client = UsersClient::builder()
+///     .on_event(|event: &RequestEvent| {
+///         if let RequestEventKind::Completed { status, .. } = &event.kind {
+///             audit_log(event.endpoint, &event.path, *status);
+///         }
+///     })
+///     .build()?;
+/// ```
+pub trait EventSubscriber: Send + Sync + 'static {
+    /// Called with each event as it happens.
+    fn on_event(&self, event: &RequestEvent);
+}
+
+impl<F> EventSubscriber for F
+where
+    F: Fn(&RequestEvent) + Send + Sync + 'static,
+{
+    fn on_event(&self, event: &RequestEvent) {
+        self(event)
+    }
+}
@@ -0,0 +1,391 @@
+//! Failover and load balancing between redundant base URLs.
+
+use crate::{AsyncHttpClient, Clock, HttpClient, RequestOptions, RequestParts};
+use http::{HeaderMap, Response};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+
+/// An ordered list of base URLs serving the same API, such as one per region.
+///
+/// Requests go to the active base URL, the first one to begin with. When a
+/// request fails with a client error (such as a refused connection) or a
+/// `5xx` status, it is retried against the next base URL in the list, which
+/// becomes active if it succeeds. If every base URL fails, the last result is
+/// returned.
+///
+/// By default a client stays on the base URL that last worked. With
+/// [`recover_after`](Self::recover_after) it returns to the primary (first)
+/// base URL once it has been on another for that long, optionally checking
+/// the primary's health first with [`health_check`](Self::health_check).
+///
+/// For services load balanced across the base URLs rather than behind a
+/// single address, [`round_robin`](Self::round_robin) and
+/// [`weighted`](Self::weighted) spread requests over them instead. Each
+/// request starts at the next base URL in turn and fails over to the others
+/// from there; recovery doesn't apply.
+///
+/// Clones share which base URL is active and whose turn it is.
+///
+/// # Example
+///
+/// ```
+/// use derive_rest_api::Failover;
+/// use std::time::Duration;
+///
+/// let failover = Failover::new(["https://eu.api.example.com", "https://us.api.example.com"])
+///     .recover_after(Duration::from_secs(60))
+///     .health_check("/health");
+///
+/// assert_eq!(failover.active_base_url(), "https://eu.api.example.com");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Failover {
+    base_urls: Arc<[String]>,
+    recover_after: Option<Duration>,
+    health_path: Option<String>,
+    /// Per base URL weights when balancing requests across them
+    weights: Option<Arc<[u32]>>,
+    clock: Option<Arc<dyn Clock>>,
+    state: Arc<Mutex<FailoverState>>,
+}
+
+#[derive(Debug, Default)]
+struct FailoverState {
+    active: usize,
+    /// When the active base URL stopped being the primary
+    #[cfg(not(target_arch = "wasm32"))]
+    switched_at: Option<Instant>,
+    /// Smooth weighted round-robin counters, one per base URL
+    turns: Vec<i64>,
+}
+
+/// What a request should try, decided before it is sent
+struct Plan {
+    /// Base URL indices in the order to try them
+    order: Vec<usize>,
+    /// Whether the primary is being tried again after a failover
+    recovering: bool,
+    /// URL to probe before returning to the primary
+    probe: Option<String>,
+}
+
+impl Failover {
+    /// Creates a failover over `base_urls`, in order of preference.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `base_urls` is empty.
+    pub fn new(base_urls: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        let base_urls: Arc<[String]> = base_urls.into_iter().map(Into::into).collect();
+        assert!(!base_urls.is_empty(), "a failover needs at least one base URL");
+        Self {
+            base_urls,
+            recover_after: None,
+            health_path: None,
+            weights: None,
+            clock: None,
+            state: Arc::default(),
+        }
+    }
+
+    /// Returns to the primary base URL once another has been active for `delay`.
+    ///
+    /// Recovery isn't available on `wasm32`.
+    pub fn recover_after(mut self, delay: Duration) -> Self {
+        self.recover_after = Some(delay);
+        self
+    }
+
+    /// Before returning to the primary base URL, sends a `GET` to `path` on it
+    /// and only returns if the response is successful.
+    ///
+    /// Only used together with [`recover_after`](Self::recover_after). If the
+    /// check fails, the next one waits another `recover_after` delay.
+    pub fn health_check(mut self, path: impl Into<String>) -> Self {
+        self.health_path = Some(path.into());
+        self
+    }
+
+    /// Times [`recover_after`](Self::recover_after) with `clock` instead of
+    /// the system clock.
+    pub fn clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Some(Arc::new(clock));
+        self
+    }
+
+    /// Spreads requests evenly across the base URLs, in turn.
+    pub fn round_robin(self) -> Self {
+        let weights = vec![1; self.base_urls.len()];
+        self.weighted(weights)
+    }
+
+    /// Spreads requests across the base URLs in proportion to `weights`, one
+    /// per base URL.
+    ///
+    /// Turns are interleaved, so weights of `[2, 1]` send requests to the
+    /// first, first, then second base URL, and so on. A base URL with a
+    /// weight of zero only receives requests failing over from the others.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there isn't one weight per base URL, or every weight is zero.
+    pub fn weighted(mut self, weights: impl IntoIterator<Item = u32>) -> Self {
+        let weights: Arc<[u32]> = weights.into_iter().collect();
+        assert_eq!(weights.len(), self.base_urls.len(), "a failover needs one weight per base URL");
+        assert!(weights.iter().any(|&weight| weight > 0), "a failover needs a positive weight");
+        self.weights = Some(weights);
+        self
+    }
+
+    /// Returns the base URLs, in order of preference.
+    pub fn base_urls(&self) -> &[String] {
+        &self.base_urls
+    }
+
+    /// Returns the base URL requests are currently sent to.
+    pub fn active_base_url(&self) -> &str {
+        &self.base_urls[self.lock().active]
+    }
+
+    /// Sends a request for `path` to the active base URL, failing over to the
+    /// others in turn.
+    ///
+    /// # Errors
+    ///
+    /// Returns the client's error if the last base URL tried fails with one.
+    pub fn send<C: HttpClient>(
+        &self,
+        client: &C,
+        method: &str,
+        path: &str,
+        headers: HeaderMap,
+        body: Option<Vec<u8>>,
+        options: RequestOptions,
+    ) -> Result<Response<Vec<u8>>, C::Error> {
+        self.route(method, path, headers, body, |request| {
+            client.send(&request.method, &request.url, request.headers, request.body, options)
+        })
+    }
+
+    /// Async version of [`send`](Self::send).
+    ///
+    /// # Errors
+    ///
+    /// Returns the client's error if the last base URL tried fails with one.
+    pub async fn send_async<A: AsyncHttpClient>(
+        &self,
+        client: &A,
+        method: &str,
+        path: &str,
+        headers: HeaderMap,
+        body: Option<Vec<u8>>,
+        options: RequestOptions,
+    ) -> Result<Response<Vec<u8>>, A::Error> {
+        self.route_async(method, path, headers, body, |request| async move {
+            client.send_async(&request.method, &request.url, request.headers, request.body, options).await
+        })
+        .await
+    }
+
+    /// Sends a request for `path` with `attempt`, once per base URL tried.
+    pub(crate) fn route<E>(
+        &self,
+        method: &str,
+        path: &str,
+        headers: HeaderMap,
+        body: Option<Vec<u8>>,
+        mut attempt: impl FnMut(RequestParts) -> Result<Response<Vec<u8>>, E>,
+    ) -> Result<Response<Vec<u8>>, E> {
+        let mut plan = self.plan();
+        if let Some(probe) = plan.probe.take() {
+            let healthy = attempt(probe_request(probe));
+            plan = self.probed(is_success(&healthy));
+        }
+
+        let mut last = None;
+        for index in plan.order {
+            let result = attempt(self.request(index, method, path, &headers, &body));
+            if is_available(&result) {
+                self.record(index, plan.recovering);
+                return result;
+            }
+            last = Some(result);
+        }
+        last.expect("a failover has at least one base URL")
+    }
+
+    /// Async version of [`route`](Self::route).
+    pub(crate) async fn route_async<E, F, Fut>(
+        &self,
+        method: &str,
+        path: &str,
+        headers: HeaderMap,
+        body: Option<Vec<u8>>,
+        mut attempt: F,
+    ) -> Result<Response<Vec<u8>>, E>
+    where
+        F: FnMut(RequestParts) -> Fut,
+        Fut: std::future::Future<Output = Result<Response<Vec<u8>>, E>>,
+    {
+        let mut plan = self.plan();
+        if let Some(probe) = plan.probe.take() {
+            let healthy = attempt(probe_request(probe)).await;
+            plan = self.probed(is_success(&healthy));
+        }
+
+        let mut last = None;
+        for index in plan.order {
+            let result = attempt(self.request(index, method, path, &headers, &body)).await;
+            if is_available(&result) {
+                self.record(index, plan.recovering);
+                return result;
+            }
+            last = Some(result);
+        }
+        last.expect("a failover has at least one base URL")
+    }
+
+    fn request(&self, index: usize, method: &str, path: &str, headers: &HeaderMap, body: &Option<Vec<u8>>) -> RequestParts {
+        RequestParts {
+            method: method.to_string(),
+            url: format!("{}{}", self.base_urls[index], path),
+            headers: headers.clone(),
+            body: body.clone(),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, FailoverState> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Tries the active base URL first, then the rest in list order
+    fn order_from(&self, start: usize) -> Vec<usize> {
+        (start..self.base_urls.len()).chain(0..start).collect()
+    }
+
+    fn plan(&self) -> Plan {
+        if let Some(weights) = &self.weights {
+            return Plan {
+                order: self.order_from(self.next_turn(weights)),
+                recovering: false,
+                probe: None,
+            };
+        }
+        let active = self.lock().active;
+        if !self.recovery_due() {
+            return Plan {
+                order: self.order_from(active),
+                recovering: false,
+                probe: None,
+            };
+        }
+        match &self.health_path {
+            Some(path) => Plan {
+                order: self.order_from(active),
+                recovering: false,
+                probe: Some(format!("{}{}", self.base_urls[0], path)),
+            },
+            None => Plan {
+                order: self.order_from(0),
+                recovering: true,
+                probe: None,
+            },
+        }
+    }
+
+    /// Picks the base URL whose turn it is, with smooth weighted round-robin:
+    /// every base URL gains its weight, and the one furthest ahead is chosen
+    /// and falls back by the total weight
+    fn next_turn(&self, weights: &[u32]) -> usize {
+        let mut state = self.lock();
+        if state.turns.is_empty() {
+            state.turns = vec![0; weights.len()];
+        }
+        let total: i64 = weights.iter().map(|&weight| i64::from(weight)).sum();
+        for (turn, &weight) in state.turns.iter_mut().zip(weights) {
+            *turn += i64::from(weight);
+        }
+        let (index, _) = state
+            .turns
+            .iter()
+            .enumerate()
+            .rev()
+            .max_by_key(|&(_, &turn)| turn)
+            .expect("a failover has at least one base URL");
+        state.turns[index] -= total;
+        index
+    }
+
+    /// Returns to the primary if its health check passed, and otherwise
+    /// restarts the wait before the next check
+    fn probed(&self, healthy: bool) -> Plan {
+        if healthy {
+            self.record(0, false);
+        } else {
+            let active = self.lock().active;
+            self.record(active, true);
+        }
+        Plan {
+            order: self.order_from(self.lock().active),
+            recovering: false,
+            probe: None,
+        }
+    }
+
+    /// Makes `index` the active base URL. Staying on a fallback after trying
+    /// to recover restarts the wait before the next attempt.
+    fn record(&self, index: usize, recovering: bool) {
+        let mut state = self.lock();
+        let switched = index != state.active;
+        state.active = index;
+        #[cfg(not(target_arch = "wasm32"))]
+        if index == 0 {
+            state.switched_at = None;
+        } else if switched || recovering {
+            state.switched_at = Some(crate::clock::clock_or_system(&self.clock).now());
+        }
+        #[cfg(target_arch = "wasm32")]
+        let _ = (switched, recovering);
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn recovery_due(&self) -> bool {
+        let state = self.lock();
+        if self.weights.is_some() {
+            return false;
+        }
+        match (self.recover_after, state.switched_at) {
+            (Some(delay), Some(switched_at)) => {
+                let now = crate::clock::clock_or_system(&self.clock).now();
+                state.active != 0 && now.saturating_duration_since(switched_at) >= delay
+            }
+            _ => false,
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn recovery_due(&self) -> bool {
+        false
+    }
+}
+
+/// Whether a base URL answered in a way that shouldn't be retried elsewhere
+fn is_available<E>(result: &Result<Response<Vec<u8>>, E>) -> bool {
+    matches!(result, Ok(response) if !response.status().is_server_error())
+}
+
+fn probe_request(url: String) -> RequestParts {
+    RequestParts {
+        method: "GET".to_string(),
+        url,
+        headers: HeaderMap::new(),
+        body: None,
+    }
+}
+
+fn is_success<E>(result: &Result<Response<Vec<u8>>, E>) -> bool {
+    matches!(result, Ok(response) if response.status().is_success())
+}
@@ -0,0 +1,272 @@
+//! Seeded fault injection around an HTTP client, for testing resilience.
+
+use crate::clock::clock_or_system;
+use crate::{AsyncHttpClient, Clock, HttpClient, RequestOptions};
+use http::{HeaderMap, Response, StatusCode};
+use std::error::Error as StdError;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// The error of a [`FaultInjectingClient`].
+///
+/// An injected error counts as transient, like the connection reset it
+/// stands in for, so retry policies retry it.
+#[derive(Debug)]
+pub enum FaultInjectionError<E> {
+    /// The request failed with an injected error, without being sent
+    Injected,
+    /// The wrapped client failed
+    Client(E),
+}
+
+impl<E: fmt::Display> fmt::Display for FaultInjectionError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Injected => f.write_str("injected fault"),
+            Self::Client(error) => error.fmt(f),
+        }
+    }
+}
+
+impl<E: StdError + 'static> StdError for FaultInjectionError<E> {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Self::Injected => Some(&InjectedFault),
+            Self::Client(error) => Some(error),
+        }
+    }
+}
+
+/// The source of an injected error, recognised by `RestApiError::is_transient()`
+#[derive(Debug)]
+pub(crate) struct InjectedFault;
+
+impl fmt::Display for InjectedFault {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("simulated connection reset")
+    }
+}
+
+impl StdError for InjectedFault {}
+
+/// HTTP client wrapper that injects errors, latency, error statuses and
+/// truncated bodies into requests.
+///
+/// Each fault happens with its own rate, from `0.0` (never) to `1.0` (every
+/// request), and is decided by a random generator seeded in [`new`](Self::new),
+/// so the same seed and requests always give the same faults. This makes
+/// retries, failover and circuit breaking testable without a flaky server.
+///
+/// For each request, in order:
+///
+/// - the latency fault sleeps before anything else
+/// - the error fault fails with [`FaultInjectionError::Injected`]
+/// - the status faults answer with their status and an empty body, the first
+///   one to trigger winning
+/// - otherwise the request is sent, and the truncation fault cuts the response
+///   body short
+///
+/// Faults injected before sending mean the wrapped client never sees the
+/// request. Clones share the random generator.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use derive_rest_api::{FaultInjectingClient, RetryPolicy, UreqBlockingClient};
+/// use derive_rest_api::http::StatusCode;
+/// use std::time::Duration;
+///
+/// let http_client = FaultInjectingClient::new(UreqBlockingClient::new(), 42)
+///     .with_error_rate(0.1)
+///     .with_status(0.2, StatusCode::SERVICE_UNAVAILABLE)
+///     .with_latency(0.5, Duration::from_millis(200));
+/// let client = MyApiClient::builder()
+///     .http_client(http_client)
+///     .retry_policy(RetryPolicy::new(3))
+///     .build()?;
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct FaultInjectingClient<C> {
+    inner: C,
+    error_rate: f64,
+    latency_rate: f64,
+    latency: Duration,
+    statuses: Vec<(f64, StatusCode)>,
+    truncation_rate: f64,
+    /// SplitMix64 state, shared between clones
+    rng: Arc<Mutex<u64>>,
+    clock: Option<Arc<dyn Clock>>,
+}
+
+/// The faults chosen for one request
+struct Faults {
+    delay: Option<Duration>,
+    error: bool,
+    status: Option<StatusCode>,
+    /// How much of the body to keep, from 0 up to (but excluding) 1
+    truncation: Option<f64>,
+}
+
+impl<C> FaultInjectingClient<C> {
+    /// Wraps `inner`, drawing faults from a generator seeded with `seed`.
+    ///
+    /// No faults are injected until their rates are set.
+    pub fn new(inner: C, seed: u64) -> Self {
+        Self {
+            inner,
+            error_rate: 0.0,
+            latency_rate: 0.0,
+            latency: Duration::ZERO,
+            statuses: Vec::new(),
+            truncation_rate: 0.0,
+            rng: Arc::new(Mutex::new(seed)),
+            clock: None,
+        }
+    }
+
+    /// Fails a `rate` of requests with [`FaultInjectionError::Injected`].
+    pub fn with_error_rate(mut self, rate: f64) -> Self {
+        self.error_rate = rate;
+        self
+    }
+
+    /// Delays a `rate` of requests by `latency`.
+    pub fn with_latency(mut self, rate: f64, latency: Duration) -> Self {
+        self.latency_rate = rate;
+        self.latency = latency;
+        self
+    }
+
+    /// Answers a `rate` of requests with `status` and an empty body.
+    ///
+    /// Can be called several times for different statuses.
+    pub fn with_status(mut self, rate: f64, status: StatusCode) -> Self {
+        self.statuses.push((rate, status));
+        self
+    }
+
+    /// Cuts the body of a `rate` of responses to a random shorter length.
+    pub fn with_truncation_rate(mut self, rate: f64) -> Self {
+        self.truncation_rate = rate;
+        self
+    }
+
+    /// Waits out injected latency with `clock` instead of the system clock.
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Some(Arc::new(clock));
+        self
+    }
+
+    /// Returns the wrapped client.
+    pub fn inner(&self) -> &C {
+        &self.inner
+    }
+
+    /// Draws the faults for a request.
+    ///
+    /// Every fault is drawn for every request, so one fault's rate doesn't
+    /// change which requests get the others.
+    fn faults(&self) -> Faults {
+        let mut state = self.rng.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut roll = |rate: f64| next_f64(&mut state) < rate;
+
+        let delay = roll(self.latency_rate).then_some(self.latency);
+        let error = roll(self.error_rate);
+        let mut status = None;
+        for &(rate, code) in &self.statuses {
+            if roll(rate) && status.is_none() {
+                status = Some(code);
+            }
+        }
+        let truncate = roll(self.truncation_rate);
+        let truncation = truncate.then(|| next_f64(&mut state));
+
+        Faults { delay, error, status, truncation }
+    }
+}
+
+impl Faults {
+    /// Returns the outcome injected in place of sending the request, if any
+    fn before_send<E>(&self) -> Option<Result<Response<Vec<u8>>, FaultInjectionError<E>>> {
+        if self.error {
+            return Some(Err(FaultInjectionError::Injected));
+        }
+        self.status.map(|status| {
+            let mut response = Response::new(Vec::new());
+            *response.status_mut() = status;
+            Ok(response)
+        })
+    }
+
+    fn after_send(&self, mut response: Response<Vec<u8>>) -> Response<Vec<u8>> {
+        if let Some(keep) = self.truncation {
+            let body = response.body_mut();
+            body.truncate((body.len() as f64 * keep) as usize);
+        }
+        response
+    }
+}
+
+/// Advances a SplitMix64 generator, returning a value in `[0, 1)`
+fn next_f64(state: &mut u64) -> f64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^= z >> 31;
+    (z >> 11) as f64 / (1u64 << 53) as f64
+}
+
+impl<C: HttpClient> HttpClient for FaultInjectingClient<C> {
+    type Error = FaultInjectionError<C::Error>;
+
+    fn send(
+        &self,
+        method: &str,
+        url: &str,
+        headers: HeaderMap,
+        body: Option<Vec<u8>>,
+        options: RequestOptions,
+    ) -> Result<Response<Vec<u8>>, Self::Error> {
+        let faults = self.faults();
+        if let Some(delay) = faults.delay {
+            clock_or_system(&self.clock).sleep(delay);
+        }
+        if let Some(outcome) = faults.before_send() {
+            return outcome;
+        }
+        let response = self
+            .inner
+            .send(method, url, headers, body, options)
+            .map_err(FaultInjectionError::Client)?;
+        Ok(faults.after_send(response))
+    }
+}
+
+impl<C: AsyncHttpClient + Sync> AsyncHttpClient for FaultInjectingClient<C> {
+    type Error = FaultInjectionError<C::Error>;
+
+    async fn send_async(
+        &self,
+        method: &str,
+        url: &str,
+        headers: HeaderMap,
+        body: Option<Vec<u8>>,
+        options: RequestOptions,
+    ) -> Result<Response<Vec<u8>>, Self::Error> {
+        let faults = self.faults();
+        if let Some(delay) = faults.delay {
+            clock_or_system(&self.clock).sleep_async(delay).await;
+        }
+        if let Some(outcome) = faults.before_send() {
+            return outcome;
+        }
+        let response = self
+            .inner
+            .send_async(method, url, headers, body, options)
+            .await
+            .map_err(FaultInjectionError::Client)?;
+        Ok(faults.after_send(response))
+    }
+}
@@ -0,0 +1,147 @@
+//! GraphQL request bodies and response envelopes for `#[request_builder(graphql, ...)]`.
+
+use crate::RestApiError;
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+use serde_json::Value;
+use std::fmt;
+
+/// The body of a GraphQL request: `{"query", "variables", "operationName"}`.
+///
+/// Structs deriving `RequestBuilder` with `graphql` build this from their
+/// query and fields; it can also be serialized by hand for other clients.
+#[derive(Debug, Clone)]
+pub struct GraphQlRequest<'a, V> {
+    /// The query or mutation document
+    pub query: &'a str,
+    /// The operation's variables, serialized as a JSON object
+    pub variables: V,
+    /// Which operation in `query` to run, if it defines several
+    pub operation_name: Option<&'a str>,
+}
+
+impl<'a, V: Serialize> GraphQlRequest<'a, V> {
+    /// Creates a request for `query` with the given variables.
+    pub fn new(query: &'a str, variables: V) -> Self {
+        Self {
+            query,
+            variables,
+            operation_name: None,
+        }
+    }
+
+    /// Sets the operation to run.
+    pub fn operation_name(mut self, operation_name: &'a str) -> Self {
+        self.operation_name = Some(operation_name);
+        self
+    }
+}
+
+impl<V: Serialize> Serialize for GraphQlRequest<'_, V> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let len = if self.operation_name.is_some() { 3 } else { 2 };
+        let mut state = serializer.serialize_struct("GraphQlRequest", len)?;
+        state.serialize_field("query", self.query)?;
+        state.serialize_field("variables", &self.variables)?;
+        if let Some(operation_name) = self.operation_name {
+            state.serialize_field("operationName", operation_name)?;
+        }
+        state.end()
+    }
+}
+
+/// An entry of a GraphQL response's `errors` array.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphQlError {
+    /// Description of the error
+    pub message: String,
+    /// `(line, column)` positions in the query the error relates to
+    pub locations: Vec<(u64, u64)>,
+    /// Path to the response field that failed, as field names and list indices
+    pub path: Vec<Value>,
+    /// Server-specific details, such as an error code
+    pub extensions: Option<Value>,
+}
+
+impl GraphQlError {
+    fn from_value(value: &Value) -> Self {
+        let message = match value.get("message") {
+            Some(Value::String(message)) => message.clone(),
+            _ => value.to_string(),
+        };
+        let locations = value
+            .get("locations")
+            .and_then(Value::as_array)
+            .map(|locations| {
+                locations
+                    .iter()
+                    .filter_map(|location| {
+                        Some((location.get("line")?.as_u64()?, location.get("column")?.as_u64()?))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        let path = value
+            .get("path")
+            .and_then(Value::as_array)
+            .cloned()
+            .unwrap_or_default();
+
+        Self {
+            message,
+            locations,
+            path,
+            extensions: value.get("extensions").cloned(),
+        }
+    }
+}
+
+impl fmt::Display for GraphQlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            return f.write_str(&self.message);
+        }
+        let path: Vec<String> = self
+            .path
+            .iter()
+            .map(|segment| match segment {
+                Value::String(name) => name.clone(),
+                other => other.to_string(),
+            })
+            .collect();
+        write!(f, "{} (at {})", self.message, path.join("."))
+    }
+}
+
+/// Formats a list of GraphQL errors for `RestApiError::GraphQlErrors`.
+pub(crate) fn summarize(errors: &[GraphQlError]) -> String {
+    errors
+        .iter()
+        .map(GraphQlError::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Unwraps a GraphQL response body, returning its `data` serialized as JSON.
+///
+/// A non-empty `errors` array becomes `RestApiError::GraphQlErrors`, carrying
+/// any partial `data` alongside the errors.
+pub fn unwrap_data(body: Vec<u8>) -> Result<Vec<u8>, RestApiError> {
+    let mut envelope: Value = serde_json::from_slice(&body)
+        .map_err(RestApiError::response_deserialization_error)?;
+
+    let data = match envelope.get_mut("data").map(Value::take) {
+        Some(Value::Null) | None => None,
+        Some(data) => Some(data),
+    };
+    let errors: Vec<GraphQlError> = envelope
+        .get("errors")
+        .and_then(Value::as_array)
+        .map(|errors| errors.iter().map(GraphQlError::from_value).collect())
+        .unwrap_or_default();
+
+    if !errors.is_empty() {
+        return Err(RestApiError::GraphQlErrors { errors, data });
+    }
+    serde_json::to_vec(&data.unwrap_or(Value::Null))
+        .map_err(RestApiError::response_deserialization_error)
+}
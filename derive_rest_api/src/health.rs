@@ -0,0 +1,128 @@
+//! Health checks for `#[api_client(health = "...")]`.
+
+use crate::{AsyncHttpClient, HttpClient, RequestOptions, RestApiError, __private::Transport};
+use http::{HeaderMap, Response};
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Duration;
+
+/// First wait between readiness checks, doubled after every failure
+#[cfg(not(target_arch = "wasm32"))]
+const FIRST_RETRY: Duration = Duration::from_millis(100);
+
+/// Longest wait between readiness checks
+#[cfg(not(target_arch = "wasm32"))]
+const MAX_RETRY: Duration = Duration::from_secs(2);
+
+/// Sends a `GET` to a health endpoint, succeeding on a `2xx` status.
+pub fn check<C: HttpClient>(
+    client: &C,
+    transport: &Transport,
+    base_url: &str,
+    path: &str,
+    headers: HeaderMap,
+    options: RequestOptions,
+) -> Result<(), RestApiError> {
+    let response = health_transport(transport)
+        .send(client, "GET", base_url, path, headers, None, options)?;
+    healthy(response)
+}
+
+/// Async version of [`check`].
+pub async fn check_async<A: AsyncHttpClient>(
+    client: &A,
+    transport: &Transport,
+    base_url: &str,
+    path: &str,
+    headers: HeaderMap,
+    options: RequestOptions,
+) -> Result<(), RestApiError> {
+    let response = health_transport(transport)
+        .send_async(client, "GET", base_url, path, headers, None, options)
+        .await?;
+    healthy(response)
+}
+
+/// The client's transport, with health checks labelled as such in its metrics
+fn health_transport(transport: &Transport) -> Transport {
+    Transport {
+        endpoint: Some("health_check"),
+        ..transport.clone()
+    }
+}
+
+fn healthy(response: Response<Vec<u8>>) -> Result<(), RestApiError> {
+    if response.status().is_success() {
+        Ok(())
+    } else {
+        Err(RestApiError::Unhealthy {
+            status: response.status(),
+        })
+    }
+}
+
+/// Repeats `check` until it succeeds, backing off between attempts, or fails
+/// with `RestApiError::NotReady` once `timeout` has passed.
+///
+/// Each attempt is limited to the time that remains.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn wait_until_ready(
+    timeout: Duration,
+    mut check: impl FnMut(RequestOptions) -> Result<(), RestApiError>,
+) -> Result<(), RestApiError> {
+    let deadline = std::time::Instant::now() + timeout;
+    let mut retry = FIRST_RETRY;
+    loop {
+        let remaining = deadline.saturating_duration_since(std::time::Instant::now());
+        let error = match check(attempt_options(remaining)) {
+            Ok(()) => return Ok(()),
+            Err(error) => error,
+        };
+        let wait = next_wait(&mut retry, deadline).ok_or_else(|| not_ready(timeout, error))?;
+        std::thread::sleep(wait);
+    }
+}
+
+/// Async version of [`wait_until_ready`].
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn wait_until_ready_async<F, Fut>(timeout: Duration, mut check: F) -> Result<(), RestApiError>
+where
+    F: FnMut(RequestOptions) -> Fut,
+    Fut: std::future::Future<Output = Result<(), RestApiError>>,
+{
+    let deadline = std::time::Instant::now() + timeout;
+    let mut retry = FIRST_RETRY;
+    loop {
+        let remaining = deadline.saturating_duration_since(std::time::Instant::now());
+        let error = match check(attempt_options(remaining)).await {
+            Ok(()) => return Ok(()),
+            Err(error) => error,
+        };
+        let wait = next_wait(&mut retry, deadline).ok_or_else(|| not_ready(timeout, error))?;
+        crate::poll::Delay::new(wait).await;
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn attempt_options(remaining: Duration) -> RequestOptions {
+    RequestOptions::from(crate::Timeouts {
+        total: Some(remaining.max(Duration::from_millis(1))),
+        ..crate::Timeouts::default()
+    })
+}
+
+/// Returns the wait before the next attempt, or `None` if the deadline has passed
+#[cfg(not(target_arch = "wasm32"))]
+fn next_wait(retry: &mut Duration, deadline: std::time::Instant) -> Option<Duration> {
+    let remaining = deadline.checked_duration_since(std::time::Instant::now()).filter(|remaining| !remaining.is_zero())?;
+    let wait = (*retry).min(remaining);
+    *retry = (*retry * 2).min(MAX_RETRY);
+    Some(wait)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn not_ready(timeout: Duration, error: RestApiError) -> RestApiError {
+    RestApiError::NotReady {
+        timeout,
+        source: Box::new(error),
+    }
+}
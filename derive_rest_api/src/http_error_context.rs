@@ -0,0 +1,53 @@
+//! Timing and size context for failed HTTP requests.
+
+use std::time::Duration;
+
+/// How far a request got before its HTTP client failed.
+///
+/// Attached to `RestApiError::HttpError` for requests sent with `send()` or
+/// `send_async()`, so a failure can be told apart without tracing: one that
+/// comes after a long `elapsed` points at a slow upstream or a timeout, while
+/// one that comes quickly points at a refused or reset connection.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpErrorContext {
+    /// The request struct the request was built from, such as `GetUser`, if
+    /// sent through a generated client or request builder
+    pub endpoint: Option<&'static str>,
+    /// The full URL of the last attempt
+    pub url: String,
+    /// Time from the start of the request until the last attempt failed,
+    /// including any retries and the waits between them. On `wasm32`, where
+    /// the system clock can't be read, it's only known for requests timed for
+    /// a metrics hook or event subscriber.
+    pub elapsed: Option<Duration>,
+    /// How many times the request was handed to the HTTP client
+    pub attempts: u32,
+    /// Request body bytes handed to the HTTP client over all attempts
+    pub bytes_sent: u64,
+    /// Response body bytes received by earlier attempts that got a response
+    /// before being retried, or `None` if no attempt got one
+    pub bytes_received: Option<u64>,
+}
+
+/// Describes the context of an HTTP error for its `Display` output.
+pub(crate) fn describe(context: &Option<Box<HttpErrorContext>>) -> String {
+    let Some(context) = context else {
+        return String::new();
+    };
+    let received = match context.bytes_received {
+        Some(bytes) => format!("{} bytes received", bytes),
+        None => "no response".to_string(),
+    };
+    let elapsed = match context.elapsed {
+        Some(elapsed) => format!("{:?} and ", elapsed),
+        None => String::new(),
+    };
+    format!(
+        " (after {}{} attempt{}; {} bytes sent, {})",
+        elapsed,
+        context.attempts,
+        if context.attempts == 1 { "" } else { "s" },
+        context.bytes_sent,
+        received
+    )
+}
@@ -0,0 +1,37 @@
+//! HTTP protocol version selection for HTTP client wrappers.
+
+/// Which HTTP version a client speaks to servers.
+///
+/// Passed to the `with_protocol` constructors of backends that can choose.
+/// Not every backend supports every option; see each client's documentation.
+///
+/// # Example
+///
+/// ```
+/// use derive_rest_api::HttpProtocol;
+///
+/// assert_eq!(HttpProtocol::default(), HttpProtocol::Negotiate);
+/// assert!(HttpProtocol::Http2PriorKnowledge.is_http2_only());
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum HttpProtocol {
+    /// Use HTTP/2 when the server offers it during the TLS handshake (ALPN),
+    /// and HTTP/1.1 otherwise. Plain-text connections use HTTP/1.1.
+    #[default]
+    Negotiate,
+
+    /// Always use HTTP/1.1, even if the server offers HTTP/2.
+    Http1Only,
+
+    /// Always use HTTP/2 without negotiating it first. Over plain-text
+    /// connections this is h2c, as used by many internal services and gRPC
+    /// gateways; servers that don't speak HTTP/2 will fail the request.
+    Http2PriorKnowledge,
+}
+
+impl HttpProtocol {
+    /// Returns `true` if the client never falls back to HTTP/1.1.
+    pub fn is_http2_only(&self) -> bool {
+        matches!(self, Self::Http2PriorKnowledge)
+    }
+}
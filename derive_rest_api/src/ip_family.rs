@@ -0,0 +1,44 @@
+//! IP address family selection for HTTP client wrappers.
+
+/// Which IP address families a client connects over.
+///
+/// With `Any`, the reqwest and isahc backends race IPv6 and IPv4 connection
+/// attempts ("Happy Eyeballs"), starting the second family shortly after the
+/// first. In networks where one family is broken in a way that stalls rather
+/// than fails, restricting clients to the working family avoids paying for
+/// that on every new connection. Pair it with a connect timeout so a host
+/// that can't be reached at all fails quickly.
+///
+/// # Example
+///
+/// ```
+/// use derive_rest_api::IpFamily;
+/// use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+///
+/// assert!(IpFamily::Ipv4Only.allows(IpAddr::V4(Ipv4Addr::LOCALHOST)));
+/// assert!(!IpFamily::Ipv4Only.allows(IpAddr::V6(Ipv6Addr::LOCALHOST)));
+/// assert!(IpFamily::default().allows(IpAddr::V6(Ipv6Addr::LOCALHOST)));
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum IpFamily {
+    /// Connect over either family, falling back between them.
+    #[default]
+    Any,
+
+    /// Connect over IPv4 only, ignoring IPv6 addresses.
+    Ipv4Only,
+
+    /// Connect over IPv6 only, ignoring IPv4 addresses.
+    Ipv6Only,
+}
+
+impl IpFamily {
+    /// Returns `true` if connections to `addr` are allowed.
+    pub fn allows(&self, addr: std::net::IpAddr) -> bool {
+        match self {
+            Self::Any => true,
+            Self::Ipv4Only => addr.is_ipv4(),
+            Self::Ipv6Only => addr.is_ipv6(),
+        }
+    }
+}
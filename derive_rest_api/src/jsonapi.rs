@@ -0,0 +1,165 @@
+//! JSON:API (`application/vnd.api+json`) response documents for `response_format = "jsonapi"`.
+
+use crate::RestApiError;
+use serde::de::{DeserializeOwned, Error as _};
+use serde_json::{Map, Value};
+
+/// The media type of JSON:API documents.
+pub const JSONAPI_MEDIA_TYPE: &str = "application/vnd.api+json";
+
+/// A JSON:API response document with its primary data flattened into `T`.
+///
+/// Each resource object in `data` is deserialized from its `attributes`
+/// together with its `id`, `type` and (if present) `relationships`, so `T`
+/// can be a plain struct, or a `Vec` of them for collection responses.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonApiDocument<T> {
+    /// The primary data
+    pub data: T,
+    /// Related resources included in the document (`included`)
+    pub included: Vec<JsonApiResource>,
+    /// Top-level links, such as pagination links
+    pub links: Map<String, Value>,
+    /// Top-level non-standard meta-information
+    pub meta: Option<Value>,
+}
+
+impl<T: DeserializeOwned> JsonApiDocument<T> {
+    /// Parses a JSON:API document, flattening its primary data into `T`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `RestApiError::ResponseDeserializationError` if the body isn't a
+    /// JSON:API document with `data`, or the data doesn't match `T`.
+    pub fn from_slice(body: &[u8]) -> Result<Self, RestApiError> {
+        let deserialization_error = RestApiError::response_deserialization_error;
+
+        let mut document: Value = serde_json::from_slice(body).map_err(deserialization_error)?;
+        let data = match document.get_mut("data").map(Value::take) {
+            Some(Value::Array(resources)) => Value::Array(
+                resources
+                    .into_iter()
+                    .map(|resource| JsonApiResource::from_value(resource).flattened())
+                    .collect(),
+            ),
+            Some(Value::Null) => Value::Null,
+            Some(resource) => JsonApiResource::from_value(resource).flattened(),
+            None => {
+                return Err(deserialization_error(serde_json::Error::custom(
+                    "JSON:API document has no `data` member",
+                )));
+            }
+        };
+        let included = match document.get_mut("included").map(Value::take) {
+            Some(Value::Array(resources)) => resources.into_iter().map(JsonApiResource::from_value).collect(),
+            _ => Vec::new(),
+        };
+        let links = match document.get_mut("links").map(Value::take) {
+            Some(Value::Object(links)) => links,
+            _ => Map::new(),
+        };
+
+        Ok(Self {
+            data: serde_json::from_value(data).map_err(deserialization_error)?,
+            included,
+            links,
+            meta: document.get_mut("meta").map(Value::take),
+        })
+    }
+}
+
+impl<T> JsonApiDocument<T> {
+    /// Returns the included resource with the given type and id.
+    pub fn find_included(&self, resource_type: &str, id: &str) -> Option<&JsonApiResource> {
+        self.included
+            .iter()
+            .find(|resource| resource.resource_type == resource_type && resource.id.as_deref() == Some(id))
+    }
+
+    /// Returns the top-level link with the given name (e.g. `"next"`), whether
+    /// it is a plain URL or a link object with an `href`.
+    pub fn link(&self, name: &str) -> Option<&str> {
+        match self.links.get(name)? {
+            Value::String(href) => Some(href),
+            Value::Object(link) => link.get("href")?.as_str(),
+            _ => None,
+        }
+    }
+}
+
+/// A JSON:API resource object, as found in `included`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonApiResource {
+    /// The resource's `id`, absent for resources not yet created
+    pub id: Option<String>,
+    /// The resource's `type`
+    pub resource_type: String,
+    /// The resource's `attributes`
+    pub attributes: Map<String, Value>,
+    /// The resource's `relationships`
+    pub relationships: Map<String, Value>,
+    /// The resource's `links`
+    pub links: Map<String, Value>,
+    /// The resource's `meta`
+    pub meta: Option<Value>,
+}
+
+impl JsonApiResource {
+    fn from_value(value: Value) -> Self {
+        let Value::Object(mut resource) = value else {
+            return Self {
+                id: None,
+                resource_type: String::new(),
+                attributes: Map::new(),
+                relationships: Map::new(),
+                links: Map::new(),
+                meta: None,
+            };
+        };
+        let mut object = |key: &str| match resource.remove(key) {
+            Some(Value::Object(map)) => map,
+            _ => Map::new(),
+        };
+        let attributes = object("attributes");
+        let relationships = object("relationships");
+        let links = object("links");
+
+        Self {
+            id: resource.remove("id").and_then(|id| match id {
+                Value::String(id) => Some(id),
+                Value::Null => None,
+                other => Some(other.to_string()),
+            }),
+            resource_type: match resource.remove("type") {
+                Some(Value::String(resource_type)) => resource_type,
+                _ => String::new(),
+            },
+            attributes,
+            relationships,
+            links,
+            meta: resource.remove("meta"),
+        }
+    }
+
+    /// Deserializes the resource, flattened like a document's primary data.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the resource doesn't match `T`.
+    pub fn deserialize<T: DeserializeOwned>(&self) -> Result<T, serde_json::Error> {
+        serde_json::from_value(self.clone().flattened())
+    }
+
+    /// Returns the attributes plus `id`, `type` and (if any) `relationships` as one object
+    fn flattened(self) -> Value {
+        let mut flattened = self.attributes;
+        if let Some(id) = self.id {
+            flattened.insert("id".to_string(), Value::String(id));
+        }
+        flattened.insert("type".to_string(), Value::String(self.resource_type));
+        if !self.relationships.is_empty() {
+            flattened.insert("relationships".to_string(), Value::Object(self.relationships));
+        }
+        Value::Object(flattened)
+    }
+}
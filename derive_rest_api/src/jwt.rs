@@ -0,0 +1,353 @@
+//! Signed JWTs for client-assertion authentication.
+
+use base64::Engine;
+use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+use ring::rand::SystemRandom;
+use ring::signature::{ECDSA_P256_SHA256_FIXED_SIGNING, EcdsaKeyPair, RSA_PKCS1_SHA256, RsaKeyPair};
+use serde::Serialize;
+use std::fmt;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Errors that can occur while loading a signing key or signing a JWT.
+#[derive(Debug, thiserror::Error)]
+pub enum JwtError {
+    /// The private key could not be decoded or does not match the algorithm.
+    #[error("Invalid JWT signing key: {0}")]
+    InvalidKey(String),
+
+    /// The claims could not be serialized to JSON.
+    #[error("Failed to serialize JWT claims: {0}")]
+    Serialization(#[from] serde_json::Error),
+
+    /// Signing failed.
+    #[error("Failed to sign JWT")]
+    Signing,
+}
+
+/// The signature algorithm of a JWT.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum JwtAlgorithm {
+    /// RSASSA-PKCS1-v1_5 with SHA-256, used by Google service accounts and GitHub Apps.
+    Rs256,
+    /// ECDSA on P-256 with SHA-256.
+    Es256,
+}
+
+impl JwtAlgorithm {
+    /// Returns the `alg` header value (e.g. `"RS256"`).
+    pub fn name(&self) -> &'static str {
+        match self {
+            JwtAlgorithm::Rs256 => "RS256",
+            JwtAlgorithm::Es256 => "ES256",
+        }
+    }
+}
+
+impl fmt::Display for JwtAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Standard claims of a client-assertion JWT, plus any extra claims.
+///
+/// `iat` and `exp` are set from the current time when the claims are created,
+/// so create fresh claims for every token.
+///
+/// # Example
+///
+/// ```
+/// use derive_rest_api::JwtClaims;
+/// use std::time::Duration;
+///
+/// // Google service account: request an access token for a scope
+/// let claims = JwtClaims::new("robot@project.iam.gserviceaccount.com", Duration::from_secs(3600))
+///     .with_audience("https://oauth2.googleapis.com/token")
+///     .with_claim("scope", "https://www.googleapis.com/auth/devstorage.read_only");
+///
+/// assert_eq!(claims.expires_at - claims.issued_at, 3600);
+/// ```
+#[derive(Clone, Debug, Serialize)]
+pub struct JwtClaims {
+    /// Issuer (`iss`), e.g. the service account email or GitHub App ID.
+    #[serde(rename = "iss")]
+    pub issuer: String,
+
+    /// Subject (`sub`).
+    #[serde(rename = "sub", skip_serializing_if = "Option::is_none")]
+    pub subject: Option<String>,
+
+    /// Audience (`aud`), usually the token endpoint.
+    #[serde(rename = "aud", skip_serializing_if = "Option::is_none")]
+    pub audience: Option<String>,
+
+    /// Issued-at time (`iat`), in seconds since the Unix epoch.
+    #[serde(rename = "iat")]
+    pub issued_at: u64,
+
+    /// Expiry time (`exp`), in seconds since the Unix epoch.
+    #[serde(rename = "exp")]
+    pub expires_at: u64,
+
+    /// Additional claims, such as `scope` or `jti`.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl JwtClaims {
+    /// Creates claims issued by `issuer` now and valid for `lifetime`.
+    pub fn new(issuer: impl Into<String>, lifetime: Duration) -> Self {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or(0);
+        Self {
+            issuer: issuer.into(),
+            subject: None,
+            audience: None,
+            issued_at: now,
+            expires_at: now.saturating_add(lifetime.as_secs()),
+            extra: serde_json::Map::new(),
+        }
+    }
+
+    /// Sets the subject (`sub`).
+    pub fn with_subject(mut self, subject: impl Into<String>) -> Self {
+        self.subject = Some(subject.into());
+        self
+    }
+
+    /// Sets the audience (`aud`).
+    pub fn with_audience(mut self, audience: impl Into<String>) -> Self {
+        self.audience = Some(audience.into());
+        self
+    }
+
+    /// Backdates the issued-at time to tolerate clock drift between client and server.
+    ///
+    /// GitHub recommends backdating App JWTs by 60 seconds.
+    pub fn with_clock_skew(mut self, skew: Duration) -> Self {
+        self.issued_at = self.issued_at.saturating_sub(skew.as_secs());
+        self
+    }
+
+    /// Adds an extra claim.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `value` cannot be represented as JSON (e.g. a map with non-string keys).
+    pub fn with_claim(mut self, name: impl Into<String>, value: impl Serialize) -> Self {
+        let value = serde_json::to_value(value).expect("JWT claim must serialize to JSON");
+        self.extra.insert(name.into(), value);
+        self
+    }
+}
+
+/// The private key behind a [`JwtSigner`]
+enum SigningKey {
+    Rsa(RsaKeyPair),
+    Ecdsa(EcdsaKeyPair),
+}
+
+/// Signs JWTs with an RS256 or ES256 private key.
+///
+/// Keys are loaded from PEM or DER. RS256 accepts PKCS#8 (`BEGIN PRIVATE KEY`,
+/// as in Google service account files) and PKCS#1 (`BEGIN RSA PRIVATE KEY`, as
+/// downloaded for GitHub Apps); ES256 accepts PKCS#8 only, so convert SEC1
+/// keys with `openssl pkcs8 -topk8 -nocrypt`.
+///
+/// The signer is usually kept in an `ApiClient` config struct and used from
+/// its `ConfigureRequest` implementation:
+///
+/// ```rust,ignore
+/// use derive_rest_api::{ConfigureRequest, JwtClaims, JwtSigner, RequestModifier};
+/// use std::time::Duration;
+///
+/// struct GitHubAppConfig {
+///     app_id: String,
+///     signer: JwtSigner,
+/// }
+///
+/// impl ConfigureRequest for GitHubAppConfig {
+///     fn configure<M: RequestModifier>(&self, modifier: M) -> M {
+///         let claims = JwtClaims::new(&self.app_id, Duration::from_secs(540))
+///             .with_clock_skew(Duration::from_secs(60));
+///         let token = self.signer.sign(&claims).expect("failed to sign GitHub App JWT");
+///         modifier.bearer_auth(token)
+///     }
+/// }
+/// ```
+pub struct JwtSigner {
+    algorithm: JwtAlgorithm,
+    key: SigningKey,
+    key_id: Option<String>,
+    rng: SystemRandom,
+}
+
+impl JwtSigner {
+    /// Loads a private key from PEM.
+    ///
+    /// # Errors
+    ///
+    /// Returns `JwtError::InvalidKey` if the PEM is malformed or the key
+    /// doesn't suit `algorithm`.
+    pub fn from_pem(algorithm: JwtAlgorithm, pem: &str) -> Result<Self, JwtError> {
+        let (label, der) = decode_pem(pem)?;
+        match (algorithm, label.as_str()) {
+            (_, "PRIVATE KEY") => Self::from_der(algorithm, &der),
+            (JwtAlgorithm::Rs256, "RSA PRIVATE KEY") => {
+                let key = RsaKeyPair::from_der(&der).map_err(|e| JwtError::InvalidKey(e.to_string()))?;
+                Ok(Self::new(algorithm, SigningKey::Rsa(key)))
+            }
+            _ => Err(JwtError::InvalidKey(format!(
+                "unsupported PEM block '{}' for {}",
+                label, algorithm
+            ))),
+        }
+    }
+
+    /// Loads a PKCS#8 private key from DER.
+    ///
+    /// # Errors
+    ///
+    /// Returns `JwtError::InvalidKey` if the key can't be parsed or doesn't suit `algorithm`.
+    pub fn from_der(algorithm: JwtAlgorithm, pkcs8: &[u8]) -> Result<Self, JwtError> {
+        let rng = SystemRandom::new();
+        let key = match algorithm {
+            JwtAlgorithm::Rs256 => RsaKeyPair::from_pkcs8(pkcs8).map(SigningKey::Rsa),
+            JwtAlgorithm::Es256 => {
+                EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, pkcs8, &rng).map(SigningKey::Ecdsa)
+            }
+        }
+        .map_err(|e| JwtError::InvalidKey(e.to_string()))?;
+        Ok(Self::new(algorithm, key))
+    }
+
+    fn new(algorithm: JwtAlgorithm, key: SigningKey) -> Self {
+        Self {
+            algorithm,
+            key,
+            key_id: None,
+            rng: SystemRandom::new(),
+        }
+    }
+
+    /// Sets the key ID (`kid`) sent in the JWT header, e.g. a service account's `private_key_id`.
+    pub fn with_key_id(mut self, key_id: impl Into<String>) -> Self {
+        self.key_id = Some(key_id.into());
+        self
+    }
+
+    /// Returns the signature algorithm.
+    pub fn algorithm(&self) -> JwtAlgorithm {
+        self.algorithm
+    }
+
+    /// Signs `claims` and returns the encoded JWT.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the claims can't be serialized or signing fails.
+    pub fn sign<C: Serialize + ?Sized>(&self, claims: &C) -> Result<String, JwtError> {
+        let mut header = serde_json::json!({ "alg": self.algorithm.name(), "typ": "JWT" });
+        if let Some(key_id) = &self.key_id {
+            header["kid"] = serde_json::Value::from(key_id.as_str());
+        }
+
+        let mut token = URL_SAFE_NO_PAD.encode(serde_json::to_vec(&header)?);
+        token.push('.');
+        token.push_str(&URL_SAFE_NO_PAD.encode(serde_json::to_vec(claims)?));
+
+        let signature = match &self.key {
+            SigningKey::Rsa(key) => {
+                let mut signature = vec![0; key.public().modulus_len()];
+                key.sign(&RSA_PKCS1_SHA256, &self.rng, token.as_bytes(), &mut signature)
+                    .map_err(|_| JwtError::Signing)?;
+                signature
+            }
+            SigningKey::Ecdsa(key) => key
+                .sign(&self.rng, token.as_bytes())
+                .map_err(|_| JwtError::Signing)?
+                .as_ref()
+                .to_vec(),
+        };
+
+        token.push('.');
+        token.push_str(&URL_SAFE_NO_PAD.encode(signature));
+        Ok(token)
+    }
+
+    /// Signs `claims` as an OAuth client assertion (RFC 7523).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if signing fails; see [`sign`](Self::sign).
+    pub fn client_assertion<C: Serialize + ?Sized>(&self, claims: &C) -> Result<ClientAssertion, JwtError> {
+        Ok(ClientAssertion {
+            client_assertion_type: ClientAssertion::JWT_BEARER,
+            client_assertion: self.sign(claims)?,
+        })
+    }
+}
+
+impl fmt::Debug for JwtSigner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Never print key material
+        f.debug_struct("JwtSigner")
+            .field("algorithm", &self.algorithm)
+            .field("key_id", &self.key_id)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The `client_assertion` and `client_assertion_type` fields of an OAuth token request.
+///
+/// It serializes to both fields, so it can be flattened into a request's body
+/// or query fields with `#[serde(flatten)]`. Token endpoints that require a
+/// form-encoded body can be sent [`to_form_body`](Self::to_form_body) instead.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ClientAssertion {
+    /// Always [`ClientAssertion::JWT_BEARER`].
+    pub client_assertion_type: &'static str,
+    /// The signed JWT.
+    pub client_assertion: String,
+}
+
+impl ClientAssertion {
+    /// The `client_assertion_type` for JWT client assertions.
+    pub const JWT_BEARER: &'static str = "urn:ietf:params:oauth:client-assertion-type:jwt-bearer";
+
+    /// Returns both fields as an `application/x-www-form-urlencoded` string.
+    pub fn to_form_body(&self) -> String {
+        // A JWT only contains URL-safe base64 and dots, so only the type needs escaping
+        format!(
+            "client_assertion_type={}&client_assertion={}",
+            self.client_assertion_type.replace(':', "%3A"),
+            self.client_assertion
+        )
+    }
+}
+
+/// Decodes the first PEM block in `pem`, returning its label and contents
+fn decode_pem(pem: &str) -> Result<(String, Vec<u8>), JwtError> {
+    let invalid = |message: &str| JwtError::InvalidKey(message.to_string());
+
+    let mut lines = pem.lines().map(str::trim).skip_while(|line| !line.starts_with("-----BEGIN "));
+    let label = lines
+        .next()
+        .and_then(|line| line.strip_prefix("-----BEGIN ")?.strip_suffix("-----"))
+        .ok_or_else(|| invalid("no PEM block found"))?
+        .to_string();
+
+    let mut encoded = String::new();
+    for line in lines {
+        if line.starts_with("-----END ") {
+            let der = STANDARD
+                .decode(&encoded)
+                .map_err(|e| JwtError::InvalidKey(e.to_string()))?;
+            return Ok((label, der));
+        }
+        encoded.push_str(line);
+    }
+    Err(invalid("PEM block is not terminated"))
+}
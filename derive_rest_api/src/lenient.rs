@@ -0,0 +1,149 @@
+//! Error context for `#[request_builder(lenient_response)]`.
+
+use crate::RestApiError;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+/// How many bytes of the body to show on each side of a deserialization error
+const SNIPPET_RADIUS: usize = 40;
+
+/// Where in a response body deserialization failed.
+///
+/// Attached to `RestApiError::ResponseDeserializationError` by requests with
+/// `#[request_builder(lenient_response)]`, to help diagnose schema drift.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeserializationContext {
+    /// Path of the value being read when deserialization failed, such as
+    /// `items[2].price` (`.` for the document itself)
+    pub path: String,
+    /// The body around the failure, truncated to a few dozen bytes either side
+    pub snippet: String,
+    /// The whole body as untyped JSON, if it is valid JSON
+    pub value: Option<Value>,
+}
+
+/// Deserializes a JSON response body, attaching a [`DeserializationContext`] on failure.
+pub fn decode<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, RestApiError> {
+    serde_json::from_slice(bytes).map_err(|source| {
+        let offset = error_offset(bytes, &source);
+        let context = DeserializationContext {
+            path: path_at(bytes, offset),
+            snippet: snippet_at(bytes, offset),
+            value: serde_json::from_slice(bytes).ok(),
+        };
+        RestApiError::ResponseDeserializationError {
+            source,
+            context: Some(Box::new(context)),
+        }
+    })
+}
+
+/// Describes the context of a deserialization error for its `Display` output.
+pub(crate) fn describe(context: &Option<Box<DeserializationContext>>) -> String {
+    match context {
+        Some(context) => format!(" (at `{}`, near `{}`)", context.path, context.snippet),
+        None => String::new(),
+    }
+}
+
+/// Converts the 1-based line and column of a serde_json error into a byte offset.
+///
+/// The offset is that of the last byte serde_json consumed, clamped to the body.
+fn error_offset(bytes: &[u8], error: &serde_json::Error) -> usize {
+    let line_start = bytes
+        .split_inclusive(|&byte| byte == b'\n')
+        .take(error.line().saturating_sub(1))
+        .map(<[u8]>::len)
+        .sum::<usize>();
+    (line_start + error.column().saturating_sub(1)).min(bytes.len())
+}
+
+/// A container being scanned by [`path_at`]
+enum Frame {
+    Object { key: Option<String>, expecting_key: bool },
+    Array { index: usize },
+}
+
+/// Returns the path of the value that contains byte `offset` of a JSON document.
+///
+/// Only the bytes before `offset` are scanned, so this works on truncated or
+/// malformed documents too.
+fn path_at(bytes: &[u8], offset: usize) -> String {
+    let mut stack: Vec<Frame> = Vec::new();
+    let mut position = 0;
+
+    while position < offset {
+        match bytes[position] {
+            b'{' => stack.push(Frame::Object { key: None, expecting_key: true }),
+            b'[' => stack.push(Frame::Array { index: 0 }),
+            b'}' | b']' => {
+                stack.pop();
+            }
+            b',' => match stack.last_mut() {
+                Some(Frame::Object { key, expecting_key }) => {
+                    *key = None;
+                    *expecting_key = true;
+                }
+                Some(Frame::Array { index }) => *index += 1,
+                None => {}
+            },
+            b'"' => {
+                let start = position + 1;
+                position = start;
+                while position < offset && bytes[position] != b'"' {
+                    // Skip the escaped character
+                    if bytes[position] == b'\\' {
+                        position += 1;
+                    }
+                    position += 1;
+                }
+                if let Some(Frame::Object { key, expecting_key }) = stack.last_mut()
+                    && *expecting_key
+                {
+                    let end = position.min(offset);
+                    *key = Some(String::from_utf8_lossy(&bytes[start..end]).into_owned());
+                    *expecting_key = false;
+                }
+            }
+            _ => {}
+        }
+        position += 1;
+    }
+
+    // An error on a closing bracket, such as a missing field, is about the
+    // container itself rather than its last member
+    let closing = matches!(bytes.get(offset), Some(b'}' | b']'));
+    let members = stack.len() - usize::from(closing && !stack.is_empty());
+
+    let mut path = String::new();
+    for frame in &stack[..members] {
+        match frame {
+            Frame::Object { key: Some(key), .. } => {
+                if !path.is_empty() {
+                    path.push('.');
+                }
+                path.push_str(key);
+            }
+            Frame::Object { key: None, .. } => {}
+            Frame::Array { index } => path.push_str(&format!("[{}]", index)),
+        }
+    }
+    if path.is_empty() {
+        path.push('.');
+    }
+    path
+}
+
+/// Returns the body around byte `offset`, with `…` marking truncation.
+fn snippet_at(bytes: &[u8], offset: usize) -> String {
+    let start = offset.saturating_sub(SNIPPET_RADIUS);
+    let end = (offset + SNIPPET_RADIUS).min(bytes.len());
+    let mut snippet = String::from_utf8_lossy(&bytes[start..end]).into_owned();
+    if start > 0 {
+        snippet.insert(0, '…');
+    }
+    if end < bytes.len() {
+        snippet.push('…');
+    }
+    snippet
+}
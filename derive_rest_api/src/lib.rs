@@ -2,9 +2,15 @@
 //!
 //! A procedural macro library for generating type-safe builder patterns for REST API request structures.
 //!
-//! This library provides two derive macros:
+//! This library provides three derive macros:
 //! - `RequestBuilder` - Generates builder patterns for individual API requests
 //! - `ApiClient` - Generates high-level client structs that wrap multiple requests
+//! - `RequestEnum` - Generates dispatching request methods for enums of endpoints
+//!
+//! The `define_api!` macro declares a client and its endpoints in a compact table,
+//! expanding to request structs deriving `RequestBuilder` and a client deriving `ApiClient`.
+//! The `#[rest_api]` attribute does the same for a trait of endpoint methods, and
+//! implements the trait (and an async copy of it) for the generated clients.
 //!
 //! ## Features
 //!
@@ -14,7 +20,7 @@
 //! - Flexible type conversion with `Into<T>`
 //! - Default value handling
 //! - Type-safe error handling with `thiserror`
-//! - Support for multiple HTTP client backends (reqwest, ureq, or custom)
+//! - Support for multiple HTTP client backends (reqwest, ureq, isahc, attohttpc, surf, browser fetch, or custom)
 //!
 //! ## Basic RequestBuilder Example
 //!
@@ -99,24 +105,78 @@
 //! }
 //! ```
 //!
+//! `build()` stops at the first problem. Use `try_build()` to collect every missing field and
+//! validation failure into a [`BuildErrors`] instead:
+//!
+//! ```rust,ignore
+//! if let Err(errors) = builder.try_build() {
+//!     for error in errors.errors() {
+//!         eprintln!("{}", error);
+//!     }
+//! }
+//! ```
+//!
 //! ## Struct-level Attributes
 //!
 //! - `#[request_builder(into)]` - Enable `Into<T>` conversion for all setter methods
 //! - `#[request_builder(default)]` - Use `Default::default()` for unset fields
 //! - `#[request_builder(method = "...")]` - Specify HTTP method (GET, POST, etc.)
 //! - `#[request_builder(path = "...")]` - URL path template with `{param}` placeholders
+//!   (`{param*}` takes a multi-segment path, percent-encoded except for its slashes)
 //! - `#[request_builder(response = Type)]` - Specify the response type
-//! - `#[request_builder(query_config = "...")]` - Custom query string serialization config
+//!   (`response = ()` discards the body, so `send()` returns `()`)
+//! - `#[request_builder(response_format = "jsonapi")]` - Decode a JSON:API document, returning a `JsonApiDocument`
+//!   whose `data` is the response type flattened from the resource objects' attributes
+//! - `#[request_builder(response_format = "negotiate")]` - Return a `Negotiated` holding the response type for JSON
+//!   bodies, or the body as text or bytes otherwise, depending on the response's `Content-Type`
+//! - `#[request_builder(unmarked_fields = "warn")]` - Warn about (default), reject (`"error"`) or allow (`"allow"`)
+//!   fields that aren't sent because they have no kind and aren't path placeholders
+//! - `#[request_builder(query_config_fn = path::to::fn)]` - Function returning the `serde_qs::Config` for the query string
+//! - `#[request_builder(query_config = "...")]` - Custom query string serialization config, as an expression in a string
+//! - `#[request_builder(query_encoder = "urlencoded")]` - Encode the query string as flat pairs (`"urlencoded"`),
+//!   with `serde_qs` (`"serde_qs"`, the default) or with an expression giving a `QueryEncoder`, such as
+//!   `UrlEncodedEncoder::rfc3986()`
+//! - `#[request_builder(query_order = "sorted")]` - Sort the query parameters by name instead of sending them in
+//!   declaration order (`"declared"`, the default)
+//! - `#[request_builder(static_query("name" = "value", ...))]` - Constant query parameters, sent before the query fields
+//! - `#[request_builder(static_header("Name" = "value", ...))]` - Constant headers, set before the header fields
+//! - `#[request_builder(garde)]` - Run `garde::Validate` (with the default context) during `build()`; requires the `garde` feature
+//! - `#[request_builder(arbitrary)]` - Implement `arbitrary::Arbitrary` for the builder and the struct, which only
+//!   yields requests that pass `build()`, for fuzzing and property tests; requires the `arbitrary` feature
+//! - `#[request_builder(cli)]` - Implement `clap::Args` for the struct, with a `--flag` per field; requires the
+//!   `cli` feature
+//! - `#[request_builder(checksum = "...")]` - Send a `Content-MD5` (`md5`) or `x-amz-checksum-*` (`sha1`, `sha256`, `crc32`,
+//!   `crc32c`) header for the request body; requires the `checksum` feature
+//! - `#[request_builder(verify_checksum)]` - Check those headers on the response against its body, failing with
+//!   `RestApiError::ChecksumMismatch`; requires the `checksum` feature
+//! - `#[request_builder(graphql, query = "...", operation_name = "...")]` - Send a GraphQL operation, POSTed to
+//!   `/graphql` unless `path` is given, with the non-path, non-query, non-header fields as its variables. The
+//!   response's `data` is returned and its `errors` become `RestApiError::GraphQlErrors`
+//! - `#[request_builder(poll(done_values("..."), status_field = "...", interval_ms = N, timeout_ms = N))]` - Generate
+//!   `send_and_wait()`/`send_and_wait_async()`, which poll the `Location` header URL (or the request URL) until the
+//!   status field holds one of `done_values`
 //!
 //! ## Field-level Attributes
 //!
 //! - `#[request_builder(path)]` - Mark field as URL path parameter
 //! - `#[request_builder(query)]` or `#[request_builder(query = "name")]` - Include field in query string (with optional custom name)
 //! - `#[request_builder(body)]` or `#[request_builder(body = "name")]` - Mark field as request body (with optional custom name)
+//! - `#[request_builder(body, flatten)]` - Merge the keys of a `Serialize` field into the top-level body object
+//! - `#[request_builder(query, flatten)]` - Send the keys of a `Serialize` field as query parameters, so groups such
+//!   as pagination can be shared between requests
 //! - `#[request_builder(header)]` or `#[request_builder(header = "Header-Name")]` - Mark field as HTTP header (auto-converts snake_case to Title-Case, or use custom name)
+//!   (`Vec<T>` fields send one header value per element)
 //! - `#[request_builder(into)]` - Enable `Into<T>` conversion for this field
 //! - `#[request_builder(default)]` - Use default value if not set
 //! - `#[request_builder(validate = "fn_path")]` - Specify custom validation function
+//! - `#[request_builder(skip)]` - Exclude field from the builder (value comes from `Default` or `default = expr`)
+//! - `#[request_builder(const_value = expr)]` - Exclude field from the builder and always set it to `expr`, for fixed
+//!   protocol fields such as `grant_type`
+//! - `#[request_builder(example = expr)]` - Value the generated `sample()` constructor passes to this field's setter;
+//!   other fields use their default, `None` or `Default::default()`
+//! - `#[request_builder(computed = "expr_or_fn")]` - Compute the field during `build()`. An expression sees
+//!   the other fields by reference under their own names; a function path is called with `&Self` once every
+//!   other field is set
 //!
 //! ## Serde Integration
 //!
@@ -128,26 +188,155 @@
 mod traits;
 mod clients;
 mod error;
+mod error_json;
+mod http_error_context;
+mod cancellation;
+mod timeouts;
+mod pool_options;
+mod http_protocol;
+mod ip_family;
+mod tls_version;
+#[cfg(unix)]
+mod unix_socket;
+mod request_options;
+mod poll;
+mod batch;
+mod built_request;
+mod canonical;
+mod query;
+mod mock;
+mod contract;
+#[cfg(not(target_arch = "wasm32"))]
+mod throttle;
+#[cfg(not(target_arch = "wasm32"))]
+mod cache;
+mod graphql;
+mod jsonapi;
+mod negotiate;
+mod lenient;
+mod unknown_fields;
+mod deprecation;
+#[cfg(not(target_arch = "wasm32"))]
+mod fault_injection;
+mod rate_limit;
+mod failover;
+mod health;
+mod retry;
+mod clock;
+mod middleware;
+mod request_metrics;
+mod events;
+mod digest;
+mod audit;
+mod offline;
+#[cfg(not(target_arch = "wasm32"))]
+mod background;
+mod transport;
+mod credentials;
+#[cfg(feature = "checksum")]
+mod checksum;
+#[cfg(feature = "jwt")]
+mod jwt;
+#[cfg(feature = "digest-auth")]
+mod digest_auth;
+#[cfg(feature = "config")]
+mod config_loader;
+
+#[doc(hidden)]
+pub mod __private;
 
 // Re-exports
-pub use derive_rest_api_macros::{ApiClient, RequestBuilder};
-pub use traits::{AsyncHttpClient, HttpClient, RequestModifier, ConfigureRequest, NoRequestConfiguration};
-pub use error::RestApiError;
+pub use derive_rest_api_macros::{ApiClient, RequestBuilder, RequestEnum, define_api, rest_api};
+pub use traits::{AsyncHttpClient, HttpClient, RequestModifier, ResponseFuture, RestRequest, ConfigureRequest, NoRequestConfiguration};
+pub use error::{BuildErrors, ErrorKind, RestApiError};
+pub use http_error_context::HttpErrorContext;
+pub use cancellation::{CancellationDropGuard, CancellationToken, WaitForCancellation};
+pub use timeouts::Timeouts;
+pub use pool_options::PoolOptions;
+pub use http_protocol::HttpProtocol;
+pub use ip_family::IpFamily;
+pub use tls_version::TlsVersion;
+#[cfg(unix)]
+pub use unix_socket::UnixSocketUrl;
+pub use request_options::RequestOptions;
+pub use batch::{Batch, BatchHandle, BatchResponse};
+pub use built_request::BuiltRequest;
+pub use canonical::CanonicalRequest;
+#[cfg(not(target_arch = "wasm32"))]
+pub use background::BackgroundSender;
+pub use offline::{ConflictHook, ConflictResolution, OfflineQueue, QueuedRequest};
+pub use query::{QueryEncoder, QueryOrder, SerdeQsEncoder, SpaceEncoding, UrlEncodedEncoder};
+pub use mock::{MockClient, RequestMatcher};
+pub use contract::{ContractMismatch, OpenApiSpec};
+#[cfg(not(target_arch = "wasm32"))]
+pub use throttle::{ThrottlePolicy, ThrottleRegistry, ThrottledClient};
+#[cfg(not(target_arch = "wasm32"))]
+pub use cache::{CachingClient, ResponseCache};
+pub use graphql::{GraphQlError, GraphQlRequest};
+pub use jsonapi::{JSONAPI_MEDIA_TYPE, JsonApiDocument, JsonApiResource};
+pub use negotiate::{NEGOTIATE_ACCEPT, Negotiated};
+pub use lenient::DeserializationContext;
+pub use unknown_fields::WithUnknownFields;
+pub use deprecation::{DeprecationClient, DeprecationNotice};
+#[cfg(not(target_arch = "wasm32"))]
+pub use fault_injection::{FaultInjectingClient, FaultInjectionError};
+pub use rate_limit::{RateLimitInfo, RateLimitState};
+pub use failover::Failover;
+pub use retry::{RetryBudget, RetryPolicy};
+pub use clock::{Clock, ManualClock, SystemClock};
+pub use middleware::{Middleware, MiddlewareLayer, MiddlewareStack, RequestParts};
+pub use request_metrics::{MetricsHook, RequestMetrics};
+pub use events::{EventSubscriber, RequestEvent, RequestEventKind};
+pub use audit::{AuditRecord, AuditSink};
+#[cfg(feature = "metrics")]
+pub use request_metrics::MetricsFacade;
+pub use credentials::{AsyncCredentialsProvider, Credentials, CredentialsFuture, CredentialsProvider, RotatingCredentials};
+
+#[cfg(feature = "checksum")]
+pub use checksum::{ChecksumAlgorithm, verify_checksums};
+
+#[cfg(feature = "digest-auth")]
+pub use digest_auth::DigestAuthClient;
+
+#[cfg(feature = "jwt")]
+pub use jwt::{ClientAssertion, JwtAlgorithm, JwtClaims, JwtError, JwtSigner};
+
+/// Re-export of the `http` crate, whose header types are used for request headers.
+pub use http;
 
 #[cfg(feature = "reqwest-blocking")]
-pub use clients::ReqwestBlockingClient;
+pub use clients::{ReqwestBlockingClient, ReqwestBlockingClientBuilder};
 
 #[cfg(feature = "reqwest-async")]
 pub use clients::ReqwestAsyncClient;
 
+#[cfg(all(feature = "reqwest-async", not(target_arch = "wasm32")))]
+pub use clients::ReqwestAsyncClientBuilder;
+
 #[cfg(feature = "ureq-blocking")]
 pub use clients::UreqBlockingClient;
 
+#[cfg(feature = "isahc")]
+pub use clients::IsahcClient;
+
+#[cfg(feature = "attohttpc-blocking")]
+pub use clients::AttohttpcClient;
+
+#[cfg(feature = "surf")]
+pub use clients::{SurfClient, SurfClientError};
+
+#[cfg(all(feature = "wasm-fetch", target_arch = "wasm32"))]
+pub use clients::{FetchBodyStream, WasmFetchClient, WasmFetchError};
+
 cfg_if::cfg_if! {
     if #[cfg(feature = "ureq-blocking")] {
         pub type DefaultBlockingClient = UreqBlockingClient;
     } else if #[cfg(feature = "reqwest-blocking")] {
         pub type DefaultBlockingClient = ReqwestBlockingClient;
+    } else if #[cfg(feature = "isahc")] {
+        pub type DefaultBlockingClient = IsahcClient;
+    } else if #[cfg(feature = "attohttpc-blocking")] {
+        pub type DefaultBlockingClient = AttohttpcClient;
     } else {
         pub type DefaultBlockingClient = clients::UnimplementedClient;
     }
@@ -156,6 +345,12 @@ cfg_if::cfg_if! {
 cfg_if::cfg_if! {
     if #[cfg(feature = "reqwest-async")] {
         pub type DefaultAsyncClient = ReqwestAsyncClient;
+    } else if #[cfg(feature = "isahc")] {
+        pub type DefaultAsyncClient = IsahcClient;
+    } else if #[cfg(feature = "surf")] {
+        pub type DefaultAsyncClient = SurfClient;
+    } else if #[cfg(all(feature = "wasm-fetch", target_arch = "wasm32"))] {
+        pub type DefaultAsyncClient = WasmFetchClient;
     } else {
         pub type DefaultAsyncClient = clients::UnimplementedClient;
     }
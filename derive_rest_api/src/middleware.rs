@@ -0,0 +1,173 @@
+//! Hooks run around every HTTP request a client sends.
+
+use crate::CanonicalRequest;
+use http::{HeaderMap, Response};
+use std::sync::Arc;
+
+/// A request about to be sent to the HTTP client.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestParts {
+    /// The HTTP method, such as `GET`
+    pub method: String,
+    /// The full URL, including the query string
+    pub url: String,
+    /// The request headers
+    pub headers: HeaderMap,
+    /// The serialized body, if any
+    pub body: Option<Vec<u8>>,
+}
+
+impl RequestParts {
+    /// Returns the request in a normalized form, for signing or as a cache key.
+    ///
+    /// Middleware signing requests should run after any middleware that
+    /// changes them, so should be added last.
+    pub fn canonical_representation(&self) -> CanonicalRequest {
+        CanonicalRequest::new(&self.method, &self.url, &self.headers, self.body.as_deref())
+    }
+}
+
+/// Code run around every HTTP request sent by a client, such as signing or
+/// logging.
+///
+/// Middleware sees each attempt separately, so a retried request or one
+/// failing over to another base URL passes through it again. Both hooks do
+/// nothing by default.
+///
+/// # Example
+///
+/// ```
+/// use derive_rest_api::{Middleware, RequestParts};
+///
+/// struct RequestId;
+///
+/// impl Middleware for RequestId {
+///     fn on_request(&self, request: &mut RequestParts) {
+///         request.headers.insert("X-Request-Id", "c0ffee".parse().unwrap());
+///     }
+/// }
+/// ```
+pub trait Middleware: Send + Sync + 'static {
+    /// Called before the request is sent, and may change it.
+    fn on_request(&self, request: &mut RequestParts) {
+        let _ = request;
+    }
+
+    /// Called with the response to a request, before it is checked or decoded.
+    ///
+    /// Not called when the HTTP client fails.
+    fn on_response(&self, method: &str, url: &str, response: &mut Response<Vec<u8>>) {
+        let _ = (method, url, response);
+    }
+
+    /// Names the middleware in [`MiddlewareStack::chain`], its type name by default.
+    fn name(&self) -> &'static str {
+        std::any::type_name::<Self>()
+    }
+}
+
+/// A middleware's place in a [`MiddlewareStack`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MiddlewareLayer {
+    /// The middleware's [`name`](Middleware::name)
+    pub name: &'static str,
+    /// The priority it was added with, `0` if none
+    pub priority: i32,
+}
+
+/// The middleware of a client, ordered by priority, then in the order it was
+/// added.
+///
+/// `on_request` hooks run in that order, and `on_response` hooks in reverse,
+/// so the middleware with the lowest priority wraps all the others. Middleware
+/// added without a priority has priority `0`, so giving a priority only
+/// matters when order does: logging at a negative priority sees requests
+/// before anything changes them, and signing at a positive one runs after
+/// everything that does, whichever order they were added in.
+/// [`chain`](Self::chain) shows the resulting order. Clones share the
+/// middleware themselves.
+///
+/// # Example
+///
+/// ```
+/// use derive_rest_api::{Middleware, MiddlewareStack, RequestParts};
+///
+/// struct Signer;
+/// impl Middleware for Signer {}
+///
+/// struct Logger;
+/// impl Middleware for Logger {
+///     fn name(&self) -> &'static str {
+///         "logger"
+///     }
+/// }
+///
+/// let mut stack = MiddlewareStack::new();
+/// stack.insert(100, Signer);
+/// stack.insert(-100, Logger);
+///
+/// let chain = stack.chain();
+/// assert_eq!((chain[0].name, chain[0].priority), ("logger", -100));
+/// // Named after its type by default
+/// assert!(chain[1].name.ends_with("Signer"));
+/// ```
+#[derive(Clone, Default)]
+pub struct MiddlewareStack {
+    layers: Vec<(i32, Arc<dyn Middleware>)>,
+}
+
+impl MiddlewareStack {
+    /// Creates an empty stack.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `middleware` with priority `0`, inside those already added with
+    /// the same or a lower priority.
+    pub fn push(&mut self, middleware: impl Middleware) {
+        self.insert(0, middleware);
+    }
+
+    /// Adds `middleware` with `priority`, inside those already added with the
+    /// same or a lower priority, and outside those with a higher one.
+    pub fn insert(&mut self, priority: i32, middleware: impl Middleware) {
+        let index = self.layers.partition_point(|(existing, _)| *existing <= priority);
+        self.layers.insert(index, (priority, Arc::new(middleware)));
+    }
+
+    /// Returns the middleware in the order their `on_request` hooks run.
+    pub fn chain(&self) -> Vec<MiddlewareLayer> {
+        self.layers
+            .iter()
+            .map(|(priority, middleware)| MiddlewareLayer { name: middleware.name(), priority: *priority })
+            .collect()
+    }
+
+    /// Returns how many middleware the stack has.
+    pub fn len(&self) -> usize {
+        self.layers.len()
+    }
+
+    /// Returns `true` if the stack has no middleware.
+    pub fn is_empty(&self) -> bool {
+        self.layers.is_empty()
+    }
+
+    pub(crate) fn on_request(&self, request: &mut RequestParts) {
+        for (_, layer) in &self.layers {
+            layer.on_request(request);
+        }
+    }
+
+    pub(crate) fn on_response(&self, method: &str, url: &str, response: &mut Response<Vec<u8>>) {
+        for (_, layer) in self.layers.iter().rev() {
+            layer.on_response(method, url, response);
+        }
+    }
+}
+
+impl std::fmt::Debug for MiddlewareStack {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.chain()).finish()
+    }
+}
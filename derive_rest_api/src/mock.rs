@@ -0,0 +1,380 @@
+//! A recording HTTP client and request matchers for tests.
+
+use crate::{AsyncHttpClient, BuiltRequest, HttpClient, RequestOptions};
+use http::{HeaderMap, Response, StatusCode};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+/// HTTP client that records every request and answers with queued responses.
+///
+/// Responses queued with [`push_response`](Self::push_response) are returned
+/// in order, then every request gets an empty `200 OK`. Clones share the
+/// recorded requests and the queue, so a test can keep one handle while a
+/// generated client owns another. Requests are recorded as [`BuiltRequest`]s
+/// and can be checked with [`assert_request!`](crate::assert_request).
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use derive_rest_api::{MockClient, assert_request};
+///
+/// let http = MockClient::new();
+/// http.push_json(StatusCode::OK, &serde_json::json!({"id": 42, "name": "Ada"}));
+/// let client = UsersClient::<MockClient>::new_with_client(http.clone());
+/// client.get_user().id(42).send()?;
+///
+/// assert_request!(http, GET "/users/{id}");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MockClient {
+    state: Arc<Mutex<MockState>>,
+}
+
+#[derive(Debug, Default)]
+struct MockState {
+    requests: Vec<BuiltRequest>,
+    responses: VecDeque<Response<Vec<u8>>>,
+}
+
+impl MockClient {
+    /// Creates a client with no recorded requests or queued responses.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queues `response` as the answer to the next unanswered request.
+    pub fn push_response(&self, response: Response<Vec<u8>>) {
+        self.lock().responses.push_back(response);
+    }
+
+    /// Queues a response with `status` and `body` serialized as JSON.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `body` can't be serialized.
+    pub fn push_json(&self, status: StatusCode, body: &impl serde::Serialize) {
+        let body = serde_json::to_vec(body).expect("mock response body should serialize");
+        let response = Response::builder()
+            .status(status)
+            .header(http::header::CONTENT_TYPE, "application/json")
+            .body(body)
+            .expect("mock response should be valid");
+        self.push_response(response);
+    }
+
+    /// Returns the requests received so far, oldest first.
+    pub fn requests(&self) -> Vec<BuiltRequest> {
+        self.lock().requests.clone()
+    }
+
+    /// Forgets the recorded requests and queued responses.
+    pub fn clear(&self) {
+        let mut state = self.lock();
+        state.requests.clear();
+        state.responses.clear();
+    }
+
+    /// Returns the first recorded request matching `matcher`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no request matches, listing why each recorded request didn't.
+    #[track_caller]
+    pub fn assert_received(&self, matcher: &RequestMatcher) -> BuiltRequest {
+        let requests = self.requests();
+        let mut mismatches = Vec::new();
+        for request in &requests {
+            match matcher.mismatch(request) {
+                None => return request.clone(),
+                Some(reason) => mismatches.push(format!("  {} {}: {}", request.method, request.url, reason)),
+            }
+        }
+        if requests.is_empty() {
+            panic!("expected a request matching {}, but none were received", matcher);
+        }
+        panic!("expected a request matching {}, but received:\n{}", matcher, mismatches.join("\n"));
+    }
+
+    fn respond(&self, method: &str, url: &str, headers: &HeaderMap, body: Option<Vec<u8>>) -> Response<Vec<u8>> {
+        let mut state = self.lock();
+        state.requests.push(BuiltRequest::new(method, url, headers, body));
+        state.responses.pop_front().unwrap_or_else(|| Response::new(Vec::new()))
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, MockState> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl HttpClient for MockClient {
+    type Error = std::convert::Infallible;
+
+    fn send(
+        &self,
+        method: &str,
+        url: &str,
+        headers: HeaderMap,
+        body: Option<Vec<u8>>,
+        _options: RequestOptions,
+    ) -> Result<Response<Vec<u8>>, Self::Error> {
+        Ok(self.respond(method, url, &headers, body))
+    }
+}
+
+impl AsyncHttpClient for MockClient {
+    type Error = std::convert::Infallible;
+
+    async fn send_async(
+        &self,
+        method: &str,
+        url: &str,
+        headers: HeaderMap,
+        body: Option<Vec<u8>>,
+        _options: RequestOptions,
+    ) -> Result<Response<Vec<u8>>, Self::Error> {
+        Ok(self.respond(method, url, &headers, body))
+    }
+}
+
+/// A JSON body predicate
+type JsonPredicate = Box<dyn Fn(&serde_json::Value) -> bool + Send + Sync>;
+
+/// What a request is expected to look like, for [`MockClient::assert_received`].
+///
+/// A request matches when it has the method, its URL path matches the path
+/// pattern, and it has every expected query parameter, header and body.
+/// Segments of the pattern written `{name}` match any single segment, as in
+/// `#[request_builder(path = "...")]`. Query parameters are compared after
+/// percent-decoding and in any order, and header names case-insensitively;
+/// parameters and headers that aren't mentioned are ignored.
+///
+/// # Example
+///
+/// ```
+/// use derive_rest_api::{BuiltRequest, RequestMatcher};
+/// use derive_rest_api::http::HeaderMap;
+///
+/// let request = BuiltRequest::new(
+///     "GET",
+///     "https://api.example.com/users/42/posts?sort=new&page=2",
+///     &HeaderMap::new(),
+///     None,
+/// );
+/// let matcher = RequestMatcher::new("GET", "/users/{id}/posts").query([("page", "2"), ("sort", "new")]);
+/// assert!(matcher.matches(&request));
+/// assert!(!RequestMatcher::new("GET", "/users/{id}").matches(&request));
+/// ```
+pub struct RequestMatcher {
+    method: String,
+    path: String,
+    query: Vec<(String, String)>,
+    headers: Vec<(String, String)>,
+    json: Option<JsonPredicate>,
+}
+
+impl RequestMatcher {
+    /// Expects a request with `method` to a path matching `path`.
+    pub fn new(method: impl Into<String>, path: impl Into<String>) -> Self {
+        Self {
+            method: method.into(),
+            path: path.into(),
+            query: Vec::new(),
+            headers: Vec::new(),
+            json: None,
+        }
+    }
+
+    /// Expects each of the query parameters `params`, in any order.
+    pub fn query<K: Into<String>, V: Into<String>>(mut self, params: impl IntoIterator<Item = (K, V)>) -> Self {
+        self.query.extend(params.into_iter().map(|(name, value)| (name.into(), value.into())));
+        self
+    }
+
+    /// Expects each of the headers `headers`.
+    pub fn headers<K: Into<String>, V: Into<String>>(mut self, headers: impl IntoIterator<Item = (K, V)>) -> Self {
+        self.headers.extend(headers.into_iter().map(|(name, value)| (name.into(), value.into())));
+        self
+    }
+
+    /// Expects a JSON body for which `predicate` returns `true`.
+    pub fn json(mut self, predicate: impl Fn(&serde_json::Value) -> bool + Send + Sync + 'static) -> Self {
+        self.json = Some(Box::new(predicate));
+        self
+    }
+
+    /// Returns `true` if `request` matches.
+    pub fn matches(&self, request: &BuiltRequest) -> bool {
+        self.mismatch(request).is_none()
+    }
+
+    /// Returns why `request` doesn't match, or `None` if it does.
+    pub fn mismatch(&self, request: &BuiltRequest) -> Option<String> {
+        if !request.method.eq_ignore_ascii_case(&self.method) {
+            return Some(format!("method is {}", request.method));
+        }
+        let (path, query) = split_url(&request.url);
+        if !path_matches(&self.path, path) {
+            return Some(format!("path is {}", path));
+        }
+
+        let mut params: Vec<_> = query.map(parse_query).unwrap_or_default();
+        for (name, value) in &self.query {
+            match params.iter().position(|param| param.0 == *name && param.1 == *value) {
+                // Each received parameter satisfies one expected parameter
+                Some(index) => {
+                    params.remove(index);
+                }
+                None => return Some(format!("no query parameter {}={}", name, value)),
+            }
+        }
+
+        for (name, value) in &self.headers {
+            let found = request
+                .headers
+                .iter()
+                .any(|(header, received)| header.eq_ignore_ascii_case(name) && received == value);
+            if !found {
+                return Some(match request.header(name) {
+                    Some(received) => format!("header {} is {}", name, received),
+                    None => format!("no header {}", name),
+                });
+            }
+        }
+
+        if let Some(predicate) = &self.json {
+            let Some(body) = &request.body else {
+                return Some("no body".to_string());
+            };
+            match serde_json::from_slice(body) {
+                Ok(json) if predicate(&json) => {}
+                Ok(json) => return Some(format!("body {} doesn't match", json)),
+                Err(_) => return Some("body isn't JSON".to_string()),
+            }
+        }
+        None
+    }
+}
+
+impl std::fmt::Display for RequestMatcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.method, self.path)?;
+        for (name, value) in &self.query {
+            write!(f, " {}={}", name, value)?;
+        }
+        for (name, value) in &self.headers {
+            write!(f, " [{}: {}]", name, value)?;
+        }
+        if self.json.is_some() {
+            write!(f, " with a matching JSON body")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for RequestMatcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RequestMatcher")
+            .field("method", &self.method)
+            .field("path", &self.path)
+            .field("query", &self.query)
+            .field("headers", &self.headers)
+            .field("json", &self.json.as_ref().map(|_| "<fn>"))
+            .finish()
+    }
+}
+
+/// Splits a URL into its path and query string, dropping the scheme, host and fragment
+pub(crate) fn split_url(url: &str) -> (&str, Option<&str>) {
+    let url = url.split('#').next().unwrap_or(url);
+    let rest = match url.split_once("://") {
+        Some((_, rest)) => rest.find('/').map_or("", |start| &rest[start..]),
+        None => url,
+    };
+    match rest.split_once('?') {
+        Some((path, query)) => (path, Some(query)),
+        None => (rest, None),
+    }
+}
+
+/// Returns `true` if `path` matches `pattern`, whose `{name}` segments match any one segment
+pub(crate) fn path_matches(pattern: &str, path: &str) -> bool {
+    let mut pattern = pattern.trim_end_matches('/').split('/');
+    let mut path = path.trim_end_matches('/').split('/');
+    loop {
+        match (pattern.next(), path.next()) {
+            (None, None) => return true,
+            (Some(expected), Some(segment)) => {
+                let is_param = expected.starts_with('{') && expected.ends_with('}');
+                if !((is_param && !segment.is_empty()) || expected == segment) {
+                    return false;
+                }
+            }
+            _ => return false,
+        }
+    }
+}
+
+/// Parses a query string into percent-decoded name and value pairs
+pub(crate) fn parse_query(query: &str) -> Vec<(String, String)> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(name), percent_decode(value))
+        })
+        .collect()
+}
+
+/// Decodes `%XX` escapes and `+` as a space
+fn percent_decode(input: &str) -> String {
+    let mut bytes = Vec::with_capacity(input.len());
+    let mut rest = input.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        match byte {
+            b'+' => bytes.push(b' '),
+            b'%' if tail.len() >= 2 => match u8::from_str_radix(std::str::from_utf8(&tail[..2]).unwrap_or(""), 16) {
+                Ok(decoded) => {
+                    bytes.push(decoded);
+                    rest = &tail[2..];
+                    continue;
+                }
+                Err(_) => bytes.push(byte),
+            },
+            _ => bytes.push(byte),
+        }
+        rest = tail;
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// Asserts that a [`MockClient`] received a matching request, returning it.
+///
+/// The method and path pattern come first, as in `define_api!`, followed by
+/// any of `query = [...]` and `headers = [...]`, lists of name and value
+/// pairs, and `json = |body| ...`, a predicate on the JSON body. See
+/// [`RequestMatcher`] for how requests are matched.
+///
+/// # Panics
+///
+/// Panics if no recorded request matches, listing why each one didn't.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use derive_rest_api::assert_request;
+///
+/// assert_request!(http, GET "/users/{id}/posts", query = [("page", "2")]);
+/// let request = assert_request!(
+///     http,
+///     POST "/users",
+///     headers = [("content-type", "application/json")],
+///     json = |body| body["name"] == "Ada",
+/// );
+/// ```
+#[macro_export]
+macro_rules! assert_request {
+    ($mock:expr, $method:ident $path:literal $(, $key:ident = $value:expr)* $(,)?) => {
+        $mock.assert_received(&$crate::RequestMatcher::new(stringify!($method), $path) $(.$key($value))*)
+    };
+}
@@ -0,0 +1,59 @@
+//! Responses that may not be JSON, for `response_format = "negotiate"`.
+
+use serde::de::DeserializeOwned;
+
+/// The `Accept` header sent with `response_format = "negotiate"` requests.
+pub const NEGOTIATE_ACCEPT: &str = "application/json, text/*;q=0.9, */*;q=0.8";
+
+/// A response body decoded according to its `Content-Type`.
+///
+/// Endpoints behind proxies or gateways sometimes answer with an HTML error
+/// page or plain text instead of JSON. Rather than failing deserialization,
+/// such bodies are returned as text, or as bytes if they aren't UTF-8.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Negotiated<T> {
+    /// A JSON body that deserialized into `T`
+    Json(T),
+    /// A body that isn't JSON, or didn't match `T`, decoded as UTF-8
+    Text(String),
+    /// A body that isn't JSON and isn't valid UTF-8
+    Bytes(Vec<u8>),
+}
+
+impl<T: DeserializeOwned> Negotiated<T> {
+    /// Decodes a response body according to its `Content-Type`.
+    ///
+    /// Bodies labelled as JSON (`application/json` or a `+json` media type), or
+    /// without a `Content-Type`, are deserialized into `T`. Anything else, or
+    /// JSON that doesn't match `T`, falls back to [`Text`](Self::Text) or
+    /// [`Bytes`](Self::Bytes).
+    pub fn from_response(response: http::Response<Vec<u8>>) -> Self {
+        let content_type = response
+            .headers()
+            .get(http::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.split(';').next().unwrap_or_default().trim().to_ascii_lowercase());
+        let body = response.into_body();
+
+        let is_json = content_type
+            .as_deref()
+            .is_none_or(|media_type| media_type == "application/json" || media_type.ends_with("+json"));
+        if is_json && let Ok(value) = serde_json::from_slice(&body) {
+            return Self::Json(value);
+        }
+        match String::from_utf8(body) {
+            Ok(text) => Self::Text(text),
+            Err(error) => Self::Bytes(error.into_bytes()),
+        }
+    }
+}
+
+impl<T> Negotiated<T> {
+    /// Returns the deserialized value, if the body was JSON.
+    pub fn into_json(self) -> Option<T> {
+        match self {
+            Self::Json(value) => Some(value),
+            Self::Text(_) | Self::Bytes(_) => None,
+        }
+    }
+}
@@ -0,0 +1,411 @@
+//! A file-backed queue of requests made while offline, replayed in order once
+//! the API can be reached again.
+
+use crate::RestApiError;
+use crate::credentials::is_sensitive;
+use crate::digest::hex;
+use http::{HeaderMap, HeaderName, HeaderValue, Response, StatusCode};
+use serde_json::{Value, json};
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// A request that couldn't be sent while offline, waiting to be replayed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueuedRequest {
+    /// Identifies the request within its queue, in the order requests were queued
+    pub id: u64,
+    pub method: String,
+    /// The base URL the request was made against
+    pub base_url: String,
+    /// The path and query of the request
+    pub path: String,
+    /// The request's headers, without the credentials of a credentials provider,
+    /// which are applied again when it's replayed. Sensitive headers are only
+    /// kept in memory, unless the queue persists them.
+    pub headers: HeaderMap,
+    pub body: Option<Vec<u8>>,
+    /// When the request was queued
+    pub queued_at: SystemTime,
+}
+
+/// What to do with a replayed request the server rejected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConflictResolution {
+    /// Drop the request and replay the ones after it
+    Discard,
+    /// Send this request in its place, such as one with a fresh `If-Match`
+    Resend(Box<QueuedRequest>),
+    /// Keep the request at the front of the queue and stop replaying for now
+    Keep,
+}
+
+/// Decides what happens to a replayed request that got a `4xx` response,
+/// typically a `409 Conflict` or `412 Precondition Failed` because the resource
+/// changed while the client was offline.
+///
+/// Implemented for closures taking the request and the response.
+pub trait ConflictHook: Send + Sync + 'static {
+    /// Returns what to do with `request`, which was answered with `response`.
+    fn on_conflict(&self, request: &QueuedRequest, response: &Response<Vec<u8>>) -> ConflictResolution;
+}
+
+impl<F> ConflictHook for F
+where
+    F: Fn(&QueuedRequest, &Response<Vec<u8>>) -> ConflictResolution + Send + Sync + 'static,
+{
+    fn on_conflict(&self, request: &QueuedRequest, response: &Response<Vec<u8>>) -> ConflictResolution {
+        self(request, response)
+    }
+}
+
+/// A persistent queue for requests made while offline.
+///
+/// Given to a client builder's `offline_queue()`, it makes requests other than
+/// a `GET` or `HEAD` tolerate the API being unreachable. When the HTTP client
+/// fails to send one, after any retries, the request is written to the
+/// queue's file and the send fails with [`RestApiError::QueuedOffline`]. The
+/// next such request first replays the queue in order, and is itself queued
+/// behind anything that still can't be sent, so the server sees mutations in
+/// the order they were made. The client's `replay_offline_queue()` replays it
+/// on demand, such as when the device reports that it's back online.
+///
+/// A replayed request is removed from the queue once it gets a response.
+/// `5xx`, `408` and `429` responses, and the HTTP client failing again, stop
+/// the replay with the request still queued. Other `4xx` responses go to the
+/// [`on_conflict`](Self::on_conflict) hook, and are discarded without one.
+///
+/// Clones share the queue. The file is rewritten whenever the queue changes,
+/// and on Unix is only readable by its owner.
+///
+/// Sensitive headers, such as `Authorization`, `Cookie` and API keys, aren't
+/// written to the file unless [`persist_sensitive_headers`](Self::persist_sensitive_headers)
+/// is set. A request read back from the file after a restart is replayed with
+/// the sensitive headers of the request that triggers the replay, or the
+/// client's default headers for `replay_offline_queue()`.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use derive_rest_api::{ConflictResolution, OfflineQueue, QueuedRequest};
+/// use derive_rest_api::http::Response;
+///
+/// let queue = OfflineQueue::open(data_dir.join("outbox.json"))?
+///     .on_conflict(|request: &QueuedRequest, response: &Response<Vec<u8>>| {
+///         eprintln!("{} {} was rejected with {}", request.method, request.path, response.status());
+///         ConflictResolution::Discard
+///     });
+/// let client = JobsClient::builder().offline_queue(queue).build()?;
+/// ```
+#[derive(Clone)]
+pub struct OfflineQueue {
+    path: PathBuf,
+    state: Arc<Mutex<QueueState>>,
+    replaying: Arc<AtomicBool>,
+    conflicts: Option<Arc<dyn ConflictHook>>,
+    persist_sensitive: bool,
+}
+
+#[derive(Debug, Default)]
+struct QueueState {
+    next_id: u64,
+    requests: VecDeque<QueuedRequest>,
+}
+
+impl OfflineQueue {
+    /// Opens the queue stored at `path`, or an empty one if the file doesn't exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns `RestApiError::OfflineQueueError` if the file can't be read or
+    /// isn't a queue.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, RestApiError> {
+        let path = path.as_ref().to_path_buf();
+        let state = match std::fs::read(&path) {
+            Ok(contents) => parse(&contents).map_err(RestApiError::offline_queue_error)?,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => QueueState::default(),
+            Err(error) => return Err(RestApiError::offline_queue_error(error)),
+        };
+        Ok(Self {
+            path,
+            state: Arc::new(Mutex::new(state)),
+            replaying: Arc::default(),
+            conflicts: None,
+            persist_sensitive: false,
+        })
+    }
+
+    /// Sets the hook deciding what to do with replayed requests the server rejects.
+    pub fn on_conflict(mut self, hook: impl ConflictHook) -> Self {
+        self.conflicts = Some(Arc::new(hook));
+        self
+    }
+
+    /// Writes sensitive headers to the file too, so that requests are replayed
+    /// with their own credentials after a restart.
+    ///
+    /// Anyone who can read the file can then use those credentials.
+    pub fn persist_sensitive_headers(mut self) -> Self {
+        self.persist_sensitive = true;
+        self
+    }
+
+    /// Returns the file the queue is stored in.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the queued requests, oldest first.
+    pub fn requests(&self) -> Vec<QueuedRequest> {
+        self.lock().requests.iter().cloned().collect()
+    }
+
+    /// Returns how many requests are queued.
+    pub fn len(&self) -> usize {
+        self.lock().requests.len()
+    }
+
+    /// Returns `true` if no requests are queued.
+    pub fn is_empty(&self) -> bool {
+        self.lock().requests.is_empty()
+    }
+
+    /// Drops every queued request.
+    ///
+    /// # Errors
+    ///
+    /// Returns `RestApiError::OfflineQueueError` if the file can't be written.
+    pub fn clear(&self) -> Result<(), RestApiError> {
+        let mut state = self.lock();
+        state.requests.clear();
+        self.save(&state)
+    }
+
+    /// Queues a request, returning the error its send fails with
+    pub(crate) fn push(
+        &self,
+        method: &str,
+        base_url: &str,
+        path: &str,
+        headers: &HeaderMap,
+        body: &Option<Vec<u8>>,
+    ) -> RestApiError {
+        let mut state = self.lock();
+        state.next_id += 1;
+        let id = state.next_id;
+        state.requests.push_back(QueuedRequest {
+            id,
+            method: method.to_string(),
+            base_url: base_url.to_string(),
+            path: path.to_string(),
+            headers: headers.clone(),
+            body: body.clone(),
+            queued_at: SystemTime::now(),
+        });
+        match self.save(&state) {
+            Ok(()) => RestApiError::QueuedOffline { id },
+            Err(error) => {
+                state.requests.pop_back();
+                error
+            }
+        }
+    }
+
+    /// Marks the queue as being replayed, unless it already is
+    pub(crate) fn start_replay(&self) -> Option<Replaying<'_>> {
+        (!self.replaying.swap(true, Ordering::AcqRel)).then_some(Replaying(&self.replaying))
+    }
+
+    /// Returns the request to replay next
+    pub(crate) fn front(&self) -> Option<QueuedRequest> {
+        self.lock().requests.front().cloned()
+    }
+
+    /// Updates the queue with the outcome of replaying `queued`, returning
+    /// `true` if the replay should go on
+    pub(crate) fn settle(
+        &self,
+        queued: &QueuedRequest,
+        result: &Result<Response<Vec<u8>>, RestApiError>,
+    ) -> Result<bool, RestApiError> {
+        let Ok(response) = result else {
+            return Ok(false);
+        };
+        let status = response.status();
+        if status.is_server_error() || matches!(status, StatusCode::REQUEST_TIMEOUT | StatusCode::TOO_MANY_REQUESTS) {
+            return Ok(false);
+        }
+        let resolution = match &self.conflicts {
+            Some(hook) if status.is_client_error() => hook.on_conflict(queued, response),
+            _ => ConflictResolution::Discard,
+        };
+
+        let mut state = self.lock();
+        let Some(front) = state.requests.front_mut().filter(|front| front.id == queued.id) else {
+            // Cleared while it was being sent
+            return Ok(true);
+        };
+        match resolution {
+            ConflictResolution::Discard => {
+                state.requests.pop_front();
+            }
+            ConflictResolution::Resend(request) => *front = *request,
+            ConflictResolution::Keep => return Ok(false),
+        }
+        self.save(&state)?;
+        Ok(true)
+    }
+
+    /// Writes the queue to a temporary file, then moves it over the queue's file
+    fn save(&self, state: &QueueState) -> Result<(), RestApiError> {
+        let mut temporary = self.path.clone().into_os_string();
+        temporary.push(".tmp");
+        let contents = serde_json::to_vec_pretty(&serialize(state, self.persist_sensitive))
+            .map_err(RestApiError::offline_queue_error)?;
+        write_private(&temporary, &contents)
+            .and_then(|()| std::fs::rename(&temporary, &self.path))
+            .map_err(RestApiError::offline_queue_error)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, QueueState> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl std::fmt::Debug for OfflineQueue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OfflineQueue")
+            .field("path", &self.path)
+            .field("requests", &self.len())
+            .field("on_conflict", &self.conflicts.is_some())
+            .field("persist_sensitive_headers", &self.persist_sensitive)
+            .finish()
+    }
+}
+
+/// Held while a queue is replayed, so that only one replay runs at a time
+pub(crate) struct Replaying<'a>(&'a AtomicBool);
+
+impl Drop for Replaying<'_> {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::Release);
+    }
+}
+
+/// Returns `true` for the requests an offline queue holds on to
+pub(crate) fn is_queued(method: &str) -> bool {
+    !matches!(method, "GET" | "HEAD")
+}
+
+/// Returns the headers to replay `queued` with: its own, plus the sensitive
+/// headers of `current` it lost by being read back from the file
+pub(crate) fn replay_headers(queued: &QueuedRequest, current: &HeaderMap) -> HeaderMap {
+    let mut headers = queued.headers.clone();
+    for (name, value) in current {
+        if is_sensitive(name, value) && !queued.headers.contains_key(name) {
+            headers.append(name, value.clone());
+        }
+    }
+    headers
+}
+
+/// Writes `contents` to a file only its owner can read, on Unix
+fn write_private(path: &std::ffi::OsStr, contents: &[u8]) -> std::io::Result<()> {
+    use std::io::Write;
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path)?;
+    // The mode only applies to new files
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
+    }
+    file.write_all(contents)
+}
+
+fn serialize(state: &QueueState, persist_sensitive: bool) -> Value {
+    let requests: Vec<Value> = state
+        .requests
+        .iter()
+        .map(|request| {
+            let headers: Vec<Value> = request
+                .headers
+                .iter()
+                .filter(|(name, value)| persist_sensitive || !is_sensitive(name, value))
+                .map(|(name, value)| json!([name.as_str(), hex(value.as_bytes())]))
+                .collect();
+            let queued_at = request.queued_at.duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as u64;
+            json!({
+                "id": request.id,
+                "method": request.method,
+                "base_url": request.base_url,
+                "path": request.path,
+                "headers": headers,
+                "body": request.body.as_deref().map(hex),
+                "queued_at": queued_at,
+            })
+        })
+        .collect();
+    json!({ "next_id": state.next_id, "requests": requests })
+}
+
+fn parse(contents: &[u8]) -> Result<QueueState, std::io::Error> {
+    let invalid = |message: &str| std::io::Error::new(std::io::ErrorKind::InvalidData, message.to_string());
+    let document: Value = serde_json::from_slice(contents)?;
+    let mut state = QueueState {
+        next_id: document["next_id"].as_u64().ok_or_else(|| invalid("missing next_id"))?,
+        requests: VecDeque::new(),
+    };
+    for request in document["requests"].as_array().ok_or_else(|| invalid("missing requests"))? {
+        let text = |field: &str| {
+            request[field]
+                .as_str()
+                .map(str::to_string)
+                .ok_or_else(|| invalid(&format!("missing {}", field)))
+        };
+        let mut headers = HeaderMap::new();
+        for header in request["headers"].as_array().into_iter().flatten() {
+            let (Some(name), Some(value)) = (header[0].as_str(), header[1].as_str()) else {
+                return Err(invalid("invalid header"));
+            };
+            let name = HeaderName::from_bytes(name.as_bytes()).map_err(|_| invalid("invalid header name"))?;
+            let value = unhex(value)
+                .and_then(|value| HeaderValue::from_bytes(&value).ok())
+                .ok_or_else(|| invalid("invalid header value"))?;
+            headers.append(name, value);
+        }
+        let body = match request["body"].as_str() {
+            Some(body) => Some(unhex(body).ok_or_else(|| invalid("invalid body"))?),
+            None => None,
+        };
+        state.requests.push_back(QueuedRequest {
+            id: request["id"].as_u64().ok_or_else(|| invalid("missing id"))?,
+            method: text("method")?,
+            base_url: text("base_url")?,
+            path: text("path")?,
+            headers,
+            body,
+            queued_at: UNIX_EPOCH + Duration::from_millis(request["queued_at"].as_u64().unwrap_or_default()),
+        });
+    }
+    Ok(state)
+}
+
+fn unhex(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok())
+        .collect()
+}
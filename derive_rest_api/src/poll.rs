@@ -0,0 +1,190 @@
+//! Polling of long-running operations for `#[request_builder(poll(...))]`.
+
+use crate::clock::clock_or_system;
+use crate::credentials::is_sensitive;
+use crate::transport::Transport;
+use crate::{HttpClient, RequestOptions, RestApiError};
+use http::{HeaderMap, HeaderName, Response};
+use std::time::Duration;
+
+/// Extra check applied to every polled response, such as checksum verification.
+pub type ResponseCheck = fn(Response<Vec<u8>>) -> Result<Response<Vec<u8>>, RestApiError>;
+
+/// How generated `send_and_wait()` methods poll an operation.
+#[derive(Clone, Copy, Debug)]
+pub struct PollConfig {
+    /// Dotted path of the status field in the JSON response (e.g. `"status"` or `"operation.state"`)
+    pub status_field: &'static str,
+    /// Status values that end polling
+    pub done_values: &'static [&'static str],
+    /// Wait between polls, unless the server sends `Retry-After`
+    pub interval: Duration,
+    /// Give up with `RestApiError::PollTimeout` after this long
+    pub timeout: Option<Duration>,
+    /// Extra check applied to every polled response, after the size limit
+    pub check_response: ResponseCheck,
+}
+
+impl PollConfig {
+    /// Returns `true` if the response body reports a finished operation.
+    ///
+    /// A body that isn't JSON or lacks the status field counts as unfinished.
+    fn is_done(&self, body: &[u8]) -> bool {
+        let Ok(value) = serde_json::from_slice::<serde_json::Value>(body) else {
+            return false;
+        };
+        let status = self
+            .status_field
+            .split('.')
+            .try_fold(&value, |value, key| value.get(key));
+        match status {
+            Some(serde_json::Value::String(status)) => self.done_values.contains(&status.as_str()),
+            Some(serde_json::Value::Null) | None => false,
+            Some(status) => self.done_values.contains(&status.to_string().as_str()),
+        }
+    }
+
+    /// Returns how long to wait before the next poll
+    fn wait_after(&self, response: &Response<Vec<u8>>) -> Duration {
+        response
+            .headers()
+            .get(http::header::RETRY_AFTER)
+            .and_then(|value| value.to_str().ok()?.trim().parse::<u64>().ok())
+            .map(Duration::from_secs)
+            .unwrap_or(self.interval)
+    }
+
+    /// Returns the wait before the next poll, `elapsed` into polling, or
+    /// `PollTimeout` if it would pass the deadline
+    fn next_wait(&self, elapsed: Duration, response: &Response<Vec<u8>>) -> Result<Duration, RestApiError> {
+        let wait = self.wait_after(response);
+        match self.timeout {
+            Some(timeout) if elapsed + wait > timeout => Err(RestApiError::PollTimeout { timeout }),
+            _ => Ok(wait),
+        }
+    }
+}
+
+/// Returns the URL to poll: the `Location` header of the first response if
+/// present (resolved against `url`), otherwise `url` itself.
+fn poll_url(url: &str, response: &Response<Vec<u8>>) -> String {
+    let Some(location) = response
+        .headers()
+        .get(http::header::LOCATION)
+        .and_then(|value| value.to_str().ok())
+    else {
+        return url.to_string();
+    };
+
+    if location.contains("://") {
+        return location.to_string();
+    }
+    let (scheme, rest) = url.split_once("://").unwrap_or(("", url));
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or(rest);
+    if location.starts_with('/') {
+        format!("{}://{}{}", scheme, authority, location)
+    } else {
+        // Relative to the directory of the request path
+        let path = url.split(['?', '#']).next().unwrap_or(url);
+        match path.rfind('/') {
+            Some(index) if index > scheme.len() + 2 => format!("{}/{}", &path[..index], location),
+            _ => format!("{}://{}/{}", scheme, authority, location),
+        }
+    }
+}
+
+/// Splits a URL into its origin (`scheme://authority`) and the path and query after it
+fn split_origin(url: &str) -> (&str, &str) {
+    let authority = url.find("://").map_or(0, |index| index + 3);
+    let end = url[authority..].find(['/', '?', '#']).map_or(url.len(), |index| authority + index);
+    url.split_at(end)
+}
+
+/// Returns the transport and headers for the follow-up GETs to `url`.
+///
+/// Polls go straight to the server that started the operation, rather than
+/// through the failover, and without the headers describing the body. A poll
+/// URL on another origin gets no credentials, from a provider or the headers.
+fn poll_transport(transport: &Transport, request_url: &str, url: &str, mut headers: HeaderMap) -> (Transport, HeaderMap) {
+    headers.remove(http::header::CONTENT_TYPE);
+    let mut transport = Transport {
+        failover: None,
+        offline: None,
+        ..transport.clone()
+    };
+    if !split_origin(request_url).0.eq_ignore_ascii_case(split_origin(url).0) {
+        transport.credentials = None;
+        transport.async_credentials = None;
+        let sensitive: Vec<HeaderName> = headers
+            .iter()
+            .filter(|(name, value)| is_sensitive(name, value))
+            .map(|(name, _)| name.clone())
+            .collect();
+        for name in sensitive {
+            headers.remove(name);
+        }
+    }
+    (transport, headers)
+}
+
+/// Polls until the operation started by `response` completes, returning the final body.
+///
+/// Each poll is sent through `transport`, and waited for with its clock.
+#[allow(clippy::too_many_arguments)]
+pub fn poll<C: HttpClient>(
+    client: &C,
+    transport: &Transport,
+    url: &str,
+    headers: HeaderMap,
+    options: RequestOptions,
+    response: Response<Vec<u8>>,
+    config: &PollConfig,
+) -> Result<Vec<u8>, RestApiError> {
+    let clock = clock_or_system(&transport.clock);
+    let started = clock.now();
+    let poll_url = poll_url(url, &response);
+    let (transport, headers) = poll_transport(transport, url, &poll_url, headers);
+    let (origin, path) = split_origin(&poll_url);
+
+    let mut response = response;
+    while response.status().is_success() && !config.is_done(response.body()) {
+        clock.sleep(config.next_wait(clock.now() - started, &response)?);
+        let next = transport.send(client, "GET", origin, path, headers.clone(), None, options)?;
+        response = (config.check_response)(crate::__private::check_response_size(next, &options)?)?;
+    }
+    Ok(response.into_body())
+}
+
+/// Async version of [`poll`].
+#[cfg(not(target_arch = "wasm32"))]
+#[allow(clippy::too_many_arguments)]
+pub async fn poll_async<C: crate::AsyncHttpClient>(
+    client: &C,
+    transport: &Transport,
+    url: &str,
+    headers: HeaderMap,
+    options: RequestOptions,
+    response: Response<Vec<u8>>,
+    config: &PollConfig,
+) -> Result<Vec<u8>, RestApiError> {
+    let clock = clock_or_system(&transport.clock);
+    let started = clock.now();
+    let poll_url = poll_url(url, &response);
+    let (transport, headers) = poll_transport(transport, url, &poll_url, headers);
+    let (origin, path) = split_origin(&poll_url);
+
+    let mut response = response;
+    while response.status().is_success() && !config.is_done(response.body()) {
+        clock.sleep_async(config.next_wait(clock.now() - started, &response)?).await;
+        let next = transport.send_async(client, "GET", origin, path, headers.clone(), None, options).await?;
+        response = (config.check_response)(crate::__private::check_response_size(next, &options)?)?;
+    }
+    Ok(response.into_body())
+}
+
+/// A runtime-agnostic timer future.
+///
+/// Waits are run by one timer thread shared by the whole process (by
+/// `setTimeout` on `wasm32`), so they work under any executor without
+/// depending on a runtime's timers, and a dropped delay is simply forgotten.
+pub use futures_timer::Delay;
@@ -0,0 +1,79 @@
+//! Connection pool settings for HTTP client wrappers.
+
+use std::time::Duration;
+
+/// Connection reuse settings for HTTP clients that keep a connection pool.
+///
+/// Each setting is optional; `None` leaves the client's own default in place.
+/// Clients apply the settings they support and ignore the rest; see each
+/// client's `with_pool` constructor for how they are mapped.
+///
+/// # Example
+///
+/// ```
+/// use derive_rest_api::PoolOptions;
+/// use std::time::Duration;
+///
+/// let pool = PoolOptions::new()
+///     .with_max_idle_per_host(64)
+///     .with_idle_timeout(Duration::from_secs(30))
+///     .with_http2_keep_alive(Duration::from_secs(20), Duration::from_secs(5));
+///
+/// assert_eq!(pool.max_idle_per_host, Some(64));
+/// assert!(!pool.http2_keep_alive_while_idle);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PoolOptions {
+    /// Maximum number of idle connections kept open per host.
+    pub max_idle_per_host: Option<usize>,
+
+    /// How long an idle connection is kept in the pool before it is closed.
+    pub idle_timeout: Option<Duration>,
+
+    /// Interval between HTTP/2 PING frames sent to keep a connection alive.
+    pub http2_keep_alive_interval: Option<Duration>,
+
+    /// How long to wait for a PING acknowledgement before closing the connection.
+    pub http2_keep_alive_timeout: Option<Duration>,
+
+    /// Whether to send keep-alive PINGs on connections with no open streams.
+    pub http2_keep_alive_while_idle: bool,
+}
+
+impl PoolOptions {
+    /// Creates `PoolOptions` that leave every client default in place.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the maximum number of idle connections kept per host.
+    pub fn with_max_idle_per_host(mut self, max: usize) -> Self {
+        self.max_idle_per_host = Some(max);
+        self
+    }
+
+    /// Sets how long idle connections are kept in the pool.
+    pub fn with_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.idle_timeout = Some(timeout);
+        self
+    }
+
+    /// Sends HTTP/2 keep-alive PINGs every `interval`, closing the connection
+    /// if one isn't acknowledged within `timeout`.
+    pub fn with_http2_keep_alive(mut self, interval: Duration, timeout: Duration) -> Self {
+        self.http2_keep_alive_interval = Some(interval);
+        self.http2_keep_alive_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets whether keep-alive PINGs are also sent on idle connections.
+    pub fn with_http2_keep_alive_while_idle(mut self, enabled: bool) -> Self {
+        self.http2_keep_alive_while_idle = enabled;
+        self
+    }
+
+    /// Returns `true` if no setting differs from the client defaults.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
@@ -0,0 +1,423 @@
+//! Pluggable encoding of query parameters into the query string.
+
+use crate::RestApiError;
+use serde::ser::{self, Impossible, Serialize, Serializer};
+use serde_json::Value;
+
+/// Writes a request's query parameters into its query string, in place of the
+/// default `serde_qs` encoding.
+///
+/// The parameters are given in the order the query fields serialize them,
+/// with each value converted to JSON so that nested structures can be written
+/// in whatever syntax the API expects. Parameters that are `None` are left out.
+/// Choose an encoder for a struct with `#[request_builder(query_encoder = ...)]`,
+/// or for every request of a client with its builder's `query_encoder()`.
+///
+/// # Example
+///
+/// ```
+/// use derive_rest_api::{QueryEncoder, RestApiError};
+/// use serde_json::Value;
+///
+/// /// Joins arrays with commas, as in `ids=1,2,3`
+/// struct CommaSeparated;
+///
+/// impl QueryEncoder for CommaSeparated {
+///     fn encode(&self, params: &[(String, Value)]) -> Result<String, RestApiError> {
+///         let pairs = params.iter().map(|(name, value)| {
+///             let value = match value {
+///                 Value::Array(items) => items.iter().map(|item| item.to_string()).collect::<Vec<_>>().join(","),
+///                 value => value.to_string(),
+///             };
+///             format!("{}={}", name, value)
+///         });
+///         Ok(pairs.collect::<Vec<_>>().join("&"))
+///     }
+/// }
+/// ```
+pub trait QueryEncoder: Send + Sync + 'static {
+    /// Encodes `params` as a query string, without the leading `?`.
+    fn encode(&self, params: &[(String, Value)]) -> Result<String, RestApiError>;
+}
+
+/// Encodes query parameters with `serde_qs`, writing nested values with
+/// brackets: `filter[status]=open&ids[0]=1&ids[1]=2`.
+///
+/// This is how requests are encoded without an encoder, and lets a struct
+/// keep it when its client uses another encoder.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SerdeQsEncoder;
+
+impl QueryEncoder for SerdeQsEncoder {
+    fn encode(&self, params: &[(String, Value)]) -> Result<String, RestApiError> {
+        serde_qs::to_string(&OrderedParams(params))
+            .map_err(|source| RestApiError::QuerySerializationError { source })
+    }
+}
+
+/// How [`UrlEncodedEncoder`] writes spaces.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SpaceEncoding {
+    /// As `+`, as in HTML forms
+    #[default]
+    Plus,
+    /// As `%20`, as RFC 3986 and most request signing schemes require
+    Percent,
+}
+
+/// Encodes query parameters as flat `name=value` pairs with no brackets, for
+/// APIs that reject `serde_qs`'s nested syntax.
+///
+/// Arrays repeat their name for each element (`ids=1&ids=2`). Objects, and
+/// arrays holding arrays or objects, can't be written this way and fail with
+/// `RestApiError::QueryEncodingError`.
+///
+/// By default names and values are encoded like `serde_urlencoded` does, in
+/// the `application/x-www-form-urlencoded` style: spaces become `+`, and
+/// everything but ASCII letters, digits and `*-._` is percent-encoded. APIs
+/// that validate signatures over the query string are sensitive to every
+/// byte, so both can be changed:
+///
+/// ```
+/// use derive_rest_api::{SpaceEncoding, UrlEncodedEncoder};
+///
+/// // `from=12:00,13:00&q=a%20b`
+/// let encoder = UrlEncodedEncoder::new().spaces(SpaceEncoding::Percent).literal(":,");
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct UrlEncodedEncoder {
+    spaces: SpaceEncoding,
+    /// Characters other than letters and digits written as-is
+    unreserved: &'static str,
+    /// More characters written as-is, from `literal()`
+    literal: &'static str,
+}
+
+impl UrlEncodedEncoder {
+    /// Creates an encoder in the `application/x-www-form-urlencoded` style.
+    pub const fn new() -> Self {
+        Self {
+            spaces: SpaceEncoding::Plus,
+            unreserved: "*-._",
+            literal: "",
+        }
+    }
+
+    /// Creates an encoder following RFC 3986, as AWS Signature Version 4 and
+    /// OAuth 1.0 signatures expect: spaces become `%20`, and everything but
+    /// ASCII letters, digits and `-._~` is percent-encoded.
+    pub const fn rfc3986() -> Self {
+        Self {
+            spaces: SpaceEncoding::Percent,
+            unreserved: "-._~",
+            literal: "",
+        }
+    }
+
+    /// Sets how spaces are written.
+    pub const fn spaces(mut self, spaces: SpaceEncoding) -> Self {
+        self.spaces = spaces;
+        self
+    }
+
+    /// Writes the ASCII characters in `chars`, such as `:` and `,`, as-is
+    /// instead of percent-encoding them.
+    ///
+    /// `&`, `=`, `+`, `#` and `%` are always percent-encoded, as leaving them
+    /// would change the meaning of the query string.
+    pub const fn literal(mut self, chars: &'static str) -> Self {
+        self.literal = chars;
+        self
+    }
+
+    /// Percent-encodes a name or value
+    fn encode_component(&self, component: &str, encoded: &mut String) {
+        for byte in component.bytes() {
+            match byte {
+                b' ' if self.spaces == SpaceEncoding::Plus => encoded.push('+'),
+                b'&' | b'=' | b'+' | b'#' | b'%' => encoded.push_str(&format!("%{:02X}", byte)),
+                _ if byte.is_ascii_alphanumeric()
+                    || self.unreserved.as_bytes().contains(&byte)
+                    || self.literal.as_bytes().contains(&byte) =>
+                {
+                    encoded.push(byte as char)
+                }
+                _ => encoded.push_str(&format!("%{:02X}", byte)),
+            }
+        }
+    }
+}
+
+impl Default for UrlEncodedEncoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl QueryEncoder for UrlEncodedEncoder {
+    fn encode(&self, params: &[(String, Value)]) -> Result<String, RestApiError> {
+        let mut pairs = Vec::with_capacity(params.len());
+        for (name, value) in params {
+            match value {
+                Value::Array(items) => {
+                    for item in items {
+                        pairs.push((name.as_str(), flat_value(name, item)?));
+                    }
+                }
+                value => pairs.push((name.as_str(), flat_value(name, value)?)),
+            }
+        }
+
+        let mut encoded = String::new();
+        for (name, value) in pairs {
+            if !encoded.is_empty() {
+                encoded.push('&');
+            }
+            self.encode_component(name, &mut encoded);
+            encoded.push('=');
+            self.encode_component(&value, &mut encoded);
+        }
+        Ok(encoded)
+    }
+}
+
+/// Returns a scalar parameter value as text
+fn flat_value(name: &str, value: &Value) -> Result<String, RestApiError> {
+    match value {
+        Value::String(value) => Ok(value.clone()),
+        Value::Number(value) => Ok(value.to_string()),
+        Value::Bool(value) => Ok(value.to_string()),
+        Value::Null => Ok(String::new()),
+        Value::Array(_) | Value::Object(_) => Err(RestApiError::query_encoding_error(format!(
+            "query parameter '{}' is nested, which can't be encoded without brackets",
+            name
+        ))),
+    }
+}
+
+/// The order of the parameters in a query string.
+///
+/// Set it for a struct with `#[request_builder(query_order = "sorted")]`, or
+/// for every request of a client with its builder's `query_order()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum QueryOrder {
+    /// Parameters from `static_query(...)` first, then the query fields in
+    /// the order they're declared
+    #[default]
+    Declared,
+    /// Sorted by name, so that request signatures, cache keys and golden tests
+    /// don't change when fields are reordered.
+    ///
+    /// Names are compared as encoded, byte by byte. Parameters sharing a name,
+    /// like the elements of an array (`ids=1&ids=2` or `ids[0]=1&ids[1]=2`) or
+    /// the keys of a nested value, keep their order.
+    Sorted,
+}
+
+/// Puts the parameters of an encoded query string in `order`.
+pub fn order(query: String, order: QueryOrder) -> String {
+    match order {
+        QueryOrder::Declared => query,
+        QueryOrder::Sorted => {
+            let mut params: Vec<&str> = query.split('&').collect();
+            params.sort_by_key(|param| top_level_name(param));
+            params.join("&")
+        }
+    }
+}
+
+/// Returns the name of a parameter, without any bracketed suffix
+fn top_level_name(param: &str) -> &str {
+    let name = param.split('=').next().unwrap_or_default();
+    let bracket = [name.find('['), name.find("%5B"), name.find("%5b")].into_iter().flatten().min();
+    &name[..bracket.unwrap_or(name.len())]
+}
+
+/// Serializes parameters as a map, keeping their order
+struct OrderedParams<'a>(&'a [(String, Value)]);
+
+impl Serialize for OrderedParams<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.0.iter().map(|(name, value)| (name, value)))
+    }
+}
+
+/// Encodes the query parameters of a request with `encoder`.
+pub fn encode<T: Serialize + ?Sized>(params: &T, encoder: &dyn QueryEncoder) -> Result<String, RestApiError> {
+    let params = params.serialize(ParamsCollector).map_err(RestApiError::query_encoding_error)?;
+    let params: Vec<_> = params.into_iter().filter(|(_, value)| !value.is_null()).collect();
+    encoder.encode(&params)
+}
+
+/// Serializer collecting the top-level fields of a struct or map in order
+struct ParamsCollector;
+
+type Params = Vec<(String, Value)>;
+
+fn not_a_struct() -> serde_json::Error {
+    ser::Error::custom("query parameters must serialize as a struct or map")
+}
+
+macro_rules! reject {
+    ($($method:ident($($ty:ty),*)),* $(,)?) => {
+        $(
+            fn $method(self, $(_: $ty),*) -> Result<Params, serde_json::Error> {
+                Err(not_a_struct())
+            }
+        )*
+    };
+}
+
+impl Serializer for ParamsCollector {
+    type Ok = Params;
+    type Error = serde_json::Error;
+    type SerializeSeq = Impossible<Params, serde_json::Error>;
+    type SerializeTuple = Impossible<Params, serde_json::Error>;
+    type SerializeTupleStruct = Impossible<Params, serde_json::Error>;
+    type SerializeTupleVariant = Impossible<Params, serde_json::Error>;
+    type SerializeMap = CollectedParams;
+    type SerializeStruct = CollectedParams;
+    type SerializeStructVariant = Impossible<Params, serde_json::Error>;
+
+    reject! {
+        serialize_bool(bool),
+        serialize_i8(i8),
+        serialize_i16(i16),
+        serialize_i32(i32),
+        serialize_i64(i64),
+        serialize_u8(u8),
+        serialize_u16(u16),
+        serialize_u32(u32),
+        serialize_u64(u64),
+        serialize_f32(f32),
+        serialize_f64(f64),
+        serialize_char(char),
+        serialize_str(&str),
+        serialize_bytes(&[u8]),
+        serialize_unit_variant(&'static str, u32, &'static str),
+    }
+
+    fn serialize_none(self) -> Result<Params, serde_json::Error> {
+        Ok(Vec::new())
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Params, serde_json::Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Params, serde_json::Error> {
+        Ok(Vec::new())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Params, serde_json::Error> {
+        Ok(Vec::new())
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Params, serde_json::Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Params, serde_json::Error> {
+        Err(not_a_struct())
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, serde_json::Error> {
+        Err(not_a_struct())
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, serde_json::Error> {
+        Err(not_a_struct())
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, serde_json::Error> {
+        Err(not_a_struct())
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, serde_json::Error> {
+        Err(not_a_struct())
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, serde_json::Error> {
+        Ok(CollectedParams::default())
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct, serde_json::Error> {
+        Ok(CollectedParams::default())
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, serde_json::Error> {
+        Err(not_a_struct())
+    }
+}
+
+/// The fields collected by [`ParamsCollector`] so far
+#[derive(Default)]
+struct CollectedParams {
+    params: Params,
+    key: Option<String>,
+}
+
+impl ser::SerializeMap for CollectedParams {
+    type Ok = Params;
+    type Error = serde_json::Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), serde_json::Error> {
+        match serde_json::to_value(key)? {
+            Value::String(key) => {
+                self.key = Some(key);
+                Ok(())
+            }
+            _ => Err(ser::Error::custom("query parameter names must be strings")),
+        }
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), serde_json::Error> {
+        let key = self.key.take().ok_or_else(|| ser::Error::custom("query parameter value without a name"))?;
+        self.params.push((key, serde_json::to_value(value)?));
+        Ok(())
+    }
+
+    fn end(self) -> Result<Params, serde_json::Error> {
+        Ok(self.params)
+    }
+}
+
+impl ser::SerializeStruct for CollectedParams {
+    type Ok = Params;
+    type Error = serde_json::Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<(), serde_json::Error> {
+        self.params.push((key.to_string(), serde_json::to_value(value)?));
+        Ok(())
+    }
+
+    fn end(self) -> Result<Params, serde_json::Error> {
+        Ok(self.params)
+    }
+}
@@ -0,0 +1,180 @@
+//! Typed rate-limit response headers.
+
+use crate::Clock;
+use http::{HeaderMap, Response};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Reset values at least this large are Unix timestamps rather than delays
+const TIMESTAMP_THRESHOLD: u64 = 1_000_000_000;
+
+/// A server's rate-limit budget, as reported by a response.
+///
+/// Parsed from the `X-RateLimit-Limit`, `X-RateLimit-Remaining` and
+/// `X-RateLimit-Reset` headers, or from the IETF draft `RateLimit-Limit`,
+/// `RateLimit-Remaining` and `RateLimit-Reset` headers, which take precedence.
+/// Headers that are missing or malformed are left as `None`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RateLimitInfo {
+    /// How many requests the current window allows
+    pub limit: Option<u64>,
+    /// How many requests are left in the current window
+    pub remaining: Option<u64>,
+    /// How long after the response the window resets
+    ///
+    /// Resets sent as Unix timestamps, as GitHub does, are converted to a
+    /// delay. A timestamp that has already passed gives `Duration::ZERO`.
+    pub reset: Option<Duration>,
+}
+
+impl RateLimitInfo {
+    /// Reads the rate-limit headers, returning `None` if there are none.
+    pub fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let number = |name: &str| {
+            let draft = header_number(headers, name);
+            draft.or_else(|| header_number(headers, &format!("x-{}", name)))
+        };
+        let info = Self {
+            limit: number("ratelimit-limit"),
+            remaining: number("ratelimit-remaining"),
+            reset: number("ratelimit-reset").and_then(reset_delay),
+        };
+        (info != Self::default()).then_some(info)
+    }
+
+    /// Reads the rate-limit headers of a response, such as one from `send_raw()`.
+    pub fn from_response<T>(response: &Response<T>) -> Option<Self> {
+        Self::from_headers(response.headers())
+    }
+
+    /// Returns `true` if the server reported no requests left in the current window.
+    pub fn is_exhausted(&self) -> bool {
+        self.remaining == Some(0)
+    }
+}
+
+/// Parses the leading number of a header, ignoring draft parameters such as
+/// the `;w=60` in `RateLimit-Limit: 100, 100;w=60`.
+fn header_number(headers: &HeaderMap, name: &str) -> Option<u64> {
+    let value = headers.get(name)?.to_str().ok()?;
+    value.split([',', ';']).next()?.trim().parse().ok()
+}
+
+/// Converts a reset header, either delta seconds or a Unix timestamp, into a delay.
+fn reset_delay(seconds: u64) -> Option<Duration> {
+    if seconds < TIMESTAMP_THRESHOLD {
+        return Some(Duration::from_secs(seconds));
+    }
+    unix_now().map(|now| Duration::from_secs(seconds.saturating_sub(now)))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn unix_now() -> Option<u64> {
+    let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).ok()?;
+    Some(now.as_secs())
+}
+
+// The system clock isn't available on wasm32-unknown-unknown
+#[cfg(target_arch = "wasm32")]
+fn unix_now() -> Option<u64> {
+    None
+}
+
+/// The latest [`RateLimitInfo`] seen by a generated API client.
+///
+/// Clones share the same state, so every request builder created by a client
+/// (and every clone of the client) updates it. Responses without rate-limit
+/// headers leave the previous value in place.
+///
+/// A state can also throttle requests preemptively: with
+/// [`throttle_below`](Self::throttle_below), once a response reports that few
+/// enough requests remain, the next requests wait for the window to reset
+/// instead of running into a `429 Too Many Requests`.
+#[derive(Debug, Clone, Default)]
+pub struct RateLimitState {
+    latest: Arc<Mutex<Option<Observed>>>,
+    min_remaining: Option<u64>,
+    clock: Option<Arc<dyn Clock>>,
+}
+
+/// A rate limit and when it was received
+#[derive(Debug, Clone, Copy)]
+struct Observed {
+    info: RateLimitInfo,
+    #[cfg(not(target_arch = "wasm32"))]
+    received_at: std::time::Instant,
+}
+
+impl RateLimitState {
+    /// Creates an empty state.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Delays requests while the server reports `min_remaining` or fewer
+    /// requests left, until the window resets.
+    ///
+    /// The setting applies to this handle and clones made from it, while the
+    /// observed rate limit stays shared. Nothing waits if the server doesn't
+    /// report when the window resets. Waiting isn't available on `wasm32`.
+    pub fn throttle_below(mut self, min_remaining: u64) -> Self {
+        self.min_remaining = Some(min_remaining);
+        self
+    }
+
+    /// Times the window and waits for it to reset with `clock` instead of the
+    /// system clock.
+    ///
+    /// Like [`throttle_below`](Self::throttle_below), this applies to this
+    /// handle and clones made from it.
+    pub fn clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Some(Arc::new(clock));
+        self
+    }
+
+    /// Returns the most recently reported rate limit, if any.
+    pub fn get(&self) -> Option<RateLimitInfo> {
+        self.snapshot().map(|observed| observed.info)
+    }
+
+    /// Records the rate-limit headers of a response, if it has any.
+    pub fn update(&self, headers: &HeaderMap) {
+        if let Some(info) = RateLimitInfo::from_headers(headers) {
+            *self.latest.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(Observed {
+                info,
+                #[cfg(not(target_arch = "wasm32"))]
+                received_at: crate::clock::clock_or_system(&self.clock).now(),
+            });
+        }
+    }
+
+    /// Returns how long the next request should wait for the window to
+    /// reset, which is zero unless the state throttles and the quota is low.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn delay(&self) -> Duration {
+        let (Some(min_remaining), Some(observed)) = (self.min_remaining, self.snapshot()) else {
+            return Duration::ZERO;
+        };
+        match observed.info {
+            RateLimitInfo {
+                remaining: Some(remaining),
+                reset: Some(reset),
+                ..
+            } if remaining <= min_remaining => {
+                let elapsed = crate::clock::clock_or_system(&self.clock).now().saturating_duration_since(observed.received_at);
+                reset.saturating_sub(elapsed)
+            }
+            _ => Duration::ZERO,
+        }
+    }
+
+    /// Returns the clock to wait for the window with
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn wait_clock(&self) -> &dyn Clock {
+        crate::clock::clock_or_system(&self.clock)
+    }
+
+    fn snapshot(&self) -> Option<Observed> {
+        *self.latest.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
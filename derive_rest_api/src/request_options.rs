@@ -0,0 +1,106 @@
+//! Per-request options passed to HTTP clients.
+
+use crate::{RestApiError, Timeouts, TlsVersion};
+
+/// Options for a single request, passed to the HTTP client alongside the request itself.
+///
+/// # Example
+///
+/// ```
+/// use derive_rest_api::{RequestOptions, Timeouts};
+/// use std::time::Duration;
+///
+/// let options = RequestOptions::new()
+///     .with_timeouts(Timeouts::from(Duration::from_secs(30)))
+///     .with_max_response_bytes(1024 * 1024);
+///
+/// assert_eq!(options.read_limit(), Some(1024 * 1024 + 1));
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RequestOptions {
+    /// Connect, read and total timeouts.
+    pub timeouts: Timeouts,
+
+    /// Largest response body, in bytes, the caller accepts.
+    ///
+    /// Clients should stop reading after [`read_limit`](Self::read_limit) bytes
+    /// rather than buffering the whole body; the generated code reports a body
+    /// longer than this limit as `RestApiError::ResponseTooLarge`.
+    pub max_response_bytes: Option<u64>,
+
+    /// Whether the request may only be sent over HTTPS.
+    ///
+    /// The generated code checks the final URL with
+    /// [`check_url`](Self::check_url) before handing the request to the client.
+    pub require_https: bool,
+
+    /// Oldest TLS version the caller accepts for this request.
+    ///
+    /// Implies [`require_https`](Self::require_https). Clients that can choose
+    /// the TLS version per request should refuse to negotiate anything older;
+    /// the bundled clients set TLS versions client-wide instead, e.g. with
+    /// `ReqwestAsyncClient::builder().min_tls_version()`.
+    pub min_tls_version: Option<TlsVersion>,
+}
+
+impl RequestOptions {
+    /// Creates `RequestOptions` with no timeouts and no response size limit.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the timeouts.
+    pub fn with_timeouts(mut self, timeouts: Timeouts) -> Self {
+        self.timeouts = timeouts;
+        self
+    }
+
+    /// Sets the maximum response size.
+    pub fn with_max_response_bytes(mut self, limit: u64) -> Self {
+        self.max_response_bytes = Some(limit);
+        self
+    }
+
+    /// Requires the request to be sent over HTTPS.
+    pub fn with_require_https(mut self) -> Self {
+        self.require_https = true;
+        self
+    }
+
+    /// Sets the oldest acceptable TLS version, which also requires HTTPS.
+    pub fn with_min_tls_version(mut self, version: TlsVersion) -> Self {
+        self.min_tls_version = Some(version);
+        self
+    }
+
+    /// Returns `true` if the request may only be sent over HTTPS.
+    pub fn requires_https(&self) -> bool {
+        self.require_https || self.min_tls_version.is_some()
+    }
+
+    /// Checks that `url` satisfies the HTTPS requirement, if there is one.
+    ///
+    /// # Errors
+    ///
+    /// Returns `RestApiError::InsecureUrl` if HTTPS is required and `url`
+    /// doesn't use the `https` scheme.
+    pub fn check_url(&self, url: &str) -> Result<(), RestApiError> {
+        let is_https = url.get(..8).is_some_and(|scheme| scheme.eq_ignore_ascii_case("https://"));
+        if self.requires_https() && !is_https {
+            return Err(RestApiError::InsecureUrl { url: url.to_string() });
+        }
+        Ok(())
+    }
+
+    /// Returns how many body bytes a client should read at most: one more than
+    /// the limit, so that an oversized body can still be told apart.
+    pub fn read_limit(&self) -> Option<u64> {
+        self.max_response_bytes.map(|limit| limit.saturating_add(1))
+    }
+}
+
+impl From<Timeouts> for RequestOptions {
+    fn from(timeouts: Timeouts) -> Self {
+        Self::new().with_timeouts(timeouts)
+    }
+}
@@ -0,0 +1,196 @@
+//! Retrying failed requests.
+
+use crate::Clock;
+use crate::clock::clock_or_system;
+use http::{Response, StatusCode};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// When and how often a request is retried.
+///
+/// A request is retried when the HTTP client fails transiently (see
+/// [`RestApiError::is_transient`](crate::RestApiError::is_transient)), for
+/// example on a timeout or a refused connection, or when the response status is
+/// one of the retryable statuses, which default to `429`, `502`, `503` and
+/// `504`. Waits between attempts start at
+/// the initial backoff and double up to the maximum, unless the response sends
+/// `Retry-After`, which is followed up to the maximum. Once the retries run
+/// out, the last result is returned.
+///
+/// Only idempotent methods (`GET`, `HEAD`, `OPTIONS`, `PUT` and `DELETE`) are
+/// retried unless [`retry_non_idempotent`](Self::retry_non_idempotent) is set.
+/// Retries aren't available on `wasm32`.
+///
+/// # Example
+///
+/// ```
+/// use derive_rest_api::RetryPolicy;
+/// use std::time::Duration;
+///
+/// let policy = RetryPolicy::new(3).backoff(Duration::from_millis(200), Duration::from_secs(5));
+/// assert_eq!(policy.max_retries(), 3);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    max_retries: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    statuses: Vec<StatusCode>,
+    non_idempotent: bool,
+}
+
+impl RetryPolicy {
+    /// Creates a policy making up to `max_retries` retries after the first attempt.
+    pub fn new(max_retries: u32) -> Self {
+        Self {
+            max_retries,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(10),
+            statuses: crate::error::TRANSIENT_STATUSES.to_vec(),
+            non_idempotent: false,
+        }
+    }
+
+    /// Sets the first wait between attempts and the longest any wait may be.
+    pub fn backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max.max(initial);
+        self
+    }
+
+    /// Replaces the response statuses that are retried.
+    pub fn statuses(mut self, statuses: impl IntoIterator<Item = StatusCode>) -> Self {
+        self.statuses = statuses.into_iter().collect();
+        self
+    }
+
+    /// Retries requests with non-idempotent methods, such as `POST`, too.
+    pub fn retry_non_idempotent(mut self) -> Self {
+        self.non_idempotent = true;
+        self
+    }
+
+    /// Returns how many retries are made after the first attempt.
+    pub fn max_retries(&self) -> u32 {
+        self.max_retries
+    }
+
+    /// Returns `true` if requests with `method` may be retried.
+    pub fn allows(&self, method: &str) -> bool {
+        self.non_idempotent || matches!(method, "GET" | "HEAD" | "OPTIONS" | "PUT" | "DELETE")
+    }
+
+    /// Returns how long to wait before retry number `retry` (starting at 0),
+    /// or `None` if `result` shouldn't be retried.
+    pub fn delay<E>(&self, retry: u32, result: &Result<Response<Vec<u8>>, E>) -> Option<Duration> {
+        if retry >= self.max_retries {
+            return None;
+        }
+        let retry_after = match result {
+            Ok(response) if self.statuses.contains(&response.status()) => retry_after(response),
+            Ok(_) => return None,
+            Err(_) => None,
+        };
+        let backoff = self
+            .initial_backoff
+            .saturating_mul(2u32.saturating_pow(retry))
+            .min(self.max_backoff);
+        Some(retry_after.map_or(backoff, |wait| wait.min(self.max_backoff)))
+    }
+}
+
+/// A cap on the retries made across every request of a client.
+///
+/// When an upstream fails across many endpoints, each request's
+/// [`RetryPolicy`] retries on its own and the combined retry volume multiplies
+/// the load on the failing service. A budget allows at most `max_retries`
+/// retries in any `window`, shared by every request that carries it: clones
+/// share the same budget, so setting it on a client covers all the requests
+/// it creates. Once the budget is spent, failures are returned without
+/// retrying until earlier retries fall out of the window. First attempts are
+/// never limited.
+///
+/// The window follows the system clock unless the budget is given another
+/// [`Clock`] with [`clock`](Self::clock).
+///
+/// # Example
+///
+/// ```
+/// use derive_rest_api::RetryBudget;
+/// use std::time::Duration;
+///
+/// let budget = RetryBudget::new(2, Duration::from_secs(10));
+/// assert!(budget.try_acquire());
+/// assert!(budget.try_acquire());
+/// assert!(!budget.try_acquire());
+/// assert_eq!(budget.remaining(), 0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct RetryBudget {
+    max_retries: u32,
+    window: Duration,
+    /// When each retry in the current window was made, oldest first
+    retries: Arc<Mutex<VecDeque<Instant>>>,
+    clock: Option<Arc<dyn Clock>>,
+}
+
+impl RetryBudget {
+    /// Creates a budget allowing `max_retries` retries in any `window`.
+    pub fn new(max_retries: u32, window: Duration) -> Self {
+        Self {
+            max_retries,
+            window,
+            retries: Arc::default(),
+            clock: None,
+        }
+    }
+
+    /// Measures the window with `clock` instead of the system clock.
+    pub fn clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Some(Arc::new(clock));
+        self
+    }
+
+    /// Returns how many retries are allowed in a window.
+    pub fn max_retries(&self) -> u32 {
+        self.max_retries
+    }
+
+    /// Returns the length of the window.
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+
+    /// Returns how many retries the budget allows right now.
+    pub fn remaining(&self) -> u32 {
+        let mut retries = self.retries.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        self.expire(&mut retries, clock_or_system(&self.clock).now());
+        self.max_retries.saturating_sub(retries.len() as u32)
+    }
+
+    /// Takes a retry from the budget, returning `false` if it's spent.
+    pub fn try_acquire(&self) -> bool {
+        let mut retries = self.retries.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let now = clock_or_system(&self.clock).now();
+        self.expire(&mut retries, now);
+        if retries.len() as u64 >= u64::from(self.max_retries) {
+            return false;
+        }
+        retries.push_back(now);
+        true
+    }
+
+    /// Forgets the retries that have fallen out of the window.
+    fn expire(&self, retries: &mut VecDeque<Instant>, now: Instant) {
+        while retries.front().is_some_and(|&retry| now.duration_since(retry) >= self.window) {
+            retries.pop_front();
+        }
+    }
+}
+
+/// Reads a `Retry-After` header given in seconds.
+fn retry_after(response: &Response<Vec<u8>>) -> Option<Duration> {
+    let value = response.headers().get(http::header::RETRY_AFTER)?.to_str().ok()?;
+    value.trim().parse().ok().map(Duration::from_secs)
+}
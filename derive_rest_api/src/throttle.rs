@@ -0,0 +1,233 @@
+//! Per-host token-bucket throttling shared between clients.
+
+use crate::clock::clock_or_system;
+use crate::{AsyncHttpClient, Clock, HttpClient, RequestOptions};
+use http::{HeaderMap, Response};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// A request rate budget, applied per host through a [`ThrottleRegistry`].
+///
+/// Each host gets a token bucket that refills at `requests` per `period` and
+/// holds up to `burst` tokens. Every request takes one token, waiting for it
+/// when the bucket is empty, so all clients using the same registry share one
+/// budget for a host.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use derive_rest_api::ThrottlePolicy;
+///
+/// // At most 10 requests per second to each host, across every client
+/// let client = GitHubClient::new().with_throttle(ThrottlePolicy::per_second(10));
+/// let search = SearchClient::new().with_throttle(ThrottlePolicy::per_second(10));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ThrottlePolicy {
+    requests: u32,
+    period: Duration,
+    burst: u32,
+    registry: ThrottleRegistry,
+    clock: Option<Arc<dyn Clock>>,
+}
+
+impl ThrottlePolicy {
+    /// Allows `requests` per `period` to each host, in bursts of up to `requests`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `requests` or `period` is zero.
+    pub fn new(requests: u32, period: Duration) -> Self {
+        assert!(requests > 0 && !period.is_zero(), "a throttle needs a positive rate");
+        Self {
+            requests,
+            period,
+            burst: requests,
+            registry: ThrottleRegistry::global(),
+            clock: None,
+        }
+    }
+
+    /// Allows `requests` per second to each host.
+    pub fn per_second(requests: u32) -> Self {
+        Self::new(requests, Duration::from_secs(1))
+    }
+
+    /// Allows `requests` per minute to each host.
+    pub fn per_minute(requests: u32) -> Self {
+        Self::new(requests, Duration::from_secs(60))
+    }
+
+    /// Sets how many requests can be sent at once after an idle period (at least 1).
+    pub fn burst(mut self, burst: u32) -> Self {
+        self.burst = burst.max(1);
+        self
+    }
+
+    /// Uses the budgets of `registry` instead of the process-wide one.
+    pub fn registry(mut self, registry: ThrottleRegistry) -> Self {
+        self.registry = registry;
+        self
+    }
+
+    /// Refills buckets and waits for tokens with `clock` instead of the system clock.
+    ///
+    /// Policies sharing a registry should share a clock too.
+    pub fn clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Some(Arc::new(clock));
+        self
+    }
+
+    fn clock_or_system(&self) -> &dyn Clock {
+        clock_or_system(&self.clock)
+    }
+
+    /// Takes a token for the host of `url`, returning how long to wait before sending
+    fn reserve(&self, url: &str) -> Duration {
+        self.registry.reserve(host_key(url), self)
+    }
+}
+
+/// The token buckets of each host, shared by every policy using the registry.
+///
+/// Policies use [`ThrottleRegistry::global`] unless given another registry.
+/// A host's bucket is created by the first request to it, with that request's
+/// policy; later policies for the same host share that bucket's rate.
+#[derive(Debug, Clone, Default)]
+pub struct ThrottleRegistry {
+    buckets: Arc<Mutex<HashMap<String, TokenBucket>>>,
+}
+
+impl ThrottleRegistry {
+    /// Creates an empty registry, independent of every other.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the process-wide registry.
+    pub fn global() -> Self {
+        static GLOBAL: OnceLock<ThrottleRegistry> = OnceLock::new();
+        GLOBAL.get_or_init(ThrottleRegistry::new).clone()
+    }
+
+    fn reserve(&self, host: String, policy: &ThrottlePolicy) -> Duration {
+        let now = policy.clock_or_system().now();
+        let mut buckets = self.buckets.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        buckets
+            .entry(host)
+            .or_insert_with(|| TokenBucket::new(policy, now))
+            .reserve(now)
+    }
+}
+
+/// A token bucket whose tokens can be reserved ahead, going negative
+#[derive(Debug)]
+struct TokenBucket {
+    /// Tokens added per second
+    rate: f64,
+    capacity: f64,
+    tokens: f64,
+    updated: Instant,
+}
+
+impl TokenBucket {
+    fn new(policy: &ThrottlePolicy, now: Instant) -> Self {
+        let capacity = f64::from(policy.burst);
+        Self {
+            rate: f64::from(policy.requests) / policy.period.as_secs_f64(),
+            capacity,
+            tokens: capacity,
+            updated: now,
+        }
+    }
+
+    /// Takes a token, returning how long until it is actually available
+    fn reserve(&mut self, now: Instant) -> Duration {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.capacity);
+        self.updated = now;
+        self.tokens -= 1.0;
+        if self.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.tokens / self.rate)
+        }
+    }
+}
+
+/// Returns the lowercase `host[:port]` of a URL
+fn host_key(url: &str) -> String {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or(rest);
+    let host = authority.rsplit_once('@').map_or(authority, |(_, host)| host);
+    host.to_ascii_lowercase()
+}
+
+/// HTTP client wrapper that waits for a [`ThrottlePolicy`]'s budget before each request.
+///
+/// Generated clients wrap their HTTP client in this with `with_throttle()`. A
+/// default `ThrottledClient` has no policy and sends requests straight away.
+#[derive(Debug, Clone, Default)]
+pub struct ThrottledClient<C> {
+    inner: C,
+    policy: Option<ThrottlePolicy>,
+}
+
+impl<C> ThrottledClient<C> {
+    /// Wraps `inner`, throttling its requests with `policy`.
+    pub fn new(inner: C, policy: ThrottlePolicy) -> Self {
+        Self {
+            inner,
+            policy: Some(policy),
+        }
+    }
+
+    /// Returns the wrapped client.
+    pub fn inner(&self) -> &C {
+        &self.inner
+    }
+
+    /// Returns how long to wait before sending a request to `url`, and the clock to wait with
+    fn wait_for(&self, url: &str) -> Option<(Duration, &dyn Clock)> {
+        let policy = self.policy.as_ref()?;
+        let wait = policy.reserve(url);
+        (!wait.is_zero()).then(|| (wait, policy.clock_or_system()))
+    }
+}
+
+impl<C: HttpClient> HttpClient for ThrottledClient<C> {
+    type Error = C::Error;
+
+    fn send(
+        &self,
+        method: &str,
+        url: &str,
+        headers: HeaderMap,
+        body: Option<Vec<u8>>,
+        options: RequestOptions,
+    ) -> Result<Response<Vec<u8>>, Self::Error> {
+        if let Some((wait, clock)) = self.wait_for(url) {
+            clock.sleep(wait);
+        }
+        self.inner.send(method, url, headers, body, options)
+    }
+}
+
+impl<C: AsyncHttpClient + Sync> AsyncHttpClient for ThrottledClient<C> {
+    type Error = C::Error;
+
+    async fn send_async(
+        &self,
+        method: &str,
+        url: &str,
+        headers: HeaderMap,
+        body: Option<Vec<u8>>,
+        options: RequestOptions,
+    ) -> Result<Response<Vec<u8>>, Self::Error> {
+        if let Some((wait, clock)) = self.wait_for(url) {
+            clock.sleep_async(wait).await;
+        }
+        self.inner.send_async(method, url, headers, body, options).await
+    }
+}
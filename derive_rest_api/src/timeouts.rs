@@ -0,0 +1,71 @@
+//! Request timeouts passed to HTTP clients.
+
+use std::time::Duration;
+
+/// Timeouts for a single request.
+///
+/// Each timeout is optional; `None` leaves the client's own setting in place.
+/// Clients apply the timeouts they support and ignore the rest; see each
+/// client's documentation for how they are mapped.
+///
+/// # Example
+///
+/// ```
+/// use derive_rest_api::Timeouts;
+/// use std::time::Duration;
+///
+/// let timeouts = Timeouts::new()
+///     .with_connect(Duration::from_secs(2))
+///     .with_total(Duration::from_secs(30));
+///
+/// assert_eq!(timeouts.connect, Some(Duration::from_secs(2)));
+/// assert_eq!(timeouts.read, None);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Timeouts {
+    /// Maximum time to establish the connection (including TLS).
+    pub connect: Option<Duration>,
+
+    /// Maximum time to wait for the server to send data while reading the response.
+    pub read: Option<Duration>,
+
+    /// Maximum time for the whole request, from connecting to reading the end of the body.
+    pub total: Option<Duration>,
+}
+
+impl Timeouts {
+    /// Creates a `Timeouts` with no timeouts set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the connect timeout.
+    pub fn with_connect(mut self, timeout: Duration) -> Self {
+        self.connect = Some(timeout);
+        self
+    }
+
+    /// Sets the read timeout.
+    pub fn with_read(mut self, timeout: Duration) -> Self {
+        self.read = Some(timeout);
+        self
+    }
+
+    /// Sets the total timeout.
+    pub fn with_total(mut self, timeout: Duration) -> Self {
+        self.total = Some(timeout);
+        self
+    }
+
+    /// Returns `true` if no timeout is set.
+    pub fn is_empty(&self) -> bool {
+        self.connect.is_none() && self.read.is_none() && self.total.is_none()
+    }
+}
+
+impl From<Duration> for Timeouts {
+    /// Creates a `Timeouts` with only the total timeout set.
+    fn from(total: Duration) -> Self {
+        Self::new().with_total(total)
+    }
+}
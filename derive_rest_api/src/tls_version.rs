@@ -0,0 +1,31 @@
+//! TLS protocol versions.
+
+use std::fmt;
+
+/// A TLS protocol version, ordered from oldest to newest.
+///
+/// # Example
+///
+/// ```
+/// use derive_rest_api::TlsVersion;
+///
+/// assert!(TlsVersion::Tls1_3 > TlsVersion::Tls1_2);
+/// assert_eq!(TlsVersion::Tls1_3.to_string(), "TLS 1.3");
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TlsVersion {
+    /// TLS 1.2
+    Tls1_2,
+
+    /// TLS 1.3
+    Tls1_3,
+}
+
+impl fmt::Display for TlsVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Tls1_2 => f.write_str("TLS 1.2"),
+            Self::Tls1_3 => f.write_str("TLS 1.3"),
+        }
+    }
+}
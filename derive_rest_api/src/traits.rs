@@ -1,6 +1,9 @@
 //! HTTP client traits for blocking and async request execution.
 
-use std::collections::HashMap;
+use crate::RequestOptions;
+use http::{HeaderMap, HeaderName, HeaderValue, Response};
+use std::future::Future;
+use std::pin::Pin;
 
 /// Trait for blocking HTTP clients that can execute REST API requests.
 ///
@@ -11,7 +14,8 @@ use std::collections::HashMap;
 ///
 /// ```
 /// use derive_rest_api::HttpClient;
-/// use std::collections::HashMap;
+/// use derive_rest_api::RequestOptions;
+/// use derive_rest_api::http::{HeaderMap, Response};
 ///
 /// #[derive(Debug)]
 /// struct MyError;
@@ -34,12 +38,12 @@ use std::collections::HashMap;
 ///         &self,
 ///         method: &str,
 ///         url: &str,
-///         headers: HashMap<String, String>,
+///         headers: HeaderMap,
 ///         body: Option<Vec<u8>>,
-///         timeout: Option<std::time::Duration>,
-///     ) -> Result<Vec<u8>, Self::Error> {
+///         options: RequestOptions,
+///     ) -> Result<Response<Vec<u8>>, Self::Error> {
 ///         // Your implementation here
-///         Ok(vec![])
+///         Ok(Response::new(vec![]))
 ///     }
 /// }
 /// ```
@@ -53,9 +57,11 @@ pub trait HttpClient: Clone + Default {
     ///
     /// - `method`: HTTP method (GET, POST, PUT, DELETE, etc.)
     /// - `url`: Complete URL including query parameters
-    /// - `headers`: HTTP headers as key-value pairs
+    /// - `headers`: HTTP headers (a name may have several values)
     /// - `body`: Optional request body as bytes
-    /// - `timeout`: Optional timeout duration for the request
+    /// - `options`: Timeouts and response size limit for the request
+    ///
+    /// Returns the response status, headers and body.
     ///
     /// # Errors
     ///
@@ -64,10 +70,10 @@ pub trait HttpClient: Clone + Default {
         &self,
         method: &str,
         url: &str,
-        headers: HashMap<String, String>,
+        headers: HeaderMap,
         body: Option<Vec<u8>>,
-        timeout: Option<std::time::Duration>,
-    ) -> Result<Vec<u8>, Self::Error>;
+        options: RequestOptions,
+    ) -> Result<Response<Vec<u8>>, Self::Error>;
 }
 
 /// Trait for async HTTP clients that can execute REST API requests.
@@ -75,11 +81,20 @@ pub trait HttpClient: Clone + Default {
 /// This trait abstracts over different async HTTP client implementations (reqwest async, hyper, etc.)
 /// allowing async code to work with any compliant async client.
 ///
+/// Nothing in this crate depends on a particular async runtime: the generated `send_async`
+/// methods only await the client's future, so the runtime is whatever the client needs
+/// (tokio for reqwest, async-std for surf, none for isahc).
+///
+/// Requests are cancelled by dropping the future returned by `send_async` (see
+/// [`CancellationToken`](crate::CancellationToken)), so implementations should abort the
+/// underlying request when that future is dropped.
+///
 /// # Example
 ///
 /// ```
 /// use derive_rest_api::AsyncHttpClient;
-/// use std::collections::HashMap;
+/// use derive_rest_api::RequestOptions;
+/// use derive_rest_api::http::{HeaderMap, Response};
 ///
 /// #[derive(Debug)]
 /// struct MyError;
@@ -102,12 +117,12 @@ pub trait HttpClient: Clone + Default {
 ///         &self,
 ///         method: &str,
 ///         url: &str,
-///         headers: HashMap<String, String>,
+///         headers: HeaderMap,
 ///         body: Option<Vec<u8>>,
-///         timeout: Option<std::time::Duration>,
-///     ) -> Result<Vec<u8>, Self::Error> {
+///         options: RequestOptions,
+///     ) -> Result<Response<Vec<u8>>, Self::Error> {
 ///         // Your async implementation here
-///         Ok(vec![])
+///         Ok(Response::new(vec![]))
 ///     }
 /// }
 /// ```
@@ -121,9 +136,11 @@ pub trait AsyncHttpClient: Clone + Default  {
     ///
     /// - `method`: HTTP method (GET, POST, PUT, DELETE, etc.)
     /// - `url`: Complete URL including query parameters
-    /// - `headers`: HTTP headers as key-value pairs
+    /// - `headers`: HTTP headers (a name may have several values)
     /// - `body`: Optional request body as bytes
-    /// - `timeout`: Optional timeout duration for the request
+    /// - `options`: Timeouts and response size limit for the request
+    ///
+    /// Returns the response status, headers and body.
     ///
     /// # Errors
     ///
@@ -133,10 +150,10 @@ pub trait AsyncHttpClient: Clone + Default  {
         &self,
         method: &str,
         url: &str,
-        headers: HashMap<String, String>,
+        headers: HeaderMap,
         body: Option<Vec<u8>>,
-        timeout: Option<std::time::Duration>,
-    ) -> impl std::future::Future<Output = Result<Vec<u8>, Self::Error>> + Send;
+        options: RequestOptions,
+    ) -> impl std::future::Future<Output = Result<Response<Vec<u8>>, Self::Error>> + Send;
 
     /// Send an async HTTP request with the given parameters (WASM version)
     ///
@@ -144,9 +161,11 @@ pub trait AsyncHttpClient: Clone + Default  {
     ///
     /// - `method`: HTTP method (GET, POST, PUT, DELETE, etc.)
     /// - `url`: Complete URL including query parameters
-    /// - `headers`: HTTP headers as key-value pairs
+    /// - `headers`: HTTP headers (a name may have several values)
     /// - `body`: Optional request body as bytes
-    /// - `timeout`: Optional timeout duration for the request
+    /// - `options`: Timeouts and response size limit for the request
+    ///
+    /// Returns the response status, headers and body.
     ///
     /// # Errors
     ///
@@ -156,10 +175,10 @@ pub trait AsyncHttpClient: Clone + Default  {
         &self,
         method: &str,
         url: &str,
-        headers: HashMap<String, String>,
+        headers: HeaderMap,
         body: Option<Vec<u8>>,
-        timeout: Option<std::time::Duration>,
-    ) -> impl std::future::Future<Output = Result<Vec<u8>, Self::Error>>;
+        options: RequestOptions,
+    ) -> impl std::future::Future<Output = Result<Response<Vec<u8>>, Self::Error>>;
 }
 
 impl HttpClient for crate::clients::UnimplementedClient {
@@ -169,10 +188,10 @@ impl HttpClient for crate::clients::UnimplementedClient {
         &self,
         _method: &str,
         _url: &str,
-        _headers: HashMap<String, String>,
+        _headers: HeaderMap,
         _body: Option<Vec<u8>>,
-        _timeout: Option<std::time::Duration>,
-    ) -> Result<Vec<u8>, Self::Error> {
+        _options: RequestOptions,
+    ) -> Result<Response<Vec<u8>>, Self::Error> {
         unimplemented!("No blocking client found.")
     }
 }
@@ -184,14 +203,64 @@ impl AsyncHttpClient for crate::clients::UnimplementedClient {
         &self,
         _method: &str,
         _url: &str,
-        _headers: HashMap<String, String>,
+        _headers: HeaderMap,
         _body: Option<Vec<u8>>,
-        _timeout: Option<std::time::Duration>,
-    ) -> Result<Vec<u8>, Self::Error> {
+        _options: RequestOptions,
+    ) -> Result<Response<Vec<u8>>, Self::Error> {
         unimplemented!("No async client found.")
     }
 }
 
+/// A built request whose parts can be sent by code that doesn't know its concrete type, such as [`Batch`](crate::Batch).
+///
+/// This trait is implemented by `#[derive(RequestBuilder)]` structs with a `path`, and by
+/// `#[derive(RequestEnum)]` enums, whose response is the raw body.
+pub trait RestRequest {
+    /// What a successful response decodes to (the `response` type, or `Vec<u8>`)
+    type Response;
+
+    /// Returns the HTTP method of the request
+    fn method(&self) -> &'static str;
+
+    /// Builds the URL path and query string, relative to the base URL
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any required path parameters are not set or if query serialization fails.
+    fn build_url(&self) -> Result<String, crate::RestApiError>;
+
+    /// Builds the request headers
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a field's value is not a valid header value.
+    fn build_headers(&self) -> Result<HeaderMap, crate::RestApiError>;
+
+    /// Builds the request body
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if body serialization fails.
+    fn build_body(&self) -> Result<Option<Vec<u8>>, crate::RestApiError>;
+
+    /// Decodes a response to this request, as `send()` does
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the response doesn't decode into `Self::Response`.
+    fn parse_response(response: Response<Vec<u8>>) -> Result<Self::Response, crate::RestApiError>;
+}
+
+/// The future returned by the methods of traits generated with `#[api_client(async_trait)]`.
+///
+/// It is `Send`, so it can be spawned onto a multi-threaded runtime.
+#[cfg(not(target_arch = "wasm32"))]
+pub type ResponseFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, crate::RestApiError>> + Send + 'a>>;
+
+/// The future returned by the methods of traits generated with `#[api_client(async_trait)]` (WASM version).
+#[cfg(target_arch = "wasm32")]
+pub type ResponseFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, crate::RestApiError>> + 'a>>;
+
 /// Trait for modifying request builders with common operations.
 ///
 /// This trait is automatically implemented by all generated request builders,
@@ -207,20 +276,98 @@ impl AsyncHttpClient for crate::clients::UnimplementedClient {
 /// }
 /// ```
 pub trait RequestModifier: Sized {
-    /// Adds an HTTP header to the request.
+    /// Adds an HTTP header to the request, replacing any previous value.
+    ///
+    /// The name and value are validated; an invalid header is reported as
+    /// [`RestApiError::InvalidHeader`](crate::RestApiError::InvalidHeader) when the request is sent.
     ///
     /// # Arguments
     ///
     /// * `name` - The header name
     /// * `value` - The header value
-    fn header(self, name: impl Into<String>, value: impl Into<String>) -> Self;
+    fn header<K, V>(self, name: K, value: V) -> Self
+    where
+        HeaderName: TryFrom<K>,
+        <HeaderName as TryFrom<K>>::Error: Into<http::Error>,
+        HeaderValue: TryFrom<V>,
+        <HeaderValue as TryFrom<V>>::Error: Into<http::Error>;
 
-    /// Sets the timeout duration for the request.
+    /// Adds an HTTP header to the request, keeping any previous values.
+    ///
+    /// Use this for headers that may appear several times, such as `Accept`
+    /// or `Forwarded`. Values are validated like [`header`](Self::header).
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The header name
+    /// * `value` - The header value
+    fn append_header<K, V>(self, name: K, value: V) -> Self
+    where
+        HeaderName: TryFrom<K>,
+        <HeaderName as TryFrom<K>>::Error: Into<http::Error>,
+        HeaderValue: TryFrom<V>,
+        <HeaderValue as TryFrom<V>>::Error: Into<http::Error>;
+
+    /// Sets the `Authorization` header to `Bearer <token>`.
+    ///
+    /// # Arguments
+    ///
+    /// * `token` - The bearer token, e.g. an access token or a signed JWT
+    fn bearer_auth(self, token: impl std::fmt::Display) -> Self {
+        self.header(http::header::AUTHORIZATION, format!("Bearer {}", token))
+    }
+
+    /// Sets the total timeout for the request, covering connecting, sending and
+    /// reading the whole response.
     ///
     /// # Arguments
     ///
     /// * `timeout` - The timeout duration
     fn timeout(self, timeout: std::time::Duration) -> Self;
+
+    /// Sets the timeout for establishing the connection.
+    ///
+    /// # Arguments
+    ///
+    /// * `timeout` - The timeout duration
+    fn connect_timeout(self, timeout: std::time::Duration) -> Self;
+
+    /// Sets the timeout for waiting on the server while reading the response.
+    ///
+    /// # Arguments
+    ///
+    /// * `timeout` - The timeout duration
+    fn read_timeout(self, timeout: std::time::Duration) -> Self;
+
+    /// Sets the largest response body, in bytes, to accept.
+    ///
+    /// Reading stops once the body grows past the limit, and the request fails
+    /// with `RestApiError::ResponseTooLarge`.
+    ///
+    /// # Arguments
+    ///
+    /// * `limit` - The maximum response size in bytes
+    fn max_response_bytes(self, limit: u64) -> Self;
+
+    /// Requires the request to be sent over HTTPS.
+    ///
+    /// If the final URL uses another scheme, the request fails with
+    /// `RestApiError::InsecureUrl` before anything is sent, including any
+    /// credentials added by the client.
+    fn require_https(self) -> Self;
+
+    /// Requires the request to be sent over HTTPS with at least the given TLS
+    /// version.
+    ///
+    /// Plain-HTTP URLs fail like with [`require_https`](Self::require_https).
+    /// The version is passed to the HTTP client in
+    /// `RequestOptions::min_tls_version`; the bundled clients fix their TLS
+    /// versions when they are created, so set the same minimum there.
+    ///
+    /// # Arguments
+    ///
+    /// * `version` - The oldest acceptable TLS version
+    fn min_tls_version(self, version: crate::TlsVersion) -> Self;
 }
 
 /// Trait for configuration structs to modify request builders.
@@ -0,0 +1,478 @@
+//! How a request builder sends its request: credentials, retries, failover, middleware, metrics, events and auditing.
+
+use crate::{
+    AsyncCredentialsProvider, AsyncHttpClient, AuditRecord, AuditSink, Clock, CredentialsProvider, EventSubscriber,
+    Failover, HttpClient, HttpErrorContext, MetricsHook, MiddlewareStack, OfflineQueue, QueryEncoder, QueryOrder,
+    RequestEvent, RequestEventKind, RequestMetrics, RequestOptions, RequestParts, RestApiError, RetryBudget,
+    RetryPolicy,
+};
+use crate::audit::{body_digest, is_audited};
+use crate::offline::{QueuedRequest, is_queued, replay_headers};
+use crate::clock::clock_or_system;
+use http::{HeaderMap, Response};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// The client-level sending behavior a request builder carries.
+///
+/// Each attempt of a retried request is routed through the failover, if any,
+/// gets the current credentials, and passes through the middleware on its way
+/// to the HTTP client. The request as a whole, retries included, is reported to
+/// the metrics hook, and each stage of it to the event subscribers. Requests
+/// other than a `GET` are recorded by the audit sink once they finish, and
+/// kept in the offline queue if they can't be sent.
+#[derive(Clone, Default)]
+pub struct Transport {
+    pub failover: Option<Failover>,
+    pub retry: Option<RetryPolicy>,
+    /// Caps the retries of every request sharing it
+    pub retry_budget: Option<RetryBudget>,
+    pub middleware: MiddlewareStack,
+    /// Credentials for `send()`
+    pub credentials: Option<Arc<dyn CredentialsProvider>>,
+    /// Credentials for `send_async()`
+    pub async_credentials: Option<Arc<dyn AsyncCredentialsProvider>>,
+    /// Waits out retry backoff, the system clock if unset
+    pub clock: Option<Arc<dyn Clock>>,
+    /// Told about every request sent
+    pub metrics: Option<Arc<dyn MetricsHook>>,
+    /// Told about each stage of every request, in order
+    pub events: Vec<Arc<dyn EventSubscriber>>,
+    /// Encodes the query string of requests without their own `query_encoder`
+    pub query_encoder: Option<Arc<dyn QueryEncoder>>,
+    /// The order of the query parameters of requests without their own `query_order`
+    pub query_order: QueryOrder,
+    /// Records every request other than a `GET`
+    pub audit: Option<Arc<dyn AuditSink>>,
+    /// Who requests are made on behalf of, in audit records
+    pub actor: Option<String>,
+    /// Holds requests other than a `GET` or `HEAD` that can't be sent, and replays them
+    pub offline: Option<OfflineQueue>,
+    /// Sends the requests of `send_background()`
+    #[cfg(not(target_arch = "wasm32"))]
+    pub background: Option<crate::BackgroundSender>,
+    /// The request struct being sent, which labels its metrics
+    pub endpoint: Option<&'static str>,
+}
+
+/// The attempts made so far by one `send()`
+struct Attempts {
+    count: AtomicU32,
+    /// The URL of the latest attempt, kept for auditing and HTTP error context
+    url: Mutex<String>,
+    /// When the request started, for the context of an HTTP error
+    started: Option<Instant>,
+    bytes_sent: AtomicU64,
+    /// Response body bytes received, if any attempt got a response
+    bytes_received: Mutex<Option<u64>>,
+}
+
+impl Attempts {
+    fn new(base_url: &str, path: &str, started: Option<Instant>) -> Self {
+        Self {
+            count: AtomicU32::new(0),
+            url: Mutex::new(format!("{}{}", base_url, path)),
+            started,
+            bytes_sent: AtomicU64::new(0),
+            bytes_received: Mutex::new(None),
+        }
+    }
+
+    /// Counts the body of a response received by an attempt
+    fn received(&self, response: &Response<Vec<u8>>) {
+        let mut received = self.bytes_received.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        *received = Some(received.unwrap_or(0) + response.body().len() as u64);
+    }
+
+    /// Adds how far the request got to an HTTP error that doesn't say yet
+    fn annotate(&self, error: RestApiError, endpoint: Option<&'static str>, clock: &dyn Clock) -> RestApiError {
+        match error {
+            RestApiError::HttpError { source, context: None } => RestApiError::HttpError {
+                source,
+                context: Some(Box::new(HttpErrorContext {
+                    endpoint,
+                    url: self.url.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).clone(),
+                    elapsed: self.started.map(|started| clock.now().saturating_duration_since(started)),
+                    attempts: self.count.load(Ordering::Relaxed),
+                    bytes_sent: self.bytes_sent.load(Ordering::Relaxed),
+                    bytes_received: *self.bytes_received.lock().unwrap_or_else(|poisoned| poisoned.into_inner()),
+                })),
+            },
+            error => error,
+        }
+    }
+}
+
+impl Transport {
+    /// Sends a request for `path` on `base_url`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn send<C: HttpClient>(
+        &self,
+        client: &C,
+        method: &str,
+        base_url: &str,
+        path: &str,
+        headers: HeaderMap,
+        body: Option<Vec<u8>>,
+        options: RequestOptions,
+    ) -> Result<Response<Vec<u8>>, RestApiError> {
+        let queue = self.offline.as_ref().filter(|_| is_queued(method));
+        if let Some(queue) = queue {
+            // Requests queued earlier go first
+            if self.replay(client, &headers, options)? > 0 {
+                return Err(queue.push(method, base_url, path, &headers, &body));
+            }
+        }
+        let started = self.start_measuring();
+        let attempts = Attempts::new(base_url, path, self.start_timing(started));
+        let attempt = |request: RequestParts| self.attempt(client, request, options, path, &attempts);
+        self.emit(method, path, RequestEventKind::Built);
+        let mut retry = 0;
+        loop {
+            let result = match &self.failover {
+                Some(failover) => failover.route(method, path, headers.clone(), body.clone(), attempt),
+                None => attempt(request(method, base_url, path, &headers, &body)),
+            };
+            match self.retry_delay(method, retry, &result) {
+                Some(delay) => {
+                    self.emit_retry(method, path, retry, delay, &result);
+                    clock_or_system(&self.clock).sleep(delay);
+                }
+                None => {
+                    let result = match (queue, result) {
+                        (Some(queue), Err(RestApiError::HttpError { .. })) => {
+                            Err(queue.push(method, base_url, path, &headers, &body))
+                        }
+                        (_, result) => result,
+                    };
+                    let clock = clock_or_system(&self.clock);
+                    let result = result.map_err(|error| attempts.annotate(error, self.endpoint, clock));
+                    self.finish(started, method, path, retry + 1, &result);
+                    self.audit(method, &attempts, &body, &result);
+                    return result;
+                }
+            }
+            retry += 1;
+        }
+    }
+
+    /// Async version of [`send`](Self::send).
+    #[allow(clippy::too_many_arguments)]
+    pub async fn send_async<A: AsyncHttpClient>(
+        &self,
+        client: &A,
+        method: &str,
+        base_url: &str,
+        path: &str,
+        headers: HeaderMap,
+        body: Option<Vec<u8>>,
+        options: RequestOptions,
+    ) -> Result<Response<Vec<u8>>, RestApiError> {
+        let queue = self.offline.as_ref().filter(|_| is_queued(method));
+        if let Some(queue) = queue {
+            // Requests queued earlier go first
+            if self.replay_async(client, &headers, options).await? > 0 {
+                return Err(queue.push(method, base_url, path, &headers, &body));
+            }
+        }
+        let started = self.start_measuring();
+        let attempts = Attempts::new(base_url, path, self.start_timing(started));
+        let attempt = |request: RequestParts| self.attempt_async(client, request, options, path, &attempts);
+        self.emit(method, path, RequestEventKind::Built);
+        let mut retry = 0;
+        loop {
+            let result = match &self.failover {
+                Some(failover) => failover.route_async(method, path, headers.clone(), body.clone(), attempt).await,
+                None => attempt(request(method, base_url, path, &headers, &body)).await,
+            };
+            match self.retry_delay(method, retry, &result) {
+                Some(delay) => {
+                    self.emit_retry(method, path, retry, delay, &result);
+                    clock_or_system(&self.clock).sleep_async(delay).await;
+                }
+                None => {
+                    let result = match (queue, result) {
+                        (Some(queue), Err(RestApiError::HttpError { .. })) => {
+                            Err(queue.push(method, base_url, path, &headers, &body))
+                        }
+                        (_, result) => result,
+                    };
+                    let clock = clock_or_system(&self.clock);
+                    let result = result.map_err(|error| attempts.annotate(error, self.endpoint, clock));
+                    self.finish(started, method, path, retry + 1, &result);
+                    self.audit(method, &attempts, &body, &result);
+                    return result;
+                }
+            }
+            retry += 1;
+        }
+    }
+
+    /// Sends the requests in the offline queue in order, returning how many are
+    /// still queued, including all of them if another replay is running.
+    ///
+    /// Requests read back from the queue's file get the sensitive headers of
+    /// `headers` they're missing.
+    pub fn replay<C: HttpClient>(
+        &self,
+        client: &C,
+        headers: &HeaderMap,
+        options: RequestOptions,
+    ) -> Result<usize, RestApiError> {
+        let Some(queue) = &self.offline else {
+            return Ok(0);
+        };
+        let Some(_replaying) = queue.start_replay() else {
+            return Ok(queue.len());
+        };
+        while let Some(queued) = queue.front() {
+            let attempts = Attempts::new(&queued.base_url, &queued.path, None);
+            let result = self.attempt(client, replayed(&queued, headers), options, &queued.path, &attempts);
+            if !queue.settle(&queued, &result)? {
+                break;
+            }
+        }
+        Ok(queue.len())
+    }
+
+    /// Async version of [`replay`](Self::replay).
+    pub async fn replay_async<A: AsyncHttpClient>(
+        &self,
+        client: &A,
+        headers: &HeaderMap,
+        options: RequestOptions,
+    ) -> Result<usize, RestApiError> {
+        let Some(queue) = &self.offline else {
+            return Ok(0);
+        };
+        let Some(_replaying) = queue.start_replay() else {
+            return Ok(queue.len());
+        };
+        while let Some(queued) = queue.front() {
+            let attempts = Attempts::new(&queued.base_url, &queued.path, None);
+            let result = self.attempt_async(client, replayed(&queued, headers), options, &queued.path, &attempts).await;
+            if !queue.settle(&queued, &result)? {
+                break;
+            }
+        }
+        Ok(queue.len())
+    }
+
+    /// Returns when the request started, if there is a metrics hook or subscriber to report it to
+    fn start_measuring(&self) -> Option<Instant> {
+        (self.metrics.is_some() || !self.events.is_empty()).then(|| clock_or_system(&self.clock).now())
+    }
+
+    /// Returns when the request started, for the context of an HTTP error
+    ///
+    /// On `wasm32`, where the system clock can't be read, the time is only
+    /// known if it's being measured anyway.
+    fn start_timing(&self, measuring: Option<Instant>) -> Option<Instant> {
+        #[cfg(not(target_arch = "wasm32"))]
+        return measuring.or_else(|| Some(clock_or_system(&self.clock).now()));
+        #[cfg(target_arch = "wasm32")]
+        return measuring;
+    }
+
+    /// Reports the outcome of the request to the metrics hook and subscribers
+    fn finish(
+        &self,
+        started: Option<Instant>,
+        method: &str,
+        path: &str,
+        attempts: u32,
+        result: &Result<Response<Vec<u8>>, RestApiError>,
+    ) {
+        let Some(started) = started else {
+            return;
+        };
+        let duration = clock_or_system(&self.clock).now().saturating_duration_since(started);
+        if let Some(hook) = &self.metrics {
+            hook.record(&RequestMetrics {
+                endpoint: self.endpoint.unwrap_or("unknown"),
+                method: method.to_string(),
+                status: result.as_ref().ok().map(Response::status),
+                duration,
+                attempts,
+            });
+        }
+        let kind = match result {
+            Ok(response) => RequestEventKind::Completed {
+                status: response.status(),
+                duration,
+            },
+            Err(error) => RequestEventKind::Failed { error: error.to_string() },
+        };
+        self.emit(method, path, kind);
+    }
+
+    /// Records a finished request with the audit sink, unless it's a `GET`
+    fn audit(
+        &self,
+        method: &str,
+        attempts: &Attempts,
+        body: &Option<Vec<u8>>,
+        result: &Result<Response<Vec<u8>>, RestApiError>,
+    ) {
+        let Some(sink) = self.audit.as_ref().filter(|_| is_audited(method)) else {
+            return;
+        };
+        sink.record(&AuditRecord {
+            endpoint: self.endpoint.unwrap_or("unknown"),
+            method: method.to_string(),
+            url: attempts.url.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).clone(),
+            actor: self.actor.clone(),
+            body_digest: body.as_deref().map(body_digest),
+            status: result.as_ref().ok().map(Response::status),
+        });
+    }
+
+    /// Reports that the request is about to be retried after `delay`
+    fn emit_retry(
+        &self,
+        method: &str,
+        path: &str,
+        retry: u32,
+        delay: Duration,
+        result: &Result<Response<Vec<u8>>, RestApiError>,
+    ) {
+        let status = result.as_ref().ok().map(Response::status);
+        self.emit(method, path, RequestEventKind::Retried { retry: retry + 1, delay, status });
+    }
+
+    /// Tells the subscribers about an event, if there are any
+    fn emit(&self, method: &str, path: &str, kind: RequestEventKind) {
+        if self.events.is_empty() {
+            return;
+        }
+        let event = RequestEvent {
+            endpoint: self.endpoint.unwrap_or("unknown"),
+            method: method.to_string(),
+            path: path.to_string(),
+            at: clock_or_system(&self.clock).now(),
+            kind,
+        };
+        for subscriber in &self.events {
+            subscriber.on_event(&event);
+        }
+    }
+
+    /// Reports an attempt being handed to the HTTP client
+    fn emit_sent(&self, request: &RequestParts, path: &str, attempts: &Attempts) {
+        let attempt = attempts.count.fetch_add(1, Ordering::Relaxed) + 1;
+        *attempts.url.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = request.url.clone();
+        self.emit(&request.method, path, RequestEventKind::Sent { url: request.url.clone(), attempt });
+    }
+
+    fn attempt<C: HttpClient>(
+        &self,
+        client: &C,
+        mut request: RequestParts,
+        options: RequestOptions,
+        path: &str,
+        attempts: &Attempts,
+    ) -> Result<Response<Vec<u8>>, RestApiError> {
+        match (&self.credentials, &self.async_credentials) {
+            (Some(provider), _) => provider.credentials()?.apply(&mut request.headers)?,
+            (None, Some(_)) => {
+                return Err(RestApiError::credentials_error("an async credentials provider can't be used by send()"));
+            }
+            (None, None) => {}
+        }
+        self.middleware.on_request(&mut request);
+        options.check_url(&request.url)?;
+        self.emit_sent(&request, path, attempts);
+        let body_len = request.body.as_ref().map_or(0, |body| body.len() as u64);
+        attempts.bytes_sent.fetch_add(body_len, Ordering::Relaxed);
+        let mut response = client
+            .send(&request.method, &request.url, request.headers, request.body, options)
+            .map_err(RestApiError::http_error)?;
+        attempts.received(&response);
+        self.middleware.on_response(&request.method, &request.url, &mut response);
+        Ok(response)
+    }
+
+    async fn attempt_async<A: AsyncHttpClient>(
+        &self,
+        client: &A,
+        mut request: RequestParts,
+        options: RequestOptions,
+        path: &str,
+        attempts: &Attempts,
+    ) -> Result<Response<Vec<u8>>, RestApiError> {
+        if let Some(provider) = &self.async_credentials {
+            provider.credentials_async().await?.apply(&mut request.headers)?;
+        }
+        self.middleware.on_request(&mut request);
+        options.check_url(&request.url)?;
+        self.emit_sent(&request, path, attempts);
+        let body_len = request.body.as_ref().map_or(0, |body| body.len() as u64);
+        attempts.bytes_sent.fetch_add(body_len, Ordering::Relaxed);
+        let mut response = client
+            .send_async(&request.method, &request.url, request.headers, request.body, options)
+            .await
+            .map_err(RestApiError::http_error)?;
+        attempts.received(&response);
+        self.middleware.on_response(&request.method, &request.url, &mut response);
+        Ok(response)
+    }
+
+    /// Returns how long to wait before retrying `result`, if it should be retried
+    #[cfg(not(target_arch = "wasm32"))]
+    fn retry_delay(&self, method: &str, retry: u32, result: &Result<Response<Vec<u8>>, RestApiError>) -> Option<Duration> {
+        // Only the HTTP client failing transiently is worth retrying, not a missing credential
+        if matches!(result, Err(error) if !error.is_transient()) {
+            return None;
+        }
+        let policy = self.retry.as_ref().filter(|policy| policy.allows(method))?;
+        let delay = policy.delay(retry, result)?;
+        match &self.retry_budget {
+            Some(budget) if !budget.try_acquire() => None,
+            _ => Some(delay),
+        }
+    }
+
+    /// Retries aren't available without a timer to wait with
+    #[cfg(target_arch = "wasm32")]
+    fn retry_delay(&self, _method: &str, _retry: u32, _result: &Result<Response<Vec<u8>>, RestApiError>) -> Option<Duration> {
+        None
+    }
+}
+
+impl std::fmt::Debug for Transport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut debug = f.debug_struct("Transport");
+        debug
+            .field("failover", &self.failover)
+            .field("retry", &self.retry)
+            .field("retry_budget", &self.retry_budget)
+            .field("middleware", &self.middleware)
+            .field("credentials", &(self.credentials.is_some() || self.async_credentials.is_some()))
+            .field("clock", &self.clock)
+            .field("metrics", &self.metrics.is_some())
+            .field("events", &self.events.len())
+            .field("query_encoder", &self.query_encoder.is_some())
+            .field("query_order", &self.query_order)
+            .field("audit", &self.audit.is_some())
+            .field("actor", &self.actor)
+            .field("offline", &self.offline)
+            .field("endpoint", &self.endpoint);
+        #[cfg(not(target_arch = "wasm32"))]
+        debug.field("background", &self.background);
+        debug.finish()
+    }
+}
+
+fn request(method: &str, base_url: &str, path: &str, headers: &HeaderMap, body: &Option<Vec<u8>>) -> RequestParts {
+    RequestParts {
+        method: method.to_string(),
+        url: format!("{}{}", base_url, path),
+        headers: headers.clone(),
+        body: body.clone(),
+    }
+}
+
+fn replayed(queued: &QueuedRequest, headers: &HeaderMap) -> RequestParts {
+    request(&queued.method, &queued.base_url, &queued.path, &replay_headers(queued, headers), &queued.body)
+}
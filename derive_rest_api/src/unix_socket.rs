@@ -0,0 +1,108 @@
+//! URLs addressing HTTP servers that listen on Unix domain sockets.
+
+use std::ffi::OsString;
+use std::fmt;
+use std::os::unix::ffi::{OsStrExt, OsStringExt};
+use std::path::{Path, PathBuf};
+
+/// A URL addressing an HTTP server on a Unix domain socket, such as the
+/// Docker daemon or a systemd-style local API.
+///
+/// These URLs use the `unix` scheme, with the socket path percent-encoded in
+/// place of the host and the request path (including the API's own prefix)
+/// after it: `unix://%2Fvar%2Frun%2Fdocker.sock/v1.43/containers/json`.
+/// Because the socket path is carried separately from the request path, such
+/// a URL works as a client's `base_url` with the request path appended.
+///
+/// The unencoded form `unix:///var/run/docker.sock/v1.43` is accepted too. Its
+/// socket path ends at the first component that exists as a file; if none
+/// does, the whole path is taken as the socket.
+///
+/// Backends that support Unix sockets (`IsahcClient`, `ReqwestBlockingClient`
+/// and `ReqwestAsyncClient`) recognise these URLs and send the request to the
+/// socket with a `Host` of `localhost`.
+///
+/// # Example
+///
+/// ```
+/// use derive_rest_api::UnixSocketUrl;
+///
+/// let base_url = UnixSocketUrl::new("/var/run/docker.sock", "/v1.43").to_string();
+/// assert_eq!(base_url, "unix://%2Fvar%2Frun%2Fdocker.sock/v1.43");
+///
+/// let url = UnixSocketUrl::parse(&format!("{base_url}/containers/json?all=true")).unwrap();
+/// assert_eq!(url.socket(), std::path::Path::new("/var/run/docker.sock"));
+/// assert_eq!(url.http_url(), "http://localhost/v1.43/containers/json?all=true");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct UnixSocketUrl {
+    socket: PathBuf,
+    path: String,
+}
+
+impl UnixSocketUrl {
+    /// Creates a URL for `path` (which may include a query) on the server
+    /// listening at `socket`.
+    pub fn new(socket: impl Into<PathBuf>, path: impl Into<String>) -> Self {
+        Self { socket: socket.into(), path: path.into() }
+    }
+
+    /// Parses a `unix://` URL, returning `None` for other schemes or a URL
+    /// without a socket path.
+    pub fn parse(url: &str) -> Option<Self> {
+        let rest = url.strip_prefix("unix://")?;
+
+        let (socket, path) = if rest.starts_with('/') {
+            let (full_path, query) = rest.split_at(rest.find('?').unwrap_or(rest.len()));
+            let end = full_path
+                .match_indices('/')
+                .map(|(index, _)| index)
+                .skip(1)
+                .chain([full_path.len()])
+                .find(|&end| Path::new(&full_path[..end]).metadata().is_ok_and(|metadata| !metadata.is_dir()))
+                .unwrap_or(full_path.len());
+            (&full_path[..end], format!("{}{}", &full_path[end..], query))
+        } else {
+            let (host, path) = rest.split_at(rest.find(['/', '?']).unwrap_or(rest.len()));
+            (host, path.to_string())
+        };
+
+        if socket.is_empty() {
+            return None;
+        }
+        let socket = PathBuf::from(OsString::from_vec(crate::canonical::percent_decode(socket)));
+        Some(Self { socket, path })
+    }
+
+    /// Returns the path of the socket the server listens on.
+    pub fn socket(&self) -> &Path {
+        &self.socket
+    }
+
+    /// Returns the request path and query.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Returns the `http://localhost` URL to send over the socket.
+    pub fn http_url(&self) -> String {
+        if self.path.starts_with('/') {
+            format!("http://localhost{}", self.path)
+        } else {
+            format!("http://localhost/{}", self.path)
+        }
+    }
+}
+
+impl fmt::Display for UnixSocketUrl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("unix://")?;
+        for &byte in self.socket.as_os_str().as_bytes() {
+            match byte {
+                b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => write!(f, "{}", byte as char)?,
+                _ => write!(f, "%{:02X}", byte)?,
+            }
+        }
+        f.write_str(&self.path)
+    }
+}
@@ -0,0 +1,217 @@
+//! Responses that keep the JSON fields their type doesn't model, for
+//! `#[request_builder(capture_unknown_fields)]`.
+
+use crate::RestApiError;
+use serde::de::{self, DeserializeOwned, DeserializeSeed, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde_json::Value;
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+/// A deserialized response together with the fields its type ignored.
+///
+/// Each unknown field is keyed by its path in the body, such as `plan` for a
+/// top-level field or `items[0].discount` for a nested one, so an SDK can
+/// notice when the API starts sending fields that aren't modelled yet.
+/// Dereferences to the response value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WithUnknownFields<T> {
+    /// The deserialized response
+    pub value: T,
+    /// Fields present in the body that `T` didn't deserialize, by path
+    pub unknown_fields: HashMap<String, Value>,
+}
+
+impl<T: DeserializeOwned> WithUnknownFields<T> {
+    /// Deserializes a JSON body into `T`, collecting the fields it ignores.
+    ///
+    /// # Errors
+    ///
+    /// Returns `RestApiError::ResponseDeserializationError` if the body isn't
+    /// JSON or doesn't match `T`.
+    pub fn from_slice(body: &[u8]) -> Result<Self, RestApiError> {
+        let value: Value = serde_json::from_slice(body).map_err(RestApiError::response_deserialization_error)?;
+        Self::from_value(value).map_err(RestApiError::response_deserialization_error)
+    }
+
+    /// Deserializes a JSON value into `T`, collecting the fields it ignores.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value doesn't match `T`.
+    pub fn from_value(value: Value) -> Result<Self, serde_json::Error> {
+        let unknown_fields = RefCell::new(HashMap::new());
+        let value = T::deserialize(Tracking {
+            value,
+            path: String::new(),
+            unknown_fields: &unknown_fields,
+        })?;
+        Ok(Self {
+            value,
+            unknown_fields: unknown_fields.into_inner(),
+        })
+    }
+}
+
+impl<T> WithUnknownFields<T> {
+    /// Returns `true` if the body had any fields that `T` doesn't model.
+    pub fn has_unknown_fields(&self) -> bool {
+        !self.unknown_fields.is_empty()
+    }
+
+    /// Consumes `self`, returning the response value.
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T> std::ops::Deref for WithUnknownFields<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+/// Deserializes a JSON body into `T`, failing on any field `T` doesn't model,
+/// for `#[request_builder(strict_response)]`.
+pub fn decode_strict<T: DeserializeOwned>(body: &[u8]) -> Result<T, RestApiError> {
+    let response = WithUnknownFields::<T>::from_slice(body)?;
+    if !response.has_unknown_fields() {
+        return Ok(response.value);
+    }
+    let mut paths: Vec<_> = response.unknown_fields.keys().map(|path| format!("`{}`", path)).collect();
+    paths.sort();
+    let message = format!("unknown field{} {}", if paths.len() == 1 { "" } else { "s" }, paths.join(", "));
+    Err(RestApiError::response_deserialization_error(de::Error::custom(message)))
+}
+
+/// Deserializer over a JSON value that records the values deserialized as
+/// `IgnoredAny`, which is how derived impls skip unknown fields.
+struct Tracking<'a> {
+    value: Value,
+    path: String,
+    unknown_fields: &'a RefCell<HashMap<String, Value>>,
+}
+
+impl<'de> Deserializer<'de> for Tracking<'_> {
+    type Error = serde_json::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.value {
+            Value::Object(map) => visitor.visit_map(TrackingMap {
+                entries: map.into_iter(),
+                pending: None,
+                path: self.path,
+                unknown_fields: self.unknown_fields,
+            }),
+            Value::Array(items) => visitor.visit_seq(TrackingSeq {
+                items: items.into_iter().enumerate(),
+                path: self.path,
+                unknown_fields: self.unknown_fields,
+            }),
+            value => value.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.value {
+            Value::Null => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    // Fields inside enum variants aren't tracked
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.value.deserialize_enum(name, variants, visitor)
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.unknown_fields.borrow_mut().insert(self.path, self.value);
+        visitor.visit_unit()
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct identifier
+    }
+}
+
+struct TrackingMap<'a> {
+    entries: serde_json::map::IntoIter,
+    pending: Option<(String, Value)>,
+    path: String,
+    unknown_fields: &'a RefCell<HashMap<String, Value>>,
+}
+
+impl<'de> MapAccess<'de> for TrackingMap<'_> {
+    type Error = serde_json::Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error> {
+        let Some((key, value)) = self.entries.next() else {
+            return Ok(None);
+        };
+        let deserialized = seed.deserialize(de::value::StrDeserializer::<serde_json::Error>::new(&key))?;
+        self.pending = Some((key, value));
+        Ok(Some(deserialized))
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Self::Error> {
+        let (key, value) = self
+            .pending
+            .take()
+            .ok_or_else(|| de::Error::custom("value requested before its key"))?;
+        let path = if self.path.is_empty() {
+            key
+        } else {
+            format!("{}.{}", self.path, key)
+        };
+        seed.deserialize(Tracking {
+            value,
+            path,
+            unknown_fields: self.unknown_fields,
+        })
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.entries.len())
+    }
+}
+
+struct TrackingSeq<'a> {
+    items: std::iter::Enumerate<std::vec::IntoIter<Value>>,
+    path: String,
+    unknown_fields: &'a RefCell<HashMap<String, Value>>,
+}
+
+impl<'de> SeqAccess<'de> for TrackingSeq<'_> {
+    type Error = serde_json::Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error> {
+        let Some((index, value)) = self.items.next() else {
+            return Ok(None);
+        };
+        seed.deserialize(Tracking {
+            value,
+            path: format!("{}[{}]", self.path, index),
+            unknown_fields: self.unknown_fields,
+        })
+        .map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.items.len())
+    }
+}
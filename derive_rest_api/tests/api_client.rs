@@ -21,8 +21,8 @@ struct GetUser {
 
 #[derive(RequestBuilder)]
 #[request_builder(method = "POST", path = "/users")]
-#[allow(dead_code)]
 struct CreateUser {
+    #[request_builder(body)]
     name: String,
 }
 
@@ -61,11 +61,11 @@ fn test_client_struct_generation() {
             &self,
             _method: &str,
             _url: &str,
-            _headers: std::collections::HashMap<String, String>,
+            _headers: derive_rest_api::http::HeaderMap,
             _body: Option<Vec<u8>>,
-            _timeout: Option<std::time::Duration>,
-        ) -> Result<Vec<u8>, Self::Error> {
-            Ok(vec![])
+            _options: derive_rest_api::RequestOptions,
+        ) -> Result<derive_rest_api::http::Response<Vec<u8>>, Self::Error> {
+            Ok(derive_rest_api::http::Response::new(vec![]))
         }
     }
 
@@ -90,11 +90,11 @@ fn test_async_client_struct_generation() {
             &self,
             _method: &str,
             _url: &str,
-            _headers: std::collections::HashMap<String, String>,
+            _headers: derive_rest_api::http::HeaderMap,
             _body: Option<Vec<u8>>,
-            _timeout: Option<std::time::Duration>,
-        ) -> Result<Vec<u8>, Self::Error> {
-            Ok(vec![])
+            _options: derive_rest_api::RequestOptions,
+        ) -> Result<derive_rest_api::http::Response<Vec<u8>>, Self::Error> {
+            Ok(derive_rest_api::http::Response::new(vec![]))
         }
     }
 
@@ -114,11 +114,11 @@ fn test_method_generation() {
             &self,
             _method: &str,
             _url: &str,
-            _headers: std::collections::HashMap<String, String>,
+            _headers: derive_rest_api::http::HeaderMap,
             _body: Option<Vec<u8>>,
-            _timeout: Option<std::time::Duration>,
-        ) -> Result<Vec<u8>, Self::Error> {
-            Ok(vec![])
+            _options: derive_rest_api::RequestOptions,
+        ) -> Result<derive_rest_api::http::Response<Vec<u8>>, Self::Error> {
+            Ok(derive_rest_api::http::Response::new(vec![]))
         }
     }
 
@@ -144,11 +144,11 @@ fn test_with_base_url() {
             &self,
             _method: &str,
             _url: &str,
-            _headers: std::collections::HashMap<String, String>,
+            _headers: derive_rest_api::http::HeaderMap,
             _body: Option<Vec<u8>>,
-            _timeout: Option<std::time::Duration>,
-        ) -> Result<Vec<u8>, Self::Error> {
-            Ok(vec![])
+            _options: derive_rest_api::RequestOptions,
+        ) -> Result<derive_rest_api::http::Response<Vec<u8>>, Self::Error> {
+            Ok(derive_rest_api::http::Response::new(vec![]))
         }
     }
 
@@ -157,7 +157,7 @@ fn test_with_base_url() {
         .with_base_url("https://custom.example.com");
 
     // Base URL should be updated
-    assert_eq!(client.base_url, "https://custom.example.com");
+    assert_eq!(client.inner.base_url, "https://custom.example.com");
 }
 
 #[test]
@@ -187,11 +187,11 @@ fn test_config_suffix_stripping() {
             &self,
             _method: &str,
             _url: &str,
-            _headers: std::collections::HashMap<String, String>,
+            _headers: derive_rest_api::http::HeaderMap,
             _body: Option<Vec<u8>>,
-            _timeout: Option<std::time::Duration>,
-        ) -> Result<Vec<u8>, Self::Error> {
-            Ok(vec![])
+            _options: derive_rest_api::RequestOptions,
+        ) -> Result<derive_rest_api::http::Response<Vec<u8>>, Self::Error> {
+            Ok(derive_rest_api::http::Response::new(vec![]))
         }
     }
 
@@ -222,11 +222,11 @@ fn test_default_attribute() {
             &self,
             _method: &str,
             _url: &str,
-            _headers: std::collections::HashMap<String, String>,
+            _headers: derive_rest_api::http::HeaderMap,
             _body: Option<Vec<u8>>,
-            _timeout: Option<std::time::Duration>,
-        ) -> Result<Vec<u8>, Self::Error> {
-            Ok(vec![])
+            _options: derive_rest_api::RequestOptions,
+        ) -> Result<derive_rest_api::http::Response<Vec<u8>>, Self::Error> {
+            Ok(derive_rest_api::http::Response::new(vec![]))
         }
     }
 
@@ -246,11 +246,11 @@ fn test_default_attribute() {
             &self,
             _method: &str,
             _url: &str,
-            _headers: std::collections::HashMap<String, String>,
+            _headers: derive_rest_api::http::HeaderMap,
             _body: Option<Vec<u8>>,
-            _timeout: Option<std::time::Duration>,
-        ) -> Result<Vec<u8>, Self::Error> {
-            Ok(vec![])
+            _options: derive_rest_api::RequestOptions,
+        ) -> Result<derive_rest_api::http::Response<Vec<u8>>, Self::Error> {
+            Ok(derive_rest_api::http::Response::new(vec![]))
         }
     }
 
@@ -282,11 +282,11 @@ fn test_without_default_attribute() {
             &self,
             _method: &str,
             _url: &str,
-            _headers: std::collections::HashMap<String, String>,
+            _headers: derive_rest_api::http::HeaderMap,
             _body: Option<Vec<u8>>,
-            _timeout: Option<std::time::Duration>,
-        ) -> Result<Vec<u8>, Self::Error> {
-            Ok(vec![])
+            _options: derive_rest_api::RequestOptions,
+        ) -> Result<derive_rest_api::http::Response<Vec<u8>>, Self::Error> {
+            Ok(derive_rest_api::http::Response::new(vec![]))
         }
     }
 
@@ -305,14 +305,289 @@ fn test_without_default_attribute() {
             &self,
             _method: &str,
             _url: &str,
-            _headers: std::collections::HashMap<String, String>,
+            _headers: derive_rest_api::http::HeaderMap,
             _body: Option<Vec<u8>>,
-            _timeout: Option<std::time::Duration>,
-        ) -> Result<Vec<u8>, Self::Error> {
-            Ok(vec![])
+            _options: derive_rest_api::RequestOptions,
+        ) -> Result<derive_rest_api::http::Response<Vec<u8>>, Self::Error> {
+            Ok(derive_rest_api::http::Response::new(vec![]))
         }
     }
 
     let async_client = NonDefaultableAsyncClient::<MockAsyncClient>::new_with_client(MockAsyncClient);
     assert!(async_client.config().is_none());
 }
+
+#[test]
+fn test_cfg_gated_clients() {
+    // `any()` is always false, so the blocking client is compiled out entirely
+    // while the async client (gated on an always-true predicate) remains.
+    #[derive(Clone, ApiClient)]
+    #[api_client(
+        base_url = "https://api.example.com",
+        requests(GetUser),
+        blocking_cfg = "any()",
+        async_cfg = "not(any())"
+    )]
+    struct GatedConfig;
+
+    #[derive(Clone, Default)]
+    struct MockAsyncClient;
+    impl derive_rest_api::AsyncHttpClient for MockAsyncClient {
+        type Error = MockError;
+        async fn send_async(
+            &self,
+            _method: &str,
+            _url: &str,
+            _headers: derive_rest_api::http::HeaderMap,
+            _body: Option<Vec<u8>>,
+            _options: derive_rest_api::RequestOptions,
+        ) -> Result<derive_rest_api::http::Response<Vec<u8>>, Self::Error> {
+            Ok(derive_rest_api::http::Response::new(vec![]))
+        }
+    }
+
+    // A type named like the blocking client can be declared because it was not generated
+    #[expect(dead_code)]
+    struct GatedClient;
+
+    let client = GatedAsyncClient::<MockAsyncClient>::new_with_client(MockAsyncClient);
+    let _builder = client.get_user();
+}
+
+#[test]
+fn test_user_agent() {
+    #[derive(Clone, ApiClient)]
+    #[api_client(
+        base_url = "https://api.example.com",
+        requests(GetUser),
+        user_agent = concat!("my-sdk/", "1.2.3")
+    )]
+    struct AgentConfig;
+
+    // Mock HTTP client that returns the User-Agent it was sent
+    #[derive(Clone, Default)]
+    struct EchoUserAgentClient;
+    impl derive_rest_api::HttpClient for EchoUserAgentClient {
+        type Error = MockError;
+        fn send(
+            &self,
+            _method: &str,
+            _url: &str,
+            headers: derive_rest_api::http::HeaderMap,
+            _body: Option<Vec<u8>>,
+            _options: derive_rest_api::RequestOptions,
+        ) -> Result<derive_rest_api::http::Response<Vec<u8>>, Self::Error> {
+            Ok(derive_rest_api::http::Response::new(headers
+                .get("User-Agent")
+                .map(|value| value.as_bytes().to_vec())
+                .unwrap_or_default()))
+        }
+    }
+
+    let client = AgentClient::<EchoUserAgentClient>::new_with_client(EchoUserAgentClient);
+    let user_agent = client.get_user().id(1).send().unwrap();
+    assert_eq!(
+        String::from_utf8(user_agent).unwrap(),
+        concat!("my-sdk/1.2.3 derive_rest_api/", env!("CARGO_PKG_VERSION"))
+    );
+
+    // The client option replaces the product token
+    let client = client.with_user_agent("other-app/0.1");
+    let user_agent = client.get_user().id(1).send().unwrap();
+    assert!(String::from_utf8(user_agent).unwrap().starts_with("other-app/0.1 derive_rest_api/"));
+
+    // A header set on the request builder wins
+    use derive_rest_api::RequestModifier;
+    let user_agent = client.get_user().id(1).header("User-Agent", "custom").send().unwrap();
+    assert_eq!(user_agent, b"custom");
+}
+
+#[test]
+fn test_max_response_bytes() {
+    use derive_rest_api::RestApiError;
+
+    #[derive(Clone, ApiClient)]
+    #[api_client(base_url = "https://api.example.com", requests(GetUser))]
+    struct LimitConfig;
+
+    // Mock HTTP client that streams a 100 byte body, honouring the read limit
+    #[derive(Clone, Default)]
+    struct LargeBodyClient;
+    impl derive_rest_api::HttpClient for LargeBodyClient {
+        type Error = MockError;
+        fn send(
+            &self,
+            _method: &str,
+            _url: &str,
+            _headers: derive_rest_api::http::HeaderMap,
+            _body: Option<Vec<u8>>,
+            options: derive_rest_api::RequestOptions,
+        ) -> Result<derive_rest_api::http::Response<Vec<u8>>, Self::Error> {
+            let read = options.read_limit().unwrap_or(u64::MAX).min(100) as usize;
+            Ok(derive_rest_api::http::Response::new(vec![b'x'; read]))
+        }
+    }
+
+    let client = LimitClient::<LargeBodyClient>::new_with_client(LargeBodyClient);
+    assert_eq!(client.get_user().id(1).send().unwrap().len(), 100);
+
+    let client = client.with_max_response_bytes(10);
+    let result = client.get_user().id(1).send();
+    assert!(matches!(result, Err(RestApiError::ResponseTooLarge { limit: 10 })));
+
+    // A body exactly at the limit is accepted
+    let client = client.with_max_response_bytes(100);
+    assert_eq!(client.get_user().id(1).send().unwrap().len(), 100);
+}
+
+#[test]
+fn test_http_client_accessors() {
+    #[derive(Clone, ApiClient)]
+    #[api_client(base_url = "https://api.example.com", requests(GetUser))]
+    struct AccessorConfig;
+
+    // Mock HTTP client that answers with its name
+    #[derive(Clone, Default)]
+    struct NamedClient {
+        name: String,
+    }
+    impl derive_rest_api::HttpClient for NamedClient {
+        type Error = MockError;
+        fn send(
+            &self,
+            _method: &str,
+            _url: &str,
+            _headers: derive_rest_api::http::HeaderMap,
+            _body: Option<Vec<u8>>,
+            _options: derive_rest_api::RequestOptions,
+        ) -> Result<derive_rest_api::http::Response<Vec<u8>>, Self::Error> {
+            Ok(derive_rest_api::http::Response::new(self.name.clone().into_bytes()))
+        }
+    }
+
+    let mut client = AccessorClient::<NamedClient>::new_with_client(NamedClient { name: "first".to_string() });
+    assert_eq!(client.http_client().name, "first");
+
+    // Changes through the mutable handle apply to later requests
+    client.http_client_mut().name = "second".to_string();
+    assert_eq!(client.get_user().id(1).send().unwrap(), b"second");
+}
+
+// Mock HTTP client that returns the version headers it was sent
+#[derive(Clone, Default)]
+struct EchoVersionClient;
+impl derive_rest_api::HttpClient for EchoVersionClient {
+    type Error = MockError;
+    fn send(
+        &self,
+        _method: &str,
+        _url: &str,
+        headers: derive_rest_api::http::HeaderMap,
+        _body: Option<Vec<u8>>,
+        _options: derive_rest_api::RequestOptions,
+    ) -> Result<derive_rest_api::http::Response<Vec<u8>>, Self::Error> {
+        let versions: Vec<_> = ["Stripe-Version", "X-API-Version"]
+            .iter()
+            .filter_map(|name| Some(format!("{}: {}", name, headers.get(*name)?.to_str().ok()?)))
+            .collect();
+        Ok(derive_rest_api::http::Response::new(versions.join(", ").into_bytes()))
+    }
+}
+
+#[test]
+fn test_api_version() {
+    #[derive(Clone, ApiClient)]
+    #[api_client(
+        base_url = "https://api.example.com",
+        requests(GetUser),
+        version_header = "Stripe-Version",
+        version = "2024-01-01"
+    )]
+    struct VersionedConfig;
+
+    let client = VersionedClient::<EchoVersionClient>::new_with_client(EchoVersionClient);
+    assert_eq!(client.api_version(), Some("2024-01-01"));
+    assert_eq!(client.get_user().id(1).send().unwrap(), b"Stripe-Version: 2024-01-01");
+
+    // Overridden per environment
+    let client = client.with_api_version("2025-06-30");
+    assert_eq!(client.get_user().id(1).send().unwrap(), b"Stripe-Version: 2025-06-30");
+
+    // A header set on the request builder wins
+    use derive_rest_api::RequestModifier;
+    let versions = client.get_user().id(1).header("Stripe-Version", "2023-10-16").send().unwrap();
+    assert_eq!(versions, b"Stripe-Version: 2023-10-16");
+}
+
+#[test]
+fn test_api_version_default_header() {
+    #[derive(Clone, ApiClient)]
+    #[api_client(base_url = "https://api.example.com", requests(GetUser))]
+    struct UnversionedConfig;
+
+    // No version is sent until one is set
+    let client = UnversionedClient::<EchoVersionClient>::new_with_client(EchoVersionClient);
+    assert_eq!(client.api_version(), None);
+    assert_eq!(client.get_user().id(1).send().unwrap(), b"");
+
+    let client = client.with_api_version("2");
+    assert_eq!(client.get_user().id(1).send().unwrap(), b"X-API-Version: 2");
+}
+
+#[tokio::test]
+async fn test_async_trait() {
+    use derive_rest_api::ResponseFuture;
+    use std::sync::Arc;
+
+    #[derive(Clone, ApiClient)]
+    #[api_client(base_url = "https://api.example.com", requests(GetUser, DeleteUser = "remove_user"), async_trait)]
+    struct UsersConfig;
+
+    // Mock async HTTP client answering with the URL it was sent to
+    #[derive(Clone, Default)]
+    struct EchoUrlClient;
+    impl derive_rest_api::AsyncHttpClient for EchoUrlClient {
+        type Error = MockError;
+        async fn send_async(
+            &self,
+            method: &str,
+            url: &str,
+            _headers: derive_rest_api::http::HeaderMap,
+            _body: Option<Vec<u8>>,
+            _options: derive_rest_api::RequestOptions,
+        ) -> Result<derive_rest_api::http::Response<Vec<u8>>, Self::Error> {
+            Ok(derive_rest_api::http::Response::new(format!("{} {}", method, url).into_bytes()))
+        }
+    }
+
+    // A fake standing in for the client
+    struct FakeUsers;
+    impl UsersAsync for FakeUsers {
+        fn get_user(&self, request: GetUser) -> ResponseFuture<'_, Vec<u8>> {
+            Box::pin(async move { Ok(format!("fake user {}", request.id).into_bytes()) })
+        }
+
+        fn remove_user(&self, _request: DeleteUser) -> ResponseFuture<'_, Vec<u8>> {
+            Box::pin(async { Ok(Vec::new()) })
+        }
+    }
+
+    let apis: Vec<Arc<dyn UsersAsync>> = vec![
+        Arc::new(UsersAsyncClient::<EchoUrlClient>::new_with_client(EchoUrlClient)),
+        Arc::new(FakeUsers),
+    ];
+
+    let request = GetUserBuilder::new().id(7).build().unwrap();
+    assert_eq!(apis[0].get_user(request).await.unwrap(), b"GET https://api.example.com/users/7");
+    let request = DeleteUserBuilder::new().id(7).build().unwrap();
+    assert_eq!(apis[0].remove_user(request).await.unwrap(), b"DELETE https://api.example.com/users/7");
+
+    let request = GetUserBuilder::new().id(7).build().unwrap();
+    assert_eq!(apis[1].get_user(request).await.unwrap(), b"fake user 7");
+
+    // The trait objects and their futures can move to spawned tasks
+    let api = Arc::clone(&apis[0]);
+    let request = GetUserBuilder::new().id(8).build().unwrap();
+    let response = tokio::spawn(async move { api.get_user(request).await }).await.unwrap();
+    assert_eq!(response.unwrap(), b"GET https://api.example.com/users/8");
+}
@@ -0,0 +1,234 @@
+use derive_rest_api::http::{HeaderMap, Response, StatusCode};
+use derive_rest_api::{ApiClient, Middleware, RequestBuilder, RequestParts, RestApiError, RetryBudget, RetryPolicy};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[derive(Debug)]
+struct MockError;
+
+impl std::fmt::Display for MockError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "MockError")
+    }
+}
+
+impl std::error::Error for MockError {}
+
+// Mock HTTP client answering with queued statuses, then 200 OK
+#[derive(Clone, Default)]
+struct MockClient {
+    statuses: Arc<Mutex<VecDeque<StatusCode>>>,
+    requests: Arc<Mutex<Vec<(String, HeaderMap, derive_rest_api::RequestOptions)>>>,
+}
+
+impl MockClient {
+    fn respond(
+        &self,
+        method: &str,
+        url: &str,
+        headers: HeaderMap,
+        options: derive_rest_api::RequestOptions,
+    ) -> Result<Response<Vec<u8>>, MockError> {
+        self.requests.lock().unwrap().push((format!("{} {}", method, url), headers, options));
+        let status = self.statuses.lock().unwrap().pop_front().unwrap_or(StatusCode::OK);
+        Ok(Response::builder().status(status).body(b"{}".to_vec()).unwrap())
+    }
+
+    fn urls(&self) -> Vec<String> {
+        self.requests.lock().unwrap().iter().map(|(url, _, _)| url.clone()).collect()
+    }
+}
+
+impl derive_rest_api::HttpClient for MockClient {
+    type Error = MockError;
+
+    fn send(
+        &self,
+        method: &str,
+        url: &str,
+        headers: HeaderMap,
+        _body: Option<Vec<u8>>,
+        options: derive_rest_api::RequestOptions,
+    ) -> Result<Response<Vec<u8>>, Self::Error> {
+        self.respond(method, url, headers, options)
+    }
+}
+
+impl derive_rest_api::AsyncHttpClient for MockClient {
+    type Error = MockError;
+
+    async fn send_async(
+        &self,
+        method: &str,
+        url: &str,
+        headers: HeaderMap,
+        _body: Option<Vec<u8>>,
+        options: derive_rest_api::RequestOptions,
+    ) -> Result<Response<Vec<u8>>, Self::Error> {
+        self.respond(method, url, headers, options)
+    }
+}
+
+#[derive(RequestBuilder)]
+#[request_builder(method = "GET", path = "/users/{id}")]
+struct GetUser {
+    id: u64,
+}
+
+#[derive(RequestBuilder)]
+#[request_builder(method = "POST", path = "/users")]
+struct CreateUser {
+    #[request_builder(body)]
+    name: String,
+}
+
+#[derive(Clone, ApiClient)]
+#[api_client(base_url = "https://api.example.com", requests(GetUser, CreateUser))]
+struct UsersConfig;
+
+// Middleware recording the order its hooks run in
+struct Trace {
+    name: &'static str,
+    log: Arc<Mutex<Vec<String>>>,
+}
+
+impl Middleware for Trace {
+    fn on_request(&self, request: &mut RequestParts) {
+        self.log.lock().unwrap().push(format!("{} request", self.name));
+        request.headers.insert("X-Trace", self.name.parse().unwrap());
+    }
+
+    fn on_response(&self, _method: &str, _url: &str, response: &mut Response<Vec<u8>>) {
+        self.log.lock().unwrap().push(format!("{} response {}", self.name, response.status().as_u16()));
+    }
+}
+
+#[test]
+fn test_builder_collects_settings() {
+    let http = MockClient::default();
+    let client = UsersClient::builder()
+        .http_client(http.clone())
+        .base_url("https://staging.example.com")
+        .user_agent("users/1.0")
+        .default_header("X-Tenant", "acme")
+        .timeout(Duration::from_secs(5))
+        .build()
+        .unwrap();
+
+    client.get_user().id(1).send_raw().unwrap();
+
+    let (url, headers, options) = http.requests.lock().unwrap().pop().unwrap();
+    assert_eq!(url, "GET https://staging.example.com/users/1");
+    assert_eq!(headers["X-Tenant"], "acme");
+    assert!(headers["User-Agent"].to_str().unwrap().starts_with("users/1.0 "));
+    assert_eq!(options.timeouts.total, Some(Duration::from_secs(5)));
+}
+
+#[test]
+fn test_builder_rejects_invalid_default_header() {
+    let result = UsersClient::builder()
+        .http_client(MockClient::default())
+        .default_header("X-Tenant", "line\nbreak")
+        .build();
+
+    assert!(matches!(result, Err(RestApiError::InvalidHeader { name, .. }) if name == "x-tenant"));
+}
+
+#[test]
+fn test_retry_policy_retries_idempotent_requests() {
+    let http = MockClient::default();
+    let client = UsersClient::builder()
+        .http_client(http.clone())
+        .retry_policy(RetryPolicy::new(2).backoff(Duration::from_millis(1), Duration::from_millis(5)))
+        .build()
+        .unwrap();
+
+    http.statuses.lock().unwrap().extend([StatusCode::SERVICE_UNAVAILABLE, StatusCode::BAD_GATEWAY]);
+    let response = client.get_user().id(1).send_raw().unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(http.urls().len(), 3);
+
+    // POST isn't retried unless the policy allows it
+    http.statuses.lock().unwrap().push_back(StatusCode::SERVICE_UNAVAILABLE);
+    let response = client.create_user().name("Ada".to_string()).send_raw().unwrap();
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(http.urls().len(), 4);
+}
+
+#[test]
+fn test_retry_budget_caps_retries_across_requests() {
+    let http = MockClient::default();
+    let budget = RetryBudget::new(3, Duration::from_secs(60));
+    let client = UsersClient::builder()
+        .http_client(http.clone())
+        .retry_policy(RetryPolicy::new(2).backoff(Duration::from_millis(1), Duration::from_millis(1)))
+        .retry_budget(budget.clone())
+        .build()
+        .unwrap();
+
+    // The first request retries twice, leaving one retry for the second
+    http.statuses.lock().unwrap().extend([StatusCode::SERVICE_UNAVAILABLE; 6]);
+    let response = client.get_user().id(1).send_raw().unwrap();
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    let response = client.get_user().id(2).send_raw().unwrap();
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(http.urls().len(), 5);
+    assert_eq!(budget.remaining(), 0);
+
+    // Once spent, failures are returned on the first attempt
+    let response = client.get_user().id(3).send_raw().unwrap();
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(http.urls().len(), 6);
+}
+
+#[test]
+fn test_middleware_wraps_each_attempt() {
+    let http = MockClient::default();
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let client = UsersClient::builder()
+        .http_client(http.clone())
+        .retry_policy(RetryPolicy::new(1).backoff(Duration::from_millis(1), Duration::from_millis(1)))
+        .middleware(Trace { name: "outer", log: log.clone() })
+        .middleware(Trace { name: "inner", log: log.clone() })
+        .build()
+        .unwrap();
+
+    http.statuses.lock().unwrap().push_back(StatusCode::TOO_MANY_REQUESTS);
+    client.get_user().id(1).send_raw().unwrap();
+
+    assert_eq!(
+        *log.lock().unwrap(),
+        [
+            "outer request",
+            "inner request",
+            "inner response 429",
+            "outer response 429",
+            "outer request",
+            "inner request",
+            "inner response 200",
+            "outer response 200",
+        ]
+    );
+    let (_, headers, _) = http.requests.lock().unwrap().pop().unwrap();
+    assert_eq!(headers["X-Trace"], "inner");
+}
+
+#[tokio::test]
+async fn test_async_builder_retries() {
+    let http = MockClient::default();
+    let client = UsersAsyncClient::builder()
+        .http_client(http.clone())
+        .default_header("X-Tenant", "acme")
+        .retry_policy(RetryPolicy::new(3).backoff(Duration::from_millis(1), Duration::from_millis(5)))
+        .build()
+        .unwrap();
+
+    http.statuses.lock().unwrap().push_back(StatusCode::GATEWAY_TIMEOUT);
+    let response = client.get_user().id(7).send_raw_async().await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(http.urls(), ["GET https://api.example.com/users/7", "GET https://api.example.com/users/7"]);
+    let (_, headers, _) = http.requests.lock().unwrap().pop().unwrap();
+    assert_eq!(headers["X-Tenant"], "acme");
+}
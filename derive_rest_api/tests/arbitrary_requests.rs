@@ -0,0 +1,75 @@
+#[cfg(feature = "arbitrary")]
+mod arbitrary_tests {
+    use arbitrary::{Arbitrary, Unstructured};
+    use derive_rest_api::RequestBuilder;
+
+    fn non_empty(value: &str) -> Result<(), String> {
+        if value.is_empty() { Err("must not be empty".to_string()) } else { Ok(()) }
+    }
+
+    #[derive(Debug, RequestBuilder)]
+    #[request_builder(method = "POST", path = "/repos/{owner}/{repo}/issues", arbitrary)]
+    struct CreateIssue {
+        #[request_builder(validate = "non_empty")]
+        owner: String,
+        repo: String,
+        #[request_builder(query)]
+        labels: Option<Vec<String>>,
+        #[request_builder(query)]
+        page: Option<u32>,
+        #[request_builder(body)]
+        title: String,
+        #[request_builder(body)]
+        draft: bool,
+    }
+
+    /// Deterministic pseudo-random bytes, so the test needs no fuzzer
+    fn bytes(seed: u64, len: usize) -> Vec<u8> {
+        let mut state = seed;
+        (0..len)
+            .map(|_| {
+                state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                (state >> 33) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_arbitrary_requests_build_urls_and_bodies() {
+        let mut built = 0;
+        for seed in 0..200 {
+            let data = bytes(seed, 256);
+            let Ok(request) = CreateIssue::arbitrary(&mut Unstructured::new(&data)) else {
+                continue;
+            };
+            built += 1;
+
+            // Only requests that pass build() are generated
+            assert!(!request.owner.is_empty());
+            let url = request.build_url().unwrap();
+            assert!(url.starts_with("/repos/"), "{}", url);
+            let body: serde_json::Value = serde_json::from_slice(&request.build_body().unwrap().unwrap()).unwrap();
+            assert_eq!(body["title"], request.title.as_str());
+            assert_eq!(body["draft"], request.draft);
+        }
+        assert!(built > 0);
+    }
+
+    #[test]
+    fn test_arbitrary_builder_sets_required_fields() {
+        let data = bytes(7, 256);
+        let builder = CreateIssueBuilder::arbitrary(&mut Unstructured::new(&data)).unwrap();
+
+        // Every required field has a value, so only validation can fail build()
+        assert!(builder.owner("octocat".to_string()).build().is_ok());
+    }
+
+    #[test]
+    fn test_invalid_input_is_rejected() {
+        // With no input, every string is empty, so the owner fails validation
+        assert!(matches!(
+            CreateIssue::arbitrary(&mut Unstructured::new(&[])),
+            Err(arbitrary::Error::IncorrectFormat)
+        ));
+    }
+}
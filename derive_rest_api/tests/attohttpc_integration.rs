@@ -0,0 +1,52 @@
+#[cfg(feature = "attohttpc-blocking")]
+mod attohttpc_tests {
+    use derive_rest_api::{AttohttpcClient, RequestBuilder};
+    use serde::Serialize;
+
+    #[test]
+    fn test_attohttpc_client_creation() {
+        let client = AttohttpcClient::new();
+        drop(client);
+
+        let client_default = AttohttpcClient::default();
+        drop(client_default);
+    }
+
+    #[test]
+    fn test_attohttpc_with_custom_session() {
+        let mut session = attohttpc::Session::new();
+        session.timeout(std::time::Duration::from_secs(30));
+
+        let client = AttohttpcClient::with_session(session);
+        drop(client);
+    }
+
+    #[test]
+    fn test_attohttpc_inner_session() {
+        let mut client = AttohttpcClient::new();
+        client.inner_mut().header("X-Custom", "value");
+
+        let session = client.into_inner();
+        drop(session);
+    }
+
+    #[test]
+    #[ignore] // Requires network connection
+    fn test_attohttpc_real_request() {
+        #[derive(RequestBuilder, Serialize)]
+        #[request_builder(method = "GET", path = "/users/{id}")]
+        struct GetUser {
+            id: u64,
+        }
+
+        let request = GetUserBuilder::new().id(1).build().unwrap();
+        let client = AttohttpcClient::new();
+
+        // Using JSONPlaceholder API for testing
+        let result = request.send_with_client(&client, "https://jsonplaceholder.typicode.com");
+
+        if let Ok(response) = result {
+            assert!(!response.is_empty());
+        }
+    }
+}
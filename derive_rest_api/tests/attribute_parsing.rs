@@ -6,7 +6,6 @@ use derive_rest_api::RequestBuilder;
 fn test_method_attribute() {
     #[derive(RequestBuilder)]
     #[request_builder(method = "GET")]
-    #[expect(unused)]
     struct GetUsers {
         limit: Option<u32>,
     }
@@ -54,9 +53,10 @@ fn test_combined_struct_attributes() {
         path = "/api/posts",
         response = String
     )]
-    #[expect(unused)]
     struct CreatePost {
+        #[request_builder(body)]
         title: String,
+        #[request_builder(body)]
         content: String,
     }
 
@@ -72,7 +72,6 @@ fn test_combined_struct_attributes() {
 #[allow(dead_code)]
 fn test_field_path_attribute() {
     #[derive(RequestBuilder)]
-    #[expect(unused)]
     struct GetUser {
         #[request_builder(path)]
         id: u64,
@@ -87,7 +86,6 @@ fn test_field_path_attribute() {
 #[allow(dead_code)]
 fn test_field_query_attribute() {
     #[derive(RequestBuilder)]
-    #[expect(unused)]
     struct SearchUsers {
         #[request_builder(query)]
         name: String,
@@ -119,7 +117,6 @@ fn test_field_body_attribute() {
 #[allow(dead_code)]
 fn test_field_header_attribute() {
     #[derive(RequestBuilder)]
-    #[expect(unused)]
     struct AuthenticatedRequest {
         #[request_builder(header)]
         authorization: String,
@@ -211,7 +208,7 @@ fn test_default_with_optional_field() {
         .build()
         .unwrap();
 
-    assert_eq!(settings.enabled, false); // Default for bool
+    assert!(!settings.enabled); // Default for bool
     assert_eq!(settings.description, Some("test".to_string()));
 }
 
@@ -255,7 +252,7 @@ fn test_struct_level_default_with_overrides() {
         .unwrap();
 
     assert_eq!(config.name, "MyApp");
-    assert_eq!(config.debug, true);
+    assert!(config.debug);
     assert_eq!(config.max_connections, 0); // Still uses default
 }
 
@@ -275,7 +272,7 @@ fn test_struct_default_with_field_override() {
         .unwrap();
 
     assert_eq!(config.name, "test");
-    assert_eq!(config.enabled, false); // Default
+    assert!(!config.enabled); // Default
 }
 
 #[test]
@@ -356,7 +353,97 @@ fn test_mixing_default_and_custom_default() {
         .build()
         .unwrap();
 
-    assert_eq!(config.flag, false);         // Default::default()
+    assert!(!config.flag);                  // Default::default()
     assert_eq!(config.count, 100);          // Custom value
     assert_eq!(config.name, "test");        // Custom expression
 }
+
+#[test]
+fn test_skip_attribute() {
+    #[derive(RequestBuilder, Debug, PartialEq)]
+    struct Config {
+        name: String,
+        #[request_builder(skip)]
+        attempts: u32,
+        #[request_builder(skip, default = "internal".to_string())]
+        source: String,
+    }
+
+    let config = ConfigBuilder::new()
+        .name("job".to_string())
+        .build()
+        .unwrap();
+
+    assert_eq!(config.name, "job");
+    assert_eq!(config.attempts, 0);                // Default::default()
+    assert_eq!(config.source, "internal");         // Custom expression
+}
+
+#[test]
+fn test_skip_attribute_excluded_from_builder() {
+    #[derive(RequestBuilder)]
+    #[expect(unused)]
+    struct Config {
+        name: String,
+        #[request_builder(skip)]
+        attempts: u32,
+    }
+
+    // The builder has no storage for skipped fields
+    let _builder: ConfigBuilder<(), ()> = ConfigBuilder {
+        name: Some("job".to_string()),
+        __http_client: None,
+        __async_http_client: None,
+        __base_url: None,
+        __dynamic_headers: Default::default(),
+        __options: Default::default(),
+        __cancellation: None,
+        __rate_limit: None,
+        __unconfigured: None,
+        __transport: Default::default(),
+    };
+}
+
+#[test]
+fn test_computed_attribute_with_expression() {
+    #[derive(RequestBuilder, Debug, PartialEq)]
+    struct Upload {
+        content: String,
+        #[request_builder(computed = "content.len() as u64")]
+        content_length: u64,
+        #[request_builder(computed = "format!(\"{}:{}\", content, content_length)")]
+        digest_input: String,
+    }
+
+    let upload = UploadBuilder::new()
+        .content("hello".to_string())
+        .build()
+        .unwrap();
+
+    assert_eq!(upload.content_length, 5);
+    assert_eq!(upload.digest_input, "hello:5");      // Sees earlier computed fields
+}
+
+#[test]
+fn test_computed_attribute_with_function() {
+    fn sign(request: &SignedRequest) -> String {
+        format!("{}-{}", request.key, request.payload.len())
+    }
+
+    #[derive(RequestBuilder, Debug, PartialEq)]
+    struct SignedRequest {
+        key: String,
+        #[request_builder(computed = "sign")]
+        signature: String,
+        payload: Vec<u8>,
+    }
+
+    let request = SignedRequestBuilder::new()
+        .key("abc".to_string())
+        .payload(vec![1, 2, 3])
+        .build()
+        .unwrap();
+
+    // Functions see the struct with every other field set, regardless of order
+    assert_eq!(request.signature, "abc-3");
+}
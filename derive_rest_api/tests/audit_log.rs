@@ -0,0 +1,125 @@
+use derive_rest_api::http::{HeaderMap, Response, StatusCode};
+use derive_rest_api::{ApiClient, AuditRecord, HttpClient, MockClient, RequestBuilder, RequestOptions};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Deserialize)]
+pub struct User {
+    pub id: u64,
+}
+
+#[derive(RequestBuilder)]
+#[request_builder(method = "GET", path = "/users/{id}", response = User)]
+struct GetUser {
+    id: u64,
+}
+
+#[derive(RequestBuilder, Serialize)]
+#[request_builder(method = "POST", path = "/users", response = User)]
+struct CreateUser {
+    #[request_builder(body)]
+    name: String,
+}
+
+#[derive(RequestBuilder)]
+#[request_builder(method = "DELETE", path = "/users/{id}")]
+struct DeleteUser {
+    id: u64,
+}
+
+#[derive(Clone, ApiClient)]
+#[api_client(base_url = "https://api.example.com", requests(GetUser, CreateUser, DeleteUser))]
+struct UsersConfig;
+
+/// An HTTP client that can't connect
+#[derive(Clone, Default)]
+struct Unreachable;
+
+impl HttpClient for Unreachable {
+    type Error = std::io::Error;
+
+    fn send(
+        &self,
+        _method: &str,
+        _url: &str,
+        _headers: HeaderMap,
+        _body: Option<Vec<u8>>,
+        _options: RequestOptions,
+    ) -> Result<Response<Vec<u8>>, Self::Error> {
+        Err(std::io::Error::new(std::io::ErrorKind::ConnectionRefused, "connection refused"))
+    }
+}
+
+/// A sink keeping every record
+fn recording_sink() -> (Arc<Mutex<Vec<AuditRecord>>>, impl Fn(&AuditRecord) + Send + Sync + 'static) {
+    let recorded = Arc::new(Mutex::new(Vec::new()));
+    let sink = {
+        let recorded = recorded.clone();
+        move |record: &AuditRecord| recorded.lock().unwrap().push(record.clone())
+    };
+    (recorded, sink)
+}
+
+#[test]
+fn test_writes_are_audited_and_reads_are_not() {
+    let http = MockClient::new();
+    http.push_json(StatusCode::OK, &serde_json::json!({"id": 1}));
+    http.push_json(StatusCode::CREATED, &serde_json::json!({"id": 2}));
+    let (recorded, sink) = recording_sink();
+    let client = UsersClient::builder()
+        .http_client(http)
+        .audit(sink)
+        .audit_actor("billing-service")
+        .build()
+        .unwrap();
+
+    client.get_user().id(1).send().unwrap();
+    client.create_user().name("Alice".to_string()).send().unwrap();
+
+    assert_eq!(
+        *recorded.lock().unwrap(),
+        [AuditRecord {
+            endpoint: "CreateUser",
+            method: "POST".to_string(),
+            url: "https://api.example.com/users".to_string(),
+            actor: Some("billing-service".to_string()),
+            body_digest: Some("sha256:3cba1e3cf23c8ce24b7e08171d823fbd9a4929aafd9f27516e30699d3a42026a".to_string()),
+            status: Some(StatusCode::CREATED),
+        }]
+    );
+}
+
+#[test]
+fn test_requests_override_the_actor_and_failures_are_audited() {
+    let (recorded, sink) = recording_sink();
+    let client = UsersClient::builder()
+        .http_client(Unreachable)
+        .audit(sink)
+        .audit_actor("billing-service")
+        .build()
+        .unwrap();
+
+    client.delete_user().id(7).audit_actor("alice@example.com").send().unwrap_err();
+
+    let recorded = recorded.lock().unwrap();
+    assert_eq!(recorded.len(), 1);
+    assert_eq!(recorded[0].url, "https://api.example.com/users/7");
+    assert_eq!(recorded[0].actor.as_deref(), Some("alice@example.com"));
+    assert_eq!(recorded[0].body_digest, None);
+    assert_eq!(recorded[0].status, None);
+}
+
+#[tokio::test]
+async fn test_async_writes_are_audited() {
+    let http = MockClient::new();
+    http.push_json(StatusCode::NO_CONTENT, &serde_json::json!({}));
+    let (recorded, sink) = recording_sink();
+    let client = UsersAsyncClient::builder().http_client(http).audit(sink).build().unwrap();
+
+    client.delete_user().id(3).send_async().await.unwrap();
+
+    let recorded = recorded.lock().unwrap();
+    assert_eq!(recorded.len(), 1);
+    assert_eq!((recorded[0].endpoint, recorded[0].method.as_str()), ("DeleteUser", "DELETE"));
+    assert_eq!(recorded[0].actor, None);
+}
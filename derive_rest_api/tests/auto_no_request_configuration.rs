@@ -22,11 +22,11 @@ impl derive_rest_api::HttpClient for MockClient {
         &self,
         _method: &str,
         _url: &str,
-        _headers: std::collections::HashMap<String, String>,
+        _headers: derive_rest_api::http::HeaderMap,
         _body: Option<Vec<u8>>,
-        _timeout: Option<std::time::Duration>,
-    ) -> Result<Vec<u8>, Self::Error> {
-        Ok(vec![])
+        _options: derive_rest_api::RequestOptions,
+    ) -> Result<derive_rest_api::http::Response<Vec<u8>>, Self::Error> {
+        Ok(derive_rest_api::http::Response::new(vec![]))
     }
 }
 
@@ -0,0 +1,111 @@
+use derive_rest_api::http::{Response, StatusCode};
+use derive_rest_api::{ApiClient, BackgroundSender, ManualClock, MockClient, RequestBuilder, RestApiError};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[derive(RequestBuilder)]
+#[request_builder(method = "POST", path = "/events", response = ())]
+struct TrackEvent {
+    #[request_builder(body)]
+    name: String,
+}
+
+#[derive(Clone, ApiClient)]
+#[api_client(base_url = "https://api.example.com", requests(TrackEvent))]
+struct AnalyticsConfig;
+
+type Workers = Arc<Mutex<Vec<Pin<Box<dyn Future<Output = ()> + Send>>>>>;
+
+/// A sender whose worker is kept in `workers` instead of being spawned
+fn sender(workers: &Workers) -> BackgroundSender {
+    let workers = workers.clone();
+    BackgroundSender::new(move |worker| workers.lock().unwrap().push(worker))
+}
+
+fn client(http: &MockClient, sender: BackgroundSender) -> AnalyticsAsyncClient<MockClient> {
+    AnalyticsAsyncClient::builder().http_client(http.clone()).background_sender(sender).build().unwrap()
+}
+
+fn track(client: &AnalyticsAsyncClient<MockClient>, name: &str) -> Result<(), RestApiError> {
+    client.track_event().name(name.to_string()).send_background()
+}
+
+#[tokio::test]
+async fn test_requests_are_sent_in_order_by_the_worker() {
+    let http = MockClient::new();
+    let workers = Workers::default();
+    let client = client(&http, sender(&workers));
+
+    track(&client, "signup").unwrap();
+    track(&client, "login").unwrap();
+    // Nothing is sent until the worker runs
+    assert!(http.requests().is_empty());
+    assert_eq!(workers.lock().unwrap().len(), 1);
+
+    // The worker ends once the client is gone and the queue is empty
+    drop(client);
+    let worker = workers.lock().unwrap().pop().unwrap();
+    worker.await;
+
+    let bodies: Vec<_> = http.requests().iter().map(|request| request.body_text().unwrap().to_string()).collect();
+    assert_eq!(bodies, [r#"{"name":"signup"}"#, r#"{"name":"login"}"#]);
+}
+
+#[tokio::test]
+async fn test_failed_requests_are_retried() {
+    let http = MockClient::new();
+    let workers = Workers::default();
+    let clock = ManualClock::new();
+    let sender = sender(&workers).max_retries(2).backoff(Duration::from_secs(1)).clock(clock.clone());
+    let client = client(&http, sender);
+    for status in [StatusCode::SERVICE_UNAVAILABLE, StatusCode::TOO_MANY_REQUESTS, StatusCode::SERVICE_UNAVAILABLE] {
+        http.push_response(Response::builder().status(status).body(Vec::new()).unwrap());
+    }
+    http.push_response(Response::builder().status(StatusCode::BAD_REQUEST).body(Vec::new()).unwrap());
+
+    track(&client, "gives up").unwrap();
+    track(&client, "rejected").unwrap();
+    drop(client);
+    let worker = workers.lock().unwrap().pop().unwrap();
+    worker.await;
+
+    // Three tries with 1s then 2s of backoff, then a 400 isn't retried
+    assert_eq!(http.requests().len(), 4);
+    assert_eq!(clock.elapsed(), Duration::from_secs(3));
+}
+
+#[tokio::test]
+async fn test_full_queue_and_missing_sender() {
+    let http = MockClient::new();
+    let workers = Workers::default();
+    let sender = sender(&workers).capacity(2);
+    let client = client(&http, sender.clone());
+
+    track(&client, "a").unwrap();
+    track(&client, "b").unwrap();
+    assert!(matches!(track(&client, "c"), Err(RestApiError::BackgroundQueueFull { capacity: 2 })));
+    assert_eq!(sender.pending(), 2);
+
+    let plain = AnalyticsAsyncClient::builder().http_client(http.clone()).build().unwrap();
+    let error = plain.track_event().name("d".to_string()).send_background().unwrap_err();
+    assert!(matches!(error, RestApiError::NoBackgroundSender));
+}
+
+#[tokio::test]
+async fn test_worker_on_a_runtime() {
+    let http = MockClient::new();
+    let sender = BackgroundSender::new(|worker| {
+        tokio::spawn(worker);
+    });
+    let client = client(&http, sender.clone());
+
+    for name in ["a", "b", "c"] {
+        track(&client, name).unwrap();
+    }
+    while sender.pending() > 0 {
+        tokio::task::yield_now().await;
+    }
+    assert_eq!(http.requests().len(), 3);
+}
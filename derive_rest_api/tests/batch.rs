@@ -0,0 +1,271 @@
+use derive_rest_api::http::{HeaderMap, Response, StatusCode};
+use derive_rest_api::{Batch, RequestBuilder, RequestEnum, RestApiError};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug)]
+struct MockError;
+
+impl std::fmt::Display for MockError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "MockError")
+    }
+}
+
+impl std::error::Error for MockError {}
+
+// Mock OData service that records the batch request and returns a canned multipart response
+#[derive(Clone, Default)]
+struct MockService {
+    content_type: &'static str,
+    response: &'static str,
+    sent: Arc<Mutex<Option<(String, HeaderMap, String)>>>,
+}
+
+impl MockService {
+    fn new(response: &'static str) -> Self {
+        Self {
+            content_type: "multipart/mixed; boundary=batchresponse_1",
+            response,
+            ..Default::default()
+        }
+    }
+
+    fn respond(&self, url: &str, headers: HeaderMap, body: Option<Vec<u8>>) -> Response<Vec<u8>> {
+        let body = String::from_utf8(body.unwrap()).unwrap();
+        *self.sent.lock().unwrap() = Some((url.to_string(), headers, body));
+        let mut response = Response::new(self.response.replace('\n', "\r\n").into_bytes());
+        response
+            .headers_mut()
+            .insert("Content-Type", self.content_type.parse().unwrap());
+        response
+    }
+}
+
+impl derive_rest_api::HttpClient for MockService {
+    type Error = MockError;
+
+    fn send(
+        &self,
+        _method: &str,
+        url: &str,
+        headers: HeaderMap,
+        body: Option<Vec<u8>>,
+        _options: derive_rest_api::RequestOptions,
+    ) -> Result<Response<Vec<u8>>, Self::Error> {
+        Ok(self.respond(url, headers, body))
+    }
+}
+
+impl derive_rest_api::AsyncHttpClient for MockService {
+    type Error = MockError;
+
+    async fn send_async(
+        &self,
+        _method: &str,
+        url: &str,
+        headers: HeaderMap,
+        body: Option<Vec<u8>>,
+        _options: derive_rest_api::RequestOptions,
+    ) -> Result<Response<Vec<u8>>, Self::Error> {
+        Ok(self.respond(url, headers, body))
+    }
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Customer {
+    id: String,
+    name: String,
+}
+
+#[derive(RequestBuilder)]
+#[request_builder(method = "GET", path = "/Customers/{id}", response = Customer)]
+struct GetCustomer {
+    id: String,
+}
+
+#[derive(RequestBuilder, Serialize)]
+#[request_builder(method = "POST", path = "/Customers", response = Customer)]
+struct CreateCustomer {
+    #[request_builder(body)]
+    name: String,
+}
+
+#[derive(RequestEnum)]
+enum Job {
+    #[request_builder(method = "DELETE", path = "/Customers/{id}")]
+    DeleteCustomer { id: String },
+    GetCustomer(GetCustomer),
+}
+
+const RESPONSE: &str = "--batchresponse_1
+Content-Type: application/http
+Content-Transfer-Encoding: binary
+
+HTTP/1.1 200 OK
+Content-Type: application/json
+
+{\"id\":\"ALFKI\",\"name\":\"Alfreds\"}
+--batchresponse_1
+Content-Type: multipart/mixed; boundary=changesetresponse_1
+
+--changesetresponse_1
+Content-Type: application/http
+Content-Transfer-Encoding: binary
+Content-ID: 3
+
+HTTP/1.1 404 Not Found
+Content-Type: application/json
+
+{\"error\":\"no such customer\"}
+--changesetresponse_1
+Content-Type: application/http
+Content-Transfer-Encoding: binary
+Content-ID: 2
+
+HTTP/1.1 201 Created
+Content-Type: application/json
+Location: https://example.com/odata/Customers('NEW')
+
+{\"id\":\"NEW\",\"name\":\"Newcomer\"}
+--changesetresponse_1--
+--batchresponse_1--
+";
+
+const SINGLE_RESPONSE: &str = "--batchresponse_1
+Content-Type: application/http
+
+HTTP/1.1 200 OK
+
+{\"id\":\"ALFKI\",\"name\":\"Alfreds\"}
+--batchresponse_1
+Content-Type: application/http
+
+HTTP/1.1 204 No Content
+
+--batchresponse_1--
+";
+
+fn get_customer(id: &str) -> GetCustomer {
+    GetCustomerBuilder::new().id(id.to_string()).build().unwrap()
+}
+
+#[test]
+fn test_encodes_requests_as_multipart() {
+    let service = MockService::new(SINGLE_RESPONSE);
+    let mut batch = Batch::new();
+    batch.add(&get_customer("ALFKI")).unwrap();
+    batch
+        .add(&CreateCustomerBuilder::new().name("Newcomer".to_string()).build().unwrap())
+        .unwrap();
+    assert_eq!(batch.len(), 2);
+    batch.send(&service, "https://example.com/odata").unwrap();
+
+    let (url, headers, body) = service.sent.lock().unwrap().take().unwrap();
+    assert_eq!(url, "https://example.com/odata/$batch");
+    let content_type = headers.get("Content-Type").unwrap().to_str().unwrap();
+    let boundary = content_type.strip_prefix("multipart/mixed; boundary=").unwrap();
+
+    let expected = format!(
+        "--{b}\r\n\
+         Content-Type: application/http\r\n\
+         Content-Transfer-Encoding: binary\r\n\
+         Content-ID: 1\r\n\
+         \r\n\
+         GET https://example.com/odata/Customers/ALFKI HTTP/1.1\r\n\
+         accept: application/json\r\n\
+         \r\n\
+         \r\n\
+         --{b}\r\n\
+         Content-Type: application/http\r\n\
+         Content-Transfer-Encoding: binary\r\n\
+         Content-ID: 2\r\n\
+         \r\n\
+         POST https://example.com/odata/Customers HTTP/1.1\r\n\
+         content-type: application/json\r\n\
+         accept: application/json\r\n\
+         \r\n\
+         {{\"name\":\"Newcomer\"}}\r\n\
+         --{b}--\r\n",
+        b = boundary
+    );
+    assert_eq!(body, expected);
+}
+
+#[test]
+fn test_demultiplexes_typed_results() {
+    let service = MockService::new(RESPONSE);
+    let mut batch = Batch::new().path("/batch");
+    let existing = batch.add(&get_customer("ALFKI")).unwrap();
+    let created = batch
+        .add(&CreateCustomerBuilder::new().name("Newcomer".to_string()).build().unwrap())
+        .unwrap();
+    let missing = batch.add(&get_customer("NOPE")).unwrap();
+
+    let mut responses = batch.send(&service, "https://example.com/odata").unwrap();
+    assert_eq!(
+        responses.take(existing).unwrap(),
+        Customer { id: "ALFKI".to_string(), name: "Alfreds".to_string() }
+    );
+
+    // Changeset parts are matched by Content-ID rather than position
+    let response = responses.response(created).unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+    assert_eq!(response.headers()["Location"], "https://example.com/odata/Customers('NEW')");
+    assert_eq!(responses.take(created).unwrap().id, "NEW");
+
+    assert_eq!(responses.response(missing).unwrap().status(), StatusCode::NOT_FOUND);
+    assert!(matches!(
+        responses.take(missing),
+        Err(RestApiError::ResponseDeserializationError { .. })
+    ));
+
+    // Each response can only be taken once
+    assert!(matches!(
+        responses.take(existing),
+        Err(RestApiError::InvalidBatchResponse { .. })
+    ));
+}
+
+#[test]
+fn test_enum_requests_return_raw_bodies() {
+    let service = MockService::new(SINGLE_RESPONSE);
+    let mut batch = Batch::new();
+    let first = batch.add(&Job::GetCustomer(get_customer("ALFKI"))).unwrap();
+    let second = batch.add(&Job::DeleteCustomer { id: "OLD".to_string() }).unwrap();
+
+    let mut responses = batch.send(&service, "https://example.com/odata").unwrap();
+    assert_eq!(responses.take(first).unwrap(), br#"{"id":"ALFKI","name":"Alfreds"}"#);
+    assert_eq!(responses.take(second).unwrap(), b"");
+
+    let (_, _, body) = service.sent.lock().unwrap().take().unwrap();
+    assert!(body.contains("DELETE https://example.com/odata/Customers/OLD HTTP/1.1\r\n"));
+}
+
+#[test]
+fn test_non_multipart_response_is_an_error() {
+    let service = MockService {
+        content_type: "application/json",
+        ..MockService::new(r#"{"error":"batching disabled"}"#)
+    };
+    let mut batch = Batch::new();
+    batch.add(&get_customer("ALFKI")).unwrap();
+
+    match batch.send(&service, "https://example.com/odata") {
+        Err(RestApiError::InvalidBatchResponse { message }) => {
+            assert!(message.contains("application/json"));
+        }
+        other => panic!("Expected InvalidBatchResponse, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_send_async() {
+    let service = MockService::new(SINGLE_RESPONSE);
+    let mut batch = Batch::new();
+    let existing = batch.add(&get_customer("ALFKI")).unwrap();
+    batch.add(&get_customer("GONE")).unwrap();
+
+    let mut responses = batch.send_async(&service, "https://example.com/odata").await.unwrap();
+    assert_eq!(responses.take(existing).unwrap().name, "Alfreds");
+}
@@ -0,0 +1,126 @@
+use derive_rest_api::{ApiClient, RequestBuilder};
+use serde::Serialize;
+use std::borrow::Cow;
+
+#[test]
+fn test_borrowed_str_fields() {
+    #[derive(RequestBuilder, Serialize)]
+    #[request_builder(method = "PUT", path = "/repos/{owner}/files")]
+    struct UploadFile<'a> {
+        owner: &'a str,
+        #[request_builder(query)]
+        branch: Option<&'a str>,
+        #[request_builder(body)]
+        content: &'a str,
+        #[request_builder(header = "X-Request-Id")]
+        request_id: &'a str,
+    }
+
+    let owner = String::from("rust-lang");
+    let content = "x".repeat(1024);
+
+    let request = UploadFileBuilder::new()
+        .owner(&owner)
+        .branch("main")
+        .content(&content)
+        .request_id("abc-123")
+        .build()
+        .unwrap();
+
+    assert_eq!(request.build_url().unwrap(), "/repos/rust-lang/files?branch=main");
+
+    let body: serde_json::Value = serde_json::from_slice(&request.build_body().unwrap().unwrap()).unwrap();
+    assert_eq!(body["content"].as_str().unwrap().len(), 1024);
+
+    let headers = request.build_headers().unwrap();
+    assert_eq!(headers.get("X-Request-Id").unwrap(), "abc-123");
+}
+
+#[test]
+fn test_cow_fields() {
+    #[derive(RequestBuilder, Serialize)]
+    #[request_builder(method = "POST", path = "/posts/{slug}")]
+    struct CreatePost<'a> {
+        slug: Cow<'a, str>,
+        #[request_builder(body)]
+        title: Cow<'a, str>,
+        #[request_builder(body)]
+        #[serde(rename = "bodyText")]
+        body_text: Option<Cow<'a, str>>,
+    }
+
+    let title = String::from("Borrowed title");
+
+    let request = CreatePostBuilder::new()
+        .slug(Cow::Owned("hello-world".to_string()))
+        .title(Cow::Borrowed(title.as_str()))
+        .body_text(Cow::Borrowed("Some text"))
+        .build()
+        .unwrap();
+
+    assert_eq!(request.build_url().unwrap(), "/posts/hello-world");
+
+    let body: serde_json::Value = serde_json::from_slice(&request.build_body().unwrap().unwrap()).unwrap();
+    assert_eq!(body["title"], "Borrowed title");
+    assert_eq!(body["bodyText"], "Some text");
+}
+
+#[test]
+fn test_borrowed_fields_with_into() {
+    #[derive(RequestBuilder, Serialize)]
+    #[request_builder(method = "GET", path = "/search", into)]
+    struct Search<'a> {
+        #[request_builder(query)]
+        q: Cow<'a, str>,
+        #[request_builder(query)]
+        page: Option<u32>,
+    }
+
+    let request = SearchBuilder::new()
+        .q("rust")
+        .page(2u32)
+        .build()
+        .unwrap();
+
+    assert_eq!(request.build_url().unwrap(), "/search?q=rust&page=2");
+}
+
+#[test]
+fn test_borrowed_fields_unused_lifetime_in_body() {
+    // The lifetime is only used by a path field, so the body struct must still compile
+    #[derive(RequestBuilder, Serialize)]
+    #[request_builder(method = "POST", path = "/users/{name}/posts")]
+    struct CreateUserPost<'a> {
+        name: &'a str,
+        #[request_builder(body)]
+        title: String,
+    }
+
+    let request = CreateUserPostBuilder::new()
+        .name("alice")
+        .title("Hello".to_string())
+        .build()
+        .unwrap();
+
+    assert_eq!(request.build_url().unwrap(), "/users/alice/posts");
+    assert_eq!(request.build_body().unwrap().unwrap(), br#"{"title":"Hello"}"#);
+}
+
+#[derive(RequestBuilder, Serialize)]
+#[request_builder(method = "GET", path = "/files/{name}")]
+struct GetFile<'a> {
+    name: &'a str,
+}
+
+#[derive(Clone, ApiClient)]
+#[api_client(base_url = "https://files.example.com", requests(GetFile))]
+struct FilesApi;
+
+#[test]
+fn test_api_client_with_borrowed_request() {
+    let client = FilesApiClient::new();
+    let name = String::from("report.pdf");
+
+    let request = client.get_file().name(&name).build().unwrap();
+    assert_eq!(request.build_url().unwrap(), "/files/report.pdf");
+}
@@ -1,5 +1,5 @@
 use derive_rest_api::RequestBuilder;
-use std::collections::HashMap;
+use derive_rest_api::http::HeaderMap;
 
 // Mock error type for testing
 #[derive(Debug)]
@@ -24,11 +24,11 @@ impl derive_rest_api::HttpClient for MockHttpClient {
         &self,
         _method: &str,
         _url: &str,
-        _headers: HashMap<String, String>,
+        _headers: HeaderMap,
         _body: Option<Vec<u8>>,
-        _timeout: Option<std::time::Duration>,
-    ) -> Result<Vec<u8>, Self::Error> {
-        Ok(b"{\"id\":1}".to_vec())
+        _options: derive_rest_api::RequestOptions,
+    ) -> Result<derive_rest_api::http::Response<Vec<u8>>, Self::Error> {
+        Ok(derive_rest_api::http::Response::new(b"{\"id\":1}".to_vec()))
     }
 }
 
@@ -43,11 +43,11 @@ impl derive_rest_api::AsyncHttpClient for MockAsyncHttpClient {
         &self,
         _method: &str,
         _url: &str,
-        _headers: HashMap<String, String>,
+        _headers: HeaderMap,
         _body: Option<Vec<u8>>,
-        _timeout: Option<std::time::Duration>,
-    ) -> Result<Vec<u8>, Self::Error> {
-        Ok(b"{\"id\":1}".to_vec())
+        _options: derive_rest_api::RequestOptions,
+    ) -> Result<derive_rest_api::http::Response<Vec<u8>>, Self::Error> {
+        Ok(derive_rest_api::http::Response::new(b"{\"id\":1}".to_vec()))
     }
 }
 
@@ -203,3 +203,226 @@ fn test_send_requires_base_url() {
     assert!(result.is_err());
     assert!(result.unwrap_err().to_string().contains("No base URL configured"));
 }
+
+#[test]
+fn test_send_ref_reuses_builder() {
+    use derive_rest_api::RequestModifier;
+
+    let builder = GetUserBuilder::new()
+        .http_client(MockHttpClient)
+        .base_url("https://api.example.com")
+        .header("X-Trace", "1")
+        .id(123);
+
+    assert_eq!(builder.send_ref().unwrap(), b"{\"id\":1}");
+    assert_eq!(builder.send_ref().unwrap(), b"{\"id\":1}");
+
+    // The builder is untouched and can still be consumed
+    assert_eq!(builder.__base_url.as_deref(), Some("https://api.example.com"));
+    assert!(builder.send().is_ok());
+}
+
+#[tokio::test]
+async fn test_send_async_ref_reuses_builder() {
+    let builder = GetUserBuilder::new()
+        .async_http_client(MockAsyncHttpClient)
+        .base_url("https://api.example.com")
+        .id(789);
+
+    assert_eq!(builder.send_async_ref().await.unwrap(), b"{\"id\":1}");
+    assert_eq!(builder.send_async_ref().await.unwrap(), b"{\"id\":1}");
+}
+
+#[test]
+fn test_builder_with_non_clone_field() {
+    // A field type without Clone only opts the builder out of Clone
+    #[derive(serde::Serialize)]
+    struct Payload {
+        name: String,
+    }
+
+    #[derive(RequestBuilder)]
+    #[request_builder(method = "POST", path = "/users")]
+    struct CreateUser {
+        #[request_builder(body)]
+        payload: Payload,
+    }
+
+    let result = CreateUserBuilder::new()
+        .payload(Payload { name: "Alice".to_string() })
+        .http_client(MockHttpClient)
+        .base_url("https://api.example.com")
+        .send();
+    assert!(result.is_ok());
+}
+
+#[derive(Debug, serde::Deserialize, PartialEq)]
+struct User {
+    id: u64,
+    name: Option<String>,
+}
+
+#[derive(Debug, serde::Deserialize, PartialEq)]
+struct UserId {
+    id: u64,
+}
+
+#[derive(RequestBuilder)]
+#[request_builder(method = "GET", path = "/users/{id}", response = User)]
+struct GetTypedUser {
+    id: u64,
+    #[request_builder(query)]
+    fields: Option<String>,
+}
+
+#[derive(RequestBuilder)]
+#[request_builder(method = "DELETE", path = "/users/{id}", response = ())]
+struct DeleteUser {
+    id: u64,
+}
+
+#[tokio::test]
+async fn test_unit_response_discards_body() {
+    // The mock's JSON body isn't `()`, so this only succeeds if it isn't decoded
+    let builder = DeleteUserBuilder::new()
+        .http_client(MockHttpClient)
+        .async_http_client(MockAsyncHttpClient)
+        .base_url("https://api.example.com")
+        .id(1);
+    let () = builder.send_ref().unwrap();
+    let () = builder.send_async().await.unwrap();
+
+    // No JSON is expected back
+    let request = DeleteUserBuilder::new().id(1).build().unwrap();
+    assert!(!request.build_headers().unwrap().contains_key("Accept"));
+}
+
+#[test]
+fn test_send_as_overrides_response_type() {
+    let user = GetTypedUserBuilder::new()
+        .http_client(MockHttpClient)
+        .base_url("https://api.example.com")
+        .id(1)
+        .send()
+        .unwrap();
+    assert_eq!(user, User { id: 1, name: None });
+
+    // A sparse fieldset deserialized into a caller-chosen type
+    let user_id: UserId = GetTypedUserBuilder::new()
+        .http_client(MockHttpClient)
+        .base_url("https://api.example.com")
+        .id(1)
+        .fields("id".to_string())
+        .send_as()
+        .unwrap();
+    assert_eq!(user_id, UserId { id: 1 });
+
+    // Untyped requests can be decoded too
+    let value = GetUserBuilder::new()
+        .http_client(MockHttpClient)
+        .base_url("https://api.example.com")
+        .id(1)
+        .send_as::<serde_json::Value>()
+        .unwrap();
+    assert_eq!(value["id"], 1);
+}
+
+#[tokio::test]
+async fn test_send_as_async_reports_mismatched_type() {
+    let result = GetTypedUserBuilder::new()
+        .async_http_client(MockAsyncHttpClient)
+        .base_url("https://api.example.com")
+        .id(1)
+        .send_as_async::<Vec<String>>()
+        .await;
+    assert!(matches!(result, Err(derive_rest_api::RestApiError::ResponseDeserializationError { .. })));
+
+    let user_id: UserId = GetTypedUserBuilder::new()
+        .async_http_client(MockAsyncHttpClient)
+        .base_url("https://api.example.com")
+        .id(1)
+        .send_as_async()
+        .await
+        .unwrap();
+    assert_eq!(user_id.id, 1);
+}
+
+#[test]
+fn test_send_json_returns_untyped_value() {
+    let value = GetTypedUserBuilder::new()
+        .http_client(MockHttpClient)
+        .base_url("https://api.example.com")
+        .id(1)
+        .send_json()
+        .unwrap();
+    assert_eq!(value, serde_json::json!({ "id": 1 }));
+}
+
+#[tokio::test]
+async fn test_send_json_async_returns_untyped_value() {
+    let value = GetUserBuilder::new()
+        .async_http_client(MockAsyncHttpClient)
+        .base_url("https://api.example.com")
+        .id(1)
+        .send_json_async()
+        .await
+        .unwrap();
+    assert_eq!(value["id"], 1);
+}
+
+#[test]
+fn test_send_raw_returns_untouched_response() {
+    // Mock HTTP client that answers with a status, a header and pretty-printed JSON
+    #[derive(Clone, Default)]
+    struct ArchivedClient;
+
+    impl derive_rest_api::HttpClient for ArchivedClient {
+        type Error = MockError;
+
+        fn send(
+            &self,
+            _method: &str,
+            _url: &str,
+            _headers: HeaderMap,
+            _body: Option<Vec<u8>>,
+            _options: derive_rest_api::RequestOptions,
+        ) -> Result<derive_rest_api::http::Response<Vec<u8>>, Self::Error> {
+            let mut response = derive_rest_api::http::Response::new(b"{\n  \"id\": 1\n}".to_vec());
+            *response.status_mut() = derive_rest_api::http::StatusCode::ACCEPTED;
+            response.headers_mut().insert("X-Request-Id", "abc".parse().unwrap());
+            Ok(response)
+        }
+    }
+
+    let response = GetTypedUserBuilder::new()
+        .http_client(ArchivedClient)
+        .base_url("https://api.example.com")
+        .id(1)
+        .send_raw()
+        .unwrap();
+    assert_eq!(response.status(), 202);
+    assert_eq!(response.headers()["X-Request-Id"], "abc");
+    assert_eq!(response.body(), b"{\n  \"id\": 1\n}");
+
+    // The typed path still decodes the same payload
+    let user = GetTypedUserBuilder::new()
+        .http_client(ArchivedClient)
+        .base_url("https://api.example.com")
+        .id(1)
+        .send()
+        .unwrap();
+    assert_eq!(user.id, 1);
+}
+
+#[tokio::test]
+async fn test_send_raw_async_returns_response() {
+    let response = GetTypedUserBuilder::new()
+        .async_http_client(MockAsyncHttpClient)
+        .base_url("https://api.example.com")
+        .id(1)
+        .send_raw_async()
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+    assert_eq!(response.into_body(), b"{\"id\":1}");
+}
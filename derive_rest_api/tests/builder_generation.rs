@@ -15,8 +15,12 @@ fn test_builder_struct_exists() {
         __http_client: None,
         __async_http_client: None,
         __base_url: None,
-        __dynamic_headers: std::collections::HashMap::new(),
-        __timeout: None,
+        __dynamic_headers: Default::default(),
+        __options: Default::default(),
+        __cancellation: None,
+        __rate_limit: None,
+        __unconfigured: None,
+        __transport: Default::default(),
     };
 }
 
@@ -36,8 +40,12 @@ fn test_builder_with_optional_field() {
         __http_client: None,
         __async_http_client: None,
         __base_url: None,
-        __dynamic_headers: std::collections::HashMap::new(),
-        __timeout: None,
+        __dynamic_headers: Default::default(),
+        __options: Default::default(),
+        __cancellation: None,
+        __rate_limit: None,
+        __unconfigured: None,
+        __transport: Default::default(),
     };
 }
 
@@ -115,7 +123,7 @@ fn test_build_success() {
     assert!(result.is_ok());
     let user = result.unwrap();
     assert_eq!(user.id, 123);
-    assert_eq!(user.include_posts, true);
+    assert!(user.include_posts);
 }
 
 #[test]
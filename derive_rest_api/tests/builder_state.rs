@@ -0,0 +1,73 @@
+use derive_rest_api::RequestBuilder;
+
+#[derive(RequestBuilder, Debug)]
+#[request_builder(method = "POST", path = "/projects/{owner}")]
+struct CreateProject {
+    owner: String,
+    #[request_builder(body)]
+    name: String,
+    #[request_builder(body)]
+    description: Option<String>,
+    #[request_builder(body, default)]
+    private: bool,
+    #[request_builder(body, default = 30)]
+    retention_days: u32,
+    #[request_builder(skip)]
+    #[allow(dead_code)]
+    attempts: u32,
+}
+
+#[test]
+fn test_missing_required_fields_lists_unset_fields_in_order() {
+    let builder = CreateProjectBuilder::new();
+    assert_eq!(builder.missing_required_fields(), vec!["owner", "name"]);
+
+    let builder = builder.name("docs".to_string());
+    assert_eq!(builder.missing_required_fields(), vec!["owner"]);
+
+    let builder = builder.owner("ada".to_string());
+    assert!(builder.missing_required_fields().is_empty());
+    assert!(builder.build().is_ok());
+}
+
+#[test]
+fn test_missing_required_fields_matches_build_errors() {
+    let builder = CreateProjectBuilder::new().description("Project docs".to_string());
+    let missing = builder.missing_required_fields();
+
+    let errors = builder.try_build().unwrap_err();
+    let reported: Vec<String> = errors
+        .errors()
+        .iter()
+        .map(|error| match error {
+            derive_rest_api::RestApiError::MissingField { field } => field.clone(),
+            error => panic!("unexpected error: {error}"),
+        })
+        .collect();
+    assert_eq!(reported, missing);
+}
+
+#[test]
+fn test_is_set() {
+    let builder = CreateProjectBuilder::new().owner("ada".to_string()).private(true);
+
+    assert!(builder.is_set_owner());
+    assert!(builder.is_set_private());
+    assert!(!builder.is_set_name());
+    assert!(!builder.is_set_description());
+    assert!(!builder.is_set_retention_days());
+}
+
+#[test]
+fn test_struct_level_default_has_no_required_fields() {
+    #[derive(RequestBuilder)]
+    #[request_builder(default)]
+    struct Settings {
+        host: String,
+    }
+
+    let builder = SettingsBuilder::new();
+    assert!(builder.missing_required_fields().is_empty());
+    assert!(!builder.is_set_host());
+    assert_eq!(builder.build().unwrap().host, "");
+}
@@ -0,0 +1,154 @@
+use derive_rest_api::http::HeaderMap;
+use derive_rest_api::{CancellationToken, RequestBuilder, RestApiError};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+// Mock error type for testing
+#[derive(Debug)]
+struct MockError;
+
+impl std::fmt::Display for MockError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "MockError")
+    }
+}
+
+impl std::error::Error for MockError {}
+
+// Sets its flag when dropped, to observe that the in-flight request was abandoned
+struct DropFlag(Arc<AtomicBool>);
+
+impl Drop for DropFlag {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+}
+
+// Mock async HTTP client whose requests never complete
+#[derive(Clone, Default)]
+struct HangingClient {
+    dropped: Arc<AtomicBool>,
+}
+
+impl derive_rest_api::AsyncHttpClient for HangingClient {
+    type Error = MockError;
+
+    async fn send_async(
+        &self,
+        _method: &str,
+        _url: &str,
+        _headers: HeaderMap,
+        _body: Option<Vec<u8>>,
+        _options: derive_rest_api::RequestOptions,
+    ) -> Result<derive_rest_api::http::Response<Vec<u8>>, Self::Error> {
+        let _in_flight = DropFlag(self.dropped.clone());
+        std::future::pending().await
+    }
+}
+
+#[derive(RequestBuilder)]
+#[request_builder(method = "GET", path = "/reports/{id}")]
+struct GetReport {
+    id: u64,
+}
+
+#[test]
+fn test_token_cancel_is_shared_between_clones() {
+    let token = CancellationToken::new();
+    let clone = token.clone();
+
+    assert!(!token.is_cancelled());
+    clone.cancel();
+    assert!(token.is_cancelled());
+
+    // Cancelling twice is harmless
+    clone.cancel();
+    assert!(token.is_cancelled());
+}
+
+#[test]
+fn test_drop_guard_cancels_on_scope_end() {
+    let token = CancellationToken::new();
+    {
+        let _guard = token.clone().drop_guard();
+        assert!(!token.is_cancelled());
+    }
+    assert!(token.is_cancelled());
+
+    let token = CancellationToken::new();
+    let disarmed = token.clone().drop_guard().disarm();
+    assert!(!token.is_cancelled());
+    assert!(!disarmed.is_cancelled());
+}
+
+#[tokio::test]
+async fn test_cancelled_future_completes_after_cancel() {
+    let token = CancellationToken::new();
+    let handle = token.clone();
+
+    std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(20));
+        handle.cancel();
+    });
+
+    token.cancelled().await;
+    assert!(token.is_cancelled());
+}
+
+#[tokio::test]
+async fn test_send_async_cancelled_in_flight() {
+    let client = HangingClient::default();
+    let token = CancellationToken::new();
+    let handle = token.clone();
+
+    std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(20));
+        handle.cancel();
+    });
+
+    let result = GetReportBuilder::new()
+        .id(1)
+        .async_http_client(client.clone())
+        .base_url("https://api.example.com")
+        .cancellation_token(token)
+        .send_async()
+        .await;
+
+    assert!(matches!(result, Err(RestApiError::Cancelled)));
+    assert!(client.dropped.load(Ordering::SeqCst));
+}
+
+#[tokio::test]
+async fn test_send_async_with_already_cancelled_token() {
+    let token = CancellationToken::new();
+    token.cancel();
+
+    let result = GetReportBuilder::new()
+        .id(1)
+        .async_http_client(HangingClient::default())
+        .base_url("https://api.example.com")
+        .cancellation_token(token)
+        .send_async()
+        .await;
+
+    assert!(matches!(result, Err(RestApiError::Cancelled)));
+}
+
+#[test]
+fn test_dropping_send_async_abandons_request() {
+    use futures::FutureExt;
+
+    let client = HangingClient::default();
+
+    // Poll the request once so it is in flight, then drop it
+    let result = GetReportBuilder::new()
+        .id(1)
+        .async_http_client(client.clone())
+        .base_url("https://api.example.com")
+        .send_async()
+        .now_or_never();
+
+    assert!(result.is_none());
+    assert!(client.dropped.load(Ordering::SeqCst));
+}
@@ -0,0 +1,115 @@
+use derive_rest_api::http::HeaderMap;
+use derive_rest_api::{CanonicalRequest, Middleware, MockClient, RequestBuilder, RequestParts};
+use std::sync::{Arc, Mutex};
+
+#[derive(RequestBuilder)]
+#[request_builder(method = "GET", path = "/users", response = ())]
+struct ListUsers {
+    #[request_builder(query)]
+    name: Option<String>,
+    #[request_builder(query)]
+    page: Option<u32>,
+    #[request_builder(header = "X-Trace")]
+    trace: Option<String>,
+}
+
+fn headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    for (name, value) in pairs {
+        headers.append(*name, value.parse().unwrap());
+    }
+    headers
+}
+
+#[test]
+fn test_matches_the_sigv4_canonical_request() {
+    // The example from the AWS Signature Version 4 documentation
+    let canonical = CanonicalRequest::new(
+        "GET",
+        "https://iam.amazonaws.com/?Action=ListUsers&Version=2010-05-08",
+        &headers(&[
+            ("Host", "iam.amazonaws.com"),
+            ("Content-Type", "application/x-www-form-urlencoded; charset=utf-8"),
+            ("X-Amz-Date", "20150830T123600Z"),
+        ]),
+        None,
+    );
+
+    assert_eq!(
+        canonical.to_string(),
+        "GET\n/\nAction=ListUsers&Version=2010-05-08\n\
+         content-type:application/x-www-form-urlencoded; charset=utf-8\nhost:iam.amazonaws.com\nx-amz-date:20150830T123600Z\n\n\
+         content-type;host;x-amz-date\ne3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+    );
+}
+
+#[test]
+fn test_equivalent_requests_have_the_same_representation() {
+    let a = CanonicalRequest::new(
+        "post",
+        "HTTPS://API.example.com/a%7eb/c%2fd?b=2&a=x+y&a=1",
+        &headers(&[("X-Tag", " one  two "), ("Accept", "a"), ("accept", "b")]),
+        Some(b"{}"),
+    );
+    let b_headers = headers(&[("accept", "a"), ("x-tag", "one two"), ("Accept", "b")]);
+    let b = CanonicalRequest::new("POST", "https://api.example.com/a~b/c%2Fd?a=1&a=x%20y&b=2", &b_headers, Some(b"{}"));
+
+    assert_eq!(a, b);
+    assert_eq!(a.digest(), b.digest());
+    assert_eq!(a.path, "/a~b/c%2Fd");
+    assert_eq!(a.query, "a=1&a=x%20y&b=2");
+    assert_eq!(a.headers, [("accept".to_string(), "a,b".to_string()), ("x-tag".to_string(), "one two".to_string())]);
+
+    let other_host = CanonicalRequest::new("POST", "https://other.example.com/a~b/c%2Fd?a=1&a=x%20y&b=2", &b_headers, Some(b"{}"));
+    assert_ne!(other_host.digest(), b.digest());
+}
+
+#[test]
+fn test_preview_canonical_representation() {
+    let first = ListUsersBuilder::new()
+        .name("Ada Lovelace".to_string())
+        .page(2)
+        .preview("https://api.example.com")
+        .unwrap();
+    let second = ListUsersBuilder::new()
+        .page(2)
+        .name("Ada Lovelace".to_string())
+        .trace("t-1".to_string())
+        .preview("https://api.example.com")
+        .unwrap();
+
+    let canonical = first.canonical_representation();
+    assert_eq!(canonical.method, "GET");
+    assert_eq!(canonical.authority, "https://api.example.com");
+    assert_eq!(canonical.query, "name=Ada%20Lovelace&page=2");
+    assert_ne!(canonical.digest(), second.canonical_representation().digest());
+}
+
+/// Middleware keeping the canonical form of each request it sees
+struct Recorder(Arc<Mutex<Vec<CanonicalRequest>>>);
+
+impl Middleware for Recorder {
+    fn on_request(&self, request: &mut RequestParts) {
+        self.0.lock().unwrap().push(request.canonical_representation());
+    }
+}
+
+#[test]
+fn test_middleware_canonical_representation() {
+    let recorded = Arc::new(Mutex::new(Vec::new()));
+    let http = MockClient::new();
+    http.push_json(derive_rest_api::http::StatusCode::OK, &serde_json::json!({}));
+
+    ListUsersBuilder::new()
+        .name("Ada".to_string())
+        .http_client(http)
+        .base_url("https://api.example.com")
+        .middleware(Recorder(recorded.clone()))
+        .send()
+        .unwrap();
+
+    let recorded = recorded.lock().unwrap();
+    assert_eq!(recorded.len(), 1);
+    assert_eq!(recorded[0].path, "/users");
+    assert_eq!(recorded[0].query, "name=Ada");
+}
@@ -0,0 +1,143 @@
+#[cfg(feature = "checksum")]
+mod checksum_tests {
+    use derive_rest_api::http::{HeaderMap, Response};
+    use derive_rest_api::{ChecksumAlgorithm, RequestBuilder, RestApiError, verify_checksums};
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Debug)]
+    struct MockError;
+
+    impl std::fmt::Display for MockError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "MockError")
+        }
+    }
+
+    impl std::error::Error for MockError {}
+
+    // Mock HTTP client that records the request headers and returns a canned response
+    #[derive(Clone, Default)]
+    struct MockClient {
+        response_headers: HeaderMap,
+        sent_headers: Arc<Mutex<Option<HeaderMap>>>,
+    }
+
+    impl derive_rest_api::HttpClient for MockClient {
+        type Error = MockError;
+
+        fn send(
+            &self,
+            _method: &str,
+            _url: &str,
+            headers: HeaderMap,
+            _body: Option<Vec<u8>>,
+            _options: derive_rest_api::RequestOptions,
+        ) -> Result<Response<Vec<u8>>, Self::Error> {
+            *self.sent_headers.lock().unwrap() = Some(headers);
+            let mut response = Response::new(b"hello".to_vec());
+            *response.headers_mut() = self.response_headers.clone();
+            Ok(response)
+        }
+    }
+
+    #[derive(RequestBuilder)]
+    #[request_builder(method = "GET", path = "/objects/{key}", verify_checksum)]
+    struct GetObject {
+        key: String,
+    }
+
+    #[derive(RequestBuilder)]
+    #[request_builder(method = "PUT", path = "/objects/{key}", checksum = "sha256")]
+    struct PutObject {
+        #[request_builder(path)]
+        key: String,
+        #[request_builder(body)]
+        content: String,
+    }
+
+    fn headers(name: &str, value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            derive_rest_api::http::HeaderName::from_bytes(name.as_bytes()).unwrap(),
+            value.parse().unwrap(),
+        );
+        headers
+    }
+
+    #[test]
+    fn test_compute_known_values() {
+        assert_eq!(ChecksumAlgorithm::Md5.compute(b"hello"), "XUFAKrxLKna5cZ2REBfFkg==");
+        assert_eq!(ChecksumAlgorithm::Sha1.compute(b"hello"), "qvTGHdzF6KLavt4PO0gs2a6pQ00=");
+        assert_eq!(
+            ChecksumAlgorithm::Sha256.compute(b"hello"),
+            "LPJNul+wow4m6DsqxbninhsWHlwfp0JecwQzYpOLmCQ="
+        );
+        assert_eq!(ChecksumAlgorithm::Crc32.compute(b"hello"), "NhCmhg==");
+        assert_eq!(ChecksumAlgorithm::Crc32c.compute(b"hello"), "mnG7TA==");
+    }
+
+    #[test]
+    fn test_algorithm_from_str() {
+        assert_eq!("SHA256".parse::<ChecksumAlgorithm>().unwrap(), ChecksumAlgorithm::Sha256);
+        assert_eq!("crc32c".parse::<ChecksumAlgorithm>().unwrap(), ChecksumAlgorithm::Crc32c);
+        assert!("sha512".parse::<ChecksumAlgorithm>().is_err());
+    }
+
+    #[test]
+    fn test_verify_checksums() {
+        assert!(verify_checksums(&HeaderMap::new(), b"hello").is_ok());
+        assert!(verify_checksums(&headers("Content-MD5", "XUFAKrxLKna5cZ2REBfFkg=="), b"hello").is_ok());
+
+        // Composite multipart checksums are skipped
+        assert!(verify_checksums(&headers("x-amz-checksum-crc32", "AAAAAA==-3"), b"hello").is_ok());
+
+        let result = verify_checksums(&headers("x-amz-checksum-crc32", "AAAAAA=="), b"hello");
+        match result {
+            Err(RestApiError::ChecksumMismatch { header, expected, actual }) => {
+                assert_eq!(header, "x-amz-checksum-crc32");
+                assert_eq!(expected, "AAAAAA==");
+                assert_eq!(actual, "NhCmhg==");
+            }
+            other => panic!("Expected ChecksumMismatch, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_send_verifies_response_checksum() {
+        let client = MockClient {
+            response_headers: headers("x-amz-checksum-sha256", "LPJNul+wow4m6DsqxbninhsWHlwfp0JecwQzYpOLmCQ="),
+            ..Default::default()
+        };
+        let body = GetObjectBuilder::new()
+            .key("a.txt".to_string())
+            .http_client(client)
+            .base_url("https://storage.example.com")
+            .send()
+            .unwrap();
+        assert_eq!(body, b"hello");
+
+        let client = MockClient {
+            response_headers: headers("Content-MD5", "1B2M2Y8AsgTpgAmY7PhCfg=="),
+            ..Default::default()
+        };
+        let request = GetObjectBuilder::new().key("a.txt".to_string()).build().unwrap();
+        let result = request.send_with_client(&client, "https://storage.example.com");
+        assert!(matches!(result, Err(RestApiError::ChecksumMismatch { .. })));
+    }
+
+    #[test]
+    fn test_send_adds_request_checksum() {
+        let client = MockClient::default();
+        PutObjectBuilder::new()
+            .key("a.txt".to_string())
+            .content("hello".to_string())
+            .http_client(client.clone())
+            .base_url("https://storage.example.com")
+            .send()
+            .unwrap();
+
+        let sent = client.sent_headers.lock().unwrap().take().unwrap();
+        let expected = ChecksumAlgorithm::Sha256.compute(br#"{"content":"hello"}"#);
+        assert_eq!(sent.get("x-amz-checksum-sha256").unwrap(), expected.as_str());
+    }
+}
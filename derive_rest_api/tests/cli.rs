@@ -0,0 +1,133 @@
+#[cfg(feature = "cli")]
+mod cli_tests {
+    use clap::{Args, CommandFactory, FromArgMatches, Parser};
+    use derive_rest_api::http::StatusCode;
+    use derive_rest_api::{ApiClient, MockClient, RequestBuilder, assert_request};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "lowercase")]
+    pub enum Role {
+        Admin,
+        Member,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    pub struct Address {
+        city: String,
+    }
+
+    /// Lists the users in an organization
+    #[derive(Debug, RequestBuilder)]
+    #[request_builder(method = "GET", path = "/orgs/{org}/users", cli)]
+    struct ListUsers {
+        /// The organization's login
+        org: String,
+        #[request_builder(query)]
+        page: Option<u32>,
+        #[request_builder(query)]
+        role: Option<Role>,
+        #[request_builder(query)]
+        include_inactive: bool,
+        #[request_builder(query)]
+        tags: Vec<String>,
+    }
+
+    #[derive(Debug, RequestBuilder)]
+    #[request_builder(method = "POST", path = "/users", cli)]
+    struct CreateUser {
+        #[request_builder(body)]
+        name: String,
+        #[request_builder(body)]
+        address: Option<Address>,
+        #[request_builder(header, default = "cli".to_string())]
+        x_source: String,
+    }
+
+    #[derive(Clone, ApiClient)]
+    #[api_client(base_url = "https://api.example.com", requests(ListUsers, CreateUser = "add_user"), cli)]
+    struct UsersConfig;
+
+    #[derive(Parser)]
+    struct Cli {
+        #[command(subcommand)]
+        command: UsersCommand,
+    }
+
+    fn parse<T: Args + FromArgMatches>(args: &[&str]) -> Result<T, clap::Error> {
+        let command = T::augment_args(clap::Command::new("test").no_binary_name(true));
+        T::from_arg_matches(&command.try_get_matches_from(args)?)
+    }
+
+    #[test]
+    fn test_request_args_parse_every_field_kind() {
+        let request: ListUsers = parse(&[
+            "--org", "acme", "--page", "2", "--role", "admin", "--include-inactive", "--tags", "a", "--tags", "b",
+        ])
+        .unwrap();
+
+        assert_eq!(request.org, "acme");
+        assert_eq!(request.page, Some(2));
+        assert_eq!(request.role, Some(Role::Admin));
+        assert!(request.include_inactive);
+        assert_eq!(request.tags, ["a", "b"]);
+
+        let request: ListUsers = parse(&["--org", "acme"]).unwrap();
+        assert_eq!(request.page, None);
+        assert!(!request.include_inactive);
+        assert!(request.tags.is_empty());
+    }
+
+    #[test]
+    fn test_request_args_parse_json_and_defaults() {
+        let request: CreateUser = parse(&["--name", "Ada", "--address", r#"{"city":"London"}"#]).unwrap();
+
+        assert_eq!(request.address, Some(Address { city: "London".to_string() }));
+        assert_eq!(request.x_source, "cli");
+    }
+
+    #[test]
+    fn test_request_args_report_invalid_values() {
+        let error = parse::<ListUsers>(&["--page", "2"]).unwrap_err();
+        assert_eq!(error.kind(), clap::error::ErrorKind::MissingRequiredArgument);
+
+        let error = parse::<ListUsers>(&["--org", "acme", "--page", "two"]).unwrap_err();
+        assert_eq!(error.kind(), clap::error::ErrorKind::ValueValidation);
+        assert!(error.to_string().contains("invalid value 'two' for '--page'"), "{}", error);
+    }
+
+    #[test]
+    fn test_subcommands_are_named_after_client_methods() {
+        let command = Cli::command();
+        let names: Vec<_> = command.get_subcommands().map(|subcommand| subcommand.get_name()).collect();
+        assert_eq!(names, ["list-users", "add-user"]);
+
+        let about = command.find_subcommand("list-users").unwrap().get_about().unwrap();
+        assert_eq!(about.to_string(), "Lists the users in an organization");
+    }
+
+    #[test]
+    fn test_run_command_sends_the_parsed_request() {
+        let http = MockClient::new();
+        http.push_json(StatusCode::NOT_FOUND, &serde_json::json!({"error": "no such org"}));
+        let client = UsersClient::<MockClient>::new_with_client(http.clone());
+
+        let cli = Cli::try_parse_from(["users", "list-users", "--org", "acme", "--role", "member"]).unwrap();
+        let response = client.run_command(cli.command).unwrap();
+
+        // Error statuses are returned as responses
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_request!(http, GET "/orgs/acme/users", query = [("role", "member")]);
+    }
+
+    #[tokio::test]
+    async fn test_async_run_command() {
+        let http = MockClient::new();
+        let client = UsersAsyncClient::<MockClient>::new_with_client(http.clone());
+
+        let cli = Cli::try_parse_from(["users", "add-user", "--name", "Ada"]).unwrap();
+        client.run_command(cli.command).await.unwrap();
+
+        assert_request!(http, POST "/users", headers = [("x-source", "cli")], json = |body| body["name"] == "Ada");
+    }
+}
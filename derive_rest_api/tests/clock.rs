@@ -0,0 +1,173 @@
+use derive_rest_api::http::{HeaderMap, Response, StatusCode};
+use derive_rest_api::{
+    ApiClient, HttpClient, ManualClock, RequestBuilder, RequestOptions, RetryBudget, RetryPolicy, ThrottlePolicy,
+    ThrottleRegistry, ThrottledClient,
+};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+// Mock HTTP client answering with queued statuses, then 200s, and reporting
+// one request left in a 60 second rate-limit window
+#[derive(Clone, Default)]
+struct MockClient {
+    statuses: Arc<Mutex<VecDeque<StatusCode>>>,
+    requests: Arc<Mutex<usize>>,
+}
+
+impl MockClient {
+    fn requests(&self) -> usize {
+        *self.requests.lock().unwrap()
+    }
+}
+
+impl HttpClient for MockClient {
+    type Error = std::convert::Infallible;
+
+    fn send(
+        &self,
+        _method: &str,
+        _url: &str,
+        _headers: HeaderMap,
+        _body: Option<Vec<u8>>,
+        _options: RequestOptions,
+    ) -> Result<Response<Vec<u8>>, Self::Error> {
+        *self.requests.lock().unwrap() += 1;
+        let status = self.statuses.lock().unwrap().pop_front().unwrap_or(StatusCode::OK);
+        Ok(Response::builder()
+            .status(status)
+            .header("RateLimit-Remaining", "1")
+            .header("RateLimit-Reset", "60")
+            .body(b"ok".to_vec())
+            .unwrap())
+    }
+}
+
+impl derive_rest_api::AsyncHttpClient for MockClient {
+    type Error = std::convert::Infallible;
+
+    async fn send_async(
+        &self,
+        method: &str,
+        url: &str,
+        headers: HeaderMap,
+        body: Option<Vec<u8>>,
+        options: RequestOptions,
+    ) -> Result<Response<Vec<u8>>, Self::Error> {
+        self.send(method, url, headers, body, options)
+    }
+}
+
+#[derive(RequestBuilder)]
+#[request_builder(method = "GET", path = "/users/{id}")]
+struct GetUser {
+    id: u64,
+}
+
+#[derive(Clone, ApiClient)]
+#[api_client(base_url = "https://api.example.com", requests(GetUser))]
+struct UsersConfig;
+
+#[test]
+fn test_retry_backoff_uses_the_clock() {
+    let http = MockClient::default();
+    let clock = ManualClock::new();
+    let client = UsersClient::builder()
+        .http_client(http.clone())
+        .retry_policy(RetryPolicy::new(3).backoff(Duration::from_secs(10), Duration::from_secs(60)))
+        .clock(clock.clone())
+        .build()
+        .unwrap();
+
+    let started = Instant::now();
+    http.statuses.lock().unwrap().extend([StatusCode::SERVICE_UNAVAILABLE; 3]);
+    client.get_user().id(1).send().unwrap();
+    assert_eq!(http.requests(), 4);
+    assert_eq!(clock.elapsed(), Duration::from_secs(10 + 20 + 40));
+    assert!(started.elapsed() < Duration::from_secs(1));
+}
+
+#[tokio::test]
+async fn test_async_retry_backoff_uses_the_clock() {
+    let http = MockClient::default();
+    let clock = ManualClock::new();
+    let client = UsersAsyncClient::builder()
+        .http_client(http.clone())
+        .retry_policy(RetryPolicy::new(1).backoff(Duration::from_secs(30), Duration::from_secs(30)))
+        .build()
+        .unwrap();
+
+    http.statuses.lock().unwrap().push_back(StatusCode::BAD_GATEWAY);
+    client.get_user().id(1).clock(clock.clone()).send_async().await.unwrap();
+    assert_eq!(clock.elapsed(), Duration::from_secs(30));
+}
+
+#[test]
+fn test_retry_budget_window_uses_the_clock() {
+    let clock = ManualClock::new();
+    let budget = RetryBudget::new(1, Duration::from_secs(60)).clock(clock.clone());
+
+    assert!(budget.try_acquire());
+    assert!(!budget.try_acquire());
+    clock.advance(Duration::from_secs(59));
+    assert_eq!(budget.remaining(), 0);
+    clock.advance(Duration::from_secs(1));
+    assert!(budget.try_acquire());
+}
+
+#[test]
+fn test_quota_throttle_uses_the_clock() {
+    let clock = ManualClock::new();
+    let client = UsersClient::builder()
+        .http_client(MockClient::default())
+        .clock(clock.clone())
+        .build()
+        .unwrap()
+        .with_quota_throttle(1);
+
+    client.get_user().id(1).send().unwrap();
+    clock.advance(Duration::from_secs(15));
+    assert_eq!(clock.elapsed(), Duration::from_secs(15));
+
+    // The response reported one request left, so this one waits out the rest of the window
+    client.get_user().id(2).send().unwrap();
+    assert_eq!(clock.elapsed(), Duration::from_secs(60));
+}
+
+#[test]
+fn test_throttle_uses_the_clock() {
+    let clock = ManualClock::new();
+    let policy = ThrottlePolicy::per_minute(2).registry(ThrottleRegistry::new()).clock(clock.clone());
+    let http = ThrottledClient::new(MockClient::default(), policy);
+
+    for _ in 0..3 {
+        http.send("GET", "https://api.example.com/", HeaderMap::new(), None, RequestOptions::default())
+            .unwrap();
+    }
+    assert_eq!(clock.elapsed(), Duration::from_secs(30));
+}
+
+// Number of threads in this process
+#[cfg(target_os = "linux")]
+fn thread_count() -> usize {
+    let status = std::fs::read_to_string("/proc/self/status").unwrap();
+    let line = status.lines().find(|line| line.starts_with("Threads:")).unwrap();
+    line["Threads:".len()..].trim().parse().unwrap()
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn test_system_clock_sleeps_share_a_timer_thread() {
+    use derive_rest_api::{Clock, SystemClock};
+
+    let before = thread_count();
+    let sleeps = futures::future::join_all((0..200).map(|_| SystemClock.sleep_async(Duration::from_millis(50))));
+    futures::pin_mut!(sleeps);
+    assert!(futures::poll!(sleeps.as_mut()).is_pending());
+
+    // Other tests run alongside this one, so allow a few threads for them
+    assert!(thread_count() < before + 20, "{} threads before, {} while sleeping", before, thread_count());
+    let started = Instant::now();
+    sleeps.await;
+    assert!(started.elapsed() < Duration::from_secs(5));
+}
@@ -0,0 +1,6 @@
+// Diagnostics reported by the derive macros, checked against tests/ui/*.stderr
+#[test]
+fn test_compile_errors() {
+    let tests = trybuild::TestCases::new();
+    tests.compile_fail("tests/ui/*.rs");
+}
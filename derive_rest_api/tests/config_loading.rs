@@ -0,0 +1,112 @@
+#[cfg(feature = "config")]
+mod config_loading_tests {
+    use derive_rest_api::http::{HeaderMap, Response};
+    use derive_rest_api::{ApiClient, RequestBuilder, RestApiError};
+    use serde::Deserialize;
+    use std::sync::{Arc, Mutex};
+
+    // Mock HTTP client recording the URLs it is sent
+    #[derive(Clone, Default)]
+    struct MockClient {
+        urls: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl derive_rest_api::HttpClient for MockClient {
+        type Error = std::convert::Infallible;
+
+        fn send(
+            &self,
+            _method: &str,
+            url: &str,
+            _headers: HeaderMap,
+            _body: Option<Vec<u8>>,
+            _options: derive_rest_api::RequestOptions,
+        ) -> Result<Response<Vec<u8>>, Self::Error> {
+            self.urls.lock().unwrap().push(url.to_string());
+            Ok(Response::new(Vec::new()))
+        }
+    }
+
+    #[derive(RequestBuilder)]
+    #[request_builder(method = "GET", path = "/users/{id}")]
+    struct GetUser {
+        id: u64,
+    }
+
+    #[derive(Clone, Debug, PartialEq, Deserialize, ApiClient)]
+    #[api_client(base_url = "https://api.example.com", requests(GetUser), env_prefix = "USERS_API")]
+    struct UsersConfig {
+        api_key: String,
+        #[serde(default)]
+        retries: u32,
+    }
+
+    impl derive_rest_api::NoRequestConfiguration for UsersConfig {}
+
+    // Tests each set their own variables, as they share the process environment
+    fn set_env(name: &str, value: &str) {
+        // SAFETY: only the tests in this file change the environment, and never the same variable
+        unsafe { std::env::set_var(name, value) };
+    }
+
+    fn write_file(name: &str, contents: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("derive_rest_api_{}_{}", std::process::id(), name));
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_from_path_with_env_override() {
+        let path = write_file("users.toml", "api_key = \"from-file\"\nretries = 2\n");
+        assert_eq!(
+            UsersConfig::from_path(&path).unwrap(),
+            UsersConfig {
+                api_key: "from-file".to_string(),
+                retries: 2,
+            }
+        );
+
+        set_env("USERS_API_RETRIES", "5");
+        let config = UsersConfig::from_path(&path).unwrap();
+        assert_eq!(config.retries, 5);
+        assert_eq!(config.api_key, "from-file");
+    }
+
+    #[test]
+    fn test_from_path_reports_errors() {
+        let result = UsersConfig::from_path("does/not/exist.toml");
+        assert!(matches!(result, Err(RestApiError::ConfigError { .. })));
+
+        let path = write_file("invalid.json", "{\"retries\": 1}");
+        let error = UsersConfig::from_path(&path).unwrap_err();
+        assert!(error.to_string().contains("api_key"), "{}", error);
+    }
+
+    #[derive(Clone, Debug, Deserialize, ApiClient)]
+    #[api_client(base_url = "https://api.example.com", requests(GetUser), env_prefix = "BILLING_API")]
+    struct BillingConfig {
+        api_key: String,
+    }
+
+    impl derive_rest_api::NoRequestConfiguration for BillingConfig {}
+
+    #[test]
+    fn test_from_env_and_builder() {
+        set_env("BILLING_API_API_KEY", "from-env");
+        assert_eq!(BillingConfig::from_env().unwrap().api_key, "from-env");
+
+        // The base URL is picked up as well when building a client
+        set_env("BILLING_API_BASE_URL", "https://billing.internal");
+        let http = MockClient::default();
+        let client = BillingClient::builder()
+            .http_client(http.clone())
+            .config_from_env()
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(client.config().as_ref().unwrap().api_key, "from-env");
+
+        client.get_user().id(1).send_raw().unwrap();
+        assert_eq!(*http.urls.lock().unwrap(), ["https://billing.internal/users/1"]);
+    }
+}
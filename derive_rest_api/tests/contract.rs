@@ -0,0 +1,206 @@
+use derive_rest_api::{OpenApiSpec, RequestBuilder};
+use serde::Deserialize;
+
+const SPEC: &str = r##"{
+  "openapi": "3.0.3",
+  "servers": [{ "url": "https://api.example.com/v1" }],
+  "paths": {
+    "/users": {
+      "get": {
+        "parameters": [
+          { "name": "page", "in": "query", "schema": { "type": "integer" } },
+          { "name": "role", "in": "query", "schema": { "type": "string", "enum": ["admin", "member"] } }
+        ],
+        "responses": {
+          "200": {
+            "description": "Users",
+            "content": {
+              "application/json": { "schema": { "type": "array", "items": { "$ref": "#/components/schemas/User" } } }
+            }
+          }
+        }
+      },
+      "post": {
+        "requestBody": {
+          "required": true,
+          "content": { "application/json": { "schema": { "$ref": "#/components/schemas/NewUser" } } }
+        },
+        "responses": {
+          "201": {
+            "description": "Created",
+            "content": { "application/json": { "schema": { "$ref": "#/components/schemas/User" } } }
+          }
+        }
+      }
+    },
+    "/users/{id}": {
+      "parameters": [{ "$ref": "#/components/parameters/UserId" }],
+      "get": {
+        "responses": {
+          "200": {
+            "description": "A user",
+            "content": { "application/json": { "schema": { "$ref": "#/components/schemas/User" } } }
+          },
+          "404": { "description": "Not found" }
+        }
+      }
+    }
+  },
+  "components": {
+    "parameters": {
+      "UserId": { "name": "id", "in": "path", "required": true, "schema": { "type": "integer" } }
+    },
+    "schemas": {
+      "User": {
+        "type": "object",
+        "required": ["id", "name"],
+        "properties": {
+          "id": { "type": "integer" },
+          "name": { "type": "string" },
+          "email": { "type": "string", "format": "email", "nullable": true }
+        }
+      },
+      "NewUser": {
+        "type": "object",
+        "required": ["name"],
+        "additionalProperties": false,
+        "properties": {
+          "name": { "type": "string" },
+          "tags": { "type": "array", "items": { "type": "string" } }
+        }
+      }
+    }
+  }
+}"##;
+
+#[derive(Debug, Deserialize)]
+pub struct User {
+    pub id: u64,
+    pub name: String,
+    pub email: Option<String>,
+}
+
+/// Expects an email that the spec allows to be null
+#[derive(Debug, Deserialize)]
+pub struct StrictUser {
+    pub id: u64,
+    pub name: String,
+    pub email: String,
+}
+
+#[derive(RequestBuilder)]
+#[request_builder(method = "GET", path = "/v1/users/{id}", response = User)]
+struct GetUser {
+    id: u64,
+}
+
+#[derive(RequestBuilder)]
+#[request_builder(method = "GET", path = "/v1/users/{id}", response = StrictUser)]
+struct GetStrictUser {
+    id: u64,
+}
+
+#[derive(RequestBuilder)]
+#[request_builder(method = "DELETE", path = "/v1/users/{id}")]
+struct DeleteUser {
+    id: u64,
+}
+
+#[derive(RequestBuilder)]
+#[request_builder(method = "GET", path = "/v1/users", response = Vec<User>)]
+struct ListUsers {
+    #[request_builder(query)]
+    page: Option<u32>,
+    #[request_builder(query)]
+    role: Option<String>,
+    #[request_builder(query)]
+    sort: Option<String>,
+}
+
+#[derive(RequestBuilder)]
+#[request_builder(method = "POST", path = "/v1/users", response = User)]
+struct CreateUser {
+    #[request_builder(body)]
+    name: String,
+    #[request_builder(body)]
+    admin: Option<bool>,
+}
+
+#[derive(RequestBuilder)]
+#[request_builder(method = "GET", path = "/v1/groups")]
+struct ListGroups {}
+
+fn spec() -> OpenApiSpec {
+    OpenApiSpec::from_json(SPEC).unwrap()
+}
+
+fn messages(mismatches: Vec<derive_rest_api::ContractMismatch>) -> Vec<String> {
+    mismatches.into_iter().map(|mismatch| mismatch.to_string()).collect()
+}
+
+#[test]
+fn test_conforming_requests_pass() {
+    let spec = spec();
+    spec.assert_conforms(&GetUserBuilder::new().id(42).build().unwrap());
+    spec.assert_conforms(&ListUsersBuilder::new().page(2).role("admin".to_string()).build().unwrap());
+    spec.assert_conforms(&CreateUserBuilder::new().name("Ada".to_string()).build().unwrap());
+}
+
+#[test]
+fn test_reports_unknown_paths_and_methods() {
+    let spec = spec();
+    assert_eq!(
+        messages(spec.check(&ListGroupsBuilder::new().build().unwrap())),
+        ["GET /v1/groups: no path in the spec matches"]
+    );
+    assert_eq!(
+        messages(spec.check(&DeleteUserBuilder::new().id(1).build().unwrap())),
+        ["DELETE /v1/users/1: `/users/{id}` has no DELETE operation"]
+    );
+}
+
+#[test]
+fn test_reports_parameter_mismatches() {
+    let request = ListUsersBuilder::new()
+        .role("owner".to_string())
+        .sort("name".to_string())
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        messages(spec().check(&request)),
+        [
+            r#"GET /v1/users: query parameter `role` is `owner`, which isn't one of ["admin","member"]"#,
+            "GET /v1/users: query parameter `sort` isn't in the spec",
+        ]
+    );
+}
+
+#[test]
+fn test_reports_body_mismatches() {
+    let request = CreateUserBuilder::new().name("Ada".to_string()).admin(true).build().unwrap();
+
+    assert_eq!(
+        messages(spec().check(&request)),
+        ["POST /v1/users: body.admin isn't a property in the spec"]
+    );
+}
+
+#[test]
+fn test_reports_response_types_that_dont_decode() {
+    let mismatches = spec().check(&GetStrictUserBuilder::new().id(1).build().unwrap());
+
+    assert_eq!(mismatches.len(), 1);
+    assert_eq!(mismatches[0].request, "GET /v1/users/1");
+    assert!(
+        mismatches[0].message.starts_with("the response type doesn't decode a 200 response with only the required properties"),
+        "{}",
+        mismatches[0].message
+    );
+}
+
+#[test]
+#[should_panic(expected = "request doesn't conform to the OpenAPI spec:\n  GET /v1/groups: no path in the spec matches")]
+fn test_assert_conforms_panics_with_mismatches() {
+    spec().assert_conforms(&ListGroupsBuilder::new().build().unwrap());
+}
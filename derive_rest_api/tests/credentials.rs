@@ -0,0 +1,171 @@
+use derive_rest_api::http::{HeaderMap, HeaderName, Response};
+use derive_rest_api::{
+    ApiClient, AsyncCredentialsProvider, Credentials, CredentialsFuture, RequestBuilder, RestApiError,
+    RotatingCredentials,
+};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug)]
+struct MockError;
+
+impl std::fmt::Display for MockError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "MockError")
+    }
+}
+
+impl std::error::Error for MockError {}
+
+// Mock HTTP client recording the headers of each request
+#[derive(Clone, Default)]
+struct MockClient {
+    headers: Arc<Mutex<Vec<HeaderMap>>>,
+}
+
+impl MockClient {
+    fn respond(&self, headers: HeaderMap) -> Result<Response<Vec<u8>>, MockError> {
+        self.headers.lock().unwrap().push(headers);
+        Ok(Response::new(b"{}".to_vec()))
+    }
+
+    fn sent(&self, name: &str) -> Vec<String> {
+        self.headers
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|headers| headers[name].to_str().unwrap().to_string())
+            .collect()
+    }
+}
+
+impl derive_rest_api::HttpClient for MockClient {
+    type Error = MockError;
+
+    fn send(
+        &self,
+        _method: &str,
+        _url: &str,
+        headers: HeaderMap,
+        _body: Option<Vec<u8>>,
+        _options: derive_rest_api::RequestOptions,
+    ) -> Result<Response<Vec<u8>>, Self::Error> {
+        self.respond(headers)
+    }
+}
+
+impl derive_rest_api::AsyncHttpClient for MockClient {
+    type Error = MockError;
+
+    async fn send_async(
+        &self,
+        _method: &str,
+        _url: &str,
+        headers: HeaderMap,
+        _body: Option<Vec<u8>>,
+        _options: derive_rest_api::RequestOptions,
+    ) -> Result<Response<Vec<u8>>, Self::Error> {
+        self.respond(headers)
+    }
+}
+
+#[derive(RequestBuilder)]
+#[request_builder(method = "GET", path = "/secrets/{name}", into)]
+struct GetSecret {
+    name: String,
+}
+
+#[derive(Clone, ApiClient)]
+#[api_client(base_url = "https://vault.example.com", requests(GetSecret))]
+struct VaultConfig;
+
+#[test]
+fn test_rotated_credentials_are_picked_up() {
+    let http = MockClient::default();
+    let credentials = RotatingCredentials::new(Credentials::bearer("first"));
+    let client = VaultClient::builder()
+        .http_client(http.clone())
+        .credentials(credentials.clone())
+        .build()
+        .unwrap();
+
+    // A builder created before the rotation still sends the new token
+    let request = client.get_secret().name("db");
+    client.get_secret().name("db").send_raw().unwrap();
+    credentials.rotate(Credentials::bearer("second"));
+    request.send_raw().unwrap();
+
+    assert_eq!(http.sent("Authorization"), ["Bearer first", "Bearer second"]);
+    assert!(http.headers.lock().unwrap()[0]["Authorization"].is_sensitive());
+}
+
+#[test]
+fn test_provider_errors_fail_the_request() {
+    let http = MockClient::default();
+    let client = VaultClient::builder()
+        .http_client(http.clone())
+        .credentials(|| Err(RestApiError::credentials_error("token expired")))
+        .build()
+        .unwrap();
+
+    let error = client.get_secret().name("db").send_raw().unwrap_err();
+    assert!(matches!(error, RestApiError::CredentialsError { .. }));
+    assert_eq!(error.to_string(), "Failed to get credentials: token expired");
+    assert!(http.headers.lock().unwrap().is_empty());
+
+    // Per-request providers take over from the client's
+    let api_key = HeaderName::from_static("x-api-key");
+    client
+        .get_secret()
+        .name("db")
+        .credentials(move || Ok(Credentials::header(api_key.clone(), "k3y")))
+        .send_raw()
+        .unwrap();
+    assert_eq!(http.sent("X-API-Key"), ["k3y"]);
+}
+
+// Async provider counting how often it is asked
+#[derive(Default)]
+struct Leases {
+    issued: AtomicU32,
+}
+
+impl AsyncCredentialsProvider for Leases {
+    fn credentials_async(&self) -> CredentialsFuture<'_> {
+        Box::pin(async move {
+            let lease = self.issued.fetch_add(1, Ordering::SeqCst) + 1;
+            Ok(Credentials::bearer(format!("lease-{}", lease)))
+        })
+    }
+}
+
+#[tokio::test]
+async fn test_async_credentials_provider() {
+    let http = MockClient::default();
+    let client = VaultAsyncClient::builder()
+        .http_client(http.clone())
+        .async_credentials(Leases::default())
+        .build()
+        .unwrap();
+
+    client.get_secret().name("a").send_raw_async().await.unwrap();
+    client.get_secret().name("b").send_raw_async().await.unwrap();
+    assert_eq!(http.sent("Authorization"), ["Bearer lease-1", "Bearer lease-2"]);
+
+    // Blocking sends can't use an async-only provider
+    let error = GetSecretBuilder::new()
+        .http_client(http.clone())
+        .base_url("https://vault.example.com")
+        .async_credentials(Leases::default())
+        .name("c")
+        .send_raw()
+        .unwrap_err();
+    assert!(matches!(error, RestApiError::CredentialsError { .. }));
+}
+
+#[test]
+fn test_credentials_debug_hides_secrets() {
+    let credentials = Credentials::header(HeaderName::from_static("x-api-key"), "s3cret");
+    assert!(!format!("{:?}", credentials).contains("s3cret"));
+    assert_eq!(format!("{:?}", Credentials::bearer("s3cret")), "Bearer(..)");
+}
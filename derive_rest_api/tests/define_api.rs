@@ -0,0 +1,103 @@
+use derive_rest_api::define_api;
+use derive_rest_api::http::{HeaderMap, Response};
+use serde::Deserialize;
+use std::sync::{Arc, Mutex};
+
+// A request as sent: method and URL, headers and body
+type SentRequest = (String, HeaderMap, Option<Vec<u8>>);
+
+// Mock HTTP client recording each request and answering with a canned body
+#[derive(Clone, Default)]
+struct MockClient {
+    requests: Arc<Mutex<Vec<SentRequest>>>,
+}
+
+impl derive_rest_api::HttpClient for MockClient {
+    type Error = std::convert::Infallible;
+
+    fn send(
+        &self,
+        method: &str,
+        url: &str,
+        headers: HeaderMap,
+        body: Option<Vec<u8>>,
+        _options: derive_rest_api::RequestOptions,
+    ) -> Result<Response<Vec<u8>>, Self::Error> {
+        self.requests.lock().unwrap().push((format!("{} {}", method, url), headers, body));
+        Ok(Response::new(br#"{"number": 7, "title": "Crash"}"#.to_vec()))
+    }
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+pub struct Issue {
+    number: u64,
+    title: String,
+}
+
+define_api! {
+    /// Issue tracker API
+    #[api_client(base_url = "https://tracker.example.com", user_agent = "tracker-tests")]
+    pub struct TrackerConfig;
+
+    /// Fetches an issue
+    pub GetIssue: GET "/repos/{repo}/issues/{number}" (repo: String, number: u64) -> Issue;
+    pub ListIssues: GET "/repos/{repo}/issues" (
+        repo: String,
+        query state: Option<String>,
+        query per_page: Option<u32>,
+    ) -> Vec<Issue>;
+    #[request_builder(static_header("X-Client" = "tests"))]
+    pub CreateIssue: post "/repos/{repo}/issues" (
+        repo: String,
+        header idempotency_key: String,
+        #[request_builder(into)] body title: String,
+        body labels: Vec<String>,
+    ) -> Issue;
+    pub Ping: HEAD "/ping" ();
+}
+
+#[test]
+fn test_endpoints_expand_to_request_structs() {
+    let request = ListIssuesBuilder::new()
+        .repo("app".to_string())
+        .state("open".to_string())
+        .per_page(20)
+        .build()
+        .unwrap();
+    assert_eq!(request.build_url().unwrap(), "/repos/app/issues?state=open&per_page=20");
+    assert_eq!(request.state.as_deref(), Some("open"));
+
+    let request = PingBuilder::new().build().unwrap();
+    assert_eq!(request.build_url().unwrap(), "/ping");
+    assert!(request.build_body().unwrap().is_none());
+}
+
+#[test]
+fn test_client_has_a_method_per_endpoint() {
+    let http = MockClient::default();
+    let client = TrackerClient::builder().http_client(http.clone()).build().unwrap();
+
+    let issue = client.get_issue().repo("app".to_string()).number(7).send().unwrap();
+    assert_eq!(issue, Issue { number: 7, title: "Crash".to_string() });
+
+    client
+        .create_issue()
+        .repo("app".to_string())
+        .idempotency_key("k-1".to_string())
+        .title("Crash")
+        .labels(vec!["bug".to_string()])
+        .send()
+        .unwrap();
+    client.ping().send().unwrap();
+
+    let requests = http.requests.lock().unwrap();
+    assert_eq!(requests[0].0, "GET https://tracker.example.com/repos/app/issues/7");
+    let (url, headers, body) = &requests[1];
+    assert_eq!(url, "POST https://tracker.example.com/repos/app/issues");
+    assert_eq!(headers["Idempotency-Key"], "k-1");
+    assert_eq!(headers["X-Client"], "tests");
+    assert!(headers["User-Agent"].to_str().unwrap().starts_with("tracker-tests"));
+    let body: serde_json::Value = serde_json::from_slice(body.as_ref().unwrap()).unwrap();
+    assert_eq!(body, serde_json::json!({"title": "Crash", "labels": ["bug"]}));
+    assert_eq!(requests[2].0, "HEAD https://tracker.example.com/ping");
+}
@@ -0,0 +1,146 @@
+use derive_rest_api::http::{HeaderMap, Response};
+use derive_rest_api::{ApiClient, DeprecationClient, DeprecationNotice, HttpClient, RequestBuilder};
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug)]
+struct MockError;
+
+impl std::fmt::Display for MockError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "MockError")
+    }
+}
+
+impl std::error::Error for MockError {}
+
+// Mock HTTP client that marks everything under /v1 as deprecated
+#[derive(Clone, Default)]
+struct MockClient;
+
+fn respond(url: &str) -> Response<Vec<u8>> {
+    let mut builder = Response::builder();
+    if url.contains("/v1/") {
+        builder = builder
+            .header("Deprecation", "@1688169599")
+            .header("Sunset", "Sun, 30 Jun 2024 23:59:59 GMT")
+            .header(
+                "Link",
+                r#"<https://api.example.com/v2/users>; rel="successor-version", <https://docs.example.com/migrate>; rel="deprecation"; type="text/html""#,
+            );
+    }
+    builder.body(b"ok".to_vec()).unwrap()
+}
+
+impl derive_rest_api::HttpClient for MockClient {
+    type Error = MockError;
+
+    fn send(
+        &self,
+        _method: &str,
+        url: &str,
+        _headers: HeaderMap,
+        _body: Option<Vec<u8>>,
+        _options: derive_rest_api::RequestOptions,
+    ) -> Result<Response<Vec<u8>>, Self::Error> {
+        Ok(respond(url))
+    }
+}
+
+impl derive_rest_api::AsyncHttpClient for MockClient {
+    type Error = MockError;
+
+    async fn send_async(
+        &self,
+        _method: &str,
+        url: &str,
+        _headers: HeaderMap,
+        _body: Option<Vec<u8>>,
+        _options: derive_rest_api::RequestOptions,
+    ) -> Result<Response<Vec<u8>>, Self::Error> {
+        Ok(respond(url))
+    }
+}
+
+#[derive(RequestBuilder)]
+#[request_builder(method = "GET", path = "/v1/users/{id}")]
+struct GetUserV1 {
+    id: u64,
+}
+
+#[derive(RequestBuilder)]
+#[request_builder(method = "GET", path = "/v2/users/{id}")]
+struct GetUser {
+    id: u64,
+}
+
+#[derive(Clone, ApiClient)]
+#[api_client(base_url = "https://api.example.com", requests(GetUserV1, GetUser))]
+struct UsersConfig;
+
+fn recorder() -> (Arc<Mutex<Vec<DeprecationNotice>>>, impl Fn(&DeprecationNotice) + Send + Sync + 'static) {
+    let notices = Arc::new(Mutex::new(Vec::new()));
+    let sink = notices.clone();
+    (notices, move |notice: &DeprecationNotice| sink.lock().unwrap().push(notice.clone()))
+}
+
+#[test]
+fn test_handler_receives_notice() {
+    let (notices, handler) = recorder();
+    let client = UsersClient::<MockClient>::new_with_client(MockClient).with_deprecation_handler(handler);
+
+    assert_eq!(client.get_user_v1().id(1).send().unwrap(), b"ok");
+    client.get_user().id(1).send().unwrap();
+
+    let notices = notices.lock().unwrap();
+    assert_eq!(
+        *notices,
+        [DeprecationNotice {
+            method: "GET".to_string(),
+            url: "https://api.example.com/v1/users/1".to_string(),
+            deprecation: Some("@1688169599".to_string()),
+            sunset: Some("Sun, 30 Jun 2024 23:59:59 GMT".to_string()),
+            links: vec!["https://docs.example.com/migrate".to_string()],
+        }]
+    );
+    assert_eq!(
+        notices[0].to_string(),
+        "GET https://api.example.com/v1/users/1 is deprecated (deprecation: @1688169599) \
+         and will be removed after Sun, 30 Jun 2024 23:59:59 GMT; see https://docs.example.com/migrate"
+    );
+}
+
+#[tokio::test]
+async fn test_async_handler_receives_notice() {
+    let (notices, handler) = recorder();
+    let client = UsersAsyncClient::<MockClient>::new_with_client(MockClient).with_deprecation_handler(handler);
+
+    client.get_user_v1().id(2).send_async().await.unwrap();
+    client.get_user().id(2).send_async().await.unwrap();
+
+    let notices = notices.lock().unwrap();
+    assert_eq!(notices.len(), 1);
+    assert_eq!(notices[0].url, "https://api.example.com/v1/users/2");
+}
+
+#[test]
+fn test_sunset_without_deprecation() {
+    let mut headers = HeaderMap::new();
+    assert_eq!(DeprecationNotice::from_headers("GET", "/", &headers), None);
+
+    headers.insert("Sunset", "Wed, 01 Jan 2031 00:00:00 GMT".parse().unwrap());
+    headers.insert("Link", r#"<https://docs.example.com/sunset>; rel=sunset"#.parse().unwrap());
+    let notice = DeprecationNotice::from_headers("DELETE", "/things/1", &headers).unwrap();
+    assert_eq!(notice.deprecation, None);
+    assert_eq!(notice.sunset.as_deref(), Some("Wed, 01 Jan 2031 00:00:00 GMT"));
+    assert_eq!(notice.links, ["https://docs.example.com/sunset"]);
+}
+
+#[test]
+fn test_wrapper_without_handler_passes_responses_through() {
+    let client = DeprecationClient::new(MockClient);
+    let response = client
+        .send("GET", "https://api.example.com/v1/users/1", HeaderMap::new(), None, Default::default())
+        .unwrap();
+    assert_eq!(response.headers()["Deprecation"], "@1688169599");
+    assert_eq!(response.body(), b"ok");
+}
@@ -0,0 +1,157 @@
+#[cfg(feature = "digest-auth")]
+mod digest_auth_tests {
+    use derive_rest_api::http::{HeaderMap, Response, StatusCode};
+    use derive_rest_api::{DigestAuthClient, HttpClient, RequestBuilder};
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Debug)]
+    struct MockError;
+
+    impl std::fmt::Display for MockError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "MockError")
+        }
+    }
+
+    impl std::error::Error for MockError {}
+
+    // Mock device that challenges any request not answering its current nonce
+    #[derive(Clone, Default)]
+    struct MockDevice {
+        nonce: Arc<Mutex<String>>,
+        authorizations: Arc<Mutex<Vec<Option<String>>>>,
+    }
+
+    impl MockDevice {
+        fn new(nonce: &str) -> Self {
+            let device = Self::default();
+            *device.nonce.lock().unwrap() = nonce.to_string();
+            device
+        }
+
+        fn authorizations(&self) -> Vec<Option<String>> {
+            self.authorizations.lock().unwrap().clone()
+        }
+    }
+
+    impl HttpClient for MockDevice {
+        type Error = MockError;
+
+        fn send(
+            &self,
+            _method: &str,
+            _url: &str,
+            headers: HeaderMap,
+            _body: Option<Vec<u8>>,
+            _options: derive_rest_api::RequestOptions,
+        ) -> Result<Response<Vec<u8>>, Self::Error> {
+            let authorization = headers
+                .get("Authorization")
+                .map(|value| value.to_str().unwrap().to_string());
+            self.authorizations.lock().unwrap().push(authorization.clone());
+
+            let nonce = self.nonce.lock().unwrap().clone();
+            let authorized = authorization.is_some_and(|value| {
+                value.starts_with("Digest ")
+                    && value.contains(r#"username="admin""#)
+                    && value.contains(&format!(r#"nonce="{}""#, nonce))
+                    && value.contains(r#"uri="/cgi-bin/status?verbose=1""#)
+            });
+            if authorized {
+                return Ok(Response::new(b"ok".to_vec()));
+            }
+
+            let mut response = Response::new(b"unauthorized".to_vec());
+            *response.status_mut() = StatusCode::UNAUTHORIZED;
+            response.headers_mut().insert(
+                "WWW-Authenticate",
+                format!(r#"Digest realm="camera", qop="auth", nonce="{}", opaque="xyz""#, nonce)
+                    .parse()
+                    .unwrap(),
+            );
+            Ok(response)
+        }
+    }
+
+    #[derive(RequestBuilder)]
+    #[request_builder(method = "GET", path = "/cgi-bin/status")]
+    struct GetStatus {
+        #[request_builder(query)]
+        verbose: u8,
+    }
+
+    fn get_status(client: &DigestAuthClient<MockDevice>) -> Vec<u8> {
+        GetStatusBuilder::new()
+            .verbose(1)
+            .http_client(client.clone())
+            .base_url("http://camera.local")
+            .send()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_answers_challenge_and_reuses_nonce() {
+        let device = MockDevice::new("nonce-1");
+        let client = DigestAuthClient::new(device.clone(), "admin", "secret");
+
+        assert_eq!(get_status(&client), b"ok");
+        let authorizations = device.authorizations();
+        assert_eq!(authorizations.len(), 2);
+        assert!(authorizations[0].is_none());
+        assert!(authorizations[1].as_deref().unwrap().contains("nc=00000001"));
+
+        // The remembered challenge authenticates the next request up front
+        assert_eq!(get_status(&client), b"ok");
+        let authorizations = device.authorizations();
+        assert_eq!(authorizations.len(), 3);
+        assert!(authorizations[2].as_deref().unwrap().contains("nc=00000002"));
+    }
+
+    #[test]
+    fn test_new_nonce_is_answered() {
+        let device = MockDevice::new("nonce-1");
+        let client = DigestAuthClient::new(device.clone(), "admin", "secret");
+        assert_eq!(get_status(&client), b"ok");
+
+        *device.nonce.lock().unwrap() = "nonce-2".to_string();
+        assert_eq!(get_status(&client), b"ok");
+
+        let authorizations = device.authorizations();
+        assert_eq!(authorizations.len(), 4);
+        assert!(authorizations[3].as_deref().unwrap().contains(r#"nonce="nonce-2""#));
+        assert!(authorizations[3].as_deref().unwrap().contains("nc=00000001"));
+    }
+
+    #[test]
+    fn test_rejected_credentials_return_the_401() {
+        let device = MockDevice::new("nonce-1");
+        let client = DigestAuthClient::new(device.clone(), "intruder", "guess");
+
+        let response = client
+            .send("GET", "http://camera.local/cgi-bin/status?verbose=1", HeaderMap::new(), None, Default::default())
+            .unwrap();
+
+        // Only one retry is made
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(device.authorizations().len(), 2);
+    }
+
+    #[test]
+    fn test_challenge_is_only_answered_up_front_for_its_origin() {
+        let device = MockDevice::new("nonce-1");
+        let client = DigestAuthClient::new(device.clone(), "admin", "secret");
+        assert_eq!(get_status(&client), b"ok");
+
+        let send = |url: &str| client.send("GET", url, HeaderMap::new(), None, Default::default()).unwrap();
+        send("http://other.example/cgi-bin/status?verbose=1");
+        send("http://camera.local:8080/cgi-bin/status?verbose=1");
+        // The same origin, spelled differently, is still answered up front
+        send("HTTP://Camera.local:80/cgi-bin/status?verbose=1");
+
+        let authorizations = device.authorizations();
+        assert_eq!(authorizations.len(), 7);
+        assert!(authorizations[2].is_none());
+        assert!(authorizations[4].is_none());
+        assert!(authorizations[6].as_deref().unwrap().contains("nc=00000002"));
+    }
+}
@@ -57,7 +57,7 @@ fn test_unit_struct_with_dynamic_headers() {
     let request = builder.build().unwrap();
 
     // Unit structs have no header fields, so build_headers() returns empty map
-    let headers = request.build_headers();
+    let headers = request.build_headers().unwrap();
     assert!(headers.is_empty());
 
     // The URL should still build correctly
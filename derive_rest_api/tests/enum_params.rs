@@ -0,0 +1,149 @@
+use derive_rest_api::{RequestBuilder, RestApiError};
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum IssueState {
+    Open,
+    Closed,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum SortOrder {
+    CreatedAt,
+    #[serde(rename = "updated")]
+    UpdatedAt,
+}
+
+#[test]
+fn test_enum_path_parameter_uses_serialize_representation() {
+    #[derive(RequestBuilder, Serialize)]
+    #[request_builder(method = "GET", path = "/repos/{repo}/issues/{state}")]
+    struct ListIssuesByState {
+        repo: String,
+        state: IssueState,
+    }
+
+    let request = ListIssuesByStateBuilder::new()
+        .repo("derive_rest_api".to_string())
+        .state(IssueState::Closed)
+        .build()
+        .unwrap();
+
+    assert_eq!(request.build_url().unwrap(), "/repos/derive_rest_api/issues/closed");
+}
+
+#[test]
+fn test_optional_enum_path_parameter() {
+    #[derive(RequestBuilder, Serialize)]
+    #[request_builder(method = "GET", path = "/issues/{state}")]
+    struct ListIssues {
+        state: Option<IssueState>,
+    }
+
+    let request = ListIssuesBuilder::new()
+        .state(IssueState::Open)
+        .build()
+        .unwrap();
+    assert_eq!(request.build_url().unwrap(), "/issues/open");
+
+    let request = ListIssuesBuilder::new().build().unwrap();
+    assert!(matches!(
+        request.build_url(),
+        Err(RestApiError::MissingPathParameter { .. })
+    ));
+}
+
+#[test]
+fn test_enum_query_parameters() {
+    #[derive(RequestBuilder, Serialize)]
+    #[request_builder(method = "GET", path = "/issues")]
+    struct SearchIssues {
+        #[request_builder(query)]
+        state: IssueState,
+        #[request_builder(query)]
+        sort: Option<SortOrder>,
+    }
+
+    let request = SearchIssuesBuilder::new()
+        .state(IssueState::Open)
+        .sort(SortOrder::CreatedAt)
+        .build()
+        .unwrap();
+    assert_eq!(request.build_url().unwrap(), "/issues?state=open&sort=created_at");
+
+    let request = SearchIssuesBuilder::new()
+        .state(IssueState::Closed)
+        .sort(SortOrder::UpdatedAt)
+        .build()
+        .unwrap();
+    assert_eq!(request.build_url().unwrap(), "/issues?state=closed&sort=updated");
+}
+
+#[test]
+fn test_enum_sequence_query_parameter() {
+    #[derive(RequestBuilder, Serialize)]
+    #[request_builder(method = "GET", path = "/issues")]
+    struct FilterIssues {
+        #[request_builder(query)]
+        states: Vec<IssueState>,
+    }
+
+    let request = FilterIssuesBuilder::new()
+        .states(vec![IssueState::Open, IssueState::Closed])
+        .build()
+        .unwrap();
+    assert_eq!(request.build_url().unwrap(), "/issues?states[0]=open&states[1]=closed");
+}
+
+#[test]
+fn test_display_takes_precedence_for_path_parameters() {
+    #[derive(Serialize)]
+    enum Region {
+        #[serde(rename = "serialized")]
+        EuWest,
+    }
+
+    impl std::fmt::Display for Region {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            match self {
+                Region::EuWest => write!(f, "eu-west-1"),
+            }
+        }
+    }
+
+    #[derive(RequestBuilder, Serialize)]
+    #[request_builder(method = "GET", path = "/regions/{region}")]
+    struct GetRegion {
+        region: Region,
+    }
+
+    let request = GetRegionBuilder::new().region(Region::EuWest).build().unwrap();
+    assert_eq!(request.build_url().unwrap(), "/regions/eu-west-1");
+}
+
+#[test]
+fn test_non_scalar_path_parameter_is_rejected() {
+    #[derive(Serialize)]
+    struct Coordinates {
+        lat: f64,
+        lng: f64,
+    }
+
+    #[derive(RequestBuilder, Serialize)]
+    #[request_builder(method = "GET", path = "/places/{at}")]
+    struct GetPlace {
+        at: Coordinates,
+    }
+
+    let request = GetPlaceBuilder::new()
+        .at(Coordinates { lat: 1.0, lng: 2.0 })
+        .build()
+        .unwrap();
+
+    match request.build_url() {
+        Err(RestApiError::InvalidPathParameter { param, .. }) => assert_eq!(param, "at"),
+        other => panic!("expected InvalidPathParameter, got {:?}", other),
+    }
+}
@@ -0,0 +1,122 @@
+use derive_rest_api::http::HeaderMap;
+use derive_rest_api::{ApiClient, HttpClient, RequestBuilder, RequestOptions, RestApiError};
+use serde_json::json;
+
+// Mock HTTP client failing like a backend that quotes the URL in its errors
+#[derive(Clone, Default)]
+struct FailingClient;
+
+#[derive(Debug, thiserror::Error)]
+#[error("error sending request for url ({url})")]
+struct SendError {
+    url: String,
+    #[source]
+    source: std::io::Error,
+}
+
+impl HttpClient for FailingClient {
+    type Error = SendError;
+
+    fn send(
+        &self,
+        _method: &str,
+        url: &str,
+        _headers: HeaderMap,
+        _body: Option<Vec<u8>>,
+        _options: RequestOptions,
+    ) -> Result<derive_rest_api::http::Response<Vec<u8>>, Self::Error> {
+        Err(SendError { url: url.to_string(), source: std::io::Error::other("connection reset") })
+    }
+}
+
+#[derive(RequestBuilder)]
+#[request_builder(method = "GET", path = "/users/{id}")]
+struct GetUser {
+    id: u64,
+    #[request_builder(query)]
+    token: Option<String>,
+}
+
+#[derive(Clone, ApiClient)]
+#[api_client(base_url = "https://api.example.com", requests(GetUser))]
+struct UsersConfig;
+
+#[test]
+fn test_http_error_json() {
+    let client = UsersClient::<FailingClient>::new_with_client(FailingClient);
+    let error = client.get_user().id(1).token("s3cret".to_string()).send().unwrap_err();
+
+    let json = error.to_json();
+    assert_eq!(json["kind"], "transport");
+    assert_eq!(json["variant"], "HttpError");
+    assert_eq!(json["endpoint"], "GetUser");
+    assert_eq!(json["url"], "https://api.example.com/users/1?token=REDACTED");
+    assert_eq!(json["attempts"], 1);
+    assert_eq!(json["bytes_received"], json!(null));
+    assert_eq!(
+        json["chain"],
+        json!(["error sending request for url (https://api.example.com/users/1?token=REDACTED)", "connection reset"])
+    );
+    assert!(!json.to_string().contains("s3cret"));
+}
+
+#[test]
+fn test_field_errors_json() {
+    let json = RestApiError::missing_field("id").to_json();
+    assert_eq!(
+        json,
+        json!({
+            "kind": "build",
+            "variant": "MissingField",
+            "message": "Missing required field: id",
+            "chain": [],
+            "field": "id",
+        })
+    );
+}
+
+#[test]
+fn test_insecure_url_json_is_redacted() {
+    let error = RestApiError::InsecureUrl { url: "http://api.example.com/users?page=2&token=abc#top".to_string() };
+    let json = error.to_json();
+
+    assert_eq!(json["url"], "http://api.example.com/users?page=REDACTED&token=REDACTED");
+    assert_eq!(
+        json["message"],
+        "Refusing to send a request that requires HTTPS to http://api.example.com/users?page=REDACTED&token=REDACTED"
+    );
+}
+
+#[test]
+fn test_unhealthy_json_has_the_status() {
+    let error = RestApiError::Unhealthy { status: derive_rest_api::http::StatusCode::SERVICE_UNAVAILABLE };
+    let json = error.to_json();
+    assert_eq!(json["kind"], "http");
+    assert_eq!(json["status"], 503);
+}
+
+#[test]
+fn test_any_quoted_url_is_redacted() {
+    // Not the request's URL, and the error has no context to say which it is
+    let error = RestApiError::http_error(SendError {
+        url: "https://login.example.com/callback?code=abc#state".to_string(),
+        source: std::io::Error::other("redirected to https://api.example.com/v2?key=xyz&page=1"),
+    });
+    let json = error.to_json();
+
+    assert_eq!(
+        json["message"],
+        "HTTP request failed: error sending request for url (https://login.example.com/callback?code=REDACTED)"
+    );
+    assert_eq!(json["chain"][1], "redirected to https://api.example.com/v2?key=REDACTED&page=REDACTED");
+    assert!(json.get("status").is_none());
+}
+
+#[test]
+fn test_error_status_json_has_the_status() {
+    let response = derive_rest_api::http::Response::builder().status(429).body(Vec::new()).unwrap();
+    let json = RestApiError::error_for_status(response).unwrap_err().to_json();
+    assert_eq!(json["kind"], "http");
+    assert_eq!(json["variant"], "ErrorStatus");
+    assert_eq!(json["status"], 429);
+}
@@ -0,0 +1,55 @@
+use derive_rest_api::http::StatusCode;
+use derive_rest_api::{ErrorKind, MockClient, RequestBuilder, RestApiError};
+use std::time::Duration;
+
+#[derive(Debug, RequestBuilder)]
+#[request_builder(method = "GET", path = "/users/{id}", response = User)]
+struct GetUser {
+    #[request_builder(validate = "positive")]
+    id: i64,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct User {
+    #[allow(dead_code)]
+    name: String,
+}
+
+fn positive(id: &i64) -> Result<(), String> {
+    if *id > 0 { Ok(()) } else { Err("must be positive".to_string()) }
+}
+
+#[test]
+fn test_builder_errors() {
+    let error = GetUserBuilder::new().build().unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::Build);
+
+    let error = GetUserBuilder::new().id(-1).build().unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::Validation);
+}
+
+#[test]
+fn test_response_errors() {
+    let http = MockClient::new();
+    http.push_json(StatusCode::OK, &serde_json::json!([]));
+    let error = GetUserBuilder::new().id(1).http_client(http).base_url("https://api.example.com").send().unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::Deserialize);
+
+    assert_eq!(RestApiError::ResponseTooLarge { limit: 10 }.kind(), ErrorKind::Http);
+}
+
+#[test]
+fn test_transport_errors() {
+    assert_eq!(RestApiError::http_error(std::io::Error::other("reset")).kind(), ErrorKind::Transport);
+    assert_eq!(RestApiError::Cancelled.kind(), ErrorKind::Transport);
+}
+
+#[test]
+fn test_not_ready_takes_the_kind_of_its_source() {
+    let error = RestApiError::NotReady {
+        timeout: Duration::from_secs(5),
+        source: Box::new(RestApiError::Unhealthy { status: StatusCode::SERVICE_UNAVAILABLE }),
+    };
+    assert_eq!(error.kind(), ErrorKind::Http);
+}
+
@@ -0,0 +1,261 @@
+use derive_rest_api::http::{HeaderMap, Response, StatusCode};
+use derive_rest_api::{ApiClient, Failover, ManualClock, RequestBuilder};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[derive(Debug)]
+struct MockError;
+
+impl std::fmt::Display for MockError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "MockError")
+    }
+}
+
+impl std::error::Error for MockError {}
+
+// Mock HTTP client with one region that can't be reached and one returning 503s
+#[derive(Clone, Default)]
+struct MockClient {
+    unreachable: Arc<Mutex<Vec<&'static str>>>,
+    unavailable: Arc<Mutex<Vec<&'static str>>>,
+    requests: Arc<Mutex<Vec<String>>>,
+}
+
+impl MockClient {
+    fn respond(&self, method: &str, url: &str) -> Result<Response<Vec<u8>>, MockError> {
+        self.requests.lock().unwrap().push(format!("{} {}", method, url));
+        let host = url.split('/').nth(2).unwrap();
+        if self.unreachable.lock().unwrap().contains(&host) {
+            return Err(MockError);
+        }
+        let status = if self.unavailable.lock().unwrap().contains(&host) {
+            StatusCode::SERVICE_UNAVAILABLE
+        } else {
+            StatusCode::OK
+        };
+        Ok(Response::builder().status(status).body(host.as_bytes().to_vec()).unwrap())
+    }
+
+    fn requests(&self) -> Vec<String> {
+        std::mem::take(&mut self.requests.lock().unwrap())
+    }
+}
+
+impl derive_rest_api::HttpClient for MockClient {
+    type Error = MockError;
+
+    fn send(
+        &self,
+        method: &str,
+        url: &str,
+        _headers: HeaderMap,
+        _body: Option<Vec<u8>>,
+        _options: derive_rest_api::RequestOptions,
+    ) -> Result<Response<Vec<u8>>, Self::Error> {
+        self.respond(method, url)
+    }
+}
+
+impl derive_rest_api::AsyncHttpClient for MockClient {
+    type Error = MockError;
+
+    async fn send_async(
+        &self,
+        method: &str,
+        url: &str,
+        _headers: HeaderMap,
+        _body: Option<Vec<u8>>,
+        _options: derive_rest_api::RequestOptions,
+    ) -> Result<Response<Vec<u8>>, Self::Error> {
+        self.respond(method, url)
+    }
+}
+
+#[derive(RequestBuilder)]
+#[request_builder(method = "GET", path = "/users/{id}")]
+struct GetUser {
+    id: u64,
+}
+
+#[derive(Clone, ApiClient)]
+#[api_client(
+    base_urls("https://eu.example.com", "https://us.example.com", "https://ap.example.com"),
+    requests(GetUser)
+)]
+struct RegionsConfig;
+
+#[test]
+fn test_fails_over_on_connection_error() {
+    let http = MockClient::default();
+    http.unreachable.lock().unwrap().push("eu.example.com");
+    let client = RegionsClient::<MockClient>::new_with_client(http.clone());
+
+    assert_eq!(client.get_user().id(1).send().unwrap(), b"us.example.com");
+    assert_eq!(
+        http.requests(),
+        ["GET https://eu.example.com/users/1", "GET https://us.example.com/users/1"]
+    );
+
+    // The working region stays active
+    assert_eq!(client.failover().unwrap().active_base_url(), "https://us.example.com");
+    client.get_user().id(2).send().unwrap();
+    assert_eq!(http.requests(), ["GET https://us.example.com/users/2"]);
+}
+
+#[test]
+fn test_fails_over_on_server_error() {
+    let http = MockClient::default();
+    http.unavailable.lock().unwrap().extend(["eu.example.com", "us.example.com"]);
+    let client = RegionsClient::<MockClient>::new_with_client(http.clone());
+
+    assert_eq!(client.get_user().id(1).send().unwrap(), b"ap.example.com");
+
+    // When every region fails, the last failure is returned
+    http.unavailable.lock().unwrap().push("ap.example.com");
+    let response = client.get_user().id(2).send_raw().unwrap();
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(
+        http.requests()[3..],
+        [
+            "GET https://ap.example.com/users/2",
+            "GET https://eu.example.com/users/2",
+            "GET https://us.example.com/users/2",
+        ]
+    );
+}
+
+#[test]
+fn test_recovers_after_health_check() {
+    let http = MockClient::default();
+    http.unreachable.lock().unwrap().push("eu.example.com");
+    let failover = Failover::new(["https://eu.example.com", "https://us.example.com"])
+        .recover_after(Duration::from_millis(50))
+        .health_check("/health");
+    let client = RegionsClient::<MockClient>::new_with_client(http.clone()).with_failover(failover);
+
+    client.get_user().id(1).send().unwrap();
+    std::thread::sleep(Duration::from_millis(60));
+
+    // The primary is still down, so the check fails and the client stays put
+    client.get_user().id(2).send().unwrap();
+    assert_eq!(
+        http.requests()[2..],
+        ["GET https://eu.example.com/health", "GET https://us.example.com/users/2"]
+    );
+
+    http.unreachable.lock().unwrap().clear();
+    client.get_user().id(3).send().unwrap();
+    assert_eq!(http.requests(), ["GET https://us.example.com/users/3"]);
+
+    std::thread::sleep(Duration::from_millis(60));
+    assert_eq!(client.get_user().id(4).send().unwrap(), b"eu.example.com");
+    assert_eq!(
+        http.requests(),
+        ["GET https://eu.example.com/health", "GET https://eu.example.com/users/4"]
+    );
+}
+
+#[test]
+fn test_recovers_without_health_check() {
+    let http = MockClient::default();
+    http.unavailable.lock().unwrap().push("eu.example.com");
+    let failover =
+        Failover::new(["https://eu.example.com", "https://us.example.com"]).recover_after(Duration::from_millis(50));
+    let client = RegionsClient::<MockClient>::new_with_client(http.clone()).with_failover(failover);
+
+    client.get_user().id(1).send().unwrap();
+    http.unavailable.lock().unwrap().clear();
+    std::thread::sleep(Duration::from_millis(60));
+
+    assert_eq!(client.get_user().id(2).send().unwrap(), b"eu.example.com");
+    assert_eq!(client.failover().unwrap().active_base_url(), "https://eu.example.com");
+}
+
+#[test]
+fn test_recovery_is_timed_with_the_clock() {
+    let http = MockClient::default();
+    http.unavailable.lock().unwrap().push("eu.example.com");
+    let clock = ManualClock::new();
+    let failover = Failover::new(["https://eu.example.com", "https://us.example.com"])
+        .recover_after(Duration::from_secs(60))
+        .clock(clock.clone());
+    let client = RegionsClient::<MockClient>::new_with_client(http.clone()).with_failover(failover);
+
+    client.get_user().id(1).send().unwrap();
+    http.unavailable.lock().unwrap().clear();
+    clock.advance(Duration::from_secs(59));
+    assert_eq!(client.get_user().id(2).send().unwrap(), b"us.example.com");
+
+    clock.advance(Duration::from_secs(1));
+    assert_eq!(client.get_user().id(3).send().unwrap(), b"eu.example.com");
+}
+
+#[tokio::test]
+async fn test_async_fails_over() {
+    let http = MockClient::default();
+    http.unreachable.lock().unwrap().push("eu.example.com");
+    let client = RegionsAsyncClient::<MockClient>::new_with_client(http.clone());
+
+    assert_eq!(client.get_user().id(1).send_async().await.unwrap(), b"us.example.com");
+
+    // An explicit base URL replaces the failover
+    let client = client.with_base_url("https://eu.example.com");
+    assert!(client.failover().is_none());
+    assert!(client.get_user().id(1).send_async().await.is_err());
+}
+
+#[derive(Clone, ApiClient)]
+#[api_client(
+    base_urls("https://a.internal", "https://b.internal", "https://c.internal"),
+    round_robin,
+    requests(GetUser)
+)]
+struct PoolConfig;
+
+#[derive(Clone, ApiClient)]
+#[api_client(base_urls("https://a.internal", "https://b.internal"), weights(2, 1), requests(GetUser))]
+struct WeightedPoolConfig;
+
+fn hosts(client: &MockClient) -> Vec<String> {
+    client
+        .requests()
+        .iter()
+        .map(|request| request.split('/').nth(2).unwrap().to_string())
+        .collect()
+}
+
+#[test]
+fn test_round_robin() {
+    let http = MockClient::default();
+    let client = PoolClient::<MockClient>::new_with_client(http.clone());
+    for id in 0..4 {
+        client.get_user().id(id).send().unwrap();
+    }
+    assert_eq!(hosts(&http), ["a.internal", "b.internal", "c.internal", "a.internal"]);
+
+    // A failing base URL's turn fails over to the next one
+    http.unreachable.lock().unwrap().push("b.internal");
+    assert_eq!(client.get_user().id(5).send().unwrap(), b"c.internal");
+    assert_eq!(hosts(&http), ["b.internal", "c.internal"]);
+}
+
+#[test]
+fn test_weighted() {
+    let http = MockClient::default();
+    let client = WeightedPoolClient::<MockClient>::new_with_client(http.clone());
+    for id in 0..6 {
+        client.get_user().id(id).send().unwrap();
+    }
+    assert_eq!(
+        hosts(&http),
+        ["a.internal", "b.internal", "a.internal", "a.internal", "b.internal", "a.internal"]
+    );
+
+    // A weight of zero only takes requests failing over from the others
+    let failover = Failover::new(["https://a.internal", "https://b.internal"]).weighted([0, 1]);
+    let client = client.with_failover(failover);
+    client.get_user().id(1).send().unwrap();
+    client.get_user().id(2).send().unwrap();
+    assert_eq!(hosts(&http), ["b.internal", "b.internal"]);
+}
@@ -0,0 +1,149 @@
+use derive_rest_api::http::{HeaderMap, Response, StatusCode};
+use derive_rest_api::{
+    ApiClient, FaultInjectingClient, FaultInjectionError, HttpClient, RequestBuilder, RequestOptions, RestApiError,
+    RetryPolicy,
+};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+// Mock HTTP client counting requests and answering with a fixed body
+#[derive(Clone, Default)]
+struct MockClient {
+    requests: Arc<Mutex<usize>>,
+}
+
+impl MockClient {
+    fn requests(&self) -> usize {
+        *self.requests.lock().unwrap()
+    }
+}
+
+impl derive_rest_api::HttpClient for MockClient {
+    type Error = std::convert::Infallible;
+
+    fn send(
+        &self,
+        _method: &str,
+        _url: &str,
+        _headers: HeaderMap,
+        _body: Option<Vec<u8>>,
+        _options: RequestOptions,
+    ) -> Result<Response<Vec<u8>>, Self::Error> {
+        *self.requests.lock().unwrap() += 1;
+        Ok(Response::new(b"0123456789".to_vec()))
+    }
+}
+
+impl derive_rest_api::AsyncHttpClient for MockClient {
+    type Error = std::convert::Infallible;
+
+    async fn send_async(
+        &self,
+        method: &str,
+        url: &str,
+        headers: HeaderMap,
+        body: Option<Vec<u8>>,
+        options: RequestOptions,
+    ) -> Result<Response<Vec<u8>>, Self::Error> {
+        self.send(method, url, headers, body, options)
+    }
+}
+
+#[derive(RequestBuilder)]
+#[request_builder(method = "GET", path = "/status")]
+struct GetStatus;
+
+#[derive(Clone, ApiClient)]
+#[api_client(base_url = "https://api.example.com", requests(GetStatus))]
+struct StatusConfig;
+
+// The outcome of each of a run of requests: a status, or `None` for an error
+fn outcomes(client: &FaultInjectingClient<MockClient>) -> Vec<Option<u16>> {
+    (0..50)
+        .map(|_| {
+            client
+                .send("GET", "https://api.example.com/", HeaderMap::new(), None, RequestOptions::default())
+                .ok()
+                .map(|response| response.status().as_u16())
+        })
+        .collect()
+}
+
+#[test]
+fn test_same_seed_injects_same_faults() {
+    let faulty = |seed| {
+        FaultInjectingClient::new(MockClient::default(), seed)
+            .with_error_rate(0.2)
+            .with_status(0.3, StatusCode::SERVICE_UNAVAILABLE)
+    };
+
+    let first = outcomes(&faulty(7));
+    assert_eq!(first, outcomes(&faulty(7)));
+    assert_ne!(first, outcomes(&faulty(8)));
+    assert!(first.contains(&None));
+    assert!(first.contains(&Some(503)));
+    assert!(first.contains(&Some(200)));
+}
+
+#[test]
+fn test_injected_faults_skip_the_inner_client() {
+    let http = MockClient::default();
+    let client = StatusClient::builder()
+        .http_client(FaultInjectingClient::new(http.clone(), 1).with_error_rate(1.0))
+        .build()
+        .unwrap();
+
+    let error = client.get_status().send().unwrap_err();
+    let RestApiError::HttpError { source: error, .. } = error else {
+        panic!("expected an HTTP error, got {error:?}");
+    };
+    assert!(matches!(
+        error.downcast_ref::<FaultInjectionError<std::convert::Infallible>>(),
+        Some(FaultInjectionError::Injected)
+    ));
+    assert_eq!(http.requests(), 0);
+}
+
+#[test]
+fn test_retries_recover_from_injected_statuses() {
+    let http = MockClient::default();
+    let faulty = FaultInjectingClient::new(http.clone(), 3).with_status(0.5, StatusCode::BAD_GATEWAY);
+    let client = StatusClient::builder()
+        .http_client(faulty)
+        .retry_policy(RetryPolicy::new(10).backoff(Duration::from_millis(1), Duration::from_millis(1)))
+        .build()
+        .unwrap();
+
+    for _ in 0..10 {
+        assert_eq!(client.get_status().send().unwrap(), b"0123456789");
+    }
+    assert_eq!(http.requests(), 10);
+}
+
+#[test]
+fn test_truncation_and_latency() {
+    let client = FaultInjectingClient::new(MockClient::default(), 5)
+        .with_truncation_rate(1.0)
+        .with_latency(1.0, Duration::from_millis(20));
+
+    let started = Instant::now();
+    let response = client
+        .send("GET", "https://api.example.com/", HeaderMap::new(), None, RequestOptions::default())
+        .unwrap();
+    assert!(started.elapsed() >= Duration::from_millis(20));
+    assert!(response.body().len() < 10);
+    assert!(b"0123456789".starts_with(response.body()));
+}
+
+#[tokio::test]
+async fn test_async_injects_statuses() {
+    let http = MockClient::default();
+    let client = StatusAsyncClient::builder()
+        .http_client(FaultInjectingClient::new(http.clone(), 9).with_status(1.0, StatusCode::TOO_MANY_REQUESTS))
+        .build()
+        .unwrap();
+
+    let response = client.get_status().send_raw_async().await.unwrap();
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(http.requests(), 0);
+}
@@ -1,4 +1,4 @@
-use derive_rest_api::RequestBuilder;
+use derive_rest_api::{RequestBuilder, RestApiError};
 
 // Validation function for testing
 fn validate_positive(value: &u32) -> Result<(), String> {
@@ -10,7 +10,7 @@ fn validate_positive(value: &u32) -> Result<(), String> {
 }
 
 // Validation function for email
-fn validate_email(value: &String) -> Result<(), String> {
+fn validate_email(value: &str) -> Result<(), String> {
     if value.contains('@') {
         Ok(())
     } else {
@@ -19,7 +19,7 @@ fn validate_email(value: &String) -> Result<(), String> {
 }
 
 // Validation function for string length
-fn validate_min_length(value: &String) -> Result<(), String> {
+fn validate_min_length(value: &str) -> Result<(), String> {
     if value.len() >= 3 {
         Ok(())
     } else {
@@ -201,3 +201,54 @@ fn test_validation_combined_with_into() {
         .build();
     assert!(result2.is_err());
 }
+
+#[test]
+fn test_try_build_collects_all_errors() {
+    #[derive(RequestBuilder, Debug, PartialEq)]
+    struct CreateUser {
+        #[request_builder(validate = "validate_min_length")]
+        name: String,
+        #[request_builder(validate = "validate_email")]
+        email: String,
+        #[request_builder(validate = "validate_positive")]
+        age: Option<u32>,
+        role: String,
+    }
+
+    let errors = CreateUserBuilder::new()
+        .name("Al".to_string())
+        .email("not-an-email".to_string())
+        .age(0)
+        .try_build()
+        .unwrap_err();
+
+    let errors = errors.into_errors();
+    assert_eq!(errors.len(), 4);
+    assert!(matches!(&errors[0], RestApiError::ValidationError { field, .. } if field == "name"));
+    assert!(matches!(&errors[1], RestApiError::ValidationError { field, .. } if field == "email"));
+    assert!(matches!(&errors[2], RestApiError::ValidationError { field, .. } if field == "age"));
+    assert!(matches!(&errors[3], RestApiError::MissingField { field } if field == "role"));
+}
+
+#[test]
+fn test_try_build_success_and_build_reports_first_error() {
+    #[derive(RequestBuilder, Debug, PartialEq)]
+    struct CreateUser {
+        #[request_builder(validate = "validate_email")]
+        email: String,
+        name: String,
+    }
+
+    let user = CreateUserBuilder::new()
+        .email("user@example.com".to_string())
+        .name("Alice".to_string())
+        .try_build()
+        .unwrap();
+    assert_eq!(user.name, "Alice");
+
+    // build() keeps reporting only the first problem
+    let result = CreateUserBuilder::new()
+        .email("invalid".to_string())
+        .build();
+    assert!(matches!(result, Err(RestApiError::ValidationError { field, .. }) if field == "email"));
+}
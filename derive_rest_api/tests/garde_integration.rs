@@ -0,0 +1,75 @@
+#[cfg(feature = "garde")]
+mod garde_tests {
+    use derive_rest_api::{RequestBuilder, RestApiError};
+    use serde::Serialize;
+
+    #[derive(Debug, RequestBuilder, Serialize, garde::Validate)]
+    #[request_builder(method = "POST", path = "/users", garde)]
+    struct CreateUser {
+        #[request_builder(body)]
+        #[garde(length(min = 3))]
+        name: String,
+        #[request_builder(body)]
+        #[garde(range(min = 18))]
+        age: u32,
+        #[request_builder(body)]
+        #[garde(skip)]
+        nickname: Option<String>,
+    }
+
+    #[test]
+    fn test_garde_validation_passes() {
+        let request = CreateUserBuilder::new()
+            .name("Alice".to_string())
+            .age(30)
+            .build()
+            .unwrap();
+
+        assert_eq!(request.name, "Alice");
+        assert!(request.nickname.is_none());
+    }
+
+    #[test]
+    fn test_garde_validation_fails_build() {
+        let result = CreateUserBuilder::new()
+            .name("Al".to_string())
+            .age(30)
+            .build();
+
+        match result {
+            Err(RestApiError::ValidationError { field, .. }) => assert_eq!(field, "name"),
+            _ => panic!("expected a ValidationError"),
+        }
+    }
+
+    #[test]
+    fn test_garde_errors_collected_by_try_build() {
+        let errors = CreateUserBuilder::new()
+            .name("Al".to_string())
+            .age(12)
+            .try_build()
+            .unwrap_err();
+
+        let fields: Vec<_> = errors
+            .errors()
+            .iter()
+            .map(|error| match error {
+                RestApiError::ValidationError { field, .. } => field.as_str(),
+                _ => panic!("expected a ValidationError"),
+            })
+            .collect();
+        assert_eq!(fields, ["age", "name"]);
+    }
+
+    #[test]
+    fn test_garde_not_run_when_fields_missing() {
+        // Missing fields are reported before the struct exists to validate
+        let errors = CreateUserBuilder::new()
+            .name("Al".to_string())
+            .try_build()
+            .unwrap_err();
+
+        assert_eq!(errors.errors().len(), 1);
+        assert!(matches!(&errors.errors()[0], RestApiError::MissingField { field } if field == "age"));
+    }
+}
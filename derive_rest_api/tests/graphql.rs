@@ -0,0 +1,225 @@
+use derive_rest_api::http::{HeaderMap, Response, StatusCode};
+use derive_rest_api::{ApiClient, GraphQlRequest, RequestBuilder, RestApiError};
+use serde::Deserialize;
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug)]
+struct MockError;
+
+impl std::fmt::Display for MockError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "MockError")
+    }
+}
+
+impl std::error::Error for MockError {}
+
+// Method, URL, headers and body of a sent request
+type SentRequest = (String, String, HeaderMap, Option<Vec<u8>>);
+
+// Mock HTTP client that records the last request and returns a canned response
+#[derive(Clone, Default)]
+struct MockClient {
+    status: Option<StatusCode>,
+    response: Vec<u8>,
+    sent: Arc<Mutex<Option<SentRequest>>>,
+}
+
+impl MockClient {
+    fn new(response: &str) -> Self {
+        Self {
+            response: response.as_bytes().to_vec(),
+            ..Default::default()
+        }
+    }
+
+    fn sent(&self) -> (String, String, HeaderMap, serde_json::Value) {
+        let (method, url, headers, body) = self.sent.lock().unwrap().take().unwrap();
+        let body = body.map(|body| serde_json::from_slice(&body).unwrap()).unwrap_or_default();
+        (method, url, headers, body)
+    }
+}
+
+impl derive_rest_api::HttpClient for MockClient {
+    type Error = MockError;
+
+    fn send(
+        &self,
+        method: &str,
+        url: &str,
+        headers: HeaderMap,
+        body: Option<Vec<u8>>,
+        _options: derive_rest_api::RequestOptions,
+    ) -> Result<Response<Vec<u8>>, Self::Error> {
+        *self.sent.lock().unwrap() = Some((method.to_string(), url.to_string(), headers, body));
+        let mut response = Response::new(self.response.clone());
+        if let Some(status) = self.status {
+            *response.status_mut() = status;
+        }
+        Ok(response)
+    }
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Repository {
+    name: String,
+    stars: u32,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct RepositoryData {
+    repository: Repository,
+}
+
+#[derive(RequestBuilder)]
+#[request_builder(
+    graphql,
+    query = "query GetRepository($owner: String!, $name: String!) { repository(owner: $owner, name: $name) { name stars } }",
+    operation_name = "GetRepository",
+    response = RepositoryData
+)]
+struct GetRepository {
+    owner: String,
+    name: String,
+    #[request_builder(header = "X-Request-Id")]
+    request_id: Option<String>,
+}
+
+#[derive(RequestBuilder)]
+#[request_builder(graphql, path = "/api/graphql", query = "{ viewer { login } }")]
+struct GetViewer;
+
+#[derive(RequestBuilder)]
+#[request_builder(method = "GET", path = "/repos/{owner}/{name}", response = Repository)]
+struct GetRepositoryRest {
+    owner: String,
+    name: String,
+}
+
+#[derive(Clone, ApiClient)]
+#[api_client(base_url = "https://api.example.com", requests(GetRepository, GetRepositoryRest))]
+struct ExampleApi;
+
+#[test]
+fn test_sends_query_and_unwraps_data() {
+    let client = MockClient::new(r#"{"data":{"repository":{"name":"derive_rest_api","stars":42}}}"#);
+
+    let data = GetRepositoryBuilder::new()
+        .owner("tmstorey".to_string())
+        .name("derive_rest_api".to_string())
+        .request_id("abc".to_string())
+        .http_client(client.clone())
+        .base_url("https://api.example.com")
+        .send()
+        .unwrap();
+    assert_eq!(data.repository, Repository { name: "derive_rest_api".to_string(), stars: 42 });
+
+    let (method, url, headers, body) = client.sent();
+    assert_eq!(method, "POST");
+    assert_eq!(url, "https://api.example.com/graphql");
+    assert_eq!(headers.get("Content-Type").unwrap(), "application/json");
+    assert_eq!(headers.get("X-Request-Id").unwrap(), "abc");
+    assert!(body["query"].as_str().unwrap().starts_with("query GetRepository"));
+    assert_eq!(body["variables"], serde_json::json!({"owner": "tmstorey", "name": "derive_rest_api"}));
+    assert_eq!(body["operationName"], "GetRepository");
+}
+
+#[test]
+fn test_without_variables_or_response_type() {
+    let client = MockClient::new(r#"{"data":{"viewer":{"login":"octocat"}}}"#);
+
+    let data = GetViewerBuilder::new()
+        .http_client(client.clone())
+        .base_url("https://api.example.com")
+        .send()
+        .unwrap();
+    assert_eq!(data, br#"{"viewer":{"login":"octocat"}}"#);
+
+    let (_, url, _, body) = client.sent();
+    assert_eq!(url, "https://api.example.com/api/graphql");
+    assert_eq!(body, serde_json::json!({"query": "{ viewer { login } }", "variables": {}}));
+}
+
+#[test]
+fn test_errors_are_returned_with_partial_data() {
+    let client = MockClient {
+        status: Some(StatusCode::OK),
+        ..MockClient::new(
+            r#"{"data":{"repository":null},"errors":[{"message":"Not found","locations":[{"line":1,"column":60}],"path":["repository"],"extensions":{"code":"NOT_FOUND"}}]}"#,
+        )
+    };
+
+    let request = GetRepositoryBuilder::new()
+        .owner("tmstorey".to_string())
+        .name("missing".to_string())
+        .build()
+        .unwrap();
+    let result = request.send_with_client(&client, "https://api.example.com");
+
+    match result {
+        Err(error @ RestApiError::GraphQlErrors { .. }) => {
+            assert_eq!(error.to_string(), "GraphQL request failed: Not found (at repository)");
+            let RestApiError::GraphQlErrors { errors, data } = error else { unreachable!() };
+            assert_eq!(errors.len(), 1);
+            assert_eq!(errors[0].message, "Not found");
+            assert_eq!(errors[0].locations, vec![(1, 60)]);
+            assert_eq!(errors[0].extensions, Some(serde_json::json!({"code": "NOT_FOUND"})));
+            assert_eq!(data, Some(serde_json::json!({"repository": null})));
+        }
+        other => panic!("Expected GraphQlErrors, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_error_status_body_is_unwrapped() {
+    let client = MockClient {
+        status: Some(StatusCode::BAD_REQUEST),
+        ..MockClient::new(r#"{"errors":[{"message":"Syntax Error"}]}"#)
+    };
+
+    let result = GetViewerBuilder::new()
+        .http_client(client)
+        .base_url("https://api.example.com")
+        .send();
+    assert!(matches!(result, Err(RestApiError::GraphQlErrors { data: None, .. })));
+}
+
+#[test]
+fn test_mixed_rest_and_graphql_client() {
+    let client = MockClient::new(r#"{"data":{"repository":{"name":"derive_rest_api","stars":7}}}"#);
+    let api = ExampleApiClient::<MockClient>::new_with_client(client.clone());
+
+    let data = api
+        .get_repository()
+        .owner("tmstorey".to_string())
+        .name("derive_rest_api".to_string())
+        .send()
+        .unwrap();
+    assert_eq!(data.repository.stars, 7);
+    assert_eq!(client.sent().1, "https://api.example.com/graphql");
+
+    let client = MockClient::new(r#"{"name":"derive_rest_api","stars":7}"#);
+    let api = ExampleApiClient::<MockClient>::new_with_client(client.clone());
+    let repository = api
+        .get_repository_rest()
+        .owner("tmstorey".to_string())
+        .name("derive_rest_api".to_string())
+        .send()
+        .unwrap();
+    assert_eq!(repository.stars, 7);
+    assert_eq!(client.sent().1, "https://api.example.com/repos/tmstorey/derive_rest_api");
+}
+
+#[test]
+fn test_graphql_request_serialization() {
+    let request = GraphQlRequest::new("query Q($id: ID!) { node(id: $id) { id } }", serde_json::json!({"id": "1"}))
+        .operation_name("Q");
+    assert_eq!(
+        serde_json::to_value(&request).unwrap(),
+        serde_json::json!({
+            "query": "query Q($id: ID!) { node(id: $id) { id } }",
+            "variables": {"id": "1"},
+            "operationName": "Q",
+        })
+    );
+}
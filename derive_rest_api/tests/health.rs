@@ -0,0 +1,134 @@
+use derive_rest_api::http::{HeaderMap, Response, StatusCode};
+use derive_rest_api::{ApiClient, RequestBuilder, RestApiError};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::{Duration, Instant};
+
+#[derive(Debug)]
+struct MockError;
+
+impl std::fmt::Display for MockError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "MockError")
+    }
+}
+
+impl std::error::Error for MockError {}
+
+// Mock HTTP client for a service that is starting up: its health endpoint
+// refuses connections, then returns 503 until it has been checked `ready_after` times
+#[derive(Clone, Default)]
+struct MockClient {
+    checks: Arc<AtomicU32>,
+    ready_after: u32,
+}
+
+impl MockClient {
+    fn respond(&self, url: &str, headers: &HeaderMap) -> Result<Response<Vec<u8>>, MockError> {
+        assert_eq!(url, "https://api.example.com/healthz");
+        assert_eq!(headers["X-API-Version"], "3");
+        let checks = self.checks.fetch_add(1, Ordering::SeqCst) + 1;
+        let status = match checks {
+            1 => return Err(MockError),
+            checks if checks < self.ready_after => StatusCode::SERVICE_UNAVAILABLE,
+            _ => StatusCode::OK,
+        };
+        Ok(Response::builder().status(status).body(Vec::new()).unwrap())
+    }
+}
+
+impl derive_rest_api::HttpClient for MockClient {
+    type Error = MockError;
+
+    fn send(
+        &self,
+        _method: &str,
+        url: &str,
+        headers: HeaderMap,
+        _body: Option<Vec<u8>>,
+        _options: derive_rest_api::RequestOptions,
+    ) -> Result<Response<Vec<u8>>, Self::Error> {
+        self.respond(url, &headers)
+    }
+}
+
+impl derive_rest_api::AsyncHttpClient for MockClient {
+    type Error = MockError;
+
+    async fn send_async(
+        &self,
+        _method: &str,
+        url: &str,
+        headers: HeaderMap,
+        _body: Option<Vec<u8>>,
+        _options: derive_rest_api::RequestOptions,
+    ) -> Result<Response<Vec<u8>>, Self::Error> {
+        self.respond(url, &headers)
+    }
+}
+
+#[derive(RequestBuilder)]
+#[request_builder(method = "GET", path = "/users/{id}")]
+struct GetUser {
+    id: u64,
+}
+
+#[derive(Clone, ApiClient)]
+#[api_client(base_url = "https://api.example.com", requests(GetUser), health = "/healthz", version = "3")]
+struct UpstreamConfig;
+
+#[test]
+fn test_health_check() {
+    let client = UpstreamClient::<MockClient>::new_with_client(MockClient {
+        ready_after: 3,
+        ..Default::default()
+    });
+
+    assert!(matches!(client.health_check(), Err(RestApiError::HttpError { .. })));
+    assert!(matches!(
+        client.health_check(),
+        Err(RestApiError::Unhealthy { status: StatusCode::SERVICE_UNAVAILABLE })
+    ));
+    assert!(client.health_check().is_ok());
+}
+
+#[test]
+fn test_wait_until_ready() {
+    let http = MockClient {
+        ready_after: 4,
+        ..Default::default()
+    };
+    let client = UpstreamClient::<MockClient>::new_with_client(http.clone());
+
+    // Backs off 100ms, 200ms, then 400ms between the four checks
+    let started = Instant::now();
+    client.wait_until_ready(Duration::from_secs(5)).unwrap();
+    assert_eq!(http.checks.load(Ordering::SeqCst), 4);
+    assert!(started.elapsed() >= Duration::from_millis(700));
+}
+
+#[test]
+fn test_wait_until_ready_times_out() {
+    let client = UpstreamClient::<MockClient>::new_with_client(MockClient {
+        ready_after: u32::MAX,
+        ..Default::default()
+    });
+
+    let error = client.wait_until_ready(Duration::from_millis(250)).unwrap_err();
+    let RestApiError::NotReady { timeout, source } = error else {
+        panic!("Expected NotReady, got {:?}", error);
+    };
+    assert_eq!(timeout, Duration::from_millis(250));
+    assert!(matches!(*source, RestApiError::Unhealthy { .. }));
+}
+
+#[tokio::test]
+async fn test_async_wait_until_ready() {
+    let client = UpstreamAsyncClient::<MockClient>::new_with_client(MockClient {
+        ready_after: 2,
+        ..Default::default()
+    });
+
+    client.wait_until_ready(Duration::from_secs(5)).await.unwrap();
+    assert!(client.health_check().await.is_ok());
+}
@@ -1,6 +1,6 @@
-use derive_rest_api::RequestBuilder;
+use derive_rest_api::{RequestBuilder, RequestModifier, RestApiError};
 use serde::Serialize;
-use std::collections::HashMap;
+use derive_rest_api::http::HeaderMap;
 
 // Mock error type for testing
 #[derive(Debug)]
@@ -35,11 +35,11 @@ impl derive_rest_api::HttpClient for MockHttpClient {
         &self,
         _method: &str,
         _url: &str,
-        _headers: HashMap<String, String>,
+        _headers: HeaderMap,
         _body: Option<Vec<u8>>,
-        _timeout: Option<std::time::Duration>,
-    ) -> Result<Vec<u8>, Self::Error> {
-        Ok(self.response.clone())
+        _options: derive_rest_api::RequestOptions,
+    ) -> Result<derive_rest_api::http::Response<Vec<u8>>, Self::Error> {
+        Ok(derive_rest_api::http::Response::new(self.response.clone()))
     }
 }
 
@@ -62,6 +62,42 @@ fn test_send_with_client_basic() {
     assert_eq!(response, br#"{"id": 123, "name": "Alice"}"#);
 }
 
+#[tokio::test]
+async fn test_send_with_async_client() {
+    // Mock async HTTP client that echoes the method and URL
+    #[derive(Clone, Default)]
+    struct EchoAsyncClient;
+
+    impl derive_rest_api::AsyncHttpClient for EchoAsyncClient {
+        type Error = MockError;
+
+        async fn send_async(
+            &self,
+            method: &str,
+            url: &str,
+            _headers: HeaderMap,
+            _body: Option<Vec<u8>>,
+            _options: derive_rest_api::RequestOptions,
+        ) -> Result<derive_rest_api::http::Response<Vec<u8>>, Self::Error> {
+            Ok(derive_rest_api::http::Response::new(format!("{} {}", method, url).into_bytes()))
+        }
+    }
+
+    #[derive(RequestBuilder)]
+    #[request_builder(method = "DELETE", path = "/api/users/{id}")]
+    struct DeleteUser {
+        id: u64,
+    }
+
+    let request = DeleteUserBuilder::new()
+        .id(7)
+        .build()
+        .unwrap();
+
+    let response = request.send_with_async_client(&EchoAsyncClient, "https://api.example.com").await.unwrap();
+    assert_eq!(response, b"DELETE https://api.example.com/api/users/7");
+}
+
 #[test]
 fn test_send_with_client_query_params() {
     #[derive(RequestBuilder, Serialize)]
@@ -154,6 +190,62 @@ fn test_build_body_optional_fields() {
     assert!(json.get("email").is_none() || json["email"].is_null());
 }
 
+#[test]
+fn test_build_body_const_value_fields() {
+    #[derive(RequestBuilder, Serialize)]
+    #[request_builder(method = "POST", path = "/oauth/token")]
+    struct RequestToken {
+        #[request_builder(body, const_value = "client_credentials")]
+        grant_type: &'static str,
+        #[request_builder(body)]
+        client_id: String,
+    }
+
+    // No setter is generated for the constant field
+    let request = RequestTokenBuilder::new()
+        .client_id("app".to_string())
+        .build()
+        .unwrap();
+
+    let json: serde_json::Value = serde_json::from_slice(&request.build_body().unwrap().unwrap()).unwrap();
+    assert_eq!(json, serde_json::json!({"grant_type": "client_credentials", "client_id": "app"}));
+}
+
+#[test]
+fn test_build_body_flatten_fields() {
+    #[derive(Serialize)]
+    struct Pagination {
+        page: u32,
+        per_page: u32,
+    }
+
+    #[derive(Serialize)]
+    struct Filters {
+        status: String,
+    }
+
+    #[derive(RequestBuilder, Serialize)]
+    #[request_builder(method = "POST", path = "/api/search")]
+    struct Search {
+        #[request_builder(body)]
+        term: String,
+        #[request_builder(body, flatten)]
+        pagination: Pagination,
+        #[request_builder(body, flatten)]
+        filters: Option<Filters>,
+    }
+
+    let request = SearchBuilder::new()
+        .term("rust".to_string())
+        .pagination(Pagination { page: 2, per_page: 50 })
+        .filters(Filters { status: "open".to_string() })
+        .build()
+        .unwrap();
+
+    let json: serde_json::Value = serde_json::from_slice(&request.build_body().unwrap().unwrap()).unwrap();
+    assert_eq!(json, serde_json::json!({"term": "rust", "page": 2, "per_page": 50, "status": "open"}));
+}
+
 #[test]
 fn test_build_headers_with_fields() {
     #[derive(RequestBuilder)]
@@ -171,10 +263,10 @@ fn test_build_headers_with_fields() {
         .build()
         .unwrap();
 
-    let headers = request.build_headers();
+    let headers = request.build_headers().unwrap();
     // Header names should be converted from snake_case to Title-Case
-    assert_eq!(headers.get("Authorization"), Some(&"Bearer token123".to_string()));
-    assert_eq!(headers.get("X-Api-Key"), Some(&"key456".to_string()));
+    assert_eq!(headers.get("Authorization").unwrap(), "Bearer token123");
+    assert_eq!(headers.get("X-Api-Key").unwrap(), "key456");
 }
 
 #[test]
@@ -191,7 +283,7 @@ fn test_build_headers_no_fields() {
         .build()
         .unwrap();
 
-    let headers = request.build_headers();
+    let headers = request.build_headers().unwrap();
     assert!(headers.is_empty());
 }
 
@@ -213,9 +305,9 @@ fn test_build_headers_optional_fields() {
         .build()
         .unwrap();
 
-    let headers1 = request1.build_headers();
+    let headers1 = request1.build_headers().unwrap();
     assert_eq!(headers1.len(), 2);
-    assert_eq!(headers1.get("Authorization"), Some(&"Bearer token".to_string()));
+    assert_eq!(headers1.get("Authorization").unwrap(), "Bearer token");
 
     // With only one header set
     let request2 = GetUsersBuilder::new()
@@ -223,10 +315,39 @@ fn test_build_headers_optional_fields() {
         .build()
         .unwrap();
 
-    let headers2 = request2.build_headers();
+    let headers2 = request2.build_headers().unwrap();
     assert_eq!(headers2.len(), 1);
-    assert_eq!(headers2.get("Authorization"), Some(&"Bearer token".to_string()));
-    assert!(headers2.get("X-Custom-Header").is_none());
+    assert_eq!(headers2.get("Authorization").unwrap(), "Bearer token");
+    assert!(!headers2.contains_key("X-Custom-Header"));
+}
+
+#[test]
+fn test_build_headers_static_headers() {
+    #[derive(RequestBuilder)]
+    #[request_builder(
+        method = "GET",
+        path = "/repos/{repo}",
+        response = serde_json::Value,
+        static_header("Accept" = "application/vnd.github+json", "X-GitHub-Api-Version" = "2022-11-28")
+    )]
+    struct GetRepo {
+        repo: String,
+        #[request_builder(header)]
+        authorization: Option<String>,
+    }
+
+    let request = GetRepoBuilder::new()
+        .repo("octocat/hello".to_string())
+        .authorization("Bearer token".to_string())
+        .build()
+        .unwrap();
+
+    let headers = request.build_headers().unwrap();
+    assert_eq!(headers.len(), 3);
+    // The static Accept header replaces the default one for typed responses
+    assert_eq!(headers.get("Accept").unwrap(), "application/vnd.github+json");
+    assert_eq!(headers.get("X-GitHub-Api-Version").unwrap(), "2022-11-28");
+    assert_eq!(headers.get("Authorization").unwrap(), "Bearer token");
 }
 
 #[test]
@@ -266,8 +387,8 @@ fn test_mixed_field_types() {
     assert_eq!(json["content"], "This is a test post");
 
     // Test headers
-    let headers = request.build_headers();
-    assert_eq!(headers.get("Authorization"), Some(&"Bearer token".to_string()));
+    let headers = request.build_headers().unwrap();
+    assert_eq!(headers.get("Authorization").unwrap(), "Bearer token");
 }
 
 #[test]
@@ -315,13 +436,13 @@ fn test_custom_header_name() {
         .build()
         .unwrap();
 
-    let headers = request.build_headers();
+    let headers = request.build_headers().unwrap();
     // Should use custom names, not converted field names
-    assert_eq!(headers.get("X-Custom-Auth"), Some(&"secret123".to_string()));
-    assert_eq!(headers.get("X-Request-ID"), Some(&"abc-123".to_string()));
+    assert_eq!(headers.get("X-Custom-Auth").unwrap(), "secret123");
+    assert_eq!(headers.get("X-Request-ID").unwrap(), "abc-123");
     // Should NOT have the converted field names
-    assert!(headers.get("Auth-Token").is_none());
-    assert!(headers.get("Request-Id").is_none());
+    assert!(!headers.contains_key("Auth-Token"));
+    assert!(!headers.contains_key("Request-Id"));
 }
 
 #[test]
@@ -382,3 +503,196 @@ fn test_serde_rename_in_query() {
     assert!(!url.contains("search_query"));
     assert!(!url.contains("max_results"));
 }
+
+#[test]
+fn test_invalid_header_value_is_rejected() {
+    #[derive(RequestBuilder)]
+    #[request_builder(method = "GET", path = "/api/data")]
+    struct GetData {
+        #[request_builder(header = "X-Note")]
+        note: String,
+    }
+
+    let request = GetDataBuilder::new()
+        .note("line one\nline two".to_string())
+        .build()
+        .unwrap();
+
+    match request.build_headers() {
+        Err(RestApiError::InvalidHeader { name, .. }) => assert_eq!(name, "X-Note"),
+        other => panic!("expected InvalidHeader, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_invalid_dynamic_header_is_reported_on_send() {
+    #[derive(RequestBuilder)]
+    #[request_builder(method = "GET", path = "/api/data")]
+    struct GetData;
+
+    let result = GetDataBuilder::new()
+        .http_client(MockHttpClient::new("ok"))
+        .base_url("https://api.example.com")
+        .header("X-Trace", "bad\r\nvalue")
+        .send();
+
+    assert!(matches!(result, Err(RestApiError::InvalidHeader { name, .. }) if name == "x-trace"));
+}
+
+#[test]
+fn test_vec_header_field_sends_each_value() {
+    #[derive(RequestBuilder)]
+    #[request_builder(method = "GET", path = "/api/data")]
+    struct GetData {
+        #[request_builder(header)]
+        accept: Vec<String>,
+        #[request_builder(header)]
+        forwarded: Option<Vec<String>>,
+    }
+
+    let request = GetDataBuilder::new()
+        .accept(vec!["application/json".to_string(), "text/plain".to_string()])
+        .build()
+        .unwrap();
+
+    let headers = request.build_headers().unwrap();
+    let accept: Vec<_> = headers.get_all("Accept").iter().collect();
+    assert_eq!(accept, ["application/json", "text/plain"]);
+    assert!(!headers.contains_key("Forwarded"));
+}
+
+#[test]
+fn test_append_header_keeps_every_value() {
+    // Client that checks the headers it receives
+    #[derive(Clone, Default)]
+    struct ForwardedCheckingClient;
+
+    impl derive_rest_api::HttpClient for ForwardedCheckingClient {
+        type Error = MockError;
+
+        fn send(
+            &self,
+            _method: &str,
+            _url: &str,
+            headers: HeaderMap,
+            _body: Option<Vec<u8>>,
+            _options: derive_rest_api::RequestOptions,
+        ) -> Result<derive_rest_api::http::Response<Vec<u8>>, Self::Error> {
+            let forwarded: Vec<_> = headers.get_all("Forwarded").iter().collect();
+            assert_eq!(forwarded, ["for=192.0.2.60", "for=198.51.100.17"]);
+            assert_eq!(headers.get("X-Trace").unwrap(), "second");
+            Ok(derive_rest_api::http::Response::new(Vec::new()))
+        }
+    }
+
+    #[derive(RequestBuilder)]
+    #[request_builder(method = "GET", path = "/api/data")]
+    struct GetData;
+
+    GetDataBuilder::new()
+        .http_client(ForwardedCheckingClient)
+        .base_url("https://api.example.com")
+        .append_header("Forwarded", "for=192.0.2.60")
+        .append_header("Forwarded", "for=198.51.100.17")
+        .header("X-Trace", "first")
+        .header("X-Trace", "second")
+        .send()
+        .unwrap();
+}
+
+#[test]
+fn test_content_type_and_accept_defaults() {
+    #[derive(Debug, serde::Deserialize)]
+    #[expect(dead_code)]
+    struct User {
+        id: u64,
+    }
+
+    #[derive(RequestBuilder, Serialize)]
+    #[request_builder(method = "POST", path = "/api/users", response = User)]
+    struct CreateUser {
+        #[request_builder(body)]
+        name: String,
+    }
+
+    #[derive(RequestBuilder)]
+    #[request_builder(method = "DELETE", path = "/api/users/{id}")]
+    struct DeleteUser {
+        id: u64,
+    }
+
+    let request = CreateUserBuilder::new().name("Alice".to_string()).build().unwrap();
+    let headers = request.build_headers().unwrap();
+    assert_eq!(headers.get("Content-Type").unwrap(), "application/json");
+    assert_eq!(headers.get("Accept").unwrap(), "application/json");
+
+    // No body and no response type: neither header is sent
+    let request = DeleteUserBuilder::new().id(1).build().unwrap();
+    assert_eq!(request.build_url().unwrap(), "/api/users/1");
+    assert!(request.build_headers().unwrap().is_empty());
+}
+
+#[test]
+fn test_header_field_overrides_content_type_default() {
+    #[derive(RequestBuilder, Serialize)]
+    #[request_builder(method = "POST", path = "/api/events")]
+    struct PublishEvent {
+        #[request_builder(body)]
+        payload: String,
+        #[request_builder(header)]
+        content_type: String,
+    }
+
+    let request = PublishEventBuilder::new()
+        .payload("{}".to_string())
+        .content_type("application/cloudevents+json".to_string())
+        .build()
+        .unwrap();
+
+    let headers = request.build_headers().unwrap();
+    let content_types: Vec<_> = headers.get_all("Content-Type").iter().collect();
+    assert_eq!(content_types, ["application/cloudevents+json"]);
+}
+
+#[test]
+fn test_timeouts_are_passed_to_client() {
+    use derive_rest_api::{RequestOptions, Timeouts};
+    use std::time::Duration;
+
+    // Client that checks the timeouts it receives
+    #[derive(Clone, Default)]
+    struct TimeoutCheckingClient;
+
+    impl derive_rest_api::HttpClient for TimeoutCheckingClient {
+        type Error = MockError;
+
+        fn send(
+            &self,
+            _method: &str,
+            _url: &str,
+            _headers: HeaderMap,
+            _body: Option<Vec<u8>>,
+            options: RequestOptions,
+        ) -> Result<derive_rest_api::http::Response<Vec<u8>>, Self::Error> {
+            let expected = Timeouts::new()
+                .with_connect(Duration::from_secs(2))
+                .with_read(Duration::from_secs(10))
+                .with_total(Duration::from_secs(30));
+            assert_eq!(options.timeouts, expected);
+            Ok(derive_rest_api::http::Response::new(Vec::new()))
+        }
+    }
+
+    #[derive(RequestBuilder)]
+    #[request_builder(method = "GET", path = "/api/data")]
+    struct GetData;
+
+    GetDataBuilder::new()
+        .http_client(TimeoutCheckingClient)
+        .base_url("https://api.example.com")
+        .connect_timeout(Duration::from_secs(2))
+        .read_timeout(Duration::from_secs(10))
+        .timeout(Duration::from_secs(30))
+        .send()
+        .unwrap();
+}
@@ -0,0 +1,112 @@
+use derive_rest_api::http::{HeaderMap, Response, StatusCode};
+use derive_rest_api::{
+    ApiClient, HttpClient, HttpErrorContext, ManualClock, RequestBuilder, RequestOptions, RestApiError, RetryPolicy,
+};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+// Mock HTTP client answering with queued statuses, then failing
+#[derive(Clone, Default)]
+struct FlakyClient {
+    statuses: Arc<Mutex<VecDeque<StatusCode>>>,
+}
+
+impl HttpClient for FlakyClient {
+    type Error = std::io::Error;
+
+    fn send(
+        &self,
+        _method: &str,
+        _url: &str,
+        _headers: HeaderMap,
+        _body: Option<Vec<u8>>,
+        _options: RequestOptions,
+    ) -> Result<Response<Vec<u8>>, Self::Error> {
+        match self.statuses.lock().unwrap().pop_front() {
+            Some(status) => Ok(Response::builder().status(status).body(b"busy".to_vec()).unwrap()),
+            None => Err(std::io::Error::new(std::io::ErrorKind::ConnectionReset, "connection reset")),
+        }
+    }
+}
+
+impl derive_rest_api::AsyncHttpClient for FlakyClient {
+    type Error = std::io::Error;
+
+    async fn send_async(
+        &self,
+        method: &str,
+        url: &str,
+        headers: HeaderMap,
+        body: Option<Vec<u8>>,
+        options: RequestOptions,
+    ) -> Result<Response<Vec<u8>>, Self::Error> {
+        self.send(method, url, headers, body, options)
+    }
+}
+
+#[derive(RequestBuilder)]
+#[request_builder(method = "PUT", path = "/notes/{id}")]
+struct PutNote {
+    id: u64,
+    #[request_builder(body)]
+    text: String,
+}
+
+#[derive(Clone, ApiClient)]
+#[api_client(base_url = "https://api.example.com", requests(PutNote))]
+struct NotesConfig;
+
+fn context(error: &RestApiError) -> HttpErrorContext {
+    match error {
+        RestApiError::HttpError { context: Some(context), .. } => (**context).clone(),
+        error => panic!("expected an HTTP error with context, got {:?}", error),
+    }
+}
+
+#[test]
+fn test_context_covers_all_attempts() {
+    let http = FlakyClient::default();
+    let clock = ManualClock::new();
+    let client = NotesClient::builder()
+        .http_client(http.clone())
+        .retry_policy(RetryPolicy::new(2).backoff(Duration::from_secs(1), Duration::from_secs(1)))
+        .clock(clock.clone())
+        .build()
+        .unwrap();
+
+    http.statuses.lock().unwrap().push_back(StatusCode::SERVICE_UNAVAILABLE);
+    let error = client.put_note().id(1).text("hello".to_string()).send().unwrap_err();
+
+    let context = context(&error);
+    let body_len = br#"{"text":"hello"}"#.len() as u64;
+    assert_eq!(context.elapsed, Some(Duration::from_secs(2)));
+    assert_eq!(context.attempts, 3);
+    assert_eq!(context.bytes_sent, 3 * body_len);
+    assert_eq!(context.bytes_received, Some(4));
+    assert_eq!(
+        error.to_string(),
+        format!("HTTP request failed: connection reset (after 2s and 3 attempts; {} bytes sent, 4 bytes received)", 3 * body_len)
+    );
+}
+
+#[tokio::test]
+async fn test_context_without_a_response() {
+    let clock = ManualClock::new();
+    let client = NotesAsyncClient::builder().http_client(FlakyClient::default()).clock(clock).build().unwrap();
+
+    let error = client.put_note().id(1).text(String::new()).send_async().await.unwrap_err();
+
+    let context = context(&error);
+    assert_eq!(context.elapsed, Some(Duration::ZERO));
+    assert_eq!(context.attempts, 1);
+    assert_eq!(context.bytes_received, None);
+    assert!(error.to_string().ends_with("(after 0ns and 1 attempt; 11 bytes sent, no response)"));
+}
+
+#[test]
+fn test_errors_built_outside_a_request_have_no_context() {
+    let error = RestApiError::http_error(std::io::Error::other("boom"));
+    assert!(matches!(error, RestApiError::HttpError { context: None, .. }));
+    assert_eq!(error.to_string(), "HTTP request failed: boom");
+}
@@ -0,0 +1,74 @@
+use derive_rest_api::http::{HeaderMap, Response};
+use derive_rest_api::{FaultInjectingClient, FaultInjectionError, HttpClient, RequestBuilder, RequestOptions, RestApiError};
+use std::error::Error;
+
+// An error like a backend's, caused by a lower-level IO error
+#[derive(Debug, thiserror::Error)]
+#[error("tls handshake failed")]
+struct HandshakeError {
+    #[source]
+    source: std::io::Error,
+}
+
+// Mock HTTP client whose every request fails its TLS handshake
+#[derive(Clone, Default)]
+struct HandshakeFailingClient;
+
+impl HttpClient for HandshakeFailingClient {
+    type Error = HandshakeError;
+
+    fn send(
+        &self,
+        _method: &str,
+        _url: &str,
+        _headers: HeaderMap,
+        _body: Option<Vec<u8>>,
+        _options: RequestOptions,
+    ) -> Result<Response<Vec<u8>>, Self::Error> {
+        Err(HandshakeError { source: std::io::Error::from(std::io::ErrorKind::ConnectionReset) })
+    }
+}
+
+#[derive(RequestBuilder)]
+#[request_builder(method = "GET", path = "/status")]
+struct GetStatus;
+
+fn send(http_client: impl HttpClient) -> RestApiError {
+    GetStatusBuilder::new().http_client(http_client).base_url("https://api.example.com").send().unwrap_err()
+}
+
+#[test]
+fn test_client_error_is_the_source() {
+    let error = send(HandshakeFailingClient);
+
+    let source = error.source().unwrap();
+    assert_eq!(source.to_string(), "tls handshake failed");
+    assert!(source.downcast_ref::<HandshakeError>().is_some());
+    assert!(error.http_source().unwrap().is::<HandshakeError>());
+}
+
+#[test]
+fn test_find_http_source_walks_the_chain() {
+    let error = send(HandshakeFailingClient);
+
+    assert!(error.find_http_source::<HandshakeError>().is_some());
+    let io_error = error.find_http_source::<std::io::Error>().unwrap();
+    assert_eq!(io_error.kind(), std::io::ErrorKind::ConnectionReset);
+    assert!(error.find_http_source::<std::fmt::Error>().is_none());
+}
+
+#[test]
+fn test_find_http_source_sees_through_client_wrappers() {
+    let error = send(FaultInjectingClient::new(HandshakeFailingClient, 7));
+
+    assert!(error.http_source().unwrap().is::<FaultInjectionError<HandshakeError>>());
+    assert!(error.find_http_source::<HandshakeError>().is_some());
+    assert!(error.find_http_source::<std::io::Error>().is_some());
+}
+
+#[test]
+fn test_other_errors_have_no_http_source() {
+    let error = RestApiError::missing_field("id");
+    assert!(error.http_source().is_none());
+    assert!(error.find_http_source::<std::io::Error>().is_none());
+}
@@ -0,0 +1,126 @@
+#[cfg(feature = "isahc")]
+mod isahc_tests {
+    use derive_rest_api::{IsahcClient, RequestBuilder};
+    use serde::Serialize;
+
+    #[test]
+    fn test_isahc_client_creation() {
+        let client = IsahcClient::new();
+        assert!(client.is_ok());
+
+        let client_default = IsahcClient::default();
+        drop(client_default);
+    }
+
+    #[test]
+    fn test_isahc_with_custom_client() {
+        use isahc::config::Configurable;
+
+        let isahc_client = isahc::HttpClient::builder()
+            .timeout(std::time::Duration::from_secs(30))
+            .build()
+            .unwrap();
+
+        let client = IsahcClient::with_client(isahc_client);
+        drop(client);
+    }
+
+    #[test]
+    fn test_isahc_with_protocol() {
+        use derive_rest_api::HttpProtocol;
+
+        for protocol in [HttpProtocol::Negotiate, HttpProtocol::Http1Only, HttpProtocol::Http2PriorKnowledge] {
+            assert!(IsahcClient::with_protocol(protocol).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_isahc_ipv4_only() {
+        use derive_rest_api::{HttpClient, IpFamily};
+        use std::io::{Read, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let _ = stream.read(&mut [0; 1024]).unwrap();
+            stream.write_all(b"HTTP/1.1 204 No Content\r\nConnection: close\r\n\r\n").unwrap();
+        });
+
+        let client = IsahcClient::new()
+            .unwrap()
+            .with_ip_family(IpFamily::Ipv4Only)
+            .with_connect_timeout(std::time::Duration::from_secs(1));
+        let response = client.send("GET", &url, Default::default(), None, Default::default()).unwrap();
+        assert_eq!(response.status(), 204);
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_isahc_resolve_pins_a_host() {
+        use derive_rest_api::HttpClient;
+        use std::io::{Read, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0; 1024];
+            let read = stream.read(&mut request).unwrap();
+            stream.write_all(b"HTTP/1.1 204 No Content\r\nConnection: close\r\n\r\n").unwrap();
+            String::from_utf8_lossy(&request[..read]).to_lowercase()
+        });
+
+        let client = IsahcClient::new().unwrap().resolve("api.internal.test", addr);
+        let url = format!("http://api.internal.test:{}/ping", addr.port());
+        let response = client.send("GET", &url, Default::default(), None, Default::default()).unwrap();
+
+        assert_eq!(response.status(), 204);
+        assert!(server.join().unwrap().contains("host: api.internal.test"));
+    }
+
+    #[derive(RequestBuilder, Serialize)]
+    #[request_builder(method = "POST", path = "/posts")]
+    struct CreatePost {
+        #[request_builder(body)]
+        title: String,
+        #[request_builder(body)]
+        #[serde(rename = "userId")]
+        user_id: u64,
+    }
+
+    #[test]
+    #[ignore] // Requires network connection
+    fn test_isahc_blocking_request() {
+        let request = CreatePostBuilder::new()
+            .title("Test Post".to_string())
+            .user_id(1)
+            .build()
+            .unwrap();
+
+        let client = IsahcClient::new().unwrap();
+
+        // Using JSONPlaceholder API for testing
+        let result = request.send_with_client(&client, "https://jsonplaceholder.typicode.com");
+
+        if let Ok(response) = result {
+            assert!(!response.is_empty());
+        }
+    }
+
+    #[tokio::test]
+    #[ignore] // Requires network connection
+    async fn test_isahc_async_request() {
+        let result = CreatePostBuilder::new()
+            .title("Test Post".to_string())
+            .user_id(1)
+            .async_http_client(IsahcClient::new().unwrap())
+            .base_url("https://jsonplaceholder.typicode.com")
+            .send_async()
+            .await;
+
+        if let Ok(response) = result {
+            assert!(!response.is_empty());
+        }
+    }
+}
@@ -0,0 +1,154 @@
+use derive_rest_api::http::{HeaderMap, Response};
+use derive_rest_api::{JsonApiDocument, RequestBuilder, RestApiError};
+use serde::Deserialize;
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug)]
+struct MockError;
+
+impl std::fmt::Display for MockError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "MockError")
+    }
+}
+
+impl std::error::Error for MockError {}
+
+// Mock HTTP client that records the request headers and returns a canned body
+#[derive(Clone, Default)]
+struct MockClient {
+    response: &'static str,
+    sent_headers: Arc<Mutex<Option<HeaderMap>>>,
+}
+
+impl derive_rest_api::HttpClient for MockClient {
+    type Error = MockError;
+
+    fn send(
+        &self,
+        _method: &str,
+        _url: &str,
+        headers: HeaderMap,
+        _body: Option<Vec<u8>>,
+        _options: derive_rest_api::RequestOptions,
+    ) -> Result<Response<Vec<u8>>, Self::Error> {
+        *self.sent_headers.lock().unwrap() = Some(headers);
+        Ok(Response::new(self.response.as_bytes().to_vec()))
+    }
+}
+
+const ARTICLE: &str = r#"{
+    "data": {
+        "type": "articles",
+        "id": "1",
+        "attributes": {"title": "JSON:API paints my bikeshed!"},
+        "relationships": {"author": {"data": {"type": "people", "id": "9"}}}
+    },
+    "included": [
+        {"type": "people", "id": "9", "attributes": {"name": "Dan"}, "links": {"self": "/people/9"}}
+    ],
+    "links": {"self": "https://example.com/articles/1"}
+}"#;
+
+const ARTICLES: &str = r#"{
+    "data": [
+        {"type": "articles", "id": "1", "attributes": {"title": "First"}},
+        {"type": "articles", "id": "2", "attributes": {"title": "Second"}}
+    ],
+    "links": {"next": {"href": "https://example.com/articles?page[number]=2"}},
+    "meta": {"total": 12}
+}"#;
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Article {
+    id: String,
+    title: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Person {
+    id: String,
+    #[serde(rename = "type")]
+    kind: String,
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct RelatedArticle {
+    relationships: serde_json::Value,
+}
+
+#[derive(RequestBuilder)]
+#[request_builder(method = "GET", path = "/articles/{id}", response = Article, response_format = "jsonapi")]
+struct GetArticle {
+    id: u64,
+}
+
+#[derive(RequestBuilder)]
+#[request_builder(method = "GET", path = "/articles", response = Vec<Article>, response_format = "jsonapi")]
+struct ListArticles;
+
+#[test]
+fn test_flattens_resource_and_sets_accept() {
+    let client = MockClient {
+        response: ARTICLE,
+        ..Default::default()
+    };
+
+    let document = GetArticleBuilder::new()
+        .id(1)
+        .http_client(client.clone())
+        .base_url("https://example.com")
+        .send()
+        .unwrap();
+    assert_eq!(
+        document.data,
+        Article {
+            id: "1".to_string(),
+            title: "JSON:API paints my bikeshed!".to_string()
+        }
+    );
+    assert_eq!(document.link("self"), Some("https://example.com/articles/1"));
+
+    let author = document.find_included("people", "9").unwrap();
+    let person: Person = author.deserialize().unwrap();
+    assert_eq!((person.id.as_str(), person.kind.as_str(), person.name.as_str()), ("9", "people", "Dan"));
+    assert_eq!(author.links["self"], "/people/9");
+
+    let headers = client.sent_headers.lock().unwrap().take().unwrap();
+    assert_eq!(headers.get("Accept").unwrap(), "application/vnd.api+json");
+}
+
+#[test]
+fn test_collection_with_links_and_meta() {
+    let client = MockClient {
+        response: ARTICLES,
+        ..Default::default()
+    };
+
+    let document = ListArticlesBuilder::new()
+        .http_client(client)
+        .base_url("https://example.com")
+        .send()
+        .unwrap();
+    let titles: Vec<_> = document.data.iter().map(|article| article.title.as_str()).collect();
+    assert_eq!(titles, ["First", "Second"]);
+    assert!(document.included.is_empty());
+    assert_eq!(document.link("next"), Some("https://example.com/articles?page[number]=2"));
+    assert_eq!(document.meta, Some(serde_json::json!({"total": 12})));
+}
+
+#[test]
+fn test_relationships_are_kept() {
+    let document = JsonApiDocument::<RelatedArticle>::from_slice(ARTICLE.as_bytes()).unwrap();
+    assert_eq!(document.data.relationships["author"]["data"]["id"], "9");
+}
+
+#[test]
+fn test_document_without_data() {
+    let result = JsonApiDocument::<Article>::from_slice(br#"{"errors":[{"status":"404","title":"Not Found"}]}"#);
+    assert!(matches!(result, Err(RestApiError::ResponseDeserializationError { .. })));
+
+    let document = JsonApiDocument::<Option<Article>>::from_slice(br#"{"data":null}"#).unwrap();
+    assert_eq!(document.data, None);
+}
@@ -0,0 +1,115 @@
+use derive_rest_api::http::{HeaderMap, Response};
+use derive_rest_api::{RequestBuilder, RestApiError};
+use serde::Deserialize;
+
+#[derive(Debug)]
+struct MockError;
+
+impl std::fmt::Display for MockError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "MockError")
+    }
+}
+
+impl std::error::Error for MockError {}
+
+// Mock HTTP client that returns a fixed body
+#[derive(Clone, Default)]
+struct MockClient {
+    body: &'static str,
+}
+
+impl derive_rest_api::HttpClient for MockClient {
+    type Error = MockError;
+
+    fn send(
+        &self,
+        _method: &str,
+        _url: &str,
+        _headers: HeaderMap,
+        _body: Option<Vec<u8>>,
+        _options: derive_rest_api::RequestOptions,
+    ) -> Result<Response<Vec<u8>>, Self::Error> {
+        Ok(Response::new(self.body.as_bytes().to_vec()))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[allow(dead_code)]
+struct Order {
+    id: u64,
+    items: Vec<Item>,
+}
+
+#[derive(Debug, Deserialize)]
+#[allow(dead_code)]
+struct Item {
+    sku: String,
+    price: f64,
+}
+
+#[derive(RequestBuilder)]
+#[request_builder(method = "GET", path = "/orders/{id}", response = Order, lenient_response)]
+struct GetOrder {
+    id: u64,
+}
+
+#[derive(RequestBuilder)]
+#[request_builder(method = "GET", path = "/orders/{id}", response = Order)]
+struct GetOrderStrict {
+    id: u64,
+}
+
+fn get_order(body: &'static str) -> Result<Order, RestApiError> {
+    GetOrderBuilder::new()
+        .id(7)
+        .http_client(MockClient { body })
+        .base_url("https://shop.example.com")
+        .send()
+}
+
+#[test]
+fn test_error_reports_path_and_snippet() {
+    let body = r#"{"id": 7, "items": [{"sku": "A1", "price": 9.5}, {"sku": "B2", "price": "12.00"}]}"#;
+
+    let Err(RestApiError::ResponseDeserializationError { context: Some(context), .. }) = get_order(body) else {
+        panic!("Expected a deserialization error with context");
+    };
+    assert_eq!(context.path, "items[1].price");
+    assert!(context.snippet.contains(r#""price": "12.00""#));
+
+    // The body is still available as untyped JSON
+    let value = context.value.unwrap();
+    assert_eq!(value["items"][1]["price"], "12.00");
+}
+
+#[test]
+fn test_missing_field_reports_containing_object() {
+    let error = get_order(r#"{"id": 7, "items": [{"sku": "A1"}]}"#).unwrap_err();
+
+    let message = error.to_string();
+    assert!(message.contains("missing field `price`"), "{}", message);
+    assert!(message.contains("at `items[0]`"), "{}", message);
+}
+
+#[test]
+fn test_invalid_json_has_no_value() {
+    let Err(RestApiError::ResponseDeserializationError { context: Some(context), .. }) =
+        get_order(r#"{"id": 7, "items": [oops]}"#)
+    else {
+        panic!("Expected a deserialization error with context");
+    };
+    assert_eq!(context.path, "items[0]");
+    assert!(context.value.is_none());
+}
+
+#[test]
+fn test_strict_errors_have_no_context() {
+    let result = GetOrderStrictBuilder::new()
+        .id(7)
+        .http_client(MockClient { body: r#"{"id": "seven"}"# })
+        .base_url("https://shop.example.com")
+        .send();
+
+    assert!(matches!(result, Err(RestApiError::ResponseDeserializationError { context: None, .. })));
+}
@@ -0,0 +1,119 @@
+use derive_rest_api::http::Response;
+use derive_rest_api::{ApiClient, Middleware, MiddlewareLayer, MockClient, RequestBuilder, RequestParts};
+use std::sync::{Arc, Mutex};
+
+#[derive(RequestBuilder)]
+#[request_builder(method = "GET", path = "/orders/{id}", response = ())]
+struct GetOrder {
+    id: u32,
+}
+
+#[derive(Clone, ApiClient)]
+#[api_client(base_url = "https://api.example.com", requests(GetOrder))]
+struct OrdersConfig;
+
+type Log = Arc<Mutex<Vec<String>>>;
+
+// Middleware recording the order its hooks run in
+struct Trace {
+    name: &'static str,
+    log: Log,
+}
+
+impl Middleware for Trace {
+    fn on_request(&self, _request: &mut RequestParts) {
+        self.log.lock().unwrap().push(format!("{} request", self.name));
+    }
+
+    fn on_response(&self, _method: &str, _url: &str, _response: &mut Response<Vec<u8>>) {
+        self.log.lock().unwrap().push(format!("{} response", self.name));
+    }
+
+    fn name(&self) -> &'static str {
+        self.name
+    }
+}
+
+fn trace(name: &'static str, log: &Log) -> Trace {
+    Trace { name, log: log.clone() }
+}
+
+fn layer(name: &'static str, priority: i32) -> MiddlewareLayer {
+    MiddlewareLayer { name, priority }
+}
+
+#[test]
+fn test_middleware_runs_by_priority() {
+    let log = Log::default();
+    let client = OrdersClient::builder()
+        .http_client(MockClient::new())
+        .middleware_with_priority(100, trace("signer", &log))
+        .middleware(trace("retry", &log))
+        .middleware_with_priority(-100, trace("logger", &log))
+        .build()
+        .unwrap();
+
+    client.get_order().id(1).send().unwrap();
+
+    assert_eq!(
+        *log.lock().unwrap(),
+        [
+            "logger request",
+            "retry request",
+            "signer request",
+            "signer response",
+            "retry response",
+            "logger response",
+        ]
+    );
+}
+
+#[test]
+fn test_equal_priorities_keep_insertion_order() {
+    let log = Log::default();
+    let client = OrdersClient::builder()
+        .http_client(MockClient::new())
+        .middleware(trace("auth", &log))
+        .middleware_with_priority(5, trace("signer", &log))
+        .middleware(trace("metrics", &log))
+        .middleware_with_priority(5, trace("audit", &log))
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        client.middleware_chain(),
+        [layer("auth", 0), layer("metrics", 0), layer("signer", 5), layer("audit", 5)]
+    );
+}
+
+#[test]
+fn test_request_middleware_joins_the_client_chain() {
+    let log = Log::default();
+    let client = OrdersClient::builder()
+        .http_client(MockClient::new())
+        .middleware_with_priority(-10, trace("logger", &log))
+        .middleware_with_priority(10, trace("signer", &log))
+        .build()
+        .unwrap();
+
+    let request = client.get_order().id(1).middleware(trace("cache", &log));
+    assert_eq!(request.middleware_chain(), [layer("logger", -10), layer("cache", 0), layer("signer", 10)]);
+    // The client's own chain is unchanged
+    assert_eq!(client.middleware_chain().len(), 2);
+
+    request.send().unwrap();
+    assert_eq!(log.lock().unwrap()[..3], ["logger request", "cache request", "signer request"]);
+}
+
+#[test]
+fn test_default_name_is_the_type_name() {
+    struct Noop;
+    impl Middleware for Noop {}
+
+    let client = OrdersClient::builder().http_client(MockClient::new()).middleware(Noop).build().unwrap();
+
+    let chain = client.middleware_chain();
+    assert_eq!(chain.len(), 1);
+    assert!(chain[0].name.ends_with("Noop"));
+    assert_eq!(chain[0].priority, 0);
+}
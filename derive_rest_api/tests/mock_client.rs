@@ -0,0 +1,122 @@
+use derive_rest_api::http::StatusCode;
+use derive_rest_api::{ApiClient, MockClient, RequestBuilder, RequestMatcher, RequestModifier, assert_request};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, PartialEq)]
+pub struct User {
+    id: u64,
+    name: String,
+}
+
+#[derive(RequestBuilder)]
+#[request_builder(method = "GET", path = "/users/{id}", response = User)]
+struct GetUser {
+    id: u64,
+}
+
+#[derive(RequestBuilder)]
+#[request_builder(method = "GET", path = "/users")]
+struct ListUsers {
+    #[request_builder(query)]
+    page: Option<u32>,
+    #[request_builder(query)]
+    search: Option<String>,
+}
+
+#[derive(RequestBuilder)]
+#[request_builder(method = "POST", path = "/users", response = User)]
+struct CreateUser {
+    #[request_builder(body)]
+    name: String,
+    #[request_builder(body)]
+    admin: bool,
+}
+
+#[derive(Clone, ApiClient)]
+#[api_client(base_url = "https://api.example.com", requests(GetUser, ListUsers, CreateUser))]
+struct UsersConfig;
+
+#[test]
+fn test_mock_client_records_and_matches_requests() {
+    let http = MockClient::new();
+    http.push_json(StatusCode::OK, &serde_json::json!({"id": 42, "name": "Ada"}));
+    http.push_json(StatusCode::CREATED, &serde_json::json!({"id": 43, "name": "Grace"}));
+    let client = UsersClient::<MockClient>::new_with_client(http.clone());
+
+    let user = client.get_user().id(42).send().unwrap();
+    assert_eq!(user, User { id: 42, name: "Ada".to_string() });
+    client.create_user().name("Grace".to_string()).admin(true).send().unwrap();
+    client
+        .list_users()
+        .page(2)
+        .search("a b&c".to_string())
+        .header("X-Trace", "abc")
+        .send()
+        .unwrap();
+
+    assert_eq!(http.requests().len(), 3);
+    assert_request!(http, GET "/users/{id}");
+    let request = assert_request!(
+        http,
+        POST "/users",
+        headers = [("content-type", "application/json")],
+        json = |body| body["name"] == "Grace" && body["admin"] == true,
+    );
+    assert_eq!(request.url, "https://api.example.com/users");
+
+    // Query parameters match in any order, after decoding
+    assert_request!(
+        http,
+        GET "/users",
+        query = [("search", "a b&c"), ("page", "2")],
+        headers = [("X-TRACE", "abc")],
+    );
+
+    http.clear();
+    assert!(http.requests().is_empty());
+}
+
+#[test]
+fn test_matcher_reports_mismatches() {
+    let http = MockClient::new();
+    let client = UsersClient::<MockClient>::new_with_client(http.clone());
+    client.list_users().page(2).send().unwrap();
+    let request = &http.requests()[0];
+
+    let mismatch = |matcher: RequestMatcher| matcher.mismatch(request).unwrap();
+    assert_eq!(mismatch(RequestMatcher::new("POST", "/users")), "method is GET");
+    assert_eq!(mismatch(RequestMatcher::new("GET", "/users/{id}")), "path is /users");
+    assert_eq!(
+        mismatch(RequestMatcher::new("GET", "/users").query([("page", "3")])),
+        "no query parameter page=3"
+    );
+    assert_eq!(
+        mismatch(RequestMatcher::new("GET", "/users").headers([("x-trace", "abc")])),
+        "no header x-trace"
+    );
+    assert_eq!(mismatch(RequestMatcher::new("GET", "/users").json(|_| true)), "no body");
+
+    // A parameter given once only satisfies one expectation
+    assert!(!RequestMatcher::new("GET", "/users").query([("page", "2"), ("page", "2")]).matches(request));
+}
+
+#[test]
+#[should_panic(expected = "expected a request matching DELETE /users/{id}, but received:\n  GET https://api.example.com/users/1: method is GET")]
+fn test_assert_request_panics_without_a_match() {
+    let http = MockClient::new();
+    http.push_json(StatusCode::OK, &serde_json::json!({"id": 1, "name": "Ada"}));
+    let client = UsersClient::<MockClient>::new_with_client(http.clone());
+    client.get_user().id(1).send().unwrap();
+
+    assert_request!(http, DELETE "/users/{id}");
+}
+
+#[tokio::test]
+async fn test_mock_client_records_async_requests() {
+    let http = MockClient::new();
+    http.push_json(StatusCode::OK, &serde_json::json!({"id": 7, "name": "Ada"}));
+    let client = UsersAsyncClient::<MockClient>::new_with_client(http.clone());
+
+    client.get_user().id(7).send_async().await.unwrap();
+    assert_request!(http, GET "/users/7");
+}
@@ -0,0 +1,92 @@
+use derive_rest_api::http::{HeaderMap, Response};
+use derive_rest_api::{Negotiated, RequestBuilder, RestRequest};
+use serde::Deserialize;
+use std::sync::{Arc, Mutex};
+
+// Mock HTTP client returning a canned body with the given Content-Type, if any
+#[derive(Clone, Default)]
+struct MockClient {
+    content_type: Option<&'static str>,
+    body: &'static [u8],
+    sent_headers: Arc<Mutex<Option<HeaderMap>>>,
+}
+
+impl MockClient {
+    fn new(content_type: Option<&'static str>, body: &'static [u8]) -> Self {
+        Self {
+            content_type,
+            body,
+            ..Default::default()
+        }
+    }
+}
+
+impl derive_rest_api::HttpClient for MockClient {
+    type Error = std::convert::Infallible;
+
+    fn send(
+        &self,
+        _method: &str,
+        _url: &str,
+        headers: HeaderMap,
+        _body: Option<Vec<u8>>,
+        _options: derive_rest_api::RequestOptions,
+    ) -> Result<Response<Vec<u8>>, Self::Error> {
+        *self.sent_headers.lock().unwrap() = Some(headers);
+        let mut response = Response::builder();
+        if let Some(content_type) = self.content_type {
+            response = response.header("Content-Type", content_type);
+        }
+        Ok(response.body(self.body.to_vec()).unwrap())
+    }
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Status {
+    healthy: bool,
+}
+
+#[derive(RequestBuilder)]
+#[request_builder(method = "GET", path = "/status", response = Status, response_format = "negotiate")]
+struct GetStatus {}
+
+fn get_status(client: MockClient) -> Negotiated<Status> {
+    GetStatusBuilder::new()
+        .http_client(client)
+        .base_url("https://api.example.com")
+        .send()
+        .unwrap()
+}
+
+#[test]
+fn test_json_responses_are_deserialized() {
+    let client = MockClient::new(Some("application/json; charset=utf-8"), br#"{"healthy": true}"#);
+    assert_eq!(get_status(client.clone()), Negotiated::Json(Status { healthy: true }));
+
+    let headers = client.sent_headers.lock().unwrap().take().unwrap();
+    assert_eq!(headers["Accept"], derive_rest_api::NEGOTIATE_ACCEPT);
+
+    // Without a Content-Type the body is still tried as JSON
+    let client = MockClient::new(None, br#"{"healthy": false}"#);
+    assert_eq!(get_status(client).into_json(), Some(Status { healthy: false }));
+}
+
+#[test]
+fn test_other_responses_fall_back_to_text_or_bytes() {
+    let client = MockClient::new(Some("text/html"), b"<h1>502 Bad Gateway</h1>");
+    assert_eq!(get_status(client), Negotiated::Text("<h1>502 Bad Gateway</h1>".to_string()));
+
+    // JSON that doesn't match the response type isn't an error either
+    let client = MockClient::new(Some("application/problem+json"), br#"{"title": "Maintenance"}"#);
+    assert_eq!(get_status(client), Negotiated::Text(r#"{"title": "Maintenance"}"#.to_string()));
+
+    let client = MockClient::new(Some("application/octet-stream"), &[0xff, 0xfe]);
+    assert_eq!(get_status(client), Negotiated::Bytes(vec![0xff, 0xfe]));
+}
+
+#[test]
+fn test_rest_request_parses_negotiated_responses() {
+    // Used for the responses of requests sent in a `Batch`
+    let response = Response::builder().header("Content-Type", "text/plain").body(b"OK".to_vec()).unwrap();
+    assert_eq!(GetStatus::parse_response(response).unwrap(), Negotiated::Text("OK".to_string()));
+}
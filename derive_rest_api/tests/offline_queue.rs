@@ -0,0 +1,213 @@
+use derive_rest_api::http::{HeaderMap, Response, StatusCode};
+use derive_rest_api::{
+    ApiClient, ConflictResolution, HttpClient, MockClient, OfflineQueue, QueuedRequest, RequestBuilder, RequestOptions,
+    RestApiError,
+};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// A `MockClient` that fails every request while offline
+#[derive(Clone, Default)]
+struct FlakyClient {
+    http: MockClient,
+    offline: Arc<AtomicBool>,
+}
+
+impl HttpClient for FlakyClient {
+    type Error = std::io::Error;
+
+    fn send(
+        &self,
+        method: &str,
+        url: &str,
+        headers: HeaderMap,
+        body: Option<Vec<u8>>,
+        options: RequestOptions,
+    ) -> Result<Response<Vec<u8>>, Self::Error> {
+        if self.offline.load(Ordering::SeqCst) {
+            return Err(std::io::Error::new(std::io::ErrorKind::NotConnected, "offline"));
+        }
+        Ok(self.http.send(method, url, headers, body, options).unwrap_or_else(|never| match never {}))
+    }
+}
+
+#[derive(RequestBuilder)]
+#[request_builder(method = "POST", path = "/jobs/{id}/notes", response = ())]
+struct AddNote {
+    id: u32,
+    #[request_builder(body)]
+    text: String,
+}
+
+#[derive(RequestBuilder)]
+#[request_builder(method = "GET", path = "/jobs", response = ())]
+struct ListJobs;
+
+#[derive(Clone, ApiClient)]
+#[api_client(base_url = "https://api.example.com", requests(AddNote, ListJobs))]
+struct JobsConfig;
+
+fn queue_file(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("derive_rest_api_{}_{}.json", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+fn client(http: &FlakyClient, queue: OfflineQueue) -> JobsClient<FlakyClient> {
+    JobsClient::builder().http_client(http.clone()).offline_queue(queue).build().unwrap()
+}
+
+fn note(client: &JobsClient<FlakyClient>, text: &str) -> Result<(), RestApiError> {
+    client.add_note().id(7).text(text.to_string()).send()
+}
+
+fn sent_bodies(http: &FlakyClient) -> Vec<String> {
+    let requests = http.http.requests().into_iter().filter(|request| request.method == "POST");
+    requests
+        .map(|request| {
+            let body: serde_json::Value = serde_json::from_str(request.body_text().unwrap()).unwrap();
+            body["text"].as_str().unwrap().to_string()
+        })
+        .collect()
+}
+
+#[test]
+fn test_mutations_are_queued_while_offline_and_replayed_in_order() {
+    let http = FlakyClient::default();
+    let queue = OfflineQueue::open(queue_file("offline_order")).unwrap();
+    let client = client(&http, queue.clone());
+
+    http.offline.store(true, Ordering::SeqCst);
+    assert!(matches!(note(&client, "first"), Err(RestApiError::QueuedOffline { id: 1 })));
+    assert!(matches!(note(&client, "second"), Err(RestApiError::QueuedOffline { id: 2 })));
+    // Reads fail as usual
+    assert!(matches!(client.list_jobs().send(), Err(RestApiError::HttpError { .. })));
+    assert_eq!(queue.len(), 2);
+
+    http.offline.store(false, Ordering::SeqCst);
+    note(&client, "third").unwrap();
+
+    assert!(queue.is_empty());
+    assert_eq!(sent_bodies(&http), ["first", "second", "third"]);
+}
+
+#[test]
+fn test_queue_survives_a_restart() {
+    let path = queue_file("offline_restart");
+    let http = FlakyClient::default();
+    http.offline.store(true, Ordering::SeqCst);
+    let before = client(&http, OfflineQueue::open(&path).unwrap());
+    note(&before, "saved").unwrap_err();
+    drop(before);
+
+    let queue = OfflineQueue::open(&path).unwrap();
+    let queued = queue.requests();
+    assert_eq!(queued.len(), 1);
+    assert_eq!((queued[0].method.as_str(), queued[0].path.as_str()), ("POST", "/jobs/7/notes"));
+    assert_eq!(queued[0].body.as_deref(), Some(&br#"{"text":"saved"}"#[..]));
+
+    http.offline.store(false, Ordering::SeqCst);
+    let after = client(&http, queue.clone());
+    assert_eq!(after.replay_offline_queue().unwrap(), 0);
+    assert_eq!(sent_bodies(&http), ["saved"]);
+    assert!(OfflineQueue::open(&path).unwrap().is_empty());
+}
+
+#[test]
+fn test_conflict_hook() {
+    let http = FlakyClient::default();
+    let hook = |request: &QueuedRequest, response: &Response<Vec<u8>>| {
+        match (response.status(), request.headers.contains_key("If-Match")) {
+            (StatusCode::CONFLICT, false) => {
+                let mut resend = request.clone();
+                resend.headers.insert("If-Match", "\"v2\"".parse().unwrap());
+                ConflictResolution::Resend(Box::new(resend))
+            }
+            (StatusCode::CONFLICT, true) => ConflictResolution::Keep,
+            _ => ConflictResolution::Discard,
+        }
+    };
+    let queue = OfflineQueue::open(queue_file("offline_conflict")).unwrap().on_conflict(hook);
+    let client = client(&http, queue.clone());
+    http.offline.store(true, Ordering::SeqCst);
+    note(&client, "a").unwrap_err();
+    note(&client, "b").unwrap_err();
+    http.offline.store(false, Ordering::SeqCst);
+
+    // "a" conflicts, is resent with an If-Match and conflicts again, so it's kept
+    for _ in 0..2 {
+        http.http.push_response(Response::builder().status(StatusCode::CONFLICT).body(Vec::new()).unwrap());
+    }
+    assert_eq!(client.replay_offline_queue().unwrap(), 2);
+    assert_eq!(queue.requests()[0].headers["If-Match"], "\"v2\"");
+
+    // A server error stops the replay too
+    http.http.push_response(Response::builder().status(StatusCode::SERVICE_UNAVAILABLE).body(Vec::new()).unwrap());
+    assert_eq!(client.replay_offline_queue().unwrap(), 2);
+
+    http.http.push_response(Response::builder().status(StatusCode::UNPROCESSABLE_ENTITY).body(Vec::new()).unwrap());
+    assert_eq!(client.replay_offline_queue().unwrap(), 0);
+    assert_eq!(sent_bodies(&http), ["a", "a", "a", "a", "b"]);
+}
+
+/// Returns the names of the headers of the first request in the queue's file
+fn saved_header_names(path: &PathBuf) -> Vec<String> {
+    let file: serde_json::Value = serde_json::from_slice(&std::fs::read(path).unwrap()).unwrap();
+    let headers = file["requests"][0]["headers"].as_array().unwrap();
+    headers.iter().map(|header| header[0].as_str().unwrap().to_string()).collect()
+}
+
+#[test]
+fn test_sensitive_headers_are_kept_out_of_the_file() {
+    let path = queue_file("offline_sensitive");
+    let http = FlakyClient::default();
+    let build = |queue: OfflineQueue| {
+        JobsClient::builder()
+            .http_client(http.clone())
+            .offline_queue(queue)
+            .default_header("Authorization", "Bearer secret")
+            .default_header("X-Api-Key", "key")
+            .default_header("X-Note", &b"caf\xe9"[..])
+            .build()
+            .unwrap()
+    };
+    http.offline.store(true, Ordering::SeqCst);
+    note(&build(OfflineQueue::open(&path).unwrap()), "private").unwrap_err();
+
+    let names = saved_header_names(&path);
+    assert!(names.contains(&"x-note".to_string()));
+    assert!(!names.contains(&"authorization".to_string()) && !names.contains(&"x-api-key".to_string()));
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        assert_eq!(std::fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+    }
+
+    // After a restart, other headers are intact and the credentials come from the client
+    let queue = OfflineQueue::open(&path).unwrap();
+    assert_eq!(queue.requests()[0].headers["x-note"].as_bytes(), b"caf\xe9");
+    http.offline.store(false, Ordering::SeqCst);
+    assert_eq!(build(queue).replay_offline_queue().unwrap(), 0);
+    let sent = &http.http.requests()[0];
+    assert_eq!(sent.header("authorization"), Some("Bearer secret"));
+    assert_eq!(sent.header("x-api-key"), Some("key"));
+}
+
+#[test]
+fn test_persisting_sensitive_headers_is_opt_in() {
+    let path = queue_file("offline_persisted");
+    let http = FlakyClient::default();
+    let queue = OfflineQueue::open(&path).unwrap().persist_sensitive_headers();
+    let client = JobsClient::builder()
+        .http_client(http.clone())
+        .offline_queue(queue)
+        .default_header("Authorization", "Bearer secret")
+        .build()
+        .unwrap();
+    http.offline.store(true, Ordering::SeqCst);
+    note(&client, "kept").unwrap_err();
+
+    assert!(saved_header_names(&path).contains(&"authorization".to_string()));
+    assert_eq!(OfflineQueue::open(&path).unwrap().requests()[0].headers["authorization"], "Bearer secret");
+}
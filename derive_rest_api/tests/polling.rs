@@ -0,0 +1,298 @@
+use derive_rest_api::http::{HeaderMap, Response, StatusCode};
+use derive_rest_api::{ApiClient, Credentials, ManualClock, RequestBuilder, RestApiError};
+use serde::Deserialize;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug)]
+struct MockError;
+
+impl std::fmt::Display for MockError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "MockError")
+    }
+}
+
+impl std::error::Error for MockError {}
+
+// Mock HTTP client that replays queued responses and records each request
+#[derive(Clone, Default)]
+struct MockClient {
+    responses: Arc<Mutex<VecDeque<Response<Vec<u8>>>>>,
+    requests: Arc<Mutex<Vec<(String, String, HeaderMap)>>>,
+}
+
+impl MockClient {
+    fn new(responses: Vec<Response<Vec<u8>>>) -> Self {
+        Self {
+            responses: Arc::new(Mutex::new(responses.into())),
+            ..Default::default()
+        }
+    }
+
+    fn requests(&self) -> Vec<(String, String, HeaderMap)> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    fn respond(&self, method: &str, url: &str, headers: HeaderMap) -> Response<Vec<u8>> {
+        self.requests
+            .lock()
+            .unwrap()
+            .push((method.to_string(), url.to_string(), headers));
+        self.responses
+            .lock()
+            .unwrap()
+            .pop_front()
+            .unwrap_or_else(|| json(r#"{"status":"running"}"#))
+    }
+}
+
+impl derive_rest_api::HttpClient for MockClient {
+    type Error = MockError;
+
+    fn send(
+        &self,
+        method: &str,
+        url: &str,
+        headers: HeaderMap,
+        _body: Option<Vec<u8>>,
+        _options: derive_rest_api::RequestOptions,
+    ) -> Result<Response<Vec<u8>>, Self::Error> {
+        Ok(self.respond(method, url, headers))
+    }
+}
+
+impl derive_rest_api::AsyncHttpClient for MockClient {
+    type Error = MockError;
+
+    async fn send_async(
+        &self,
+        method: &str,
+        url: &str,
+        headers: HeaderMap,
+        _body: Option<Vec<u8>>,
+        _options: derive_rest_api::RequestOptions,
+    ) -> Result<Response<Vec<u8>>, Self::Error> {
+        Ok(self.respond(method, url, headers))
+    }
+}
+
+fn json(body: &str) -> Response<Vec<u8>> {
+    Response::new(body.as_bytes().to_vec())
+}
+
+fn accepted(location: &str) -> Response<Vec<u8>> {
+    let mut response = json(r#"{"status":"queued"}"#);
+    *response.status_mut() = StatusCode::ACCEPTED;
+    response.headers_mut().insert("Location", location.parse().unwrap());
+    response
+}
+
+#[derive(Debug, Deserialize)]
+struct Operation {
+    status: String,
+}
+
+#[derive(RequestBuilder)]
+#[request_builder(
+    method = "POST",
+    path = "/exports",
+    response = Operation,
+    poll(done_values("succeeded", "failed"), interval_ms = 1)
+)]
+struct StartExport {
+    #[request_builder(body)]
+    format: String,
+}
+
+#[derive(RequestBuilder)]
+#[request_builder(
+    method = "POST",
+    path = "/jobs",
+    poll(status_field = "job.state", done_values("done"), interval_ms = 5, timeout_ms = 20)
+)]
+struct StartJob {
+    #[request_builder(body)]
+    name: String,
+}
+
+#[derive(RequestBuilder)]
+#[request_builder(
+    method = "POST",
+    path = "/backups",
+    poll(done_values("done"), interval_ms = 60000, timeout_ms = 600000)
+)]
+struct StartBackup;
+
+#[derive(Clone, ApiClient)]
+#[api_client(base_url = "https://api.example.com", requests(StartBackup))]
+struct BackupConfig;
+
+fn backup_client(http: &MockClient, clock: &ManualClock) -> BackupClient<MockClient> {
+    BackupClient::builder()
+        .http_client(http.clone())
+        .clock(clock.clone())
+        .credentials(|| Ok(Credentials::bearer("secret")))
+        .default_header("X-Api-Key", "key")
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn test_polls_location_until_done() {
+    let client = MockClient::new(vec![
+        accepted("/operations/42"),
+        json(r#"{"status":"running"}"#),
+        json(r#"{"status":"succeeded"}"#),
+    ]);
+
+    let operation = StartExportBuilder::new()
+        .format("csv".to_string())
+        .http_client(client.clone())
+        .base_url("https://api.example.com/v1")
+        .send_and_wait()
+        .unwrap();
+    assert_eq!(operation.status, "succeeded");
+
+    let requests = client.requests();
+    assert_eq!(requests.len(), 3);
+    assert_eq!(requests[0].0, "POST");
+    assert_eq!(requests[0].1, "https://api.example.com/v1/exports");
+    assert!(requests[0].2.contains_key("Content-Type"));
+    for (method, url, headers) in &requests[1..] {
+        assert_eq!(method, "GET");
+        assert_eq!(url, "https://api.example.com/operations/42");
+        assert!(!headers.contains_key("Content-Type"));
+    }
+}
+
+#[test]
+fn test_done_initial_response_is_not_polled() {
+    let client = MockClient::new(vec![json(r#"{"status":"failed"}"#)]);
+
+    let operation = StartExportBuilder::new()
+        .format("csv".to_string())
+        .http_client(client.clone())
+        .base_url("https://api.example.com")
+        .send_and_wait()
+        .unwrap();
+    assert_eq!(operation.status, "failed");
+    assert_eq!(client.requests().len(), 1);
+}
+
+#[test]
+fn test_polls_request_url_and_nested_status_field() {
+    let client = MockClient::new(vec![
+        json(r#"{"job":{"state":"pending"}}"#),
+        json(r#"{"job":{"state":"done"}}"#),
+    ]);
+
+    let body = StartJobBuilder::new()
+        .name("reindex".to_string())
+        .http_client(client.clone())
+        .base_url("https://api.example.com")
+        .send_and_wait()
+        .unwrap();
+    assert_eq!(body, br#"{"job":{"state":"done"}}"#);
+
+    let requests = client.requests();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[1].0, "GET");
+    assert_eq!(requests[1].1, "https://api.example.com/jobs");
+}
+
+#[test]
+fn test_error_status_stops_polling() {
+    let mut not_found = json(r#"{"error":"gone"}"#);
+    *not_found.status_mut() = StatusCode::NOT_FOUND;
+    let client = MockClient::new(vec![accepted("operations/7"), not_found]);
+
+    let body = StartJobBuilder::new()
+        .name("reindex".to_string())
+        .http_client(client.clone())
+        .base_url("https://api.example.com/v1")
+        .send_and_wait()
+        .unwrap();
+    assert_eq!(body, br#"{"error":"gone"}"#);
+
+    let requests = client.requests();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[1].1, "https://api.example.com/v1/operations/7");
+}
+
+#[test]
+fn test_times_out() {
+    let client = MockClient::default();
+
+    let result = StartJobBuilder::new()
+        .name("reindex".to_string())
+        .http_client(client)
+        .base_url("https://api.example.com")
+        .send_and_wait();
+    match result {
+        Err(RestApiError::PollTimeout { timeout }) => {
+            assert_eq!(timeout, std::time::Duration::from_millis(20));
+        }
+        other => panic!("Expected PollTimeout, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_send_and_wait_async() {
+    let client = MockClient::new(vec![
+        accepted("https://operations.example.com/42"),
+        json(r#"{"status":"running"}"#),
+        json(r#"{"status":"succeeded"}"#),
+    ]);
+
+    let operation = StartExportBuilder::new()
+        .format("csv".to_string())
+        .async_http_client(client.clone())
+        .base_url("https://api.example.com")
+        .send_and_wait_async()
+        .await
+        .unwrap();
+    assert_eq!(operation.status, "succeeded");
+
+    let requests = client.requests();
+    assert_eq!(requests.len(), 3);
+    assert_eq!(requests[2].1, "https://operations.example.com/42");
+}
+
+#[test]
+fn test_polling_waits_on_the_client_clock() {
+    let http = MockClient::new(vec![
+        accepted("/backups/1"),
+        json(r#"{"status":"running"}"#),
+        json(r#"{"status":"done"}"#),
+    ]);
+    let clock = ManualClock::new();
+    backup_client(&http, &clock).start_backup().send_and_wait().unwrap();
+    assert_eq!(clock.elapsed(), std::time::Duration::from_secs(120));
+
+    // Polls go through the transport, so they get the client's credentials
+    for (_, _, headers) in http.requests() {
+        assert_eq!(headers["Authorization"], "Bearer secret");
+    }
+
+    let clock = ManualClock::new();
+    let result = backup_client(&MockClient::default(), &clock).start_backup().send_and_wait();
+    assert!(matches!(result, Err(RestApiError::PollTimeout { .. })));
+    assert_eq!(clock.elapsed(), std::time::Duration::from_secs(600));
+}
+
+#[test]
+fn test_cross_origin_location_is_polled_without_credentials() {
+    let http = MockClient::new(vec![
+        accepted("https://storage.example.net/backups/1"),
+        json(r#"{"status":"done"}"#),
+    ]);
+    backup_client(&http, &ManualClock::new()).start_backup().send_and_wait().unwrap();
+
+    let requests = http.requests();
+    assert_eq!(requests[0].2["Authorization"], "Bearer secret");
+    assert_eq!(requests[0].2["X-Api-Key"], "key");
+    assert_eq!(requests[1].1, "https://storage.example.net/backups/1");
+    assert!(!requests[1].2.contains_key("Authorization"));
+    assert!(!requests[1].2.contains_key("X-Api-Key"));
+}
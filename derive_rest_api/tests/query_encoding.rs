@@ -0,0 +1,241 @@
+use derive_rest_api::http::StatusCode;
+use derive_rest_api::{
+    ApiClient, MockClient, QueryEncoder, QueryOrder, RequestBuilder, RestApiError, SpaceEncoding, UrlEncodedEncoder,
+};
+use serde::Serialize;
+use serde_json::Value;
+
+#[derive(Debug, Clone, Serialize)]
+struct Range {
+    from: u32,
+    to: u32,
+}
+
+#[derive(RequestBuilder)]
+#[request_builder(method = "GET", path = "/search", query_encoder = "urlencoded", static_query("v" = "2"))]
+struct Search {
+    #[request_builder(query)]
+    q: String,
+    #[request_builder(query)]
+    tags: Vec<String>,
+    #[request_builder(query)]
+    page: Option<u32>,
+}
+
+#[derive(RequestBuilder)]
+#[request_builder(method = "GET", path = "/events", query_encoder = "urlencoded")]
+struct ListEvents {
+    #[request_builder(query)]
+    range: Range,
+}
+
+#[derive(RequestBuilder)]
+#[request_builder(method = "GET", path = "/items", response = ())]
+struct ListItems {
+    #[request_builder(query)]
+    ids: Vec<u32>,
+}
+
+#[derive(RequestBuilder)]
+#[request_builder(method = "GET", path = "/reports", response = (), query_encoder = "serde_qs")]
+struct ListReports {
+    #[request_builder(query)]
+    ids: Vec<u32>,
+}
+
+/// Joins arrays with commas
+struct CommaSeparated;
+
+impl QueryEncoder for CommaSeparated {
+    fn encode(&self, params: &[(String, Value)]) -> Result<String, RestApiError> {
+        let pairs = params.iter().map(|(name, value)| {
+            let value = match value {
+                Value::Array(items) => items.iter().map(Value::to_string).collect::<Vec<_>>().join(","),
+                value => value.to_string(),
+            };
+            format!("{}={}", name, value)
+        });
+        Ok(pairs.collect::<Vec<_>>().join("&"))
+    }
+}
+
+#[derive(RequestBuilder)]
+#[request_builder(method = "GET", path = "/users", query_encoder = CommaSeparated)]
+struct ListUsers {
+    #[request_builder(query)]
+    ids: Vec<u32>,
+    #[request_builder(query)]
+    active: bool,
+}
+
+#[derive(Clone, ApiClient)]
+#[api_client(base_url = "https://api.example.com", requests(ListItems, ListReports))]
+struct ItemsConfig;
+
+#[test]
+fn test_urlencoded_encoder_writes_flat_pairs() {
+    let search = SearchBuilder::new()
+        .q("rust lang".to_string())
+        .tags(vec!["a&b".to_string(), "c".to_string()])
+        .build()
+        .unwrap();
+
+    assert_eq!(search.build_url().unwrap(), "/search?v=2&q=rust+lang&tags=a%26b&tags=c");
+}
+
+#[test]
+fn test_urlencoded_encoder_rejects_nested_values() {
+    let request = ListEventsBuilder::new().range(Range { from: 1, to: 2 }).build().unwrap();
+
+    let error = request.build_url().unwrap_err();
+    assert!(matches!(error, RestApiError::QueryEncodingError { .. }));
+    assert!(error.to_string().contains("'range' is nested"));
+}
+
+#[test]
+fn test_custom_encoder() {
+    let request = ListUsersBuilder::new().ids(vec![1, 2, 3]).active(true).build().unwrap();
+
+    assert_eq!(request.build_url().unwrap(), "/users?ids=1,2,3&active=true");
+}
+
+#[test]
+fn test_client_encoder_applies_to_structs_without_their_own() {
+    let http = MockClient::new();
+    http.push_json(StatusCode::OK, &serde_json::json!({}));
+    http.push_json(StatusCode::OK, &serde_json::json!({}));
+    let client = ItemsClient::builder()
+        .http_client(http.clone())
+        .query_encoder(UrlEncodedEncoder::new())
+        .build()
+        .unwrap();
+
+    client.list_items().ids(vec![1, 2]).send().unwrap();
+    client.list_reports().ids(vec![1, 2]).send().unwrap();
+
+    let urls: Vec<_> = http.requests().into_iter().map(|request| request.url).collect();
+    assert_eq!(
+        urls,
+        [
+            "https://api.example.com/items?ids=1&ids=2",
+            "https://api.example.com/reports?ids[0]=1&ids[1]=2",
+        ]
+    );
+}
+
+mod qs {
+    pub fn form_encoding() -> serde_qs::Config {
+        serde_qs::Config::new().use_form_encoding(true)
+    }
+}
+
+#[derive(RequestBuilder)]
+#[request_builder(method = "GET", path = "/tags", query_config_fn = qs::form_encoding)]
+struct ListTags {
+    #[request_builder(query)]
+    ids: Vec<u32>,
+}
+
+#[derive(RequestBuilder)]
+#[request_builder(method = "GET", path = "/labels", query_config = "qs::form_encoding()")]
+struct ListLabels {
+    #[request_builder(query)]
+    ids: Vec<u32>,
+}
+
+#[test]
+fn test_query_config_fn_and_query_config() {
+    let tags = ListTagsBuilder::new().ids(vec![1, 2]).build().unwrap();
+    let labels = ListLabelsBuilder::new().ids(vec![1, 2]).build().unwrap();
+
+    assert_eq!(tags.build_url().unwrap(), "/tags?ids%5B0%5D=1&ids%5B1%5D=2");
+    assert_eq!(labels.build_url().unwrap(), "/labels?ids%5B0%5D=1&ids%5B1%5D=2");
+}
+
+#[derive(RequestBuilder)]
+#[request_builder(
+    method = "GET",
+    path = "/prices",
+    query_encoder = derive_rest_api::UrlEncodedEncoder::rfc3986().literal(":,")
+)]
+struct GetPrices {
+    #[request_builder(query)]
+    window: String,
+    #[request_builder(query)]
+    note: String,
+}
+
+#[test]
+fn test_space_encoding_and_literal_characters() {
+    let params = [
+        ("window".to_string(), Value::from("09:00,17:00")),
+        ("note".to_string(), Value::from("a b~*&c")),
+    ];
+
+    assert_eq!(
+        UrlEncodedEncoder::new().encode(&params).unwrap(),
+        "window=09%3A00%2C17%3A00&note=a+b%7E*%26c"
+    );
+    assert_eq!(
+        UrlEncodedEncoder::new().spaces(SpaceEncoding::Percent).literal(":&").encode(&params).unwrap(),
+        "window=09:00%2C17:00&note=a%20b%7E*%26c"
+    );
+
+    let prices = GetPricesBuilder::new()
+        .window("09:00,17:00".to_string())
+        .note("a b~*".to_string())
+        .build()
+        .unwrap();
+    assert_eq!(prices.build_url().unwrap(), "/prices?window=09:00,17:00&note=a%20b~%2A");
+}
+
+#[derive(RequestBuilder)]
+#[request_builder(method = "GET", path = "/orders", query_order = "sorted", static_query("limit" = "10"))]
+struct ListOrders {
+    #[request_builder(query)]
+    status: String,
+    #[request_builder(query)]
+    ids: Vec<u32>,
+    #[request_builder(query)]
+    after: Option<String>,
+}
+
+#[derive(RequestBuilder)]
+#[request_builder(method = "GET", path = "/invoices", response = ())]
+struct ListInvoices {
+    #[request_builder(query)]
+    status: String,
+    #[request_builder(query)]
+    customer: String,
+}
+
+#[derive(Clone, ApiClient)]
+#[api_client(base_url = "https://api.example.com", requests(ListInvoices))]
+struct BillingConfig;
+
+#[test]
+fn test_sorted_query_order() {
+    let orders = ListOrdersBuilder::new()
+        .status("open".to_string())
+        .ids(vec![3, 1, 2])
+        .after("x".to_string())
+        .build()
+        .unwrap();
+
+    assert_eq!(orders.build_url().unwrap(), "/orders?after=x&ids[0]=3&ids[1]=1&ids[2]=2&limit=10&status=open");
+}
+
+#[test]
+fn test_client_query_order() {
+    let http = MockClient::new();
+    http.push_json(StatusCode::OK, &serde_json::json!({}));
+    let client = BillingClient::builder()
+        .http_client(http.clone())
+        .query_order(QueryOrder::Sorted)
+        .build()
+        .unwrap();
+
+    client.list_invoices().status("paid".to_string()).customer("c1".to_string()).send().unwrap();
+
+    assert_eq!(http.requests()[0].url, "https://api.example.com/invoices?customer=c1&status=paid");
+}
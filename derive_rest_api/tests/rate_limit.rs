@@ -0,0 +1,211 @@
+use derive_rest_api::http::{HeaderMap, Response};
+use derive_rest_api::{ApiClient, RateLimitInfo, RateLimitState, RequestBuilder};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+#[derive(Debug)]
+struct MockError;
+
+impl std::fmt::Display for MockError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "MockError")
+    }
+}
+
+impl std::error::Error for MockError {}
+
+// Mock HTTP client that spends one request of a 60 request budget per call,
+// except on /health, which reports no rate limit
+#[derive(Clone, Default)]
+struct MockClient {
+    used: Arc<AtomicU64>,
+}
+
+fn respond(used: &AtomicU64, url: &str) -> Response<Vec<u8>> {
+    if url.ends_with("/health") {
+        return Response::new(b"ok".to_vec());
+    }
+    let used = used.fetch_add(1, Ordering::SeqCst) + 1;
+    Response::builder()
+        .header("X-RateLimit-Limit", "60")
+        .header("X-RateLimit-Remaining", (60 - used).to_string())
+        .header("X-RateLimit-Reset", "30")
+        .body(b"ok".to_vec())
+        .unwrap()
+}
+
+impl derive_rest_api::HttpClient for MockClient {
+    type Error = MockError;
+
+    fn send(
+        &self,
+        _method: &str,
+        url: &str,
+        _headers: HeaderMap,
+        _body: Option<Vec<u8>>,
+        _options: derive_rest_api::RequestOptions,
+    ) -> Result<Response<Vec<u8>>, Self::Error> {
+        Ok(respond(&self.used, url))
+    }
+}
+
+impl derive_rest_api::AsyncHttpClient for MockClient {
+    type Error = MockError;
+
+    async fn send_async(
+        &self,
+        _method: &str,
+        url: &str,
+        _headers: HeaderMap,
+        _body: Option<Vec<u8>>,
+        _options: derive_rest_api::RequestOptions,
+    ) -> Result<Response<Vec<u8>>, Self::Error> {
+        Ok(respond(&self.used, url))
+    }
+}
+
+#[derive(RequestBuilder)]
+#[request_builder(method = "GET", path = "/users/{id}")]
+struct GetUser {
+    id: u64,
+}
+
+#[derive(RequestBuilder)]
+#[request_builder(method = "GET", path = "/health")]
+struct GetHealth;
+
+#[derive(Clone, ApiClient)]
+#[api_client(base_url = "https://api.example.com", requests(GetUser, GetHealth))]
+struct UsersConfig;
+
+#[test]
+fn test_client_tracks_latest_rate_limit() {
+    let client = UsersClient::<MockClient>::new_with_client(MockClient::default());
+    assert_eq!(client.rate_limit_state(), None);
+
+    client.get_user().id(1).send().unwrap();
+    client.get_user().id(2).send().unwrap();
+    let expected = RateLimitInfo {
+        limit: Some(60),
+        remaining: Some(58),
+        reset: Some(Duration::from_secs(30)),
+    };
+    assert_eq!(client.rate_limit_state(), Some(expected));
+
+    // Responses without the headers keep the last known state, which clones share
+    client.get_health().send().unwrap();
+    assert_eq!(client.clone().rate_limit_state(), Some(expected));
+}
+
+#[tokio::test]
+async fn test_async_client_tracks_latest_rate_limit() {
+    let client = UsersAsyncClient::<MockClient>::new_with_client(MockClient::default());
+    client.get_user().id(1).send_async().await.unwrap();
+
+    let info = client.rate_limit_state().unwrap();
+    assert_eq!(info.remaining, Some(59));
+    assert!(!info.is_exhausted());
+}
+
+#[test]
+fn test_info_from_raw_response() {
+    let response = GetUserBuilder::new()
+        .id(1)
+        .http_client(MockClient::default())
+        .base_url("https://api.example.com")
+        .send_raw()
+        .unwrap();
+
+    let info = RateLimitInfo::from_response(&response).unwrap();
+    assert_eq!(info.limit, Some(60));
+    assert_eq!(info.remaining, Some(59));
+}
+
+#[test]
+fn test_draft_headers() {
+    let mut headers = HeaderMap::new();
+    assert_eq!(RateLimitInfo::from_headers(&headers), None);
+
+    headers.insert("RateLimit-Limit", "100, 100;w=60".parse().unwrap());
+    headers.insert("RateLimit-Remaining", "0".parse().unwrap());
+    headers.insert("RateLimit-Reset", "12".parse().unwrap());
+    // The draft headers take precedence over the legacy ones
+    headers.insert("X-RateLimit-Limit", "5000".parse().unwrap());
+
+    let info = RateLimitInfo::from_headers(&headers).unwrap();
+    assert_eq!(info.limit, Some(100));
+    assert_eq!(info.reset, Some(Duration::from_secs(12)));
+    assert!(info.is_exhausted());
+}
+
+#[test]
+fn test_timestamp_reset() {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+    let mut headers = HeaderMap::new();
+    headers.insert("X-RateLimit-Reset", (now + 120).to_string().parse().unwrap());
+
+    let reset = RateLimitInfo::from_headers(&headers).unwrap().reset.unwrap();
+    assert!(reset <= Duration::from_secs(120) && reset >= Duration::from_secs(110), "{:?}", reset);
+
+    // A reset in the past has already happened
+    headers.insert("X-RateLimit-Reset", (now - 10).to_string().parse().unwrap());
+    assert_eq!(RateLimitInfo::from_headers(&headers).unwrap().reset, Some(Duration::ZERO));
+}
+
+// Mock HTTP client whose one second window allows three requests
+#[derive(Clone, Default)]
+struct QuotaClient {
+    used: Arc<AtomicU64>,
+}
+
+impl derive_rest_api::HttpClient for QuotaClient {
+    type Error = MockError;
+
+    fn send(
+        &self,
+        _method: &str,
+        _url: &str,
+        _headers: HeaderMap,
+        _body: Option<Vec<u8>>,
+        _options: derive_rest_api::RequestOptions,
+    ) -> Result<Response<Vec<u8>>, Self::Error> {
+        let used = self.used.fetch_add(1, Ordering::SeqCst) % 3 + 1;
+        Ok(Response::builder()
+            .header("RateLimit-Remaining", (3 - used).to_string())
+            .header("RateLimit-Reset", "1")
+            .body(b"ok".to_vec())
+            .unwrap())
+    }
+}
+
+#[test]
+fn test_quota_throttle_waits_for_reset() {
+    let client = UsersClient::<QuotaClient>::new_with_client(QuotaClient::default()).with_quota_throttle(1);
+
+    let started = Instant::now();
+    client.get_user().id(1).send().unwrap();
+    client.get_user().id(2).send().unwrap();
+    assert!(started.elapsed() < Duration::from_millis(500));
+
+    // One request left is at the threshold, so the next request waits out the window
+    assert_eq!(client.rate_limit_state().unwrap().remaining, Some(1));
+    client.get_user().id(3).send().unwrap();
+    assert!(started.elapsed() >= Duration::from_millis(900), "{:?}", started.elapsed());
+}
+
+#[test]
+fn test_quota_throttle_is_opt_in() {
+    let client = UsersClient::<QuotaClient>::new_with_client(QuotaClient::default());
+
+    let started = Instant::now();
+    for id in 0..4 {
+        client.get_user().id(id).send().unwrap();
+    }
+    assert!(started.elapsed() < Duration::from_millis(500));
+
+    let state = RateLimitState::new();
+    state.update(client.get_user().id(5).send_raw().unwrap().headers());
+    assert_eq!(state.delay(), Duration::ZERO);
+    assert!(state.clone().throttle_below(3).delay() > Duration::from_millis(500));
+}
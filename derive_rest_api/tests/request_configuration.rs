@@ -24,16 +24,16 @@ impl derive_rest_api::HttpClient for MockHttpClient {
         &self,
         _method: &str,
         _url: &str,
-        headers: std::collections::HashMap<String, String>,
+        headers: derive_rest_api::http::HeaderMap,
         _body: Option<Vec<u8>>,
-        _timeout: Option<std::time::Duration>,
-    ) -> Result<Vec<u8>, Self::Error> {
+        _options: derive_rest_api::RequestOptions,
+    ) -> Result<derive_rest_api::http::Response<Vec<u8>>, Self::Error> {
         // Verify headers were set
         assert!(headers.contains_key("X-API-Key"));
         assert!(headers.contains_key("User-Agent"));
         assert_eq!(headers.get("X-API-Key").unwrap(), "test_api_key_123");
         assert_eq!(headers.get("User-Agent").unwrap(), "my-app/1.0");
-        Ok(vec![])
+        Ok(derive_rest_api::http::Response::new(vec![]))
     }
 }
 
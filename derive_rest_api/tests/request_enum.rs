@@ -0,0 +1,137 @@
+use derive_rest_api::{RequestBuilder, RequestEnum};
+use serde::Serialize;
+use derive_rest_api::http::HeaderMap;
+use std::sync::{Arc, Mutex};
+
+// Mock error type for testing
+#[derive(Debug)]
+struct MockError;
+
+impl std::fmt::Display for MockError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "MockError")
+    }
+}
+
+impl std::error::Error for MockError {}
+
+// (method, url, body) of a request seen by the mock client
+type RecordedRequest = (String, String, Option<Vec<u8>>);
+
+// Mock HTTP client that records every request it receives
+#[derive(Clone, Default)]
+struct RecordingClient {
+    requests: Arc<Mutex<Vec<RecordedRequest>>>,
+}
+
+impl derive_rest_api::HttpClient for RecordingClient {
+    type Error = MockError;
+
+    fn send(
+        &self,
+        method: &str,
+        url: &str,
+        _headers: HeaderMap,
+        body: Option<Vec<u8>>,
+        _options: derive_rest_api::RequestOptions,
+    ) -> Result<derive_rest_api::http::Response<Vec<u8>>, Self::Error> {
+        self.requests
+            .lock()
+            .unwrap()
+            .push((method.to_string(), url.to_string(), body));
+        Ok(derive_rest_api::http::Response::new(b"ok".to_vec()))
+    }
+}
+
+#[derive(RequestBuilder, Serialize)]
+#[request_builder(method = "DELETE", path = "/users/{id}")]
+struct DeleteUser {
+    id: u64,
+}
+
+#[derive(RequestEnum)]
+enum Job {
+    #[request_builder(method = "POST", path = "/users")]
+    CreateUser {
+        #[request_builder(body)]
+        name: String,
+        #[request_builder(body)]
+        email: Option<String>,
+    },
+    #[request_builder(method = "GET", path = "/users/{id}/posts")]
+    ListPosts {
+        id: u64,
+        #[request_builder(query)]
+        page: Option<u32>,
+        #[request_builder(header = "X-Trace-Id")]
+        trace_id: String,
+    },
+    #[request_builder(method = "POST", path = "/maintenance/reindex")]
+    Reindex,
+    DeleteUser(DeleteUser),
+}
+
+#[test]
+fn test_named_variant_builds_request() {
+    let job = Job::ListPosts {
+        id: 42,
+        page: Some(3),
+        trace_id: "abc".to_string(),
+    };
+
+    assert_eq!(job.build_url().unwrap(), "/users/42/posts?page=3");
+    assert_eq!(job.build_headers().unwrap().get("X-Trace-Id").unwrap(), "abc");
+    assert!(job.build_body().unwrap().is_none());
+}
+
+#[test]
+fn test_named_variant_body() {
+    let job = Job::CreateUser {
+        name: "Alice".to_string(),
+        email: None,
+    };
+
+    assert_eq!(job.build_url().unwrap(), "/users");
+    assert_eq!(job.build_body().unwrap().unwrap(), br#"{"name":"Alice"}"#);
+}
+
+#[test]
+fn test_unit_and_newtype_variants() {
+    assert_eq!(Job::Reindex.build_url().unwrap(), "/maintenance/reindex");
+    assert!(Job::Reindex.build_body().unwrap().is_none());
+
+    let job = Job::DeleteUser(DeleteUserBuilder::new().id(9).build().unwrap());
+    assert_eq!(job.build_url().unwrap(), "/users/9");
+}
+
+#[test]
+fn test_send_dispatches_on_variant() {
+    let client = RecordingClient::default();
+
+    let queue = vec![
+        Job::CreateUser {
+            name: "Bob".to_string(),
+            email: Some("bob@example.com".to_string()),
+        },
+        Job::Reindex,
+        Job::DeleteUser(DeleteUser { id: 5 }),
+    ];
+
+    for job in &queue {
+        let response = job.send_with_client(&client, "https://api.example.com").unwrap();
+        assert_eq!(response, b"ok");
+    }
+
+    let requests = client.requests.lock().unwrap();
+    assert_eq!(requests.len(), 3);
+    assert_eq!(requests[0].0, "POST");
+    assert_eq!(requests[0].1, "https://api.example.com/users");
+    assert_eq!(
+        requests[0].2.as_deref(),
+        Some(br#"{"name":"Bob","email":"bob@example.com"}"#.as_slice())
+    );
+    assert_eq!(requests[1].0, "POST");
+    assert_eq!(requests[1].1, "https://api.example.com/maintenance/reindex");
+    assert_eq!(requests[2].0, "DELETE");
+    assert_eq!(requests[2].1, "https://api.example.com/users/5");
+}
@@ -0,0 +1,155 @@
+use derive_rest_api::http::{HeaderMap, Response, StatusCode};
+use derive_rest_api::{
+    ApiClient, HttpClient, ManualClock, MockClient, RequestBuilder, RequestEvent, RequestEventKind, RequestOptions,
+    RetryPolicy,
+};
+use serde::Deserialize;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[derive(Debug, Deserialize)]
+pub struct User {
+    pub id: u64,
+}
+
+#[derive(RequestBuilder)]
+#[request_builder(method = "GET", path = "/users/{id}", response = User)]
+struct GetUser {
+    id: u64,
+}
+
+#[derive(Clone, ApiClient)]
+#[api_client(base_url = "https://api.example.com", requests(GetUser))]
+struct UsersConfig;
+
+/// An HTTP client that can't connect
+#[derive(Clone, Default)]
+struct Unreachable;
+
+impl HttpClient for Unreachable {
+    type Error = std::io::Error;
+
+    fn send(
+        &self,
+        _method: &str,
+        _url: &str,
+        _headers: HeaderMap,
+        _body: Option<Vec<u8>>,
+        _options: RequestOptions,
+    ) -> Result<Response<Vec<u8>>, Self::Error> {
+        Err(std::io::Error::new(std::io::ErrorKind::ConnectionRefused, "connection refused"))
+    }
+}
+
+/// A subscriber keeping every event
+fn recording_subscriber() -> (Arc<Mutex<Vec<RequestEvent>>>, impl Fn(&RequestEvent) + Send + Sync + 'static) {
+    let recorded = Arc::new(Mutex::new(Vec::new()));
+    let subscriber = {
+        let recorded = recorded.clone();
+        move |event: &RequestEvent| recorded.lock().unwrap().push(event.clone())
+    };
+    (recorded, subscriber)
+}
+
+#[test]
+fn test_events_follow_the_request_through_retries() {
+    let http = MockClient::new();
+    http.push_json(StatusCode::SERVICE_UNAVAILABLE, &serde_json::json!({}));
+    http.push_json(StatusCode::OK, &serde_json::json!({"id": 1}));
+    let clock = ManualClock::new();
+    let (recorded, subscriber) = recording_subscriber();
+    let client = UsersClient::builder()
+        .http_client(http)
+        .clock(clock.clone())
+        .retry_policy(RetryPolicy::new(1).backoff(Duration::from_millis(100), Duration::from_secs(1)))
+        .on_event(subscriber)
+        .build()
+        .unwrap();
+
+    client.get_user().id(1).send().unwrap();
+
+    let recorded = recorded.lock().unwrap();
+    let kinds: Vec<_> = recorded.iter().map(|event| event.kind.clone()).collect();
+    let url = "https://api.example.com/users/1".to_string();
+    assert_eq!(
+        kinds,
+        [
+            RequestEventKind::Built,
+            RequestEventKind::Sent { url: url.clone(), attempt: 1 },
+            RequestEventKind::Retried {
+                retry: 1,
+                delay: Duration::from_millis(100),
+                status: Some(StatusCode::SERVICE_UNAVAILABLE),
+            },
+            RequestEventKind::Sent { url, attempt: 2 },
+            RequestEventKind::Completed {
+                status: StatusCode::OK,
+                duration: Duration::from_millis(100),
+            },
+        ]
+    );
+    assert!(recorded.iter().all(|event| event.endpoint == "GetUser" && event.method == "GET" && event.path == "/users/1"));
+    assert_eq!(recorded[3].at.duration_since(recorded[0].at), Duration::from_millis(100));
+}
+
+#[test]
+fn test_failed_requests_emit_failed() {
+    let (recorded, subscriber) = recording_subscriber();
+    let client = UsersClient::builder()
+        .http_client(Unreachable)
+        .clock(ManualClock::new())
+        .on_event(subscriber)
+        .build()
+        .unwrap();
+
+    client.get_user().id(1).send().unwrap_err();
+
+    let recorded = recorded.lock().unwrap();
+    assert_eq!(recorded.len(), 3);
+    assert!(matches!(&recorded[2].kind, RequestEventKind::Failed { error } if error.contains("connection refused")));
+}
+
+#[test]
+fn test_subscribers_are_called_in_order() {
+    let order = Arc::new(Mutex::new(Vec::new()));
+    let first = {
+        let order = order.clone();
+        move |_: &RequestEvent| order.lock().unwrap().push("client")
+    };
+    let second = {
+        let order = order.clone();
+        move |_: &RequestEvent| order.lock().unwrap().push("request")
+    };
+    let http = MockClient::new();
+    http.push_json(StatusCode::OK, &serde_json::json!({"id": 1}));
+    let client = UsersClient::builder()
+        .http_client(http)
+        .clock(ManualClock::new())
+        .on_event(first)
+        .build()
+        .unwrap();
+
+    client.get_user().id(1).on_event(second).send().unwrap();
+
+    assert_eq!(order.lock().unwrap()[..2], ["client", "request"]);
+    assert_eq!(order.lock().unwrap().len(), 6);
+}
+
+#[tokio::test]
+async fn test_async_requests_emit_events() {
+    let http = MockClient::new();
+    http.push_json(StatusCode::OK, &serde_json::json!({"id": 1}));
+    let (recorded, subscriber) = recording_subscriber();
+    let client = UsersAsyncClient::builder()
+        .http_client(http)
+        .clock(ManualClock::new())
+        .on_event(subscriber)
+        .build()
+        .unwrap();
+
+    client.get_user().id(1).send_async().await.unwrap();
+
+    let recorded = recorded.lock().unwrap();
+    assert_eq!(recorded.len(), 3);
+    assert!(matches!(recorded[2].kind, RequestEventKind::Completed { status: StatusCode::OK, .. }));
+}
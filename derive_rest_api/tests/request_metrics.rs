@@ -0,0 +1,137 @@
+use derive_rest_api::http::StatusCode;
+use derive_rest_api::{ApiClient, ManualClock, MockClient, RequestBuilder, RequestMetrics, RetryPolicy};
+use serde::Deserialize;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[derive(Debug, Deserialize)]
+pub struct User {
+    pub id: u64,
+}
+
+#[derive(RequestBuilder)]
+#[request_builder(method = "GET", path = "/users/{id}", response = User)]
+struct GetUser {
+    id: u64,
+}
+
+#[derive(RequestBuilder)]
+#[request_builder(method = "DELETE", path = "/users/{id}")]
+struct DeleteUser {
+    id: u64,
+}
+
+#[derive(Clone, ApiClient)]
+#[api_client(base_url = "https://api.example.com", requests(GetUser, DeleteUser), health = "/healthz")]
+struct UsersConfig;
+
+/// A hook keeping every measurement
+fn recording_hook() -> (Arc<Mutex<Vec<RequestMetrics>>>, impl Fn(&RequestMetrics) + Send + Sync + 'static) {
+    let recorded = Arc::new(Mutex::new(Vec::new()));
+    let hook = {
+        let recorded = recorded.clone();
+        move |metrics: &RequestMetrics| recorded.lock().unwrap().push(metrics.clone())
+    };
+    (recorded, hook)
+}
+
+#[test]
+fn test_metrics_are_labelled_with_endpoint_and_method() {
+    let http = MockClient::new();
+    http.push_json(StatusCode::OK, &serde_json::json!({"id": 1}));
+    http.push_json(StatusCode::NOT_FOUND, &serde_json::json!({}));
+    let (recorded, hook) = recording_hook();
+    let client = UsersClient::builder().http_client(http).metrics(hook).build().unwrap();
+
+    client.get_user().id(1).send().unwrap();
+    client.delete_user().id(2).send_raw().unwrap();
+    client.health_check().unwrap();
+
+    let recorded = recorded.lock().unwrap();
+    let labels: Vec<_> = recorded
+        .iter()
+        .map(|metrics| (metrics.endpoint, metrics.method.as_str(), metrics.status.map(|status| status.as_u16())))
+        .collect();
+    assert_eq!(
+        labels,
+        [("GetUser", "GET", Some(200)), ("DeleteUser", "DELETE", Some(404)), ("health_check", "GET", Some(200))]
+    );
+}
+
+#[test]
+fn test_metrics_measure_the_whole_request_including_retries() {
+    let http = MockClient::new();
+    http.push_json(StatusCode::SERVICE_UNAVAILABLE, &serde_json::json!({}));
+    http.push_json(StatusCode::SERVICE_UNAVAILABLE, &serde_json::json!({}));
+    http.push_json(StatusCode::OK, &serde_json::json!({"id": 1}));
+    let (recorded, hook) = recording_hook();
+    let client = UsersClient::builder()
+        .http_client(http)
+        .clock(ManualClock::new())
+        .retry_policy(RetryPolicy::new(3).backoff(Duration::from_millis(100), Duration::from_secs(1)))
+        .build()
+        .unwrap();
+
+    client.get_user().id(1).metrics(hook).send().unwrap();
+
+    let recorded = recorded.lock().unwrap();
+    assert_eq!(recorded.len(), 1);
+    assert_eq!(recorded[0].attempts, 3);
+    assert_eq!(recorded[0].duration, Duration::from_millis(300));
+    assert_eq!(recorded[0].status, Some(StatusCode::OK));
+}
+
+#[tokio::test]
+async fn test_async_requests_report_metrics() {
+    let http = MockClient::new();
+    http.push_json(StatusCode::OK, &serde_json::json!({"id": 1}));
+    let (recorded, hook) = recording_hook();
+    let client = UsersAsyncClient::builder().http_client(http).metrics(hook).build().unwrap();
+
+    client.get_user().id(1).send_async().await.unwrap();
+
+    assert_eq!(recorded.lock().unwrap()[0].endpoint, "GetUser");
+}
+
+#[cfg(feature = "metrics")]
+#[test]
+fn test_metrics_facade_records_histograms_and_counters() {
+    use derive_rest_api::MetricsFacade;
+    use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+
+    let recorder = DebuggingRecorder::new();
+    let snapshotter = recorder.snapshotter();
+    let http = MockClient::new();
+    http.push_json(StatusCode::SERVICE_UNAVAILABLE, &serde_json::json!({}));
+    http.push_json(StatusCode::OK, &serde_json::json!({"id": 1}));
+    let client = UsersClient::builder()
+        .http_client(http)
+        .clock(ManualClock::new())
+        .retry_policy(RetryPolicy::new(1).backoff(Duration::from_millis(250), Duration::from_secs(1)))
+        .metrics(MetricsFacade::with_prefix("users_api"))
+        .build()
+        .unwrap();
+
+    metrics::with_local_recorder(&recorder, || client.get_user().id(1).send().unwrap());
+
+    let mut recorded: Vec<_> = snapshotter
+        .snapshot()
+        .into_vec()
+        .into_iter()
+        .map(|(key, _, _, value)| {
+            let labels: Vec<_> = key.key().labels().map(|label| format!("{}={}", label.key(), label.value())).collect();
+            (key.key().name().to_string(), labels, value)
+        })
+        .collect();
+    recorded.sort_by(|(a, ..), (b, ..)| a.cmp(b));
+
+    let labels = ["endpoint=GetUser", "method=GET", "status=200"];
+    assert_eq!(recorded.len(), 3);
+    assert_eq!(recorded[0].0, "users_api_request_duration_seconds");
+    assert_eq!(recorded[0].1, labels);
+    assert_eq!(recorded[0].2, DebugValue::Histogram(vec![0.25.into()]));
+    assert_eq!(recorded[1].0, "users_api_request_retries_total");
+    assert_eq!(recorded[1].2, DebugValue::Counter(1));
+    assert_eq!(recorded[2].0, "users_api_requests_total");
+    assert_eq!(recorded[2].2, DebugValue::Counter(1));
+}
@@ -0,0 +1,81 @@
+use derive_rest_api::{ApiClient, BuiltRequest, RequestBuilder, RequestModifier};
+
+#[derive(RequestBuilder)]
+#[request_builder(method = "POST", path = "/repos/{repo}/issues")]
+struct CreateIssue {
+    repo: String,
+    #[request_builder(query)]
+    notify: Option<bool>,
+    #[request_builder(header)]
+    x_request_id: String,
+    #[request_builder(body)]
+    title: String,
+}
+
+#[derive(RequestBuilder)]
+#[request_builder(method = "GET", path = "/repos/{repo}")]
+struct GetRepo {
+    repo: String,
+}
+
+#[derive(Clone, ApiClient)]
+#[api_client(base_url = "https://api.example.com", user_agent = "preview-tests", requests(GetRepo))]
+struct ReposConfig;
+
+#[test]
+fn test_preview_matches_snapshot() {
+    let request = CreateIssueBuilder::new()
+        .repo("app".to_string())
+        .notify(true)
+        .x_request_id("r-1".to_string())
+        .title("Crash".to_string())
+        .header("Accept", "application/json")
+        .preview("https://api.example.com")
+        .unwrap();
+
+    assert_eq!(
+        request.to_string(),
+        "POST https://api.example.com/repos/app/issues?notify=true\n\
+         accept: application/json\n\
+         content-type: application/json\n\
+         x-request-id: r-1\n\
+         \n\
+         {\"title\":\"Crash\"}"
+    );
+    assert_eq!(request.header("X-Request-Id"), Some("r-1"));
+    assert_eq!(request.body_text(), Some(r#"{"title":"Crash"}"#));
+}
+
+#[test]
+fn test_preview_sorts_headers() {
+    let request = GetRepoBuilder::new()
+        .repo("app".to_string())
+        .header("X-B", "2")
+        .header("X-A", "1")
+        .preview("https://api.example.com")
+        .unwrap();
+
+    assert_eq!(
+        request,
+        BuiltRequest {
+            method: "GET".to_string(),
+            url: "https://api.example.com/repos/app".to_string(),
+            headers: vec![("x-a".to_string(), "1".to_string()), ("x-b".to_string(), "2".to_string())],
+            body: None,
+        }
+    );
+}
+
+#[test]
+fn test_preview_from_client_includes_client_headers() {
+    let client = ReposClient::new();
+    let request = client.get_repo().repo("app".to_string()).preview("https://staging.example.com").unwrap();
+
+    assert_eq!(request.url, "https://staging.example.com/repos/app");
+    assert!(request.header("User-Agent").unwrap().starts_with("preview-tests"));
+}
+
+#[test]
+fn test_preview_reports_build_errors() {
+    assert!(GetRepoBuilder::new().preview("https://api.example.com").is_err());
+}
@@ -0,0 +1,108 @@
+use derive_rest_api::http::{HeaderMap, Response};
+use derive_rest_api::{
+    ApiClient, ConfigureRequest, HttpClient, MockClient, RequestBuilder, RequestModifier, RequestOptions, RestApiError,
+    TlsVersion,
+};
+use std::sync::{Arc, Mutex};
+
+#[derive(RequestBuilder)]
+#[request_builder(method = "GET", path = "/accounts/{id}", response = ())]
+struct GetAccount {
+    id: u32,
+}
+
+#[derive(Clone, ApiClient)]
+#[api_client(base_url = "https://bank.example.com", requests(GetAccount))]
+struct BankConfig {
+    token: String,
+}
+
+impl ConfigureRequest for BankConfig {
+    fn configure<M: RequestModifier>(&self, modifier: M) -> M {
+        modifier.bearer_auth(&self.token).require_https()
+    }
+}
+
+fn client(http: &MockClient, base_url: &str) -> BankClient<MockClient> {
+    BankClient::builder()
+        .http_client(http.clone())
+        .config(BankConfig { token: "secret".to_string() })
+        .base_url(base_url)
+        .build()
+        .unwrap()
+}
+
+/// Records the options each request was sent with
+#[derive(Clone, Default)]
+struct RecordingClient {
+    options: Arc<Mutex<Vec<RequestOptions>>>,
+}
+
+impl HttpClient for RecordingClient {
+    type Error = std::convert::Infallible;
+
+    fn send(
+        &self,
+        _method: &str,
+        _url: &str,
+        _headers: HeaderMap,
+        _body: Option<Vec<u8>>,
+        options: RequestOptions,
+    ) -> Result<Response<Vec<u8>>, Self::Error> {
+        self.options.lock().unwrap().push(options);
+        Ok(Response::new(Vec::new()))
+    }
+}
+
+#[test]
+fn test_https_requests_are_sent() {
+    let http = MockClient::new();
+    client(&http, "https://bank.example.com").get_account().id(1).send().unwrap();
+
+    assert_eq!(http.requests()[0].url, "https://bank.example.com/accounts/1");
+}
+
+#[test]
+fn test_plain_http_fails_before_sending() {
+    let http = MockClient::new();
+    let error = client(&http, "http://bank.example.com").get_account().id(1).send().unwrap_err();
+
+    assert!(matches!(&error, RestApiError::InsecureUrl { url } if url == "http://bank.example.com/accounts/1"));
+    assert_eq!(
+        error.to_string(),
+        "Refusing to send a request that requires HTTPS to http://bank.example.com/accounts/1"
+    );
+    assert!(http.requests().is_empty());
+}
+
+#[test]
+fn test_min_tls_version_requires_https_and_reaches_the_client() {
+    let http = RecordingClient::default();
+    let error = GetAccountBuilder::new()
+        .http_client(http.clone())
+        .base_url("http://localhost:8080")
+        .min_tls_version(TlsVersion::Tls1_3)
+        .id(1)
+        .send()
+        .unwrap_err();
+    assert!(matches!(error, RestApiError::InsecureUrl { .. }));
+
+    GetAccountBuilder::new()
+        .http_client(http.clone())
+        .base_url("https://bank.example.com")
+        .min_tls_version(TlsVersion::Tls1_3)
+        .id(1)
+        .send()
+        .unwrap();
+    let options = http.options.lock().unwrap();
+    assert_eq!(options.len(), 1);
+    assert_eq!(options[0].min_tls_version, Some(TlsVersion::Tls1_3));
+}
+
+#[test]
+fn test_check_url() {
+    let options = RequestOptions::new().with_require_https();
+    assert!(options.check_url("HTTPS://bank.example.com/").is_ok());
+    assert!(options.check_url("unix://%2Frun%2Fbank.sock/").is_err());
+    assert!(RequestOptions::new().check_url("http://bank.example.com/").is_ok());
+}
@@ -24,6 +24,80 @@ mod blocking_tests {
         drop(client);
     }
 
+    #[test]
+    fn test_reqwest_blocking_with_pool() {
+        let pool = derive_rest_api::PoolOptions::new()
+            .with_max_idle_per_host(16)
+            .with_idle_timeout(std::time::Duration::from_secs(30))
+            .with_http2_keep_alive(std::time::Duration::from_secs(20), std::time::Duration::from_secs(5));
+
+        let client = ReqwestBlockingClient::with_pool(pool);
+        assert!(client.is_ok());
+    }
+
+    #[test]
+    fn test_reqwest_blocking_resolve_pins_a_host() {
+        use std::io::{Read, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0; 1024];
+            let read = stream.read(&mut request).unwrap();
+            stream.write_all(b"HTTP/1.1 204 No Content\r\nConnection: close\r\n\r\n").unwrap();
+            String::from_utf8_lossy(&request[..read]).to_lowercase()
+        });
+
+        let client = ReqwestBlockingClient::builder()
+            .resolve("api.internal.test", addr)
+            .build()
+            .unwrap();
+        let response = derive_rest_api::HttpClient::send(
+            &client,
+            "GET",
+            "http://api.internal.test/ping",
+            Default::default(),
+            None,
+            Default::default(),
+        )
+        .unwrap();
+
+        assert_eq!(response.status(), 204);
+        assert!(server.join().unwrap().contains("host: api.internal.test"));
+    }
+
+    #[test]
+    fn test_reqwest_blocking_ip_family_skips_other_addresses() {
+        use derive_rest_api::{HttpClient, IpFamily};
+        use std::io::{Read, Write};
+        use std::time::Duration;
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let _ = stream.read(&mut [0; 1024]).unwrap();
+            stream.write_all(b"HTTP/1.1 204 No Content\r\nConnection: close\r\n\r\n").unwrap();
+        });
+
+        let ipv6_only = ReqwestBlockingClient::builder()
+            .ip_family(IpFamily::Ipv6Only)
+            .connect_timeout(Duration::from_secs(1))
+            .build()
+            .unwrap();
+        assert!(ipv6_only.send("GET", &url, Default::default(), None, Default::default()).is_err());
+
+        let ipv4_only = ReqwestBlockingClient::builder()
+            .ip_family(IpFamily::Ipv4Only)
+            .connect_timeout(Duration::from_secs(1))
+            .build()
+            .unwrap();
+        let response = ipv4_only.send("GET", &url, Default::default(), None, Default::default()).unwrap();
+        assert_eq!(response.status(), 204);
+        server.join().unwrap();
+    }
+
     #[test]
     #[ignore] // Requires network connection
     fn test_reqwest_blocking_real_request() {
@@ -53,6 +127,26 @@ mod blocking_tests {
         }
     }
 
+    #[test]
+    fn test_reqwest_error_is_exposed() {
+        #[derive(RequestBuilder)]
+        #[request_builder(method = "GET", path = "/status")]
+        struct GetStatus;
+
+        // A port nothing listens on, so the connection is refused
+        let port = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let error = GetStatusBuilder::new()
+            .http_client(ReqwestBlockingClient::new().unwrap())
+            .base_url(format!("http://127.0.0.1:{}", port))
+            .send()
+            .unwrap_err();
+
+        let reqwest_error = error.as_reqwest_error().unwrap();
+        assert!(reqwest_error.is_connect());
+        assert!(!reqwest_error.is_timeout());
+        assert!(error.is_transient());
+    }
+
     #[test]
     #[ignore] // Requires network connection
     fn test_reqwest_blocking_post_request() {
@@ -144,6 +238,59 @@ mod async_tests {
         drop(client);
     }
 
+    #[test]
+    fn test_reqwest_async_with_pool() {
+        let pool = derive_rest_api::PoolOptions::new()
+            .with_max_idle_per_host(64)
+            .with_idle_timeout(std::time::Duration::from_secs(30))
+            .with_http2_keep_alive(std::time::Duration::from_secs(20), std::time::Duration::from_secs(5))
+            .with_http2_keep_alive_while_idle(true);
+
+        let client = ReqwestAsyncClient::with_pool(pool);
+        assert!(client.is_ok());
+    }
+
+    #[test]
+    fn test_reqwest_async_builder_combines_settings() {
+        use derive_rest_api::{HttpProtocol, PoolOptions};
+
+        let client = ReqwestAsyncClient::builder()
+            .pool(PoolOptions::new().with_max_idle_per_host(8))
+            .protocol(HttpProtocol::Http1Only)
+            .resolve("api.internal.test", "127.0.0.1:0".parse().unwrap())
+            .build();
+        assert!(client.is_ok());
+    }
+
+    /// Sends a request to a local listener and returns the first bytes it received
+    async fn first_bytes(client: ReqwestAsyncClient) -> String {
+        use std::io::Read;
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/ping", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buf = [0; 16];
+            stream.read_exact(&mut buf).unwrap();
+            String::from_utf8_lossy(&buf).into_owned()
+        });
+
+        // The listener hangs up without answering, so the request itself fails
+        let _ = client.send_async("GET", &url, Default::default(), None, Default::default()).await;
+        server.join().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_reqwest_async_with_protocol() {
+        use derive_rest_api::HttpProtocol;
+
+        let http1 = ReqwestAsyncClient::with_protocol(HttpProtocol::Http1Only).unwrap();
+        assert!(first_bytes(http1).await.starts_with("GET /ping HTTP/1"));
+
+        let h2c = ReqwestAsyncClient::with_protocol(HttpProtocol::Http2PriorKnowledge).unwrap();
+        assert_eq!(first_bytes(h2c).await, "PRI * HTTP/2.0\r\n");
+    }
+
     #[tokio::test]
     #[ignore] // Requires network connection
     async fn test_reqwest_async_real_request() {
@@ -166,14 +313,14 @@ mod async_tests {
         let result = client.send_async(
             "GET",
             &format!("https://jsonplaceholder.typicode.com{}", request.build_url().unwrap()),
-            request.build_headers(),
+            request.build_headers().unwrap(),
             request.build_body().unwrap(),
-            None
+            Default::default()
         ).await;
 
         if let Ok(response) = result {
-            assert!(!response.is_empty());
-            let json: serde_json::Value = serde_json::from_slice(&response).unwrap();
+            assert!(response.status().is_success());
+            let json: serde_json::Value = serde_json::from_slice(response.body()).unwrap();
             assert_eq!(json["id"], 1);
         }
     }
@@ -205,14 +352,14 @@ mod async_tests {
         let result = client.send_async(
             "POST",
             &format!("https://jsonplaceholder.typicode.com{}", request.build_url().unwrap()),
-            request.build_headers(),
+            request.build_headers().unwrap(),
             request.build_body().unwrap(),
-            None
+            Default::default()
         ).await;
 
         if let Ok(response) = result {
-            assert!(!response.is_empty());
-            let json: serde_json::Value = serde_json::from_slice(&response).unwrap();
+            assert!(response.status().is_success());
+            let json: serde_json::Value = serde_json::from_slice(response.body()).unwrap();
             assert!(json.get("id").is_some());
         }
     }
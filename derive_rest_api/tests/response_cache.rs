@@ -0,0 +1,201 @@
+use derive_rest_api::http::{HeaderMap, Response};
+use derive_rest_api::{ApiClient, AsyncHttpClient, CachingClient, HttpClient, ManualClock, MockClient, RequestBuilder, RequestOptions, ResponseCache};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[derive(RequestBuilder)]
+#[request_builder(method = "GET", path = "/users/{id}", response = ())]
+struct GetUser {
+    id: u32,
+}
+
+#[derive(RequestBuilder)]
+#[request_builder(method = "DELETE", path = "/users/{id}", response = ())]
+struct DeleteUser {
+    id: u32,
+}
+
+#[derive(Clone, ApiClient)]
+#[api_client(base_url = "https://api.example.com", requests(GetUser, DeleteUser))]
+struct UsersConfig;
+
+fn respond(http: &MockClient, cache_control: &str) {
+    let response = Response::builder().header("Cache-Control", cache_control).body(b"{}".to_vec()).unwrap();
+    http.push_response(response);
+}
+
+fn get(client: &impl HttpClient, url: &str, headers: &[(&'static str, &'static str)]) -> Response<Vec<u8>> {
+    let mut map = HeaderMap::new();
+    for (name, value) in headers {
+        map.insert(*name, value.parse().unwrap());
+    }
+    client.send("GET", url, map, None, RequestOptions::default()).unwrap_or_else(|_| unreachable!())
+}
+
+#[test]
+fn test_max_age_responses_are_reused_until_they_expire() {
+    let http = MockClient::new();
+    let clock = ManualClock::new();
+    let client = UsersClient::builder()
+        .http_client(http.clone())
+        .build()
+        .unwrap()
+        .with_cache(ResponseCache::new().clock(clock.clone()));
+    respond(&http, "public, max-age=60");
+    respond(&http, "max-age=60");
+
+    client.get_user().id(1).send().unwrap();
+    clock.advance(Duration::from_secs(30));
+    client.get_user().id(1).send().unwrap();
+    assert_eq!(http.requests().len(), 1);
+
+    clock.advance(Duration::from_secs(30));
+    client.get_user().id(1).send().unwrap();
+    assert_eq!(http.requests().len(), 2);
+}
+
+#[test]
+fn test_hits_carry_their_age() {
+    let http = MockClient::new();
+    let clock = ManualClock::new();
+    let client = CachingClient::new(http.clone(), ResponseCache::new().clock(clock.clone()));
+    let response = Response::builder()
+        .header("Cache-Control", "max-age=100")
+        .header("Age", "40")
+        .body(Vec::new())
+        .unwrap();
+    http.push_response(response);
+
+    get(&client, "https://api.example.com/a?x=1&y=2", &[]);
+    clock.advance(Duration::from_secs(10));
+    let hit = get(&client, "https://api.example.com/a?y=2&x=1", &[]);
+    assert_eq!(hit.headers()["age"], "50");
+    assert_eq!(http.requests().len(), 1);
+
+    // The response was already 40s old, so it expires after 60s in the cache
+    clock.advance(Duration::from_secs(50));
+    get(&client, "https://api.example.com/a?x=1&y=2", &[]);
+    assert_eq!(http.requests().len(), 2);
+}
+
+#[test]
+fn test_no_store_private_and_no_cache_requests() {
+    let http = MockClient::new();
+    let shared = CachingClient::new(http.clone(), ResponseCache::new());
+    respond(&http, "no-store, max-age=60");
+    respond(&http, "private, max-age=60");
+    respond(&http, "max-age=60");
+
+    get(&shared, "https://api.example.com/no-store", &[]);
+    get(&shared, "https://api.example.com/private", &[]);
+    get(&shared, "https://api.example.com/authorized", &[("Authorization", "Bearer t")]);
+    assert!(shared.cache().unwrap().is_empty());
+
+    let private = CachingClient::new(http.clone(), ResponseCache::private());
+    respond(&http, "private, max-age=60");
+    get(&private, "https://api.example.com/private", &[("Authorization", "Bearer t")]);
+    get(&private, "https://api.example.com/private", &[("Authorization", "Bearer t")]);
+    assert_eq!(http.requests().len(), 4);
+
+    get(&private, "https://api.example.com/private", &[("Cache-Control", "no-cache")]);
+    assert_eq!(http.requests().len(), 5);
+}
+
+#[test]
+fn test_vary_and_invalidation() {
+    let http = MockClient::new();
+    let cache = ResponseCache::private();
+    let client = CachingClient::new(http.clone(), cache.clone());
+    let response = Response::builder()
+        .header("Cache-Control", "max-age=60")
+        .header("Vary", "Accept-Language")
+        .body(Vec::new())
+        .unwrap();
+    http.push_response(response);
+
+    get(&client, "https://api.example.com/users/1", &[("Accept-Language", "en")]);
+    get(&client, "https://api.example.com/users/1", &[("Accept-Language", "en")]);
+    assert_eq!(http.requests().len(), 1);
+    respond(&http, "max-age=60");
+    get(&client, "https://api.example.com/users/1", &[("Accept-Language", "fr")]);
+    assert_eq!(http.requests().len(), 2);
+    assert_eq!(cache.len(), 1);
+
+    let users = UsersClient::builder().http_client(http.clone()).build().unwrap().with_cache(cache.clone());
+    respond(&http, "max-age=60");
+    users.get_user().id(2).send().unwrap();
+    assert_eq!(cache.len(), 2);
+    users.delete_user().id(2).send().unwrap();
+    assert_eq!(cache.len(), 1);
+}
+
+type Refresh = std::pin::Pin<Box<dyn std::future::Future<Output = ()> + Send>>;
+
+async fn get_async(client: &impl AsyncHttpClient, url: &str) -> Response<Vec<u8>> {
+    let response = client.send_async("GET", url, HeaderMap::new(), None, RequestOptions::default()).await;
+    response.unwrap_or_else(|_| unreachable!())
+}
+
+#[tokio::test]
+async fn test_stale_while_revalidate_serves_stale_responses_and_refreshes_them() {
+    let http = MockClient::new();
+    let clock = ManualClock::new();
+    let refreshes: Arc<Mutex<Vec<Refresh>>> = Arc::default();
+    let spawned = refreshes.clone();
+    let cache = ResponseCache::new()
+        .clock(clock.clone())
+        .stale_while_revalidate(Duration::from_secs(30), move |refresh| spawned.lock().unwrap().push(refresh));
+    let client = CachingClient::new(http.clone(), cache);
+    let url = "https://api.example.com/dashboard";
+    for body in ["v1", "v2"] {
+        let response = Response::builder().header("Cache-Control", "max-age=60").body(body.as_bytes().to_vec());
+        http.push_response(response.unwrap());
+    }
+
+    get_async(&client, url).await;
+    clock.advance(Duration::from_secs(70));
+    // Expired, but within the window: answered at once, with one refresh started
+    assert_eq!(get_async(&client, url).await.body(), b"v1");
+    assert_eq!(get_async(&client, url).await.body(), b"v1");
+    let pending: Vec<_> = refreshes.lock().unwrap().drain(..).collect();
+    assert_eq!(pending.len(), 1);
+    assert_eq!(http.requests().len(), 1);
+
+    for refresh in pending {
+        refresh.await;
+    }
+    assert_eq!(http.requests().len(), 2);
+    assert_eq!(get_async(&client, url).await.body(), b"v2");
+
+    // Past the window, the request waits for a fresh response
+    clock.advance(Duration::from_secs(100));
+    get_async(&client, url).await;
+    assert_eq!(http.requests().len(), 3);
+    assert!(refreshes.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_stale_responses_honor_their_own_directives() {
+    let http = MockClient::new();
+    let clock = ManualClock::new();
+    // Refreshes are dropped rather than run
+    let cache = ResponseCache::new().clock(clock.clone()).stale_while_revalidate(Duration::ZERO, drop);
+    let client = CachingClient::new(http.clone(), cache);
+    for cache_control in ["max-age=10, stale-while-revalidate=60", "max-age=10, stale-while-revalidate=60, must-revalidate"] {
+        let response = Response::builder().header("Cache-Control", cache_control).body(Vec::new());
+        http.push_response(response.unwrap());
+    }
+
+    get_async(&client, "https://api.example.com/a").await;
+    get_async(&client, "https://api.example.com/b").await;
+    clock.advance(Duration::from_secs(30));
+
+    assert_eq!(get_async(&client, "https://api.example.com/a").await.headers()["age"], "30");
+    get_async(&client, "https://api.example.com/b").await;
+    let sent = |path: &str| http.requests().iter().filter(|request| request.url.ends_with(path)).count();
+    assert_eq!((sent("/a"), sent("/b")), (1, 2));
+
+    // Blocking clients don't serve stale responses
+    get(&client, "https://api.example.com/a", &[]);
+    assert_eq!(sent("/a"), 2);
+}
@@ -0,0 +1,151 @@
+use derive_rest_api::http::{HeaderMap, Response};
+use derive_rest_api::{RequestModifier, RestApiError, rest_api};
+use serde::Deserialize;
+use std::sync::{Arc, Mutex};
+
+// A request as sent: method and URL, headers and body
+type SentRequest = (String, HeaderMap, Option<Vec<u8>>);
+
+// Mock HTTP client recording each request and answering with a canned body
+#[derive(Clone, Default)]
+struct MockClient {
+    requests: Arc<Mutex<Vec<SentRequest>>>,
+}
+
+impl MockClient {
+    fn respond(&self, method: &str, url: &str, headers: HeaderMap, body: Option<Vec<u8>>) -> Response<Vec<u8>> {
+        self.requests.lock().unwrap().push((format!("{} {}", method, url), headers, body));
+        Response::new(br#"{"number": 7, "title": "Crash"}"#.to_vec())
+    }
+}
+
+impl derive_rest_api::HttpClient for MockClient {
+    type Error = std::convert::Infallible;
+
+    fn send(
+        &self,
+        method: &str,
+        url: &str,
+        headers: HeaderMap,
+        body: Option<Vec<u8>>,
+        _options: derive_rest_api::RequestOptions,
+    ) -> Result<Response<Vec<u8>>, Self::Error> {
+        Ok(self.respond(method, url, headers, body))
+    }
+}
+
+impl derive_rest_api::AsyncHttpClient for MockClient {
+    type Error = std::convert::Infallible;
+
+    async fn send_async(
+        &self,
+        method: &str,
+        url: &str,
+        headers: HeaderMap,
+        body: Option<Vec<u8>>,
+        _options: derive_rest_api::RequestOptions,
+    ) -> Result<Response<Vec<u8>>, Self::Error> {
+        Ok(self.respond(method, url, headers, body))
+    }
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+pub struct Issue {
+    number: u64,
+    title: String,
+}
+
+/// Issue tracker API
+#[rest_api(base_url = "https://tracker.example.com")]
+pub trait Tracker {
+    /// Fetches an issue
+    #[request_builder(method = "GET", path = "/repos/{repo}/issues/{number}")]
+    fn get_issue(&self, repo: String, number: u64) -> Result<Issue, RestApiError>;
+
+    #[request_builder(method = "GET", path = "/repos/{repo}/issues")]
+    fn list_issues(
+        &self,
+        repo: String,
+        #[request_builder(query)] state: Option<String>,
+        #[request_builder(query)] per_page: Option<u32>,
+    ) -> Result<Vec<Issue>, RestApiError>;
+
+    #[request_builder(method = "POST", path = "/repos/{repo}/issues", static_header("X-Client" = "tests"))]
+    fn create_issue(
+        &self,
+        repo: String,
+        #[request_builder(header)] idempotency_key: String,
+        #[request_builder(body)] title: String,
+    ) -> Result<Issue, RestApiError>;
+
+    #[request_builder(method = "DELETE", path = "/repos/{repo}/issues/{number}")]
+    fn delete_issue(&self, repo: String, number: u64) -> Result<(), RestApiError>;
+
+    // Methods with a body are left as they are
+    fn get_issue_title(&self, repo: String, number: u64) -> Result<String, RestApiError> {
+        Ok(self.get_issue(repo, number)?.title)
+    }
+}
+
+#[test]
+fn test_client_implements_the_trait() {
+    let http = MockClient::default();
+    let client = TrackerClient::builder().http_client(http.clone()).build().unwrap();
+
+    let issue = client.get_issue("app".to_string(), 7).unwrap();
+    assert_eq!(issue, Issue { number: 7, title: "Crash".to_string() });
+    assert_eq!(client.get_issue_title("app".to_string(), 7).unwrap(), "Crash");
+    // The canned body is a single issue rather than a list
+    client.list_issues("app".to_string(), None, Some(20)).unwrap_err();
+    client
+        .create_issue("app".to_string(), "k-1".to_string(), "Crash".to_string())
+        .unwrap();
+    client.delete_issue("app".to_string(), 7).unwrap();
+
+    let requests = http.requests.lock().unwrap();
+    assert_eq!(requests[0].0, "GET https://tracker.example.com/repos/app/issues/7");
+    assert_eq!(requests[2].0, "GET https://tracker.example.com/repos/app/issues?per_page=20");
+    let (url, headers, body) = &requests[3];
+    assert_eq!(url, "POST https://tracker.example.com/repos/app/issues");
+    assert_eq!(headers["Idempotency-Key"], "k-1");
+    assert_eq!(headers["X-Client"], "tests");
+    let body: serde_json::Value = serde_json::from_slice(body.as_ref().unwrap()).unwrap();
+    assert_eq!(body, serde_json::json!({"title": "Crash"}));
+    assert_eq!(requests[4].0, "DELETE https://tracker.example.com/repos/app/issues/7");
+}
+
+#[test]
+fn test_request_builders_remain_available() {
+    let request = ListIssuesRequestBuilder::new()
+        .repo("app".to_string())
+        .state("open".to_string())
+        .build()
+        .unwrap();
+    assert_eq!(request.build_url().unwrap(), "/repos/app/issues?state=open");
+
+    // The client's builder methods allow per-call customisation
+    let http = MockClient::default();
+    let client = TrackerClient::builder().http_client(http.clone()).build().unwrap();
+    client
+        .get_issue_request()
+        .repo("app".to_string())
+        .number(7)
+        .header("X-Trace", "abc")
+        .send()
+        .unwrap();
+    assert_eq!(http.requests.lock().unwrap()[0].1["X-Trace"], "abc");
+}
+
+#[tokio::test]
+async fn test_async_client_implements_the_async_trait() {
+    let http = MockClient::default();
+    let client = TrackerAsyncClient::builder().http_client(http.clone()).build().unwrap();
+
+    let issue = client.get_issue("app".to_string(), 7).await.unwrap();
+    assert_eq!(issue.number, 7);
+    client.delete_issue("app".to_string(), 7).await.unwrap();
+
+    let requests = http.requests.lock().unwrap();
+    assert_eq!(requests[0].0, "GET https://tracker.example.com/repos/app/issues/7");
+    assert_eq!(requests[1].0, "DELETE https://tracker.example.com/repos/app/issues/7");
+}
@@ -0,0 +1,71 @@
+use derive_rest_api::RequestBuilder;
+
+#[derive(Debug, RequestBuilder)]
+#[request_builder(method = "POST", path = "/users/{org}/members")]
+struct AddMember {
+    #[request_builder(example = "acme".to_string())]
+    org: String,
+    #[request_builder(body, example = "Ada Lovelace".to_string())]
+    name: String,
+    #[request_builder(body, example = 36)]
+    age: u32,
+    #[request_builder(body, example = "ada@example.com".to_string())]
+    email: Option<String>,
+    #[request_builder(body)]
+    nickname: Option<String>,
+    #[request_builder(body)]
+    admin: bool,
+    #[request_builder(query, default = 30)]
+    ttl_days: u32,
+}
+
+#[derive(Debug, RequestBuilder)]
+#[request_builder(method = "GET", path = "/search", into)]
+struct Search {
+    #[request_builder(query, example = "rust")]
+    q: String,
+    #[request_builder(query, example = 2u32)]
+    page: Option<u32>,
+}
+
+fn not_blank(value: &str) -> Result<(), String> {
+    if value.trim().is_empty() { Err("must not be blank".to_string()) } else { Ok(()) }
+}
+
+#[derive(Debug, RequestBuilder)]
+#[request_builder(method = "GET", path = "/tags/{tag}")]
+struct GetTag {
+    #[request_builder(validate = "not_blank")]
+    tag: String,
+}
+
+#[test]
+fn test_sample_uses_examples_and_defaults() {
+    let request = AddMember::sample();
+
+    assert_eq!(request.org, "acme");
+    assert_eq!(request.name, "Ada Lovelace");
+    assert_eq!(request.age, 36);
+    assert_eq!(request.email.as_deref(), Some("ada@example.com"));
+    assert_eq!(request.nickname, None);
+    assert!(!request.admin);
+    assert_eq!(request.ttl_days, 30);
+
+    assert_eq!(request.build_url().unwrap(), "/users/acme/members?ttl_days=30");
+    let body: serde_json::Value = serde_json::from_slice(&request.build_body().unwrap().unwrap()).unwrap();
+    assert_eq!(body["name"], "Ada Lovelace");
+    assert_eq!(body["email"], "ada@example.com");
+}
+
+#[test]
+fn test_sample_passes_examples_through_into_setters() {
+    let request = Search::sample();
+
+    assert_eq!(request.build_url().unwrap(), "/search?q=rust&page=2");
+}
+
+#[test]
+#[should_panic(expected = "the sample values for `GetTag` don't build")]
+fn test_sample_panics_when_defaults_fail_validation() {
+    GetTag::sample();
+}
@@ -0,0 +1,63 @@
+use derive_rest_api::{ApiClient, MockClient, RequestBuilder};
+use serde::Deserialize;
+use std::future::Future;
+use std::rc::Rc;
+
+#[derive(Debug, Deserialize)]
+struct Order {
+    id: u32,
+}
+
+#[derive(RequestBuilder)]
+#[request_builder(method = "GET", path = "/orders/{id}", response = Order)]
+struct GetOrder<'a> {
+    id: u32,
+    #[request_builder(query)]
+    fields: Option<&'a str>,
+}
+
+#[derive(Clone, ApiClient)]
+#[api_client(base_url = "https://api.example.com", health = "/health", requests(GetOrder))]
+struct ShopConfig;
+
+/// A request the builder can't share between threads, which must still compile
+#[derive(RequestBuilder)]
+#[request_builder(method = "GET", path = "/local", response = ())]
+#[allow(dead_code)]
+struct LocalRequest {
+    #[request_builder(skip)]
+    handle: Option<Rc<()>>,
+}
+
+fn assert_send_sync<T: Send + Sync>(_: &T) {}
+
+fn assert_send<T: Future + Send>(future: T) -> T {
+    future
+}
+
+#[test]
+fn test_clients_and_builders_are_send_and_sync() {
+    let client = ShopClient::<MockClient>::new_with_client(MockClient::new());
+    let async_client = ShopAsyncClient::<MockClient>::new_with_client(MockClient::new());
+
+    assert_send_sync(&client);
+    assert_send_sync(&async_client);
+    assert_send_sync(&client.get_order().id(1).fields("id"));
+    assert_send_sync(&async_client.get_order().id(1));
+}
+
+#[tokio::test]
+async fn test_futures_are_send() {
+    let http = MockClient::new();
+    http.push_json(derive_rest_api::http::StatusCode::OK, &serde_json::json!({ "id": 7 }));
+    let client = ShopAsyncClient::<MockClient>::new_with_client(http.clone());
+
+    let order = tokio::spawn(assert_send(async move { client.get_order().id(7).send_async().await }))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(order.id, 7);
+
+    let client = ShopAsyncClient::<MockClient>::new_with_client(http);
+    tokio::spawn(assert_send(async move { client.health_check().await })).await.unwrap().unwrap();
+}
@@ -0,0 +1,108 @@
+use derive_rest_api::http::{HeaderMap, Response};
+use derive_rest_api::{ApiClient, HttpClient, MockClient, RequestBuilder, RequestOptions};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+#[derive(RequestBuilder)]
+#[request_builder(method = "GET", path = "/items/{id}", response = ())]
+struct GetItem {
+    id: u32,
+}
+
+#[derive(Clone, ApiClient)]
+#[api_client(base_url = "https://api.example.com", requests(GetItem))]
+struct ItemsConfig {
+    api_key: String,
+}
+
+impl derive_rest_api::NoRequestConfiguration for ItemsConfig {}
+
+/// A `MockClient` counting how often it is cloned
+#[derive(Default)]
+struct CountingClient {
+    http: MockClient,
+    clones: Arc<AtomicUsize>,
+}
+
+impl Clone for CountingClient {
+    fn clone(&self) -> Self {
+        self.clones.fetch_add(1, Ordering::SeqCst);
+        Self { http: self.http.clone(), clones: self.clones.clone() }
+    }
+}
+
+impl HttpClient for CountingClient {
+    type Error = std::convert::Infallible;
+
+    fn send(
+        &self,
+        method: &str,
+        url: &str,
+        headers: HeaderMap,
+        body: Option<Vec<u8>>,
+        options: RequestOptions,
+    ) -> Result<Response<Vec<u8>>, Self::Error> {
+        self.http.send(method, url, headers, body, options)
+    }
+}
+
+fn config() -> ItemsConfig {
+    ItemsConfig { api_key: "k".repeat(1024) }
+}
+
+#[test]
+fn test_clones_share_the_http_client() {
+    let http = CountingClient::default();
+    let clones = http.clones.clone();
+    let client = ItemsClient::builder().http_client(http).config(config()).build().unwrap();
+
+    let copies: Vec<_> = (0..100).map(|_| client.clone()).collect();
+    assert_eq!(clones.load(Ordering::SeqCst), 0);
+    assert_eq!(copies[99].config().as_ref().unwrap().api_key.len(), 1024);
+}
+
+#[test]
+fn test_changing_a_clone_leaves_the_original() {
+    let http = MockClient::new();
+    let client = ItemsClient::builder().http_client(http.clone()).build().unwrap();
+    let staging = client.clone().with_base_url("https://staging.example.com");
+
+    client.get_item().id(1).send().unwrap();
+    staging.get_item().id(2).send().unwrap();
+
+    let urls: Vec<_> = http.requests().into_iter().map(|request| request.url).collect();
+    assert_eq!(urls, ["https://api.example.com/items/1", "https://staging.example.com/items/2"]);
+}
+
+#[test]
+fn test_one_client_across_threads() {
+    let http = MockClient::new();
+    let client = ItemsClient::builder().http_client(http.clone()).build().unwrap();
+
+    std::thread::scope(|scope| {
+        for id in 0..8 {
+            let client = client.clone();
+            scope.spawn(move || client.get_item().id(id).send().unwrap());
+        }
+    });
+
+    assert_eq!(http.requests().len(), 8);
+}
+
+#[tokio::test]
+async fn test_one_async_client_across_tasks() {
+    let http = MockClient::new();
+    let client = ItemsAsyncClient::builder().http_client(http.clone()).build().unwrap();
+
+    let tasks: Vec<_> = (0..8)
+        .map(|id| {
+            let client = client.clone();
+            tokio::spawn(async move { client.get_item().id(id).send_async().await.unwrap() })
+        })
+        .collect();
+    for task in tasks {
+        task.await.unwrap();
+    }
+
+    assert_eq!(http.requests().len(), 8);
+}
@@ -0,0 +1,115 @@
+#[cfg(feature = "surf")]
+mod surf_tests {
+    use derive_rest_api::{AsyncHttpClient, RequestBuilder, RequestModifier, SurfClient, SurfClientError};
+    use serde::Serialize;
+    use std::io::{Read, Write};
+    use std::net::{SocketAddr, TcpListener};
+    use std::thread::JoinHandle;
+    use std::time::Duration;
+
+    // Accepts a single connection and answers it with `body`, or never answers if `body` is None
+    fn serve_once(body: Option<Vec<u8>>) -> (SocketAddr, JoinHandle<()>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buffer = [0u8; 4096];
+            let _ = stream.read(&mut buffer).unwrap();
+            match body {
+                Some(body) => {
+                    let head = format!(
+                        "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                        body.len()
+                    );
+                    stream.write_all(head.as_bytes()).unwrap();
+                    stream.write_all(&body).unwrap();
+                }
+                None => std::thread::sleep(Duration::from_millis(500)),
+            }
+        });
+        (address, server)
+    }
+
+    #[derive(RequestBuilder, Serialize)]
+    #[request_builder(method = "POST", path = "/posts")]
+    struct CreatePost {
+        #[request_builder(body)]
+        title: String,
+        #[request_builder(body)]
+        #[serde(rename = "userId")]
+        user_id: u64,
+    }
+
+    #[test]
+    fn test_surf_client_creation() {
+        let client = SurfClient::new();
+        drop(client);
+
+        let client_default = SurfClient::default();
+        drop(client_default);
+    }
+
+    #[test]
+    fn test_surf_with_custom_client() {
+        let surf_client: surf::Client = surf::Config::new()
+            .set_timeout(Some(Duration::from_secs(30)))
+            .try_into()
+            .unwrap();
+
+        let client = SurfClient::with_client(surf_client);
+        drop(client);
+    }
+
+    #[test]
+    fn test_surf_send_async_without_tokio() {
+        let (address, server) = serve_once(Some(br#"{"id":101}"#.to_vec()));
+
+        // Driven by a plain executor, with no tokio runtime present
+        let response = futures::executor::block_on(
+            CreatePostBuilder::new()
+                .title("Test Post".to_string())
+                .user_id(1)
+                .async_http_client(SurfClient::new())
+                .base_url(format!("http://{address}"))
+                .timeout(Duration::from_secs(5))
+                .send_async(),
+        )
+        .unwrap();
+
+        server.join().unwrap();
+        assert_eq!(response, br#"{"id":101}"#);
+    }
+
+    #[test]
+    fn test_surf_timeout() {
+        let (address, server) = serve_once(None);
+
+        let result = async_std::task::block_on(SurfClient::new().send_async(
+            "GET",
+            &format!("http://{address}/slow"),
+            Default::default(),
+            None,
+            derive_rest_api::Timeouts::from(Duration::from_millis(50)).into(),
+        ));
+
+        assert!(matches!(result, Err(SurfClientError::Timeout)));
+        server.join().unwrap();
+    }
+
+    #[test]
+    #[ignore] // Requires network connection
+    fn test_surf_real_request() {
+        let result = async_std::task::block_on(
+            CreatePostBuilder::new()
+                .title("Test Post".to_string())
+                .user_id(1)
+                .async_http_client(SurfClient::new())
+                .base_url("https://jsonplaceholder.typicode.com")
+                .send_async(),
+        );
+
+        if let Ok(response) = result {
+            assert!(!response.is_empty());
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use derive_rest_api::http::{HeaderMap, Response};
+use derive_rest_api::{ApiClient, HttpClient, RequestBuilder, ThrottlePolicy, ThrottleRegistry, ThrottledClient};
+use std::time::{Duration, Instant};
+
+#[derive(Debug)]
+struct MockError;
+
+impl std::fmt::Display for MockError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "MockError")
+    }
+}
+
+impl std::error::Error for MockError {}
+
+#[derive(Clone, Default)]
+struct MockClient;
+
+impl derive_rest_api::HttpClient for MockClient {
+    type Error = MockError;
+
+    fn send(
+        &self,
+        _method: &str,
+        _url: &str,
+        _headers: HeaderMap,
+        _body: Option<Vec<u8>>,
+        _options: derive_rest_api::RequestOptions,
+    ) -> Result<Response<Vec<u8>>, Self::Error> {
+        Ok(Response::new(b"ok".to_vec()))
+    }
+}
+
+impl derive_rest_api::AsyncHttpClient for MockClient {
+    type Error = MockError;
+
+    async fn send_async(
+        &self,
+        _method: &str,
+        _url: &str,
+        _headers: HeaderMap,
+        _body: Option<Vec<u8>>,
+        _options: derive_rest_api::RequestOptions,
+    ) -> Result<Response<Vec<u8>>, Self::Error> {
+        Ok(Response::new(b"ok".to_vec()))
+    }
+}
+
+#[derive(RequestBuilder)]
+#[request_builder(method = "GET", path = "/status")]
+struct GetStatus;
+
+#[derive(Clone, ApiClient)]
+#[api_client(base_url = "https://api.example.com", requests(GetStatus))]
+struct StatusApi;
+
+// 20 requests per second with a burst of 2: one token every 50ms
+fn policy(registry: &ThrottleRegistry) -> ThrottlePolicy {
+    ThrottlePolicy::per_second(20).burst(2).registry(registry.clone())
+}
+
+fn send(client: &ThrottledClient<MockClient>, url: &str) {
+    client.send("GET", url, HeaderMap::new(), None, Default::default()).unwrap();
+}
+
+#[test]
+fn test_burst_then_steady_rate() {
+    let registry = ThrottleRegistry::new();
+    let client = ThrottledClient::new(MockClient, policy(&registry));
+
+    let started = Instant::now();
+    send(&client, "https://api.example.com/a");
+    send(&client, "https://api.example.com/b");
+    assert!(started.elapsed() < Duration::from_millis(40));
+
+    send(&client, "https://api.example.com/c");
+    send(&client, "https://api.example.com/d");
+    assert!(started.elapsed() >= Duration::from_millis(95));
+}
+
+#[test]
+fn test_budget_is_per_host_and_shared_between_clients() {
+    let registry = ThrottleRegistry::new();
+    let first = StatusApiClient::<MockClient>::new_with_client(MockClient).with_throttle(policy(&registry));
+    let second = StatusApiClient::<MockClient>::new_with_client(MockClient).with_throttle(policy(&registry));
+
+    let started = Instant::now();
+    first.get_status().send().unwrap();
+    second.get_status().send().unwrap();
+
+    // Another host has its own budget
+    let other = ThrottledClient::new(MockClient, policy(&registry));
+    send(&other, "https://OTHER.example.com:8443/status");
+    send(&other, "https://other.example.com:8443/status");
+    assert!(started.elapsed() < Duration::from_millis(40));
+
+    // The two clients used up the host's burst between them
+    first.get_status().send().unwrap();
+    assert!(started.elapsed() >= Duration::from_millis(45));
+}
+
+#[test]
+fn test_default_client_is_not_throttled() {
+    let client = ThrottledClient::<MockClient>::default();
+    let started = Instant::now();
+    for _ in 0..100 {
+        send(&client, "https://api.example.com/status");
+    }
+    assert!(started.elapsed() < Duration::from_millis(40));
+}
+
+#[tokio::test]
+async fn test_async_client_waits() {
+    let registry = ThrottleRegistry::new();
+    let client = StatusApiAsyncClient::<MockClient>::new_with_client(MockClient).with_throttle(policy(&registry));
+
+    let started = Instant::now();
+    for _ in 0..3 {
+        client.get_status().send_async().await.unwrap();
+    }
+    assert!(started.elapsed() >= Duration::from_millis(45));
+}
@@ -0,0 +1,129 @@
+use derive_rest_api::http::{HeaderMap, Response, StatusCode};
+use derive_rest_api::{
+    ApiClient, FaultInjectingClient, HttpClient, RequestBuilder, RequestOptions, RestApiError, RetryPolicy,
+};
+use std::io::ErrorKind;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+// Mock HTTP client failing every request with a connection reset, or with a
+// permission error if `permanent`
+#[derive(Clone, Default)]
+struct FailingClient {
+    permanent: bool,
+    requests: Arc<Mutex<usize>>,
+}
+
+impl FailingClient {
+    fn permanent() -> Self {
+        Self { permanent: true, ..Self::default() }
+    }
+
+    fn requests(&self) -> usize {
+        *self.requests.lock().unwrap()
+    }
+}
+
+impl HttpClient for FailingClient {
+    type Error = std::io::Error;
+
+    fn send(
+        &self,
+        _method: &str,
+        _url: &str,
+        _headers: HeaderMap,
+        _body: Option<Vec<u8>>,
+        _options: RequestOptions,
+    ) -> Result<Response<Vec<u8>>, Self::Error> {
+        *self.requests.lock().unwrap() += 1;
+        let kind = if self.permanent { ErrorKind::PermissionDenied } else { ErrorKind::ConnectionReset };
+        Err(std::io::Error::from(kind))
+    }
+}
+
+#[derive(RequestBuilder)]
+#[request_builder(method = "GET", path = "/status")]
+struct GetStatus;
+
+#[derive(Clone, ApiClient)]
+#[api_client(base_url = "https://api.example.com", requests(GetStatus))]
+struct StatusConfig;
+
+fn retrying_client<C: HttpClient>(http: C) -> StatusClient<C> {
+    StatusClient::builder()
+        .http_client(http)
+        .retry_policy(RetryPolicy::new(2).backoff(Duration::from_millis(1), Duration::from_millis(1)))
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn test_timeouts_and_dropped_connections_are_transient() {
+    for kind in [ErrorKind::TimedOut, ErrorKind::ConnectionRefused, ErrorKind::ConnectionReset] {
+        assert!(RestApiError::http_error(std::io::Error::from(kind)).is_transient(), "{kind:?}");
+    }
+    assert!(!RestApiError::http_error(std::io::Error::from(ErrorKind::InvalidData)).is_transient());
+    assert!(!RestApiError::http_error(std::fmt::Error).is_transient());
+}
+
+#[test]
+fn test_statuses_and_other_errors() {
+    assert!(RestApiError::Unhealthy { status: StatusCode::TOO_MANY_REQUESTS }.is_transient());
+    assert!(RestApiError::Unhealthy { status: StatusCode::GATEWAY_TIMEOUT }.is_transient());
+    assert!(!RestApiError::Unhealthy { status: StatusCode::INTERNAL_SERVER_ERROR }.is_transient());
+
+    let not_ready = RestApiError::NotReady {
+        timeout: Duration::from_secs(1),
+        source: Box::new(RestApiError::Unhealthy { status: StatusCode::SERVICE_UNAVAILABLE }),
+    };
+    assert!(not_ready.is_transient());
+    assert!(!RestApiError::missing_field("id").is_transient());
+}
+
+#[test]
+fn test_retry_policy_retries_only_transient_errors() {
+    let transient = FailingClient::default();
+    assert!(retrying_client(transient.clone()).get_status().send().unwrap_err().is_transient());
+    assert_eq!(transient.requests(), 3);
+
+    let permanent = FailingClient::permanent();
+    assert!(!retrying_client(permanent.clone()).get_status().send().unwrap_err().is_transient());
+    assert_eq!(permanent.requests(), 1);
+}
+
+#[test]
+fn test_injected_faults_are_transient() {
+    let http = FailingClient::permanent();
+    let error = retrying_client(FaultInjectingClient::new(http.clone(), 1).with_error_rate(1.0))
+        .get_status()
+        .send()
+        .unwrap_err();
+
+    assert!(error.is_transient());
+    assert_eq!(http.requests(), 0);
+}
+
+#[test]
+fn test_error_statuses_are_classified() {
+    let error_for = |status: StatusCode| {
+        let response = Response::builder().status(status).body(b"busy".to_vec()).unwrap();
+        RestApiError::error_for_status(response).unwrap_err()
+    };
+
+    let transient = [
+        StatusCode::TOO_MANY_REQUESTS,
+        StatusCode::BAD_GATEWAY,
+        StatusCode::SERVICE_UNAVAILABLE,
+        StatusCode::GATEWAY_TIMEOUT,
+    ];
+    for status in transient {
+        assert!(error_for(status).is_transient(), "{status}");
+    }
+    let error = error_for(StatusCode::INTERNAL_SERVER_ERROR);
+    assert!(!error.is_transient());
+    assert_eq!(error.status(), Some(StatusCode::INTERNAL_SERVER_ERROR));
+    assert!(matches!(error, RestApiError::ErrorStatus { body, .. } if body == b"busy"));
+
+    let response = Response::builder().status(StatusCode::NOT_MODIFIED).body(Vec::new()).unwrap();
+    assert_eq!(RestApiError::error_for_status(response).unwrap().status(), StatusCode::NOT_MODIFIED);
+}
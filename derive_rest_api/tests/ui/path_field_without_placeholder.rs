@@ -0,0 +1,11 @@
+use derive_rest_api::RequestBuilder;
+
+#[derive(RequestBuilder)]
+#[request_builder(method = "GET", path = "/items/{id}")]
+struct GetItem {
+    id: u64,
+    #[request_builder(path)]
+    r#type: String,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use derive_rest_api::RequestBuilder;
+
+#[derive(RequestBuilder)]
+#[request_builder(method = "GET", path = "/items/{id}", unmarked_fields = "error")]
+struct GetItem {
+    id: u64,
+    r#type: String,
+    limit: Option<u32>,
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+#![deny(deprecated)]
+
+use derive_rest_api::RequestBuilder;
+
+#[derive(RequestBuilder)]
+#[request_builder(method = "GET", path = "/items/{id}")]
+struct GetItem {
+    id: u64,
+    r#type: String,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use derive_rest_api::RequestBuilder;
+
+#[derive(RequestBuilder)]
+#[request_builder(method = "GET", path = "/users/{user_id}/posts/{post}")]
+struct GetPost {
+    id: u64,
+    post: u64,
+}
+
+fn main() {}
@@ -0,0 +1,90 @@
+#![cfg(unix)]
+
+use derive_rest_api::UnixSocketUrl;
+use std::os::unix::net::UnixListener;
+use std::path::{Path, PathBuf};
+
+fn socket_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("derive_rest_api_{}_{}.sock", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+#[test]
+fn test_encoded_url_round_trips() {
+    let base_url = UnixSocketUrl::new("/run/my service/api.sock", "/v2").to_string();
+    assert_eq!(base_url, "unix://%2Frun%2Fmy%20service%2Fapi.sock/v2");
+
+    let url = UnixSocketUrl::parse(&format!("{base_url}/jobs/7?verbose=1")).unwrap();
+    assert_eq!(url.socket(), Path::new("/run/my service/api.sock"));
+    assert_eq!(url.path(), "/v2/jobs/7?verbose=1");
+    assert_eq!(url.http_url(), "http://localhost/v2/jobs/7?verbose=1");
+}
+
+#[test]
+fn test_unencoded_url_splits_at_the_socket_file() {
+    let path = socket_path("split");
+    let _listener = UnixListener::bind(&path).unwrap();
+
+    let url = UnixSocketUrl::parse(&format!("unix://{}/v1.43/containers/json", path.display())).unwrap();
+    assert_eq!(url.socket(), path);
+    assert_eq!(url.path(), "/v1.43/containers/json");
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_unencoded_url_without_a_socket_file() {
+    let url = UnixSocketUrl::parse("unix:///nonexistent/daemon.sock").unwrap();
+    assert_eq!(url.socket(), Path::new("/nonexistent/daemon.sock"));
+    assert_eq!(url.http_url(), "http://localhost/");
+}
+
+#[test]
+fn test_other_urls_are_not_unix_sockets() {
+    assert_eq!(UnixSocketUrl::parse("https://api.example.com/v1"), None);
+    assert_eq!(UnixSocketUrl::parse("unix://"), None);
+}
+
+#[cfg(feature = "reqwest-blocking")]
+#[test]
+fn test_reqwest_blocking_client_talks_to_a_socket() {
+    use derive_rest_api::{ApiClient, RequestBuilder, ReqwestBlockingClient};
+    use serde::Deserialize;
+    use std::io::{Read, Write};
+
+    #[derive(Debug, Deserialize)]
+    struct Version {
+        version: String,
+    }
+
+    #[derive(RequestBuilder)]
+    #[request_builder(method = "GET", path = "/version", response = Version)]
+    struct GetVersion;
+
+    #[derive(Clone, ApiClient)]
+    #[api_client(base_url = "http://localhost", requests(GetVersion))]
+    struct DaemonConfig;
+
+    let path = socket_path("reqwest");
+    let listener = UnixListener::bind(&path).unwrap();
+    let server = std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut request = [0; 1024];
+        let read = stream.read(&mut request).unwrap();
+        let body = r#"{"version":"24.0"}"#;
+        write!(stream, "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", body.len(), body).unwrap();
+        String::from_utf8_lossy(&request[..read]).into_owned()
+    });
+
+    let client = DaemonClient::builder()
+        .http_client(ReqwestBlockingClient::new().unwrap())
+        .base_url(UnixSocketUrl::new(&path, "/v1.43").to_string())
+        .build()
+        .unwrap();
+    let version = client.get_version().send().unwrap();
+
+    assert_eq!(version.version, "24.0");
+    assert!(server.join().unwrap().starts_with("GET /v1.43/version HTTP/1.1"));
+    std::fs::remove_file(&path).unwrap();
+}
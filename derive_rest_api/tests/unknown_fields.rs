@@ -0,0 +1,156 @@
+use derive_rest_api::http::{HeaderMap, Response};
+use derive_rest_api::{RequestBuilder, WithUnknownFields};
+use serde::Deserialize;
+use serde_json::json;
+
+#[derive(Debug)]
+struct MockError;
+
+impl std::fmt::Display for MockError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "MockError")
+    }
+}
+
+impl std::error::Error for MockError {}
+
+// Mock HTTP client that returns a subscription with fields the model lacks
+#[derive(Clone, Default)]
+struct MockClient;
+
+impl derive_rest_api::HttpClient for MockClient {
+    type Error = MockError;
+
+    fn send(
+        &self,
+        _method: &str,
+        _url: &str,
+        _headers: HeaderMap,
+        _body: Option<Vec<u8>>,
+        _options: derive_rest_api::RequestOptions,
+    ) -> Result<Response<Vec<u8>>, Self::Error> {
+        let body = json!({
+            "id": "sub_1",
+            "status": "active",
+            "trial_end": null,
+            "items": [
+                { "price": 900 },
+                { "price": 1500, "discount": { "percent": 10 } }
+            ],
+            "metadata": { "team": "billing" }
+        });
+        Ok(Response::new(body.to_string().into_bytes()))
+    }
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Subscription {
+    id: String,
+    items: Vec<SubscriptionItem>,
+    metadata: serde_json::Value,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct SubscriptionItem {
+    price: u64,
+}
+
+#[derive(RequestBuilder)]
+#[request_builder(method = "GET", path = "/subscriptions/{id}", response = Subscription, capture_unknown_fields)]
+struct GetSubscription {
+    id: String,
+}
+
+#[test]
+fn test_send_captures_unknown_fields() {
+    let subscription = GetSubscriptionBuilder::new()
+        .id("sub_1".to_string())
+        .http_client(MockClient)
+        .base_url("https://billing.example.com")
+        .send()
+        .unwrap();
+
+    assert_eq!(subscription.id, "sub_1");
+    assert_eq!(subscription.items[1].price, 1500);
+    assert!(subscription.has_unknown_fields());
+
+    let mut paths: Vec<_> = subscription.unknown_fields.keys().map(String::as_str).collect();
+    paths.sort();
+    assert_eq!(paths, ["items[1].discount", "status", "trial_end"]);
+    assert_eq!(subscription.unknown_fields["items[1].discount"], json!({ "percent": 10 }));
+
+    // Fields deserialized into untyped values aren't unknown
+    assert_eq!(subscription.into_inner().metadata, json!({ "team": "billing" }));
+}
+
+#[test]
+fn test_modelled_body_has_no_unknown_fields() {
+    #[derive(Debug, Deserialize)]
+    struct Item {
+        price: u64,
+        tax: Option<u64>,
+    }
+
+    let items = WithUnknownFields::<Vec<Item>>::from_slice(br#"[{"price": 1, "tax": null}, {"price": 2}]"#).unwrap();
+    assert_eq!(items.len(), 2);
+    assert_eq!(items[0].price, 1);
+    assert_eq!(items[1].tax, None);
+    assert!(!items.has_unknown_fields());
+}
+
+#[test]
+fn test_mismatched_body_is_an_error() {
+    let result = WithUnknownFields::<Subscription>::from_slice(br#"{"id": 1}"#);
+    assert!(matches!(
+        result,
+        Err(derive_rest_api::RestApiError::ResponseDeserializationError { .. })
+    ));
+}
+
+#[derive(RequestBuilder)]
+#[request_builder(method = "GET", path = "/subscriptions/{id}", response = Subscription, strict_response)]
+struct GetSubscriptionStrict {
+    id: String,
+}
+
+#[test]
+fn test_strict_response_rejects_unknown_fields() {
+    let error = GetSubscriptionStrictBuilder::new()
+        .id("sub_1".to_string())
+        .http_client(MockClient)
+        .base_url("https://billing.example.com")
+        .send()
+        .unwrap_err();
+
+    assert!(matches!(error, derive_rest_api::RestApiError::ResponseDeserializationError { .. }));
+    assert!(
+        error.to_string().contains("unknown fields `items[1].discount`, `status`, `trial_end`"),
+        "{}",
+        error
+    );
+}
+
+#[test]
+fn test_strict_response_applies_to_send_as() {
+    #[derive(Debug, Deserialize)]
+    struct Loose {
+        #[serde(flatten)]
+        fields: serde_json::Map<String, serde_json::Value>,
+    }
+
+    // Every field is collected by the flattened map, so none are unknown
+    let loose: Loose = GetSubscriptionStrictBuilder::new()
+        .id("sub_1".to_string())
+        .http_client(MockClient)
+        .base_url("https://billing.example.com")
+        .send_as()
+        .unwrap();
+    assert_eq!(loose.fields.len(), 5);
+
+    let result = GetSubscriptionStrictBuilder::new()
+        .id("sub_1".to_string())
+        .http_client(MockClient)
+        .base_url("https://billing.example.com")
+        .send_as::<SubscriptionItem>();
+    assert!(result.is_err());
+}
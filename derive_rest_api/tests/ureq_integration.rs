@@ -15,14 +15,135 @@ mod ureq_tests {
 
     #[test]
     fn test_ureq_with_custom_agent() {
-        let agent = ureq::AgentBuilder::new()
-            .timeout(std::time::Duration::from_secs(30))
-            .build();
+        let agent: ureq::Agent = ureq::Agent::config_builder()
+            .timeout_global(Some(std::time::Duration::from_secs(30)))
+            .build()
+            .into();
 
         let client = UreqBlockingClient::with_agent(agent);
         drop(client);
     }
 
+    #[test]
+    fn test_ureq_with_pool_limits_and_connect_timeout() {
+        let client = UreqBlockingClient::with_pool_limits(8, 2)
+            .with_connect_timeout(std::time::Duration::from_secs(5));
+        drop(client);
+    }
+
+    #[test]
+    fn test_ureq_ipv4_only() {
+        use derive_rest_api::{HttpClient, IpFamily};
+
+        let (address, server) = serve_once(b"ok".to_vec());
+        let client = UreqBlockingClient::new()
+            .with_ip_family(IpFamily::Ipv4Only)
+            .with_connect_timeout(std::time::Duration::from_secs(1));
+
+        let url = format!("http://{}/", address);
+        let response = client.send("GET", &url, Default::default(), None, Default::default()).unwrap();
+        assert_eq!(response.body(), b"ok");
+        server.join().unwrap();
+    }
+
+    // Accepts a single connection and answers it with `body`
+    fn serve_once(body: Vec<u8>) -> (std::net::SocketAddr, std::thread::JoinHandle<()>) {
+        serve_once_with_status("200 OK", body)
+    }
+
+    // Accepts a single connection and answers it with `status` and `body`
+    fn serve_once_with_status(
+        status: &'static str,
+        body: Vec<u8>,
+    ) -> (std::net::SocketAddr, std::thread::JoinHandle<()>) {
+        use std::io::{Read, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buffer = [0u8; 4096];
+            let _ = stream.read(&mut buffer).unwrap();
+            let head = format!(
+                "HTTP/1.1 {}\r\nContent-Type: application/octet-stream\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                status,
+                body.len()
+            );
+            stream.write_all(head.as_bytes()).unwrap();
+            // The client may hang up early when it stops reading
+            let _ = stream.write_all(&body);
+        });
+        (address, server)
+    }
+
+    #[test]
+    fn test_ureq_binary_response_is_not_corrupted() {
+        use derive_rest_api::HttpClient;
+
+        // Serve a single response whose body is not valid UTF-8
+        let body: Vec<u8> = vec![0x89, b'P', b'N', b'G', 0x00, 0xff, 0xfe, 0x80];
+        let (address, server) = serve_once(body.clone());
+
+        let client = UreqBlockingClient::new();
+        let response = client
+            .send(
+                "GET",
+                &format!("http://{address}/image.png"),
+                Default::default(),
+                None,
+                derive_rest_api::Timeouts::from(std::time::Duration::from_secs(5)).into(),
+            )
+            .unwrap();
+
+        server.join().unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(response.headers()["content-type"], "application/octet-stream");
+        assert_eq!(response.body(), &body);
+    }
+
+    #[test]
+    fn test_ureq_error_status_is_returned_as_response() {
+        use derive_rest_api::HttpClient;
+
+        let (address, server) = serve_once_with_status("401 Unauthorized", b"denied".to_vec());
+
+        let client = UreqBlockingClient::new();
+        let response = client
+            .send(
+                "GET",
+                &format!("http://{address}/private"),
+                Default::default(),
+                None,
+                Default::default(),
+            )
+            .unwrap();
+
+        server.join().unwrap();
+        assert_eq!(response.status(), 401);
+        assert_eq!(response.body(), b"denied");
+    }
+
+    #[test]
+    fn test_ureq_stops_reading_past_limit() {
+        use derive_rest_api::{HttpClient, RequestOptions};
+
+        let (address, server) = serve_once(vec![b'x'; 64 * 1024]);
+
+        let client = UreqBlockingClient::new();
+        let response = client
+            .send(
+                "GET",
+                &format!("http://{address}/large"),
+                Default::default(),
+                None,
+                RequestOptions::new().with_max_response_bytes(16),
+            )
+            .unwrap();
+
+        server.join().unwrap();
+        assert_eq!(response.body().len(), 17);
+    }
+
     #[test]
     #[ignore] // Requires network connection
     fn test_ureq_real_request() {
@@ -1,4 +1,4 @@
-use derive_rest_api::RequestBuilder;
+use derive_rest_api::{RequestBuilder, RestApiError};
 use serde::Serialize;
 
 #[test]
@@ -58,8 +58,9 @@ fn test_path_with_string_param() {
 
 #[test]
 fn test_path_with_extra_fields() {
+    // Fields without a kind are left out of the request, here without a warning
     #[derive(RequestBuilder)]
-    #[request_builder(path = "/api/users/{id}")]
+    #[request_builder(path = "/api/users/{id}", unmarked_fields = "allow")]
     struct GetUser {
         id: u64,
         #[expect(unused)]
@@ -225,3 +226,148 @@ fn test_query_params_mixed() {
     assert!(url.contains("limit=20"));
     assert!(!url.contains("offset"));
 }
+
+#[test]
+fn test_static_query_params() {
+    #[derive(RequestBuilder)]
+    #[request_builder(path = "/api/posts/{id}", static_query("include" = "comments", "format" = "full view"))]
+    struct GetPost {
+        id: u64,
+    }
+
+    let post = GetPostBuilder::new().id(5).build().unwrap();
+
+    let url = post.build_url().unwrap();
+    assert_eq!(url, "/api/posts/5?include=comments&format=full%20view");
+}
+
+#[test]
+fn test_static_query_params_with_query_fields() {
+    #[derive(RequestBuilder, Serialize)]
+    #[request_builder(path = "/api/search", static_query("fields[post]" = "title"))]
+    struct Search {
+        #[request_builder(query)]
+        q: Option<String>,
+    }
+
+    let search = SearchBuilder::new().q("rust".to_string()).build().unwrap();
+    assert_eq!(search.build_url().unwrap(), "/api/search?fields%5Bpost%5D=title&q=rust");
+
+    // Static parameters are sent even when no query field is set
+    let search = SearchBuilder::new().build().unwrap();
+    assert_eq!(search.build_url().unwrap(), "/api/search?fields%5Bpost%5D=title");
+}
+
+#[test]
+fn test_wildcard_path_params() {
+    #[derive(RequestBuilder)]
+    #[request_builder(path = "/buckets/{bucket}/objects/{key*}")]
+    struct GetObject {
+        bucket: String,
+        key: String,
+    }
+
+    let object = GetObjectBuilder::new()
+        .bucket("artifacts".to_string())
+        .key("/builds/2024 q1/app#1.tar.gz".to_string())
+        .build()
+        .unwrap();
+
+    // Slashes are kept, everything else outside the unreserved set is encoded
+    assert_eq!(object.build_url().unwrap(), "/buckets/artifacts/objects/builds/2024%20q1/app%231.tar.gz");
+}
+
+#[test]
+fn test_raw_identifier_path_params() {
+    #[derive(RequestBuilder)]
+    #[request_builder(path = "/resources/{type}/{id}")]
+    struct GetResource {
+        r#type: String,
+        #[request_builder(path)]
+        id: u64,
+    }
+
+    let resource = GetResourceBuilder::new().r#type("books".to_string()).id(7).build().unwrap();
+    assert_eq!(resource.build_url().unwrap(), "/resources/books/7");
+}
+
+#[test]
+fn test_path_params_are_encoded_as_one_segment() {
+    #[derive(RequestBuilder)]
+    #[request_builder(path = "/users/{name}/files/{file}")]
+    struct GetFile {
+        name: String,
+        file: String,
+    }
+
+    let file = GetFileBuilder::new()
+        .name("../admin".to_string())
+        .file("a b/c?d=1#e".to_string())
+        .build()
+        .unwrap();
+
+    // Unlike `{key*}`, slashes are encoded too, so values can't add segments or a query
+    assert_eq!(file.build_url().unwrap(), "/users/..%2Fadmin/files/a%20b%2Fc%3Fd%3D1%23e");
+}
+
+#[test]
+fn test_dot_segments_are_rejected() {
+    #[derive(RequestBuilder)]
+    #[request_builder(path = "/buckets/{bucket}/objects/{key*}")]
+    struct GetObject {
+        bucket: String,
+        key: String,
+    }
+
+    let url = |bucket: &str, key: &str| {
+        GetObjectBuilder::new().bucket(bucket.to_string()).key(key.to_string()).build().unwrap().build_url()
+    };
+
+    for key in ["../admin", "builds/../../admin", "builds/./app", "..", "/.."] {
+        let error = url("artifacts", key).unwrap_err();
+        assert!(matches!(error, RestApiError::InvalidPathParameter { ref param, .. } if param == "key"), "{key}");
+    }
+    let error = url("..", "app").unwrap_err();
+    assert!(matches!(error, RestApiError::InvalidPathParameter { ref param, .. } if param == "bucket"));
+
+    // Dots within a segment are fine
+    assert_eq!(url("artifacts", "builds/..app/.env").unwrap(), "/buckets/artifacts/objects/builds/..app/.env");
+}
+
+#[test]
+fn test_flattened_query_params() {
+    #[derive(Serialize, Clone, Copy)]
+    struct Pagination {
+        page: u32,
+        per_page: u32,
+    }
+
+    #[derive(RequestBuilder, Serialize)]
+    #[request_builder(path = "/api/repos")]
+    struct ListRepos {
+        #[request_builder(query)]
+        sort: Option<String>,
+        #[request_builder(query, flatten)]
+        pagination: Pagination,
+    }
+
+    #[derive(RequestBuilder, Serialize)]
+    #[request_builder(path = "/api/issues")]
+    struct ListIssues {
+        #[request_builder(query, flatten)]
+        pagination: Option<Pagination>,
+    }
+
+    let pagination = Pagination { page: 2, per_page: 50 };
+    let repos = ListReposBuilder::new()
+        .sort("updated".to_string())
+        .pagination(pagination)
+        .build()
+        .unwrap();
+    assert_eq!(repos.build_url().unwrap(), "/api/repos?sort=updated&page=2&per_page=50");
+
+    let issues = ListIssuesBuilder::new().pagination(pagination).build().unwrap();
+    assert_eq!(issues.build_url().unwrap(), "/api/issues?page=2&per_page=50");
+    let issues = ListIssuesBuilder::new().build().unwrap();
+    assert_eq!(issues.build_url().unwrap(), "/api/issues");
+}
@@ -40,26 +40,20 @@ pub(crate) fn generate_api_client(input: syn::DeriveInput) -> syn::Result<TokenS
     let client_name = generate_client_name(struct_name);
     let async_client_name = generate_async_client_name(struct_name);
 
-    // Generate the blocking client
-    let blocking_client = generate_blocking_client(
-        struct_name,
-        &client_name,
-        &attrs,
-        attrs.use_default,
-    );
-
-    // Generate the async client
-    let async_client = generate_async_client(
-        struct_name,
-        &async_client_name,
-        &attrs,
-        attrs.use_default,
-    );
+    let blocking_client = generate_client(struct_name, &client_name, &attrs, ClientKind::Blocking);
+    let async_client = generate_client(struct_name, &async_client_name, &attrs, ClientKind::Async);
+
+    let config_loader = generate_config_loader(struct_name, &attrs);
+    let async_trait = generate_async_trait(struct_name, &async_client_name, &attrs);
+    let cli = generate_cli(struct_name, &input.vis, &client_name, &async_client_name, &attrs);
 
     Ok(quote! {
         #no_config_impl
+        #config_loader
         #blocking_client
         #async_client
+        #async_trait
+        #cli
     })
 }
 
@@ -67,8 +61,41 @@ pub(crate) fn generate_api_client(input: syn::DeriveInput) -> syn::Result<TokenS
 #[derive(Debug)]
 struct ApiClientAttributes {
     base_url: String,
+    /// Further base URLs to fail over to, from `base_urls(...)`
+    failover_urls: Option<Vec<String>>,
+    /// How to spread requests across the `base_urls`
+    balance: Balance,
     requests: Vec<RequestMapping>,
     use_default: bool,
+    /// cfg predicate applied to the generated blocking client
+    blocking_cfg: Option<syn::Meta>,
+    /// cfg predicate applied to the generated async client
+    async_cfg: Option<syn::Meta>,
+    /// Product token (e.g. `"my-sdk/1.2.3"`) for the default User-Agent
+    user_agent: Option<syn::Expr>,
+    /// Header carrying the API version, `X-API-Version` by default
+    version_header: String,
+    /// API version sent with every request
+    version: Option<String>,
+    /// Path of the health endpoint, from `health = "..."`
+    health: Option<String>,
+    /// Prefix of the environment variables the config is loaded from, from `env_prefix = "..."`
+    env_prefix: Option<String>,
+    /// Whether to generate an object-safe trait implemented by the async client, from `async_trait`
+    async_trait: bool,
+    /// Whether to generate a `clap` subcommand per request, from `cli`
+    cli: bool,
+}
+
+/// Load balancing across `base_urls(...)`
+#[derive(Debug)]
+enum Balance {
+    /// Always prefer the active base URL
+    None,
+    /// Take turns, from `round_robin`
+    RoundRobin,
+    /// Take turns in proportion to `weights(...)`
+    Weighted(Vec<u32>),
 }
 
 /// Maps a request struct to a method name
@@ -95,8 +122,19 @@ fn parse_api_client_attributes(attrs: &[syn::Attribute]) -> syn::Result<ApiClien
 impl Parse for ApiClientAttributes {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
         let mut base_url: Option<String> = None;
+        let mut base_urls: Option<Vec<String>> = None;
+        let mut balance = Balance::None;
         let mut requests: Option<Vec<RequestMapping>> = None;
         let mut use_default = false;
+        let mut blocking_cfg: Option<syn::Meta> = None;
+        let mut async_cfg: Option<syn::Meta> = None;
+        let mut user_agent: Option<syn::Expr> = None;
+        let mut version_header: Option<String> = None;
+        let mut version: Option<String> = None;
+        let mut health: Option<String> = None;
+        let mut env_prefix: Option<String> = None;
+        let mut async_trait = false;
+        let mut cli = false;
 
         while !input.is_empty() {
             let key: Ident = input.parse()?;
@@ -105,12 +143,62 @@ impl Parse for ApiClientAttributes {
                 input.parse::<Token![=]>()?;
                 let lit: LitStr = input.parse()?;
                 base_url = Some(lit.value());
+            } else if key == "base_urls" {
+                let content;
+                syn::parenthesized!(content in input);
+                let urls = Punctuated::<LitStr, Comma>::parse_terminated(&content)?;
+                if urls.is_empty() {
+                    return Err(syn::Error::new_spanned(&key, "'base_urls' needs at least one URL"));
+                }
+                base_urls = Some(urls.iter().map(LitStr::value).collect());
+            } else if key == "round_robin" {
+                balance = Balance::RoundRobin;
+            } else if key == "weights" {
+                let content;
+                syn::parenthesized!(content in input);
+                let weights = Punctuated::<syn::LitInt, Comma>::parse_terminated(&content)?;
+                let weights = weights.iter().map(syn::LitInt::base10_parse).collect::<syn::Result<Vec<u32>>>()?;
+                if weights.iter().all(|&weight| weight == 0) {
+                    return Err(syn::Error::new_spanned(&key, "'weights' needs a positive weight"));
+                }
+                balance = Balance::Weighted(weights);
             } else if key == "requests" {
                 let content;
                 syn::parenthesized!(content in input);
                 requests = Some(parse_request_mappings(&content)?);
             } else if key == "default" {
                 use_default = true;
+            } else if key == "blocking_cfg" {
+                input.parse::<Token![=]>()?;
+                let lit: LitStr = input.parse()?;
+                blocking_cfg = Some(lit.parse()?);
+            } else if key == "async_cfg" {
+                input.parse::<Token![=]>()?;
+                let lit: LitStr = input.parse()?;
+                async_cfg = Some(lit.parse()?);
+            } else if key == "user_agent" {
+                input.parse::<Token![=]>()?;
+                user_agent = Some(input.parse()?);
+            } else if key == "version_header" {
+                input.parse::<Token![=]>()?;
+                let lit: LitStr = input.parse()?;
+                version_header = Some(lit.value());
+            } else if key == "version" {
+                input.parse::<Token![=]>()?;
+                let lit: LitStr = input.parse()?;
+                version = Some(lit.value());
+            } else if key == "health" {
+                input.parse::<Token![=]>()?;
+                let lit: LitStr = input.parse()?;
+                health = Some(lit.value());
+            } else if key == "env_prefix" {
+                input.parse::<Token![=]>()?;
+                let lit: LitStr = input.parse()?;
+                env_prefix = Some(lit.value());
+            } else if key == "async_trait" {
+                async_trait = true;
+            } else if key == "cli" {
+                cli = true;
             } else {
                 return Err(syn::Error::new_spanned(
                     &key,
@@ -124,14 +212,45 @@ impl Parse for ApiClientAttributes {
             }
         }
 
+        let (base_url, failover_urls) = match (base_url, base_urls) {
+            (Some(_), Some(_)) => {
+                return Err(syn::Error::new(input.span(), "'base_url' and 'base_urls' cannot be combined"));
+            }
+            (Some(base_url), None) => (base_url, None),
+            (None, Some(base_urls)) => (base_urls[0].clone(), Some(base_urls)),
+            (None, None) => {
+                return Err(syn::Error::new(input.span(), "Missing 'base_url' attribute"));
+            }
+        };
+
+        match (&balance, &failover_urls) {
+            (Balance::None, _) => {}
+            (_, None) => {
+                return Err(syn::Error::new(input.span(), "'round_robin' and 'weights' require 'base_urls'"));
+            }
+            (Balance::Weighted(weights), Some(urls)) if weights.len() != urls.len() => {
+                return Err(syn::Error::new(input.span(), "'weights' needs one weight per base URL"));
+            }
+            _ => {}
+        }
+
         Ok(ApiClientAttributes {
-            base_url: base_url.ok_or_else(|| {
-                syn::Error::new(input.span(), "Missing 'base_url' attribute")
-            })?,
+            base_url,
+            failover_urls,
+            balance,
             requests: requests.ok_or_else(|| {
                 syn::Error::new(input.span(), "Missing 'requests' attribute")
             })?,
             use_default,
+            blocking_cfg,
+            async_cfg,
+            user_agent,
+            version_header: version_header.unwrap_or_else(|| "X-API-Version".to_string()),
+            version,
+            health,
+            env_prefix,
+            async_trait,
+            cli,
         })
     }
 }
@@ -175,129 +294,926 @@ fn generate_async_client_name(struct_name: &Ident) -> Ident {
     quote::format_ident!("{}AsyncClient", base_name)
 }
 
-/// Generate the blocking client struct and impl
-fn generate_blocking_client(
+/// Generate the name of the trait from `async_trait`
+fn generate_async_trait_name(struct_name: &Ident) -> Ident {
+    let name = struct_name.to_string();
+    let base_name = name.strip_suffix("Config").unwrap_or(&name);
+    quote::format_ident!("{}Async", base_name)
+}
+
+/// Generate the object-safe trait for `#[api_client(async_trait)]`, and its impl for the async client
+///
+/// Each method takes a built request and sends it through the client's builder
+/// for that request, so `Arc<dyn MyApiAsync>` can stand in for the client.
+fn generate_async_trait(config_struct: &Ident, async_client_name: &Ident, attrs: &ApiClientAttributes) -> TokenStream {
+    if !attrs.async_trait {
+        return quote! {};
+    }
+
+    let trait_name = generate_async_trait_name(config_struct);
+    let cfg_attr = generate_cfg_attr(attrs.async_cfg.as_ref());
+    let (signatures, methods): (Vec<_>, Vec<_>) = attrs.requests.iter().map(|mapping| {
+        let struct_name = &mapping.struct_name;
+        let method_name = mapping.method_name.as_ref()
+            .map(|s| quote::format_ident!("{}", s))
+            .unwrap_or_else(|| quote::format_ident!("{}", pascal_to_snake_case(&struct_name.to_string())));
+        let signature = quote! {
+            fn #method_name(&self, request: #struct_name)
+                -> derive_rest_api::ResponseFuture<'_, <#struct_name as derive_rest_api::RestRequest>::Response>
+        };
+        let method = quote! {
+            #signature {
+                std::boxed::Box::pin(self.#method_name().with_request(request).send_async())
+            }
+        };
+        (signature, method)
+    }).unzip();
+
+    // Off wasm32 the trait is `Send + Sync`, like the futures it returns, so
+    // `Arc<dyn MyApiAsync>` can be shared with spawned tasks
+    quote! {
+        #[doc = concat!("The requests of [`", stringify!(#async_client_name), "`], as an object-safe trait.")]
+        #[doc = ""]
+        #[doc = concat!("Code holding an `Arc<dyn ", stringify!(#trait_name), ">` can be given fakes, caches or")]
+        #[doc = "recording decorators instead of the client."]
+        #cfg_attr
+        #[cfg(not(target_arch = "wasm32"))]
+        pub trait #trait_name: Send + Sync {
+            #(
+                #[doc = "Sends the request, as `send_async()` on the client's builder for it does."]
+                #signatures;
+            )*
+        }
+
+        #[doc = concat!("The requests of [`", stringify!(#async_client_name), "`], as an object-safe trait.")]
+        #[doc = ""]
+        #[doc = concat!("Code holding an `Arc<dyn ", stringify!(#trait_name), ">` can be given fakes, caches or")]
+        #[doc = "recording decorators instead of the client."]
+        #cfg_attr
+        #[cfg(target_arch = "wasm32")]
+        pub trait #trait_name {
+            #(
+                #[doc = "Sends the request, as `send_async()` on the client's builder for it does."]
+                #signatures;
+            )*
+        }
+
+        #cfg_attr
+        #[cfg(not(target_arch = "wasm32"))]
+        impl<A: derive_rest_api::AsyncHttpClient + Send + Sync> #trait_name for #async_client_name<A> {
+            #(#methods)*
+        }
+
+        #cfg_attr
+        #[cfg(target_arch = "wasm32")]
+        impl<A: derive_rest_api::AsyncHttpClient> #trait_name for #async_client_name<A> {
+            #(#methods)*
+        }
+    }
+}
+
+/// Generate the command name of `cli`
+fn generate_command_name(struct_name: &Ident) -> Ident {
+    let name = struct_name.to_string();
+    let base_name = name.strip_suffix("Config").unwrap_or(&name);
+    quote::format_ident!("{}Command", base_name)
+}
+
+/// Generate the `clap` subcommands for `#[api_client(cli)]`, and `run_command()` on the clients
+///
+/// Each request struct must have `#[request_builder(cli)]`. A subcommand is
+/// named after the request's method in kebab case, and running it sends the
+/// parsed request through the client's builder for it with `send_raw()`, so
+/// error statuses are returned rather than decoded. The enum has the config
+/// struct's visibility, as its variants hold the request structs.
+fn generate_cli(
     config_struct: &Ident,
+    vis: &syn::Visibility,
     client_name: &Ident,
+    async_client_name: &Ident,
     attrs: &ApiClientAttributes,
-    use_default: bool,
 ) -> TokenStream {
-    let base_url = &attrs.base_url;
+    if !attrs.cli {
+        return quote! {};
+    }
 
-    // Generate methods for each request
-    let methods: Vec<_> = attrs.requests.iter().map(|mapping| {
-        let struct_name = &mapping.struct_name;
-        let builder_name = quote::format_ident!("{}Builder", struct_name);
+    let command_name = generate_command_name(config_struct);
+    let clap = quote! { derive_rest_api::__private::clap };
+    let blocking_cfg_attr = generate_blocking_cfg_attr(attrs.blocking_cfg.as_ref());
+    let async_cfg_attr = generate_cfg_attr(attrs.async_cfg.as_ref());
 
-        let method_name = mapping.method_name.as_ref()
+    let struct_names: Vec<_> = attrs.requests.iter().map(|mapping| &mapping.struct_name).collect();
+    let method_names: Vec<_> = attrs.requests.iter().map(|mapping| {
+        mapping.method_name.as_ref()
             .map(|s| quote::format_ident!("{}", s))
-            .unwrap_or_else(|| {
-                let name = pascal_to_snake_case(&struct_name.to_string());
-                quote::format_ident!("{}", name)
-            });
+            .unwrap_or_else(|| quote::format_ident!("{}", pascal_to_snake_case(&mapping.struct_name.to_string())))
+    }).collect();
+    let subcommands: Vec<_> = method_names.iter().map(|method_name| method_name.to_string().replace('_', "-")).collect();
 
-        quote! {
-            #[doc = concat!("Creates a new [`", stringify!(#struct_name), "`] request builder.")]
+    quote! {
+        #[doc = concat!("The requests of [`", stringify!(#config_struct), "`] as `clap` subcommands, for [`")]
+        #[doc = concat!(stringify!(#client_name), "::run_command`].")]
+        #vis enum #command_name {
+            #(
+                #[doc = concat!("Sends a [`", stringify!(#struct_names), "`] request.")]
+                #struct_names(#struct_names),
+            )*
+        }
+
+        impl #clap::FromArgMatches for #command_name {
+            fn from_arg_matches(__matches: &#clap::ArgMatches) -> std::result::Result<Self, #clap::Error> {
+                match __matches.subcommand() {
+                    #(
+                        std::option::Option::Some((#subcommands, __matches)) => std::result::Result::Ok(
+                            Self::#struct_names(<#struct_names as #clap::FromArgMatches>::from_arg_matches(__matches)?),
+                        ),
+                    )*
+                    std::option::Option::Some((__name, _)) => std::result::Result::Err(#clap::Error::raw(
+                        #clap::error::ErrorKind::InvalidSubcommand,
+                        std::format!("unrecognized subcommand '{}'\n", __name),
+                    )),
+                    std::option::Option::None => std::result::Result::Err(#clap::Error::raw(
+                        #clap::error::ErrorKind::MissingSubcommand,
+                        "a subcommand is required\n",
+                    )),
+                }
+            }
+
+            fn update_from_arg_matches(&mut self, __matches: &#clap::ArgMatches) -> std::result::Result<(), #clap::Error> {
+                *self = <Self as #clap::FromArgMatches>::from_arg_matches(__matches)?;
+                std::result::Result::Ok(())
+            }
+        }
+
+        impl #clap::Subcommand for #command_name {
+            fn augment_subcommands(__command: #clap::Command) -> #clap::Command {
+                __command
+                    #(
+                        .subcommand({
+                            let __subcommand = <#struct_names as #clap::Args>::augment_args(#clap::Command::new(#subcommands));
+                            match #struct_names::__cli_about() {
+                                std::option::Option::Some(__about) => __subcommand.about(__about),
+                                std::option::Option::None => __subcommand,
+                            }
+                        })
+                    )*
+                    .subcommand_required(true)
+            }
+
+            fn augment_subcommands_for_update(__command: #clap::Command) -> #clap::Command {
+                <Self as #clap::Subcommand>::augment_subcommands(__command)
+            }
+
+            fn has_subcommand(__name: &str) -> bool {
+                [#(#subcommands),*].contains(&__name)
+            }
+        }
+
+        #blocking_cfg_attr
+        impl<C: derive_rest_api::HttpClient> #client_name<C> {
+            #[doc = "Sends the request parsed from a subcommand, returning the raw response."]
             #[doc = ""]
-            #[doc = "The builder is pre-configured with the client's HTTP client and base URL."]
-            #[doc = "If the config implements `ConfigureRequest`, it will also be pre-configured with those settings."]
-            pub fn #method_name(&self) -> #builder_name<C, ()> {
-                let builder = #builder_name::new()
-                    .http_client((&self.client).clone())
-                    .base_url(&self.base_url);
+            #[doc = "Error statuses are returned as responses, as with `send_raw()`."]
+            #[doc = ""]
+            #[doc = "# Errors"]
+            #[doc = ""]
+            #[doc = "Returns the same errors as `send_raw()`."]
+            pub fn run_command(
+                &self,
+                command: #command_name,
+            ) -> std::result::Result<derive_rest_api::http::Response<std::vec::Vec<u8>>, derive_rest_api::RestApiError> {
+                match command {
+                    #(
+                        #command_name::#struct_names(request) => self.#method_names().with_request(request).send_raw(),
+                    )*
+                }
+            }
+        }
 
-                // Apply configuration if the config implements ConfigureRequest
-                if let std::option::Option::Some(config) = &self.config {
-                    <#config_struct as derive_rest_api::ConfigureRequest>::configure(config, builder)
-                } else {
-                    builder
+        #async_cfg_attr
+        impl<A: derive_rest_api::AsyncHttpClient> #async_client_name<A> {
+            #[doc = "Sends the request parsed from a subcommand, returning the raw response."]
+            #[doc = ""]
+            #[doc = "Error statuses are returned as responses, as with `send_raw_async()`."]
+            #[doc = ""]
+            #[doc = "# Errors"]
+            #[doc = ""]
+            #[doc = "Returns the same errors as `send_raw_async()`."]
+            pub async fn run_command(
+                &self,
+                command: #command_name,
+            ) -> std::result::Result<derive_rest_api::http::Response<std::vec::Vec<u8>>, derive_rest_api::RestApiError> {
+                match command {
+                    #(
+                        #command_name::#struct_names(request) => self.#method_names().with_request(request).send_raw_async().await,
+                    )*
                 }
             }
         }
-    }).collect();
+    }
+}
+
+/// Generate the initial User-Agent value of a client
+fn generate_initial_user_agent(user_agent: Option<&syn::Expr>) -> TokenStream {
+    match user_agent {
+        Some(product) => quote! {
+            std::option::Option::Some(derive_rest_api::__private::user_agent(#product))
+        },
+        None => quote! { std::option::Option::None },
+    }
+}
+
+/// Generate the initial API version of a client
+fn generate_initial_api_version(version: Option<&String>) -> TokenStream {
+    match version {
+        Some(version) => quote! { std::option::Option::Some(#version.to_string()) },
+        None => quote! { std::option::Option::None },
+    }
+}
+
+/// Generate the initial failover of a client
+fn generate_initial_failover(failover_urls: Option<&Vec<String>>, balance: &Balance) -> TokenStream {
+    let Some(urls) = failover_urls else {
+        return quote! { std::option::Option::None };
+    };
+    let balance = match balance {
+        Balance::None => quote! {},
+        Balance::RoundRobin => quote! { .round_robin() },
+        Balance::Weighted(weights) => quote! { .weighted([#(#weights),*]) },
+    };
+    quote! {
+        std::option::Option::Some(derive_rest_api::Failover::new([#(#urls),*])#balance)
+    }
+}
+
+/// Generate the headers a client sends with its health checks
+fn generate_health_headers(version_header: &str) -> TokenStream {
+    quote! {
+        let mut headers = self.inner.default_headers.clone();
+        if let std::option::Option::Some(user_agent) = &self.inner.user_agent {
+            let value = derive_rest_api::http::HeaderValue::from_str(user_agent)
+                .map_err(|e| derive_rest_api::RestApiError::invalid_header("User-Agent", e.to_string()))?;
+            headers.insert(derive_rest_api::http::header::USER_AGENT, value);
+        }
+        if let std::option::Option::Some(version) = &self.inner.api_version {
+            let value = derive_rest_api::http::HeaderValue::from_str(version)
+                .map_err(|e| derive_rest_api::RestApiError::invalid_header(#version_header, e.to_string()))?;
+            headers.insert(#version_header, value);
+        }
+    }
+}
 
-    // Generate the initial config value based on the use_default flag
-    let initial_config = if use_default {
+/// Generate `health_check()` and `wait_until_ready()` for a blocking client
+fn generate_blocking_health_methods(attrs: &ApiClientAttributes) -> TokenStream {
+    let Some(path) = &attrs.health else {
+        return quote! {};
+    };
+    let headers = generate_health_headers(&attrs.version_header);
+    let check_doc = format!("Sends a `GET` to the health endpoint, `{}`, succeeding on a `2xx` status.", path);
+
+    quote! {
+        #[doc = #check_doc]
+        #[doc = ""]
+        #[doc = "# Errors"]
+        #[doc = ""]
+        #[doc = "Returns `RestApiError::Unhealthy` for any other status, or the HTTP client's error."]
+        pub fn health_check(&self) -> std::result::Result<(), derive_rest_api::RestApiError> {
+            self.__check_health(derive_rest_api::RequestOptions::default())
+        }
+
+        #[doc = "Repeats `health_check()`, backing off between attempts, until it succeeds or `timeout` passes."]
+        #[doc = ""]
+        #[doc = "Use this to hold back startup until the API is available."]
+        #[doc = ""]
+        #[doc = "# Errors"]
+        #[doc = ""]
+        #[doc = "Returns `RestApiError::NotReady`, wrapping the last check's error, once `timeout` passes."]
+        #[cfg(not(target_arch = "wasm32"))]
+        pub fn wait_until_ready(&self, timeout: std::time::Duration) -> std::result::Result<(), derive_rest_api::RestApiError> {
+            derive_rest_api::__private::wait_until_ready(timeout, |options| self.__check_health(options))
+        }
+
+        fn __check_health(&self, options: derive_rest_api::RequestOptions) -> std::result::Result<(), derive_rest_api::RestApiError> {
+            #headers
+            derive_rest_api::__private::check_health(&self.inner.client, &self.inner.transport, &self.inner.base_url, #path, headers, options)
+        }
+    }
+}
+
+/// Generate `health_check()` and `wait_until_ready()` for an async client
+fn generate_async_health_methods(attrs: &ApiClientAttributes) -> TokenStream {
+    let Some(path) = &attrs.health else {
+        return quote! {};
+    };
+    let headers = generate_health_headers(&attrs.version_header);
+    let check_doc = format!("Sends a `GET` to the health endpoint, `{}`, succeeding on a `2xx` status.", path);
+
+    quote! {
+        #[doc = #check_doc]
+        #[doc = ""]
+        #[doc = "# Errors"]
+        #[doc = ""]
+        #[doc = "Returns `RestApiError::Unhealthy` for any other status, or the HTTP client's error."]
+        pub async fn health_check(&self) -> std::result::Result<(), derive_rest_api::RestApiError> {
+            self.__check_health(derive_rest_api::RequestOptions::default()).await
+        }
+
+        #[doc = "Repeats `health_check()`, backing off between attempts, until it succeeds or `timeout` passes."]
+        #[doc = ""]
+        #[doc = "Use this to hold back startup until the API is available. The wait doesn't depend on an async runtime."]
+        #[doc = ""]
+        #[doc = "# Errors"]
+        #[doc = ""]
+        #[doc = "Returns `RestApiError::NotReady`, wrapping the last check's error, once `timeout` passes."]
+        #[cfg(not(target_arch = "wasm32"))]
+        pub async fn wait_until_ready(&self, timeout: std::time::Duration) -> std::result::Result<(), derive_rest_api::RestApiError> {
+            derive_rest_api::__private::wait_until_ready_async(timeout, |options| self.__check_health(options)).await
+        }
+
+        async fn __check_health(&self, options: derive_rest_api::RequestOptions) -> std::result::Result<(), derive_rest_api::RestApiError> {
+            #headers
+            derive_rest_api::__private::check_health_async(&self.inner.client, &self.inner.transport, &self.inner.base_url, #path, headers, options).await
+        }
+    }
+}
+
+/// Generate a compile-time check that a client is `Send` and `Sync` whenever
+/// its config and HTTP client are
+///
+/// The futures of its async methods are checked in the library. The config
+/// bound is written as `for<'__send> Config: Send` so that a config that
+/// isn't `Send` skips the check instead of failing to compile.
+fn generate_send_sync_assertions(
+    config_struct: &Ident,
+    client_name: &Ident,
+    client_trait: TokenStream,
+    cfg_attr: &TokenStream,
+) -> TokenStream {
+    quote! {
+        #cfg_attr
+        const _: () = {
+            fn assert_send_sync<T: std::marker::Send + std::marker::Sync>(_: &T) {}
+
+            #[allow(dead_code)]
+            fn assert_client<C: #client_trait + std::marker::Send + std::marker::Sync>(client: &#client_name<C>)
+            where
+                for<'__send> #config_struct: std::marker::Send + std::marker::Sync,
+            {
+                assert_send_sync(client);
+            }
+        };
+    }
+}
+
+/// Which of a config's two clients is being generated
+#[derive(Clone, Copy)]
+enum ClientKind {
+    Blocking,
+    Async,
+}
+
+impl ClientKind {
+    /// The client trait bound, `HttpClient` or `AsyncHttpClient`
+    fn client_trait(self) -> TokenStream {
+        match self {
+            ClientKind::Blocking => quote! { derive_rest_api::HttpClient },
+            ClientKind::Async => quote! { derive_rest_api::AsyncHttpClient },
+        }
+    }
+
+    /// The HTTP client type used unless another is given
+    fn default_client(self) -> TokenStream {
+        match self {
+            ClientKind::Blocking => quote! { derive_rest_api::DefaultBlockingClient },
+            ClientKind::Async => quote! { derive_rest_api::DefaultAsyncClient },
+        }
+    }
+
+    /// The doc lines listing which features select the default client
+    fn default_client_docs(self) -> &'static [&'static str] {
+        match self {
+            ClientKind::Blocking => &[
+                "- `ureq-blocking` → `UreqBlockingClient`",
+                "- `reqwest-blocking` → `ReqwestBlockingClient`",
+                "- `isahc` → `IsahcClient`",
+                "- `attohttpc-blocking` → `AttohttpcClient`",
+            ],
+            ClientKind::Async => &[
+                "- `reqwest-async` → `ReqwestAsyncClient`",
+                "- `isahc` → `IsahcClient`",
+                "- `surf` → `SurfClient`",
+                "- `wasm-fetch` (on `wasm32`) → `WasmFetchClient`",
+            ],
+        }
+    }
+
+    /// The client's type parameter
+    fn client_param(self) -> Ident {
+        match self {
+            ClientKind::Blocking => quote::format_ident!("C"),
+            ClientKind::Async => quote::format_ident!("A"),
+        }
+    }
+
+    /// The `#[cfg(...)]` attribute the client is generated under
+    fn cfg_attr(self, attrs: &ApiClientAttributes) -> TokenStream {
+        match self {
+            ClientKind::Blocking => generate_blocking_cfg_attr(attrs.blocking_cfg.as_ref()),
+            ClientKind::Async => generate_cfg_attr(attrs.async_cfg.as_ref()),
+        }
+    }
+}
+
+/// Generate the builder collecting all of a client's settings before `build()`
+fn generate_client_builder(
+    config_struct: &Ident,
+    client_name: &Ident,
+    kind: ClientKind,
+    attrs: &ApiClientAttributes,
+) -> TokenStream {
+    let client_trait = kind.client_trait();
+    let default_client = kind.default_client();
+    let cfg_attr = kind.cfg_attr(attrs);
+    let async_credentials = if matches!(kind, ClientKind::Async) {
+        quote! {
+            #[doc = "Asks `provider` for the credentials to send with each request, when it is sent."]
+            #[doc = ""]
+            #[doc = "Use this for providers fetching secrets asynchronously, such as from a secrets manager."]
+            pub fn async_credentials(mut self, provider: impl derive_rest_api::AsyncCredentialsProvider) -> Self {
+                self.transport.async_credentials = std::option::Option::Some(std::sync::Arc::new(provider));
+                self
+            }
+
+            #[doc = "Sends the requests of `send_background()` with `sender`, on its worker task."]
+            #[cfg(not(target_arch = "wasm32"))]
+            pub fn background_sender(mut self, sender: derive_rest_api::BackgroundSender) -> Self {
+                self.transport.background = std::option::Option::Some(sender);
+                self
+            }
+        }
+    } else {
+        quote! {}
+    };
+    let builder_name = quote::format_ident!("{}Builder", client_name);
+    let inner_name = quote::format_ident!("{}Inner", client_name);
+    let base_url = &attrs.base_url;
+    let initial_user_agent = generate_initial_user_agent(attrs.user_agent.as_ref());
+    let initial_api_version = generate_initial_api_version(attrs.version.as_ref());
+    let initial_failover = generate_initial_failover(attrs.failover_urls.as_ref(), &attrs.balance);
+    let initial_config = if attrs.use_default {
         quote! { std::option::Option::Some(#config_struct::default()) }
     } else {
         quote! { std::option::Option::None }
     };
+    let version_header_doc = format!("Sets the API version sent in the `{}` header of every request.", attrs.version_header);
+    let config_loader = generate_builder_config_loader(config_struct, attrs);
+    let built_inner = inner_init(config_struct, &inner_name, attrs, InnerSource::Builder, quote! { self.client });
 
     quote! {
-        #[doc = concat!("Blocking HTTP client for [`", stringify!(#config_struct), "`].")]
-        #[derive(Clone)]
-        pub struct #client_name<C: derive_rest_api::HttpClient> {
+        #[doc = concat!("Builder for [`", stringify!(#client_name), "`], collecting all of its settings before a single `build()`.")]
+        #[doc = ""]
+        #[doc = "Settings left unset keep the defaults from `#[api_client(...)]`."]
+        #cfg_attr
+        pub struct #builder_name<C: #client_trait = #default_client> {
             config: std::option::Option<#config_struct>,
             base_url: std::string::String,
             client: C,
+            user_agent: std::option::Option<std::string::String>,
+            api_version: std::option::Option<std::string::String>,
+            max_response_bytes: std::option::Option<u64>,
+            default_headers: derive_rest_api::__private::DynamicHeaders,
+            timeout: std::option::Option<std::time::Duration>,
+            transport: derive_rest_api::__private::Transport,
         }
 
-        // Non-generic impl for default client type
-        impl #client_name<derive_rest_api::DefaultBlockingClient> {
-            #[doc = concat!("Creates a new [`", stringify!(#client_name), "`] with the default HTTP client.")]
-            #[doc = ""]
-            #[doc = "The default client is determined by enabled cargo features:"]
-            #[doc = "- `ureq-blocking` → `UreqBlockingClient`"]
-            #[doc = "- `reqwest-blocking` → `ReqwestBlockingClient`"]
+        #cfg_attr
+        impl #builder_name<#default_client> {
+            #[doc = concat!("Creates a new [`", stringify!(#builder_name), "`] with the default HTTP client.")]
             pub fn new() -> Self {
-                let client = derive_rest_api::DefaultBlockingClient::default();
                 Self {
                     config: #initial_config,
                     base_url: #base_url.to_string(),
-                    client,
+                    client: #default_client::default(),
+                    user_agent: #initial_user_agent,
+                    api_version: #initial_api_version,
+                    max_response_bytes: std::option::Option::None,
+                    default_headers: std::default::Default::default(),
+                    timeout: std::option::Option::None,
+                    transport: derive_rest_api::__private::Transport {
+                        failover: #initial_failover,
+                        ..std::default::Default::default()
+                    },
                 }
             }
         }
 
-        impl<C: derive_rest_api::HttpClient> #client_name<C> {
-            #[doc = concat!("Creates a new [`", stringify!(#client_name), "`] with a custom HTTP client type.")]
-            #[doc = ""]
-            #[doc = "Use this method when you want to specify a different client than the default."]
-            pub fn new_with_client(client: impl std::convert::Into<C>) -> Self {
-                let client = client.into();
-                Self {
-                    config: #initial_config,
-                    base_url: #base_url.to_string(),
+        #cfg_attr
+        impl<C: #client_trait> #builder_name<C> {
+            #[doc = "Sets the base URL, replacing any failover."]
+            pub fn base_url(mut self, base_url: impl std::convert::Into<std::string::String>) -> Self {
+                self.base_url = base_url.into();
+                self.transport.failover = std::option::Option::None;
+                self
+            }
+
+            #[doc = "Sends requests to the failover's base URLs, moving on to the next one on a client error or `5xx` status."]
+            pub fn failover(mut self, failover: derive_rest_api::Failover) -> Self {
+                self.base_url = failover.base_urls()[0].clone();
+                self.transport.failover = std::option::Option::Some(failover);
+                self
+            }
+
+            #[doc = "Sets the config applied to every request."]
+            pub fn config(mut self, config: #config_struct) -> Self {
+                self.config = std::option::Option::Some(config);
+                self
+            }
+
+            #[doc = "Sets the HTTP client, which may be of a different type."]
+            pub fn http_client<C2: #client_trait>(self, client: C2) -> #builder_name<C2> {
+                #builder_name {
+                    config: self.config,
+                    base_url: self.base_url,
                     client,
+                    user_agent: self.user_agent,
+                    api_version: self.api_version,
+                    max_response_bytes: self.max_response_bytes,
+                    default_headers: self.default_headers,
+                    timeout: self.timeout,
+                    transport: self.transport,
                 }
             }
 
-            #[doc = "Sets a custom base URL for this client."]
-            pub fn with_base_url(mut self, base_url: impl std::convert::Into<std::string::String>) -> Self {
-                self.base_url = base_url.into();
+            #[doc = "Sets the `User-Agent` sent with every request to `<product> derive_rest_api/<version>`."]
+            pub fn user_agent(mut self, product: impl std::convert::AsRef<str>) -> Self {
+                self.user_agent = std::option::Option::Some(derive_rest_api::__private::user_agent(product.as_ref()));
                 self
             }
 
-            #[doc = "Sets the underlying HTTP client for this API client."]
-            pub fn with_http_client(mut self, client: impl std::convert::Into<C>) -> Self {
-                self.client = client.into();
+            #[doc = #version_header_doc]
+            pub fn api_version(mut self, version: impl std::convert::Into<std::string::String>) -> Self {
+                self.api_version = std::option::Option::Some(version.into());
                 self
             }
 
-            #[doc = "Sets the config for this client."]
-            pub fn with_config(mut self, config: #config_struct) -> Self {
-                self.config = std::option::Option::Some(config);
+            #[doc = "Sets the largest response body, in bytes, accepted by every request."]
+            pub fn max_response_bytes(mut self, limit: u64) -> Self {
+                self.max_response_bytes = std::option::Option::Some(limit);
                 self
             }
 
-            #[doc = "Returns a reference to the configuration."]
-            pub fn config(&self) -> &std::option::Option<#config_struct> {
-                &self.config
+            #[doc = "Adds a header sent with every request, replacing any previous value for the same name."]
+            #[doc = ""]
+            #[doc = "Headers set by the config or on a request builder take precedence. An invalid header makes `build()` fail."]
+            pub fn default_header<K, V>(mut self, name: K, value: V) -> Self
+            where
+                derive_rest_api::http::HeaderName: std::convert::TryFrom<K>,
+                <derive_rest_api::http::HeaderName as std::convert::TryFrom<K>>::Error: std::convert::Into<derive_rest_api::http::Error>,
+                derive_rest_api::http::HeaderValue: std::convert::TryFrom<V>,
+                <derive_rest_api::http::HeaderValue as std::convert::TryFrom<V>>::Error: std::convert::Into<derive_rest_api::http::Error>,
+            {
+                self.default_headers.insert(name, value);
+                self
             }
 
-            #(#methods)*
+            #[doc = "Sets the total timeout of every request."]
+            #[doc = ""]
+            #[doc = "A timeout set by the config or on a request builder takes precedence."]
+            pub fn timeout(mut self, timeout: std::time::Duration) -> Self {
+                self.timeout = std::option::Option::Some(timeout);
+                self
+            }
+
+            #[doc = "Retries every request according to `policy`."]
+            pub fn retry_policy(mut self, policy: derive_rest_api::RetryPolicy) -> Self {
+                self.transport.retry = std::option::Option::Some(policy);
+                self
+            }
+
+            #[doc = "Caps the retries made across all of the client's requests with `budget`."]
+            #[doc = ""]
+            #[doc = "The budget is shared by every request and clone of the client, and by any other client given a clone of it."]
+            pub fn retry_budget(mut self, budget: derive_rest_api::RetryBudget) -> Self {
+                self.transport.retry_budget = std::option::Option::Some(budget);
+                self
+            }
+
+            #[doc = "Waits out retry backoff and times rate-limit windows with `clock` instead of the system clock."]
+            #[doc = ""]
+            #[doc = "Tests can pass a `ManualClock` so that waiting happens instantly."]
+            pub fn clock(mut self, clock: impl derive_rest_api::Clock + 'static) -> Self {
+                self.transport.clock = std::option::Option::Some(std::sync::Arc::new(clock));
+                self
+            }
+
+            #[doc = "Encodes the query string of every request with `encoder` instead of `serde_qs`, except for"]
+            #[doc = "requests whose struct sets its own `query_encoder`."]
+            pub fn query_encoder(mut self, encoder: impl derive_rest_api::QueryEncoder) -> Self {
+                self.transport.query_encoder = std::option::Option::Some(std::sync::Arc::new(encoder));
+                self
+            }
+
+            #[doc = "Puts the query parameters of every request in `order`, except for requests whose struct sets"]
+            #[doc = "its own `query_order`."]
+            pub fn query_order(mut self, order: derive_rest_api::QueryOrder) -> Self {
+                self.transport.query_order = order;
+                self
+            }
+
+            #[doc = "Runs `middleware` around every request, inside any middleware already added without a"]
+            #[doc = "higher priority."]
+            pub fn middleware(mut self, middleware: impl derive_rest_api::Middleware) -> Self {
+                self.transport.middleware.push(middleware);
+                self
+            }
+
+            #[doc = "Runs `middleware` around every request, inside middleware with a lower `priority` and outside"]
+            #[doc = "middleware with a higher one. `middleware()` adds with priority `0`."]
+            pub fn middleware_with_priority(mut self, priority: i32, middleware: impl derive_rest_api::Middleware) -> Self {
+                self.transport.middleware.insert(priority, middleware);
+                self
+            }
+
+            #[doc = "Reports the latency and status of every request to `hook`, labelled with the request's"]
+            #[doc = "endpoint (its struct name) and method."]
+            pub fn metrics(mut self, hook: impl derive_rest_api::MetricsHook) -> Self {
+                self.transport.metrics = std::option::Option::Some(std::sync::Arc::new(hook));
+                self
+            }
+
+            #[doc = "Tells `subscriber` about each stage of every request: built, each attempt sent, each retry,"]
+            #[doc = "and its completion or failure. Subscribers are called in the order they were added."]
+            pub fn on_event(mut self, subscriber: impl derive_rest_api::EventSubscriber) -> Self {
+                self.transport.events.push(std::sync::Arc::new(subscriber));
+                self
+            }
+
+            #[doc = "Records every request other than a `GET` with `sink`, once it has completed or failed: its"]
+            #[doc = "method, URL, actor and a SHA-256 digest of its body, but not the body itself."]
+            pub fn audit(mut self, sink: impl derive_rest_api::AuditSink) -> Self {
+                self.transport.audit = std::option::Option::Some(std::sync::Arc::new(sink));
+                self
+            }
+
+            #[doc = "Sets who requests are made on behalf of, in audit records. Requests can override it."]
+            pub fn audit_actor(mut self, actor: impl std::convert::Into<std::string::String>) -> Self {
+                self.transport.actor = std::option::Option::Some(actor.into());
+                self
+            }
+
+            #[doc = "Saves requests other than a `GET` or `HEAD` to `queue` when they can't be sent, to be replayed in"]
+            #[doc = "order by the next such request or `replay_offline_queue()`."]
+            pub fn offline_queue(mut self, queue: derive_rest_api::OfflineQueue) -> Self {
+                self.transport.offline = std::option::Option::Some(queue);
+                self
+            }
+
+            #[doc = "Asks `provider` for the credentials to send with each request, when it is sent."]
+            #[doc = ""]
+            #[doc = "Rotated secrets are picked up by the next request, without rebuilding the client."]
+            pub fn credentials(mut self, provider: impl derive_rest_api::CredentialsProvider) -> Self {
+                let provider = std::sync::Arc::new(provider);
+                self.transport.credentials = std::option::Option::Some(provider.clone());
+                self.transport.async_credentials = std::option::Option::Some(provider);
+                self
+            }
+
+            #async_credentials
+
+            #[doc = "Builds the client."]
+            #[doc = ""]
+            #[doc = "# Errors"]
+            #[doc = ""]
+            #[doc = "Returns `RestApiError::InvalidHeader` if a default header is invalid."]
+            pub fn build(self) -> std::result::Result<#client_name<C>, derive_rest_api::RestApiError> {
+                std::result::Result::Ok(#client_name {
+                    inner: std::sync::Arc::new(#built_inner),
+                })
+            }
+
+            #config_loader
+        }
+    }
+}
+
+/// Generate `from_env()` and `from_path()` on the config struct for `env_prefix = "..."`
+fn generate_config_loader(config_struct: &Ident, attrs: &ApiClientAttributes) -> TokenStream {
+    let Some(prefix) = &attrs.env_prefix else {
+        return quote! {};
+    };
+    let env_doc = format!("Loads the config from `{}_*` environment variables, e.g. `{}_API_KEY` for `api_key`.", prefix, prefix);
+
+    quote! {
+        impl #config_struct {
+            #[doc = #env_doc]
+            #[doc = ""]
+            #[doc = "Nested keys are separated by `__`. Requires the `config` feature."]
+            #[doc = ""]
+            #[doc = "# Errors"]
+            #[doc = ""]
+            #[doc = "Returns `RestApiError::ConfigError` if the variables don't deserialize into the config."]
+            pub fn from_env() -> std::result::Result<Self, derive_rest_api::RestApiError> {
+                std::result::Result::Ok(derive_rest_api::__private::load_config(#prefix, std::option::Option::None)?.config)
+            }
+
+            #[doc = "Loads the config from a `.toml`, `.json`, `.yaml` or `.yml` file, overridden by the environment variables"]
+            #[doc = "read by `from_env()`. Requires the `config` feature."]
+            #[doc = ""]
+            #[doc = "# Errors"]
+            #[doc = ""]
+            #[doc = "Returns `RestApiError::ConfigError` if the file can't be read or the values don't deserialize into the config."]
+            pub fn from_path(path: impl std::convert::AsRef<std::path::Path>) -> std::result::Result<Self, derive_rest_api::RestApiError> {
+                std::result::Result::Ok(derive_rest_api::__private::load_config(#prefix, std::option::Option::Some(path.as_ref()))?.config)
+            }
         }
     }
 }
 
-/// Generate the async client struct and impl
-fn generate_async_client(
+/// Generate `config_from_env()` and `config_from_path()` on a client builder for `env_prefix = "..."`
+fn generate_builder_config_loader(config_struct: &Ident, attrs: &ApiClientAttributes) -> TokenStream {
+    let Some(prefix) = &attrs.env_prefix else {
+        return quote! {};
+    };
+
+    quote! {
+        #[doc = "Loads the config like its `from_env()`, also taking the base URL from a `base_url` key if there is one."]
+        #[doc = ""]
+        #[doc = "# Errors"]
+        #[doc = ""]
+        #[doc = "Returns `RestApiError::ConfigError` if the variables don't deserialize into the config."]
+        pub fn config_from_env(self) -> std::result::Result<Self, derive_rest_api::RestApiError> {
+            std::result::Result::Ok(self.loaded_config(derive_rest_api::__private::load_config(#prefix, std::option::Option::None)?))
+        }
+
+        #[doc = "Loads the config like its `from_path()`, also taking the base URL from a `base_url` key if there is one."]
+        #[doc = ""]
+        #[doc = "# Errors"]
+        #[doc = ""]
+        #[doc = "Returns `RestApiError::ConfigError` if the file can't be read or the values don't deserialize into the config."]
+        pub fn config_from_path(self, path: impl std::convert::AsRef<std::path::Path>) -> std::result::Result<Self, derive_rest_api::RestApiError> {
+            std::result::Result::Ok(self.loaded_config(derive_rest_api::__private::load_config(#prefix, std::option::Option::Some(path.as_ref()))?))
+        }
+
+        fn loaded_config(self, loaded: derive_rest_api::__private::LoadedConfig<#config_struct>) -> Self {
+            let builder = self.config(loaded.config);
+            match loaded.base_url {
+                std::option::Option::Some(base_url) => builder.base_url(base_url),
+                std::option::Option::None => builder,
+            }
+        }
+    }
+}
+
+/// Generate a `#[cfg(...)]` attribute from an optional cfg predicate
+fn generate_cfg_attr(cfg: Option<&syn::Meta>) -> TokenStream {
+    match cfg {
+        Some(predicate) => quote! { #[cfg(#predicate)] },
+        None => quote! {},
+    }
+}
+
+/// Generate the `#[cfg(...)]` attribute for the blocking client
+///
+/// Browsers have no blocking HTTP, so the blocking client is always left out on `wasm32`.
+fn generate_blocking_cfg_attr(cfg: Option<&syn::Meta>) -> TokenStream {
+    match cfg {
+        Some(predicate) => quote! { #[cfg(all(not(target_arch = "wasm32"), #predicate))] },
+        None => quote! { #[cfg(not(target_arch = "wasm32"))] },
+    }
+}
+
+/// Where the settings of a new `XInner` come from
+enum InnerSource {
+    /// The defaults from `#[api_client(...)]`
+    Defaults,
+    /// The client builder `self`, in its `build()`
+    Builder,
+    /// The settings `inner` of a client whose HTTP client is being replaced
+    Inner,
+}
+
+/// Generate the `XInner { ... }` expression holding a client's settings, with `client` as its HTTP client
+fn inner_init(
     config_struct: &Ident,
-    client_name: &Ident,
+    inner_name: &Ident,
     attrs: &ApiClientAttributes,
-    use_default: bool,
+    source: InnerSource,
+    client: TokenStream,
 ) -> TokenStream {
-    let base_url = &attrs.base_url;
+    let settings = match source {
+        InnerSource::Defaults => {
+            let base_url = &attrs.base_url;
+            let initial_config = if attrs.use_default {
+                quote! { std::option::Option::Some(#config_struct::default()) }
+            } else {
+                quote! { std::option::Option::None }
+            };
+            let initial_user_agent = generate_initial_user_agent(attrs.user_agent.as_ref());
+            let initial_api_version = generate_initial_api_version(attrs.version.as_ref());
+            let initial_failover = generate_initial_failover(attrs.failover_urls.as_ref(), &attrs.balance);
+            quote! {
+                config: #initial_config,
+                base_url: #base_url.to_string(),
+                user_agent: #initial_user_agent,
+                api_version: #initial_api_version,
+                max_response_bytes: std::option::Option::None,
+                rate_limit: derive_rest_api::RateLimitState::new(),
+                default_headers: derive_rest_api::http::HeaderMap::new(),
+                timeout: std::option::Option::None,
+                transport: derive_rest_api::__private::Transport {
+                    failover: #initial_failover,
+                    ..std::default::Default::default()
+                },
+            }
+        }
+        InnerSource::Builder => quote! {
+            config: self.config,
+            base_url: self.base_url,
+            user_agent: self.user_agent,
+            api_version: self.api_version,
+            max_response_bytes: self.max_response_bytes,
+            rate_limit: match &self.transport.clock {
+                std::option::Option::Some(clock) => derive_rest_api::RateLimitState::new().clock(clock.clone()),
+                std::option::Option::None => derive_rest_api::RateLimitState::new(),
+            },
+            default_headers: self.default_headers.into_header_map()?,
+            timeout: self.timeout,
+            transport: self.transport,
+        },
+        InnerSource::Inner => quote! {
+            config: inner.config,
+            base_url: inner.base_url,
+            user_agent: inner.user_agent,
+            api_version: inner.api_version,
+            max_response_bytes: inner.max_response_bytes,
+            rate_limit: inner.rate_limit,
+            default_headers: inner.default_headers,
+            timeout: inner.timeout,
+            transport: inner.transport,
+        },
+    };
+    quote! {
+        #inner_name {
+            client: #client,
+            #settings
+        }
+    }
+}
+
+/// Generate the blocking or async client struct and impl
+fn generate_client(config_struct: &Ident, client_name: &Ident, attrs: &ApiClientAttributes, kind: ClientKind) -> TokenStream {
+    let cfg_attr = kind.cfg_attr(attrs);
+    let client_trait = kind.client_trait();
+    let default_client = kind.default_client();
+    let param = kind.client_param();
+    let mapped_param = quote::format_ident!("{}2", param);
+    let version_header = &attrs.version_header;
+    let version_header_doc = format!("Sets the API version sent in the `{}` header of every request.", version_header);
+    let health_methods = match kind {
+        ClientKind::Blocking => generate_blocking_health_methods(attrs),
+        ClientKind::Async => generate_async_health_methods(attrs),
+    };
+    let client_builder = generate_client_builder(config_struct, client_name, kind, attrs);
+    let inner_name = quote::format_ident!("{}Inner", client_name);
+    let send_sync_assertions = generate_send_sync_assertions(config_struct, client_name, client_trait.clone(), &cfg_attr);
+    let builder_name = quote::format_ident!("{}Builder", client_name);
+    let default_inner = inner_init(config_struct, &inner_name, attrs, InnerSource::Defaults, quote! { client });
+    let mapped_inner = inner_init(config_struct, &inner_name, attrs, InnerSource::Inner, quote! { map(inner.client) });
+
+    let (client_docs, client_accessor, client_accessor_mut, replay_offline_queue) = match kind {
+        ClientKind::Blocking => (
+            ["Blocking HTTP client", "HTTP client"],
+            quote::format_ident!("http_client"),
+            quote::format_ident!("http_client_mut"),
+            quote! {
+                pub fn replay_offline_queue(&self) -> std::result::Result<usize, derive_rest_api::RestApiError> {
+                    self.inner.transport.replay(&self.inner.client, &self.inner.default_headers, derive_rest_api::RequestOptions::default())
+                }
+            },
+        ),
+        ClientKind::Async => (
+            ["Async HTTP client", "async HTTP client"],
+            quote::format_ident!("async_http_client"),
+            quote::format_ident!("async_http_client_mut"),
+            quote! {
+                pub async fn replay_offline_queue(&self) -> std::result::Result<usize, derive_rest_api::RestApiError> {
+                    self.inner.transport.replay_async(&self.inner.client, &self.inner.default_headers, derive_rest_api::RequestOptions::default()).await
+                }
+            },
+        ),
+    };
+    let [client_title, client_noun] = client_docs;
+    let struct_doc = format!("{} for [`{}`].", client_title, config_struct);
+    let new_doc = format!("Creates a new [`{}`] with the default {}.", client_name, client_noun);
+    let default_client_docs = kind.default_client_docs();
+    let default_client_heading = format!("The default {} is determined by enabled cargo features:", match kind {
+        ClientKind::Blocking => "client",
+        ClientKind::Async => "async client",
+    });
+    let new_with_client_doc = format!("Creates a new [`{}`] with a custom {} type.", client_name, client_noun);
+    let accessor_doc = format!("Returns the underlying {}.", client_noun);
+    let accessor_mut_doc = format!("Returns the underlying {} mutably, e.g. to reconfigure the wrapped backend client.", client_noun);
+    let method_builder_doc = format!("The builder is pre-configured with the client's {} and base URL.", client_noun);
 
     // Generate methods for each request
     let methods: Vec<_> = attrs.requests.iter().map(|mapping| {
@@ -310,20 +1226,54 @@ fn generate_async_client(
                 let name = pascal_to_snake_case(&struct_name.to_string());
                 quote::format_ident!("{}", name)
             });
+        let (builder_type, with_client) = match kind {
+            ClientKind::Blocking => (quote! { #builder_name<C, ()> }, quote! { http_client }),
+            ClientKind::Async => (quote! { #builder_name<(), A> }, quote! { async_http_client }),
+        };
 
         quote! {
             #[doc = concat!("Creates a new [`", stringify!(#struct_name), "`] request builder.")]
             #[doc = ""]
-            #[doc = "The builder is pre-configured with the client's async HTTP client and base URL."]
-            #[doc = "If the config implements `ConfigureRequest`, it will also be pre-configured with those settings."]
-            pub fn #method_name(&self) -> #builder_name<(), A> {
+            #[doc = #method_builder_doc]
+            #[doc = "If the config implements `ConfigureRequest`, it will also be pre-configured with those settings,"]
+            #[doc = "which `without_config()` on the builder drops."]
+            pub fn #method_name(&self) -> #builder_type {
                 let builder = #builder_name::new()
-                    .async_http_client((&self.client).clone())
-                    .base_url(&self.base_url);
+                    .#with_client((&self.inner.client).clone())
+                    .base_url(&self.inner.base_url)
+                    .track_rate_limit(self.inner.rate_limit.clone())
+                    .__with_transport(self.inner.transport.clone());
+                let builder = match &self.inner.user_agent {
+                    std::option::Option::Some(user_agent) => derive_rest_api::RequestModifier::header(
+                        builder,
+                        derive_rest_api::http::header::USER_AGENT,
+                        user_agent.as_str(),
+                    ),
+                    std::option::Option::None => builder,
+                };
+                let builder = match &self.inner.api_version {
+                    std::option::Option::Some(version) => derive_rest_api::RequestModifier::header(
+                        builder,
+                        #version_header,
+                        version.as_str(),
+                    ),
+                    std::option::Option::None => builder,
+                };
+                let builder = match self.inner.max_response_bytes {
+                    std::option::Option::Some(limit) => derive_rest_api::RequestModifier::max_response_bytes(builder, limit),
+                    std::option::Option::None => builder,
+                };
+                let builder = self.inner.default_headers.iter().fold(builder, |builder, (name, value)| {
+                    derive_rest_api::RequestModifier::header(builder, name.clone(), value.clone())
+                });
+                let builder = match self.inner.timeout {
+                    std::option::Option::Some(timeout) => derive_rest_api::RequestModifier::timeout(builder, timeout),
+                    std::option::Option::None => builder,
+                };
 
                 // Apply configuration if the config implements ConfigureRequest
-                if let std::option::Option::Some(config) = &self.config {
-                    <#config_struct as derive_rest_api::ConfigureRequest>::configure(config, builder)
+                if let std::option::Option::Some(config) = &self.inner.config {
+                    <#config_struct as derive_rest_api::ConfigureRequest>::configure(config, builder.__before_configure())
                 } else {
                     builder
                 }
@@ -331,75 +1281,230 @@ fn generate_async_client(
         }
     }).collect();
 
-    // Generate the initial config value based on the use_default flag
-    let initial_config = if use_default {
-        quote! { std::option::Option::Some(#config_struct::default()) }
-    } else {
-        quote! { std::option::Option::None }
-    };
-
     quote! {
-        #[doc = concat!("Async HTTP client for [`", stringify!(#config_struct), "`].")]
+        #[doc = #struct_doc]
+        #[doc = ""]
+        #[doc = "Clones are cheap and share the client's settings and HTTP client, so one client can be cloned into"]
+        #[doc = "every thread or task using it. Changing a clone's settings copies them first, leaving the others as they were."]
+        #cfg_attr
+        pub struct #client_name<#param: #client_trait> {
+            inner: std::sync::Arc<#inner_name<#param>>,
+        }
+
+        #cfg_attr
         #[derive(Clone)]
-        pub struct #client_name<A: derive_rest_api::AsyncHttpClient> {
+        struct #inner_name<#param> {
             config: std::option::Option<#config_struct>,
             base_url: std::string::String,
-            client: A,
+            client: #param,
+            user_agent: std::option::Option<std::string::String>,
+            api_version: std::option::Option<std::string::String>,
+            max_response_bytes: std::option::Option<u64>,
+            rate_limit: derive_rest_api::RateLimitState,
+            default_headers: derive_rest_api::http::HeaderMap,
+            timeout: std::option::Option<std::time::Duration>,
+            transport: derive_rest_api::__private::Transport,
+        }
+
+        #cfg_attr
+        impl<#param: #client_trait> std::clone::Clone for #client_name<#param> {
+            fn clone(&self) -> Self {
+                Self { inner: std::sync::Arc::clone(&self.inner) }
+            }
         }
 
         // Non-generic impl for default client type
-        impl #client_name<derive_rest_api::DefaultAsyncClient> {
-            #[doc = concat!("Creates a new [`", stringify!(#client_name), "`] with the default async HTTP client.")]
+        #cfg_attr
+        impl #client_name<#default_client> {
+            #[doc = concat!("Returns a [`", stringify!(#builder_name), "`] for configuring a client in one place.")]
+            pub fn builder() -> #builder_name<#default_client> {
+                #builder_name::new()
+            }
+
+            #[doc = #new_doc]
             #[doc = ""]
-            #[doc = "The default async client is determined by enabled cargo features:"]
-            #[doc = "- `reqwest-async` → `ReqwestAsyncClient`"]
+            #[doc = #default_client_heading]
+            #(#[doc = #default_client_docs])*
             pub fn new() -> Self {
-                let client = derive_rest_api::DefaultAsyncClient::default();
-                Self {
-                    config: #initial_config,
-                    base_url: #base_url.to_string(),
-                    client,
-                }
+                Self::new_with_client(#default_client::default())
             }
         }
 
-        impl<A: derive_rest_api::AsyncHttpClient> #client_name<A> {
-            #[doc = concat!("Creates a new [`", stringify!(#client_name), "`] with a custom async HTTP client type.")]
+        #cfg_attr
+        impl<#param: #client_trait> #client_name<#param> {
+            #[doc = #new_with_client_doc]
             #[doc = ""]
             #[doc = "Use this method when you want to specify a different client than the default."]
-            pub fn new_with_client(client: impl std::convert::Into<A>) -> Self {
+            pub fn new_with_client(client: impl std::convert::Into<#param>) -> Self {
                 let client = client.into();
                 Self {
-                    config: #initial_config,
-                    base_url: #base_url.to_string(),
-                    client,
+                    inner: std::sync::Arc::new(#default_inner),
                 }
             }
 
-            #[doc = "Sets a custom base URL for this client."]
+            #[doc = "Sets a custom base URL for this client, replacing any failover."]
             pub fn with_base_url(mut self, base_url: impl std::convert::Into<std::string::String>) -> Self {
-                self.base_url = base_url.into();
+                let inner = self.__inner_mut();
+                inner.base_url = base_url.into();
+                inner.transport.failover = std::option::Option::None;
+                self
+            }
+
+            #[doc = "Sends requests to the failover's base URLs, moving on to the next one on a client error or `5xx` status."]
+            #[doc = ""]
+            #[doc = "Replaces the base URL, or the `base_urls` from `#[api_client(...)]`. The polls of `send_and_wait()` skip it."]
+            pub fn with_failover(mut self, failover: derive_rest_api::Failover) -> Self {
+                let inner = self.__inner_mut();
+                inner.base_url = failover.base_urls()[0].clone();
+                inner.transport.failover = std::option::Option::Some(failover);
                 self
             }
 
+            #[doc = "Returns the failover between base URLs, if any, e.g. to see which base URL is active."]
+            pub fn failover(&self) -> std::option::Option<&derive_rest_api::Failover> {
+                self.inner.transport.failover.as_ref()
+            }
+
             #[doc = "Sets the underlying HTTP client for this API client."]
-            pub fn with_http_client(mut self, client: impl std::convert::Into<A>) -> Self {
-                self.client = client.into();
+            pub fn with_http_client(mut self, client: impl std::convert::Into<#param>) -> Self {
+                self.__inner_mut().client = client.into();
+                self
+            }
+
+            #[doc = #accessor_doc]
+            pub fn #client_accessor(&self) -> &#param {
+                &self.inner.client
+            }
+
+            #[doc = #accessor_mut_doc]
+            pub fn #client_accessor_mut(&mut self) -> &mut #param {
+                &mut self.__inner_mut().client
+            }
+
+            #[doc = "Sets the `User-Agent` sent with every request to `<product> derive_rest_api/<version>`."]
+            #[doc = ""]
+            #[doc = "`product` identifies your application, e.g. `concat!(env!(\"CARGO_PKG_NAME\"), \"/\", env!(\"CARGO_PKG_VERSION\"))`."]
+            #[doc = "A `User-Agent` header set by the config or on a request builder takes precedence."]
+            pub fn with_user_agent(mut self, product: impl std::convert::AsRef<str>) -> Self {
+                self.__inner_mut().user_agent = std::option::Option::Some(derive_rest_api::__private::user_agent(product.as_ref()));
+                self
+            }
+
+            #[doc = #version_header_doc]
+            #[doc = ""]
+            #[doc = "Overrides the `version` from `#[api_client(...)]`, e.g. to pin a different version per environment."]
+            #[doc = "A version header set by the config or on a request builder takes precedence."]
+            pub fn with_api_version(mut self, version: impl std::convert::Into<std::string::String>) -> Self {
+                self.__inner_mut().api_version = std::option::Option::Some(version.into());
+                self
+            }
+
+            #[doc = "Returns the API version sent with every request, if any."]
+            pub fn api_version(&self) -> std::option::Option<&str> {
+                self.inner.api_version.as_deref()
+            }
+
+            #[doc = "Sets the largest response body, in bytes, accepted by every request."]
+            #[doc = ""]
+            #[doc = "Reading stops once a body grows past the limit, and the request fails with `RestApiError::ResponseTooLarge`."]
+            #[doc = "A limit set by the config or on a request builder takes precedence."]
+            pub fn with_max_response_bytes(mut self, limit: u64) -> Self {
+                self.__inner_mut().max_response_bytes = std::option::Option::Some(limit);
+                self
+            }
+
+            #[doc = "Delays requests while the latest response reports `min_remaining` or fewer requests left, until its window resets."]
+            #[doc = ""]
+            #[doc = "This avoids running into `429 Too Many Requests` once the quota is nearly spent. Nothing waits if the"]
+            #[doc = "server doesn't report when the window resets."]
+            #[cfg(not(target_arch = "wasm32"))]
+            pub fn with_quota_throttle(mut self, min_remaining: u64) -> Self {
+                let inner = self.__inner_mut();
+                inner.rate_limit = inner.rate_limit.clone().throttle_below(min_remaining);
                 self
             }
 
+            #[doc = "Returns the rate limit reported by the most recent response that had rate-limit headers."]
+            #[doc = ""]
+            #[doc = "The state is shared with clones of this client and updated by every request builder it creates."]
+            pub fn rate_limit_state(&self) -> std::option::Option<derive_rest_api::RateLimitInfo> {
+                self.inner.rate_limit.get()
+            }
+
+            #[doc = "Returns the client's middleware in the order their `on_request` hooks run."]
+            pub fn middleware_chain(&self) -> std::vec::Vec<derive_rest_api::MiddlewareLayer> {
+                self.inner.transport.middleware.chain()
+            }
+
+            #[doc = "Sends the requests queued while offline, in order, returning how many are still queued."]
+            #[doc = ""]
+            #[doc = "Call this when connectivity returns. Without an `offline_queue()`, there is nothing to send."]
+            #[doc = ""]
+            #[doc = "# Errors"]
+            #[doc = ""]
+            #[doc = "Returns `RestApiError::OfflineQueueError` if the queue's file can't be written."]
+            #replay_offline_queue
+
             #[doc = "Sets the config for this client."]
             pub fn with_config(mut self, config: #config_struct) -> Self {
-                self.config = std::option::Option::Some(config);
+                self.__inner_mut().config = std::option::Option::Some(config);
                 self
             }
 
             #[doc = "Returns a reference to the configuration."]
             pub fn config(&self) -> &std::option::Option<#config_struct> {
-                &self.config
+                &self.inner.config
+            }
+
+            #[doc = "Throttles every request to the policy's per-host budget, shared with other clients using the same registry."]
+            #[cfg(not(target_arch = "wasm32"))]
+            pub fn with_throttle(self, policy: derive_rest_api::ThrottlePolicy) -> #client_name<derive_rest_api::ThrottledClient<#param>>
+            where
+                derive_rest_api::ThrottledClient<#param>: #client_trait,
+            {
+                self.__map_client(|client| derive_rest_api::ThrottledClient::new(client, policy))
             }
 
+            #[doc = "Answers repeated requests from `cache` for as long as the responses' `Cache-Control` allows."]
+            #[cfg(not(target_arch = "wasm32"))]
+            pub fn with_cache(self, cache: derive_rest_api::ResponseCache) -> #client_name<derive_rest_api::CachingClient<#param>>
+            where
+                derive_rest_api::CachingClient<#param>: #client_trait,
+            {
+                self.__map_client(|client| derive_rest_api::CachingClient::new(client, cache))
+            }
+
+            #[doc = "Calls `handler` whenever a response carries a `Deprecation` or `Sunset` header."]
+            pub fn with_deprecation_handler(
+                self,
+                handler: impl Fn(&derive_rest_api::DeprecationNotice) + Send + Sync + 'static,
+            ) -> #client_name<derive_rest_api::DeprecationClient<#param>>
+            where
+                derive_rest_api::DeprecationClient<#param>: #client_trait,
+            {
+                self.__map_client(|client| derive_rest_api::DeprecationClient::new(client).with_handler(handler))
+            }
+
+            /// Returns the settings to change, copying them first if they're shared with a clone
+            fn __inner_mut(&mut self) -> &mut #inner_name<#param> {
+                std::sync::Arc::make_mut(&mut self.inner)
+            }
+
+            /// Wraps the HTTP client, keeping every other setting
+            fn __map_client<#mapped_param: #client_trait>(self, map: impl FnOnce(#param) -> #mapped_param) -> #client_name<#mapped_param> {
+                let inner = std::sync::Arc::unwrap_or_clone(self.inner);
+                #client_name {
+                    inner: std::sync::Arc::new(#mapped_inner),
+                }
+            }
+
+            #health_methods
+
             #(#methods)*
         }
+
+        #client_builder
+
+        #send_sync_assertions
     }
 }
@@ -0,0 +1,167 @@
+//! `define_api!` function-like macro implementation.
+//!
+//! Expands a client struct and a table of endpoints into the same code as
+//! writing them out by hand: each endpoint becomes a request struct deriving
+//! `RequestBuilder`, and the client struct derives `ApiClient` with every
+//! endpoint in its `requests(...)`.
+
+use proc_macro2::{TokenStream, TokenTree};
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{Ident, LitStr, Token};
+
+/// The whole `define_api! { ... }` input
+struct DefineApi {
+    client: syn::ItemStruct,
+    endpoints: Vec<Endpoint>,
+}
+
+/// One endpoint: `Name: METHOD "/path" (params) -> Response;`
+struct Endpoint {
+    attrs: Vec<syn::Attribute>,
+    vis: syn::Visibility,
+    name: Ident,
+    method: Ident,
+    path: LitStr,
+    params: Punctuated<Param, Token![,]>,
+    response: Option<syn::Type>,
+}
+
+/// One parameter: `[kind] name: Type`, where kind is `path`, `query`, `body` or `header`
+struct Param {
+    attrs: Vec<syn::Attribute>,
+    kind: Option<Ident>,
+    name: Ident,
+    ty: syn::Type,
+}
+
+impl Parse for DefineApi {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let client = input.parse()?;
+        let mut endpoints = Vec::new();
+        while !input.is_empty() {
+            endpoints.push(input.parse()?);
+        }
+        Ok(DefineApi { client, endpoints })
+    }
+}
+
+impl Parse for Endpoint {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let attrs = input.call(syn::Attribute::parse_outer)?;
+        let vis = input.parse()?;
+        let name = input.parse()?;
+        input.parse::<Token![:]>()?;
+        let method = input.parse()?;
+        let path = input.parse()?;
+
+        let content;
+        syn::parenthesized!(content in input);
+        let params = Punctuated::parse_terminated(&content)?;
+
+        let response = if input.peek(Token![->]) {
+            input.parse::<Token![->]>()?;
+            Some(input.parse()?)
+        } else {
+            None
+        };
+        input.parse::<Token![;]>()?;
+
+        Ok(Endpoint { attrs, vis, name, method, path, params, response })
+    }
+}
+
+impl Parse for Param {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let attrs = input.call(syn::Attribute::parse_outer)?;
+        let kind = if input.peek(Ident) && input.peek2(Ident) {
+            let kind: Ident = input.parse()?;
+            if !["path", "query", "body", "header"].iter().any(|expected| kind == expected) {
+                return Err(syn::Error::new_spanned(
+                    &kind,
+                    "unsupported parameter kind, expected one of: path, query, body, header",
+                ));
+            }
+            Some(kind)
+        } else {
+            None
+        };
+        let name = input.parse()?;
+        input.parse::<Token![:]>()?;
+        let ty = input.parse()?;
+        Ok(Param { attrs, kind, name, ty })
+    }
+}
+
+/// Main entry point for the define_api! macro
+pub(crate) fn generate_define_api(input: TokenStream) -> syn::Result<TokenStream> {
+    let DefineApi { client, endpoints } = syn::parse2(input)?;
+
+    let client_struct = generate_client_struct(client, &endpoints)?;
+    let request_structs = endpoints.iter().map(generate_request_struct);
+
+    Ok(quote! {
+        #client_struct
+        #(#request_structs)*
+    })
+}
+
+/// Derive `ApiClient` on the client struct, adding the endpoints to its `#[api_client(...)]`
+fn generate_client_struct(mut client: syn::ItemStruct, endpoints: &[Endpoint]) -> syn::Result<TokenStream> {
+    let Some(attr) = client.attrs.iter_mut().find(|attr| attr.path().is_ident("api_client")) else {
+        return Err(syn::Error::new_spanned(
+            &client.ident,
+            "define_api! needs an #[api_client(...)] attribute on the client struct",
+        ));
+    };
+
+    let syn::Meta::List(list) = &mut attr.meta else {
+        return Err(syn::Error::new_spanned(attr, "expected #[api_client(...)]"));
+    };
+    if let Some(requests) = list.tokens.clone().into_iter().find(|token| {
+        matches!(token, TokenTree::Ident(ident) if ident == "requests")
+    }) {
+        return Err(syn::Error::new_spanned(
+            requests,
+            "define_api! fills in `requests(...)` from the endpoints",
+        ));
+    }
+    let names = endpoints.iter().map(|endpoint| &endpoint.name);
+    let tokens = &list.tokens;
+    let separator = match tokens.clone().into_iter().last() {
+        None => quote! {},
+        Some(TokenTree::Punct(punct)) if punct.as_char() == ',' => quote! {},
+        Some(_) => quote! { , },
+    };
+    list.tokens = quote! { #tokens #separator requests(#(#names),*) };
+
+    // The derive goes first, as it introduces the `api_client` attribute
+    client.attrs.insert(0, syn::parse_quote! { #[derive(Clone, derive_rest_api::ApiClient)] });
+    Ok(quote! { #client })
+}
+
+/// Generate the request struct for an endpoint
+fn generate_request_struct(endpoint: &Endpoint) -> TokenStream {
+    let Endpoint { attrs, vis, name, method, path, params, response } = endpoint;
+
+    let method = method.to_string().to_uppercase();
+    let response = response.as_ref().map(|response| quote! { , response = #response });
+    let fields = params.iter().map(|Param { attrs, kind, name, ty }| {
+        let kind = kind.as_ref().map(|kind| quote! { #[request_builder(#kind)] });
+        quote! {
+            #(#attrs)*
+            #kind
+            #vis #name: #ty
+        }
+    });
+
+    quote! {
+        #[derive(derive_rest_api::RequestBuilder)]
+        #(#attrs)*
+        #[request_builder(method = #method, path = #path #response)]
+        #vis struct #name {
+            #(#fields,)*
+        }
+    }
+}
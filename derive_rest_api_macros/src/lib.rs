@@ -3,7 +3,10 @@
 //! This crate provides derive macros for generating REST API request builders.
 
 mod api_client;
+mod define_api;
 mod request_builder;
+mod request_enum;
+mod rest_api;
 mod utils;
 
 /// Derive macro for generating a builder pattern for REST API requests.
@@ -81,6 +84,8 @@ pub fn derive_request_builder(input: proc_macro::TokenStream) -> proc_macro::Tok
 /// This generates:
 /// - `MyApiClient<C: HttpClient>` - Blocking client
 /// - `MyApiAsyncClient<A: AsyncHttpClient>` - Async client
+/// - `MyApiAsync` - With `async_trait`, an object-safe trait implemented by the async client
+/// - `MyApiCommand` - With `cli`, a `clap` subcommand per request, run with the clients' `run_command()`
 #[proc_macro_derive(ApiClient, attributes(api_client))]
 pub fn derive_api_client(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let input = syn::parse_macro_input!(input as syn::DeriveInput);
@@ -90,3 +95,151 @@ pub fn derive_api_client(input: proc_macro::TokenStream) -> proc_macro::TokenStr
         Err(err) => err.to_compile_error().into(),
     }
 }
+
+/// Derive macro for enums where each variant maps to an endpoint.
+///
+/// Variants with named fields (or no fields) take the same struct-level
+/// `#[request_builder(...)]` attributes as a request struct, and their fields
+/// take the usual field-level attributes. Single-field tuple variants wrap an
+/// existing `RequestBuilder` struct.
+///
+/// The enum gets `build_url`, `build_headers`, `build_body` and
+/// `send_with_client` methods that dispatch on the variant, which makes it
+/// easy to queue heterogeneous API operations and replay them later.
+///
+/// # Example
+///
+/// ```rust
+/// use derive_rest_api::{RequestBuilder, RequestEnum};
+/// use serde::Serialize;
+///
+/// #[derive(RequestBuilder, Serialize)]
+/// #[request_builder(method = "DELETE", path = "/users/{id}")]
+/// struct DeleteUser {
+///     id: u64,
+/// }
+///
+/// #[derive(RequestEnum)]
+/// enum Job {
+///     #[request_builder(method = "POST", path = "/users")]
+///     CreateUser {
+///         #[request_builder(body)]
+///         name: String,
+///     },
+///     #[request_builder(method = "POST", path = "/users/{id}/archive")]
+///     ArchiveUser { id: u64 },
+///     DeleteUser(DeleteUser),
+/// }
+///
+/// let job = Job::ArchiveUser { id: 7 };
+/// assert_eq!(job.build_url().unwrap(), "/users/7/archive");
+/// ```
+#[proc_macro_derive(RequestEnum, attributes(request_builder))]
+pub fn derive_request_enum(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = syn::parse_macro_input!(input as syn::DeriveInput);
+
+    match request_enum::generate_request_enum(input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+/// Function-like macro declaring a client and its endpoints in a compact table.
+///
+/// The first item is the client's config struct with its `#[api_client(...)]`
+/// attribute, which derives `Clone` and `ApiClient` (so neither should be
+/// derived by hand) and gets `requests(...)` filled in with every endpoint.
+/// Each endpoint that follows becomes a request struct deriving `RequestBuilder`:
+///
+/// `Name: METHOD "/path" (params) -> Response;`
+///
+/// Parameters are `name: Type`, optionally prefixed with their kind (`path`,
+/// `query`, `body` or `header`), and may carry `#[request_builder(...)]`
+/// attributes. The response type is optional.
+/// Attributes before an endpoint, including doc comments and struct-level
+/// `#[request_builder(...)]` attributes, are applied to its request struct.
+///
+/// # Example
+///
+/// ```rust
+/// use derive_rest_api::define_api;
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct User {
+///     login: String,
+/// }
+///
+/// define_api! {
+///     #[api_client(base_url = "https://api.github.com")]
+///     struct GitHubConfig;
+///
+///     /// Fetches a user by login
+///     GetUser: GET "/users/{username}" (username: String) -> User;
+///     ListFollowers: GET "/users/{username}/followers" (username: String, query page: Option<u32>) -> Vec<User>;
+///     Follow: PUT "/user/following/{username}" (username: String);
+/// }
+///
+/// let request = GetUserBuilder::new().username("octocat".to_string()).build().unwrap();
+/// assert_eq!(request.build_url().unwrap(), "/users/octocat");
+/// ```
+#[proc_macro]
+pub fn define_api(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    match define_api::generate_define_api(input.into()) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+/// Attribute macro generating a client that implements a trait of endpoints.
+///
+/// Each method of the trait describes an endpoint with a struct-level
+/// `#[request_builder(method = "...", path = "...", ...)]` attribute, takes
+/// `&self` and its parameters by value, and returns `Result<T, RestApiError>`.
+/// Parameters become fields of a request struct named after the method
+/// (`get_user` becomes `GetUserRequest`) and may carry field-level
+/// `#[request_builder(...)]` attributes. The macro's arguments are those of
+/// `#[api_client(...)]`, without `requests(...)`.
+///
+/// For a trait `GitHub` this generates:
+/// - A request struct deriving `RequestBuilder` for each method
+/// - `GitHubConfig`, deriving `ApiClient`, and so `GitHubClient` and `GitHubAsyncClient`,
+///   with a `get_user_request()` method returning each request's builder
+/// - `GitHubAsync`, the same trait with `async fn` methods
+/// - Implementations of `GitHub` for `GitHubClient` and `GitHubAsync` for `GitHubAsyncClient`
+///
+/// # Example
+///
+/// ```rust
+/// use derive_rest_api::{RestApiError, rest_api};
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct User {
+///     login: String,
+/// }
+///
+/// #[rest_api(base_url = "https://api.github.com")]
+/// trait GitHub {
+///     /// Fetches a user by login
+///     #[request_builder(method = "GET", path = "/users/{username}")]
+///     fn get_user(&self, username: String) -> Result<User, RestApiError>;
+///
+///     #[request_builder(method = "GET", path = "/users/{username}/followers")]
+///     fn list_followers(
+///         &self,
+///         username: String,
+///         #[request_builder(query)] page: Option<u32>,
+///     ) -> Result<Vec<User>, RestApiError>;
+/// }
+///
+/// let request = GetUserRequestBuilder::new().username("octocat".to_string()).build().unwrap();
+/// assert_eq!(request.build_url().unwrap(), "/users/octocat");
+/// ```
+#[proc_macro_attribute]
+pub fn rest_api(args: proc_macro::TokenStream, item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    match rest_api::generate_rest_api(args.into(), item.into()) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
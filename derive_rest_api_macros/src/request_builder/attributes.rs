@@ -3,7 +3,8 @@
 //! This module defines the attribute structures and parsing logic for both
 //! struct-level and field-level `#[request_builder(...)]` attributes.
 
-use syn;
+use super::utils::is_valid_header_name;
+use crate::utils::is_unit_type;
 
 /// Struct-level attributes from #[request_builder(...)]
 #[derive(Debug, Default)]
@@ -16,10 +17,144 @@ pub(super) struct StructAttributes {
     pub method: Option<String>,
     /// URL path (e.g., "/api/users/{id}")
     pub path: Option<String>,
-    /// Query string config expression (e.g., "my_qs_config()")
-    pub query_config: Option<String>,
+    /// Span of the `path = "..."` literal, for errors about its placeholders
+    pub path_span: Option<proc_macro2::Span>,
+    /// Query string config expression, from `query_config = "my_qs_config()"` or `query_config_fn = my_qs_config`
+    pub query_config: Option<syn::Expr>,
+    /// `QueryEncoder` used instead of `serde_qs`, from `query_encoder = "urlencoded"` or an expression
+    pub query_encoder: Option<syn::Expr>,
+    /// Whether to sort the query parameters, from `query_order = "sorted"` or `"declared"`
+    pub sort_query: Option<bool>,
+    /// Constant query parameters, from `static_query("name" = "value", ...)`
+    pub static_query: Vec<(String, String)>,
+    /// Constant headers, from `static_header("Name" = "value", ...)`
+    pub static_headers: Vec<(String, String)>,
     /// Response type
     pub response: Option<syn::Type>,
+    /// How the response body is decoded into the response type
+    pub response_format: ResponseFormat,
+    /// Attach the failure's location and the untyped body to deserialization errors
+    pub lenient_response: bool,
+    /// Return the response in a `WithUnknownFields` holding the fields its type ignores
+    pub capture_unknown_fields: bool,
+    /// Fail deserialization on any response field the response type doesn't model
+    pub strict_response: bool,
+    /// Run `garde::Validate` on the built struct
+    pub garde: bool,
+    /// Implement `arbitrary::Arbitrary` for the builder and the struct
+    pub arbitrary: bool,
+    /// Implement `clap::Args` for the struct, with a flag per field
+    pub cli: bool,
+    /// `ChecksumAlgorithm` variant used to add a checksum header to the request body
+    pub checksum: Option<syn::Ident>,
+    /// Verify checksum headers on the response against its body
+    pub verify_checksum: bool,
+    /// Long-running operation polling for `send_and_wait()`
+    pub poll: Option<PollAttributes>,
+    /// Send the request as a GraphQL operation, with the fields as variables
+    pub graphql: bool,
+    /// GraphQL query document, from `query = "..."`
+    pub graphql_query: Option<String>,
+    /// GraphQL operation name, from `operation_name = "..."`
+    pub operation_name: Option<String>,
+    /// What to do about fields that aren't sent with the request
+    pub unmarked_fields: UnmarkedFields,
+}
+
+/// Handling of fields that have no `path`, `query`, `body` or `header` kind and
+/// aren't path placeholders, from `#[request_builder(unmarked_fields = "...")]`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(super) enum UnmarkedFields {
+    /// Leave them out of the request silently
+    Allow,
+    /// Leave them out of the request with a compiler warning
+    #[default]
+    Warn,
+    /// Fail to compile
+    Error,
+}
+
+/// Format of the response body, from `#[request_builder(response_format = "...")]`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(super) enum ResponseFormat {
+    /// Plain JSON deserialized into the response type
+    #[default]
+    Json,
+    /// A JSON:API document whose primary data is flattened into the response type
+    JsonApi,
+    /// JSON, or text or bytes depending on the response's `Content-Type`
+    Negotiate,
+}
+
+/// Settings from `#[request_builder(poll(...))]`
+#[derive(Debug, Clone)]
+pub(super) struct PollAttributes {
+    /// Dotted path of the status field in the response JSON
+    pub status_field: String,
+    /// Status values that end polling
+    pub done_values: Vec<String>,
+    /// Milliseconds between polls
+    pub interval_ms: u64,
+    /// Milliseconds before giving up
+    pub timeout_ms: Option<u64>,
+}
+
+/// Set the query string config, which can only be given once
+fn set_query_config(result: &mut StructAttributes, expr: syn::Expr, meta: &syn::meta::ParseNestedMeta) -> syn::Result<()> {
+    if result.query_config.is_some() {
+        return Err(meta.error("only one of query_config and query_config_fn can be given"));
+    }
+    result.query_config = Some(expr);
+    Ok(())
+}
+
+/// Parse the contents of `poll(...)`
+fn parse_poll_attributes(meta: &syn::meta::ParseNestedMeta) -> syn::Result<PollAttributes> {
+    let mut status_field = None;
+    let mut done_values = Vec::new();
+    let mut interval_ms = 1000;
+    let mut timeout_ms = None;
+
+    meta.parse_nested_meta(|nested| {
+        if nested.path.is_ident("status_field") {
+            let value: syn::LitStr = nested.value()?.parse()?;
+            status_field = Some(value.value());
+            return Ok(());
+        }
+
+        if nested.path.is_ident("done_values") {
+            let content;
+            syn::parenthesized!(content in nested.input);
+            let values = content.parse_terminated(|input| input.parse::<syn::LitStr>(), syn::Token![,])?;
+            done_values.extend(values.iter().map(syn::LitStr::value));
+            return Ok(());
+        }
+
+        if nested.path.is_ident("interval_ms") {
+            let value: syn::LitInt = nested.value()?.parse()?;
+            interval_ms = value.base10_parse()?;
+            return Ok(());
+        }
+
+        if nested.path.is_ident("timeout_ms") {
+            let value: syn::LitInt = nested.value()?.parse()?;
+            timeout_ms = Some(value.base10_parse()?);
+            return Ok(());
+        }
+
+        Err(nested.error("unsupported poll attribute, expected status_field, done_values, interval_ms or timeout_ms"))
+    })?;
+
+    if done_values.is_empty() {
+        return Err(meta.error("poll requires done_values(\"...\")"));
+    }
+
+    Ok(PollAttributes {
+        status_field: status_field.unwrap_or_else(|| "status".to_string()),
+        done_values,
+        interval_ms,
+        timeout_ms,
+    })
 }
 
 /// Field-level attributes from #[request_builder(...)]
@@ -38,9 +173,10 @@ pub(super) enum FieldKind {
 }
 
 /// Default value behavior for a field
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub(super) enum DefaultBehavior {
     /// Field is required (no default)
+    #[default]
     Required,
     /// Use Default::default() if not set
     UseDefault,
@@ -48,12 +184,6 @@ pub(super) enum DefaultBehavior {
     Custom(syn::Expr),
 }
 
-impl Default for DefaultBehavior {
-    fn default() -> Self {
-        DefaultBehavior::Required
-    }
-}
-
 /// Field-level attributes
 #[derive(Debug, Default)]
 pub(super) struct FieldAttributes {
@@ -67,11 +197,20 @@ pub(super) struct FieldAttributes {
     pub kind: FieldKind,
     /// Custom name for this field (for headers, query params, etc.)
     pub rename: Option<String>,
+    /// Exclude this field from the builder (no setter, value comes from its default)
+    pub skip: bool,
+    /// Expression or function computing this field during build()
+    pub computed: Option<syn::Expr>,
+    /// Merge this field's keys into the enclosing body object instead of nesting them
+    pub flatten: bool,
+    /// Value `sample()` passes to this field's setter
+    pub example: Option<syn::Expr>,
 }
 
 /// Parse struct-level #[request_builder(...)] attributes
 pub(super) fn parse_struct_attributes(attrs: &[syn::Attribute]) -> syn::Result<StructAttributes> {
     let mut result = StructAttributes::default();
+    let mut last_attr = None;
 
     for attr in attrs {
         if !attr.path().is_ident("request_builder") {
@@ -98,6 +237,25 @@ pub(super) fn parse_struct_attributes(attrs: &[syn::Attribute]) -> syn::Result<S
                 let value = meta.value()?;
                 let path: syn::LitStr = value.parse()?;
                 result.path = Some(path.value());
+                result.path_span = Some(path.span());
+                return Ok(());
+            }
+
+            // #[request_builder(unmarked_fields = "error")]
+            if meta.path.is_ident("unmarked_fields") {
+                let value = meta.value()?;
+                let level: syn::LitStr = value.parse()?;
+                result.unmarked_fields = match level.value().as_str() {
+                    "allow" => UnmarkedFields::Allow,
+                    "warn" => UnmarkedFields::Warn,
+                    "error" => UnmarkedFields::Error,
+                    _ => {
+                        return Err(syn::Error::new_spanned(
+                            &level,
+                            "unsupported unmarked_fields level, expected one of: allow, warn, error",
+                        ));
+                    }
+                };
                 return Ok(());
             }
 
@@ -109,27 +267,313 @@ pub(super) fn parse_struct_attributes(attrs: &[syn::Attribute]) -> syn::Result<S
                 return Ok(());
             }
 
+            // #[request_builder(response_format = "jsonapi")]
+            if meta.path.is_ident("response_format") {
+                let value = meta.value()?;
+                let format: syn::LitStr = value.parse()?;
+                result.response_format = match format.value().to_ascii_lowercase().as_str() {
+                    "json" => ResponseFormat::Json,
+                    "jsonapi" => ResponseFormat::JsonApi,
+                    "negotiate" => ResponseFormat::Negotiate,
+                    _ => {
+                        return Err(syn::Error::new_spanned(
+                            &format,
+                            "unsupported response format, expected one of: json, jsonapi, negotiate",
+                        ));
+                    }
+                };
+                return Ok(());
+            }
+
+            // #[request_builder(lenient_response)]
+            if meta.path.is_ident("lenient_response") {
+                result.lenient_response = true;
+                return Ok(());
+            }
+
+            // #[request_builder(capture_unknown_fields)]
+            if meta.path.is_ident("capture_unknown_fields") {
+                result.capture_unknown_fields = true;
+                return Ok(());
+            }
+
+            // #[request_builder(strict_response)]
+            if meta.path.is_ident("strict_response") {
+                result.strict_response = true;
+                return Ok(());
+            }
+
             // #[request_builder(default)]
             if meta.path.is_ident("default") {
                 result.default = true;
                 return Ok(());
             }
 
+            // #[request_builder(garde)]
+            if meta.path.is_ident("garde") {
+                result.garde = true;
+                return Ok(());
+            }
+
+            // #[request_builder(arbitrary)]
+            if meta.path.is_ident("arbitrary") {
+                result.arbitrary = true;
+                return Ok(());
+            }
+
+            // #[request_builder(cli)]
+            if meta.path.is_ident("cli") {
+                result.cli = true;
+                return Ok(());
+            }
+
+            // #[request_builder(checksum = "sha256")]
+            if meta.path.is_ident("checksum") {
+                let value = meta.value()?;
+                let algorithm: syn::LitStr = value.parse()?;
+                let variant = match algorithm.value().to_ascii_lowercase().as_str() {
+                    "md5" => "Md5",
+                    "sha1" => "Sha1",
+                    "sha256" => "Sha256",
+                    "crc32" => "Crc32",
+                    "crc32c" => "Crc32c",
+                    _ => {
+                        return Err(syn::Error::new_spanned(
+                            &algorithm,
+                            "unsupported checksum algorithm, expected one of: md5, sha1, sha256, crc32, crc32c",
+                        ));
+                    }
+                };
+                result.checksum = Some(syn::Ident::new(variant, algorithm.span()));
+                return Ok(());
+            }
+
+            // #[request_builder(verify_checksum)]
+            if meta.path.is_ident("verify_checksum") {
+                result.verify_checksum = true;
+                return Ok(());
+            }
+
+            // #[request_builder(poll(status_field = "status", done_values("succeeded", "failed")))]
+            if meta.path.is_ident("poll") {
+                result.poll = Some(parse_poll_attributes(&meta)?);
+                return Ok(());
+            }
+
+            // #[request_builder(graphql)]
+            if meta.path.is_ident("graphql") {
+                result.graphql = true;
+                return Ok(());
+            }
+
+            // #[request_builder(query = "query { viewer { login } }")]
+            if meta.path.is_ident("query") {
+                let value = meta.value()?;
+                let query: syn::LitStr = value.parse()?;
+                result.graphql_query = Some(query.value());
+                return Ok(());
+            }
+
+            // #[request_builder(operation_name = "GetViewer")]
+            if meta.path.is_ident("operation_name") {
+                let value = meta.value()?;
+                let name: syn::LitStr = value.parse()?;
+                result.operation_name = Some(name.value());
+                return Ok(());
+            }
+
+            // #[request_builder(static_query("include" = "comments", "format" = "full"))]
+            if meta.path.is_ident("static_query") {
+                let content;
+                syn::parenthesized!(content in meta.input);
+                let params = content.parse_terminated(
+                    |input| {
+                        let name: syn::LitStr = input.parse()?;
+                        input.parse::<syn::Token![=]>()?;
+                        let value: syn::LitStr = input.parse()?;
+                        Ok((name.value(), value.value()))
+                    },
+                    syn::Token![,],
+                )?;
+                result.static_query.extend(params);
+                return Ok(());
+            }
+
+            // #[request_builder(static_header("Accept" = "application/vnd.github+json"))]
+            if meta.path.is_ident("static_header") {
+                let content;
+                syn::parenthesized!(content in meta.input);
+                let headers = content.parse_terminated(
+                    |input| {
+                        let name: syn::LitStr = input.parse()?;
+                        input.parse::<syn::Token![=]>()?;
+                        let value: syn::LitStr = input.parse()?;
+                        // Checked here so that `HeaderName::from_static` and `HeaderValue::from_static` can't panic
+                        if !is_valid_header_name(&name.value()) {
+                            return Err(syn::Error::new_spanned(&name, "not a valid HTTP header name"));
+                        }
+                        if !value.value().bytes().all(|b| b == b'\t' || (32..127).contains(&b)) {
+                            return Err(syn::Error::new_spanned(&value, "not a valid HTTP header value"));
+                        }
+                        Ok((name.value(), value.value()))
+                    },
+                    syn::Token![,],
+                )?;
+                result.static_headers.extend(headers);
+                return Ok(());
+            }
+
             // #[request_builder(query_config = "my_qs_config()")]
             if meta.path.is_ident("query_config") {
                 let value = meta.value()?;
                 let config_expr: syn::LitStr = value.parse()?;
-                result.query_config = Some(config_expr.value());
+                let expr = config_expr.parse::<syn::Expr>().map_err(|e| {
+                    syn::Error::new_spanned(&config_expr, format!("query_config is not a valid expression: {}", e))
+                })?;
+                set_query_config(&mut result, expr, &meta)?;
+                return Ok(());
+            }
+
+            // #[request_builder(query_order = "sorted")]
+            if meta.path.is_ident("query_order") {
+                let order: syn::LitStr = meta.value()?.parse()?;
+                result.sort_query = match order.value().as_str() {
+                    "sorted" => Some(true),
+                    "declared" => Some(false),
+                    _ => return Err(syn::Error::new_spanned(order, "query_order must be \"sorted\" or \"declared\"")),
+                };
+                return Ok(());
+            }
+
+            // #[request_builder(query_config_fn = path::to::my_qs_config)]
+            if meta.path.is_ident("query_config_fn") {
+                let function: syn::Path = meta.value()?.parse()?;
+                set_query_config(&mut result, syn::parse_quote! { #function() }, &meta)?;
+                return Ok(());
+            }
+
+            // #[request_builder(query_encoder = "urlencoded")] or #[request_builder(query_encoder = MyEncoder)]
+            if meta.path.is_ident("query_encoder") {
+                let expr: syn::Expr = meta.value()?.parse()?;
+                let encoder = match &expr {
+                    syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(name), .. }) => match name.value().as_str() {
+                        "urlencoded" => syn::parse_quote! { derive_rest_api::UrlEncodedEncoder::new() },
+                        "serde_qs" => syn::parse_quote! { derive_rest_api::SerdeQsEncoder },
+                        _ => {
+                            return Err(syn::Error::new_spanned(
+                                name,
+                                "query_encoder must be \"urlencoded\", \"serde_qs\" or a QueryEncoder expression",
+                            ));
+                        }
+                    },
+                    _ => expr,
+                };
+                result.query_encoder = Some(encoder);
                 return Ok(());
             }
 
             Err(meta.error("unsupported request_builder attribute"))
         })?;
+        last_attr = Some(attr);
+    }
+
+    if let Some(attr) = last_attr {
+        if result.query_config.is_some() && result.query_encoder.is_some() {
+            return Err(syn::Error::new_spanned(
+                attr,
+                "query_config and query_config_fn only configure the default serde_qs encoding and can't be \
+                 combined with query_encoder",
+            ));
+        }
+        check_graphql_attributes(&mut result, attr)?;
+        check_negotiate_attributes(&result, attr)?;
+        if result.response_format == ResponseFormat::JsonApi && result.response.is_none() {
+            return Err(syn::Error::new_spanned(attr, "response_format = \"jsonapi\" requires response = Type"));
+        }
+        if result.response.as_ref().is_some_and(is_unit_type)
+            && (result.response_format == ResponseFormat::JsonApi || result.capture_unknown_fields)
+        {
+            return Err(syn::Error::new_spanned(
+                attr,
+                "response = () can't be combined with response_format = \"jsonapi\" or capture_unknown_fields",
+            ));
+        }
+        if result.capture_unknown_fields {
+            if result.response.is_none() {
+                return Err(syn::Error::new_spanned(attr, "capture_unknown_fields requires response = Type"));
+            }
+            if result.response_format == ResponseFormat::JsonApi || result.lenient_response {
+                return Err(syn::Error::new_spanned(
+                    attr,
+                    "capture_unknown_fields can't be combined with response_format = \"jsonapi\" or lenient_response",
+                ));
+            }
+        }
+        if result.strict_response
+            && (result.response_format == ResponseFormat::JsonApi || result.lenient_response || result.capture_unknown_fields)
+        {
+            return Err(syn::Error::new_spanned(
+                attr,
+                "strict_response can't be combined with response_format = \"jsonapi\", lenient_response or capture_unknown_fields",
+            ));
+        }
+        if result.response_format == ResponseFormat::JsonApi && result.lenient_response {
+            return Err(syn::Error::new_spanned(attr, "lenient_response is not supported with response_format = \"jsonapi\""));
+        }
     }
 
     Ok(result)
 }
 
+/// Check the GraphQL attributes and fill in the POST to `/graphql` they imply
+fn check_graphql_attributes(result: &mut StructAttributes, attr: &syn::Attribute) -> syn::Result<()> {
+    if !result.graphql {
+        if result.graphql_query.is_some() || result.operation_name.is_some() {
+            return Err(syn::Error::new_spanned(attr, "query and operation_name require graphql"));
+        }
+        return Ok(());
+    }
+
+    if result.graphql_query.is_none() {
+        return Err(syn::Error::new_spanned(attr, "graphql requires query = \"...\""));
+    }
+    if result.poll.is_some() {
+        return Err(syn::Error::new_spanned(attr, "poll is not supported for graphql requests"));
+    }
+    match result.method.as_deref() {
+        None => result.method = Some("POST".to_string()),
+        Some(method) if method.eq_ignore_ascii_case("POST") => {}
+        Some(_) => return Err(syn::Error::new_spanned(attr, "graphql requests are sent with POST")),
+    }
+    if result.path.is_none() {
+        result.path = Some("/graphql".to_string());
+    }
+    Ok(())
+}
+
+/// Check that `response_format = "negotiate"` has a response type and no other decoding options
+fn check_negotiate_attributes(result: &StructAttributes, attr: &syn::Attribute) -> syn::Result<()> {
+    if result.response_format != ResponseFormat::Negotiate {
+        return Ok(());
+    }
+
+    if result.response.as_ref().is_none_or(is_unit_type) {
+        return Err(syn::Error::new_spanned(attr, "response_format = \"negotiate\" requires response = Type"));
+    }
+    if result.graphql
+        || result.poll.is_some()
+        || result.lenient_response
+        || result.strict_response
+        || result.capture_unknown_fields
+    {
+        return Err(syn::Error::new_spanned(
+            attr,
+            "response_format = \"negotiate\" can't be combined with graphql, poll, lenient_response, strict_response or capture_unknown_fields",
+        ));
+    }
+    Ok(())
+}
+
 /// Parse field-level #[request_builder(...)] attributes
 pub(super) fn parse_field_attributes(attrs: &[syn::Attribute]) -> syn::Result<FieldAttributes> {
     let mut result = FieldAttributes::default();
@@ -199,6 +643,44 @@ pub(super) fn parse_field_attributes(attrs: &[syn::Attribute]) -> syn::Result<Fi
                 return Ok(());
             }
 
+            // #[request_builder(flatten)]
+            if meta.path.is_ident("flatten") {
+                result.flatten = true;
+                return Ok(());
+            }
+
+            // #[request_builder(skip)]
+            if meta.path.is_ident("skip") {
+                result.skip = true;
+                return Ok(());
+            }
+
+            // #[request_builder(const_value = expr)]: a skipped field that always takes `expr`
+            if meta.path.is_ident("const_value") {
+                let value = meta.value()?;
+                let expr: syn::Expr = value.parse()?;
+                result.skip = true;
+                result.default = DefaultBehavior::Custom(expr);
+                return Ok(());
+            }
+
+            // #[request_builder(computed = "expr_or_fn")]
+            if meta.path.is_ident("computed") {
+                let value = meta.value()?;
+                let lit: syn::LitStr = value.parse()?;
+                let expr: syn::Expr = lit.parse()?;
+                result.computed = Some(expr);
+                return Ok(());
+            }
+
+            // #[request_builder(example = expr)]
+            if meta.path.is_ident("example") {
+                let value = meta.value()?;
+                let expr: syn::Expr = value.parse()?;
+                result.example = Some(expr);
+                return Ok(());
+            }
+
             // #[request_builder(validate = "function_path")]
             if meta.path.is_ident("validate") {
                 let value = meta.value()?;
@@ -5,16 +5,29 @@
 
 use crate::utils::{extract_doc_attributes, option_inner_type};
 use super::attributes::{StructAttributes, parse_field_attributes, DefaultBehavior};
+use super::http::{
+    generate_json_decoder, generate_request_checksum, generate_response_body, generate_response_checksum,
+    generate_response_decoding, generate_response_unwrap,
+};
+use super::utils::{GenericsTokens, with_lifetime};
 use proc_macro2::TokenStream;
 use quote::quote;
-use syn;
+
+/// Returns true if the field is marked `#[request_builder(skip)]` or
+/// `#[request_builder(computed = "...")]` and therefore has no corresponding
+/// builder field or setter
+pub(super) fn is_skipped(field: &syn::Field) -> bool {
+    parse_field_attributes(&field.attrs)
+        .map(|attrs| attrs.skip || attrs.computed.is_some())
+        .unwrap_or(false)
+}
 
 /// Generate builder struct field definitions
 /// Wraps non-Option fields in Option, keeps Option fields as-is
 pub(super) fn generate_builder_fields(
     fields: &syn::punctuated::Punctuated<syn::Field, syn::token::Comma>,
 ) -> impl Iterator<Item = TokenStream> + '_ {
-    fields.iter().map(|field| {
+    fields.iter().filter(|field| !is_skipped(field)).map(|field| {
         let field_name = &field.ident;
         let field_type = &field.ty;
 
@@ -33,12 +46,226 @@ pub(super) fn generate_builder_fields(
     })
 }
 
+/// Generate a `Clone` impl for the builder that applies when every field type is `Clone`
+///
+/// The field bounds are written as `for<'__clone> T: Clone` so that a concrete
+/// non-`Clone` field type leaves the builder without `Clone` instead of
+/// failing to compile as a trivially false bound.
+pub(super) fn generate_builder_clone_impl(
+    builder_name: &syn::Ident,
+    generics: &GenericsTokens,
+    fields: &syn::punctuated::Punctuated<syn::Field, syn::token::Comma>,
+) -> TokenStream {
+    let GenericsTokens { params, args, where_clause, .. } = generics;
+    let fields: Vec<_> = fields.iter().filter(|field| !is_skipped(field)).collect();
+    let field_names = fields.iter().map(|field| &field.ident);
+    let field_bounds = fields.iter().map(|field| {
+        let field_type = &field.ty;
+        quote! { for<'__clone> #field_type: std::clone::Clone }
+    });
+    let existing_predicates = where_clause.iter().flat_map(|clause| clause.predicates.iter());
+
+    quote! {
+        impl<#params __C: std::clone::Clone, __A: std::clone::Clone> std::clone::Clone for #builder_name<#args __C, __A>
+        where
+            #(#existing_predicates,)*
+            #(#field_bounds,)*
+        {
+            fn clone(&self) -> Self {
+                Self {
+                    #(#field_names: std::clone::Clone::clone(&self.#field_names),)*
+                    __http_client: std::clone::Clone::clone(&self.__http_client),
+                    __async_http_client: std::clone::Clone::clone(&self.__async_http_client),
+                    __base_url: std::clone::Clone::clone(&self.__base_url),
+                    __dynamic_headers: std::clone::Clone::clone(&self.__dynamic_headers),
+                    __options: self.__options,
+                    __cancellation: std::clone::Clone::clone(&self.__cancellation),
+                    __rate_limit: std::clone::Clone::clone(&self.__rate_limit),
+                    __unconfigured: std::clone::Clone::clone(&self.__unconfigured),
+                    __transport: std::clone::Clone::clone(&self.__transport),
+                }
+            }
+        }
+    }
+}
+
+/// Generate a compile-time check that the builder is `Send` and `Sync`
+/// whenever the request's fields and the HTTP clients are
+///
+/// The check is never called: type-checking it is enough. It only names the
+/// builder's type, so it doesn't count as a use of the request for dead code
+/// warnings. `send_async()` holds the built request across awaits, so its
+/// future is `Send` too, given the futures checked in the library. Field
+/// bounds use the same `for<'__send>` form as the `Clone` impl, so a request
+/// with a field that isn't `Send` skips the check instead of failing to
+/// compile.
+pub(super) fn generate_send_sync_assertions(
+    builder_name: &syn::Ident,
+    generics: &GenericsTokens,
+    fields: &syn::punctuated::Punctuated<syn::Field, syn::token::Comma>,
+) -> TokenStream {
+    let GenericsTokens { params, args, where_clause, .. } = generics;
+    let existing_predicates = where_clause.iter().flat_map(|clause| clause.predicates.iter());
+    // Skipped fields are kept in the request too. The struct's lifetimes are replaced so that the
+    // bounds don't tie them to the higher-ranked one.
+    let lifetime = syn::Lifetime::new("'__send", proc_macro2::Span::call_site());
+    let field_bounds = fields.iter().map(|field| {
+        let field_type = with_lifetime(&field.ty, &lifetime);
+        quote! { for<'__send> #field_type: std::marker::Send + std::marker::Sync }
+    });
+
+    quote! {
+        const _: () = {
+            fn assert_send_sync<T: std::marker::Send + std::marker::Sync>(_: &T) {}
+
+            #[allow(dead_code)]
+            fn assert_builder<#params __C, __A>(builder: &#builder_name<#args __C, __A>)
+            where
+                #(#existing_predicates,)*
+                #(#field_bounds,)*
+                __C: std::marker::Send + std::marker::Sync,
+                __A: std::marker::Send + std::marker::Sync,
+            {
+                assert_send_sync(builder);
+            }
+        };
+    }
+}
+
+/// Generate `arbitrary::Arbitrary` impls for `#[request_builder(arbitrary)]`
+///
+/// The builder gets a value for every field (`Option` fields may stay unset),
+/// and the struct is built from an arbitrary builder, so it only yields requests
+/// that pass `build()`. Input that builds an invalid request is rejected with
+/// `arbitrary::Error::IncorrectFormat`, which fuzzers skip.
+pub(super) fn generate_arbitrary_impls(
+    struct_name: &syn::Ident,
+    builder_name: &syn::Ident,
+    generics: &GenericsTokens,
+    fields: &syn::punctuated::Punctuated<syn::Field, syn::token::Comma>,
+) -> TokenStream {
+    let GenericsTokens { params, args, where_clause, .. } = generics;
+    let fields: Vec<_> = fields.iter().filter(|field| !is_skipped(field)).collect();
+    let assignments = fields.iter().map(|field| {
+        let field_name = &field.ident;
+        if option_inner_type(&field.ty).is_some() {
+            quote! { __builder.#field_name = derive_rest_api::__private::arbitrary::Arbitrary::arbitrary(__u)?; }
+        } else {
+            quote! {
+                __builder.#field_name =
+                    std::option::Option::Some(derive_rest_api::__private::arbitrary::Arbitrary::arbitrary(__u)?);
+            }
+        }
+    });
+    let field_bounds: Vec<_> = fields
+        .iter()
+        .map(|field| {
+            let field_type = &field.ty;
+            quote! { #field_type: derive_rest_api::__private::arbitrary::Arbitrary<'__arbitrary> }
+        })
+        .collect();
+    let existing_predicates: Vec<_> = where_clause.iter().flat_map(|clause| clause.predicates.iter()).collect();
+
+    quote! {
+        impl<'__arbitrary, #params> derive_rest_api::__private::arbitrary::Arbitrary<'__arbitrary> for #builder_name<#args (), ()>
+        where
+            #(#existing_predicates,)*
+            #(#field_bounds,)*
+        {
+            fn arbitrary(
+                __u: &mut derive_rest_api::__private::arbitrary::Unstructured<'__arbitrary>,
+            ) -> derive_rest_api::__private::arbitrary::Result<Self> {
+                let mut __builder = Self::new();
+                #(#assignments)*
+                std::result::Result::Ok(__builder)
+            }
+        }
+
+        impl<'__arbitrary, #params> derive_rest_api::__private::arbitrary::Arbitrary<'__arbitrary> for #struct_name<#args>
+        where
+            #(#existing_predicates,)*
+            #(#field_bounds,)*
+        {
+            fn arbitrary(
+                __u: &mut derive_rest_api::__private::arbitrary::Unstructured<'__arbitrary>,
+            ) -> derive_rest_api::__private::arbitrary::Result<Self> {
+                <#builder_name<#args (), ()> as derive_rest_api::__private::arbitrary::Arbitrary<'__arbitrary>>::arbitrary(__u)?
+                    .build()
+                    .map_err(|_| derive_rest_api::__private::arbitrary::Error::IncorrectFormat)
+            }
+        }
+    }
+}
+
+/// Generate the request struct's `sample()` constructor
+///
+/// Fields with `#[request_builder(example = expr)]` are set through their
+/// setters, fields with a default or an `Option` type are left to `build()`,
+/// and the rest get `Default::default()`. The method only exists when those
+/// remaining field types are `Default` (bounded like the `Clone` impl).
+pub(super) fn generate_sample_method(
+    struct_name: &syn::Ident,
+    builder_name: &syn::Ident,
+    generics: &GenericsTokens,
+    fields: &syn::punctuated::Punctuated<syn::Field, syn::token::Comma>,
+    struct_attrs: &StructAttributes,
+) -> TokenStream {
+    let GenericsTokens { params, args, where_clause, .. } = generics;
+    let mut assignments = Vec::new();
+    let mut field_bounds = Vec::new();
+    for field in fields.iter().filter(|field| !is_skipped(field)) {
+        let field_name = &field.ident;
+        let field_type = &field.ty;
+        let field_attrs = parse_field_attributes(&field.attrs).unwrap_or_default();
+        if let Some(example) = &field_attrs.example {
+            assignments.push(quote! { __builder = __builder.#field_name(#example); });
+        } else if option_inner_type(field_type).is_none()
+            && matches!(field_attrs.default, DefaultBehavior::Required)
+            && !struct_attrs.default
+        {
+            assignments.push(quote! {
+                __builder.#field_name = std::option::Option::Some(std::default::Default::default());
+            });
+            field_bounds.push(quote! { for<'__sample> #field_type: std::default::Default });
+        }
+    }
+    let existing_predicates = where_clause.iter().flat_map(|clause| clause.predicates.iter());
+    let mutability = if assignments.is_empty() { quote! {} } else { quote! { mut } };
+
+    quote! {
+        impl<#params> #struct_name<#args>
+        where
+            #(#existing_predicates,)*
+            #(#field_bounds,)*
+        {
+            #[doc = "Returns an example request, for documentation, demos and mock servers."]
+            #[doc = ""]
+            #[doc = "Fields take their `#[request_builder(example = ...)]` value, or otherwise their default,"]
+            #[doc = "`None`, or `Default::default()`."]
+            #[doc = ""]
+            #[doc = "# Panics"]
+            #[doc = ""]
+            #[doc = "Panics if the example values don't pass `build()`."]
+            pub fn sample() -> Self {
+                let #mutability __builder = #builder_name::new();
+                #(#assignments)*
+                match __builder.build() {
+                    std::result::Result::Ok(request) => request,
+                    std::result::Result::Err(error) => {
+                        std::panic!("the sample values for `{}` don't build: {}", std::stringify!(#struct_name), error)
+                    }
+                }
+            }
+        }
+    }
+}
+
 /// Generate setter methods for builder fields
 pub(super) fn generate_setter_methods<'a>(
     fields: &'a syn::punctuated::Punctuated<syn::Field, syn::token::Comma>,
     struct_attrs: &'a StructAttributes,
 ) -> impl Iterator<Item = TokenStream> + 'a {
-    fields.iter().map(move |field| {
+    fields.iter().filter(|field| !is_skipped(field)).map(move |field| {
         let field_name = &field.ident;
         let field_type = &field.ty;
 
@@ -92,13 +319,105 @@ pub(super) fn generate_setter_methods<'a>(
     })
 }
 
-/// Generate field processing code for the build() method
+/// Generate `with_request()`, which sets every builder field from a built request
+///
+/// Skipped and computed fields have no builder field, and are set again by `build()`.
+pub(super) fn generate_with_request_method(
+    struct_name: &syn::Ident,
+    generics: &GenericsTokens,
+    fields: &syn::punctuated::Punctuated<syn::Field, syn::token::Comma>,
+) -> TokenStream {
+    let args = &generics.args;
+    let assignments = fields.iter().filter(|field| !is_skipped(field)).map(|field| {
+        let field_name = &field.ident;
+        if option_inner_type(&field.ty).is_some() {
+            quote! { self.#field_name = request.#field_name; }
+        } else {
+            quote! { self.#field_name = std::option::Option::Some(request.#field_name); }
+        }
+    });
+
+    quote! {
+        #[doc = concat!("Sets every field from an already built [`", stringify!(#struct_name), "`].")]
+        #[doc = ""]
+        #[doc = "Useful for sending a request value through a builder pre-configured by a client."]
+        #[allow(unused_mut, unused_variables)]
+        pub fn with_request(mut self, request: #struct_name<#args>) -> Self {
+            #(#assignments)*
+            self
+        }
+    }
+}
+
+/// Generate `is_set_<field>()` for each builder field and `missing_required_fields()`
+///
+/// A field is required when `try_build()` would fail with `MissingField` without
+/// it: it isn't an `Option` and has no default, from the field or the struct.
+pub(super) fn generate_state_methods(
+    fields: &syn::punctuated::Punctuated<syn::Field, syn::token::Comma>,
+    struct_attrs: &StructAttributes,
+) -> TokenStream {
+    let fields: Vec<_> = fields.iter().filter(|field| !is_skipped(field)).collect();
+
+    let is_set_methods = fields.iter().map(|field| {
+        let field_name = field.ident.as_ref().unwrap();
+        let method_name = quote::format_ident!("is_set_{}", field_name);
+        let doc = format!("Returns `true` if `{}` has been set.", field_name);
+        quote! {
+            #[doc = #doc]
+            pub fn #method_name(&self) -> bool {
+                self.#field_name.is_some()
+            }
+        }
+    });
+
+    let is_required = |field: &&&syn::Field| {
+        let field_attrs = parse_field_attributes(&field.attrs).unwrap_or_default();
+        option_inner_type(&field.ty).is_none()
+            && !struct_attrs.default
+            && matches!(field_attrs.default, DefaultBehavior::Required)
+    };
+    let required_checks = fields.iter().filter(is_required).map(|field| {
+        let field_name = &field.ident;
+        let field_name_str = field_name.as_ref().unwrap().to_string();
+        quote! {
+            if self.#field_name.is_none() {
+                __missing.push(#field_name_str);
+            }
+        }
+    });
+
+    quote! {
+        #(#is_set_methods)*
+
+        #[doc = "Returns the required fields that haven't been set, in field order."]
+        #[doc = ""]
+        #[doc = "These are the fields `build()` would report as `MissingField`, so an empty list"]
+        #[doc = "means the builder is complete apart from validation. Useful for checking a form"]
+        #[doc = "before building a request from it."]
+        #[allow(unused_mut)]
+        pub fn missing_required_fields(&self) -> std::vec::Vec<&'static str> {
+            let mut __missing = std::vec::Vec::new();
+            #(#required_checks)*
+            __missing
+        }
+    }
+}
+
+/// Generate field processing code for the try_build() method
 /// Handles extraction, validation, and default values
-pub(super) fn generate_field_processing<'a>(
-    fields: &'a syn::punctuated::Punctuated<syn::Field, syn::token::Comma>,
-    struct_attrs: &'a StructAttributes,
-) -> impl Iterator<Item = TokenStream> + 'a {
-    fields.iter().map(move |field| {
+///
+/// Each field's value is bound to `Option<T>`: on failure the error is pushed
+/// onto `__errors` and the binding is `None`, so every field gets checked.
+/// Returns the processing statements and the temporary variables bound.
+pub(super) fn generate_field_processing(
+    fields: &syn::punctuated::Punctuated<syn::Field, syn::token::Comma>,
+    struct_attrs: &StructAttributes,
+) -> (Vec<TokenStream>, Vec<syn::Ident>) {
+    let mut processing = Vec::new();
+    let mut temp_vars = Vec::new();
+
+    for field in fields {
         let field_name = &field.ident;
         let field_type = &field.ty;
         let field_name_str = field_name.as_ref().unwrap().to_string();
@@ -107,6 +426,11 @@ pub(super) fn generate_field_processing<'a>(
         // Parse field-level attributes
         let field_attrs = parse_field_attributes(&field.attrs).unwrap_or_default();
 
+        // Computed fields are evaluated once every other field is available
+        if field_attrs.computed.is_some() {
+            continue;
+        }
+
         // Determine the default behavior for this field
         let default_behavior = match &field_attrs.default {
             DefaultBehavior::Required => {
@@ -123,30 +447,40 @@ pub(super) fn generate_field_processing<'a>(
 
         let validate_fn = field_attrs.validate.as_ref();
 
-        // Generate value extraction
-        let value_extraction = if option_inner_type(field_type).is_some() {
+        // Generate value extraction, as a Result
+        let value_extraction = if field_attrs.skip {
+            // Skipped fields have no builder storage: always use the default
+            match &field_attrs.default {
+                DefaultBehavior::Custom(expr) => quote! {
+                    std::result::Result::<#field_type, derive_rest_api::RestApiError>::Ok(#expr)
+                },
+                _ => quote! {
+                    std::result::Result::<#field_type, derive_rest_api::RestApiError>::Ok(std::default::Default::default())
+                },
+            }
+        } else if option_inner_type(field_type).is_some() {
             // Field is already Option, just use it
             quote! {
-                let #temp_var = self.#field_name;
+                std::result::Result::<#field_type, derive_rest_api::RestApiError>::Ok(self.#field_name)
             }
         } else {
             match &default_behavior {
                 DefaultBehavior::Required => {
                     // Field is required, error if not set
                     quote! {
-                        let #temp_var = self.#field_name.ok_or_else(|| derive_rest_api::RestApiError::missing_field(#field_name_str))?;
+                        self.#field_name.ok_or_else(|| derive_rest_api::RestApiError::missing_field(#field_name_str))
                     }
                 },
                 DefaultBehavior::UseDefault => {
                     // Use Default::default() if not set
                     quote! {
-                        let #temp_var = self.#field_name.unwrap_or_default();
+                        std::result::Result::<#field_type, derive_rest_api::RestApiError>::Ok(self.#field_name.unwrap_or_default())
                     }
                 },
                 DefaultBehavior::Custom(expr) => {
                     // Use custom expression if not set
                     quote! {
-                        let #temp_var = self.#field_name.unwrap_or_else(|| #expr);
+                        std::result::Result::<#field_type, derive_rest_api::RestApiError>::Ok(self.#field_name.unwrap_or_else(|| #expr))
                     }
                 },
             }
@@ -157,25 +491,92 @@ pub(super) fn generate_field_processing<'a>(
             if option_inner_type(field_type).is_some() {
                 // Optional field: validate if Some
                 quote! {
-                    if let std::option::Option::Some(ref value) = #temp_var {
-                        #validate_fn(value).map_err(|e| derive_rest_api::RestApiError::validation_error(#field_name_str, e))?;
-                    }
+                    .and_then(|value| {
+                        if let std::option::Option::Some(ref inner) = value {
+                            #validate_fn(inner).map_err(|e| derive_rest_api::RestApiError::validation_error(#field_name_str, e))?;
+                        }
+                        std::result::Result::Ok(value)
+                    })
                 }
             } else {
                 // Non-optional field: always validate
                 quote! {
-                    #validate_fn(&#temp_var).map_err(|e| derive_rest_api::RestApiError::validation_error(#field_name_str, e))?;
+                    .and_then(|value| {
+                        #validate_fn(&value).map_err(|e| derive_rest_api::RestApiError::validation_error(#field_name_str, e))?;
+                        std::result::Result::Ok(value)
+                    })
                 }
             }
         } else {
             quote! {}
         };
 
-        quote! {
-            #value_extraction
-            #validation
+        processing.push(quote! {
+            let #temp_var = match #value_extraction #validation {
+                std::result::Result::Ok(value) => std::option::Option::Some(value),
+                std::result::Result::Err(error) => {
+                    __errors.push(error);
+                    std::option::Option::None
+                }
+            };
+        });
+        temp_vars.push(temp_var);
+    }
+
+    (processing, temp_vars)
+}
+
+/// Generate code evaluating `#[request_builder(computed = "...")]` fields.
+///
+/// Returns the statements to run before the struct is constructed and the
+/// statements to run on the constructed struct (bound as `__request`):
+/// - A function path is called with a reference to the struct once every
+///   other field is set, so the field holds `Default::default()` until then.
+/// - Any other expression is evaluated with the other fields in scope by
+///   reference under their own names.
+///
+/// Computed fields are evaluated in declaration order, so later ones can use
+/// the values of earlier ones.
+pub(super) fn generate_computed_fields(
+    fields: &syn::punctuated::Punctuated<syn::Field, syn::token::Comma>,
+) -> (Vec<TokenStream>, Vec<TokenStream>) {
+    let mut before_construction = Vec::new();
+    let mut after_construction = Vec::new();
+    let mut available = Vec::new();
+
+    for field in fields {
+        let field_name = field.ident.as_ref().unwrap();
+        let field_type = &field.ty;
+        let temp_var = quote::format_ident!("__field_{}", field_name);
+        let field_attrs = parse_field_attributes(&field.attrs).unwrap_or_default();
+
+        match &field_attrs.computed {
+            None => available.push((field_name, temp_var)),
+            Some(syn::Expr::Path(compute_fn)) => {
+                before_construction.push(quote! {
+                    let #temp_var: #field_type = std::default::Default::default();
+                });
+                after_construction.push(quote! {
+                    __request.#field_name = #compute_fn(&__request);
+                });
+            }
+            Some(expr) => {
+                let bindings = available.iter().map(|(name, temp_var)| {
+                    quote! { let #name = &#temp_var; }
+                });
+                before_construction.push(quote! {
+                    #[allow(unused_variables)]
+                    let #temp_var: #field_type = {
+                        #(#bindings)*
+                        #expr
+                    };
+                });
+                available.push((field_name, temp_var));
+            }
         }
-    })
+    }
+
+    (before_construction, after_construction)
 }
 
 /// Generate final field assignments for struct construction
@@ -195,24 +596,51 @@ pub(super) fn generate_build_fields(
 /// Generate send() and send_async() methods for the builder
 pub(super) fn generate_builder_send_methods(
     struct_name: &syn::Ident,
+    generics: &GenericsTokens,
     struct_attrs: &StructAttributes,
 ) -> TokenStream {
     let builder_name = quote::format_ident!("{}Builder", struct_name);
-    let method_value = struct_attrs.method.as_ref().map(|s| s.as_str()).unwrap_or("GET");
-    let return_type = struct_attrs.response.clone().unwrap_or(syn::Type::Verbatim(quote! {Vec<u8>}));
-
-    let return_value = match struct_attrs.response.clone() {
-        Some(_) => quote! {
-            let bytes = response?;
-            serde_json::from_slice(&bytes)
-                .map_err(|e| derive_rest_api::RestApiError::ResponseDeserializationError { source: e })
-        },
-        _ => quote! { response },
-    };
+    let GenericsTokens { params, args, where_clause, .. } = generics;
+    let method_value = struct_attrs.method.as_deref().unwrap_or("GET");
+    let (return_type, return_value) = generate_response_decoding(struct_attrs);
+    let decode_json = generate_json_decoder(struct_attrs);
+
+    let request_checksum = generate_request_checksum(struct_attrs);
+    let response_checksum = generate_response_checksum(struct_attrs);
+    let response_unwrap = generate_response_unwrap(struct_attrs);
+    let response_body = generate_response_body(struct_attrs);
+    let (send_and_wait, send_and_wait_async) = generate_poll_methods(struct_name, struct_attrs, &return_type, &return_value);
 
     quote! {
+        // Impl block for every builder, with or without an HTTP client
+        impl<#params __C, __A> #builder_name<#args __C, __A> #where_clause {
+            #[doc = "Builds the request as it would be sent to `base_url`, without sending it."]
+            #[doc = ""]
+            #[doc = "The preview has the method, full URL, headers and body, with the headers sorted by name,"]
+            #[doc = "so tests can compare it against a snapshot. Credentials and middleware are applied when"]
+            #[doc = "the request is sent, so they aren't included."]
+            #[doc = ""]
+            #[doc = "# Errors"]
+            #[doc = ""]
+            #[doc = "Returns the same errors as `send()` before the request is sent: building it, an invalid"]
+            #[doc = "header, URL building or body serialization."]
+            pub fn preview(mut self, base_url: &str) -> std::result::Result<derive_rest_api::BuiltRequest, derive_rest_api::RestApiError> {
+                let dynamic_headers = std::mem::take(&mut self.__dynamic_headers).into_header_map()?;
+                let query_encoder = self.__transport.query_encoder.take();
+                let query_order = self.__transport.query_order;
+                let request = self.build()?;
+                let path = request.build_url_with(query_encoder.as_deref(), query_order).map_err(|e| derive_rest_api::RestApiError::UrlBuildError { source: std::boxed::Box::new(e) })?;
+                let mut headers = request.build_headers()?;
+                headers.extend(dynamic_headers);
+                let body = request.build_body()?;
+                #request_checksum
+
+                std::result::Result::Ok(derive_rest_api::BuiltRequest::new(#method_value, format!("{}{}", base_url, path), &headers, body))
+            }
+        }
+
         // Impl block for builders with an HTTP client
-        impl<__C: derive_rest_api::HttpClient, __A> #builder_name<__C, __A> {
+        impl<#params __C: derive_rest_api::HttpClient, __A> #builder_name<#args __C, __A> #where_clause {
             #[doc = "Builds the request and sends it using the embedded HTTP client."]
             #[doc = ""]
             #[doc = "# Errors"]
@@ -223,7 +651,48 @@ pub(super) fn generate_builder_send_methods(
             #[doc = "- URL building fails"]
             #[doc = "- Body serialization fails"]
             #[doc = "- The HTTP request fails"]
-            pub fn send(mut self) -> std::result::Result<#return_type, derive_rest_api::RestApiError> {
+            #[doc = "- The response is larger than the limit set with `max_response_bytes()`"]
+            #[doc = "- A response checksum doesn't match the body (with `verify_checksum`)"]
+            pub fn send(self) -> std::result::Result<#return_type, derive_rest_api::RestApiError> {
+                let response = self.send_raw() #response_body;
+                #return_value
+            }
+
+            #[doc = "Builds the request, sends it using the embedded HTTP client, and deserializes"]
+            #[doc = "the JSON response into `T` instead of the declared response type."]
+            #[doc = ""]
+            #[doc = "Useful when the response's shape depends on the request, e.g. sparse fieldsets"]
+            #[doc = "selected through a query parameter. For GraphQL requests `T` is the `data` member."]
+            #[doc = ""]
+            #[doc = "# Errors"]
+            #[doc = ""]
+            #[doc = "Returns the same errors as `send()`, and `RestApiError::ResponseDeserializationError`"]
+            #[doc = "if the body doesn't deserialize into `T`."]
+            pub fn send_as<T: derive_rest_api::__private::DeserializeOwned>(self) -> std::result::Result<T, derive_rest_api::RestApiError> {
+                #decode_json(&self.__send_bytes()?)
+            }
+
+            #[doc = "Builds the request, sends it using the embedded HTTP client, and returns the"]
+            #[doc = "response as an untyped JSON value, without needing a response model."]
+            #[doc = ""]
+            #[doc = "# Errors"]
+            #[doc = ""]
+            #[doc = "Returns the same errors as `send()`, and `RestApiError::ResponseDeserializationError`"]
+            #[doc = "if the body isn't JSON."]
+            pub fn send_json(self) -> std::result::Result<derive_rest_api::__private::JsonValue, derive_rest_api::RestApiError> {
+                self.send_as()
+            }
+
+            #[doc = "Builds the request and sends it using the embedded HTTP client, returning the raw response."]
+            #[doc = ""]
+            #[doc = "The status, headers and body are returned exactly as received, so the payload can be"]
+            #[doc = "archived or inspected before decoding. Error statuses are returned as responses, and"]
+            #[doc = "the response size limit and checksum verification still apply."]
+            #[doc = ""]
+            #[doc = "# Errors"]
+            #[doc = ""]
+            #[doc = "Returns the same errors as `send()`, except those from decoding the body."]
+            pub fn send_raw(mut self) -> std::result::Result<derive_rest_api::http::Response<std::vec::Vec<u8>>, derive_rest_api::RestApiError> {
                 // Extract client and base URL before building
                 let client = self.__http_client.take()
                     .ok_or_else(|| derive_rest_api::RestApiError::missing_field("http_client"))?;
@@ -231,25 +700,52 @@ pub(super) fn generate_builder_send_methods(
                 let base_url = self.__base_url.take()
                     .ok_or_else(|| derive_rest_api::RestApiError::MissingBaseUrl)?;
 
-                let timeout = self.__timeout.take();
-                let dynamic_headers = self.__dynamic_headers.clone();
+                let options = self.__options;
+                let rate_limit = self.__rate_limit.take();
+                let mut transport = std::mem::take(&mut self.__transport);
+                transport.endpoint = std::option::Option::Some(std::stringify!(#struct_name));
+                let dynamic_headers = std::mem::take(&mut self.__dynamic_headers).into_header_map()?;
                 let request = self.build()?;
-                let path = request.build_url().map_err(|e| derive_rest_api::RestApiError::UrlBuildError { source: std::boxed::Box::new(e) })?;
-                let url = format!("{}{}", base_url, path);
-                let mut headers = request.build_headers();
+                let path = request.build_url_with(transport.query_encoder.as_deref(), transport.query_order).map_err(|e| derive_rest_api::RestApiError::UrlBuildError { source: std::boxed::Box::new(e) })?;
+                let mut headers = request.build_headers()?;
                 // Merge dynamic headers (these override request headers)
                 headers.extend(dynamic_headers);
                 let body = request.build_body()?;
+                #request_checksum
 
-                let response = client.send(#method_value, &url, headers, body, timeout)
-                    .map_err(|e| derive_rest_api::RestApiError::http_error(e));
+                derive_rest_api::__private::wait_for_quota(&rate_limit);
+                transport.send(&client, #method_value, &base_url, &path, headers, body, options)
+                    .inspect(|response| derive_rest_api::__private::record_rate_limit(&rate_limit, response))
+                    .and_then(|response| derive_rest_api::__private::check_response_size(response, &options))
+                    #response_checksum
+            }
 
-                #return_value
+            // Sends the request, returning the body before it is decoded
+            fn __send_bytes(self) -> std::result::Result<std::vec::Vec<u8>, derive_rest_api::RestApiError> {
+                self.send_raw()
+                    .map(derive_rest_api::http::Response::into_body)
+                    #response_unwrap
             }
+
+            #[doc = "Sends a copy of the request, leaving the builder ready to send again."]
+            #[doc = ""]
+            #[doc = "Available when every field and the HTTP client are `Clone`."]
+            #[doc = ""]
+            #[doc = "# Errors"]
+            #[doc = ""]
+            #[doc = "Returns the same errors as `send()`."]
+            pub fn send_ref(&self) -> std::result::Result<#return_type, derive_rest_api::RestApiError>
+            where
+                Self: std::clone::Clone,
+            {
+                std::clone::Clone::clone(self).send()
+            }
+
+            #send_and_wait
         }
 
         // Impl block for builders with an async HTTP client
-        impl<__C, __A: derive_rest_api::AsyncHttpClient> #builder_name<__C, __A> {
+        impl<#params __C, __A: derive_rest_api::AsyncHttpClient> #builder_name<#args __C, __A> #where_clause {
             #[doc = "Builds the request and sends it using the embedded async HTTP client."]
             #[doc = ""]
             #[doc = "# Errors"]
@@ -260,7 +756,47 @@ pub(super) fn generate_builder_send_methods(
             #[doc = "- URL building fails"]
             #[doc = "- Body serialization fails"]
             #[doc = "- The HTTP request fails"]
-            pub async fn send_async(mut self) -> std::result::Result<#return_type, derive_rest_api::RestApiError> {
+            #[doc = "- The response is larger than the limit set with `max_response_bytes()`"]
+            #[doc = "- A response checksum doesn't match the body (with `verify_checksum`)"]
+            #[doc = "- The request is cancelled through its cancellation token"]
+            #[doc = ""]
+            #[doc = "Dropping the returned future also abandons the request."]
+            pub async fn send_async(self) -> std::result::Result<#return_type, derive_rest_api::RestApiError> {
+                let response = self.send_raw_async().await #response_body;
+                #return_value
+            }
+
+            #[doc = "Async version of `send_as()`: sends the request using the embedded async HTTP"]
+            #[doc = "client and deserializes the JSON response into `T`."]
+            #[doc = ""]
+            #[doc = "# Errors"]
+            #[doc = ""]
+            #[doc = "Returns the same errors as `send_async()`, and `RestApiError::ResponseDeserializationError`"]
+            #[doc = "if the body doesn't deserialize into `T`."]
+            pub async fn send_as_async<T: derive_rest_api::__private::DeserializeOwned>(self) -> std::result::Result<T, derive_rest_api::RestApiError> {
+                #decode_json(&self.__send_bytes_async().await?)
+            }
+
+            #[doc = "Async version of `send_json()`: returns the response as an untyped JSON value."]
+            #[doc = ""]
+            #[doc = "# Errors"]
+            #[doc = ""]
+            #[doc = "Returns the same errors as `send_async()`, and `RestApiError::ResponseDeserializationError`"]
+            #[doc = "if the body isn't JSON."]
+            pub async fn send_json_async(self) -> std::result::Result<derive_rest_api::__private::JsonValue, derive_rest_api::RestApiError> {
+                self.send_as_async().await
+            }
+
+            #[doc = "Builds the request and sends it using the embedded async HTTP client, returning the raw response."]
+            #[doc = ""]
+            #[doc = "The status, headers and body are returned exactly as received, so the payload can be"]
+            #[doc = "archived or inspected before decoding. Error statuses are returned as responses, and"]
+            #[doc = "the response size limit and checksum verification still apply."]
+            #[doc = ""]
+            #[doc = "# Errors"]
+            #[doc = ""]
+            #[doc = "Returns the same errors as `send_async()`, except those from decoding the body."]
+            pub async fn send_raw_async(mut self) -> std::result::Result<derive_rest_api::http::Response<std::vec::Vec<u8>>, derive_rest_api::RestApiError> {
                 // Extract client and base URL before building
                 let client = self.__async_http_client.take()
                     .ok_or_else(|| derive_rest_api::RestApiError::missing_field("async_http_client"))?;
@@ -268,21 +804,222 @@ pub(super) fn generate_builder_send_methods(
                 let base_url = self.__base_url.take()
                     .ok_or_else(|| derive_rest_api::RestApiError::MissingBaseUrl)?;
 
-                let timeout = self.__timeout.take();
-                let dynamic_headers = self.__dynamic_headers.clone();
+                let options = self.__options;
+                let rate_limit = self.__rate_limit.take();
+                let mut transport = std::mem::take(&mut self.__transport);
+                transport.endpoint = std::option::Option::Some(std::stringify!(#struct_name));
+                let cancellation = self.__cancellation.take();
+                let dynamic_headers = std::mem::take(&mut self.__dynamic_headers).into_header_map()?;
                 let request = self.build()?;
-                let path = request.build_url().map_err(|e| derive_rest_api::RestApiError::UrlBuildError { source: std::boxed::Box::new(e) })?;
-                let url = format!("{}{}", base_url, path);
-                let mut headers = request.build_headers();
+                let path = request.build_url_with(transport.query_encoder.as_deref(), transport.query_order).map_err(|e| derive_rest_api::RestApiError::UrlBuildError { source: std::boxed::Box::new(e) })?;
+                let mut headers = request.build_headers()?;
                 // Merge dynamic headers (these override request headers)
                 headers.extend(dynamic_headers);
                 let body = request.build_body()?;
+                #request_checksum
 
-                let response = client.send_async(#method_value, &url, headers, body, timeout).await
-                    .map_err(|e| derive_rest_api::RestApiError::http_error(e));
+                // Stop waiting (and drop the in-flight request) if the cancellation token fires
+                derive_rest_api::__private::with_cancellation(cancellation, async {
+                    derive_rest_api::__private::wait_for_quota_async(&rate_limit).await;
+                    transport.send_async(&client, #method_value, &base_url, &path, headers, body, options).await
+                }).await?
+                    .inspect(|response| derive_rest_api::__private::record_rate_limit(&rate_limit, response))
+                    .and_then(|response| derive_rest_api::__private::check_response_size(response, &options))
+                    #response_checksum
+            }
 
-                #return_value
+            #[doc = "Builds the request and queues it on the client's background sender, returning at once"]
+            #[doc = "without waiting for a response."]
+            #[doc = ""]
+            #[doc = "For fire-and-forget calls such as telemetry: the sender's worker task sends the request"]
+            #[doc = "and retries it if it fails, and its response is discarded."]
+            #[doc = ""]
+            #[doc = "# Errors"]
+            #[doc = ""]
+            #[doc = "Returns the same errors as `send_async()` before the request is sent, and"]
+            #[doc = "`RestApiError::NoBackgroundSender` or `RestApiError::BackgroundQueueFull` if it can't be queued."]
+            #[cfg(not(target_arch = "wasm32"))]
+            pub fn send_background(mut self) -> std::result::Result<(), derive_rest_api::RestApiError>
+            where
+                __A: std::marker::Send + std::marker::Sync + 'static,
+            {
+                let client = self.__async_http_client.take()
+                    .ok_or_else(|| derive_rest_api::RestApiError::missing_field("async_http_client"))?;
+
+                let base_url = self.__base_url.take()
+                    .ok_or_else(|| derive_rest_api::RestApiError::MissingBaseUrl)?;
+
+                let options = self.__options;
+                let mut transport = std::mem::take(&mut self.__transport);
+                transport.endpoint = std::option::Option::Some(std::stringify!(#struct_name));
+                let dynamic_headers = std::mem::take(&mut self.__dynamic_headers).into_header_map()?;
+                let request = self.build()?;
+                let path = request.build_url_with(transport.query_encoder.as_deref(), transport.query_order).map_err(|e| derive_rest_api::RestApiError::UrlBuildError { source: std::boxed::Box::new(e) })?;
+                let mut headers = request.build_headers()?;
+                headers.extend(dynamic_headers);
+                let body = request.build_body()?;
+                #request_checksum
+
+                derive_rest_api::__private::send_background(client, transport, #method_value, base_url, path, headers, body, options)
+            }
+
+            // Sends the request, returning the body before it is decoded
+            async fn __send_bytes_async(self) -> std::result::Result<std::vec::Vec<u8>, derive_rest_api::RestApiError> {
+                self.send_raw_async().await
+                    .map(derive_rest_api::http::Response::into_body)
+                    #response_unwrap
+            }
+
+            #[doc = "Sends a copy of the request asynchronously, leaving the builder ready to send again."]
+            #[doc = ""]
+            #[doc = "Available when every field and the async HTTP client are `Clone`. The copy"]
+            #[doc = "shares the builder's cancellation token."]
+            #[doc = ""]
+            #[doc = "# Errors"]
+            #[doc = ""]
+            #[doc = "Returns the same errors as `send_async()`."]
+            pub async fn send_async_ref(&self) -> std::result::Result<#return_type, derive_rest_api::RestApiError>
+            where
+                Self: std::clone::Clone,
+            {
+                std::clone::Clone::clone(self).send_async().await
             }
+
+            #send_and_wait_async
         }
     }
 }
+
+/// Generate send_and_wait() and send_and_wait_async() for `#[request_builder(poll(...))]`
+fn generate_poll_methods(
+    struct_name: &syn::Ident,
+    struct_attrs: &StructAttributes,
+    return_type: &syn::Type,
+    return_value: &TokenStream,
+) -> (TokenStream, TokenStream) {
+    let Some(poll) = &struct_attrs.poll else {
+        return (quote! {}, quote! {});
+    };
+
+    let method_value = struct_attrs.method.as_deref().unwrap_or("GET");
+    let request_checksum = generate_request_checksum(struct_attrs);
+    let status_field = &poll.status_field;
+    let done_values = &poll.done_values;
+    let interval_ms = poll.interval_ms;
+    let timeout = match poll.timeout_ms {
+        Some(timeout_ms) => quote! { std::option::Option::Some(std::time::Duration::from_millis(#timeout_ms)) },
+        None => quote! { std::option::Option::None },
+    };
+    let check_response = if struct_attrs.verify_checksum {
+        quote! { derive_rest_api::__private::verify_checksum }
+    } else {
+        quote! { std::result::Result::Ok }
+    };
+    let poll_config = quote! {
+        derive_rest_api::__private::PollConfig {
+            status_field: #status_field,
+            done_values: &[#(#done_values),*],
+            interval: std::time::Duration::from_millis(#interval_ms),
+            timeout: #timeout,
+            check_response: #check_response,
+        }
+    };
+    let done_list = done_values.iter().map(|value| format!("`{}`", value)).collect::<Vec<_>>().join(", ");
+    let status_doc = format!("Sends the request, then polls the operation until its `{}` field is one of {}.", status_field, done_list);
+
+    let send_and_wait = quote! {
+        #[doc = #status_doc]
+        #[doc = ""]
+        #[doc = "Polling re-GETs the URL in the response's `Location` header, or the request URL if there is none,"]
+        #[doc = "waiting between polls as configured or as the server asks with `Retry-After`. Polling stops"]
+        #[doc = "early on an error status, whose body is returned like `send()` would."]
+        #[doc = ""]
+        #[doc = "Every request goes through the client's retries, middleware, credentials and metrics, and waits use"]
+        #[doc = "its clock. Polls skip the failover, and a `Location` on another origin is polled without credentials."]
+        #[doc = ""]
+        #[doc = "# Errors"]
+        #[doc = ""]
+        #[doc = "Returns the errors of `send()`, and `RestApiError::PollTimeout` if the operation doesn't finish in time."]
+        pub fn send_and_wait(mut self) -> std::result::Result<#return_type, derive_rest_api::RestApiError> {
+            let client = self.__http_client.take()
+                .ok_or_else(|| derive_rest_api::RestApiError::missing_field("http_client"))?;
+
+            let base_url = self.__base_url.take()
+                .ok_or_else(|| derive_rest_api::RestApiError::MissingBaseUrl)?;
+
+            let options = self.__options;
+            let rate_limit = self.__rate_limit.take();
+            let mut transport = std::mem::take(&mut self.__transport);
+            transport.endpoint = std::option::Option::Some(std::stringify!(#struct_name));
+            let dynamic_headers = std::mem::take(&mut self.__dynamic_headers).into_header_map()?;
+            let request = self.build()?;
+            let path = request.build_url_with(transport.query_encoder.as_deref(), transport.query_order).map_err(|e| derive_rest_api::RestApiError::UrlBuildError { source: std::boxed::Box::new(e) })?;
+            let url = format!("{}{}", base_url, path);
+            let mut headers = request.build_headers()?;
+            headers.extend(dynamic_headers);
+            // Polls reuse the headers, but not those describing the body
+            let poll_headers = headers.clone();
+            let body = request.build_body()?;
+            #request_checksum
+
+            let config = #poll_config;
+            derive_rest_api::__private::wait_for_quota(&rate_limit);
+            let response = transport.send(&client, #method_value, &base_url, &path, headers, body, options)
+                .inspect(|response| derive_rest_api::__private::record_rate_limit(&rate_limit, response))
+                .and_then(|response| derive_rest_api::__private::check_response_size(response, &options))
+                .and_then(config.check_response)
+                .and_then(|response| derive_rest_api::__private::poll(&client, &transport, &url, poll_headers, options, response, &config));
+
+            #return_value
+        }
+    };
+
+    let send_and_wait_async = quote! {
+        #[doc = #status_doc]
+        #[doc = ""]
+        #[doc = "See `send_and_wait()`. The wait between polls doesn't depend on an async runtime."]
+        #[doc = ""]
+        #[doc = "# Errors"]
+        #[doc = ""]
+        #[doc = "Returns the errors of `send_async()`, and `RestApiError::PollTimeout` if the operation doesn't finish in time."]
+        #[cfg(not(target_arch = "wasm32"))]
+        pub async fn send_and_wait_async(mut self) -> std::result::Result<#return_type, derive_rest_api::RestApiError> {
+            let client = self.__async_http_client.take()
+                .ok_or_else(|| derive_rest_api::RestApiError::missing_field("async_http_client"))?;
+
+            let base_url = self.__base_url.take()
+                .ok_or_else(|| derive_rest_api::RestApiError::MissingBaseUrl)?;
+
+            let options = self.__options;
+            let rate_limit = self.__rate_limit.take();
+            let cancellation = self.__cancellation.take();
+            let mut transport = std::mem::take(&mut self.__transport);
+            transport.endpoint = std::option::Option::Some(std::stringify!(#struct_name));
+            let dynamic_headers = std::mem::take(&mut self.__dynamic_headers).into_header_map()?;
+            let request = self.build()?;
+            let path = request.build_url_with(transport.query_encoder.as_deref(), transport.query_order).map_err(|e| derive_rest_api::RestApiError::UrlBuildError { source: std::boxed::Box::new(e) })?;
+            let url = format!("{}{}", base_url, path);
+            let mut headers = request.build_headers()?;
+            headers.extend(dynamic_headers);
+            // Polls reuse the headers, but not those describing the body
+            let poll_headers = headers.clone();
+            let body = request.build_body()?;
+            #request_checksum
+
+            let config = #poll_config;
+            // The cancellation token covers the initial request and every poll
+            let response = derive_rest_api::__private::with_cancellation(cancellation, async {
+                derive_rest_api::__private::wait_for_quota_async(&rate_limit).await;
+                let response = transport.send_async(&client, #method_value, &base_url, &path, headers, body, options).await
+                    .inspect(|response| derive_rest_api::__private::record_rate_limit(&rate_limit, response))
+                    .and_then(|response| derive_rest_api::__private::check_response_size(response, &options))
+                    .and_then(config.check_response)?;
+                derive_rest_api::__private::poll_async(&client, &transport, &url, poll_headers, options, response, &config).await
+            }).await?;
+
+            #return_value
+        }
+    };
+
+    (send_and_wait, send_and_wait_async)
+}
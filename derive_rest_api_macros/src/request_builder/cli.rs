@@ -0,0 +1,169 @@
+//! `clap` integration for `#[request_builder(cli)]`.
+//!
+//! This module generates `clap::Args` and `clap::FromArgMatches` impls for the
+//! request struct, with a `--flag` per builder field, so a request can be
+//! parsed from the command line and then built like any other.
+
+use crate::utils::{doc_text, option_inner_type, vec_inner_type};
+use super::attributes::{DefaultBehavior, StructAttributes, parse_field_attributes};
+use super::builder::is_skipped;
+use proc_macro2::TokenStream;
+use quote::quote;
+
+/// How a field's flag takes its values
+enum FlagKind {
+    /// A `bool` field, set by the flag's presence
+    Switch,
+    /// A `Vec<T>` (or `Option<Vec<T>>`) field, taking the flag once per element
+    Repeated,
+    /// Any other field, taking one value
+    Single,
+}
+
+/// Generate the `clap` impls for `#[request_builder(cli)]`
+pub(super) fn generate_cli_impls(
+    input: &syn::DeriveInput,
+    builder_name: &syn::Ident,
+    fields: &syn::punctuated::Punctuated<syn::Field, syn::token::Comma>,
+    struct_attrs: &StructAttributes,
+) -> syn::Result<TokenStream> {
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &input.generics,
+            "cli is not supported for request structs with generic parameters",
+        ));
+    }
+
+    let struct_name = &input.ident;
+    let clap = quote! { derive_rest_api::__private::clap };
+    let mut args = Vec::new();
+    let mut assignments = Vec::new();
+
+    for field in fields.iter().filter(|field| !is_skipped(field)) {
+        let field_name = field.ident.as_ref().expect("named field");
+        let id = field_name.to_string().trim_start_matches("r#").to_string();
+        let long = id.replace('_', "-");
+        let field_attrs = parse_field_attributes(&field.attrs)?;
+        let has_default = !matches!(field_attrs.default, DefaultBehavior::Required) || struct_attrs.default;
+
+        let optional_type = option_inner_type(&field.ty);
+        let value_type = optional_type.unwrap_or(&field.ty);
+        let (kind, element_type) = match vec_inner_type(value_type) {
+            Some(element) => (FlagKind::Repeated, element),
+            None if optional_type.is_none() && is_bool(value_type) => (FlagKind::Switch, value_type),
+            None => (FlagKind::Single, value_type),
+        };
+
+        let help = match doc_text(&field.attrs) {
+            Some(help) => quote! { .help(#help) },
+            None => quote! {},
+        };
+        let parse = quote! {
+            {
+                use derive_rest_api::__private::{FromStrCliValue, JsonCliValue};
+                (&derive_rest_api::__private::CliValue::<#element_type>::new(__raw)).parse_cli_value(#long)?
+            }
+        };
+
+        let (arg, assignment) = match kind {
+            FlagKind::Switch => {
+                // Without the flag, a field with a default is left to `build()`
+                let assignment = if has_default {
+                    quote! {
+                        if __matches.get_flag(#id) {
+                            __builder.#field_name = std::option::Option::Some(true);
+                        }
+                    }
+                } else {
+                    quote! { __builder.#field_name = std::option::Option::Some(__matches.get_flag(#id)); }
+                };
+                (quote! { .action(#clap::ArgAction::SetTrue) }, assignment)
+            }
+            FlagKind::Repeated => {
+                // A required list may be empty, so only an optional one is left unset
+                let absent = if optional_type.is_none() && !has_default {
+                    quote! { __builder.#field_name = std::option::Option::Some(std::vec::Vec::new()); }
+                } else {
+                    quote! {}
+                };
+                (
+                    quote! { .value_name("VALUE").action(#clap::ArgAction::Append) },
+                    quote! {
+                        match __matches.get_many::<std::string::String>(#id) {
+                            std::option::Option::Some(__values) => {
+                                let mut __parsed = std::vec::Vec::new();
+                                for __raw in __values {
+                                    __parsed.push(#parse);
+                                }
+                                __builder.#field_name = std::option::Option::Some(__parsed);
+                            }
+                            std::option::Option::None => { #absent }
+                        }
+                    },
+                )
+            }
+            FlagKind::Single => {
+                let required = optional_type.is_none() && !has_default;
+                (
+                    quote! { .value_name("VALUE").required(#required) },
+                    quote! {
+                        if let std::option::Option::Some(__raw) = __matches.get_one::<std::string::String>(#id) {
+                            __builder.#field_name = std::option::Option::Some(#parse);
+                        }
+                    },
+                )
+            }
+        };
+
+        args.push(quote! {
+            .arg(#clap::Arg::new(#id).long(#long) #arg #help)
+        });
+        assignments.push(assignment);
+    }
+
+    let about = match doc_text(&input.attrs) {
+        Some(about) => quote! { std::option::Option::Some(#about) },
+        None => quote! { std::option::Option::None },
+    };
+    let mutability = if assignments.is_empty() { quote! {} } else { quote! { mut } };
+
+    Ok(quote! {
+        impl #struct_name {
+            #[doc(hidden)]
+            pub fn __cli_about() -> std::option::Option<&'static str> {
+                #about
+            }
+        }
+
+        impl #clap::FromArgMatches for #struct_name {
+            #[allow(unused_variables)]
+            fn from_arg_matches(__matches: &#clap::ArgMatches) -> std::result::Result<Self, #clap::Error> {
+                let #mutability __builder = #builder_name::new();
+                #(#assignments)*
+                __builder
+                    .build()
+                    .map_err(|error| #clap::Error::raw(#clap::error::ErrorKind::ValueValidation, std::format!("{}\n", error)))
+            }
+
+            fn update_from_arg_matches(&mut self, __matches: &#clap::ArgMatches) -> std::result::Result<(), #clap::Error> {
+                *self = <Self as #clap::FromArgMatches>::from_arg_matches(__matches)?;
+                std::result::Result::Ok(())
+            }
+        }
+
+        impl #clap::Args for #struct_name {
+            fn augment_args(__command: #clap::Command) -> #clap::Command {
+                __command #(#args)*
+            }
+
+            fn augment_args_for_update(__command: #clap::Command) -> #clap::Command {
+                <Self as #clap::Args>::augment_args(__command)
+            }
+        }
+    })
+}
+
+/// Returns true if the type is written `bool`
+fn is_bool(ty: &syn::Type) -> bool {
+    matches!(ty, syn::Type::Path(path) if path.qself.is_none() && path.path.is_ident("bool"))
+}
@@ -1,23 +1,28 @@
 //! HTTP request methods generation.
 //!
 //! This module generates the HTTP-related methods on the request struct,
-//! including build_url, build_body, build_headers, and send_with_client.
-
-use crate::utils::{extract_serde_attributes, option_inner_type, snake_to_title_case};
-use super::attributes::{FieldKind, StructAttributes, parse_field_attributes};
-use super::utils::extract_path_params;
+//! including build_url, build_body, build_headers, send_with_client and
+//! send_with_async_client.
+
+use crate::utils::{
+    extract_serde_attributes, is_unit_type, option_inner_type, percent_encode_query, snake_to_title_case, vec_inner_type,
+};
+use super::attributes::{FieldKind, ResponseFormat, StructAttributes, UnmarkedFields, parse_field_attributes};
+use super::utils::{extract_path_params, is_valid_header_name, GenericsTokens, PathParam};
 use proc_macro2::TokenStream;
 use quote::quote;
-use syn;
+use syn::ext::IdentExt;
 
-/// Generate the impl block with HTTP-related methods (build_url, build_body, build_headers, send_with_client)
+/// Generate the impl block with HTTP-related methods (build_url, build_body, build_headers, send_with_client, send_with_async_client)
 pub(super) fn generate_http_methods_impl(
     struct_name: &syn::Ident,
+    generics: &GenericsTokens,
     fields: &syn::punctuated::Punctuated<syn::Field, syn::token::Comma>,
     struct_attrs: &StructAttributes,
 ) -> TokenStream {
     if let Some(path_template) = &struct_attrs.path {
-        let path_params: Vec<String> = extract_path_params(path_template);
+        let GenericsTokens { params, args, where_clause, .. } = generics;
+        let path_params = extract_path_params(path_template);
 
         let query_fields: Vec<_> = fields.iter().filter(|field| {
             parse_field_attributes(&field.attrs)
@@ -25,9 +30,17 @@ pub(super) fn generate_http_methods_impl(
                 .unwrap_or(false)
         }).collect();
 
+        // GraphQL variables are every field that isn't a path parameter, query parameter or header
         let body_fields: Vec<_> = fields.iter().filter(|field| {
             parse_field_attributes(&field.attrs)
-                .map(|attrs| attrs.kind == FieldKind::Body)
+                .map(|attrs| match attrs.kind {
+                    FieldKind::Body => true,
+                    FieldKind::Unspecified => {
+                        struct_attrs.graphql
+                            && !path_params.iter().any(|param| field.ident.as_ref().is_some_and(|ident| ident.unraw() == param.name))
+                    }
+                    _ => false,
+                })
                 .unwrap_or(false)
         }).collect();
 
@@ -38,19 +51,47 @@ pub(super) fn generate_http_methods_impl(
         }).collect();
 
         let path_replacements = generate_path_replacements(&path_params, fields);
-        let query_serialization = generate_query_serialization(&query_fields, struct_attrs);
-        let build_body_method = generate_build_body_method(&body_fields);
-        let build_headers_method = generate_request_build_headers_method(&header_fields);
+        let query_serialization = generate_query_serialization(struct_name, generics, &query_fields, struct_attrs);
+        let build_body_method = if struct_attrs.graphql {
+            generate_graphql_build_body_method(struct_name, generics, &body_fields, struct_attrs)
+        } else {
+            generate_build_body_method(struct_name, generics, &body_fields)
+        };
+        let accept = match struct_attrs.response_format {
+            ResponseFormat::JsonApi => Some("application/vnd.api+json"),
+            ResponseFormat::Negotiate => Some("application/json, text/*;q=0.9, */*;q=0.8"),
+            ResponseFormat::Json if struct_attrs.response.as_ref().is_some_and(|ty| !is_unit_type(ty)) || struct_attrs.graphql => {
+                Some("application/json")
+            }
+            ResponseFormat::Json => None,
+        };
+        let build_headers_method = generate_request_build_headers_method(
+            &header_fields,
+            &struct_attrs.static_headers,
+            !body_fields.is_empty() || struct_attrs.graphql,
+            accept,
+        );
         let send_with_client_method = generate_send_with_client_method(struct_attrs);
+        let rest_request_impl = generate_rest_request_impl(struct_name, generics, struct_attrs);
 
         quote! {
-            impl #struct_name {
+            impl<#params> #struct_name<#args> #where_clause {
                 #[doc = "Builds the URL path by substituting path parameters and appending query string."]
                 #[doc = ""]
                 #[doc = "# Errors"]
                 #[doc = ""]
                 #[doc = "Returns an error if any required path parameters are not set or if query serialization fails."]
                 pub fn build_url(&self) -> std::result::Result<std::string::String, derive_rest_api::RestApiError> {
+                    self.build_url_with(std::option::Option::None, derive_rest_api::QueryOrder::Declared)
+                }
+
+                #[doc = "Builds the URL path like `build_url()`, encoding the query string with `query_encoder` and"]
+                #[doc = "putting its parameters in `query_order`, unless the struct sets its own."]
+                pub fn build_url_with(
+                    &self,
+                    query_encoder: std::option::Option<&dyn derive_rest_api::QueryEncoder>,
+                    query_order: derive_rest_api::QueryOrder,
+                ) -> std::result::Result<std::string::String, derive_rest_api::RestApiError> {
                     let mut path = std::string::String::from(#path_template);
                     #(#path_replacements)*
                     #query_serialization
@@ -63,6 +104,8 @@ pub(super) fn generate_http_methods_impl(
 
                 #send_with_client_method
             }
+
+            #rest_request_impl
         }
     } else {
         quote! {}
@@ -70,102 +113,318 @@ pub(super) fn generate_http_methods_impl(
 }
 
 /// Generate path parameter replacement code
+///
+/// Wildcard (`{name*}`) values are percent-encoded apart from their slashes.
 fn generate_path_replacements(
-    path_params: &[String],
+    path_params: &[PathParam],
     fields: &syn::punctuated::Punctuated<syn::Field, syn::token::Comma>,
 ) -> Vec<TokenStream> {
-    path_params.iter().map(|param| {
-        let matching_field = fields.iter().find(|field| {
-            field.ident.as_ref().unwrap().to_string() == *param
-        });
-
-        if let Some(field) = matching_field {
-            let field_name = &field.ident;
-            let placeholder = format!("{{{}}}", param);
-            let is_option = option_inner_type(&field.ty).is_some();
-
-            if is_option {
-                quote! {
-                    path = path.replace(#placeholder, &self.#field_name
-                        .as_ref()
-                        .ok_or_else(|| derive_rest_api::RestApiError::missing_path_parameter(#param))?
-                        .to_string());
-                }
-            } else {
-                quote! {
-                    path = path.replace(#placeholder, &self.#field_name.to_string());
-                }
+    // Every placeholder has a field, see `check_path_params()`
+    path_params.iter().filter_map(|param| {
+        let field = fields.iter().find(|field| {
+            field.ident.as_ref().unwrap().unraw() == param.name
+        })?;
+
+        let field_name = &field.ident;
+        let name = &param.name;
+        let placeholder = param.placeholder();
+        let value = if option_inner_type(&field.ty).is_some() {
+            quote! {
+                self.#field_name
+                    .as_ref()
+                    .ok_or_else(|| derive_rest_api::RestApiError::missing_path_parameter(#name))?
             }
         } else {
-            quote! {
-                compile_error!(concat!("Path parameter '", #param, "' does not match any field"));
+            quote! { &self.#field_name }
+        };
+        let to_path_value = quote! {
+            {
+                use derive_rest_api::__private::{DisplayPathValue, SerializePathValue};
+                (&derive_rest_api::__private::PathValue(value)).to_path_value(#name)?
             }
-        }
+        };
+        let replacement = if param.wildcard {
+            quote! { derive_rest_api::__private::encode_wildcard_path(#name, &#to_path_value)? }
+        } else {
+            quote! { derive_rest_api::__private::encode_path_segment(#name, &#to_path_value)? }
+        };
+
+        Some(quote! {
+            let value = #value;
+            path = path.replace(#placeholder, &#replacement);
+        })
     }).collect()
 }
 
-/// Generate query string serialization code
-fn generate_query_serialization(
-    query_fields: &[&syn::Field],
+/// Check that the path's placeholders and the fields marked `path` match up
+///
+/// Unmatched placeholders are reported at the `path = "..."` literal, and
+/// `path` fields without a placeholder at the field.
+pub(super) fn check_path_params(
+    fields: &syn::punctuated::Punctuated<syn::Field, syn::token::Comma>,
     struct_attrs: &StructAttributes,
-) -> TokenStream {
-    if query_fields.is_empty() {
-        return quote! {};
-    }
+) -> syn::Result<()> {
+    let Some(path_template) = &struct_attrs.path else {
+        return Ok(());
+    };
+    let path_params = extract_path_params(path_template);
+    let path_span = struct_attrs.path_span.unwrap_or_else(proc_macro2::Span::call_site);
+    let has_field = |name: &str| {
+        fields.iter().any(|field| field.ident.as_ref().is_some_and(|ident| ident.unraw() == name))
+    };
+
+    let placeholder_errors = path_params.iter().filter(|param| !has_field(&param.name)).map(|param| {
+        syn::Error::new(path_span, format!("path parameter `{}` does not match any field", param.placeholder()))
+    });
+    let field_errors = fields.iter().filter_map(|field| {
+        let ident = field.ident.as_ref()?;
+        let attrs = parse_field_attributes(&field.attrs).ok()?;
+        (attrs.kind == FieldKind::Path && !path_params.iter().any(|param| ident.unraw() == param.name)).then(|| {
+            syn::Error::new(
+                ident.span(),
+                format!("field `{}` is marked `path` but the path has no `{{{}}}` placeholder", ident, ident.unraw()),
+            )
+        })
+    });
+
+    placeholder_errors.chain(field_errors).reduce(|mut error, next| {
+        error.combine(next);
+        error
+    }).map_or(Ok(()), Err)
+}
+
+/// Generate a serialization struct that borrows the given fields from `self`.
+///
+/// Returns the struct declaration and an expression constructing it. The
+/// struct carries the request struct's generics so borrowed field types
+/// (`&'a str`, `Cow<'a, str>`, ...) resolve, and holds references rather than
+/// clones of the field values. Fields marked `flatten` have their keys merged
+/// into the struct's own.
+fn generate_params_struct(
+    params_name: &syn::Ident,
+    struct_name: &syn::Ident,
+    generics: &GenericsTokens,
+    fields: &[&syn::Field],
+) -> (TokenStream, TokenStream) {
+    let GenericsTokens { decl_params, args, where_clause, .. } = generics;
 
-    let query_struct_fields = query_fields.iter().map(|field| {
+    let struct_fields = fields.iter().map(|field| {
         let field_name = &field.ident;
         let field_type = &field.ty;
         let serde_attrs = extract_serde_attributes(&field.attrs);
-
-        let skip_attr = if option_inner_type(field_type).is_some() {
-            quote! { #[serde(skip_serializing_if = "Option::is_none")] }
+        let flatten = if parse_field_attributes(&field.attrs).is_ok_and(|attrs| attrs.flatten) {
+            quote! { #[serde(flatten)] }
         } else {
             quote! {}
         };
 
-        quote! {
-            #(#serde_attrs)*
-            #skip_attr
-            #field_name: #field_type
+        if let Some(inner_type) = option_inner_type(field_type) {
+            quote! {
+                #(#serde_attrs)*
+                #flatten
+                #[serde(skip_serializing_if = "Option::is_none")]
+                #field_name: std::option::Option<&'__r #inner_type>
+            }
+        } else {
+            quote! {
+                #(#serde_attrs)*
+                #flatten
+                #field_name: &'__r #field_type
+            }
         }
     });
 
-    let query_field_assignments = query_fields.iter().map(|field| {
+    let field_assignments = fields.iter().map(|field| {
         let field_name = &field.ident;
-        quote! { #field_name: self.#field_name.clone() }
+        if option_inner_type(&field.ty).is_some() {
+            quote! { #field_name: self.#field_name.as_ref() }
+        } else {
+            quote! { #field_name: &self.#field_name }
+        }
     });
 
+    let declaration = quote! {
+        #[derive(serde::Serialize)]
+        struct #params_name<'__r, #decl_params> #where_clause {
+            #(#struct_fields,)*
+            #[serde(skip)]
+            __marker: std::marker::PhantomData<&'__r #struct_name<#args>>,
+        }
+    };
+
+    let construction = quote! {
+        #params_name {
+            #(#field_assignments,)*
+            __marker: std::marker::PhantomData,
+        }
+    };
+
+    (declaration, construction)
+}
+
+/// Generate query string serialization code
+///
+/// Parameters from `static_query(...)` come first, followed by the query fields.
+/// The fields are encoded with the struct's `query_encoder`, or else the
+/// `query_encoder` passed to `build_url_with()`, or else `serde_qs`. The
+/// parameters are then put in the struct's `query_order`, or else the
+/// `query_order` passed to `build_url_with()`.
+fn generate_query_serialization(
+    struct_name: &syn::Ident,
+    generics: &GenericsTokens,
+    query_fields: &[&syn::Field],
+    struct_attrs: &StructAttributes,
+) -> TokenStream {
+    let static_query = struct_attrs
+        .static_query
+        .iter()
+        .map(|(name, value)| format!("{}={}", percent_encode_query(name), percent_encode_query(value)))
+        .collect::<Vec<_>>()
+        .join("&");
+
+    let order = match struct_attrs.sort_query {
+        Some(true) => quote! { { let _ = query_order; derive_rest_api::QueryOrder::Sorted } },
+        Some(false) => quote! { { let _ = query_order; derive_rest_api::QueryOrder::Declared } },
+        None => quote! { query_order },
+    };
+
+    if query_fields.is_empty() {
+        if static_query.is_empty() {
+            return quote! { let _ = (query_encoder, query_order); };
+        }
+        return quote! {
+            let _ = (query_encoder, query_order);
+            path.push('?');
+            path.push_str(&derive_rest_api::__private::order_query(std::string::String::from(#static_query), #order));
+        };
+    }
+
+    let params_name = quote::format_ident!("QueryParams");
+    let (query_struct, query_construction) =
+        generate_params_struct(&params_name, struct_name, generics, query_fields);
+
     let config_expr = if let Some(config) = &struct_attrs.query_config {
-        let config_tokens: TokenStream = config.parse().unwrap();
-        quote! { #config_tokens }
+        quote! { #config }
     } else {
         quote! { serde_qs::Config::new() }
     };
 
-    quote! {
-        #[derive(serde::Serialize)]
-        struct QueryParams {
-            #(#query_struct_fields),*
+    let encode = if let Some(encoder) = &struct_attrs.query_encoder {
+        quote! {
+            let _ = query_encoder;
+            let encoder = #encoder;
+            let mut query_string = derive_rest_api::__private::encode_query(&query_params, &encoder)?;
         }
+    } else {
+        quote! {
+            let mut query_string = match query_encoder {
+                std::option::Option::Some(encoder) => derive_rest_api::__private::encode_query(&query_params, encoder)?,
+                std::option::Option::None => {
+                    let config = #config_expr;
+                    let mut query_string = config.serialize_string(&derive_rest_api::__private::QueryValue(&query_params))
+                        .map_err(|e| derive_rest_api::RestApiError::QuerySerializationError { source: e })?;
+                    // serde_qs writes an empty map, as from flattened fields that are all `None`, as "="
+                    if query_string == "=" {
+                        query_string.clear();
+                    }
+                    query_string
+                }
+            };
+        }
+    };
 
-        let query_params = QueryParams {
-            #(#query_field_assignments),*
-        };
+    let prepend_static_query = if static_query.is_empty() {
+        quote! {}
+    } else {
+        quote! {
+            let query_string = if query_string.is_empty() {
+                std::string::String::from(#static_query)
+            } else {
+                format!("{}&{}", #static_query, query_string)
+            };
+        }
+    };
+
+    quote! {
+        #query_struct
+
+        let query_params = #query_construction;
 
-        let config = #config_expr;
-        let query_string = config.serialize_string(&query_params)
-            .map_err(|e| derive_rest_api::RestApiError::QuerySerializationError { source: e })?;
+        #encode
+
+        #prepend_static_query
 
         if !query_string.is_empty() {
             path.push('?');
-            path.push_str(&query_string);
+            path.push_str(&derive_rest_api::__private::order_query(query_string, #order));
+        }
+    }
+}
+
+/// Check for fields that aren't sent with the request, as set by `unmarked_fields`
+///
+/// Such fields have no `path`, `query`, `body` or `header` kind and aren't path
+/// placeholders. Warnings are raised through a deprecated constant spanning the
+/// field, as derive macros can't emit warnings directly.
+pub(super) fn check_unmarked_fields(
+    fields: &syn::punctuated::Punctuated<syn::Field, syn::token::Comma>,
+    struct_attrs: &StructAttributes,
+) -> syn::Result<TokenStream> {
+    let Some(path_template) = &struct_attrs.path else {
+        return Ok(quote! {});
+    };
+    // GraphQL requests send unmarked fields as variables
+    if struct_attrs.graphql || struct_attrs.unmarked_fields == UnmarkedFields::Allow {
+        return Ok(quote! {});
+    }
+
+    let path_params = extract_path_params(path_template);
+    let unmarked = fields.iter().filter_map(|field| {
+        let attrs = parse_field_attributes(&field.attrs).unwrap_or_default();
+        let ident = field.ident.as_ref()?;
+        let is_path_param = path_params.iter().any(|param| ident.unraw() == param.name);
+        (attrs.kind == FieldKind::Unspecified && !attrs.skip && !is_path_param).then_some(ident)
+    });
+
+    let mut error: Option<syn::Error> = None;
+    let mut warnings = Vec::new();
+    for ident in unmarked {
+        let message = format!(
+            "field `{}` is not sent with the request; mark it with `path`, `query`, `body` or `header`, \
+             or set `unmarked_fields = \"allow\"` on the struct",
+            ident
+        );
+        if struct_attrs.unmarked_fields == UnmarkedFields::Error {
+            let field_error = syn::Error::new(ident.span(), message);
+            match &mut error {
+                Some(error) => error.combine(field_error),
+                None => error = Some(field_error),
+            }
+        } else {
+            let name = ident.unraw().to_string().to_uppercase();
+            let marker = quote::format_ident!("__UNMARKED_FIELD_{}", name, span = ident.span());
+            warnings.push(quote::quote_spanned! {ident.span()=>
+                const _: () = {
+                    #[deprecated(note = #message)]
+                    const #marker: () = ();
+                    #marker
+                };
+            });
         }
     }
+
+    match error {
+        Some(error) => Err(error),
+        None => Ok(quote! { #(#warnings)* }),
+    }
 }
 
 /// Generate the build_body() method
-fn generate_build_body_method(body_fields: &[&syn::Field]) -> TokenStream {
+fn generate_build_body_method(
+    struct_name: &syn::Ident,
+    generics: &GenericsTokens,
+    body_fields: &[&syn::Field],
+) -> TokenStream {
     if body_fields.is_empty() {
         return quote! {
             #[doc = "Builds the request body (always returns None as there are no body fields)."]
@@ -175,46 +434,61 @@ fn generate_build_body_method(body_fields: &[&syn::Field]) -> TokenStream {
         };
     }
 
-    let body_struct_fields = body_fields.iter().map(|field| {
-        let field_name = &field.ident;
-        let field_type = &field.ty;
-        let serde_attrs = extract_serde_attributes(&field.attrs);
+    let params_name = quote::format_ident!("BodyParams");
+    let (body_struct, body_construction) =
+        generate_params_struct(&params_name, struct_name, generics, body_fields);
 
-        let skip_attr = if option_inner_type(field_type).is_some() {
-            quote! { #[serde(skip_serializing_if = "Option::is_none")] }
-        } else {
-            quote! {}
-        };
+    quote! {
+        #[doc = "Builds the request body as JSON."]
+        #[doc = ""]
+        #[doc = "# Errors"]
+        #[doc = ""]
+        #[doc = "Returns an error if JSON serialization fails."]
+        pub fn build_body(&self) -> std::result::Result<std::option::Option<std::vec::Vec<u8>>, derive_rest_api::RestApiError> {
+            #body_struct
 
-        quote! {
-            #(#serde_attrs)*
-            #skip_attr
-            #field_name: #field_type
+            let body_params = #body_construction;
+
+            let json = serde_json::to_vec(&body_params)
+                .map_err(|e| derive_rest_api::RestApiError::BodySerializationError { source: e })?;
+
+            std::result::Result::Ok(std::option::Option::Some(json))
         }
-    });
+    }
+}
 
-    let body_field_assignments = body_fields.iter().map(|field| {
-        let field_name = &field.ident;
-        quote! { #field_name: self.#field_name.clone() }
-    });
+/// Generate the build_body() method for a GraphQL request, sending the fields as its variables
+fn generate_graphql_build_body_method(
+    struct_name: &syn::Ident,
+    generics: &GenericsTokens,
+    variable_fields: &[&syn::Field],
+    struct_attrs: &StructAttributes,
+) -> TokenStream {
+    let params_name = quote::format_ident!("GraphQlVariables");
+    let (variables_struct, variables_construction) =
+        generate_params_struct(&params_name, struct_name, generics, variable_fields);
+    let query = struct_attrs.graphql_query.as_deref().unwrap_or_default();
+    let operation_name = match &struct_attrs.operation_name {
+        Some(name) => quote! { std::option::Option::Some(#name) },
+        None => quote! { std::option::Option::None },
+    };
 
     quote! {
-        #[doc = "Builds the request body as JSON."]
+        #[doc = "Builds the GraphQL request body (`query`, `variables` and `operationName`) as JSON."]
         #[doc = ""]
         #[doc = "# Errors"]
         #[doc = ""]
         #[doc = "Returns an error if JSON serialization fails."]
         pub fn build_body(&self) -> std::result::Result<std::option::Option<std::vec::Vec<u8>>, derive_rest_api::RestApiError> {
-            #[derive(serde::Serialize)]
-            struct BodyParams {
-                #(#body_struct_fields),*
-            }
+            #variables_struct
 
-            let body_params = BodyParams {
-                #(#body_field_assignments),*
+            let request = derive_rest_api::GraphQlRequest {
+                query: #query,
+                variables: #variables_construction,
+                operation_name: #operation_name,
             };
 
-            let json = serde_json::to_vec(&body_params)
+            let json = serde_json::to_vec(&request)
                 .map_err(|e| derive_rest_api::RestApiError::BodySerializationError { source: e })?;
 
             std::result::Result::Ok(std::option::Option::Some(json))
@@ -223,7 +497,24 @@ fn generate_build_body_method(body_fields: &[&syn::Field]) -> TokenStream {
 }
 
 /// Generate the build_headers() method for the request struct (no dynamic headers)
-fn generate_request_build_headers_method(header_fields: &[&syn::Field]) -> TokenStream {
+///
+/// `Content-Type` is set when the request has a JSON body and `Accept` to the
+/// expected response media type, unless a header field already provides them.
+fn generate_request_build_headers_method(
+    header_fields: &[&syn::Field],
+    static_headers: &[(String, String)],
+    has_body: bool,
+    accept: Option<&str>,
+) -> TokenStream {
+    let static_insertions = static_headers.iter().map(|(name, value)| {
+        let lowercase_name = name.to_ascii_lowercase();
+        quote! {
+            headers.insert(
+                derive_rest_api::http::HeaderName::from_static(#lowercase_name),
+                derive_rest_api::http::HeaderValue::from_static(#value),
+            );
+        }
+    });
     let header_insertions = header_fields.iter().map(|field| {
         let field_name = &field.ident;
         let field_type = &field.ty;
@@ -233,33 +524,254 @@ fn generate_request_build_headers_method(header_fields: &[&syn::Field]) -> Token
         let header_name = field_attrs.rename
             .unwrap_or_else(|| snake_to_title_case(&field_name_str));
 
+        // Header names are checked here so that `HeaderName::from_static` can't panic
+        if !is_valid_header_name(&header_name) {
+            let message = format!("'{}' is not a valid HTTP header name", header_name);
+            return syn::Error::new_spanned(field, message).to_compile_error();
+        }
+        let lowercase_name = header_name.to_ascii_lowercase();
+
+        // Vec fields send one header line per element
+        let values_type = option_inner_type(field_type).unwrap_or(field_type);
+        if vec_inner_type(values_type).is_some() {
+            let values = if option_inner_type(field_type).is_some() {
+                quote! { self.#field_name.iter().flatten() }
+            } else {
+                quote! { self.#field_name.iter() }
+            };
+            return quote! {
+                for value in #values {
+                    headers.append(
+                        derive_rest_api::http::HeaderName::from_static(#lowercase_name),
+                        derive_rest_api::__private::header_value(#header_name, value)?,
+                    );
+                }
+            };
+        }
+
         if option_inner_type(field_type).is_some() {
             quote! {
                 if let std::option::Option::Some(ref value) = self.#field_name {
-                    headers.insert(#header_name.to_string(), value.to_string());
+                    headers.append(
+                        derive_rest_api::http::HeaderName::from_static(#lowercase_name),
+                        derive_rest_api::__private::header_value(#header_name, value)?,
+                    );
                 }
             }
         } else {
             quote! {
-                headers.insert(#header_name.to_string(), self.#field_name.to_string());
+                headers.append(
+                    derive_rest_api::http::HeaderName::from_static(#lowercase_name),
+                    derive_rest_api::__private::header_value(#header_name, &self.#field_name)?,
+                );
             }
         }
     });
 
+    let default_header = |name: TokenStream, media_type: &str| quote! {
+        if !headers.contains_key(derive_rest_api::http::header::#name) {
+            headers.insert(
+                derive_rest_api::http::header::#name,
+                derive_rest_api::http::HeaderValue::from_static(#media_type),
+            );
+        }
+    };
+    let content_type = if has_body { default_header(quote! { CONTENT_TYPE }, "application/json") } else { quote! {} };
+    let accept = match accept {
+        Some(media_type) => default_header(quote! { ACCEPT }, media_type),
+        None => quote! {},
+    };
+
     quote! {
-        #[doc = "Builds HTTP headers from header-annotated fields."]
-        pub fn build_headers(&self) -> std::collections::HashMap<std::string::String, std::string::String> {
-            let mut headers = std::collections::HashMap::new();
+        #[doc = "Builds HTTP headers from the `static_header(...)` attribute and header-annotated fields."]
+        #[doc = ""]
+        #[doc = "`Content-Type` and `Accept` default to `application/json` (or the JSON:API media type) when the request has a body or a response type."]
+        #[doc = ""]
+        #[doc = "# Errors"]
+        #[doc = ""]
+        #[doc = "Returns an error if a field's value is not a valid header value."]
+        pub fn build_headers(&self) -> std::result::Result<derive_rest_api::http::HeaderMap, derive_rest_api::RestApiError> {
+            #[allow(unused_mut)]
+            let mut headers = derive_rest_api::http::HeaderMap::new();
+            #(#static_insertions)*
             #(#header_insertions)*
-            headers
+            #content_type
+            #accept
+            std::result::Result::Ok(headers)
+        }
+    }
+}
+
+
+/// Generate the code adding a checksum header for the request body, if `checksum` is set
+///
+/// Expects `headers` and `body` to be in scope.
+pub(super) fn generate_request_checksum(struct_attrs: &StructAttributes) -> TokenStream {
+    match &struct_attrs.checksum {
+        Some(variant) => quote! {
+            if let std::option::Option::Some(body) = &body {
+                derive_rest_api::ChecksumAlgorithm::#variant.insert_header(&mut headers, body);
+            }
+        },
+        None => quote! {},
+    }
+}
+
+/// Generate the response check verifying its checksum headers, if `verify_checksum` is set
+///
+/// The tokens are a method call chained onto the client's `Result<http::Response<Vec<u8>>, _>`.
+pub(super) fn generate_response_checksum(struct_attrs: &StructAttributes) -> TokenStream {
+    if struct_attrs.verify_checksum {
+        quote! { .and_then(derive_rest_api::__private::verify_checksum) }
+    } else {
+        quote! {}
+    }
+}
+
+/// Generate the unwrapping of a GraphQL response's `data`, if `graphql` is set
+///
+/// The tokens are a method call chained onto a `Result<Vec<u8>, RestApiError>` of the response body.
+pub(super) fn generate_response_unwrap(struct_attrs: &StructAttributes) -> TokenStream {
+    if struct_attrs.graphql {
+        quote! { .and_then(derive_rest_api::__private::graphql_data) }
+    } else {
+        quote! {}
+    }
+}
+
+/// Generate the extraction of what `generate_response_decoding()` decodes
+///
+/// The tokens are method calls chained onto a `Result<http::Response<Vec<u8>>, RestApiError>`.
+/// Negotiated responses keep the whole response, as they depend on its `Content-Type`.
+pub(super) fn generate_response_body(struct_attrs: &StructAttributes) -> TokenStream {
+    if struct_attrs.response_format == ResponseFormat::Negotiate {
+        return quote! {};
+    }
+    let response_unwrap = generate_response_unwrap(struct_attrs);
+    quote! {
+        .map(derive_rest_api::http::Response::into_body)
+        #response_unwrap
+    }
+}
+
+/// Generate the type `send()` returns and the code decoding the response into it
+///
+/// The code expects `response`, a `Result<Vec<u8>, RestApiError>` of the response body, in scope,
+/// or of the whole response for `response_format = "negotiate"` (see `generate_response_body()`).
+/// With `response = ()` the body is discarded without being decoded.
+pub(super) fn generate_response_decoding(struct_attrs: &StructAttributes) -> (syn::Type, TokenStream) {
+    if struct_attrs.response.as_ref().is_some_and(is_unit_type) {
+        return (syn::Type::Verbatim(quote! { () }), quote! { response.map(|_| ()) });
+    }
+
+    let return_type = match (&struct_attrs.response, struct_attrs.response_format) {
+        (Some(response), ResponseFormat::JsonApi) => {
+            syn::Type::Verbatim(quote! { derive_rest_api::JsonApiDocument<#response> })
+        }
+        (Some(response), ResponseFormat::Negotiate) => {
+            syn::Type::Verbatim(quote! { derive_rest_api::Negotiated<#response> })
+        }
+        (Some(response), ResponseFormat::Json) if struct_attrs.capture_unknown_fields => {
+            syn::Type::Verbatim(quote! { derive_rest_api::WithUnknownFields<#response> })
+        }
+        (Some(response), ResponseFormat::Json) => response.clone(),
+        (None, _) => syn::Type::Verbatim(quote! {Vec<u8>}),
+    };
+
+    let return_value = match (&struct_attrs.response, struct_attrs.response_format) {
+        (Some(_), ResponseFormat::JsonApi) => quote! {
+            let bytes = response?;
+            derive_rest_api::JsonApiDocument::from_slice(&bytes)
+        },
+        (Some(_), ResponseFormat::Negotiate) => quote! {
+            response.map(derive_rest_api::Negotiated::from_response)
+        },
+        (Some(_), ResponseFormat::Json) if struct_attrs.capture_unknown_fields => quote! {
+            let bytes = response?;
+            derive_rest_api::WithUnknownFields::from_slice(&bytes)
+        },
+        (Some(_), ResponseFormat::Json) => {
+            let decode_json = generate_json_decoder(struct_attrs);
+            quote! {
+                let bytes = response?;
+                #decode_json(&bytes)
+            }
         }
+        _ => quote! { response },
+    };
+
+    (return_type, return_value)
+}
+
+/// Returns the function that decodes a JSON response body, attaching context
+/// to errors with `#[request_builder(lenient_response)]` and rejecting unknown
+/// fields with `#[request_builder(strict_response)]`
+pub(super) fn generate_json_decoder(struct_attrs: &StructAttributes) -> TokenStream {
+    if struct_attrs.lenient_response {
+        quote! { derive_rest_api::__private::decode_json_lenient }
+    } else if struct_attrs.strict_response {
+        quote! { derive_rest_api::__private::decode_json_strict }
+    } else {
+        quote! { derive_rest_api::__private::decode_json }
     }
 }
 
+/// Generate the `RestRequest` impl, through which requests are sent in a `Batch`
+fn generate_rest_request_impl(
+    struct_name: &syn::Ident,
+    generics: &GenericsTokens,
+    struct_attrs: &StructAttributes,
+) -> TokenStream {
+    let GenericsTokens { params, args, where_clause, .. } = generics;
+    let method_value = struct_attrs.method.as_deref().unwrap_or("GET");
+    let response_checksum = generate_response_checksum(struct_attrs);
+    let response_body = generate_response_body(struct_attrs);
+    let (return_type, return_value) = generate_response_decoding(struct_attrs);
+
+    quote! {
+        impl<#params> derive_rest_api::RestRequest for #struct_name<#args> #where_clause {
+            type Response = #return_type;
+
+            fn method(&self) -> &'static str {
+                #method_value
+            }
+
+            fn build_url(&self) -> std::result::Result<std::string::String, derive_rest_api::RestApiError> {
+                Self::build_url(self)
+            }
+
+            fn build_headers(&self) -> std::result::Result<derive_rest_api::http::HeaderMap, derive_rest_api::RestApiError> {
+                Self::build_headers(self)
+            }
+
+            fn build_body(&self) -> std::result::Result<std::option::Option<std::vec::Vec<u8>>, derive_rest_api::RestApiError> {
+                Self::build_body(self)
+            }
+
+            fn parse_response(
+                response: derive_rest_api::http::Response<std::vec::Vec<u8>>,
+            ) -> std::result::Result<Self::Response, derive_rest_api::RestApiError> {
+                let response = std::result::Result::<_, derive_rest_api::RestApiError>::Ok(response)
+                    #response_checksum
+                    #response_body;
+
+                #return_value
+            }
+        }
+    }
+}
 
 /// Generate the send_with_client() method
 fn generate_send_with_client_method(struct_attrs: &StructAttributes) -> TokenStream {
-    let method_value = struct_attrs.method.as_ref().map(|s| s.as_str()).unwrap_or("GET");
+    let method_value = struct_attrs.method.as_deref().unwrap_or("GET");
+    let request_checksum = generate_request_checksum(struct_attrs);
+    let response_checksum = generate_response_checksum(struct_attrs);
+    let response_unwrap = generate_response_unwrap(struct_attrs);
+    let headers_mutability = if struct_attrs.checksum.is_some() {
+        quote! { mut }
+    } else {
+        quote! {}
+    };
 
     quote! {
         #[doc = "Sends the HTTP request using the provided client."]
@@ -279,11 +791,44 @@ fn generate_send_with_client_method(struct_attrs: &StructAttributes) -> TokenStr
         ) -> std::result::Result<std::vec::Vec<u8>, derive_rest_api::RestApiError> {
             let path = self.build_url().map_err(|e| derive_rest_api::RestApiError::UrlBuildError { source: std::boxed::Box::new(e) })?;
             let url = format!("{}{}", base_url, path);
-            let headers = self.build_headers();
+            let #headers_mutability headers = self.build_headers()?;
+            let body = self.build_body()?;
+            #request_checksum
+
+            client.send(#method_value, &url, headers, body, derive_rest_api::RequestOptions::default())
+                .map_err(|e| derive_rest_api::RestApiError::http_error(e))
+                #response_checksum
+                .map(derive_rest_api::http::Response::into_body)
+                #response_unwrap
+        }
+
+        #[doc = "Sends the HTTP request using the provided async client."]
+        #[doc = ""]
+        #[doc = "# Arguments"]
+        #[doc = ""]
+        #[doc = "- `client`: An implementation of the `AsyncHttpClient` trait"]
+        #[doc = "- `base_url`: The base URL to prepend to the request path"]
+        #[doc = ""]
+        #[doc = "# Errors"]
+        #[doc = ""]
+        #[doc = "Returns an error if URL building, body serialization, or the HTTP request fails."]
+        pub async fn send_with_async_client<A: derive_rest_api::AsyncHttpClient>(
+            &self,
+            client: &A,
+            base_url: &str,
+        ) -> std::result::Result<std::vec::Vec<u8>, derive_rest_api::RestApiError> {
+            let path = self.build_url().map_err(|e| derive_rest_api::RestApiError::UrlBuildError { source: std::boxed::Box::new(e) })?;
+            let url = format!("{}{}", base_url, path);
+            let #headers_mutability headers = self.build_headers()?;
             let body = self.build_body()?;
+            #request_checksum
 
-            client.send(#method_value, &url, headers, body, std::option::Option::None)
+            client.send_async(#method_value, &url, headers, body, derive_rest_api::RequestOptions::default())
+                .await
                 .map_err(|e| derive_rest_api::RestApiError::http_error(e))
+                #response_checksum
+                .map(derive_rest_api::http::Response::into_body)
+                #response_unwrap
         }
     }
 }
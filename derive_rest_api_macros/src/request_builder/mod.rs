@@ -9,18 +9,21 @@
 
 mod attributes;
 mod builder;
+mod cli;
 mod http;
 mod utils;
 
 use crate::utils::extract_doc_attributes;
 use attributes::parse_struct_attributes;
 use builder::{
-    generate_build_fields, generate_builder_fields, generate_builder_send_methods,
-    generate_field_processing, generate_setter_methods,
+    generate_build_fields, generate_arbitrary_impls, generate_builder_clone_impl, generate_builder_fields, generate_builder_send_methods,
+    generate_computed_fields, generate_field_processing, generate_sample_method, generate_send_sync_assertions, generate_setter_methods,
+    generate_state_methods, generate_with_request_method, is_skipped,
 };
-use http::generate_http_methods_impl;
+use cli::generate_cli_impls;
+use http::{check_path_params, check_unmarked_fields, generate_http_methods_impl};
 use quote::quote;
-use syn;
+use utils::GenericsTokens;
 
 /// Main entry point for generating the RequestBuilder derive macro code.
 ///
@@ -37,6 +40,10 @@ pub(crate) fn generate_request_builder(input: syn::DeriveInput) -> syn::Result<p
     // Extract doc comments and other attributes to copy to the builder
     let struct_doc_attrs = extract_doc_attributes(&input.attrs);
 
+    // Generic parameters (e.g. lifetimes of borrowed fields) carried over to the builder
+    let generics = GenericsTokens::new(&input.generics);
+    let GenericsTokens { params, decl_params, args, where_clause } = &generics;
+
     // Extract fields from the struct
     // For unit structs, we create an empty collection
     let empty_fields = syn::punctuated::Punctuated::new();
@@ -60,43 +67,120 @@ pub(crate) fn generate_request_builder(input: syn::DeriveInput) -> syn::Result<p
         }
     };
 
+    // Report path placeholders and `path` fields that don't match up
+    check_path_params(fields, &struct_attrs)?;
+
     // Generate builder struct fields
     let builder_fields = generate_builder_fields(fields);
 
-    // Generate field names for constructor (collect to allow multiple uses)
-    let field_names: Vec<_> = fields.iter().map(|field| &field.ident).collect();
+    // Generate builder field names for constructor (collect to allow multiple uses)
+    let field_names: Vec<_> = fields
+        .iter()
+        .filter(|field| !is_skipped(field))
+        .map(|field| &field.ident)
+        .collect();
+
+    // Builders whose fields are all Clone can be cloned and sent repeatedly
+    let clone_impl = generate_builder_clone_impl(&builder_name, &generics, fields);
+
+    // Builders can be moved into spawned tasks whenever their fields and clients can
+    let send_sync_assertions = generate_send_sync_assertions(&builder_name, &generics, fields);
+
+    // Command-line parsing with a flag per field
+    let cli_impls = if struct_attrs.cli {
+        generate_cli_impls(&input, &builder_name, fields, &struct_attrs)?
+    } else {
+        quote! {}
+    };
+
+    // Example requests built from each field's `example` value or default
+    let sample_method = generate_sample_method(struct_name, &builder_name, &generics, fields, &struct_attrs);
+
+    // Random valid requests for fuzzing and property tests
+    let arbitrary_impls = if struct_attrs.arbitrary {
+        generate_arbitrary_impls(struct_name, &builder_name, &generics, fields)
+    } else {
+        quote! {}
+    };
 
     // Generate setter methods for each field
     let setter_methods = generate_setter_methods(fields, &struct_attrs);
+    let with_request_method = generate_with_request_method(struct_name, &generics, fields);
+    let state_methods = generate_state_methods(fields, &struct_attrs);
 
-    // Generate field extraction and validation for build() method
-    let field_processing = generate_field_processing(fields, &struct_attrs);
+    // Generate field extraction and validation for try_build() method
+    let (field_processing, processed_fields) = generate_field_processing(fields, &struct_attrs);
+
+    // Collect every error before giving up, then unwrap the checked values
+    let error_collection = if processed_fields.is_empty() {
+        quote! {}
+    } else {
+        quote! {
+            let mut __errors = std::vec::Vec::new();
+            #(#field_processing)*
+            let (#(std::option::Option::Some(#processed_fields),)*) = (#(#processed_fields,)*) else {
+                return std::result::Result::Err(derive_rest_api::__private::build_errors(__errors));
+            };
+        }
+    };
+
+    // Generate evaluation of computed fields, before and after construction
+    let (computed_fields, computed_after_construction) = generate_computed_fields(fields);
 
     // Generate final field assignments using temporary variables
     let build_fields = generate_build_fields(fields);
 
     // Generate HTTP methods impl block (build_url, build_body, build_headers, send_with_client)
-    let http_methods_impl = generate_http_methods_impl(struct_name, fields, &struct_attrs);
+    let http_methods_impl = generate_http_methods_impl(struct_name, &generics, fields, &struct_attrs);
+
+    // Warn about (or reject) fields that would silently be left out of the request
+    let unmarked_field_warnings = check_unmarked_fields(fields, &struct_attrs)?;
 
     // Generate send() and send_async() methods if path is present
     let send_methods = if struct_attrs.path.is_some() {
-        generate_builder_send_methods(struct_name, &struct_attrs)
+        generate_builder_send_methods(struct_name, &generics, &struct_attrs)
     } else {
         quote! {}
     };
 
     // Generate struct construction code based on whether it's a unit struct
-    let struct_construction = if is_unit_struct {
+    let struct_value = if is_unit_struct {
         // Unit struct: construct without braces
-        quote! {
-            std::result::Result::Ok(#struct_name)
-        }
+        quote! { #struct_name }
     } else {
         // Named fields struct: construct with braces (even if empty)
         quote! {
-            std::result::Result::Ok(#struct_name {
+            #struct_name {
                 #(#build_fields),*
-            })
+            }
+        }
+    };
+
+    // Run garde validation on the constructed struct if requested
+    let garde_validation = if struct_attrs.garde {
+        quote! {
+            derive_rest_api::__private::garde_validate(&__request)?;
+        }
+    } else {
+        quote! {}
+    };
+
+    let struct_construction = if computed_after_construction.is_empty() && !struct_attrs.garde {
+        quote! {
+            std::result::Result::Ok(#struct_value)
+        }
+    } else {
+        // Computed fields that need the constructed struct are filled in afterwards
+        let mutability = if computed_after_construction.is_empty() {
+            quote! {}
+        } else {
+            quote! { mut }
+        };
+        quote! {
+            let #mutability __request = #struct_value;
+            #(#computed_after_construction)*
+            #garde_validation
+            std::result::Result::Ok(__request)
         }
     };
 
@@ -105,16 +189,20 @@ pub(crate) fn generate_request_builder(input: syn::DeriveInput) -> syn::Result<p
         #(#struct_doc_attrs)*
         #[doc = ""]
         #[doc = concat!("Builder for [`", stringify!(#struct_name), "`].")]
-        pub struct #builder_name<__C = (), __A = ()> {
+        pub struct #builder_name<#decl_params __C = (), __A = ()> #where_clause {
             #(#builder_fields,)*
             __http_client: std::option::Option<__C>,
             __async_http_client: std::option::Option<__A>,
             __base_url: std::option::Option<std::string::String>,
-            __dynamic_headers: std::collections::HashMap<std::string::String, std::string::String>,
-            __timeout: std::option::Option<std::time::Duration>,
+            __dynamic_headers: derive_rest_api::__private::DynamicHeaders,
+            __options: derive_rest_api::RequestOptions,
+            __cancellation: std::option::Option<derive_rest_api::CancellationToken>,
+            __rate_limit: std::option::Option<derive_rest_api::RateLimitState>,
+            __unconfigured: std::option::Option<(derive_rest_api::__private::DynamicHeaders, derive_rest_api::RequestOptions)>,
+            __transport: derive_rest_api::__private::Transport,
         }
 
-        impl #builder_name<(), ()> {
+        impl<#params> #builder_name<#args (), ()> #where_clause {
             #[doc = concat!("Creates a new [`", stringify!(#builder_name), "`] with all fields set to `None`.")]
             pub fn new() -> Self {
                 Self {
@@ -122,34 +210,46 @@ pub(crate) fn generate_request_builder(input: syn::DeriveInput) -> syn::Result<p
                     __http_client: std::option::Option::None,
                     __async_http_client: std::option::Option::None,
                     __base_url: std::option::Option::None,
-                    __dynamic_headers: std::collections::HashMap::new(),
-                    __timeout: std::option::Option::None,
+                    __dynamic_headers: std::default::Default::default(),
+                    __options: std::default::Default::default(),
+                    __cancellation: std::option::Option::None,
+                    __rate_limit: std::option::Option::None,
+                    __unconfigured: std::option::Option::None,
+                    __transport: std::default::Default::default(),
                 }
             }
         }
 
-        impl<__C, __A> #builder_name<__C, __A> {
+        impl<#params __C, __A> #builder_name<#args __C, __A> #where_clause {
             #[doc = "Sets the HTTP client to use for blocking requests."]
-            pub fn http_client<C2: derive_rest_api::HttpClient>(self, client: C2) -> #builder_name<C2, __A> {
+            pub fn http_client<C2: derive_rest_api::HttpClient>(self, client: C2) -> #builder_name<#args C2, __A> {
                 #builder_name {
                     #(#field_names: self.#field_names,)*
                     __http_client: std::option::Option::Some(client),
                     __async_http_client: self.__async_http_client,
                     __base_url: self.__base_url,
                     __dynamic_headers: self.__dynamic_headers,
-                    __timeout: self.__timeout,
+                    __options: self.__options,
+                    __cancellation: self.__cancellation,
+                    __rate_limit: self.__rate_limit,
+                    __unconfigured: self.__unconfigured,
+                    __transport: self.__transport,
                 }
             }
 
             #[doc = "Sets the async HTTP client to use for async requests."]
-            pub fn async_http_client<A2: derive_rest_api::AsyncHttpClient>(self, client: A2) -> #builder_name<__C, A2> {
+            pub fn async_http_client<A2: derive_rest_api::AsyncHttpClient>(self, client: A2) -> #builder_name<#args __C, A2> {
                 #builder_name {
                     #(#field_names: self.#field_names,)*
                     __http_client: self.__http_client,
                     __async_http_client: std::option::Option::Some(client),
                     __base_url: self.__base_url,
                     __dynamic_headers: self.__dynamic_headers,
-                    __timeout: self.__timeout,
+                    __options: self.__options,
+                    __cancellation: self.__cancellation,
+                    __rate_limit: self.__rate_limit,
+                    __unconfigured: self.__unconfigured,
+                    __transport: self.__transport,
                 }
             }
 
@@ -158,33 +258,239 @@ pub(crate) fn generate_request_builder(input: syn::DeriveInput) -> syn::Result<p
                 self.__base_url = std::option::Option::Some(base_url.into());
                 self
             }
+
+            #[doc = "Sets a token that cancels `send_async()` when it is cancelled."]
+            #[doc = ""]
+            #[doc = "A cancelled request returns `RestApiError::Cancelled` and its in-flight HTTP request is dropped."]
+            pub fn cancellation_token(mut self, token: derive_rest_api::CancellationToken) -> Self {
+                self.__cancellation = std::option::Option::Some(token);
+                self
+            }
+
+            #[doc = "Records the response's rate-limit headers in `state`."]
+            #[doc = ""]
+            #[doc = "Clients generated by `#[derive(ApiClient)]` set this, so `rate_limit_state()` reflects every request they send."]
+            pub fn track_rate_limit(mut self, state: derive_rest_api::RateLimitState) -> Self {
+                self.__rate_limit = std::option::Option::Some(state);
+                self
+            }
+
+            #[doc = "Sends the request to the failover's base URLs instead of `base_url`, moving on to the next one"]
+            #[doc = "on a client error or `5xx` status."]
+            #[doc = ""]
+            #[doc = "`send_and_wait()` still uses `base_url`."]
+            pub fn failover(mut self, failover: derive_rest_api::Failover) -> Self {
+                self.__transport.failover = std::option::Option::Some(failover);
+                self
+            }
+
+            #[doc = "Retries the request according to `policy`."]
+            #[doc = ""]
+            #[doc = "`send_and_wait()` doesn't retry."]
+            pub fn retry_policy(mut self, policy: derive_rest_api::RetryPolicy) -> Self {
+                self.__transport.retry = std::option::Option::Some(policy);
+                self
+            }
+
+            #[doc = "Counts the request's retries against `budget`, shared with other requests, and stops retrying once it's spent."]
+            pub fn retry_budget(mut self, budget: derive_rest_api::RetryBudget) -> Self {
+                self.__transport.retry_budget = std::option::Option::Some(budget);
+                self
+            }
+
+            #[doc = "Waits out retry backoff with `clock` instead of the system clock."]
+            pub fn clock(mut self, clock: impl derive_rest_api::Clock + 'static) -> Self {
+                self.__transport.clock = std::option::Option::Some(std::sync::Arc::new(clock));
+                self
+            }
+
+            #[doc = "Runs `middleware` around the request, inside any middleware already added without a"]
+            #[doc = "higher priority."]
+            pub fn middleware(mut self, middleware: impl derive_rest_api::Middleware) -> Self {
+                self.__transport.middleware.push(middleware);
+                self
+            }
+
+            #[doc = "Runs `middleware` around the request, inside middleware with a lower `priority` and outside"]
+            #[doc = "middleware with a higher one, including the client's."]
+            pub fn middleware_with_priority(mut self, priority: i32, middleware: impl derive_rest_api::Middleware) -> Self {
+                self.__transport.middleware.insert(priority, middleware);
+                self
+            }
+
+            #[doc = "Returns the middleware that will run around the request, in the order their `on_request`"]
+            #[doc = "hooks run."]
+            pub fn middleware_chain(&self) -> std::vec::Vec<derive_rest_api::MiddlewareLayer> {
+                self.__transport.middleware.chain()
+            }
+
+            #[doc = "Replaces the middleware run around the request with `stack`."]
+            pub fn middleware_stack(mut self, stack: derive_rest_api::MiddlewareStack) -> Self {
+                self.__transport.middleware = stack;
+                self
+            }
+
+            #[doc = "Reports the request's latency and status to `hook`, labelled with its endpoint and method."]
+            pub fn metrics(mut self, hook: impl derive_rest_api::MetricsHook) -> Self {
+                self.__transport.metrics = std::option::Option::Some(std::sync::Arc::new(hook));
+                self
+            }
+
+            #[doc = "Tells `subscriber` about each stage of the request: built, each attempt sent, each retry, and"]
+            #[doc = "its completion or failure. Subscribers already added are kept."]
+            pub fn on_event(mut self, subscriber: impl derive_rest_api::EventSubscriber) -> Self {
+                self.__transport.events.push(std::sync::Arc::new(subscriber));
+                self
+            }
+
+            #[doc = "Records the request with `sink` once it has completed or failed, unless it's a `GET`."]
+            pub fn audit(mut self, sink: impl derive_rest_api::AuditSink) -> Self {
+                self.__transport.audit = std::option::Option::Some(std::sync::Arc::new(sink));
+                self
+            }
+
+            #[doc = "Sets who the request is made on behalf of, in audit records, replacing the client's actor."]
+            pub fn audit_actor(mut self, actor: impl std::convert::Into<std::string::String>) -> Self {
+                self.__transport.actor = std::option::Option::Some(actor.into());
+                self
+            }
+
+            #[doc = "Asks `provider` for the credentials to send when the request is sent, and again for every retry."]
+            pub fn credentials(mut self, provider: impl derive_rest_api::CredentialsProvider) -> Self {
+                let provider = std::sync::Arc::new(provider);
+                self.__transport.credentials = std::option::Option::Some(provider.clone());
+                self.__transport.async_credentials = std::option::Option::Some(provider);
+                self
+            }
+
+            #[doc = "Asks `provider` for the credentials to send when the request is sent with `send_async()`."]
+            #[doc = ""]
+            #[doc = "`send()` fails with `RestApiError::CredentialsError` unless `credentials()` is set too."]
+            pub fn async_credentials(mut self, provider: impl derive_rest_api::AsyncCredentialsProvider) -> Self {
+                self.__transport.async_credentials = std::option::Option::Some(std::sync::Arc::new(provider));
+                self
+            }
+
+            #[doc(hidden)]
+            pub fn __with_transport(mut self, transport: derive_rest_api::__private::Transport) -> Self {
+                self.__transport = transport;
+                self
+            }
+
+            #[doc = "Sends the request without the headers and options set by the client config's"]
+            #[doc = "`ConfigureRequest` implementation, such as an unauthenticated token exchange."]
+            #[doc = ""]
+            #[doc = "Headers and options set on the builder before this call are dropped too, so call it first."]
+            #[doc = "Settings from the client builder, such as its default headers, are kept."]
+            pub fn without_config(mut self) -> Self {
+                if let std::option::Option::Some((headers, options)) = self.__unconfigured.take() {
+                    self.__dynamic_headers = headers;
+                    self.__options = options;
+                }
+                self
+            }
+
+            #[doc(hidden)]
+            pub fn __before_configure(mut self) -> Self {
+                self.__unconfigured = std::option::Option::Some((self.__dynamic_headers.clone(), self.__options));
+                self
+            }
         }
 
+        #clone_impl
+
+        #send_sync_assertions
+
+        #arbitrary_impls
+
+        #sample_method
+
+        #cli_impls
+
         // Implement RequestModifier trait for the builder
-        impl<__C, __A> derive_rest_api::RequestModifier for #builder_name<__C, __A> {
-            fn header(mut self, name: impl std::convert::Into<std::string::String>, value: impl std::convert::Into<std::string::String>) -> Self {
-                self.__dynamic_headers.insert(name.into(), value.into());
+        impl<#params __C, __A> derive_rest_api::RequestModifier for #builder_name<#args __C, __A> #where_clause {
+            fn header<K, V>(mut self, name: K, value: V) -> Self
+            where
+                derive_rest_api::http::HeaderName: std::convert::TryFrom<K>,
+                <derive_rest_api::http::HeaderName as std::convert::TryFrom<K>>::Error: std::convert::Into<derive_rest_api::http::Error>,
+                derive_rest_api::http::HeaderValue: std::convert::TryFrom<V>,
+                <derive_rest_api::http::HeaderValue as std::convert::TryFrom<V>>::Error: std::convert::Into<derive_rest_api::http::Error>,
+            {
+                self.__dynamic_headers.insert(name, value);
+                self
+            }
+
+            fn append_header<K, V>(mut self, name: K, value: V) -> Self
+            where
+                derive_rest_api::http::HeaderName: std::convert::TryFrom<K>,
+                <derive_rest_api::http::HeaderName as std::convert::TryFrom<K>>::Error: std::convert::Into<derive_rest_api::http::Error>,
+                derive_rest_api::http::HeaderValue: std::convert::TryFrom<V>,
+                <derive_rest_api::http::HeaderValue as std::convert::TryFrom<V>>::Error: std::convert::Into<derive_rest_api::http::Error>,
+            {
+                self.__dynamic_headers.append(name, value);
                 self
             }
 
             fn timeout(mut self, timeout: std::time::Duration) -> Self {
-                self.__timeout = std::option::Option::Some(timeout);
+                self.__options.timeouts.total = std::option::Option::Some(timeout);
+                self
+            }
+
+            fn connect_timeout(mut self, timeout: std::time::Duration) -> Self {
+                self.__options.timeouts.connect = std::option::Option::Some(timeout);
+                self
+            }
+
+            fn read_timeout(mut self, timeout: std::time::Duration) -> Self {
+                self.__options.timeouts.read = std::option::Option::Some(timeout);
+                self
+            }
+
+            fn max_response_bytes(mut self, limit: u64) -> Self {
+                self.__options.max_response_bytes = std::option::Option::Some(limit);
+                self
+            }
+
+            fn require_https(mut self) -> Self {
+                self.__options.require_https = true;
+                self
+            }
+
+            fn min_tls_version(mut self, version: derive_rest_api::TlsVersion) -> Self {
+                self.__options.min_tls_version = std::option::Option::Some(version);
                 self
             }
         }
 
-        impl<__C, __A> #builder_name<__C, __A> {
+        impl<#params __C, __A> #builder_name<#args __C, __A> #where_clause {
 
             #(#setter_methods)*
 
+            #with_request_method
+
+            #state_methods
+
             #[doc = concat!("Builds a [`", stringify!(#struct_name), "`] from the builder.")]
             #[doc = ""]
             #[doc = "# Errors"]
             #[doc = ""]
             #[doc = "Returns an error if any required fields are not set or if validation fails."]
-            pub fn build(self) -> std::result::Result<#struct_name, derive_rest_api::RestApiError> {
+            #[doc = "Use `try_build()` to get every error instead of the first one."]
+            pub fn build(self) -> std::result::Result<#struct_name<#args>, derive_rest_api::RestApiError> {
+                self.try_build().map_err(derive_rest_api::BuildErrors::into_first)
+            }
+
+            #[doc = concat!("Builds a [`", stringify!(#struct_name), "`] from the builder, reporting every problem at once.")]
+            #[doc = ""]
+            #[doc = "# Errors"]
+            #[doc = ""]
+            #[doc = "Returns every missing required field and validation failure, in field order."]
+            pub fn try_build(self) -> std::result::Result<#struct_name<#args>, derive_rest_api::BuildErrors> {
                 // Extract and validate fields
-                #(#field_processing)*
+                #error_collection
+
+                // Evaluate computed fields
+                #(#computed_fields)*
 
                 // Construct the struct
                 #struct_construction
@@ -196,6 +502,8 @@ pub(crate) fn generate_request_builder(input: syn::DeriveInput) -> syn::Result<p
 
         // Generate HTTP methods impl for the original struct
         #http_methods_impl
+
+        #unmarked_field_warnings
     };
 
     Ok(expanded)
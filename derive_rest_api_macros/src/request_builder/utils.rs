@@ -3,6 +3,102 @@
 //! This module contains utilities that are specific to the RequestBuilder
 //! derive macro and not generally useful for other macros.
 
+use proc_macro2::TokenStream;
+use quote::quote;
+
+/// Returns `ty` with every named lifetime other than `'static` replaced by
+/// `lifetime`, for bounds like `for<'__send> T: Send` that must hold for any
+/// lifetime the struct is used with.
+pub(super) fn with_lifetime(ty: &syn::Type, lifetime: &syn::Lifetime) -> syn::Type {
+    struct Replace<'a>(&'a syn::Lifetime);
+
+    impl syn::visit_mut::VisitMut for Replace<'_> {
+        fn visit_lifetime_mut(&mut self, lifetime: &mut syn::Lifetime) {
+            if lifetime.ident != "static" {
+                *lifetime = self.0.clone();
+            }
+        }
+    }
+
+    let mut ty = ty.clone();
+    syn::visit_mut::VisitMut::visit_type_mut(&mut Replace(lifetime), &mut ty);
+    ty
+}
+
+/// Generic parameter fragments of the request struct, used when emitting the
+/// builder and impl blocks.
+///
+/// `params` and `args` are emitted with a trailing comma (when non-empty) so
+/// they can be followed directly by the builder's own client type parameters.
+pub(super) struct GenericsTokens {
+    /// Parameters with bounds but without defaults (e.g. `'a, T: Serialize,`)
+    pub params: TokenStream,
+    /// Parameters with bounds and defaults, for struct declarations
+    pub decl_params: TokenStream,
+    /// Arguments matching the parameters (e.g. `'a, T,`)
+    pub args: TokenStream,
+    /// The struct's where clause, if any
+    pub where_clause: Option<syn::WhereClause>,
+}
+
+impl GenericsTokens {
+    pub(super) fn new(generics: &syn::Generics) -> Self {
+        let decl_params: Vec<_> = generics.params.iter().collect();
+        let params: Vec<_> = generics.params.iter().map(|param| {
+            let mut param = param.clone();
+            match &mut param {
+                syn::GenericParam::Type(ty) => {
+                    ty.eq_token = None;
+                    ty.default = None;
+                }
+                syn::GenericParam::Const(c) => {
+                    c.eq_token = None;
+                    c.default = None;
+                }
+                syn::GenericParam::Lifetime(_) => {}
+            }
+            param
+        }).collect();
+        let args: Vec<_> = generics.params.iter().map(|param| match param {
+            syn::GenericParam::Type(ty) => {
+                let ident = &ty.ident;
+                quote! { #ident }
+            }
+            syn::GenericParam::Lifetime(lt) => {
+                let lifetime = &lt.lifetime;
+                quote! { #lifetime }
+            }
+            syn::GenericParam::Const(c) => {
+                let ident = &c.ident;
+                quote! { #ident }
+            }
+        }).collect();
+
+        Self {
+            params: quote! { #(#params,)* },
+            decl_params: quote! { #(#decl_params,)* },
+            args: quote! { #(#args,)* },
+            where_clause: generics.where_clause.clone(),
+        }
+    }
+}
+
+/// A `{placeholder}` in a URL path template
+#[derive(Debug, PartialEq, Eq)]
+pub(super) struct PathParam {
+    /// Name of the field substituted for the placeholder
+    pub name: String,
+    /// A `{name*}` placeholder, capturing several path segments
+    pub wildcard: bool,
+}
+
+impl PathParam {
+    /// The placeholder as written in the template, e.g. `{id}` or `{key*}`
+    pub fn placeholder(&self) -> String {
+        format!("{{{}{}}}", self.name, if self.wildcard { "*" } else { "" })
+    }
+}
+
 /// Extract path parameters from a URL path template.
 ///
 /// # Examples
@@ -10,7 +106,8 @@
 /// - "/api/users/{id}/posts/{post_id}" -> ["id", "post_id"]
 /// - "/api/users" -> []
 /// - "/api/{version}/users/{id}" -> ["version", "id"]
-pub(super) fn extract_path_params(path: &str) -> Vec<String> {
+/// - "/files/{path*}" -> ["path" (wildcard)]
+pub(super) fn extract_path_params(path: &str) -> Vec<PathParam> {
     let mut params = Vec::new();
     let mut chars = path.chars().peekable();
 
@@ -24,8 +121,12 @@ pub(super) fn extract_path_params(path: &str) -> Vec<String> {
                 }
                 param.push(chars.next().unwrap());
             }
+            let wildcard = param.ends_with('*');
+            if wildcard {
+                param.pop();
+            }
             if !param.is_empty() {
-                params.push(param);
+                params.push(PathParam { name: param, wildcard });
             }
         }
     }
@@ -33,20 +134,45 @@ pub(super) fn extract_path_params(path: &str) -> Vec<String> {
     params
 }
 
+/// Returns true if `name` is a valid HTTP header name (an RFC 9110 token)
+pub(super) fn is_valid_header_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn names(params: Vec<PathParam>) -> Vec<String> {
+        params.into_iter().map(|param| param.name).collect()
+    }
+
     #[test]
     fn test_extract_path_params() {
         assert_eq!(
-            extract_path_params("/api/users/{id}/posts/{post_id}"),
+            names(extract_path_params("/api/users/{id}/posts/{post_id}")),
             vec!["id", "post_id"]
         );
-        assert_eq!(extract_path_params("/api/users"), Vec::<String>::new());
+        assert_eq!(names(extract_path_params("/api/users")), Vec::<String>::new());
         assert_eq!(
-            extract_path_params("/api/{version}/users/{id}"),
+            names(extract_path_params("/api/{version}/users/{id}")),
             vec!["version", "id"]
         );
     }
+
+    #[test]
+    fn test_extract_wildcard_path_params() {
+        let params = extract_path_params("/buckets/{bucket}/objects/{key*}");
+        assert_eq!(
+            params,
+            vec![
+                PathParam { name: "bucket".to_string(), wildcard: false },
+                PathParam { name: "key".to_string(), wildcard: true },
+            ]
+        );
+        assert_eq!(params[1].placeholder(), "{key*}");
+    }
 }
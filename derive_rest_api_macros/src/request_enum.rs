@@ -0,0 +1,305 @@
+//! RequestEnum derive macro implementation.
+//!
+//! This module generates dispatching HTTP methods for enums where each variant
+//! maps to an endpoint:
+//! - Variants with named fields (or unit variants) carry their own
+//!   `#[request_builder(method = "...", path = "...")]` attribute. A hidden
+//!   request struct borrowing the variant's fields is generated for each one
+//!   and derives `RequestBuilder`, so field attributes work exactly as they do
+//!   on request structs.
+//! - Single-field tuple variants wrap an existing request struct and delegate
+//!   to its generated methods.
+
+use crate::utils::{extract_serde_attributes, option_inner_type};
+use proc_macro2::TokenStream;
+use quote::quote;
+
+/// How a variant is turned into a request
+enum VariantRequest {
+    /// A generated hidden request struct, built from the variant's fields
+    Generated { construction: TokenStream },
+    /// An existing request struct wrapped by a newtype variant
+    Wrapped,
+}
+
+/// Main entry point for the RequestEnum derive macro
+pub(crate) fn generate_request_enum(input: syn::DeriveInput) -> syn::Result<TokenStream> {
+    let enum_name = &input.ident;
+    let vis = &input.vis;
+
+    let data_enum = match &input.data {
+        syn::Data::Enum(data_enum) => data_enum,
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input,
+                "RequestEnum can only be derived for enums",
+            ))
+        }
+    };
+
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &input.generics,
+            "RequestEnum does not support generic enums",
+        ));
+    }
+
+    let mut hidden_structs = Vec::new();
+    let mut patterns = Vec::new();
+    let mut requests = Vec::new();
+
+    for variant in &data_enum.variants {
+        let variant_name = &variant.ident;
+        let request_attrs: Vec<_> = variant
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("request_builder"))
+            .collect();
+
+        match &variant.fields {
+            syn::Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                if let Some(attr) = request_attrs.first() {
+                    return Err(syn::Error::new_spanned(
+                        attr,
+                        "newtype variants use the attributes of the wrapped request struct",
+                    ));
+                }
+                patterns.push(quote! { #enum_name::#variant_name(request) });
+                requests.push(VariantRequest::Wrapped);
+            }
+            syn::Fields::Unnamed(fields) => {
+                return Err(syn::Error::new_spanned(
+                    fields,
+                    "RequestEnum tuple variants must wrap exactly one request struct",
+                ));
+            }
+            syn::Fields::Named(_) | syn::Fields::Unit => {
+                if request_attrs.is_empty() {
+                    return Err(syn::Error::new_spanned(
+                        variant,
+                        "RequestEnum variants need a #[request_builder(method = \"...\", path = \"...\")] attribute",
+                    ));
+                }
+
+                let struct_name = quote::format_ident!("__{}{}Request", enum_name, variant_name);
+                let (hidden_struct, pattern, construction) =
+                    generate_variant_struct(enum_name, vis, variant, &struct_name, &request_attrs);
+
+                hidden_structs.push(hidden_struct);
+                patterns.push(pattern);
+                requests.push(VariantRequest::Generated { construction });
+            }
+        }
+    }
+
+    let dispatch = |call: TokenStream| -> Vec<TokenStream> {
+        patterns
+            .iter()
+            .zip(&requests)
+            .map(|(pattern, request)| match request {
+                VariantRequest::Generated { construction, .. } => quote! {
+                    #pattern => {
+                        let request = #construction;
+                        request.#call
+                    }
+                },
+                VariantRequest::Wrapped => quote! {
+                    #pattern => request.#call
+                },
+            })
+            .collect()
+    };
+
+    let method_arms = dispatch(quote! { method() });
+    let build_url_arms = dispatch(quote! { build_url() });
+    let build_headers_arms = dispatch(quote! { build_headers() });
+    let build_body_arms = dispatch(quote! { build_body() });
+    let send_arms = dispatch(quote! { send_with_client(client, base_url) });
+    let send_async_arms = dispatch(quote! { send_with_async_client(client, base_url).await });
+
+    Ok(quote! {
+        #(#hidden_structs)*
+
+        impl #enum_name {
+            #[doc = "Builds the URL path for the endpoint selected by this variant."]
+            #[doc = ""]
+            #[doc = "# Errors"]
+            #[doc = ""]
+            #[doc = "Returns an error if any required path parameters are not set or if query serialization fails."]
+            pub fn build_url(&self) -> std::result::Result<std::string::String, derive_rest_api::RestApiError> {
+                match self {
+                    #(#build_url_arms,)*
+                }
+            }
+
+            #[doc = "Builds HTTP headers for the endpoint selected by this variant."]
+            #[doc = ""]
+            #[doc = "# Errors"]
+            #[doc = ""]
+            #[doc = "Returns an error if a field's value is not a valid header value."]
+            pub fn build_headers(&self) -> std::result::Result<derive_rest_api::http::HeaderMap, derive_rest_api::RestApiError> {
+                match self {
+                    #(#build_headers_arms,)*
+                }
+            }
+
+            #[doc = "Builds the request body for the endpoint selected by this variant."]
+            #[doc = ""]
+            #[doc = "# Errors"]
+            #[doc = ""]
+            #[doc = "Returns an error if JSON serialization fails."]
+            pub fn build_body(&self) -> std::result::Result<std::option::Option<std::vec::Vec<u8>>, derive_rest_api::RestApiError> {
+                match self {
+                    #(#build_body_arms,)*
+                }
+            }
+
+            #[doc = "Sends the request for the endpoint selected by this variant using the provided client."]
+            #[doc = ""]
+            #[doc = "# Errors"]
+            #[doc = ""]
+            #[doc = "Returns an error if URL building, body serialization, or the HTTP request fails."]
+            pub fn send_with_client<C: derive_rest_api::HttpClient>(
+                &self,
+                client: &C,
+                base_url: &str,
+            ) -> std::result::Result<std::vec::Vec<u8>, derive_rest_api::RestApiError> {
+                match self {
+                    #(#send_arms,)*
+                }
+            }
+
+            #[doc = "Sends the request for the endpoint selected by this variant using the provided async client."]
+            #[doc = ""]
+            #[doc = "# Errors"]
+            #[doc = ""]
+            #[doc = "Returns an error if URL building, body serialization, or the HTTP request fails."]
+            pub async fn send_with_async_client<A: derive_rest_api::AsyncHttpClient>(
+                &self,
+                client: &A,
+                base_url: &str,
+            ) -> std::result::Result<std::vec::Vec<u8>, derive_rest_api::RestApiError> {
+                match self {
+                    #(#send_async_arms,)*
+                }
+            }
+        }
+
+        // Variants can respond with different types, so a batched enum request returns the raw body
+        impl derive_rest_api::RestRequest for #enum_name {
+            type Response = std::vec::Vec<u8>;
+
+            fn method(&self) -> &'static str {
+                use derive_rest_api::RestRequest as _;
+                match self {
+                    #(#method_arms,)*
+                }
+            }
+
+            fn build_url(&self) -> std::result::Result<std::string::String, derive_rest_api::RestApiError> {
+                Self::build_url(self)
+            }
+
+            fn build_headers(&self) -> std::result::Result<derive_rest_api::http::HeaderMap, derive_rest_api::RestApiError> {
+                Self::build_headers(self)
+            }
+
+            fn build_body(&self) -> std::result::Result<std::option::Option<std::vec::Vec<u8>>, derive_rest_api::RestApiError> {
+                Self::build_body(self)
+            }
+
+            fn parse_response(
+                response: derive_rest_api::http::Response<std::vec::Vec<u8>>,
+            ) -> std::result::Result<Self::Response, derive_rest_api::RestApiError> {
+                std::result::Result::Ok(response.into_body())
+            }
+        }
+    })
+}
+
+/// Generate the hidden request struct for a named or unit variant.
+///
+/// Returns the struct definition, the match pattern binding the variant's
+/// fields by reference, and an expression constructing the hidden struct from
+/// those bindings.
+fn generate_variant_struct(
+    enum_name: &syn::Ident,
+    vis: &syn::Visibility,
+    variant: &syn::Variant,
+    struct_name: &syn::Ident,
+    request_attrs: &[&syn::Attribute],
+) -> (TokenStream, TokenStream, TokenStream) {
+    let variant_name = &variant.ident;
+    let doc = format!("Request for [`{}::{}`].", enum_name, variant_name);
+
+    let fields = match &variant.fields {
+        syn::Fields::Named(fields) => &fields.named,
+        _ => {
+            let definition = quote! {
+                #[doc(hidden)]
+                #[doc = #doc]
+                #[derive(derive_rest_api::RequestBuilder)]
+                #(#request_attrs)*
+                #vis struct #struct_name;
+            };
+            return (
+                definition,
+                quote! { #enum_name::#variant_name },
+                quote! { #struct_name },
+            );
+        }
+    };
+
+    let field_names: Vec<_> = fields.iter().map(|field| &field.ident).collect();
+
+    // Borrow every field: `Option<T>` becomes `Option<&T>` so it keeps its
+    // optional semantics, everything else becomes `&T`.
+    let struct_fields = fields.iter().map(|field| {
+        let field_name = &field.ident;
+        let field_attrs: Vec<_> = field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("request_builder"))
+            .collect();
+        let serde_attrs = extract_serde_attributes(&field.attrs);
+
+        let field_type = match option_inner_type(&field.ty) {
+            Some(inner_type) => quote! { std::option::Option<&'__e #inner_type> },
+            None => {
+                let ty = &field.ty;
+                quote! { &'__e #ty }
+            }
+        };
+
+        quote! {
+            #(#field_attrs)*
+            #(#serde_attrs)*
+            #field_name: #field_type
+        }
+    });
+
+    let field_conversions = fields.iter().map(|field| {
+        let field_name = &field.ident;
+        if option_inner_type(&field.ty).is_some() {
+            quote! { #field_name: #field_name.as_ref() }
+        } else {
+            quote! { #field_name }
+        }
+    });
+
+    let definition = quote! {
+        #[doc(hidden)]
+        #[doc = #doc]
+        #[derive(derive_rest_api::RequestBuilder)]
+        #(#request_attrs)*
+        #vis struct #struct_name<'__e> {
+            #(#struct_fields,)*
+        }
+    };
+
+    (
+        definition,
+        quote! { #enum_name::#variant_name { #(#field_names),* } },
+        quote! { #struct_name { #(#field_conversions,)* } },
+    )
+}
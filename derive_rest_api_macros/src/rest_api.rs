@@ -0,0 +1,304 @@
+//! `#[rest_api]` attribute macro implementation.
+//!
+//! Turns a trait whose methods describe endpoints into request structs and a
+//! client implementing the trait. Each method becomes a request struct deriving
+//! `RequestBuilder`, the client is an `ApiClient` with every request in its
+//! `requests(...)`, and the trait is implemented by building and sending those
+//! requests. An async copy of the trait is implemented by the async client.
+
+use crate::utils::{option_inner_type, snake_to_pascal_case};
+use proc_macro2::{TokenStream, TokenTree};
+use quote::{format_ident, quote};
+use syn::{Ident, spanned::Spanned};
+
+/// A trait method describing an endpoint
+struct Endpoint {
+    /// The trait method, with its `#[request_builder(...)]` attributes removed
+    sig: syn::Signature,
+    /// The request struct generated for the method
+    struct_name: Ident,
+    /// The client method returning the request's builder
+    builder_method: Ident,
+    /// Struct-level `#[request_builder(...)]` attributes from the method
+    request_attrs: Vec<syn::Attribute>,
+    doc_attrs: Vec<syn::Attribute>,
+    params: Vec<Param>,
+    response: syn::Type,
+}
+
+/// A method parameter, which becomes a field of the request struct
+struct Param {
+    attrs: Vec<syn::Attribute>,
+    name: Ident,
+    ty: syn::Type,
+}
+
+/// Main entry point for the #[rest_api] attribute macro
+pub(crate) fn generate_rest_api(args: TokenStream, item: TokenStream) -> syn::Result<TokenStream> {
+    let mut item_trait: syn::ItemTrait = syn::parse2(item)?;
+
+    if let Some(requests) = args.clone().into_iter().find(|token| {
+        matches!(token, TokenTree::Ident(ident) if ident == "requests")
+    }) {
+        return Err(syn::Error::new_spanned(
+            requests,
+            "#[rest_api] fills in `requests(...)` from the trait's methods",
+        ));
+    }
+    if !item_trait.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &item_trait.generics,
+            "#[rest_api] does not support generic traits",
+        ));
+    }
+
+    let mut endpoints = Vec::new();
+    let mut errors: Option<syn::Error> = None;
+    for trait_item in &mut item_trait.items {
+        match parse_endpoint(trait_item) {
+            Ok(Some(endpoint)) => endpoints.push(endpoint),
+            Ok(None) => {}
+            Err(err) => match &mut errors {
+                Some(errors) => errors.combine(err),
+                None => errors = Some(err),
+            },
+        }
+    }
+    if let Some(errors) = errors {
+        return Err(errors);
+    }
+
+    let vis = &item_trait.vis;
+    let trait_name = &item_trait.ident;
+    let async_trait_name = format_ident!("{}Async", trait_name);
+    let config_name = format_ident!("{}Config", trait_name);
+    let client_name = format_ident!("{}Client", trait_name);
+    let async_client_name = format_ident!("{}AsyncClient", trait_name);
+
+    let request_structs = endpoints.iter().map(|endpoint| generate_request_struct(endpoint, vis));
+
+    let requests = endpoints.iter().map(|endpoint| {
+        let struct_name = &endpoint.struct_name;
+        let builder_method = endpoint.builder_method.to_string();
+        quote! { #struct_name = #builder_method }
+    });
+    let separator = match args.clone().into_iter().last() {
+        None => quote! {},
+        Some(TokenTree::Punct(punct)) if punct.as_char() == ',' => quote! {},
+        Some(_) => quote! { , },
+    };
+
+    let blocking_methods = endpoints.iter().map(|endpoint| generate_method(endpoint, false));
+    let async_methods = endpoints.iter().map(|endpoint| generate_method(endpoint, true));
+
+    // The async trait has the endpoints as `async fn`s; methods with default
+    // bodies call the blocking methods, so they stay on the trait only
+    let async_trait_methods = endpoints.iter().map(|endpoint| {
+        let doc_attrs = &endpoint.doc_attrs;
+        let sig = &endpoint.sig;
+        quote! {
+            #(#doc_attrs)*
+            async #sig;
+        }
+    });
+
+    let config_doc = format!("Configuration for [`{}`] and [`{}`].", client_name, async_client_name);
+    let async_trait_doc = format!("Async version of [`{}`], implemented by [`{}`].", trait_name, async_client_name);
+
+    Ok(quote! {
+        #item_trait
+
+        #[doc = #async_trait_doc]
+        #[allow(async_fn_in_trait)]
+        #vis trait #async_trait_name {
+            #(#async_trait_methods)*
+        }
+
+        #(#request_structs)*
+
+        #[doc = #config_doc]
+        #[derive(Clone, derive_rest_api::ApiClient)]
+        #[api_client(#args #separator requests(#(#requests),*))]
+        #vis struct #config_name;
+
+        #[cfg(not(target_arch = "wasm32"))]
+        impl<C: derive_rest_api::HttpClient> #trait_name for #client_name<C> {
+            #(#blocking_methods)*
+        }
+
+        impl<A: derive_rest_api::AsyncHttpClient> #async_trait_name for #async_client_name<A> {
+            #(#async_methods)*
+        }
+    })
+}
+
+/// Parse a trait method into an endpoint, removing the attributes the macro consumes.
+///
+/// Methods without `#[request_builder(...)]` are left alone if they have a
+/// default body.
+fn parse_endpoint(trait_item: &mut syn::TraitItem) -> syn::Result<Option<Endpoint>> {
+    let syn::TraitItem::Fn(method) = trait_item else {
+        return Err(syn::Error::new_spanned(
+            trait_item,
+            "#[rest_api] traits may only contain methods",
+        ));
+    };
+
+    let (request_attrs, attrs): (Vec<_>, Vec<_>) = std::mem::take(&mut method.attrs)
+        .into_iter()
+        .partition(|attr| attr.path().is_ident("request_builder"));
+    method.attrs = attrs;
+    if request_attrs.is_empty() {
+        if method.default.is_some() {
+            return Ok(None);
+        }
+        return Err(syn::Error::new_spanned(
+            &method.sig.ident,
+            "missing #[request_builder(method = \"...\", path = \"...\")] on endpoint method",
+        ));
+    }
+    if let Some(body) = &method.default {
+        return Err(syn::Error::new_spanned(body, "endpoint methods must not have a body"));
+    }
+
+    let sig = &mut method.sig;
+    if !sig.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(&sig.generics, "endpoint methods must not be generic"));
+    }
+    if sig.asyncness.is_some() {
+        return Err(syn::Error::new_spanned(
+            sig.asyncness,
+            "endpoint methods must not be async; the async client implements the generated async trait",
+        ));
+    }
+
+    let mut inputs = sig.inputs.iter_mut();
+    match inputs.next() {
+        Some(syn::FnArg::Receiver(receiver)) if receiver.reference.is_some() && receiver.mutability.is_none() => {}
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &sig.ident,
+                "endpoint methods must take `&self` as their first parameter",
+            ));
+        }
+    }
+
+    let mut params = Vec::new();
+    for input in inputs {
+        let syn::FnArg::Typed(pat_type) = input else {
+            return Err(syn::Error::new_spanned(input, "unexpected receiver"));
+        };
+        let syn::Pat::Ident(pat_ident) = &*pat_type.pat else {
+            return Err(syn::Error::new_spanned(&pat_type.pat, "endpoint parameters must be plain names"));
+        };
+        if let syn::Type::Reference(_) = &*pat_type.ty {
+            return Err(syn::Error::new_spanned(
+                &pat_type.ty,
+                "endpoint parameters must be taken by value, as they become fields of the request struct",
+            ));
+        }
+        params.push(Param {
+            attrs: std::mem::take(&mut pat_type.attrs),
+            name: pat_ident.ident.clone(),
+            ty: (*pat_type.ty).clone(),
+        });
+    }
+
+    let response = response_type(&sig.output).ok_or_else(|| {
+        syn::Error::new(
+            sig.output.span(),
+            "endpoint methods must return `Result<T, RestApiError>`",
+        )
+    })?;
+
+    let struct_name = format_ident!("{}Request", snake_to_pascal_case(&sig.ident.to_string()));
+    let builder_method = format_ident!("{}_request", sig.ident);
+    let doc_attrs = method.attrs.iter().filter(|attr| attr.path().is_ident("doc")).cloned().collect();
+
+    Ok(Some(Endpoint {
+        sig: sig.clone(),
+        struct_name,
+        builder_method,
+        request_attrs,
+        doc_attrs,
+        params,
+        response,
+    }))
+}
+
+/// Extract `T` from a `Result<T, E>` return type
+fn response_type(output: &syn::ReturnType) -> Option<syn::Type> {
+    let syn::ReturnType::Type(_, ty) = output else {
+        return None;
+    };
+    let syn::Type::Path(type_path) = &**ty else {
+        return None;
+    };
+    let segment = type_path.path.segments.last()?;
+    if segment.ident != "Result" {
+        return None;
+    }
+    let syn::PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    match args.args.first()? {
+        syn::GenericArgument::Type(ty) => Some(ty.clone()),
+        _ => None,
+    }
+}
+
+/// Generate the request struct for an endpoint
+fn generate_request_struct(endpoint: &Endpoint, vis: &syn::Visibility) -> TokenStream {
+    let Endpoint { struct_name, request_attrs, doc_attrs, params, response, .. } = endpoint;
+
+    let fields = params.iter().map(|Param { attrs, name, ty }| {
+        quote! {
+            #(#attrs)*
+            #vis #name: #ty
+        }
+    });
+
+    quote! {
+        #(#doc_attrs)*
+        #[derive(derive_rest_api::RequestBuilder)]
+        #(#request_attrs)*
+        #[request_builder(response = #response)]
+        #vis struct #struct_name {
+            #(#fields,)*
+        }
+    }
+}
+
+/// Generate the trait method implementation sending an endpoint's request
+fn generate_method(endpoint: &Endpoint, is_async: bool) -> TokenStream {
+    let Endpoint { sig, builder_method, params, .. } = endpoint;
+
+    // Option parameters are only set when present, as their setters take the inner type
+    let setters = params.iter().map(|Param { name, ty, .. }| {
+        if option_inner_type(ty).is_some() {
+            quote! {
+                let builder = match #name {
+                    std::option::Option::Some(value) => builder.#name(value),
+                    std::option::Option::None => builder,
+                };
+            }
+        } else {
+            quote! { let builder = builder.#name(#name); }
+        }
+    });
+
+    let (asyncness, send) = if is_async {
+        (quote! { async }, quote! { builder.send_async().await })
+    } else {
+        (quote! {}, quote! { builder.send() })
+    };
+    let syn::Signature { ident, inputs, output, .. } = sig;
+
+    quote! {
+        #asyncness fn #ident(#inputs) #output {
+            let builder = self.#builder_method();
+            #(#setters)*
+            #send
+        }
+    }
+}
@@ -3,7 +3,6 @@
 //! This module contains common utilities that can be used across different
 //! derive macros in this crate.
 
-use syn;
 
 /// Extract the inner type from an Option<T> type.
 ///
@@ -12,6 +11,24 @@ use syn;
 ///
 /// Based on https://duskmoon314.com/en/blog/2022/10/01/extract-type-from-option-in-rs-procmacro/
 pub(crate) fn option_inner_type(ty: &syn::Type) -> Option<&syn::Type> {
+    wrapper_inner_type(ty, &["Option", "std::option::Option", "core::option::Option"])
+}
+
+/// Extract the inner type from a Vec<T> type.
+///
+/// Returns Some(&T) if the type is Vec<T>, None otherwise.
+/// Handles various Vec type paths: Vec, std::vec::Vec, alloc::vec::Vec.
+pub(crate) fn vec_inner_type(ty: &syn::Type) -> Option<&syn::Type> {
+    wrapper_inner_type(ty, &["Vec", "std::vec::Vec", "alloc::vec::Vec"])
+}
+
+/// Returns true if the type is the unit type `()`.
+pub(crate) fn is_unit_type(ty: &syn::Type) -> bool {
+    matches!(ty, syn::Type::Tuple(tuple) if tuple.elems.is_empty())
+}
+
+/// Extract the first generic argument of a type whose path is one of `wrapper_paths`.
+fn wrapper_inner_type<'a>(ty: &'a syn::Type, wrapper_paths: &[&str]) -> Option<&'a syn::Type> {
     if let syn::Type::Path(syn::TypePath { qself: None, path }) = ty {
         let segments_str = &path
             .segments
@@ -19,11 +36,11 @@ pub(crate) fn option_inner_type(ty: &syn::Type) -> Option<&syn::Type> {
             .map(|segment| segment.ident.to_string())
             .collect::<Vec<_>>()
             .join("::");
-        let option_segment = ["Option", "std::option::Option", "core::option::Option"]
+        let wrapper_segment = wrapper_paths
             .iter()
             .find(|s| segments_str == *s)
             .and_then(|_| path.segments.last());
-        let inner_type = option_segment
+        wrapper_segment
             .and_then(|path_seg| match &path_seg.arguments {
                 syn::PathArguments::AngleBracketed(syn::AngleBracketedGenericArguments {
                     args,
@@ -34,8 +51,7 @@ pub(crate) fn option_inner_type(ty: &syn::Type) -> Option<&syn::Type> {
             .and_then(|generic_arg| match generic_arg {
                 syn::GenericArgument::Type(ty) => Some(ty),
                 _ => None,
-            });
-        inner_type
+            })
     } else {
         None
     }
@@ -55,6 +71,25 @@ pub(crate) fn extract_doc_attributes(attrs: &[syn::Attribute]) -> Vec<syn::Attri
         .collect()
 }
 
+/// Joins the lines of an item's doc comments into one line of text, for help messages.
+///
+/// Returns `None` if there are no doc comments.
+pub(crate) fn doc_text(attrs: &[syn::Attribute]) -> Option<String> {
+    let lines: Vec<_> = attrs
+        .iter()
+        .filter_map(|attr| match &attr.meta {
+            syn::Meta::NameValue(syn::MetaNameValue {
+                path,
+                value: syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(doc), .. }),
+                ..
+            }) if path.is_ident("doc") => Some(doc.value().trim().to_string()),
+            _ => None,
+        })
+        .filter(|line| !line.is_empty())
+        .collect();
+    if lines.is_empty() { None } else { Some(lines.join(" ")) }
+}
+
 /// Extract serde attributes to copy to generated structs.
 ///
 /// This preserves `#[serde(...)]` attributes so they can be applied to
@@ -101,9 +136,7 @@ pub(crate) fn snake_to_title_case(s: &str) -> String {
 /// - "APIClient" -> "a_p_i_client"
 pub(crate) fn pascal_to_snake_case(s: &str) -> String {
     let mut result = String::new();
-    let mut chars = s.chars().peekable();
-
-    while let Some(ch) = chars.next() {
+    for ch in s.chars() {
         if ch.is_uppercase() {
             if !result.is_empty() {
                 result.push('_');
@@ -117,6 +150,43 @@ pub(crate) fn pascal_to_snake_case(s: &str) -> String {
     result
 }
 
+/// Convert snake_case to PascalCase.
+///
+/// # Examples
+///
+/// - "get_user" -> "GetUser"
+/// - "list_repos_v2" -> "ListReposV2"
+pub(crate) fn snake_to_pascal_case(s: &str) -> String {
+    s.split('_')
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect::<String>(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+/// Percent-encode a query string key or value, leaving only unreserved characters as-is.
+///
+/// # Examples
+///
+/// - "comments" -> "comments"
+/// - "a b&c" -> "a%20b%26c"
+pub(crate) fn percent_encode_query(s: &str) -> String {
+    let mut result = String::new();
+    for byte in s.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+            result.push(byte as char);
+        } else {
+            result.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    result
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -129,10 +199,39 @@ mod tests {
         assert_eq!(snake_to_title_case("api_key"), "Api-Key");
     }
 
+    #[test]
+    fn test_snake_to_pascal_case() {
+        assert_eq!(snake_to_pascal_case("get_user"), "GetUser");
+        assert_eq!(snake_to_pascal_case("list_repos_v2"), "ListReposV2");
+        assert_eq!(snake_to_pascal_case("_private"), "Private");
+    }
+
     #[test]
     fn test_pascal_to_snake_case() {
         assert_eq!(pascal_to_snake_case("GetUser"), "get_user");
         assert_eq!(pascal_to_snake_case("CreateUserRequest"), "create_user_request");
         assert_eq!(pascal_to_snake_case("APIClient"), "a_p_i_client");
     }
+
+    #[test]
+    fn test_doc_text() {
+        let item: syn::ItemStruct = syn::parse_quote! {
+            /// Gets a user.
+            ///
+            ///   By ID.
+            struct GetUser;
+        };
+        assert_eq!(doc_text(&item.attrs).as_deref(), Some("Gets a user. By ID."));
+
+        let item: syn::ItemStruct = syn::parse_quote! { struct GetUser; };
+        assert_eq!(doc_text(&item.attrs), None);
+    }
+
+    #[test]
+    fn test_percent_encode_query() {
+        assert_eq!(percent_encode_query("comments"), "comments");
+        assert_eq!(percent_encode_query("a b&c=d"), "a%20b%26c%3Dd");
+        assert_eq!(percent_encode_query("fields[user]"), "fields%5Buser%5D");
+        assert_eq!(percent_encode_query("é"), "%C3%A9");
+    }
 }
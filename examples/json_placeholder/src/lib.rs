@@ -14,7 +14,7 @@
 //!
 //! ## Example Usage
 //!
-//! ```rust
+//! ```rust,no_run
 //! use json_placeholder::JsonPlaceholderClient;
 //!
 //! fn main() -> Result<(), Box<dyn std::error::Error>> {
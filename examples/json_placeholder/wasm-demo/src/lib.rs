@@ -2,7 +2,10 @@
 //!
 //! This demo showcases async usage in a WebAssembly environment.
 
-use json_placeholder::JsonPlaceholderAsyncClient;
+use derive_rest_api::RequestOptions;
+use derive_rest_api::http::HeaderMap;
+use futures_util::StreamExt;
+use json_placeholder::{JsonPlaceholderAsyncClient, ListCommentsBuilder};
 use wasm_bindgen::prelude::*;
 
 // Set up panic hook for better error messages in the browser console
@@ -155,6 +158,36 @@ pub async fn run_demo() -> Result<String, JsValue> {
     }
     log("");
 
+    // ========================================================================
+    // Comments - Streamed (rendered as the body downloads)
+    // ========================================================================
+    log("📡 Streaming all comments...");
+    let request = ListCommentsBuilder::new()
+        .build()
+        .map_err(|e| JsValue::from_str(&format!("Error building request: {}", e)))?;
+    let path = request
+        .build_url()
+        .map_err(|e| JsValue::from_str(&format!("Error building URL: {}", e)))?;
+    let response = client
+        .async_http_client()
+        .send_streaming(
+            "GET",
+            &format!("https://jsonplaceholder.typicode.com{}", path),
+            HeaderMap::new(),
+            None,
+            RequestOptions::default(),
+        )
+        .await
+        .map_err(|e| JsValue::from_str(&format!("Error streaming comments: {}", e)))?;
+    let mut body = response.into_body();
+    let mut received = 0;
+    while let Some(chunk) = body.next().await {
+        let chunk = chunk.map_err(|e| JsValue::from_str(&format!("Error reading comments: {}", e)))?;
+        received += chunk.len();
+        log(&format!("   Received {} bytes so far", received));
+    }
+    log("");
+
     log("✅ WASM demo completed successfully!");
 
     Ok("Demo completed successfully!".to_string())